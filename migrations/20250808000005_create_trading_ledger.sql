@@ -0,0 +1,70 @@
+-- SQLx migration: create trading ledger tables (orders, order events, executions, positions)
+CREATE TABLE IF NOT EXISTS trade_orders (
+    id TEXT PRIMARY KEY,
+    token TEXT NOT NULL,
+    side TEXT NOT NULL,
+    size REAL NOT NULL,
+    leverage INTEGER NOT NULL,
+    order_type TEXT NOT NULL,
+    price REAL,
+    stop_price REAL,
+    status TEXT NOT NULL,
+    timeout INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    metadata TEXT -- JSON object
+);
+
+CREATE INDEX IF NOT EXISTS idx_trade_orders_token ON trade_orders (token);
+CREATE INDEX IF NOT EXISTS idx_trade_orders_status ON trade_orders (status);
+CREATE INDEX IF NOT EXISTS idx_trade_orders_created ON trade_orders (created_at);
+
+CREATE TABLE IF NOT EXISTS order_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    order_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    message TEXT,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (order_id) REFERENCES trade_orders(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_events_order ON order_events (order_id);
+
+CREATE TABLE IF NOT EXISTS trade_executions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    order_id TEXT NOT NULL,
+    transaction_id TEXT,
+    success INTEGER NOT NULL,
+    executed_price REAL,
+    executed_size REAL,
+    fees REAL,
+    executed_at INTEGER NOT NULL,
+    error_message TEXT,
+    metadata TEXT, -- JSON object
+    FOREIGN KEY (order_id) REFERENCES trade_orders(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_trade_executions_order ON trade_executions (order_id);
+CREATE INDEX IF NOT EXISTS idx_trade_executions_time ON trade_executions (executed_at);
+
+CREATE TABLE IF NOT EXISTS positions (
+    id TEXT PRIMARY KEY,
+    token TEXT NOT NULL,
+    side TEXT NOT NULL,
+    size REAL NOT NULL,
+    leverage INTEGER NOT NULL,
+    entry_price REAL NOT NULL,
+    current_price REAL NOT NULL,
+    pnl REAL NOT NULL,
+    liquidation_price REAL NOT NULL,
+    status TEXT NOT NULL,
+    exit_price REAL,
+    realized_pnl REAL,
+    opened_at INTEGER NOT NULL,
+    closed_at INTEGER,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_positions_status ON positions (status);
+CREATE INDEX IF NOT EXISTS idx_positions_token ON positions (token);
+CREATE INDEX IF NOT EXISTS idx_positions_opened ON positions (opened_at);
@@ -0,0 +1,6 @@
+-- SQLx migration: create risk_state table (persisted state of risk components)
+CREATE TABLE IF NOT EXISTS risk_state (
+    key TEXT PRIMARY KEY,
+    state TEXT NOT NULL, -- JSON
+    updated_at INTEGER NOT NULL
+);
@@ -0,0 +1,11 @@
+-- SQLx migration: create emergency_audit table (emergency stop audit trail)
+CREATE TABLE IF NOT EXISTS emergency_audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event TEXT NOT NULL,       -- activated / flattened / reset
+    trigger_source TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    details TEXT,              -- JSON report
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_emergency_audit_created ON emergency_audit (created_at);
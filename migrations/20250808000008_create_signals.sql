@@ -0,0 +1,18 @@
+-- SQLx migration: create signals table (accepted and scored trading signals)
+CREATE TABLE IF NOT EXISTS signals (
+    id TEXT PRIMARY KEY,
+    hash TEXT NOT NULL,
+    token TEXT NOT NULL,
+    source TEXT NOT NULL,
+    confidence TEXT NOT NULL,
+    price REAL NOT NULL,
+    volume REAL NOT NULL,
+    score REAL NOT NULL,
+    timestamp INTEGER NOT NULL,
+    received_at INTEGER NOT NULL,
+    metadata TEXT -- JSON object
+);
+
+CREATE INDEX IF NOT EXISTS idx_signals_hash ON signals (hash);
+CREATE INDEX IF NOT EXISTS idx_signals_token ON signals (token);
+CREATE INDEX IF NOT EXISTS idx_signals_received ON signals (received_at);
@@ -0,0 +1,19 @@
+-- SQLx migration: per-position fee/funding accounting and settled funding events
+ALTER TABLE positions ADD COLUMN fees_paid REAL NOT NULL DEFAULT 0;
+ALTER TABLE positions ADD COLUMN funding_paid REAL NOT NULL DEFAULT 0;
+ALTER TABLE positions ADD COLUMN realized_costs REAL NOT NULL DEFAULT 0;
+ALTER TABLE positions ADD COLUMN last_funding_at INTEGER;
+
+CREATE TABLE IF NOT EXISTS funding_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    position_id TEXT NOT NULL,
+    token TEXT NOT NULL,
+    rate REAL NOT NULL,
+    notional REAL NOT NULL,
+    amount REAL NOT NULL, -- positive = paid, negative = received
+    settled_at INTEGER NOT NULL,
+    UNIQUE (position_id, settled_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_funding_events_position ON funding_events (position_id);
+CREATE INDEX IF NOT EXISTS idx_funding_events_time ON funding_events (settled_at);
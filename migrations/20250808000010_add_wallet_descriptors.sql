@@ -0,0 +1,2 @@
+-- SQLx migration: BIP-380 output descriptors for watch-only Bitcoin wallets
+ALTER TABLE wallets ADD COLUMN descriptors TEXT; -- JSON array
@@ -50,6 +50,13 @@ impl AlertManager {
         }
     }
 
+    /// Tworzy manager alertów z domyślnym kanałem logującym
+    pub fn from_config(config: &AlertsConfig) -> Self {
+        let mut manager = Self::new(config.clone());
+        manager.add_sender(Box::new(AlertSender::new("log".to_string())));
+        manager
+    }
+
     /// Dodaje sender alertów
     pub fn add_sender(&mut self, sender: Box<dyn AlertSenderTrait>) {
         self.senders.push(sender);
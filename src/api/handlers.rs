@@ -1,16 +1,26 @@
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Json,
 };
 use serde_json::Value;
 use tracing::{error, info, warn};
 
+use super::models::{
+    CreateSignalRequest, CreateSignalResponse, EmergencyStopResponse, ResetSystemRequest,
+    RiskAssessmentRequest,
+};
 use super::{ApiResponse, ApiState, PaginationParams, TimeRangeParams};
 use crate::{
+    database::LedgerQuery,
     errors::CerberusError,
-    monitoring::{ComponentHealth, HealthReport, HealthStatus},
-    signals::Signal,
+    monitoring::{AlertLevel, ComponentHealth, HealthReport, HealthStatus, SystemAlert},
+    risk::{EmergencyTrigger, Portfolio, RiskManagerTrait},
+    signals::{
+        sources::{WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER},
+        Confidence, Signal, SignalDecision, SignalRejection, WebhookError, EMERGENCY_STOP_ACTION,
+    },
 };
 
 /// Health check endpoint
@@ -39,7 +49,7 @@ pub async fn health_handler(
     health_report.add_component("database".to_string(), db_health);
 
     // Sprawdzenie metryk systemu
-    let metrics = state.metrics.clone();
+    let metrics = state.metrics.read().await.clone();
     let system_health = ComponentHealth {
         status: if metrics.memory_usage < 512.0 && metrics.cpu_usage < 80.0 {
             HealthStatus::Healthy
@@ -67,7 +77,7 @@ pub async fn detailed_health_handler(
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
     info!("Detailed health check requested");
 
-    let metrics = state.metrics.clone();
+    let metrics = state.metrics.read().await.clone();
 
     let detailed_health = serde_json::json!({
         "system": {
@@ -110,8 +120,8 @@ pub async fn metrics_handler(
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
     info!("Metrics requested");
 
-    let metrics = state.metrics.clone();
-    let metrics_json = serde_json::to_value(&*metrics).map_err(|e| {
+    let metrics = state.metrics.read().await.clone();
+    let metrics_json = serde_json::to_value(&metrics).map_err(|e| {
         error!("Failed to serialize metrics: {}", e);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -130,16 +140,17 @@ pub async fn prometheus_metrics_handler(
 ) -> Result<String, (StatusCode, Json<ApiResponse<()>>)> {
     info!("Prometheus metrics requested");
 
-    // Extract values to local variables to avoid potential Arc access issues
-    let total_signals = state.metrics.total_signals;
-    let successful_trades = state.metrics.successful_trades;
-    let failed_trades = state.metrics.failed_trades;
-    let success_rate = state.metrics.success_rate;
-    let current_balance = state.metrics.current_balance;
-    let daily_pnl = state.metrics.daily_pnl;
-    let memory_usage = state.metrics.memory_usage;
-    let cpu_usage = state.metrics.cpu_usage;
-    let active_connections = state.metrics.active_connections;
+    // Extract values to local variables to avoid holding the lock while formatting
+    let metrics = state.metrics.read().await.clone();
+    let total_signals = metrics.total_signals;
+    let successful_trades = metrics.successful_trades;
+    let failed_trades = metrics.failed_trades;
+    let success_rate = metrics.success_rate;
+    let current_balance = metrics.current_balance;
+    let daily_pnl = metrics.daily_pnl;
+    let memory_usage = metrics.memory_usage;
+    let cpu_usage = metrics.cpu_usage;
+    let active_connections = metrics.active_connections;
 
     info!("Building prometheus format with {} signals", total_signals);
 
@@ -196,46 +207,200 @@ pub async fn prometheus_metrics_handler(
 
 /// Get signals endpoint
 pub async fn get_signals_handler(
-    State(_state): State<ApiState>,
+    State(state): State<ApiState>,
     Query(params): Query<PaginationParams>,
     Query(time_params): Query<TimeRangeParams>,
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
     info!("Get signals requested with pagination: {:?}", params);
 
-    // TODO: Implement actual signal retrieval from database
-    let mock_signals = serde_json::json!({
-        "signals": [],
-        "pagination": {
-            "page": params.page.unwrap_or(1),
-            "limit": params.limit.unwrap_or(50),
-            "total": 0
-        },
-        "time_range": {
-            "from": time_params.from,
-            "to": time_params.to
+    let query = ledger_query(&params, &time_params);
+    match state.signal_processor.store().list(&query).await {
+        Ok(page) => Ok(Json(ApiResponse::success(serde_json::json!({
+            "signals": page.items,
+            "pagination": {
+                "page": page.page,
+                "limit": page.limit,
+                "total": page.total
+            },
+            "time_range": {
+                "from": time_params.from,
+                "to": time_params.to
+            }
+        })))),
+        Err(e) => {
+            error!("Failed to list signals: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(e.to_string())),
+            ))
         }
-    });
-
-    Ok(Json(ApiResponse::success(mock_signals)))
+    }
 }
 
 /// Create signal endpoint
 pub async fn create_signal_handler(
-    State(_state): State<ApiState>,
+    State(state): State<ApiState>,
     Json(payload): Json<Value>,
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
     info!("Create signal requested: {:?}", payload);
 
-    // TODO: Implement signal creation logic
-    let now = chrono::Utc::now().timestamp();
-    let response = serde_json::json!({
-        "signal_id": "mock_signal_id",
-        "status": "created",
-        "timestamp": now
-    });
+    // Sygnał awaryjny uruchamia emergency stop
+    if payload.pointer("/metadata/action").and_then(Value::as_str) == Some(EMERGENCY_STOP_ACTION) {
+        let source = payload
+            .get("source")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown");
+        let report = state
+            .emergency_stop
+            .activate(
+                EmergencyTrigger::Signal,
+                &format!("emergency signal from {}", source),
+            )
+            .await
+            .map_err(|e| {
+                error!("Emergency stop failed: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error(e.to_string())),
+                )
+            })?;
+
+        return Ok(Json(ApiResponse::success(serde_json::json!({
+            "status": "emergency_stop_activated",
+            "report": report
+        }))));
+    }
+
+    if let Err(e) = state.emergency_stop.ensure_accepting_signals().await {
+        warn!("Signal refused: {}", e);
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::<()>::error(e.to_string())),
+        ));
+    }
+
+    let request: CreateSignalRequest = serde_json::from_value(payload).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(format!("Invalid signal: {}", e))),
+        )
+    })?;
+    let confidence: Confidence = request.confidence.parse().map_err(|e: anyhow::Error| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(e.to_string())),
+        )
+    })?;
+    let signal = Signal::new(
+        request.token,
+        request.source,
+        confidence,
+        request.price,
+        request.volume,
+        request.metadata.unwrap_or(Value::Null),
+    );
+
+    let decision = state.signal_processor.process(signal).await.map_err(|e| {
+        error!("Failed to process signal: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(e.to_string())),
+        )
+    })?;
+
+    match decision {
+        SignalDecision::Accepted(scored) => {
+            let response = CreateSignalResponse {
+                signal_id: scored.signal.id,
+                status: "accepted".to_string(),
+                timestamp: scored.received_at,
+            };
+
+            info!("Returning signal response: {:?}", response);
+            Ok(Json(ApiResponse::success(serde_json::json!({
+                "signal_id": response.signal_id,
+                "status": response.status,
+                "timestamp": response.timestamp,
+                "score": scored.score
+            }))))
+        }
+        SignalDecision::Rejected(rejection) => {
+            let status = match rejection {
+                SignalRejection::Invalid(_) => StatusCode::BAD_REQUEST,
+                SignalRejection::Duplicate { .. } => StatusCode::CONFLICT,
+                SignalRejection::Stale { .. }
+                | SignalRejection::LowScore { .. }
+                | SignalRejection::Unsafe { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            };
+            warn!("Signal rejected: {}", rejection);
+            Err((
+                status,
+                Json(ApiResponse::<()>::error(rejection.to_string())),
+            ))
+        }
+    }
+}
+
+/// Webhook endpoint for external signal sources (e.g. TradingView alerts)
+pub async fn webhook_handler(
+    State(state): State<ApiState>,
+    Path(source): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
+    info!("Webhook received from {} ({} bytes)", source, body.len());
 
-    info!("Returning signal response: {:?}", response);
-    Ok(Json(ApiResponse::success(response)))
+    let webhook = state.webhooks.get(&source).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(format!(
+                "Unknown webhook source {}",
+                source
+            ))),
+        )
+    })?;
+
+    if let Err(e) = state.emergency_stop.ensure_accepting_signals().await {
+        warn!("Webhook signal refused: {}", e);
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::<()>::error(e.to_string())),
+        ));
+    }
+
+    let signature = headers
+        .get(WEBHOOK_SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok());
+    let timestamp = headers
+        .get(WEBHOOK_TIMESTAMP_HEADER)
+        .and_then(|v| v.to_str().ok());
+
+    match webhook.ingest(&body, timestamp, signature).await {
+        Ok(signal) => Ok(Json(ApiResponse::success(serde_json::json!({
+            "signal_id": signal.id,
+            "status": "queued",
+            "timestamp": signal.timestamp
+        })))),
+        Err(e) => {
+            warn!("Webhook {} rejected: {}", source, e);
+            let status = match e {
+                WebhookError::RateLimited { .. } => {
+                    state.signal_processor.record_rate_limited(&source).await;
+                    StatusCode::TOO_MANY_REQUESTS
+                }
+                WebhookError::Unauthorized(_) => {
+                    state.signal_processor.record_source_error(&source).await;
+                    StatusCode::UNAUTHORIZED
+                }
+                WebhookError::InvalidPayload(_) => {
+                    state.signal_processor.record_source_error(&source).await;
+                    StatusCode::BAD_REQUEST
+                }
+                WebhookError::QueueFull(_) => StatusCode::SERVICE_UNAVAILABLE,
+            };
+            Err((status, Json(ApiResponse::<()>::error(e.to_string()))))
+        }
+    }
 }
 
 /// Validate signals endpoint
@@ -261,75 +426,208 @@ pub async fn validate_signals_handler(
 
 /// Get single signal endpoint
 pub async fn get_signal_handler(
-    State(_state): State<ApiState>,
+    State(state): State<ApiState>,
     Path(signal_id): Path<String>,
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
     info!("Get signal {} requested", signal_id);
 
-    // TODO: Implement signal retrieval by ID
-    let signal = serde_json::json!({
-        "id": signal_id,
-        "status": "not_found"
-    });
-
-    Ok(Json(ApiResponse::success(signal)))
+    match state.signal_processor.store().get(&signal_id).await {
+        Ok(Some(scored)) => Ok(Json(ApiResponse::success(serde_json::json!(scored)))),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error(format!(
+                "Signal {} not found",
+                signal_id
+            ))),
+        )),
+        Err(e) => {
+            error!("Failed to load signal {}: {}", signal_id, e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(e.to_string())),
+            ))
+        }
+    }
 }
 
 /// Risk assessment endpoint
 pub async fn assess_risk_handler(
-    State(_state): State<ApiState>,
-    Json(_payload): Json<Value>,
+    State(state): State<ApiState>,
+    Json(payload): Json<RiskAssessmentRequest>,
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
-    info!("Risk assessment requested");
-
-    // TODO: Implement risk assessment logic
-    let risk_assessment = serde_json::json!({
-        "approved": false,
-        "risk_level": "high",
-        "max_leverage": 5,
-        "position_size": 0.0,
-        "reasoning": "Mock risk assessment - implementation pending"
-    });
+    info!(
+        "Risk assessment requested for {} signals",
+        payload.signals.len()
+    );
+
+    let portfolio = match payload.portfolio_balance {
+        Some(balance) if balance > 0.0 => {
+            Portfolio::new(balance).with_margin_model(state.risk_manager.margin_model().clone())
+        }
+        Some(balance) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(format!(
+                    "Invalid portfolio_balance: {}",
+                    balance
+                ))),
+            ))
+        }
+        None => state.risk_manager.portfolio().await,
+    };
+
+    let mut assessments = Vec::with_capacity(payload.signals.len());
+    for raw in payload.signals {
+        let request: CreateSignalRequest = serde_json::from_value(raw).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(format!("Invalid signal: {}", e))),
+            )
+        })?;
+        let confidence: Confidence = request.confidence.parse().map_err(|e: anyhow::Error| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(e.to_string())),
+            )
+        })?;
+        let signal = Signal::new(
+            request.token,
+            request.source,
+            confidence,
+            request.price,
+            request.volume,
+            request.metadata.unwrap_or(Value::Null),
+        );
+
+        let mut assessment = state.risk_manager.evaluate_risk(&signal, &portfolio).await;
+
+        // Opcjonalny próg ryzyka (0-100) podany przez klienta
+        if let Some(threshold) = payload.risk_threshold {
+            if assessment.approved && assessment.risk_score as f64 > threshold {
+                assessment.approved = false;
+                assessment.reasoning = format!(
+                    "Risk score {} exceeds threshold {}",
+                    assessment.risk_score, threshold
+                );
+            }
+        }
 
-    Ok(Json(ApiResponse::success(risk_assessment)))
+        assessments.push(serde_json::json!({
+            "token": signal.token,
+            "confidence": signal.confidence.to_string(),
+            "assessment": assessment
+        }));
+    }
+
+    Ok(Json(ApiResponse::success(serde_json::json!({
+        "assessments": assessments,
+        "portfolio": {
+            "balance": portfolio.balance,
+            "equity": portfolio.equity,
+            "margin_available": portfolio.margin_available,
+            "open_positions": portfolio.open_positions.len()
+        }
+    }))))
 }
 
 /// Risk status endpoint
 pub async fn risk_status_handler(
-    State(_state): State<ApiState>,
+    State(state): State<ApiState>,
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
     info!("Risk status requested");
 
+    let breaker = state.circuit_breaker.status().await;
+    let portfolio = state.risk_manager.portfolio().await;
+
     let risk_status = serde_json::json!({
-        "circuit_breaker_active": false,
-        "daily_loss": 0.0,
-        "daily_loss_limit": 15.0,
-        "consecutive_failures": 0,
-        "max_consecutive_failures": 5,
-        "current_positions": 0,
-        "max_positions": 3
+        "circuit_breaker_active": breaker.active,
+        "circuit_breaker_reason": breaker.reason,
+        "tripped_at": breaker.tripped_at,
+        "trading_day": breaker.trading_day,
+        "daily_loss": breaker.daily_loss,
+        "daily_loss_limit": breaker.daily_loss_limit,
+        "consecutive_failures": breaker.consecutive_failures,
+        "max_consecutive_failures": breaker.max_consecutive_failures,
+        "daily_trades": breaker.daily_trades,
+        "daily_failures": breaker.daily_failures,
+        "current_positions": portfolio.open_positions.len(),
+        "max_positions": state.config.trading.max_concurrent_positions,
+        "margin_mode": state.risk_manager.margin_model().mode,
+        "equity": portfolio.equity,
+        "unrealized_pnl": portfolio.unrealized_pnl,
+        "margin_used": portfolio.margin_used,
+        "margin_available": portfolio.margin_available,
+        "maintenance_margin": portfolio.maintenance_margin,
+        "margin_ratio": portfolio.margin_ratio,
+        "deleverage_margin_ratio": state.config.risk.margin.deleverage_margin_ratio,
+        "strategy_phase": state.risk_manager.active_phase(),
+        "phase_trades_today": state.risk_manager.trades_today().await,
+        "trade_limits": state.risk_manager.trade_limits().await
     });
 
     Ok(Json(ApiResponse::success(risk_status)))
 }
 
+/// Circuit breaker manual reset endpoint
+pub async fn reset_circuit_breaker_handler(
+    State(state): State<ApiState>,
+) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
+    warn!("Circuit breaker reset requested");
+
+    match state.circuit_breaker.reset("manual reset via API").await {
+        Ok(alert) => {
+            let was_active = alert.is_some();
+            if let Some(alert) = alert {
+                if let Err(e) = state.alert_manager.lock().await.send_alert(&alert).await {
+                    error!("Failed to send circuit breaker alert: {}", e);
+                }
+            }
+
+            Ok(Json(ApiResponse::success(serde_json::json!({
+                "reset": was_active,
+                "status": state.circuit_breaker.status().await
+            }))))
+        }
+        Err(e) => {
+            error!("Failed to reset circuit breaker: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(e.to_string())),
+            ))
+        }
+    }
+}
+
 /// Get trades endpoint
 pub async fn get_trades_handler(
-    State(_state): State<ApiState>,
+    State(state): State<ApiState>,
     Query(params): Query<PaginationParams>,
+    Query(time_params): Query<TimeRangeParams>,
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
-    info!("Get trades requested");
-
-    let trades = serde_json::json!({
-        "trades": [],
-        "pagination": {
-            "page": params.page.unwrap_or(1),
-            "limit": params.limit.unwrap_or(50),
-            "total": 0
+    info!("Get trades requested with pagination: {:?}", params);
+
+    let query = ledger_query(&params, &time_params);
+    match state.ledger.list_trades(&query).await {
+        Ok(page) => Ok(Json(ApiResponse::success(serde_json::json!({
+            "trades": page.items,
+            "pagination": {
+                "page": page.page,
+                "limit": page.limit,
+                "total": page.total
+            },
+            "time_range": {
+                "from": time_params.from,
+                "to": time_params.to
+            }
+        })))),
+        Err(e) => {
+            error!("Failed to list trades: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(e.to_string())),
+            ))
         }
-    });
-
-    Ok(Json(ApiResponse::success(trades)))
+    }
 }
 
 /// Execute trade endpoint
@@ -350,47 +648,124 @@ pub async fn execute_trade_handler(
 
 /// Get single trade endpoint
 pub async fn get_trade_handler(
-    State(_state): State<ApiState>,
+    State(state): State<ApiState>,
     Path(trade_id): Path<String>,
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
     info!("Get trade {} requested", trade_id);
 
-    let trade = serde_json::json!({
-        "id": trade_id,
-        "status": "not_found"
-    });
+    let internal_error = |e: anyhow::Error| {
+        error!("Failed to load trade {}: {}", trade_id, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(e.to_string())),
+        )
+    };
 
-    Ok(Json(ApiResponse::success(trade)))
+    let record = state
+        .ledger
+        .get_order(&trade_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("Trade not found".into())),
+            )
+        })?;
+    let history = state
+        .ledger
+        .order_history(&trade_id)
+        .await
+        .map_err(internal_error)?;
+    let executions = state
+        .ledger
+        .order_executions(&trade_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(ApiResponse::success(serde_json::json!({
+        "order": record.order,
+        "status": record.status,
+        "updated_at": record.updated_at,
+        "history": history,
+        "executions": executions
+    }))))
 }
 
 /// Get positions endpoint
 pub async fn get_positions_handler(
-    State(_state): State<ApiState>,
+    State(state): State<ApiState>,
+    Query(params): Query<PaginationParams>,
+    Query(time_params): Query<TimeRangeParams>,
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
-    info!("Get positions requested");
-
-    let positions = serde_json::json!({
-        "positions": [],
-        "total_positions": 0,
-        "total_value": 0.0
-    });
-
-    Ok(Json(ApiResponse::success(positions)))
+    info!("Get positions requested with pagination: {:?}", params);
+
+    let query = ledger_query(&params, &time_params);
+    match state.ledger.list_positions(None, &query).await {
+        Ok(page) => Ok(Json(ApiResponse::success(serde_json::json!({
+            "positions": page.items,
+            "pagination": {
+                "page": page.page,
+                "limit": page.limit,
+                "total": page.total
+            },
+            "time_range": {
+                "from": time_params.from,
+                "to": time_params.to
+            }
+        })))),
+        Err(e) => {
+            error!("Failed to list positions: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(e.to_string())),
+            ))
+        }
+    }
 }
 
 /// Get single position endpoint
 pub async fn get_position_handler(
-    State(_state): State<ApiState>,
+    State(state): State<ApiState>,
     Path(position_id): Path<String>,
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
     info!("Get position {} requested", position_id);
 
-    let position = serde_json::json!({
-        "id": position_id,
-        "status": "not_found"
-    });
+    let internal_error = |e: anyhow::Error| {
+        error!("Failed to load position {}: {}", position_id, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(e.to_string())),
+        )
+    };
 
-    Ok(Json(ApiResponse::success(position)))
+    let record = state
+        .ledger
+        .get_position(&position_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("Position not found".into())),
+            )
+        })?;
+    let funding = state
+        .ledger
+        .position_funding(&position_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(ApiResponse::success(serde_json::json!({
+        "position": record.position,
+        "exit_price": record.exit_price,
+        "realized_pnl": record.realized_pnl,
+        "updated_at": record.updated_at,
+        "price_pnl": record.position.price_pnl(),
+        "fees_paid": record.position.fees_paid,
+        "funding_paid": record.position.funding_paid,
+        "funding_events": funding
+    }))))
 }
 
 /// Close position endpoint
@@ -415,11 +790,17 @@ pub async fn system_status_handler(
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
     info!("System status requested");
 
+    let emergency = state.emergency_stop.state().await;
+    let circuit_breaker_active = state.circuit_breaker.status().await.active;
+
     let system_status = serde_json::json!({
-        "status": "running",
+        "status": if emergency.active { "emergency_stop" } else { "running" },
         "version": env!("CARGO_PKG_VERSION"),
-        "uptime_seconds": chrono::Utc::now().timestamp() - state.metrics.last_updated,
-        "environment": state.config.environment
+        "uptime_seconds": chrono::Utc::now().timestamp() - state.metrics.read().await.last_updated,
+        "environment": state.config.environment,
+        "trading_enabled": !emergency.active && !circuit_breaker_active,
+        "circuit_breaker_active": circuit_breaker_active,
+        "emergency_stop": emergency
     });
 
     Ok(Json(ApiResponse::success(system_status)))
@@ -427,30 +808,106 @@ pub async fn system_status_handler(
 
 /// Emergency stop endpoint
 pub async fn emergency_stop_handler(
-    State(_state): State<ApiState>,
+    State(state): State<ApiState>,
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
     warn!("Emergency stop requested!");
 
-    // TODO: Implement emergency stop logic
-    let result = serde_json::json!({
-        "status": "emergency_stop_activated",
-        "timestamp": chrono::Utc::now().timestamp(),
-        "message": "All trading operations halted"
-    });
+    let report = state
+        .emergency_stop
+        .activate(EmergencyTrigger::Api, "emergency stop requested via API")
+        .await
+        .map_err(|e| {
+            error!("Emergency stop failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(e.to_string())),
+            )
+        })?;
+
+    let alert = SystemAlert::new(
+        AlertLevel::Critical,
+        "Emergency stop activated".to_string(),
+        format!(
+            "{} orders cancelled, {} positions closed ({})",
+            report.cancelled_count(),
+            report.closed_count(),
+            report.reason
+        ),
+        "emergency_stop".to_string(),
+    );
+    if let Err(e) = state.alert_manager.lock().await.send_alert(&alert).await {
+        error!("Failed to send emergency stop alert: {}", e);
+    }
 
-    Ok(Json(ApiResponse::success(result)))
+    let response = EmergencyStopResponse {
+        status: if report.is_complete() {
+            "emergency_stop_activated".to_string()
+        } else {
+            "emergency_stop_incomplete".to_string()
+        },
+        timestamp: report.completed_at,
+        message: "All trading operations halted".to_string(),
+        positions_closed: report.closed_count(),
+        orders_cancelled: report.cancelled_count(),
+    };
+
+    Ok(Json(ApiResponse::success(serde_json::json!({
+        "result": response,
+        "report": report
+    }))))
 }
 
 /// Reset system endpoint
 pub async fn reset_system_handler(
-    State(_state): State<ApiState>,
+    State(state): State<ApiState>,
+    Json(payload): Json<ResetSystemRequest>,
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ApiResponse<()>>)> {
     warn!("System reset requested!");
 
+    if !payload.confirm {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                "System reset requires \"confirm\": true".to_string(),
+            )),
+        ));
+    }
+
+    let reason = payload
+        .reason
+        .unwrap_or_else(|| "manual reset via API".to_string());
+    let was_active = state
+        .emergency_stop
+        .reset(payload.confirm, &reason)
+        .await
+        .map_err(|e| {
+            error!("System reset failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(e.to_string())),
+            )
+        })?;
+
+    if was_active {
+        let alert = SystemAlert::new(
+            AlertLevel::High,
+            "Emergency stop reset".to_string(),
+            format!("Trading re-enabled: {}", reason),
+            "emergency_stop".to_string(),
+        );
+        if let Err(e) = state.alert_manager.lock().await.send_alert(&alert).await {
+            error!("Failed to send reset alert: {}", e);
+        }
+    }
+
     let result = serde_json::json!({
-        "status": "system_reset",
+        "status": if was_active { "system_reset" } else { "not_active" },
         "timestamp": chrono::Utc::now().timestamp(),
-        "message": "System reset initiated"
+        "message": if was_active {
+            "Emergency stop cleared, signals accepted again"
+        } else {
+            "Emergency stop was not active"
+        }
     });
 
     Ok(Json(ApiResponse::success(result)))
@@ -494,3 +951,13 @@ pub async fn validate_config_handler(
 
     Ok(Json(ApiResponse::success(validation_result)))
 }
+
+/// Build a ledger query from pagination and time range params
+fn ledger_query(params: &PaginationParams, time_params: &TimeRangeParams) -> LedgerQuery {
+    LedgerQuery {
+        page: params.page,
+        limit: params.limit,
+        from: time_params.from,
+        to: time_params.to,
+    }
+}
@@ -6,17 +6,26 @@ use axum::{
     Router,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::net::TcpListener;
+use tokio::sync::{Mutex, RwLock};
 use tower::ServiceBuilder;
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing::info;
 
+use crate::alerts::AlertManager;
 use crate::errors::CerberusError;
+use crate::risk::{CircuitBreaker, EmergencyStop, RiskManager};
+use crate::signals::{SignalProcessor, WebhookSource};
 
 use crate::wallets::sync::WalletSynchronizer;
 use crate::wallets::WalletManager;
-use crate::{config::Config, database::DatabaseManager, monitoring::SystemMetrics};
+use crate::{
+    config::Config,
+    database::{DatabaseManager, TradeLedger},
+    monitoring::SystemMetrics,
+};
 
 pub mod handlers;
 pub mod middleware;
@@ -31,9 +40,16 @@ pub use wallet_handlers::*;
 pub struct ApiState {
     pub config: Arc<Config>,
     pub db_manager: Arc<DatabaseManager>,
-    pub metrics: Arc<SystemMetrics>,
+    pub metrics: Arc<RwLock<SystemMetrics>>,
     pub wallet_manager: Arc<WalletManager>,
     pub wallet_sync: Arc<WalletSynchronizer>,
+    pub ledger: Arc<TradeLedger>,
+    pub risk_manager: Arc<RiskManager>,
+    pub circuit_breaker: Arc<CircuitBreaker>,
+    pub alert_manager: Arc<Mutex<AlertManager>>,
+    pub emergency_stop: Arc<EmergencyStop>,
+    pub signal_processor: Arc<SignalProcessor>,
+    pub webhooks: Arc<HashMap<String, WebhookSource>>,
 }
 
 /// Serwer HTTP API dla integracji z Kestra
@@ -43,36 +59,6 @@ pub struct ApiServer {
 }
 
 impl ApiServer {
-    /// Tworzy nowy serwer API
-    pub async fn new(
-        config: Arc<Config>,
-        db_manager: Arc<DatabaseManager>,
-        metrics: Arc<SystemMetrics>,
-    ) -> Result<Self> {
-        // Backward-compatible constructor (without wallet manager)
-        // Initialize lightweight placeholders for wallet components
-        let db_pool = db_manager.pool().clone();
-        let wm = Arc::new(
-            crate::wallets::WalletManager::new(Arc::new(db_pool))
-                .await
-                .map_err(|e| anyhow::anyhow!("WalletManager init error: {}", e))?,
-        );
-        let ws = Arc::new(crate::wallets::sync::WalletSynchronizer::new(wm.clone()));
-
-        let state = ApiState {
-            config,
-            db_manager,
-            metrics,
-            wallet_manager: wm,
-            wallet_sync: ws,
-        };
-
-        Ok(Self {
-            state,
-            listener: None,
-        })
-    }
-
     /// Tworzy nowy serwer API z podanym stanem
     pub async fn new_with_state(state: ApiState) -> Result<Self> {
         Ok(Self {
@@ -105,9 +91,11 @@ impl ApiServer {
             .route("/api/signals", post(create_signal_handler))
             .route("/api/signals/validate", post(validate_signals_handler))
             .route("/api/signals/:id", get(get_signal_handler))
+            .route("/api/webhooks/:source", post(webhook_handler))
             // Risk management endpoints
             .route("/api/risk/assess", post(assess_risk_handler))
             .route("/api/risk/status", get(risk_status_handler))
+            .route("/api/risk/reset", post(reset_circuit_breaker_handler))
             // Trading endpoints
             .route("/api/trades", get(get_trades_handler))
             .route("/api/trades", post(execute_trade_handler))
@@ -174,6 +174,13 @@ pub struct EmergencyStopResponse {
     pub orders_cancelled: u32,
 }
 
+/// Request model for system reset after an emergency stop
+#[derive(Debug, Deserialize)]
+pub struct ResetSystemRequest {
+    pub confirm: bool,
+    pub reason: Option<String>,
+}
+
 /// Model for signal validation
 #[derive(Debug, Serialize)]
 pub struct SignalValidationResult {
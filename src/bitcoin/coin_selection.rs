@@ -0,0 +1,686 @@
+//! Coin Selection Module
+//!
+//! This module selects UTXOs for a payment using Branch-and-Bound for
+//! changeless spends, a knapsack solver and single-random-draw. Results are
+//! compared with the waste metric and input weights follow the script type
+//! of every UTXO.
+
+use super::{script_types::ScriptType, Amount, BitcoinError, BitcoinResult, Utxo};
+use bitcoin::ScriptBuf;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{debug, warn};
+
+/// Weight of version and lock time
+const TX_BASE_WEIGHT: u64 = (4 + 4) * 4;
+/// Weight of SegWit marker and flag
+const SEGWIT_MARKER_WEIGHT: u64 = 2;
+/// Maximum number of Branch-and-Bound iterations
+const BNB_MAX_TRIES: usize = 100_000;
+/// Number of random passes of the knapsack solver
+const KNAPSACK_ITERATIONS: usize = 1_000;
+
+/// Coin selection algorithm that produced a selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SelectionAlgorithm {
+    /// Branch-and-Bound search for a changeless input set
+    BranchAndBound,
+    /// Knapsack solver (randomized subset approximation)
+    Knapsack,
+    /// Random UTXOs until the target is reached
+    SingleRandomDraw,
+}
+
+/// Coin selection strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SelectionStrategy {
+    /// Run every algorithm and keep the selection with the lowest waste
+    LowestWaste,
+    /// Branch-and-Bound, falling back to knapsack when no changeless
+    /// input set exists
+    BranchAndBound,
+    /// Knapsack solver only
+    Knapsack,
+    /// Single random draw only
+    SingleRandomDraw,
+}
+
+/// Coin selection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoinSelectionConfig {
+    /// Selection strategy
+    pub strategy: SelectionStrategy,
+    /// Expected future fee rate in sat/vB, used by the waste metric
+    pub long_term_fee_rate: f64,
+    /// Script type of the change output
+    pub change_type: ScriptType,
+    /// Minimum change amount (smaller change is added to the fee)
+    pub min_change: Amount,
+    /// Minimum confirmations of selected UTXOs
+    pub min_confirmations: u32,
+    /// Spend all UTXOs of an address together so it is never reused
+    pub avoid_address_reuse: bool,
+    /// Never combine UTXOs with different labels in one transaction
+    pub avoid_label_mixing: bool,
+}
+
+/// Result of coin selection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoinSelection {
+    /// Algorithm that produced the selection
+    pub algorithm: SelectionAlgorithm,
+    /// Selected UTXOs
+    pub utxos: Vec<Utxo>,
+    /// Total value of selected UTXOs
+    pub input_amount: Amount,
+    /// Transaction fee
+    pub fee: Amount,
+    /// Change amount (None for changeless transactions)
+    pub change: Option<Amount>,
+    /// Estimated transaction weight
+    pub weight: u64,
+    /// Waste metric in satoshis (lower is better)
+    pub waste: i64,
+}
+
+/// Coin selector
+pub struct CoinSelector {
+    config: CoinSelectionConfig,
+}
+
+/// Group of UTXOs that is selected as a whole
+#[derive(Debug, Clone)]
+struct OutputGroup {
+    utxos: Vec<(Utxo, ScriptType)>,
+    label: Option<String>,
+    value: u64,
+    /// Fee of spending the group at the current fee rate
+    fee: u64,
+    /// Fee of spending the group at the long-term fee rate
+    long_term_fee: u64,
+}
+
+/// Payment parameters shared by all algorithms
+struct SelectionContext<'a> {
+    payment: u64,
+    outputs: &'a [ScriptType],
+    fee_rate: f64,
+    long_term_fee_rate: f64,
+    /// Payment plus fee of the transaction without inputs
+    target: u64,
+    /// Fee of adding a change output now and spending it later
+    cost_of_change: u64,
+    /// Minimum effective value above `target` for a selection with change
+    change_target: u64,
+}
+
+impl Default for CoinSelectionConfig {
+    fn default() -> Self {
+        Self {
+            strategy: SelectionStrategy::LowestWaste,
+            long_term_fee_rate: 10.0,
+            change_type: ScriptType::P2WPKH,
+            min_change: Amount::from_sat(546), // Dust threshold
+            min_confirmations: 0,
+            avoid_address_reuse: false,
+            avoid_label_mixing: false,
+        }
+    }
+}
+
+impl CoinSelectionConfig {
+    /// Privacy preserving configuration (no address reuse, no label mixing)
+    pub fn privacy() -> Self {
+        Self {
+            avoid_address_reuse: true,
+            avoid_label_mixing: true,
+            ..Self::default()
+        }
+    }
+}
+
+impl CoinSelection {
+    /// Virtual size in vBytes
+    pub fn vsize(&self) -> u64 {
+        weight_to_vsize(self.weight)
+    }
+
+    /// Effective fee rate in sat/vB
+    pub fn fee_rate(&self) -> f64 {
+        self.fee.to_sat() as f64 / self.vsize() as f64
+    }
+}
+
+impl OutputGroup {
+    fn effective_value(&self) -> i64 {
+        self.value as i64 - self.fee as i64
+    }
+}
+
+impl CoinSelector {
+    /// Create new coin selector
+    pub fn new(config: CoinSelectionConfig) -> Self {
+        Self { config }
+    }
+
+    /// Get configuration
+    pub fn config(&self) -> &CoinSelectionConfig {
+        &self.config
+    }
+
+    /// Select UTXOs paying `payment` to outputs of the given script types
+    /// at `fee_rate` sat/vB
+    pub fn select(
+        &self,
+        utxos: &[Utxo],
+        payment: Amount,
+        outputs: &[ScriptType],
+        fee_rate: f64,
+    ) -> BitcoinResult<CoinSelection> {
+        self.select_with_rng(utxos, payment, outputs, fee_rate, &mut rand::thread_rng())
+    }
+
+    /// Select UTXOs using the given random number generator
+    pub fn select_with_rng<R: Rng>(
+        &self,
+        utxos: &[Utxo],
+        payment: Amount,
+        outputs: &[ScriptType],
+        fee_rate: f64,
+        rng: &mut R,
+    ) -> BitcoinResult<CoinSelection> {
+        if !fee_rate.is_finite() || fee_rate < 0.0 {
+            return Err(BitcoinError::InvalidInput(format!("Invalid fee rate: {}", fee_rate)));
+        }
+        if payment.is_zero() || outputs.is_empty() {
+            return Err(BitcoinError::InvalidInput("Nothing to pay".to_string()));
+        }
+
+        let candidates: Vec<(Utxo, ScriptType)> = utxos
+            .iter()
+            .filter(|utxo| utxo.spendable && utxo.safe)
+            .filter(|utxo| utxo.confirmations >= self.config.min_confirmations)
+            .filter_map(|utxo| {
+                let script_type = ScriptBuf::from_hex(&utxo.script_pubkey)
+                    .map(|script| ScriptType::from_script_pubkey(&script))
+                    .unwrap_or(ScriptType::Custom);
+                if script_type.input_weight().is_none() {
+                    warn!("Skipping UTXO {}:{} with unsupported script type {}", utxo.txid, utxo.vout, script_type.as_str());
+                    return None;
+                }
+                Some((utxo.clone(), script_type))
+            })
+            .collect();
+
+        let witness_tx = candidates.iter().any(|(_, script_type)| script_type.has_witness_input());
+        let context = self.context(payment.to_sat(), outputs, fee_rate, witness_tx);
+        let groups = self.group_outputs(candidates, fee_rate, witness_tx);
+
+        // Every label is a separate pool when labels must not be mixed
+        let pools: Vec<Vec<OutputGroup>> = if self.config.avoid_label_mixing {
+            let mut pools: HashMap<Option<String>, Vec<OutputGroup>> = HashMap::new();
+            for group in groups {
+                pools.entry(group.label.clone()).or_default().push(group);
+            }
+            pools.into_values().collect()
+        } else {
+            vec![groups]
+        };
+
+        let best = pools
+            .iter()
+            .flat_map(|pool| self.select_from_pool(pool, &context, rng))
+            .min_by_key(|selection| (selection.waste, selection.utxos.len()));
+
+        match best {
+            Some(selection) => {
+                debug!(
+                    "Selected {} UTXOs with {:?}, fee: {}, change: {:?}, waste: {}",
+                    selection.utxos.len(),
+                    selection.algorithm,
+                    selection.fee,
+                    selection.change,
+                    selection.waste
+                );
+                Ok(selection)
+            }
+            None => {
+                let available = pools
+                    .iter()
+                    .map(|pool| pool.iter().map(|group| group.value).sum::<u64>())
+                    .max()
+                    .unwrap_or(0);
+                Err(BitcoinError::InsufficientFunds {
+                    required: Amount::from_sat(context.target),
+                    available: Amount::from_sat(available),
+                })
+            }
+        }
+    }
+
+    fn context<'a>(
+        &self,
+        payment: u64,
+        outputs: &'a [ScriptType],
+        fee_rate: f64,
+        witness_tx: bool,
+    ) -> SelectionContext<'a> {
+        // Input count is assumed to fit in one byte
+        let mut weight = TX_BASE_WEIGHT + 4 + compact_size_weight(outputs.len());
+        weight += outputs.iter().map(ScriptType::output_weight).sum::<u64>();
+        if witness_tx {
+            weight += SEGWIT_MARKER_WEIGHT;
+        }
+
+        let change_type = self.config.change_type;
+        let change_fee = fee_for_weight(fee_rate, change_type.output_weight());
+        let change_spend_fee = fee_for_weight(
+            self.config.long_term_fee_rate,
+            change_type.input_weight().unwrap_or(0),
+        );
+
+        SelectionContext {
+            payment,
+            outputs,
+            fee_rate,
+            long_term_fee_rate: self.config.long_term_fee_rate,
+            target: payment + fee_for_weight(fee_rate, weight),
+            cost_of_change: change_fee + change_spend_fee,
+            change_target: change_fee + self.config.min_change.to_sat(),
+        }
+    }
+
+    /// Build output groups, one per UTXO or one per address when address
+    /// reuse is avoided
+    fn group_outputs(&self, candidates: Vec<(Utxo, ScriptType)>, fee_rate: f64, witness_tx: bool) -> Vec<OutputGroup> {
+        let mut groups: Vec<OutputGroup> = Vec::new();
+        let mut by_script: HashMap<String, usize> = HashMap::new();
+
+        for (utxo, script_type) in candidates {
+            let mut weight = script_type.input_weight().unwrap_or(0);
+            // Empty witness of a non-witness input in a SegWit transaction
+            if witness_tx && !script_type.has_witness_input() {
+                weight += 1;
+            }
+            let fee = fee_for_weight(fee_rate, weight);
+            let long_term_fee = fee_for_weight(self.config.long_term_fee_rate, weight);
+
+            let existing = if self.config.avoid_address_reuse {
+                by_script.get(&utxo.script_pubkey).copied()
+            } else {
+                None
+            };
+
+            match existing {
+                Some(index) => {
+                    let group = &mut groups[index];
+                    group.value += utxo.amount.to_sat();
+                    group.fee += fee;
+                    group.long_term_fee += long_term_fee;
+                    group.label = group.label.take().or_else(|| utxo.label.clone());
+                    group.utxos.push((utxo, script_type));
+                }
+                None => {
+                    by_script.insert(utxo.script_pubkey.clone(), groups.len());
+                    groups.push(OutputGroup {
+                        label: utxo.label.clone(),
+                        value: utxo.amount.to_sat(),
+                        fee,
+                        long_term_fee,
+                        utxos: vec![(utxo, script_type)],
+                    });
+                }
+            }
+        }
+
+        groups
+    }
+
+    fn select_from_pool<R: Rng>(
+        &self,
+        pool: &[OutputGroup],
+        context: &SelectionContext,
+        rng: &mut R,
+    ) -> Vec<CoinSelection> {
+        // Groups costing more to spend than they are worth never help
+        let pool: Vec<&OutputGroup> = pool.iter().filter(|group| group.effective_value() > 0).collect();
+
+        let bnb = || {
+            branch_and_bound(&pool, context)
+                .and_then(|selection| self.finish(&selection, SelectionAlgorithm::BranchAndBound, context))
+        };
+
+        let mut selections = Vec::new();
+        match self.config.strategy {
+            SelectionStrategy::LowestWaste => {
+                selections.extend(bnb());
+                selections.extend(
+                    knapsack(&pool, context, rng)
+                        .and_then(|selection| self.finish(&selection, SelectionAlgorithm::Knapsack, context)),
+                );
+                selections.extend(
+                    single_random_draw(&pool, context, rng)
+                        .and_then(|selection| self.finish(&selection, SelectionAlgorithm::SingleRandomDraw, context)),
+                );
+            }
+            SelectionStrategy::BranchAndBound => {
+                let selection = bnb().or_else(|| {
+                    knapsack(&pool, context, rng)
+                        .and_then(|selection| self.finish(&selection, SelectionAlgorithm::Knapsack, context))
+                });
+                selections.extend(selection);
+            }
+            SelectionStrategy::Knapsack => {
+                selections.extend(
+                    knapsack(&pool, context, rng)
+                        .and_then(|selection| self.finish(&selection, SelectionAlgorithm::Knapsack, context)),
+                );
+            }
+            SelectionStrategy::SingleRandomDraw => {
+                selections.extend(
+                    single_random_draw(&pool, context, rng)
+                        .and_then(|selection| self.finish(&selection, SelectionAlgorithm::SingleRandomDraw, context)),
+                );
+            }
+        }
+
+        selections
+    }
+
+    /// Compute fee, change and waste of the selected groups
+    fn finish(
+        &self,
+        groups: &[&OutputGroup],
+        algorithm: SelectionAlgorithm,
+        context: &SelectionContext,
+    ) -> Option<CoinSelection> {
+        let utxos: Vec<&(Utxo, ScriptType)> = groups.iter().flat_map(|group| &group.utxos).collect();
+        let inputs: Vec<ScriptType> = utxos.iter().map(|(_, script_type)| *script_type).collect();
+        let input_amount: u64 = groups.iter().map(|group| group.value).sum();
+        let input_waste: i64 = groups
+            .iter()
+            .map(|group| group.fee as i64 - group.long_term_fee as i64)
+            .sum();
+
+        let weight = estimate_weight(&inputs, context.outputs)?;
+        let fee = fee_for_weight(context.fee_rate, weight);
+        let excess = input_amount.checked_sub(context.payment + fee)?;
+
+        // Branch-and-Bound selections are changeless by construction
+        if algorithm != SelectionAlgorithm::BranchAndBound {
+            let mut outputs = context.outputs.to_vec();
+            outputs.push(self.config.change_type);
+            let weight = estimate_weight(&inputs, &outputs)?;
+            let fee = fee_for_weight(context.fee_rate, weight);
+            let change = input_amount.saturating_sub(context.payment + fee);
+
+            if change >= self.config.min_change.to_sat() {
+                return Some(CoinSelection {
+                    algorithm,
+                    utxos: utxos.into_iter().map(|(utxo, _)| utxo.clone()).collect(),
+                    input_amount: Amount::from_sat(input_amount),
+                    fee: Amount::from_sat(fee),
+                    change: Some(Amount::from_sat(change)),
+                    weight,
+                    waste: input_waste + context.cost_of_change as i64,
+                });
+            }
+        }
+
+        Some(CoinSelection {
+            algorithm,
+            utxos: utxos.into_iter().map(|(utxo, _)| utxo.clone()).collect(),
+            input_amount: Amount::from_sat(input_amount),
+            fee: Amount::from_sat(fee + excess),
+            change: None,
+            weight,
+            waste: input_waste + excess as i64,
+        })
+    }
+}
+
+impl Default for CoinSelector {
+    fn default() -> Self {
+        Self::new(CoinSelectionConfig::default())
+    }
+}
+
+/// Estimate weight of a transaction spending and creating the given
+/// script types
+///
+/// Returns `None` if an input script type has no weight estimate.
+pub fn estimate_weight(inputs: &[ScriptType], outputs: &[ScriptType]) -> Option<u64> {
+    let witness_tx = inputs.iter().any(ScriptType::has_witness_input);
+
+    let mut weight = TX_BASE_WEIGHT + compact_size_weight(inputs.len()) + compact_size_weight(outputs.len());
+    for input in inputs {
+        weight += input.input_weight()?;
+        if witness_tx && !input.has_witness_input() {
+            weight += 1;
+        }
+    }
+    weight += outputs.iter().map(ScriptType::output_weight).sum::<u64>();
+    if witness_tx {
+        weight += SEGWIT_MARKER_WEIGHT;
+    }
+
+    Some(weight)
+}
+
+/// Convert weight to virtual size
+pub fn weight_to_vsize(weight: u64) -> u64 {
+    weight.div_ceil(4)
+}
+
+/// Fee in satoshis for the given weight at `fee_rate` sat/vB
+fn fee_for_weight(fee_rate: f64, weight: u64) -> u64 {
+    (fee_rate * weight as f64 / 4.0).ceil() as u64
+}
+
+fn compact_size_weight(count: usize) -> u64 {
+    let size = match count {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        _ => 5,
+    };
+    size * 4
+}
+
+/// Depth-first search for the input set with the lowest waste whose
+/// effective value lies within [target, target + cost_of_change]
+fn branch_and_bound<'a>(pool: &[&'a OutputGroup], context: &SelectionContext) -> Option<Vec<&'a OutputGroup>> {
+    let (target, cost_of_change) = (context.target, context.cost_of_change);
+    let mut pool = pool.to_vec();
+    pool.sort_by_key(|group| std::cmp::Reverse(group.effective_value()));
+    let values: Vec<u64> = pool.iter().map(|group| group.effective_value() as u64).collect();
+    let waste: Vec<i64> = pool
+        .iter()
+        .map(|group| group.fee as i64 - group.long_term_fee as i64)
+        .collect();
+
+    let mut available: u64 = values.iter().sum();
+    if available < target {
+        return None;
+    }
+
+    // Adding inputs only increases waste when fees are above the long-term rate
+    let prune_by_waste = context.fee_rate > context.long_term_fee_rate;
+
+    let mut selection: Vec<usize> = Vec::new();
+    let mut best: Option<Vec<usize>> = None;
+    let mut best_waste = i64::MAX;
+    let mut value = 0u64;
+    let mut current_waste = 0i64;
+    let mut index = 0usize;
+
+    for _ in 0..BNB_MAX_TRIES {
+        let mut backtrack = false;
+        if value + available < target
+            || value > target + cost_of_change
+            || (prune_by_waste && current_waste > best_waste)
+        {
+            backtrack = true;
+        } else if value >= target {
+            let total_waste = current_waste + (value - target) as i64;
+            if total_waste <= best_waste {
+                best = Some(selection.clone());
+                best_waste = total_waste;
+            }
+            backtrack = true;
+        }
+
+        if backtrack {
+            let Some(&last) = selection.last() else {
+                break;
+            };
+            // Return omitted groups to the lookahead, then exclude the last
+            // included group
+            index -= 1;
+            while index > last {
+                available += values[index];
+                index -= 1;
+            }
+            value -= values[index];
+            current_waste -= waste[index];
+            selection.pop();
+        } else {
+            available -= values[index];
+            // Skip inclusion when an equivalent previous group was excluded
+            let equivalent_excluded = index > 0
+                && selection.last() != Some(&(index - 1))
+                && values[index] == values[index - 1]
+                && waste[index] == waste[index - 1];
+            if !equivalent_excluded {
+                selection.push(index);
+                value += values[index];
+                current_waste += waste[index];
+            }
+        }
+        index += 1;
+    }
+
+    best.map(|selection| selection.into_iter().map(|index| pool[index]).collect())
+}
+
+/// Knapsack solver: exact match, smallest sufficient group or the best of
+/// randomized subset approximations
+fn knapsack<'a, R: Rng>(
+    pool: &[&'a OutputGroup],
+    context: &SelectionContext,
+    rng: &mut R,
+) -> Option<Vec<&'a OutputGroup>> {
+    let target = context.target;
+    let target_with_change = target + context.change_target;
+
+    let mut pool = pool.to_vec();
+    pool.shuffle(rng);
+
+    let mut applicable: Vec<&OutputGroup> = Vec::new();
+    let mut lowest_larger: Option<&OutputGroup> = None;
+    for group in pool {
+        let value = group.effective_value() as u64;
+        if value == target {
+            return Some(vec![group]);
+        } else if value < target_with_change {
+            applicable.push(group);
+        } else if lowest_larger.map_or(true, |lowest| value < lowest.effective_value() as u64) {
+            lowest_larger = Some(group);
+        }
+    }
+
+    let applicable_total: u64 = applicable.iter().map(|group| group.effective_value() as u64).sum();
+    if applicable_total == target {
+        return Some(applicable);
+    }
+    if applicable_total < target_with_change {
+        return match lowest_larger {
+            Some(group) => Some(vec![group]),
+            None if applicable_total >= target => Some(applicable),
+            None => None,
+        };
+    }
+
+    applicable.sort_by_key(|group| std::cmp::Reverse(group.effective_value()));
+    let values: Vec<u64> = applicable.iter().map(|group| group.effective_value() as u64).collect();
+    let (included, best_total) = approximate_best_subset(&values, target_with_change, rng);
+
+    // Prefer the smallest sufficient group when it is closer to the target
+    if let Some(group) = lowest_larger {
+        if best_total != target_with_change && group.effective_value() as u64 <= best_total {
+            return Some(vec![group]);
+        }
+    }
+
+    Some(
+        applicable
+            .into_iter()
+            .zip(included)
+            .filter_map(|(group, included)| included.then_some(group))
+            .collect(),
+    )
+}
+
+fn approximate_best_subset<R: Rng>(values: &[u64], target: u64, rng: &mut R) -> (Vec<bool>, u64) {
+    let mut best = vec![true; values.len()];
+    let mut best_total: u64 = values.iter().sum();
+
+    for _ in 0..KNAPSACK_ITERATIONS {
+        if best_total == target {
+            break;
+        }
+
+        let mut included = vec![false; values.len()];
+        let mut total = 0u64;
+        let mut reached = false;
+        // First pass picks random groups, the second adds the remaining ones
+        for pass in 0..2 {
+            if reached {
+                break;
+            }
+            for i in 0..values.len() {
+                let include = if pass == 0 { rng.gen_bool(0.5) } else { !included[i] };
+                if !include {
+                    continue;
+                }
+                total += values[i];
+                included[i] = true;
+                if total >= target {
+                    reached = true;
+                    if total < best_total {
+                        best_total = total;
+                        best = included.clone();
+                    }
+                    total -= values[i];
+                    included[i] = false;
+                }
+            }
+        }
+    }
+
+    (best, best_total)
+}
+
+/// Random groups until the target plus change is covered
+fn single_random_draw<'a, R: Rng>(
+    pool: &[&'a OutputGroup],
+    context: &SelectionContext,
+    rng: &mut R,
+) -> Option<Vec<&'a OutputGroup>> {
+    let target = context.target + context.change_target;
+
+    let mut pool = pool.to_vec();
+    pool.shuffle(rng);
+
+    let mut selected = Vec::new();
+    let mut total = 0u64;
+    for group in pool {
+        total += group.effective_value() as u64;
+        selected.push(group);
+        if total >= target {
+            return Some(selected);
+        }
+    }
+
+    None
+}
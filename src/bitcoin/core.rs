@@ -6,6 +6,7 @@ use super::{
     Network, Utxo, AddressType,
 };
 use anyhow::{Context, Result};
+use bitcoin::Transaction;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::{debug, error, info, warn};
@@ -182,6 +183,12 @@ impl BitcoinCore {
         Ok(true)
     }
 
+    /// Whether any transaction ever paid to an address watched by the node wallet
+    pub async fn address_has_history(&self, address: &str) -> BitcoinResult<bool> {
+        let received = self.rpc.list_received_by_address(0, true, Some(address)).await?;
+        Ok(received.iter().any(|entry| !entry.txids.is_empty()))
+    }
+
     /// Get transaction details
     pub async fn get_transaction(&self, txid: &str) -> BitcoinResult<BitcoinTransactionInfo> {
         let tx_info = self.rpc.get_transaction(txid).await?;
@@ -199,10 +206,40 @@ impl BitcoinCore {
                 category: d.category,
                 amount: Amount::from_btc(d.amount),
                 label: d.label,
+                vout: d.vout,
             }).collect(),
+            hex: tx_info.hex,
         })
     }
 
+    /// Get decoded wallet transaction
+    pub async fn get_raw_transaction(&self, txid: &str) -> BitcoinResult<Transaction> {
+        let tx_info = self.rpc.get_transaction(txid).await?;
+        let bytes = hex::decode(&tx_info.hex)
+            .map_err(|e| BitcoinError::Rpc(format!("Invalid transaction hex for {}: {}", txid, e)))?;
+        bitcoin::consensus::encode::deserialize(&bytes)
+            .map_err(|e| BitcoinError::Rpc(format!("Invalid transaction {}: {}", txid, e)))
+    }
+
+    /// Get incremental relay fee in sat/vB
+    pub async fn get_incremental_relay_fee(&self) -> BitcoinResult<f64> {
+        let network_info = self.rpc.get_network_info().await?;
+        // Bitcoin Core reports BTC/kvB
+        Ok(network_info.incremental_fee * 100_000.0)
+    }
+
+    /// Sign transaction with wallet keys and broadcast it
+    pub async fn sign_and_send_transaction(&self, transaction: &Transaction) -> BitcoinResult<String> {
+        let raw_tx = bitcoin::consensus::encode::serialize_hex(transaction);
+        let signed_tx = self.rpc.sign_raw_transaction_with_wallet(&raw_tx).await?;
+
+        if !signed_tx.complete {
+            return Err(BitcoinError::SigningError("Transaction signing incomplete".to_string()));
+        }
+
+        self.rpc.send_raw_transaction(&signed_tx.hex).await
+    }
+
     /// Create wallet if it doesn't exist
     pub async fn create_wallet(&self, wallet_name: &str, disable_private_keys: bool) -> BitcoinResult<()> {
         // This would require additional RPC calls like createwallet
@@ -269,6 +306,22 @@ pub struct BitcoinUtxo {
     pub label: Option<String>,
 }
 
+impl From<BitcoinUtxo> for Utxo {
+    fn from(utxo: BitcoinUtxo) -> Self {
+        Self {
+            txid: utxo.txid,
+            vout: utxo.vout,
+            amount: utxo.amount,
+            script_pubkey: utxo.script_pubkey,
+            address: Some(utxo.address),
+            confirmations: utxo.confirmations,
+            spendable: utxo.spendable,
+            safe: utxo.safe,
+            label: utxo.label,
+        }
+    }
+}
+
 /// Bitcoin transaction information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BitcoinTransactionInfo {
@@ -280,6 +333,8 @@ pub struct BitcoinTransactionInfo {
     pub block_time: Option<u64>,
     pub time: u64,
     pub details: Vec<TransactionDetail>,
+    /// Raw transaction hex
+    pub hex: String,
 }
 
 /// Transaction detail
@@ -289,6 +344,7 @@ pub struct TransactionDetail {
     pub category: String,
     pub amount: Amount,
     pub label: Option<String>,
+    pub vout: u32,
 }
 
 /// Bitcoin wallet information
@@ -0,0 +1,486 @@
+//! Output Descriptor Module
+//!
+//! This module implements BIP-380 output descriptors with checksums for the
+//! script types the wallet tracks: `wpkh`, `sh(wpkh)`, `tr` (key path) and
+//! `wsh(multi)`/`wsh(sortedmulti)`. Descriptors only carry public keys, so
+//! they can drive address derivation for watch-only wallets.
+
+use super::{AddressType, BitcoinError, BitcoinResult, Network};
+use bitcoin::{
+    bip32::{ChildNumber, DerivationPath, Fingerprint, Xpub},
+    opcodes::all::OP_CHECKMULTISIG,
+    script::Builder,
+    secp256k1::{Parity, PublicKey as SecpPublicKey, Secp256k1, Verification},
+    Address, PublicKey, ScriptBuf, XOnlyPublicKey,
+};
+use std::fmt;
+use std::str::FromStr;
+
+/// Characters accepted in a descriptor, ordered for the checksum
+const INPUT_CHARSET: &str =
+    "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+
+/// Bech32 alphabet used for the checksum characters
+const CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Maximum number of keys in a `multi`/`sortedmulti` expression
+const MAX_MULTISIG_KEYS: usize = 20;
+
+/// Key origin information (`[fingerprint/path]`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyOrigin {
+    /// Fingerprint of the master key
+    pub fingerprint: Fingerprint,
+    /// Path from the master key to the descriptor key
+    pub path: DerivationPath,
+}
+
+/// Public key material inside a descriptor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DescriptorKeyKind {
+    /// A single compressed public key
+    Single(PublicKey),
+    /// A single x-only public key (only valid inside `tr()`)
+    XOnly(XOnlyPublicKey),
+    /// An extended public key with an unhardened child path
+    Extended {
+        /// Extended public key
+        xpub: Xpub,
+        /// Child path below the extended key
+        path: DerivationPath,
+        /// Whether the path ends in `/*`
+        wildcard: bool,
+    },
+}
+
+/// A key expression with optional origin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DescriptorKey {
+    /// Key origin, if given
+    pub origin: Option<KeyOrigin>,
+    /// Key material
+    pub kind: DescriptorKeyKind,
+}
+
+/// Threshold multisig inside `wsh()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiSig {
+    /// Required signatures
+    pub threshold: usize,
+    /// Participating keys
+    pub keys: Vec<DescriptorKey>,
+    /// Whether keys are sorted lexicographically (`sortedmulti`)
+    pub sorted: bool,
+}
+
+/// Supported output descriptors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Descriptor {
+    /// Native SegWit v0 single key: `wpkh(KEY)`
+    Wpkh(DescriptorKey),
+    /// Nested SegWit v0 single key: `sh(wpkh(KEY))`
+    ShWpkh(DescriptorKey),
+    /// Taproot key-path only: `tr(KEY)`
+    Tr(DescriptorKey),
+    /// SegWit v0 multisig: `wsh(multi(...))` or `wsh(sortedmulti(...))`
+    Wsh(MultiSig),
+}
+
+/// Compute the BIP-380 checksum of a descriptor without its `#` suffix
+pub fn descriptor_checksum(descriptor: &str) -> BitcoinResult<String> {
+    fn polymod(c: u64, val: u64) -> u64 {
+        let c0 = c >> 35;
+        let mut c = ((c & 0x7ffffffff) << 5) ^ val;
+        if c0 & 1 != 0 {
+            c ^= 0xf5dee51989;
+        }
+        if c0 & 2 != 0 {
+            c ^= 0xa9fdca3312;
+        }
+        if c0 & 4 != 0 {
+            c ^= 0x1bab10e32d;
+        }
+        if c0 & 8 != 0 {
+            c ^= 0x3706b1677a;
+        }
+        if c0 & 16 != 0 {
+            c ^= 0x644d626ffd;
+        }
+        c
+    }
+
+    let mut c = 1u64;
+    let mut cls = 0u64;
+    let mut cls_count = 0;
+    for ch in descriptor.chars() {
+        let pos = INPUT_CHARSET.find(ch).ok_or_else(|| {
+            BitcoinError::InvalidInput(format!("Invalid character in descriptor: {:?}", ch))
+        })? as u64;
+        c = polymod(c, pos & 31);
+        cls = cls * 3 + (pos >> 5);
+        cls_count += 1;
+        if cls_count == 3 {
+            c = polymod(c, cls);
+            cls = 0;
+            cls_count = 0;
+        }
+    }
+    if cls_count > 0 {
+        c = polymod(c, cls);
+    }
+    for _ in 0..8 {
+        c = polymod(c, 0);
+    }
+    c ^= 1;
+
+    Ok((0..8)
+        .map(|j| CHECKSUM_CHARSET[((c >> (5 * (7 - j))) & 31) as usize] as char)
+        .collect())
+}
+
+impl DescriptorKey {
+    /// Whether the key derives a new child per index
+    pub fn is_ranged(&self) -> bool {
+        matches!(self.kind, DescriptorKeyKind::Extended { wildcard: true, .. })
+    }
+
+    /// Full derivation path from the master key at `index`, when the origin is known
+    pub fn full_path(&self, index: u32) -> Option<DerivationPath> {
+        let origin = self.origin.as_ref()?;
+        match &self.kind {
+            DescriptorKeyKind::Extended { path, wildcard, .. } => {
+                let mut full = origin.path.extend(path);
+                if *wildcard {
+                    full = full.child(ChildNumber::Normal { index });
+                }
+                Some(full)
+            }
+            _ => Some(origin.path.clone()),
+        }
+    }
+
+    /// Derive the public key at `index`
+    pub fn derive<C: Verification>(&self, secp: &Secp256k1<C>, index: u32) -> BitcoinResult<SecpPublicKey> {
+        match &self.kind {
+            DescriptorKeyKind::Single(key) => Ok(key.inner),
+            DescriptorKeyKind::XOnly(key) => Ok(key.public_key(Parity::Even)),
+            DescriptorKeyKind::Extended { xpub, path, wildcard } => {
+                let mut children: Vec<ChildNumber> = path.into_iter().copied().collect();
+                if *wildcard {
+                    children.push(ChildNumber::from_normal_idx(index).map_err(|e| {
+                        BitcoinError::KeyManagement(format!("Invalid derivation index: {}", e))
+                    })?);
+                }
+                xpub.derive_pub(secp, &children)
+                    .map(|derived| derived.public_key)
+                    .map_err(|e| BitcoinError::KeyManagement(format!("Key derivation failed: {}", e)))
+            }
+        }
+    }
+
+    /// Network the key is bound to, if it is an extended key
+    fn network(&self) -> Option<bitcoin::Network> {
+        match &self.kind {
+            DescriptorKeyKind::Extended { xpub, .. } => Some(xpub.network),
+            _ => None,
+        }
+    }
+
+    fn parse(s: &str, allow_x_only: bool) -> BitcoinResult<Self> {
+        let (origin, key) = match s.strip_prefix('[') {
+            Some(rest) => {
+                let (origin, key) = rest
+                    .split_once(']')
+                    .ok_or_else(|| invalid("Unterminated key origin"))?;
+                let mut parts = origin.split('/');
+                let fingerprint = Fingerprint::from_str(parts.next().unwrap_or_default())
+                    .map_err(|e| invalid(&format!("Invalid key origin fingerprint: {}", e)))?;
+                let path = parse_path(parts)?;
+                (Some(KeyOrigin { fingerprint, path }), key)
+            }
+            None => (None, s),
+        };
+
+        let kind = if key.len() == 66 && key.chars().all(|c| c.is_ascii_hexdigit()) {
+            DescriptorKeyKind::Single(
+                PublicKey::from_str(key).map_err(|e| invalid(&format!("Invalid public key: {}", e)))?,
+            )
+        } else if key.len() == 64 && key.chars().all(|c| c.is_ascii_hexdigit()) {
+            if !allow_x_only {
+                return Err(invalid("X-only keys are only allowed in tr()"));
+            }
+            DescriptorKeyKind::XOnly(
+                XOnlyPublicKey::from_str(key)
+                    .map_err(|e| invalid(&format!("Invalid x-only key: {}", e)))?,
+            )
+        } else if key.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid("Only compressed public keys are supported"));
+        } else {
+            let mut parts = key.split('/');
+            let xpub = Xpub::from_str(parts.next().unwrap_or_default())
+                .map_err(|e| invalid(&format!("Invalid extended public key: {}", e)))?;
+            let mut children: Vec<&str> = parts.collect();
+            let wildcard = match children.last() {
+                Some(&"*") => {
+                    children.pop();
+                    true
+                }
+                Some(last) if last.starts_with('*') => {
+                    return Err(invalid("Hardened wildcards require private keys"));
+                }
+                _ => false,
+            };
+            let path = parse_path(children.into_iter())?;
+            if path.into_iter().any(ChildNumber::is_hardened) {
+                return Err(invalid("Hardened steps after an extended public key require private keys"));
+            }
+            DescriptorKeyKind::Extended { xpub, path, wildcard }
+        };
+
+        Ok(Self { origin, kind })
+    }
+}
+
+impl fmt::Display for DescriptorKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(origin) = &self.origin {
+            write!(f, "[{}", origin.fingerprint)?;
+            for child in &origin.path {
+                write!(f, "/{}", child)?;
+            }
+            write!(f, "]")?;
+        }
+        match &self.kind {
+            DescriptorKeyKind::Single(key) => write!(f, "{}", key),
+            DescriptorKeyKind::XOnly(key) => write!(f, "{}", key),
+            DescriptorKeyKind::Extended { xpub, path, wildcard } => {
+                write!(f, "{}", xpub)?;
+                for child in path {
+                    write!(f, "/{}", child)?;
+                }
+                if *wildcard {
+                    write!(f, "/*")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Descriptor {
+    /// Parse a descriptor, verifying its checksum when one is present
+    pub fn parse(s: &str) -> BitcoinResult<Self> {
+        let s = s.trim();
+        let body = match s.split_once('#') {
+            Some((body, checksum)) => {
+                let expected = descriptor_checksum(body)?;
+                if checksum != expected {
+                    return Err(invalid(&format!(
+                        "Descriptor checksum mismatch: expected {}, got {}",
+                        expected, checksum
+                    )));
+                }
+                body
+            }
+            None => s,
+        };
+
+        if let Some(inner) = wrapped(body, "sh(wpkh(", "))") {
+            return Ok(Descriptor::ShWpkh(DescriptorKey::parse(inner, false)?));
+        }
+        if let Some(inner) = wrapped(body, "wpkh(", ")") {
+            return Ok(Descriptor::Wpkh(DescriptorKey::parse(inner, false)?));
+        }
+        if let Some(inner) = wrapped(body, "tr(", ")") {
+            if inner.contains(',') {
+                return Err(invalid("tr() script trees are not supported"));
+            }
+            return Ok(Descriptor::Tr(DescriptorKey::parse(inner, true)?));
+        }
+        let multi = wrapped(body, "wsh(multi(", "))")
+            .map(|inner| (inner, false))
+            .or_else(|| wrapped(body, "wsh(sortedmulti(", "))").map(|inner| (inner, true)));
+        if let Some((inner, sorted)) = multi {
+            let mut parts = inner.split(',');
+            let threshold = parts
+                .next()
+                .and_then(|t| t.parse::<usize>().ok())
+                .ok_or_else(|| invalid("Invalid multisig threshold"))?;
+            let keys = parts
+                .map(|key| DescriptorKey::parse(key, false))
+                .collect::<BitcoinResult<Vec<_>>>()?;
+            if keys.is_empty() || keys.len() > MAX_MULTISIG_KEYS {
+                return Err(invalid("Multisig needs between 1 and 20 keys"));
+            }
+            if threshold == 0 || threshold > keys.len() {
+                return Err(invalid("Multisig threshold must be between 1 and the number of keys"));
+            }
+            return Ok(Descriptor::Wsh(MultiSig { threshold, keys, sorted }));
+        }
+
+        Err(invalid(&format!("Unsupported descriptor: {}", body)))
+    }
+
+    /// Keys referenced by the descriptor
+    pub fn keys(&self) -> Vec<&DescriptorKey> {
+        match self {
+            Descriptor::Wpkh(key) | Descriptor::ShWpkh(key) | Descriptor::Tr(key) => vec![key],
+            Descriptor::Wsh(multi) => multi.keys.iter().collect(),
+        }
+    }
+
+    /// Whether the descriptor derives a new script per index
+    pub fn is_ranged(&self) -> bool {
+        self.keys().iter().any(|key| key.is_ranged())
+    }
+
+    /// Address type produced by the descriptor
+    pub fn address_type(&self) -> AddressType {
+        match self {
+            Descriptor::Wpkh(_) | Descriptor::Wsh(_) => AddressType::Bech32,
+            Descriptor::ShWpkh(_) => AddressType::P2shSegwit,
+            Descriptor::Tr(_) => AddressType::Taproot,
+        }
+    }
+
+    /// Script type produced by the descriptor
+    pub fn script_type(&self) -> super::ScriptType {
+        match self {
+            Descriptor::Wpkh(_) => super::ScriptType::P2WPKH,
+            Descriptor::ShWpkh(_) => super::ScriptType::P2SH,
+            Descriptor::Tr(_) => super::ScriptType::P2TR,
+            Descriptor::Wsh(_) => super::ScriptType::P2WSH,
+        }
+    }
+
+    /// Descriptor body without the checksum
+    pub fn to_string_without_checksum(&self) -> String {
+        match self {
+            Descriptor::Wpkh(key) => format!("wpkh({})", key),
+            Descriptor::ShWpkh(key) => format!("sh(wpkh({}))", key),
+            Descriptor::Tr(key) => format!("tr({})", key),
+            Descriptor::Wsh(multi) => {
+                let keys: Vec<String> = multi.keys.iter().map(ToString::to_string).collect();
+                format!(
+                    "wsh({}({},{}))",
+                    if multi.sorted { "sortedmulti" } else { "multi" },
+                    multi.threshold,
+                    keys.join(",")
+                )
+            }
+        }
+    }
+
+    /// Check that extended keys belong to `network`
+    pub fn check_network(&self, network: Network) -> BitcoinResult<()> {
+        let mainnet = network == Network::Mainnet;
+        let mismatch = self
+            .keys()
+            .iter()
+            .filter_map(|key| key.network())
+            .any(|key_network| (key_network == bitcoin::Network::Bitcoin) != mainnet);
+        if mismatch {
+            return Err(BitcoinError::InvalidInput(format!(
+                "Descriptor keys do not belong to network {}",
+                network.as_str()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Derive the public keys at `index`
+    pub fn derive_keys<C: Verification>(&self, secp: &Secp256k1<C>, index: u32) -> BitcoinResult<Vec<SecpPublicKey>> {
+        self.keys().iter().map(|key| key.derive(secp, index)).collect()
+    }
+
+    /// Output script at `index`
+    pub fn script_pubkey<C: Verification>(&self, secp: &Secp256k1<C>, index: u32) -> BitcoinResult<ScriptBuf> {
+        let keys = self.derive_keys(secp, index)?;
+        let script = match self {
+            Descriptor::Wpkh(_) => ScriptBuf::new_p2wpkh(&wpkh(&keys[0])),
+            Descriptor::ShWpkh(_) => {
+                ScriptBuf::new_p2sh(&ScriptBuf::new_p2wpkh(&wpkh(&keys[0])).script_hash())
+            }
+            Descriptor::Tr(_) => ScriptBuf::new_p2tr(secp, keys[0].x_only_public_key().0, None),
+            Descriptor::Wsh(_) => ScriptBuf::new_p2wsh(&self.witness_script(secp, index)?.wscript_hash()),
+        };
+        Ok(script)
+    }
+
+    /// Witness script at `index` for `wsh()` descriptors
+    pub fn witness_script<C: Verification>(&self, secp: &Secp256k1<C>, index: u32) -> BitcoinResult<ScriptBuf> {
+        let Descriptor::Wsh(multi) = self else {
+            return Err(invalid("Only wsh() descriptors have a witness script"));
+        };
+        let mut keys = self.derive_keys(secp, index)?;
+        if multi.sorted {
+            keys.sort_by_key(|key| key.serialize());
+        }
+
+        let mut builder = Builder::new().push_int(multi.threshold as i64);
+        for key in &keys {
+            builder = builder.push_key(&PublicKey::new(*key));
+        }
+        Ok(builder
+            .push_int(keys.len() as i64)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script())
+    }
+
+    /// Address at `index` on `network`
+    pub fn address<C: Verification>(&self, secp: &Secp256k1<C>, index: u32, network: Network) -> BitcoinResult<Address> {
+        self.check_network(network)?;
+        let script = self.script_pubkey(secp, index)?;
+        Address::from_script(&script, network.into())
+            .map_err(|e| BitcoinError::InvalidAddress(e.to_string()))
+    }
+
+    /// Addresses for every index in `range`
+    pub fn addresses<C: Verification>(
+        &self,
+        secp: &Secp256k1<C>,
+        range: std::ops::Range<u32>,
+        network: Network,
+    ) -> BitcoinResult<Vec<Address>> {
+        range.map(|index| self.address(secp, index, network)).collect()
+    }
+}
+
+impl fmt::Display for Descriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let body = self.to_string_without_checksum();
+        let checksum = descriptor_checksum(&body).map_err(|_| fmt::Error)?;
+        write!(f, "{}#{}", body, checksum)
+    }
+}
+
+impl FromStr for Descriptor {
+    type Err = BitcoinError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Descriptor::parse(s)
+    }
+}
+
+fn invalid(message: &str) -> BitcoinError {
+    BitcoinError::InvalidInput(message.to_string())
+}
+
+fn wrapped<'a>(s: &'a str, prefix: &str, suffix: &str) -> Option<&'a str> {
+    s.strip_prefix(prefix)?.strip_suffix(suffix)
+}
+
+fn parse_path<'a>(parts: impl Iterator<Item = &'a str>) -> BitcoinResult<DerivationPath> {
+    parts
+        .map(|part| {
+            ChildNumber::from_str(part).map_err(|e| invalid(&format!("Invalid path step {:?}: {}", part, e)))
+        })
+        .collect::<BitcoinResult<Vec<_>>>()
+        .map(DerivationPath::from)
+}
+
+fn wpkh(key: &SecpPublicKey) -> bitcoin::WPubkeyHash {
+    PublicKey::new(*key)
+        .wpubkey_hash()
+        .expect("descriptor keys are compressed")
+}
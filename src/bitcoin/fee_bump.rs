@@ -0,0 +1,387 @@
+//! Fee Bumping Module
+//!
+//! This module builds Replace-By-Fee replacements following the BIP 125
+//! rules and Child-Pays-For-Parent transactions spending our change
+//! output. Every result is checked by the security validator before it is
+//! handed out for signing.
+
+use super::{
+    coin_selection::{estimate_weight, weight_to_vsize},
+    psbt::PsbtBuilder,
+    script_types::ScriptType,
+    security_validator::{SecurityValidator, ValidationResult},
+    Amount, BitcoinError, BitcoinResult, Network, Utxo,
+};
+use bitcoin::{
+    absolute::LockTime, psbt::Psbt, transaction::Version, Amount as BitcoinAmount, OutPoint, Script,
+    ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
+};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use tracing::{debug, info};
+
+/// Sequence number of inputs added by fee bumping (signals BIP 125)
+const RBF_SEQUENCE: Sequence = Sequence(0xfffffffd);
+
+/// Fee bumping configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeBumpConfig {
+    /// Incremental relay fee in sat/vB (BIP 125 rule 4)
+    pub incremental_relay_fee: f64,
+    /// Minimum relay fee in sat/vB
+    pub min_relay_fee: f64,
+    /// Minimum value of the change output (dust threshold)
+    pub min_change: Amount,
+}
+
+/// Fee bumping method
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeeBumpMethod {
+    /// Replacement transaction (BIP 125)
+    ReplaceByFee,
+    /// Child transaction spending an output of the stuck parent
+    ChildPaysForParent,
+}
+
+/// Unsigned fee bumping transaction
+#[derive(Debug, Clone)]
+pub struct FeeBump {
+    /// Fee bumping method
+    pub method: FeeBumpMethod,
+    /// Unsigned replacement or child transaction
+    pub transaction: Transaction,
+    /// Outputs spent by the transaction inputs
+    pub spent_outputs: Vec<TxOut>,
+    /// Fee of the transaction
+    pub fee: Amount,
+    /// Estimated virtual size of the signed transaction
+    pub vsize: u64,
+    /// Fee rate in sat/vB (package fee rate for CPFP)
+    pub fee_rate: f64,
+    /// Security validation result
+    pub validation: ValidationResult,
+}
+
+/// Builder of RBF and CPFP transactions
+pub struct FeeBumper {
+    config: FeeBumpConfig,
+    validator: SecurityValidator,
+}
+
+impl Default for FeeBumpConfig {
+    fn default() -> Self {
+        Self {
+            incremental_relay_fee: 1.0,
+            min_relay_fee: 1.0,
+            min_change: Amount::from_sat(546), // Dust threshold
+        }
+    }
+}
+
+impl FeeBump {
+    /// Amounts of the spent outputs in satoshis
+    pub fn input_amounts(&self) -> Vec<u64> {
+        self.spent_outputs.iter().map(|output| output.value.to_sat()).collect()
+    }
+
+    /// Convert to PSBT for signing
+    pub fn to_psbt(&self) -> BitcoinResult<PsbtBuilder> {
+        let mut psbt = Psbt::from_unsigned_tx(self.transaction.clone())
+            .map_err(|e| BitcoinError::InvalidPsbt(format!("Invalid unsigned transaction: {}", e)))?;
+        for (input, spent) in psbt.inputs.iter_mut().zip(&self.spent_outputs) {
+            input.witness_utxo = Some(spent.clone());
+        }
+        PsbtBuilder::from_psbt(&psbt)
+    }
+}
+
+impl FeeBumper {
+    /// Create fee bumper with default configuration
+    pub fn new(network: Network) -> Self {
+        Self::with_config(FeeBumpConfig::default(), SecurityValidator::new(network))
+    }
+
+    /// Create fee bumper with custom configuration and validator
+    pub fn with_config(config: FeeBumpConfig, validator: SecurityValidator) -> Self {
+        Self { config, validator }
+    }
+
+    /// Get configuration
+    pub fn config(&self) -> &FeeBumpConfig {
+        &self.config
+    }
+
+    /// Build a BIP 125 replacement paying `fee_rate` sat/vB
+    ///
+    /// The higher fee is taken from the change output at `change_index`.
+    /// When the change cannot cover it, confirmed UTXOs from `extra_utxos`
+    /// are added (largest first); unconfirmed ones are never used.
+    pub fn bump_rbf(
+        &self,
+        original: &Transaction,
+        spent_outputs: &[TxOut],
+        change_index: usize,
+        fee_rate: f64,
+        extra_utxos: &[Utxo],
+    ) -> BitcoinResult<FeeBump> {
+        if spent_outputs.len() != original.input.len() {
+            return Err(BitcoinError::InvalidInput(format!(
+                "Expected {} spent outputs, got {}",
+                original.input.len(),
+                spent_outputs.len()
+            )));
+        }
+        if change_index >= original.output.len() {
+            return Err(BitcoinError::InvalidInput(format!("Change output {} does not exist", change_index)));
+        }
+        // Rule 1: the original must signal replaceability
+        if !original.is_explicitly_rbf() {
+            return Err(BitcoinError::InvalidInput(format!(
+                "Transaction {} does not signal BIP 125 replaceability",
+                original.txid()
+            )));
+        }
+
+        let original_fee = total_value(spent_outputs)
+            .checked_sub(total_value(&original.output))
+            .ok_or_else(|| BitcoinError::InvalidInput("Outputs exceed inputs".to_string()))?;
+        let original_fee_rate = original_fee as f64 / original.vsize() as f64;
+        if !fee_rate.is_finite() || fee_rate <= original_fee_rate {
+            return Err(BitcoinError::InvalidInput(format!(
+                "Fee rate {:.2} sat/vB must exceed the original {:.2} sat/vB",
+                fee_rate, original_fee_rate
+            )));
+        }
+
+        let mut inputs: Vec<(TxIn, TxOut)> = original
+            .input
+            .iter()
+            .map(|input| TxIn {
+                script_sig: ScriptBuf::new(),
+                witness: Witness::new(),
+                ..input.clone()
+            })
+            .zip(spent_outputs.iter().cloned())
+            .collect();
+
+        // Rule 2: only confirmed UTXOs may be added
+        let mut extras = extra_utxos
+            .iter()
+            .filter(|utxo| utxo.confirmations > 0 && utxo.spendable && utxo.safe)
+            .map(utxo_input)
+            .collect::<BitcoinResult<Vec<_>>>()?;
+        extras.retain(|(extra, _)| {
+            !inputs
+                .iter()
+                .any(|(input, _)| input.previous_output == extra.previous_output)
+        });
+        extras.sort_by_key(|(_, spent)| std::cmp::Reverse(spent.value));
+        let mut extras = extras.into_iter();
+
+        let payments: Vec<TxOut> = original
+            .output
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != change_index)
+            .map(|(_, output)| output.clone())
+            .collect();
+        let payment_total = total_value(&payments);
+
+        let outputs = loop {
+            let input_total = inputs.iter().map(|(_, spent)| spent.value.to_sat()).sum::<u64>();
+            let available = input_total.saturating_sub(payment_total);
+
+            let required = self.replacement_fee(&inputs, &original.output, fee_rate, original_fee)?;
+            if available >= required + self.config.min_change.to_sat() {
+                let mut outputs = original.output.clone();
+                outputs[change_index].value = BitcoinAmount::from_sat(available - required);
+                break outputs;
+            }
+
+            if let Some(extra) = extras.next() {
+                debug!("Adding input {} to cover the higher fee", extra.0.previous_output);
+                inputs.push(extra);
+                continue;
+            }
+
+            // Last resort: give up the change output
+            let required = self.replacement_fee(&inputs, &payments, fee_rate, original_fee)?;
+            if available >= required {
+                break payments;
+            }
+            return Err(BitcoinError::InsufficientFunds {
+                required: Amount::from_sat(payment_total + required),
+                available: Amount::from_sat(input_total),
+            });
+        };
+
+        let (tx_inputs, spent_outputs): (Vec<TxIn>, Vec<TxOut>) = inputs.into_iter().unzip();
+        let transaction = Transaction {
+            version: original.version,
+            lock_time: original.lock_time,
+            input: tx_inputs,
+            output: outputs,
+        };
+        let fee = total_value(&spent_outputs) - total_value(&transaction.output);
+        let vsize = weight_to_vsize(transaction_weight(&spent_outputs, &transaction.output)?);
+
+        info!(
+            "Built RBF replacement of {}: fee {} -> {} sat ({:.2} sat/vB)",
+            original.txid(),
+            original_fee,
+            fee,
+            fee as f64 / vsize as f64
+        );
+
+        let validation = self.validate(&transaction, &spent_outputs)?;
+        Ok(FeeBump {
+            method: FeeBumpMethod::ReplaceByFee,
+            transaction,
+            spent_outputs,
+            fee: Amount::from_sat(fee),
+            vsize,
+            fee_rate: fee as f64 / vsize as f64,
+            validation,
+        })
+    }
+
+    /// Build a child spending output `vout` of `parent` to `destination`
+    /// so that parent and child together pay `package_fee_rate` sat/vB
+    pub fn bump_cpfp(
+        &self,
+        parent: &Transaction,
+        parent_fee: Amount,
+        vout: u32,
+        destination: &Script,
+        package_fee_rate: f64,
+    ) -> BitcoinResult<FeeBump> {
+        let spent = parent
+            .output
+            .get(vout as usize)
+            .cloned()
+            .ok_or_else(|| BitcoinError::InvalidInput(format!("Output {} does not exist", vout)))?;
+
+        let parent_vsize = parent.vsize() as u64;
+        let parent_fee_rate = parent_fee.to_sat() as f64 / parent_vsize as f64;
+        if !package_fee_rate.is_finite() || package_fee_rate <= parent_fee_rate {
+            return Err(BitcoinError::InvalidInput(format!(
+                "Fee rate {:.2} sat/vB must exceed the parent {:.2} sat/vB",
+                package_fee_rate, parent_fee_rate
+            )));
+        }
+
+        let mut transaction = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(parent.txid(), vout),
+                script_sig: ScriptBuf::new(),
+                sequence: RBF_SEQUENCE,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: BitcoinAmount::ZERO,
+                script_pubkey: destination.to_owned(),
+            }],
+        };
+
+        let spent_outputs = vec![spent];
+        let child_vsize = weight_to_vsize(transaction_weight(&spent_outputs, &transaction.output)?);
+        let package_fee = fee_for_vsize(package_fee_rate, parent_vsize + child_vsize);
+        let fee = package_fee
+            .saturating_sub(parent_fee.to_sat())
+            .max(fee_for_vsize(self.config.min_relay_fee, child_vsize));
+
+        let value = spent_outputs[0].value.to_sat();
+        if value < fee + self.config.min_change.to_sat() {
+            return Err(BitcoinError::InsufficientFunds {
+                required: Amount::from_sat(fee + self.config.min_change.to_sat()),
+                available: Amount::from_sat(value),
+            });
+        }
+        transaction.output[0].value = BitcoinAmount::from_sat(value - fee);
+
+        let fee_rate = (parent_fee.to_sat() + fee) as f64 / (parent_vsize + child_vsize) as f64;
+        info!(
+            "Built CPFP child of {}: fee {} sat, package {:.2} sat/vB",
+            parent.txid(),
+            fee,
+            fee_rate
+        );
+
+        let validation = self.validate(&transaction, &spent_outputs)?;
+        Ok(FeeBump {
+            method: FeeBumpMethod::ChildPaysForParent,
+            transaction,
+            spent_outputs,
+            fee: Amount::from_sat(fee),
+            vsize: child_vsize,
+            fee_rate,
+            validation,
+        })
+    }
+
+    /// Fee required by BIP 125 rules 3 and 4 and the target fee rate
+    fn replacement_fee(
+        &self,
+        inputs: &[(TxIn, TxOut)],
+        outputs: &[TxOut],
+        fee_rate: f64,
+        original_fee: u64,
+    ) -> BitcoinResult<u64> {
+        let spent: Vec<TxOut> = inputs.iter().map(|(_, spent)| spent.clone()).collect();
+        let vsize = weight_to_vsize(transaction_weight(&spent, outputs)?);
+
+        // Rule 3: at least the original fee; rule 4: pay for own relay
+        let min_fee = original_fee + fee_for_vsize(self.config.incremental_relay_fee, vsize);
+        Ok(fee_for_vsize(fee_rate, vsize).max(min_fee))
+    }
+
+    fn validate(&self, transaction: &Transaction, spent_outputs: &[TxOut]) -> BitcoinResult<ValidationResult> {
+        let input_amounts: Vec<u64> = spent_outputs.iter().map(|output| output.value.to_sat()).collect();
+        let validation = self.validator.validate_transaction(transaction, &input_amounts)?;
+        if !validation.is_valid {
+            return Err(BitcoinError::SecurityValidation(validation.errors.join("; ")));
+        }
+        Ok(validation)
+    }
+}
+
+/// Estimated weight of the signed transaction
+fn transaction_weight(spent_outputs: &[TxOut], outputs: &[TxOut]) -> BitcoinResult<u64> {
+    let inputs: Vec<ScriptType> = spent_outputs
+        .iter()
+        .map(|output| ScriptType::from_script_pubkey(&output.script_pubkey))
+        .collect();
+    let weight = estimate_weight(&inputs, &[]).ok_or_else(|| {
+        BitcoinError::InvalidInput("Cannot estimate the size of non-standard inputs".to_string())
+    })?;
+    Ok(weight + outputs.iter().map(|output| output.weight().to_wu()).sum::<u64>())
+}
+
+fn utxo_input(utxo: &Utxo) -> BitcoinResult<(TxIn, TxOut)> {
+    let txid = Txid::from_str(&utxo.txid)
+        .map_err(|e| BitcoinError::InvalidInput(format!("Invalid txid {}: {}", utxo.txid, e)))?;
+    let script_pubkey = ScriptBuf::from_hex(&utxo.script_pubkey)
+        .map_err(|e| BitcoinError::InvalidInput(format!("Invalid script of {}: {}", utxo.txid, e)))?;
+
+    let input = TxIn {
+        previous_output: OutPoint::new(txid, utxo.vout),
+        script_sig: ScriptBuf::new(),
+        sequence: RBF_SEQUENCE,
+        witness: Witness::new(),
+    };
+    let spent = TxOut {
+        value: BitcoinAmount::from_sat(utxo.amount.to_sat()),
+        script_pubkey,
+    };
+    Ok((input, spent))
+}
+
+fn total_value(outputs: &[TxOut]) -> u64 {
+    outputs.iter().map(|output| output.value.to_sat()).sum()
+}
+
+fn fee_for_vsize(fee_rate: f64, vsize: u64) -> u64 {
+    (fee_rate * vsize as f64).ceil() as u64
+}
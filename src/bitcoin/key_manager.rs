@@ -3,12 +3,14 @@
 //! This module provides secure key management capabilities including
 //! BIP32 HD wallets, BIP39 mnemonic support, and secure key storage.
 
-use super::{BitcoinError, BitcoinResult, Network};
+use super::{
+    descriptor::{Descriptor, DescriptorKey, DescriptorKeyKind, KeyOrigin},
+    BitcoinError, BitcoinResult, Network};
 use anyhow::{Context, Result};
 use base64::prelude::*;
 use bip39::{Language, Mnemonic};
 use bitcoin::{
-    bip32::{DerivationPath, Xpriv, Xpub, Fingerprint},
+    bip32::{ChildNumber, DerivationPath, Xpriv, Xpub, Fingerprint},
     key::{PrivateKey, PublicKey},
     secp256k1::{All, Secp256k1, SecretKey},
     Address,
@@ -34,6 +36,9 @@ pub struct HDWallet {
     pub extended_public_key: String,
     /// Derivation paths for different purposes
     pub derivation_paths: HashMap<String, String>,
+    /// Output descriptors (with checksum) for each account, keyed like the derivation paths
+    #[serde(default)]
+    pub descriptors: HashMap<String, String>,
     /// Created timestamp
     pub created_at: i64,
     /// Last used timestamp
@@ -190,9 +195,12 @@ impl KeyManager {
         derivation_paths.insert("receiving".to_string(), "m/44'/0'/0'/0".to_string());
         derivation_paths.insert("change".to_string(), "m/44'/0'/0'/1".to_string());
         derivation_paths.insert("legacy".to_string(), "m/44'/0'/0'".to_string());
+        derivation_paths.insert("nested_segwit".to_string(), "m/49'/0'/0'".to_string());
         derivation_paths.insert("segwit".to_string(), "m/84'/0'/0'".to_string());
         derivation_paths.insert("taproot".to_string(), "m/86'/0'/0'".to_string());
 
+        let descriptors = self.account_descriptors(&master_key, &derivation_paths)?;
+
         let wallet = HDWallet {
             id: wallet_id.clone(),
             name,
@@ -200,6 +208,7 @@ impl KeyManager {
             master_fingerprint: master_key.fingerprint(&self.secp).to_string(),
             extended_public_key: master_pubkey.to_string(),
             derivation_paths,
+            descriptors,
             created_at: chrono::Utc::now().timestamp(),
             last_used_at: None,
         };
@@ -240,19 +249,19 @@ impl KeyManager {
         // Determine address type and generate address
         let (address, address_type) = if derivation_path.contains("84'") {
             // SegWit v0 (P2WPKH) - simplified implementation
-            let address = format!("bc1q{}", hex::encode(&[0u8; 20])); // Placeholder
+            let address = format!("bc1q{}", hex::encode([0u8; 20])); // Placeholder
             (address, super::AddressType::Bech32)
         } else if derivation_path.contains("86'") {
             // Taproot (P2TR) - simplified implementation
-            let address = format!("bc1p{}", hex::encode(&[0u8; 32])); // Placeholder
+            let address = format!("bc1p{}", hex::encode([0u8; 32])); // Placeholder
             (address, super::AddressType::Taproot)
         } else if derivation_path.contains("49'") {
             // P2SH-SegWit - simplified implementation
-            let address = format!("3{}", hex::encode(&[0u8; 20])); // Placeholder
+            let address = format!("3{}", hex::encode([0u8; 20])); // Placeholder
             (address, super::AddressType::P2shSegwit)
         } else {
             // Legacy (P2PKH) - simplified implementation
-            let address = format!("1{}", hex::encode(&[0u8; 20])); // Placeholder
+            let address = format!("1{}", hex::encode([0u8; 20])); // Placeholder
             (address, super::AddressType::Legacy)
         };
 
@@ -268,6 +277,84 @@ impl KeyManager {
         })
     }
 
+    /// Build receive and change descriptors for the single-key accounts
+    fn account_descriptors(
+        &self,
+        master_key: &Xpriv,
+        derivation_paths: &HashMap<String, String>,
+    ) -> BitcoinResult<HashMap<String, String>> {
+        let fingerprint = master_key.fingerprint(&self.secp);
+        let mut descriptors = HashMap::new();
+
+        type Wrap = fn(DescriptorKey) -> Descriptor;
+        let accounts: [(&str, Wrap); 3] = [
+            ("segwit", Descriptor::Wpkh),
+            ("nested_segwit", Descriptor::ShWpkh),
+            ("taproot", Descriptor::Tr),
+        ];
+        for (purpose, wrap) in accounts {
+            let Some(account_path) = derivation_paths.get(purpose) else {
+                continue;
+            };
+            let path = DerivationPath::from_str(account_path)
+                .map_err(|e| BitcoinError::KeyManagement(format!("Invalid derivation path: {}", e)))?;
+            let account_key = master_key.derive_priv(&self.secp, &path)
+                .map_err(|e| BitcoinError::KeyManagement(format!("Key derivation failed: {}", e)))?;
+
+            for (suffix, chain) in [("", 0), ("_change", 1)] {
+                let key = DescriptorKey {
+                    origin: Some(KeyOrigin { fingerprint, path: path.clone() }),
+                    kind: DescriptorKeyKind::Extended {
+                        xpub: Xpub::from_priv(&self.secp, &account_key),
+                        path: DerivationPath::from(vec![ChildNumber::Normal { index: chain }]),
+                        wildcard: true,
+                    },
+                };
+                descriptors.insert(format!("{}{}", purpose, suffix), wrap(key).to_string());
+            }
+        }
+
+        Ok(descriptors)
+    }
+
+    /// Derive the address at `index` from an output descriptor
+    ///
+    /// Works for watch-only descriptors; no private key is needed. For multisig
+    /// descriptors the public keys are reported comma separated in key order.
+    pub fn derive_descriptor_address(&self, descriptor: &str, index: u32) -> BitcoinResult<KeyDerivation> {
+        let descriptor = Descriptor::parse(descriptor)?;
+        let address = descriptor.address(&self.secp, index, self.network)?;
+        let public_keys: Vec<String> = descriptor
+            .derive_keys(&self.secp, index)?
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let path = descriptor
+            .keys()
+            .first()
+            .and_then(|key| key.full_path(index))
+            .map(|path| path.to_string())
+            .unwrap_or_default();
+
+        debug!("Derived descriptor address {} at index {}", address, index);
+
+        Ok(KeyDerivation {
+            path,
+            purpose: "descriptor".to_string(),
+            index,
+            public_key: public_keys.join(","),
+            address: address.to_string(),
+            address_type: descriptor.address_type(),
+        })
+    }
+
+    /// Derive `count` consecutive addresses from an output descriptor starting at `start`
+    pub fn derive_descriptor_addresses(&self, descriptor: &str, start: u32, count: u32) -> BitcoinResult<Vec<KeyDerivation>> {
+        (start..start.saturating_add(count))
+            .map(|index| self.derive_descriptor_address(descriptor, index))
+            .collect()
+    }
+
     /// Get private key for specific derivation
     pub fn get_private_key(&self, wallet_id: &str, derivation_path: &str, index: u32) -> BitcoinResult<String> {
         let master_key_str = self.private_keys.get(wallet_id)
@@ -348,8 +435,7 @@ impl KeyManager {
     /// Restore wallet from backup
     pub fn restore_wallet(&mut self, backup_data: &str, password: &str) -> BitcoinResult<String> {
         // Placeholder decryption (in real implementation, use proper decryption)
-        let backup_data = if backup_data.starts_with("ENCRYPTED:") {
-            let encoded = &backup_data[10..];
+        let backup_data = if let Some(encoded) = backup_data.strip_prefix("ENCRYPTED:") {
             String::from_utf8(base64::prelude::BASE64_STANDARD.decode(encoded)
                 .map_err(|e| BitcoinError::KeyManagement(format!("Backup decoding failed: {}", e)))?)
                 .map_err(|e| BitcoinError::KeyManagement(format!("Backup UTF-8 conversion failed: {}", e)))?
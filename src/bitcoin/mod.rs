@@ -3,7 +3,10 @@
 //! This module provides comprehensive Bitcoin Core RPC integration,
 //! including wallet management, transaction handling, and PSBT support.
 
+pub mod coin_selection;
 pub mod core;
+pub mod descriptor;
+pub mod fee_bump;
 pub mod hardware_signer;
 pub mod key_manager;
 pub mod psbt;
@@ -15,7 +18,10 @@ pub mod transaction;
 pub mod transaction_signer;
 pub mod wallet;
 
+pub use coin_selection::{CoinSelection, CoinSelectionConfig, CoinSelector, SelectionAlgorithm, SelectionStrategy};
 pub use core::BitcoinCore;
+pub use descriptor::{descriptor_checksum, Descriptor, DescriptorKey, MultiSig};
+pub use fee_bump::{FeeBump, FeeBumpConfig, FeeBumpMethod, FeeBumper};
 pub use hardware_signer::{HardwareWalletManager, HardwareDevice, HardwareSigningRequest, HardwareSigningResponse};
 pub use key_manager::{KeyManager, HDWallet, KeyDerivation, MnemonicInfo};
 pub use psbt::{PsbtBuilder, PsbtSigner, AdvancedPsbtBuilder, PsbtWorkflowManager, MultiSigPsbtManager};
@@ -217,6 +223,9 @@ pub struct Utxo {
     pub spendable: bool,
     /// Whether this UTXO is safe to spend
     pub safe: bool,
+    /// Wallet label of the receiving address
+    #[serde(default)]
+    pub label: Option<String>,
 }
 
 /// Bitcoin transaction input
@@ -1,12 +1,30 @@
 //! PSBT (Partially Signed Bitcoin Transaction) implementation
 //!
 //! This module provides both legacy and advanced PSBT implementations.
-//! The advanced implementation uses the bitcoin library for proper BIP 174 compliance.
+//! The simple builder keeps a string-based view of a PSBT and serializes it
+//! to the BIP 174 (v0) or BIP 370 (v2) binary format through the bitcoin library.
+//! The advanced implementation wraps `bitcoin::Psbt` directly.
 
 use super::{Amount, BitcoinError, BitcoinResult, Utxo};
 use anyhow::{Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use bitcoin::{
+    absolute::{LockTime, LOCK_TIME_THRESHOLD},
+    bip32::{DerivationPath, Fingerprint, KeySource},
+    blockdata::opcodes::all::{OP_PUSHBYTES_0, OP_PUSHNUM_1, OP_PUSHNUM_16},
+    consensus::encode,
+    hashes::Hash,
+    key::XOnlyPublicKey,
+    psbt::{raw, Input, Psbt, PsbtSighashType},
+    script::{Builder, Instruction, PushBytesBuf},
+    taproot::TapLeafHash,
+    transaction::Version,
+    Address, Amount as BitcoinAmount, OutPoint, Script, ScriptBuf, Sequence, Transaction, TxIn,
+    TxOut, Txid, Witness,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
 use tracing::{debug, error, info, warn};
 
 // Re-export advanced PSBT implementation
@@ -16,15 +34,22 @@ pub use super::psbt_advanced::{
     PsbtInputInfo, PsbtOutputInfo, PsbtValidationResult, PsbtStats,
 };
 
+/// Default input sequence (RBF disabled, lock time enabled)
+const DEFAULT_SEQUENCE: u32 = 0xfffffffe;
+
 /// PSBT (Partially Signed Bitcoin Transaction) builder
 #[derive(Clone)]
 pub struct PsbtBuilder {
     inputs: Vec<PsbtInput>,
     outputs: Vec<PsbtOutput>,
     global_data: HashMap<String, String>,
+    version: i32,
+    lock_time: u32,
 }
 
 /// PSBT input
+///
+/// Scripts, transactions, keys and signatures are hex encoded.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PsbtInput {
     /// Previous transaction ID
@@ -33,18 +58,34 @@ pub struct PsbtInput {
     pub prev_vout: u32,
     /// Previous output amount
     pub prev_amount: Amount,
-    /// Script public key of previous output
+    /// Script public key of previous output (empty if unknown)
     pub script_pubkey: String,
+    /// Full previous transaction (required for non-SegWit inputs)
+    #[serde(default)]
+    pub non_witness_utxo: Option<String>,
+    /// Sequence number
+    #[serde(default = "default_sequence")]
+    pub sequence: u32,
     /// Redeem script (for P2SH)
     pub redeem_script: Option<String>,
     /// Witness script (for P2WSH)
     pub witness_script: Option<String>,
-    /// BIP32 derivation paths
+    /// BIP32 derivation paths (pubkey -> "fingerprint/path", e.g. "d90c6a4f/84'/0'/0'/0/0")
     pub bip32_derivation: HashMap<String, String>,
-    /// Partial signatures
+    /// Partial signatures (pubkey -> DER signature with sighash byte)
     pub partial_sigs: HashMap<String, String>,
     /// Signature hash type
     pub sighash_type: Option<u32>,
+    /// Finalized scriptSig
+    #[serde(default)]
+    pub final_script_sig: Option<String>,
+    /// Finalized witness stack
+    #[serde(default)]
+    pub final_script_witness: Option<Vec<String>>,
+    /// Pairs without a field above, e.g. taproot or proprietary
+    /// (key type and key data -> value)
+    #[serde(default)]
+    pub unknown: HashMap<String, String>,
 }
 
 /// PSBT output
@@ -60,6 +101,14 @@ pub struct PsbtOutput {
     pub redeem_script: Option<String>,
     /// Witness script (for P2WSH)
     pub witness_script: Option<String>,
+    /// Pairs without a field above, e.g. taproot or proprietary
+    /// (key type and key data -> value)
+    #[serde(default)]
+    pub unknown: HashMap<String, String>,
+}
+
+fn default_sequence() -> u32 {
+    DEFAULT_SEQUENCE
 }
 
 impl PsbtBuilder {
@@ -69,6 +118,8 @@ impl PsbtBuilder {
             inputs: Vec::new(),
             outputs: Vec::new(),
             global_data: HashMap::new(),
+            version: 2,
+            lock_time: 0,
         }
     }
 
@@ -84,12 +135,24 @@ impl PsbtBuilder {
         self
     }
 
-    /// Set global data
+    /// Set global data (hex key including the type byte -> hex value)
     pub fn set_global_data(&mut self, key: String, value: String) -> &mut Self {
         self.global_data.insert(key, value);
         self
     }
 
+    /// Set transaction version
+    pub fn set_version(&mut self, version: i32) -> &mut Self {
+        self.version = version;
+        self
+    }
+
+    /// Set lock time
+    pub fn set_lock_time(&mut self, lock_time: u32) -> &mut Self {
+        self.lock_time = lock_time;
+        self
+    }
+
     /// Build PSBT from UTXOs and outputs
     pub fn from_utxos_and_outputs(
         utxos: Vec<Utxo>,
@@ -104,23 +167,34 @@ impl PsbtBuilder {
                 prev_vout: utxo.vout,
                 prev_amount: utxo.amount,
                 script_pubkey: utxo.script_pubkey,
+                non_witness_utxo: None,
+                sequence: DEFAULT_SEQUENCE,
                 redeem_script: None,
                 witness_script: None,
                 bip32_derivation: HashMap::new(),
                 partial_sigs: HashMap::new(),
                 sighash_type: Some(1), // SIGHASH_ALL
+                final_script_sig: None,
+                final_script_witness: None,
+                unknown: HashMap::new(),
             };
             builder.add_input(input);
         }
 
         // Add outputs
         for (address, amount) in outputs {
+            let script_pubkey = Address::from_str(&address)
+                .map_err(|e| BitcoinError::InvalidAddress(format!("{}: {}", address, e)))?
+                .assume_checked()
+                .script_pubkey();
+
             let output = PsbtOutput {
                 amount,
-                script_pubkey: format!("OP_DUP OP_HASH160 {} OP_EQUALVERIFY OP_CHECKSIG", address), // Simplified
+                script_pubkey: hex::encode(script_pubkey.as_bytes()),
                 bip32_derivation: HashMap::new(),
                 redeem_script: None,
                 witness_script: None,
+                unknown: HashMap::new(),
             };
             builder.add_output(output);
         }
@@ -128,47 +202,166 @@ impl PsbtBuilder {
         Ok(builder)
     }
 
-    /// Build PSBT string (base64 encoded)
-    pub fn build(&self) -> BitcoinResult<String> {
-        // This is a simplified implementation
-        // In a real implementation, you would:
-        // 1. Serialize according to BIP 174 format
-        // 2. Base64 encode the result
-        
-        let psbt_data = PsbtData {
-            inputs: self.inputs.clone(),
-            outputs: self.outputs.clone(),
-            global_data: self.global_data.clone(),
+    /// Convert to a BIP 174 PSBT
+    pub fn to_psbt(&self) -> BitcoinResult<Psbt> {
+        let mut tx = Transaction {
+            version: Version(self.version),
+            lock_time: LockTime::from_consensus(self.lock_time),
+            input: Vec::with_capacity(self.inputs.len()),
+            output: Vec::with_capacity(self.outputs.len()),
         };
 
-        let json_str = serde_json::to_string(&psbt_data)
-            .map_err(|e| BitcoinError::Serialization(e))?;
+        for (i, input) in self.inputs.iter().enumerate() {
+            let txid = Txid::from_str(&input.prev_txid)
+                .map_err(|e| invalid(format!("Input {}: invalid txid: {}", i, e)))?;
+            tx.input.push(TxIn {
+                previous_output: OutPoint::new(txid, input.prev_vout),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence(input.sequence),
+                witness: Witness::new(),
+            });
+        }
 
-        // In real implementation, this would be proper PSBT binary format
-        let base64_psbt = base64::encode(json_str.as_bytes());
-        
-        debug!("Built PSBT with {} inputs and {} outputs", self.inputs.len(), self.outputs.len());
-        Ok(base64_psbt)
+        for (i, output) in self.outputs.iter().enumerate() {
+            tx.output.push(TxOut {
+                value: BitcoinAmount::from_sat(output.amount.to_sat()),
+                script_pubkey: parse_script(&output.script_pubkey)
+                    .map_err(|e| invalid(format!("Output {}: {}", i, e)))?,
+            });
+        }
+
+        let mut psbt = Psbt::from_unsigned_tx(tx)
+            .map_err(|e| invalid(format!("Invalid unsigned transaction: {}", e)))?;
+
+        psbt.unknown =
+            parse_unknown(&self.global_data).map_err(|e| invalid(format!("Global data: {}", e)))?;
+
+        for (i, input) in self.inputs.iter().enumerate() {
+            psbt.inputs[i] = input
+                .to_psbt_input()
+                .map_err(|e| invalid(format!("Input {}: {}", i, e)))?;
+        }
+
+        for (i, output) in self.outputs.iter().enumerate() {
+            let psbt_output = &mut psbt.outputs[i];
+            let parse = |script: &Option<String>| script.as_deref().map(parse_script).transpose();
+            psbt_output.redeem_script = parse(&output.redeem_script)
+                .map_err(|e| invalid(format!("Output {}: redeem script: {}", i, e)))?;
+            psbt_output.witness_script = parse(&output.witness_script)
+                .map_err(|e| invalid(format!("Output {}: witness script: {}", i, e)))?;
+            psbt_output.unknown = parse_unknown(&output.unknown)
+                .map_err(|e| invalid(format!("Output {}: {}", i, e)))?;
+            for (pubkey, source) in &output.bip32_derivation {
+                let (pubkey, source) = parse_derivation(pubkey, source)
+                    .map_err(|e| invalid(format!("Output {}: {}", i, e)))?;
+                psbt_output.bip32_derivation.insert(pubkey, source);
+            }
+        }
+
+        // Parse again so that pairs kept in `unknown` (taproot, xpubs,
+        // proprietary) become typed fields
+        Psbt::deserialize(&psbt.serialize())
+            .map_err(|e| invalid(format!("Failed to parse PSBT: {}", e)))
     }
 
-    /// Parse PSBT from base64 string
-    pub fn parse(psbt_base64: &str) -> BitcoinResult<Self> {
-        let psbt_bytes = base64::decode(psbt_base64)
-            .map_err(|e| BitcoinError::InvalidPsbt(format!("Invalid base64: {}", e)))?;
+    /// Create builder from a BIP 174 PSBT
+    ///
+    /// Pairs without a builder field (taproot, xpubs, proprietary) are kept
+    /// in the `unknown` maps, so converting back loses nothing.
+    pub fn from_psbt(psbt: &Psbt) -> BitcoinResult<Self> {
+        let tx = &psbt.unsigned_tx;
+        let raw = RawPsbt::parse(
+            &psbt.serialize(),
+            Some((psbt.inputs.len(), psbt.outputs.len())),
+        )?;
 
-        let psbt_str = String::from_utf8(psbt_bytes)
-            .map_err(|e| BitcoinError::InvalidPsbt(format!("Invalid UTF-8: {}", e)))?;
+        let inputs = tx
+            .input
+            .iter()
+            .zip(&psbt.inputs)
+            .zip(&raw.inputs)
+            .map(|((tx_input, psbt_input), raw_input)| {
+                PsbtInput::from_psbt_input(tx_input, psbt_input, raw_input)
+            })
+            .collect();
 
-        let psbt_data: PsbtData = serde_json::from_str(&psbt_str)
-            .map_err(|e| BitcoinError::InvalidPsbt(format!("Invalid JSON: {}", e)))?;
+        let outputs = tx
+            .output
+            .iter()
+            .zip(&psbt.outputs)
+            .zip(&raw.outputs)
+            .map(|((tx_output, psbt_output), raw_output)| PsbtOutput {
+                amount: Amount::from_sat(tx_output.value.to_sat()),
+                script_pubkey: hex::encode(tx_output.script_pubkey.as_bytes()),
+                bip32_derivation: format_derivations(&psbt_output.bip32_derivation),
+                redeem_script: psbt_output.redeem_script.as_ref().map(|s| hex::encode(s.as_bytes())),
+                witness_script: psbt_output.witness_script.as_ref().map(|s| hex::encode(s.as_bytes())),
+                unknown: unrepresented(raw_output, OUTPUT_FIELDS),
+            })
+            .collect();
 
         Ok(Self {
-            inputs: psbt_data.inputs,
-            outputs: psbt_data.outputs,
-            global_data: psbt_data.global_data,
+            inputs,
+            outputs,
+            global_data: unrepresented(&raw.global, GLOBAL_FIELDS),
+            version: tx.version.0,
+            lock_time: tx.lock_time.to_consensus_u32(),
         })
     }
 
+    /// Serialize PSBT in BIP 174 (v0) binary format
+    pub fn serialize(&self) -> BitcoinResult<Vec<u8>> {
+        Ok(self.to_psbt()?.serialize())
+    }
+
+    /// Serialize PSBT in BIP 370 (v2) binary format
+    pub fn serialize_v2(&self) -> BitcoinResult<Vec<u8>> {
+        let psbt = self.to_psbt()?;
+        let raw = RawPsbt::parse(
+            &psbt.serialize(),
+            Some((psbt.inputs.len(), psbt.outputs.len())),
+        )?;
+        Ok(raw.into_v2(&psbt.unsigned_tx).serialize())
+    }
+
+    /// Deserialize PSBT from BIP 174 (v0) or BIP 370 (v2) binary format
+    pub fn deserialize(bytes: &[u8]) -> BitcoinResult<Self> {
+        let raw = RawPsbt::parse(bytes, None)?;
+        let psbt = match raw.version()? {
+            0 => Psbt::deserialize(bytes),
+            2 => Psbt::deserialize(&raw.into_v0()?.serialize()),
+            version => {
+                return Err(invalid(format!("Unsupported PSBT version {}", version)));
+            }
+        }
+        .map_err(|e| invalid(format!("Failed to parse PSBT: {}", e)))?;
+
+        Self::from_psbt(&psbt)
+    }
+
+    /// Build PSBT string (base64 encoded BIP 174)
+    pub fn build(&self) -> BitcoinResult<String> {
+        let psbt = general_purpose::STANDARD.encode(self.serialize()?);
+        debug!("Built PSBT with {} inputs and {} outputs", self.inputs.len(), self.outputs.len());
+        Ok(psbt)
+    }
+
+    /// Build PSBT v2 string (base64 encoded BIP 370)
+    pub fn build_v2(&self) -> BitcoinResult<String> {
+        let psbt = general_purpose::STANDARD.encode(self.serialize_v2()?);
+        debug!("Built PSBT v2 with {} inputs and {} outputs", self.inputs.len(), self.outputs.len());
+        Ok(psbt)
+    }
+
+    /// Parse PSBT (v0 or v2) from base64 string
+    pub fn parse(psbt_base64: &str) -> BitcoinResult<Self> {
+        let psbt_bytes = general_purpose::STANDARD
+            .decode(psbt_base64.trim())
+            .map_err(|e| BitcoinError::InvalidPsbt(format!("Invalid base64: {}", e)))?;
+
+        Self::deserialize(&psbt_bytes)
+    }
+
     /// Get total input amount
     pub fn total_input_amount(&self) -> Amount {
         let total_sat: u64 = self.inputs.iter().map(|i| i.prev_amount.to_sat()).sum();
@@ -188,9 +381,14 @@ impl PsbtBuilder {
         Amount::from_sat(input_total.to_sat().saturating_sub(output_total.to_sat()))
     }
 
-    /// Check if PSBT is complete (all inputs signed)
+    /// Check if PSBT is complete (all inputs signed or finalized)
     pub fn is_complete(&self) -> bool {
-        !self.inputs.is_empty() && self.inputs.iter().all(|input| !input.partial_sigs.is_empty())
+        !self.inputs.is_empty()
+            && self.inputs.iter().all(|input| {
+                !input.partial_sigs.is_empty()
+                    || input.final_script_sig.is_some()
+                    || input.final_script_witness.is_some()
+            })
     }
 
     /// Get inputs
@@ -202,6 +400,134 @@ impl PsbtBuilder {
     pub fn outputs(&self) -> &[PsbtOutput] {
         &self.outputs
     }
+
+    /// Get global data
+    pub fn global_data(&self) -> &HashMap<String, String> {
+        &self.global_data
+    }
+
+    /// Get transaction version
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    /// Get lock time
+    pub fn lock_time(&self) -> u32 {
+        self.lock_time
+    }
+}
+
+impl PsbtInput {
+    fn to_psbt_input(&self) -> Result<Input> {
+        let mut input = Input::default();
+
+        let script_pubkey = parse_script(&self.script_pubkey).context("script_pubkey")?;
+        input.redeem_script = self
+            .redeem_script
+            .as_deref()
+            .map(parse_script)
+            .transpose()
+            .context("redeem script")?;
+        input.witness_script = self
+            .witness_script
+            .as_deref()
+            .map(parse_script)
+            .transpose()
+            .context("witness script")?;
+
+        if let Some(tx_hex) = &self.non_witness_utxo {
+            let tx: Transaction = encode::deserialize(&decode_hex(tx_hex)?)
+                .context("invalid non-witness UTXO")?;
+            if tx.txid().to_string() != self.prev_txid {
+                anyhow::bail!("non-witness UTXO {} does not match {}", tx.txid(), self.prev_txid);
+            }
+            if tx.output.len() <= self.prev_vout as usize {
+                anyhow::bail!("non-witness UTXO has no output {}", self.prev_vout);
+            }
+            input.non_witness_utxo = Some(tx);
+        }
+
+        // SegWit inputs carry the spent output; others only when the full
+        // previous transaction is unknown
+        let is_segwit = script_pubkey.is_witness_program()
+            || input.witness_script.is_some()
+            || input
+                .redeem_script
+                .as_ref()
+                .is_some_and(|script| script.is_witness_program());
+        if !script_pubkey.is_empty() && (input.non_witness_utxo.is_none() || is_segwit) {
+            input.witness_utxo = Some(TxOut {
+                value: BitcoinAmount::from_sat(self.prev_amount.to_sat()),
+                script_pubkey,
+            });
+        }
+
+        for (pubkey, signature) in &self.partial_sigs {
+            let pubkey = bitcoin::PublicKey::from_str(pubkey).context("invalid public key")?;
+            let signature = bitcoin::ecdsa::Signature::from_slice(&decode_hex(signature)?)
+                .context("invalid signature")?;
+            input.partial_sigs.insert(pubkey, signature);
+        }
+
+        input.sighash_type = self.sighash_type.map(PsbtSighashType::from_u32);
+
+        for (pubkey, source) in &self.bip32_derivation {
+            let (pubkey, source) = parse_derivation(pubkey, source)?;
+            input.bip32_derivation.insert(pubkey, source);
+        }
+
+        input.final_script_sig = self
+            .final_script_sig
+            .as_deref()
+            .map(parse_script)
+            .transpose()
+            .context("final scriptSig")?;
+        input.final_script_witness = self
+            .final_script_witness
+            .as_ref()
+            .map(|items| {
+                items
+                    .iter()
+                    .map(|item| decode_hex(item))
+                    .collect::<Result<Vec<_>>>()
+                    .map(|items| Witness::from_slice(&items))
+            })
+            .transpose()
+            .context("final witness")?;
+
+        input.unknown = parse_unknown(&self.unknown)?;
+
+        Ok(input)
+    }
+
+    fn from_psbt_input(tx_input: &TxIn, input: &Input, raw: &RawMap) -> Self {
+        let outpoint = tx_input.previous_output;
+        let spent = spent_output(tx_input, input);
+
+        Self {
+            prev_txid: outpoint.txid.to_string(),
+            prev_vout: outpoint.vout,
+            prev_amount: Amount::from_sat(spent.map_or(0, |out| out.value.to_sat())),
+            script_pubkey: spent.map_or_else(String::new, |out| hex::encode(out.script_pubkey.as_bytes())),
+            non_witness_utxo: input.non_witness_utxo.as_ref().map(encode::serialize_hex),
+            sequence: tx_input.sequence.0,
+            redeem_script: input.redeem_script.as_ref().map(|s| hex::encode(s.as_bytes())),
+            witness_script: input.witness_script.as_ref().map(|s| hex::encode(s.as_bytes())),
+            bip32_derivation: format_derivations(&input.bip32_derivation),
+            partial_sigs: input
+                .partial_sigs
+                .iter()
+                .map(|(pubkey, signature)| (pubkey.to_string(), hex::encode(signature.to_vec())))
+                .collect(),
+            sighash_type: input.sighash_type.map(|sighash| sighash.to_u32()),
+            final_script_sig: input.final_script_sig.as_ref().map(|s| hex::encode(s.as_bytes())),
+            final_script_witness: input
+                .final_script_witness
+                .as_ref()
+                .map(|witness| witness.iter().map(hex::encode).collect()),
+            unknown: unrepresented(raw, INPUT_FIELDS),
+        }
+    }
 }
 
 /// PSBT signer for signing transactions
@@ -223,7 +549,7 @@ impl PsbtSigner {
         // 1. Parse the private key
         // 2. For each input, create the signature
         // 3. Add the signature to partial_sigs
-        
+
         info!("PSBT signing not fully implemented - placeholder");
         Ok(())
     }
@@ -236,84 +562,690 @@ impl PsbtSigner {
         // 2. Send PSBT to device
         // 3. Get signatures back
         // 4. Update PSBT with signatures
-        
+
         info!("Hardware wallet signing not implemented - placeholder");
         Ok(())
     }
 
-    /// Combine multiple PSBTs
-    pub fn combine_psbts(&self, psbts: Vec<PsbtBuilder>) -> BitcoinResult<PsbtBuilder> {
-        if psbts.is_empty() {
-            return Err(BitcoinError::InvalidPsbt("No PSBTs to combine".to_string()));
+    /// Combine multiple PSBTs (BIP 174 combiner)
+    ///
+    /// All PSBTs must describe the same unsigned transaction.
+    pub fn combine(&self, psbts: Vec<Psbt>) -> BitcoinResult<Psbt> {
+        let mut psbts = psbts.into_iter();
+        let mut combined = psbts
+            .next()
+            .ok_or_else(|| BitcoinError::InvalidPsbt("No PSBTs to combine".to_string()))?;
+
+        for psbt in psbts {
+            combined
+                .combine(psbt)
+                .map_err(|e| invalid(format!("Failed to combine PSBT: {}", e)))?;
         }
 
-        let mut combined = psbts[0].clone();
-        
-        // In a real implementation, you would properly combine PSBTs according to BIP 174
-        for psbt in psbts.iter().skip(1) {
-            // Combine signatures and other data
-            for (i, input) in psbt.inputs.iter().enumerate() {
-                if let Some(combined_input) = combined.inputs.get_mut(i) {
-                    // Merge partial signatures
-                    for (pubkey, sig) in &input.partial_sigs {
-                        combined_input.partial_sigs.insert(pubkey.clone(), sig.clone());
-                    }
-                }
+        Ok(combined)
+    }
+
+    /// Combine multiple builder PSBTs, see [`PsbtSigner::combine`]
+    pub fn combine_psbts(&self, psbts: Vec<PsbtBuilder>) -> BitcoinResult<PsbtBuilder> {
+        let psbts = psbts
+            .iter()
+            .map(PsbtBuilder::to_psbt)
+            .collect::<BitcoinResult<Vec<_>>>()?;
+        PsbtBuilder::from_psbt(&self.combine(psbts)?)
+    }
+
+    /// Finalize PSBT inputs (BIP 174 finalizer)
+    ///
+    /// Builds the final scriptSig and witness from partial signatures for
+    /// P2PKH, P2WPKH, P2SH-P2WPKH and (P2SH-/P2WSH-wrapped) multisig inputs.
+    pub fn finalize(&self, psbt: &mut Psbt) -> BitcoinResult<()> {
+        for (i, input) in psbt.inputs.iter_mut().enumerate() {
+            if input.final_script_sig.is_some() || input.final_script_witness.is_some() {
+                continue;
             }
+
+            let spent = spent_output(&psbt.unsigned_tx.input[i], input)
+                .map(|out| out.script_pubkey.clone())
+                .ok_or_else(|| invalid(format!("Input {} is missing UTXO information", i)))?;
+            finalize_input(input, &spent)
+                .map_err(|e| BitcoinError::SigningError(format!("Input {}: {}", i, e)))?;
+            debug!("Finalized input {}", i);
         }
 
-        Ok(combined)
+        Ok(())
+    }
+
+    /// Finalize builder PSBT inputs, see [`PsbtSigner::finalize`]
+    pub fn finalize_inputs(&self, psbt: &mut PsbtBuilder) -> BitcoinResult<()> {
+        let mut finalized = psbt.to_psbt()?;
+        self.finalize(&mut finalized)?;
+        *psbt = PsbtBuilder::from_psbt(&finalized)?;
+        Ok(())
     }
 
-    /// Finalize PSBT (convert to final transaction)
+    /// Finalize PSBT (convert to final transaction hex)
     pub fn finalize_psbt(&self, psbt: &PsbtBuilder) -> BitcoinResult<String> {
         if !psbt.is_complete() {
             return Err(BitcoinError::InvalidPsbt("PSBT is not complete".to_string()));
         }
 
-        // This is a placeholder implementation
-        // In a real implementation, you would:
-        // 1. Create final transaction from PSBT
-        // 2. Add final scriptSig and witness data
-        // 3. Return raw transaction hex
-        
-        info!("PSBT finalization not fully implemented - placeholder");
-        Ok("placeholder_transaction_hex".to_string())
+        let mut finalized = psbt.to_psbt()?;
+        self.finalize(&mut finalized)?;
+
+        let tx = finalized
+            .extract_tx()
+            .map_err(|e| invalid(format!("Failed to extract transaction: {}", e)))?;
+
+        info!("Finalized PSBT into transaction {}", tx.txid());
+        Ok(encode::serialize_hex(&tx))
     }
 }
 
-/// Internal PSBT data structure for serialization
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct PsbtData {
-    inputs: Vec<PsbtInput>,
-    outputs: Vec<PsbtOutput>,
-    global_data: HashMap<String, String>,
+/// Builds final scripts from partial signatures and clears signing data
+fn finalize_input(input: &mut Input, spent: &Script) -> Result<()> {
+    let (script_sig, witness) = if spent.is_p2pkh() {
+        let (pubkey, signature) = single_signature(input)?;
+        let script_sig = Builder::new()
+            .push_slice(signature.serialize())
+            .push_key(&pubkey)
+            .into_script();
+        (script_sig, None)
+    } else if spent.is_p2wpkh() {
+        let (pubkey, signature) = single_signature(input)?;
+        (ScriptBuf::new(), Some(Witness::p2wpkh(&signature, &pubkey.inner)))
+    } else if spent.is_p2wsh() {
+        (ScriptBuf::new(), Some(multisig_witness(input)?))
+    } else if spent.is_p2sh() {
+        let redeem_script = input
+            .redeem_script
+            .clone()
+            .context("missing redeem script")?;
+        let push_redeem = Builder::new().push_slice(push_bytes(redeem_script.as_bytes())?);
+
+        if redeem_script.is_p2wpkh() {
+            let (pubkey, signature) = single_signature(input)?;
+            (
+                push_redeem.into_script(),
+                Some(Witness::p2wpkh(&signature, &pubkey.inner)),
+            )
+        } else if redeem_script.is_p2wsh() {
+            (push_redeem.into_script(), Some(multisig_witness(input)?))
+        } else {
+            let mut builder = Builder::new().push_opcode(OP_PUSHBYTES_0);
+            for signature in multisig_signatures(input, &redeem_script)? {
+                builder = builder.push_slice(push_bytes(&signature)?);
+            }
+            let script_sig = builder
+                .push_slice(push_bytes(redeem_script.as_bytes())?)
+                .into_script();
+            (script_sig, None)
+        }
+    } else if spent.is_p2tr() {
+        (ScriptBuf::new(), Some(taproot_witness(input)?))
+    } else {
+        anyhow::bail!("unsupported script type for finalization: {}", spent);
+    };
+
+    input.final_script_sig = (!script_sig.is_empty()).then_some(script_sig);
+    input.final_script_witness = witness;
+
+    // BIP 174: the finalizer removes data only needed for signing
+    input.partial_sigs.clear();
+    input.sighash_type = None;
+    input.redeem_script = None;
+    input.witness_script = None;
+    input.bip32_derivation.clear();
+
+    // BIP 371: taproot signing data is removed as well
+    input.tap_key_sig = None;
+    input.tap_script_sigs.clear();
+    input.tap_scripts.clear();
+    input.tap_key_origins.clear();
+    input.tap_internal_key = None;
+    input.tap_merkle_root = None;
+
+    Ok(())
 }
 
-impl Default for PsbtBuilder {
-    fn default() -> Self {
-        Self::new()
+/// Witness stack for a P2TR input
+///
+/// Prefers the key path; otherwise spends the cheapest script leaf whose
+/// keys have all signed.
+fn taproot_witness(input: &Input) -> Result<Witness> {
+    if let Some(signature) = input.tap_key_sig {
+        return Ok(Witness::from_slice(&[signature.to_vec()]));
     }
+
+    let mut best: Option<Witness> = None;
+    for (control_block, (script, version)) in &input.tap_scripts {
+        let leaf_hash = TapLeafHash::from_script(script, *version);
+        let keys: Vec<XOnlyPublicKey> = script
+            .instructions()
+            .filter_map(|instruction| match instruction {
+                Ok(Instruction::PushBytes(bytes)) => {
+                    XOnlyPublicKey::from_slice(bytes.as_bytes()).ok()
+                }
+                _ => None,
+            })
+            .collect();
+
+        // Signatures are consumed in script key order, so the last key's goes first
+        let signatures: Option<Vec<_>> = keys
+            .iter()
+            .rev()
+            .map(|key| input.tap_script_sigs.get(&(*key, leaf_hash)))
+            .collect();
+        let Some(signatures) = signatures.filter(|signatures| !signatures.is_empty()) else {
+            continue;
+        };
+
+        let mut witness = Witness::new();
+        for signature in signatures {
+            witness.push(signature.to_vec());
+        }
+        witness.push(script.as_bytes());
+        witness.push(control_block.serialize());
+
+        match &best {
+            Some(cheaper) if cheaper.size() <= witness.size() => {}
+            _ => best = Some(witness),
+        }
+    }
+
+    best.context("no complete taproot signature")
 }
 
-impl Default for PsbtSigner {
-    fn default() -> Self {
-        Self::new()
+/// The only partial signature of a single-key input
+fn single_signature(input: &Input) -> Result<(bitcoin::PublicKey, bitcoin::ecdsa::Signature)> {
+    let mut signatures = input.partial_sigs.iter();
+    match (signatures.next(), signatures.next()) {
+        (Some((pubkey, signature)), None) => Ok((*pubkey, *signature)),
+        (None, _) => anyhow::bail!("no signature"),
+        _ => anyhow::bail!("expected a single signature"),
     }
 }
 
-// Add base64 dependency placeholder (would be imported from external crate)
-mod base64 {
-    pub fn encode(input: &[u8]) -> String {
-        // Placeholder implementation
-        format!("base64_{}", hex::encode(input))
+/// Witness stack for a P2WSH multisig input
+fn multisig_witness(input: &Input) -> Result<Witness> {
+    let witness_script = input
+        .witness_script
+        .as_ref()
+        .context("missing witness script")?;
+
+    // Empty element consumed by the OP_CHECKMULTISIG off-by-one
+    let mut items = vec![Vec::new()];
+    items.extend(multisig_signatures(input, witness_script)?);
+    items.push(witness_script.to_bytes());
+    Ok(Witness::from_slice(&items))
+}
+
+/// Signatures of an m-of-n multisig script, in public key order
+fn multisig_signatures(input: &Input, script: &Script) -> Result<Vec<Vec<u8>>> {
+    let mut instructions = script.instructions();
+    let required = match instructions.next() {
+        Some(Ok(Instruction::Op(op)))
+            if (OP_PUSHNUM_1.to_u8()..=OP_PUSHNUM_16.to_u8()).contains(&op.to_u8()) =>
+        {
+            Some((op.to_u8() - OP_PUSHNUM_1.to_u8() + 1) as usize)
+        }
+        _ => None,
     }
+    .with_context(|| format!("unsupported script (expected multisig): {}", script))?;
 
-    pub fn decode(input: &str) -> Result<Vec<u8>, String> {
-        if let Some(hex_part) = input.strip_prefix("base64_") {
-            hex::decode(hex_part).map_err(|e| e.to_string())
-        } else {
-            Err("Invalid base64 format".to_string())
+    let mut signatures = Vec::with_capacity(required);
+    for instruction in instructions {
+        let Ok(Instruction::PushBytes(bytes)) = instruction else {
+            continue;
+        };
+        let Ok(pubkey) = bitcoin::PublicKey::from_slice(bytes.as_bytes()) else {
+            continue;
+        };
+        if let Some(signature) = input.partial_sigs.get(&pubkey) {
+            signatures.push(signature.to_vec());
+        }
+        if signatures.len() == required {
+            return Ok(signatures);
+        }
+    }
+
+    anyhow::bail!("{} of {} required signatures", signatures.len(), required)
+}
+
+/// Output spent by an input (from the witness or full previous transaction)
+fn spent_output<'a>(tx_input: &TxIn, input: &'a Input) -> Option<&'a TxOut> {
+    input.witness_utxo.as_ref().or_else(|| {
+        input
+            .non_witness_utxo
+            .as_ref()?
+            .output
+            .get(tx_input.previous_output.vout as usize)
+    })
+}
+
+fn invalid(message: String) -> BitcoinError {
+    BitcoinError::InvalidPsbt(message)
+}
+
+fn decode_hex(data: &str) -> Result<Vec<u8>> {
+    hex::decode(data).with_context(|| format!("invalid hex: {}", data))
+}
+
+fn parse_script(data: &str) -> Result<ScriptBuf> {
+    Ok(ScriptBuf::from_bytes(decode_hex(data)?))
+}
+
+fn push_bytes(data: &[u8]) -> Result<PushBytesBuf> {
+    PushBytesBuf::try_from(data.to_vec()).context("push data too large")
+}
+
+/// Parses a derivation entry ("fingerprint/path")
+fn parse_derivation(pubkey: &str, source: &str) -> Result<(bitcoin::secp256k1::PublicKey, KeySource)> {
+    let pubkey = bitcoin::secp256k1::PublicKey::from_str(pubkey)
+        .with_context(|| format!("invalid public key {}", pubkey))?;
+    let (fingerprint, path) = source.split_once('/').unwrap_or((source, ""));
+    let fingerprint = Fingerprint::from_str(fingerprint)
+        .with_context(|| format!("invalid fingerprint {}", fingerprint))?;
+    let path = DerivationPath::from_str(format!("m/{}", path).trim_end_matches('/'))
+        .with_context(|| format!("invalid derivation path {}", source))?;
+    Ok((pubkey, (fingerprint, path)))
+}
+
+fn format_derivations<'a>(
+    derivations: impl IntoIterator<Item = (&'a bitcoin::secp256k1::PublicKey, &'a KeySource)>,
+) -> HashMap<String, String> {
+    derivations
+        .into_iter()
+        .map(|(pubkey, (fingerprint, path))| {
+            let path = path.to_string();
+            let path = path.trim_start_matches('m');
+            (pubkey.to_string(), format!("{}{}", fingerprint, path))
+        })
+        .collect()
+}
+
+/// Parse hex encoded unknown pairs (key type and key data -> value)
+fn parse_unknown(pairs: &HashMap<String, String>) -> Result<BTreeMap<raw::Key, Vec<u8>>> {
+    pairs
+        .iter()
+        .map(|(key, value)| {
+            let key = decode_hex(key).context("unknown key")?;
+            let (type_value, key) = key
+                .split_first()
+                .ok_or_else(|| anyhow::anyhow!("unknown key is empty"))?;
+            let key = raw::Key {
+                type_value: *type_value,
+                key: key.to_vec(),
+            };
+            Ok((key, decode_hex(value).context("unknown value")?))
+        })
+        .collect()
+}
+
+/// Hex encoded pairs whose key type has no builder field
+fn unrepresented(map: &RawMap, represented: &[u8]) -> HashMap<String, String> {
+    map.iter()
+        .filter(|(key, _)| {
+            key.first()
+                .is_some_and(|key_type| !represented.contains(key_type))
+        })
+        .map(|(key, value)| (hex::encode(key), hex::encode(value)))
+        .collect()
+}
+
+/// PSBT magic bytes ("psbt" + 0xff)
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+
+// Key types rewritten when converting between PSBT v0 and v2 (BIP 370)
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_GLOBAL_TX_VERSION: u8 = 0x02;
+const PSBT_GLOBAL_FALLBACK_LOCKTIME: u8 = 0x03;
+const PSBT_GLOBAL_INPUT_COUNT: u8 = 0x04;
+const PSBT_GLOBAL_OUTPUT_COUNT: u8 = 0x05;
+const PSBT_GLOBAL_TX_MODIFIABLE: u8 = 0x06;
+const PSBT_GLOBAL_VERSION: u8 = 0xfb;
+const PSBT_IN_PREVIOUS_TXID: u8 = 0x0e;
+const PSBT_IN_OUTPUT_INDEX: u8 = 0x0f;
+const PSBT_IN_SEQUENCE: u8 = 0x10;
+const PSBT_IN_REQUIRED_TIME_LOCKTIME: u8 = 0x11;
+const PSBT_IN_REQUIRED_HEIGHT_LOCKTIME: u8 = 0x12;
+const PSBT_OUT_AMOUNT: u8 = 0x03;
+const PSBT_OUT_SCRIPT: u8 = 0x04;
+
+// Key types with a builder field; other pairs are kept as unknown
+const GLOBAL_FIELDS: &[u8] = &[PSBT_GLOBAL_UNSIGNED_TX, PSBT_GLOBAL_VERSION];
+const INPUT_FIELDS: &[u8] = &[0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+const OUTPUT_FIELDS: &[u8] = &[0x00, 0x01, 0x02];
+
+/// Key-value map of a serialized PSBT (keys include the type byte)
+type RawMap = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// PSBT split into raw maps, used for BIP 370 conversion
+struct RawPsbt {
+    global: RawMap,
+    inputs: Vec<RawMap>,
+    outputs: Vec<RawMap>,
+}
+
+impl RawPsbt {
+    /// Parses raw maps; map counts come from the v2 global map if not given
+    fn parse(bytes: &[u8], counts: Option<(usize, usize)>) -> BitcoinResult<Self> {
+        let data = bytes
+            .strip_prefix(PSBT_MAGIC)
+            .ok_or_else(|| invalid("Missing PSBT magic bytes".to_string()))?;
+        let mut reader = RawReader { data, position: 0 };
+
+        let global = reader.map()?;
+        let (input_count, output_count) = match counts {
+            Some(counts) => counts,
+            None if global_version(&global)? == 2 => (
+                global_count(&global, PSBT_GLOBAL_INPUT_COUNT)?,
+                global_count(&global, PSBT_GLOBAL_OUTPUT_COUNT)?,
+            ),
+            None => (0, 0),
+        };
+
+        let inputs = (0..input_count).map(|_| reader.map()).collect::<BitcoinResult<_>>()?;
+        let outputs = (0..output_count).map(|_| reader.map()).collect::<BitcoinResult<_>>()?;
+
+        Ok(Self { global, inputs, outputs })
+    }
+
+    /// PSBT_GLOBAL_VERSION (0 if absent)
+    fn version(&self) -> BitcoinResult<u32> {
+        global_version(&self.global)
+    }
+
+    /// Converts v0 maps to v2: the unsigned transaction moves into per-input
+    /// and per-output fields
+    fn into_v2(self, tx: &Transaction) -> Self {
+        let mut global: RawMap = self
+            .global
+            .into_iter()
+            .filter(|(key, _)| !matches!(key.as_slice(), [PSBT_GLOBAL_UNSIGNED_TX] | [PSBT_GLOBAL_VERSION]))
+            .collect();
+        global.extend([
+            (vec![PSBT_GLOBAL_TX_VERSION], tx.version.0.to_le_bytes().to_vec()),
+            (
+                vec![PSBT_GLOBAL_FALLBACK_LOCKTIME],
+                tx.lock_time.to_consensus_u32().to_le_bytes().to_vec(),
+            ),
+            (vec![PSBT_GLOBAL_INPUT_COUNT], encode::serialize(&encode::VarInt(tx.input.len() as u64))),
+            (vec![PSBT_GLOBAL_OUTPUT_COUNT], encode::serialize(&encode::VarInt(tx.output.len() as u64))),
+            (vec![PSBT_GLOBAL_VERSION], 2u32.to_le_bytes().to_vec()),
+        ]);
+
+        let inputs = self
+            .inputs
+            .into_iter()
+            .zip(&tx.input)
+            .map(|(mut map, tx_input)| {
+                map.extend([
+                    (
+                        vec![PSBT_IN_PREVIOUS_TXID],
+                        tx_input.previous_output.txid.to_byte_array().to_vec(),
+                    ),
+                    (vec![PSBT_IN_OUTPUT_INDEX], tx_input.previous_output.vout.to_le_bytes().to_vec()),
+                    (vec![PSBT_IN_SEQUENCE], tx_input.sequence.0.to_le_bytes().to_vec()),
+                ]);
+                map
+            })
+            .collect();
+
+        let outputs = self
+            .outputs
+            .into_iter()
+            .zip(&tx.output)
+            .map(|(mut map, tx_output)| {
+                map.extend([
+                    (vec![PSBT_OUT_AMOUNT], (tx_output.value.to_sat() as i64).to_le_bytes().to_vec()),
+                    (vec![PSBT_OUT_SCRIPT], tx_output.script_pubkey.to_bytes()),
+                ]);
+                map
+            })
+            .collect();
+
+        Self { global, inputs, outputs }
+    }
+
+    /// Converts v2 maps to v0 by rebuilding the unsigned transaction
+    fn into_v0(self) -> BitcoinResult<Self> {
+        let mut tx_version = None;
+        let mut fallback_lock_time = None;
+        let mut global = RawMap::new();
+        for (key, value) in self.global {
+            match key.as_slice() {
+                [PSBT_GLOBAL_TX_VERSION] => tx_version = Some(read_u32(&value, "transaction version")? as i32),
+                [PSBT_GLOBAL_FALLBACK_LOCKTIME] => {
+                    fallback_lock_time = Some(read_u32(&value, "fallback lock time")?)
+                }
+                [PSBT_GLOBAL_INPUT_COUNT]
+                | [PSBT_GLOBAL_OUTPUT_COUNT]
+                | [PSBT_GLOBAL_TX_MODIFIABLE]
+                | [PSBT_GLOBAL_VERSION] => {}
+                [PSBT_GLOBAL_UNSIGNED_TX] => {
+                    return Err(invalid("PSBT v2 must not contain an unsigned transaction".to_string()));
+                }
+                _ => global.push((key, value)),
+            }
         }
+        let tx_version = tx_version.ok_or_else(|| invalid("PSBT v2 is missing the transaction version".to_string()))?;
+
+        let mut tx_inputs = Vec::with_capacity(self.inputs.len());
+        let mut required_lock_times = Vec::with_capacity(self.inputs.len());
+        let mut inputs = Vec::with_capacity(self.inputs.len());
+        for (i, map) in self.inputs.into_iter().enumerate() {
+            let mut txid = None;
+            let mut vout = None;
+            let mut sequence = Sequence::MAX;
+            let mut lock_times = (None, None);
+            let mut rest = RawMap::new();
+            for (key, value) in map {
+                match key.as_slice() {
+                    [PSBT_IN_PREVIOUS_TXID] => {
+                        txid = Some(Txid::from_slice(&value).map_err(|e| invalid(format!("Input {}: invalid txid: {}", i, e)))?)
+                    }
+                    [PSBT_IN_OUTPUT_INDEX] => vout = Some(read_u32(&value, "output index")?),
+                    [PSBT_IN_SEQUENCE] => sequence = Sequence(read_u32(&value, "sequence")?),
+                    [PSBT_IN_REQUIRED_TIME_LOCKTIME] => {
+                        let time = read_u32(&value, "required time lock")?;
+                        if time < LOCK_TIME_THRESHOLD {
+                            return Err(invalid(format!(
+                                "Input {}: required time lock {} is below {}",
+                                i, time, LOCK_TIME_THRESHOLD
+                            )));
+                        }
+                        lock_times.0 = Some(time);
+                    }
+                    [PSBT_IN_REQUIRED_HEIGHT_LOCKTIME] => {
+                        let height = read_u32(&value, "required height lock")?;
+                        if height == 0 || height >= LOCK_TIME_THRESHOLD {
+                            return Err(invalid(format!(
+                                "Input {}: invalid required height lock {}",
+                                i, height
+                            )));
+                        }
+                        lock_times.1 = Some(height);
+                    }
+                    _ => rest.push((key, value)),
+                }
+            }
+
+            let (Some(txid), Some(vout)) = (txid, vout) else {
+                return Err(invalid(format!("Input {} is missing its previous output", i)));
+            };
+            tx_inputs.push(TxIn {
+                previous_output: OutPoint::new(txid, vout),
+                script_sig: ScriptBuf::new(),
+                sequence,
+                witness: Witness::new(),
+            });
+            required_lock_times.push(lock_times);
+            inputs.push(rest);
+        }
+
+        let mut tx_outputs = Vec::with_capacity(self.outputs.len());
+        let mut outputs = Vec::with_capacity(self.outputs.len());
+        for (i, map) in self.outputs.into_iter().enumerate() {
+            let mut amount = None;
+            let mut script = None;
+            let mut rest = RawMap::new();
+            for (key, value) in map {
+                match key.as_slice() {
+                    [PSBT_OUT_AMOUNT] => {
+                        let bytes = <[u8; 8]>::try_from(value.as_slice())
+                            .map_err(|_| invalid(format!("Output {}: invalid amount", i)))?;
+                        amount = u64::try_from(i64::from_le_bytes(bytes)).ok();
+                    }
+                    [PSBT_OUT_SCRIPT] => script = Some(ScriptBuf::from_bytes(value)),
+                    _ => rest.push((key, value)),
+                }
+            }
+
+            let (Some(amount), Some(script_pubkey)) = (amount, script) else {
+                return Err(invalid(format!("Output {} is missing its amount or script", i)));
+            };
+            tx_outputs.push(TxOut {
+                value: BitcoinAmount::from_sat(amount),
+                script_pubkey,
+            });
+            outputs.push(rest);
+        }
+
+        let lock_time = v2_lock_time(fallback_lock_time, &required_lock_times)?;
+
+        // Serialized without witness flag so that 0-input transactions parse
+        let mut unsigned_tx = encode::serialize(&Version(tx_version));
+        unsigned_tx.extend(encode::serialize(&tx_inputs));
+        unsigned_tx.extend(encode::serialize(&tx_outputs));
+        unsigned_tx.extend(encode::serialize(&LockTime::from_consensus(lock_time)));
+        global.push((vec![PSBT_GLOBAL_UNSIGNED_TX], unsigned_tx));
+
+        Ok(Self { global, inputs, outputs })
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut bytes = PSBT_MAGIC.to_vec();
+        for map in std::iter::once(&self.global).chain(&self.inputs).chain(&self.outputs) {
+            let mut pairs: Vec<_> = map.iter().collect();
+            pairs.sort();
+            for (key, value) in pairs {
+                bytes.extend(encode::serialize(&encode::VarInt(key.len() as u64)));
+                bytes.extend(key);
+                bytes.extend(encode::serialize(&encode::VarInt(value.len() as u64)));
+                bytes.extend(value);
+            }
+            bytes.push(0x00);
+        }
+        bytes
+    }
+}
+
+/// Required (time, height) lock times of a v2 PSBT input
+type RequiredLockTime = (Option<u32>, Option<u32>);
+
+/// Lock time of a v2 PSBT (BIP 370 lock time determination)
+///
+/// `required` holds lock times per input; height is chosen when every
+/// input with a requirement accepts it.
+fn v2_lock_time(fallback: Option<u32>, required: &[RequiredLockTime]) -> BitcoinResult<u32> {
+    if required.iter().all(|(time, height)| time.is_none() && height.is_none()) {
+        return Ok(fallback.unwrap_or(0));
+    }
+
+    let max = |pick: fn(&RequiredLockTime) -> Option<u32>| {
+        required.iter().filter_map(pick).max().unwrap_or(0)
+    };
+    if required.iter().all(|(time, height)| height.is_some() || time.is_none()) {
+        Ok(max(|lock| lock.1))
+    } else if required.iter().all(|(time, height)| time.is_some() || height.is_none()) {
+        Ok(max(|lock| lock.0))
+    } else {
+        Err(invalid("Inputs require conflicting lock time types".to_string()))
+    }
+}
+
+fn global_value(map: &RawMap, key_type: u8) -> Option<&[u8]> {
+    map.iter()
+        .find(|(key, _)| key.as_slice() == [key_type])
+        .map(|(_, value)| value.as_slice())
+}
+
+fn global_version(map: &RawMap) -> BitcoinResult<u32> {
+    global_value(map, PSBT_GLOBAL_VERSION).map_or(Ok(0), |value| read_u32(value, "global version"))
+}
+
+fn global_count(map: &RawMap, key_type: u8) -> BitcoinResult<usize> {
+    let value = global_value(map, key_type)
+        .ok_or_else(|| invalid(format!("PSBT v2 is missing global field 0x{:02x}", key_type)))?;
+    let mut reader = RawReader { data: value, position: 0 };
+    let count = reader.compact_size()?;
+    usize::try_from(count).map_err(|_| invalid(format!("Invalid count {}", count)))
+}
+
+fn read_u32(value: &[u8], field: &str) -> BitcoinResult<u32> {
+    <[u8; 4]>::try_from(value)
+        .map(u32::from_le_bytes)
+        .map_err(|_| invalid(format!("Invalid {} (expected 4 bytes)", field)))
+}
+
+/// Reader of PSBT key-value maps
+struct RawReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> RawReader<'a> {
+    fn bytes(&mut self, len: usize) -> BitcoinResult<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| invalid("Unexpected end of PSBT data".to_string()))?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn compact_size(&mut self) -> BitcoinResult<u64> {
+        let value = match self.bytes(1)?[0] {
+            0xfd => u16::from_le_bytes(self.bytes(2)?.try_into().unwrap_or_default()) as u64,
+            0xfe => u32::from_le_bytes(self.bytes(4)?.try_into().unwrap_or_default()) as u64,
+            0xff => u64::from_le_bytes(self.bytes(8)?.try_into().unwrap_or_default()),
+            byte => byte as u64,
+        };
+        Ok(value)
+    }
+
+    fn sized_bytes(&mut self) -> BitcoinResult<&'a [u8]> {
+        let len = self.compact_size()?;
+        self.bytes(usize::try_from(len).map_err(|_| invalid(format!("Invalid length {}", len)))?)
+    }
+
+    /// Reads key-value pairs up to the 0x00 separator
+    fn map(&mut self) -> BitcoinResult<RawMap> {
+        let mut map = RawMap::new();
+        loop {
+            let key = self.sized_bytes()?;
+            if key.is_empty() {
+                return Ok(map);
+            }
+            if map.iter().any(|(existing, _)| existing.as_slice() == key) {
+                return Err(invalid(format!("Duplicate PSBT key {}", hex::encode(key))));
+            }
+            let value = self.sized_bytes()?;
+            map.push((key.to_vec(), value.to_vec()));
+        }
+    }
+}
+
+impl Default for PsbtBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for PsbtSigner {
+    fn default() -> Self {
+        Self::new()
     }
 }
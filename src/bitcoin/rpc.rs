@@ -177,6 +177,16 @@ impl RpcClient {
         self.call("listunspent", params).await
     }
 
+    /// List amounts and transactions received by wallet addresses
+    pub async fn list_received_by_address(&self, min_conf: u32, include_empty: bool, address: Option<&str>) -> BitcoinResult<Vec<ReceivedByAddress>> {
+        let params = match address {
+            Some(address) => json!([min_conf, include_empty, true, address]),
+            None => json!([min_conf, include_empty, true]),
+        };
+
+        self.call("listreceivedbyaddress", params).await
+    }
+
     /// Send to address
     pub async fn send_to_address(&self, address: &str, amount: f64, comment: Option<&str>) -> BitcoinResult<String> {
         let params = match comment {
@@ -313,6 +323,15 @@ pub struct Utxo {
     pub safe: bool,
 }
 
+/// Address entry from listreceivedbyaddress
+#[derive(Debug, Deserialize)]
+pub struct ReceivedByAddress {
+    pub address: String,
+    pub amount: f64,
+    pub confirmations: u32,
+    pub txids: Vec<String>,
+}
+
 /// Transaction input for createrawtransaction
 #[derive(Debug, Serialize)]
 pub struct TxInput {
@@ -150,6 +150,73 @@ impl ScriptType {
             ScriptType::Custom => 100,
         }
     }
+
+    /// Detect script type of a scriptPubKey
+    ///
+    /// P2SH-wrapped scripts are reported as `P2SH`; scripts that do not
+    /// match a standard template are `Custom`.
+    pub fn from_script_pubkey(script: &Script) -> Self {
+        if script.is_p2pkh() {
+            ScriptType::P2PKH
+        } else if script.is_p2sh() {
+            ScriptType::P2SH
+        } else if script.is_p2pk() {
+            ScriptType::P2PK
+        } else if script.is_p2wpkh() {
+            ScriptType::P2WPKH
+        } else if script.is_p2wsh() {
+            ScriptType::P2WSH
+        } else if script.is_p2tr() {
+            ScriptType::P2TR
+        } else if script.is_multisig() {
+            ScriptType::Multisig
+        } else {
+            ScriptType::Custom
+        }
+    }
+
+    /// Estimated weight of an input spending this script type
+    ///
+    /// Covers outpoint, sequence, scriptSig and witness with 72 byte ECDSA
+    /// signatures. P2SH is estimated as P2SH-P2WPKH, P2WSH and bare multisig
+    /// as 2-of-3 and P2TR as a key path spend. Returns `None` for script
+    /// types without a standard spending template.
+    pub fn input_weight(&self) -> Option<u64> {
+        // Outpoint (36) + scriptSig length (1) + sequence (4)
+        const BASE: u64 = 41 * 4;
+        // Item count + signature + compressed public key
+        const P2WPKH_WITNESS: u64 = 1 + 73 + 34;
+
+        let weight = match self {
+            // Signature + compressed public key
+            ScriptType::P2PKH => BASE + (73 + 34) * 4,
+            // Signature
+            ScriptType::P2PK => BASE + 73 * 4,
+            // OP_0 + 2 signatures
+            ScriptType::Multisig => BASE + (1 + 2 * 73) * 4,
+            // Push of the P2WPKH redeem script
+            ScriptType::P2SH => BASE + 23 * 4 + P2WPKH_WITNESS,
+            ScriptType::P2WPKH => BASE + P2WPKH_WITNESS,
+            // Item count + empty item + 2 signatures + 2-of-3 witness script
+            ScriptType::P2WSH => BASE + 1 + 1 + 2 * 73 + 1 + 105,
+            // Item count + Schnorr signature
+            ScriptType::P2TR => BASE + 1 + 65,
+            ScriptType::TimeLock | ScriptType::HashLock | ScriptType::Custom => return None,
+        };
+
+        Some(weight)
+    }
+
+    /// Whether inputs of this type carry witness data (see `input_weight`)
+    pub fn has_witness_input(&self) -> bool {
+        matches!(self, ScriptType::P2SH | ScriptType::P2WPKH | ScriptType::P2WSH | ScriptType::P2TR)
+    }
+
+    /// Weight of an output paying to this script type
+    pub fn output_weight(&self) -> u64 {
+        // Amount (8) + script length (1) + script
+        (8 + 1 + self.typical_size() as u64) * 4
+    }
 }
 
 /// Bitcoin Script Builder and Analyzer
@@ -343,13 +410,14 @@ impl ScriptBuilder {
 
         // P2WPKH: OP_0 <20-byte-pubkey-hash>
         if instructions.len() == 2 {
+            // OP_0 is decoded as an empty push
             if let [
-                Instruction::Op(OP_0),
+                Instruction::PushBytes(version),
                 Instruction::PushBytes(hash),
             ] = &instructions[..] {
-                if hash.len() == 20 {
+                if version.is_empty() && hash.len() == 20 {
                     return Ok(ScriptType::P2WPKH);
-                } else if hash.len() == 32 {
+                } else if version.is_empty() && hash.len() == 32 {
                     return Ok(ScriptType::P2WSH);
                 }
             }
@@ -1,9 +1,15 @@
 //! Bitcoin transaction handling and building
 
-use super::{Amount, BitcoinError, BitcoinResult, FeeEstimate, Utxo};
+use super::{
+    coin_selection::{estimate_weight, weight_to_vsize, CoinSelectionConfig, CoinSelector},
+    script_types::ScriptType,
+    Amount, BitcoinError, BitcoinResult, FeeEstimate, Utxo,
+};
 use anyhow::{Context, Result};
+use bitcoin::{Address, ScriptBuf};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::str::FromStr;
 use tracing::{debug, error, info, warn};
 
 /// Bitcoin transaction representation
@@ -75,6 +81,9 @@ pub struct TransactionBuilder {
     outputs: Vec<TransactionOutput>,
     lock_time: u32,
     version: u32,
+    /// Script types of the UTXOs spent by `inputs`
+    input_types: Vec<ScriptType>,
+    coin_selection: CoinSelectionConfig,
 }
 
 impl TransactionBuilder {
@@ -85,6 +94,8 @@ impl TransactionBuilder {
             outputs: Vec::new(),
             lock_time: 0,
             version: 2, // BIP 68 compatible
+            input_types: Vec::new(),
+            coin_selection: CoinSelectionConfig::default(),
         }
     }
 
@@ -100,6 +111,12 @@ impl TransactionBuilder {
         self
     }
 
+    /// Set coin selection configuration
+    pub fn coin_selection(mut self, config: CoinSelectionConfig) -> Self {
+        self.coin_selection = config;
+        self
+    }
+
     /// Add input from UTXO
     pub fn add_input_from_utxo(mut self, utxo: &Utxo) -> Self {
         let input = TransactionInput {
@@ -112,6 +129,11 @@ impl TransactionBuilder {
             prev_address: utxo.address.clone(),
         };
         self.inputs.push(input);
+        self.input_types.push(
+            ScriptBuf::from_hex(&utxo.script_pubkey)
+                .map(|script| ScriptType::from_script_pubkey(&script))
+                .unwrap_or(ScriptType::Custom),
+        );
         self
     }
 
@@ -139,32 +161,30 @@ impl TransactionBuilder {
         let total_output: u64 = target_outputs.values().map(|a| a.to_sat()).sum();
         let total_output_amount = Amount::from_sat(total_output);
 
-        // Estimate transaction size and fee
-        let estimated_size = self.estimate_transaction_size(target_outputs.len(), change_address.is_some());
-        let estimated_fee = Amount::from_sat((fee_estimate.fee_rate * estimated_size as f64) as u64);
+        let output_types = target_outputs
+            .keys()
+            .map(|address| address_script_type(address))
+            .collect::<BitcoinResult<Vec<_>>>()?;
 
-        // Total amount needed (outputs + fee)
-        let total_needed = Amount::from_sat(total_output_amount.to_sat() + estimated_fee.to_sat());
+        // Change goes to the type of the provided change address
+        let mut config = self.coin_selection.clone();
+        if let Some(change_addr) = &change_address {
+            config.change_type = address_script_type(change_addr)?;
+        }
 
         // Select UTXOs
-        let selected_utxos = self.select_utxos(&available_utxos, total_needed)?;
-        
-        // Calculate total input amount
-        let total_input: u64 = selected_utxos.iter().map(|u| u.amount.to_sat()).sum();
-        let total_input_amount = Amount::from_sat(total_input);
-
-        // Calculate change
-        let change_amount = Amount::from_sat(
-            total_input_amount.to_sat()
-                .saturating_sub(total_output_amount.to_sat())
-                .saturating_sub(estimated_fee.to_sat())
-        );
+        let selection = CoinSelector::new(config).select(
+            &available_utxos,
+            total_output_amount,
+            &output_types,
+            fee_estimate.fee_rate,
+        )?;
 
         // Build transaction
         let mut builder = TransactionBuilder::new();
 
         // Add inputs
-        for utxo in &selected_utxos {
+        for utxo in &selection.utxos {
             builder = builder.add_input_from_utxo(utxo);
         }
 
@@ -174,7 +194,7 @@ impl TransactionBuilder {
         }
 
         // Add change output if needed
-        if change_amount.to_sat() > 546 { // Dust threshold
+        if let Some(change_amount) = selection.change {
             if let Some(change_addr) = change_address {
                 builder = builder.add_output(change_addr, change_amount);
             } else {
@@ -185,61 +205,30 @@ impl TransactionBuilder {
         // Create raw transaction hex (simplified)
         let raw_tx = builder.to_raw_transaction()?;
         
-        debug!("Built transaction with {} inputs, {} outputs, fee: {}", 
-               selected_utxos.len(), builder.outputs.len(), estimated_fee);
-
-        Ok((raw_tx, estimated_fee))
-    }
-
-    /// Select UTXOs for transaction (simple greedy algorithm)
-    fn select_utxos(&self, available_utxos: &[Utxo], target_amount: Amount) -> BitcoinResult<Vec<Utxo>> {
-        let mut selected = Vec::new();
-        let mut total_selected = 0u64;
-
-        // Sort UTXOs by amount (largest first)
-        let mut sorted_utxos = available_utxos.to_vec();
-        sorted_utxos.sort_by(|a, b| b.amount.to_sat().cmp(&a.amount.to_sat()));
-
-        for utxo in sorted_utxos {
-            if !utxo.spendable || !utxo.safe {
-                continue;
-            }
-
-            total_selected += utxo.amount.to_sat();
-            selected.push(utxo);
-
-            if total_selected >= target_amount.to_sat() {
-                break;
-            }
-        }
-
-        if total_selected < target_amount.to_sat() {
-            return Err(BitcoinError::InsufficientFunds {
-                required: target_amount,
-                available: Amount::from_sat(total_selected),
-            });
-        }
+        debug!("Built transaction with {} inputs, {} outputs, fee: {} ({:?})", 
+               selection.utxos.len(), builder.outputs.len(), selection.fee, selection.algorithm);
 
-        Ok(selected)
+        Ok((raw_tx, selection.fee))
     }
 
     /// Estimate transaction size in vBytes
+    ///
+    /// Inputs are weighted by the script type of the spent UTXO (unknown
+    /// types as P2PKH); outputs and change are assumed to be P2WPKH.
     pub fn estimate_transaction_size(&self, num_outputs: usize, has_change: bool) -> u32 {
-        // Simplified estimation
-        // Real implementation would consider input types (P2PKH, P2WPKH, etc.)
-        let num_inputs = self.inputs.len();
+        let inputs: Vec<ScriptType> = self
+            .input_types
+            .iter()
+            .map(|script_type| match script_type.input_weight() {
+                Some(_) => *script_type,
+                None => ScriptType::P2PKH,
+            })
+            .collect();
         let total_outputs = num_outputs + if has_change { 1 } else { 0 };
+        let outputs = vec![ScriptType::P2WPKH; total_outputs];
 
-        // Base transaction size
-        let base_size = 10; // version (4) + input count (1) + output count (1) + lock time (4)
-        
-        // Input size (varies by type, using P2WPKH estimate)
-        let input_size = num_inputs * 68; // 32 (txid) + 4 (vout) + 1 (script len) + 25 (script) + 4 (sequence) + 2 (witness)
-        
-        // Output size
-        let output_size = total_outputs * 34; // 8 (amount) + 1 (script len) + 25 (script)
-
-        (base_size + input_size + output_size) as u32
+        let weight = estimate_weight(&inputs, &outputs).unwrap_or_default();
+        weight_to_vsize(weight) as u32
     }
 
     /// Convert address to script public key (simplified)
@@ -326,6 +315,14 @@ impl TransactionBuilder {
     }
 }
 
+/// Script type paid by an address
+fn address_script_type(address: &str) -> BitcoinResult<ScriptType> {
+    let address = Address::from_str(address)
+        .map_err(|e| BitcoinError::InvalidAddress(format!("{}: {}", address, e)))?
+        .assume_checked();
+    Ok(ScriptType::from_script_pubkey(&address.script_pubkey()))
+}
+
 impl Default for TransactionBuilder {
     fn default() -> Self {
         Self::new()
@@ -4,23 +4,19 @@
 //! supporting various script types including Legacy, SegWit v0, and Taproot.
 
 use super::{BitcoinError, BitcoinResult, Network};
-use anyhow::{Context, Result};
 use bitcoin::{
-    absolute::LockTime,
-    ecdsa::Signature,
+    ecdsa,
     hashes::Hash,
-    key::{PrivateKey, PublicKey},
-    psbt::{Input as PsbtInput, Psbt},
+    key::{Keypair, PrivateKey, PublicKey, TapTweak, XOnlyPublicKey},
+    script::{Builder, PushBytesBuf},
     secp256k1::{All, Message, Secp256k1, SecretKey},
-    sighash::{EcdsaSighashType, SighashCache, TapSighashType},
-    taproot::TapLeafHash,
-    transaction::Version,
-    Address, Amount, OutPoint, ScriptBuf, Transaction, TxIn, TxOut, Txid, Witness,
+    sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType},
+    taproot::{self, ControlBlock, TapLeafHash, TapNodeHash},
+    Amount, Script, ScriptBuf, Transaction, TxOut, Witness,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::str::FromStr;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info};
 
 /// Transaction signing context
 #[derive(Debug, Clone)]
@@ -50,6 +46,18 @@ pub struct InputSigningInfo {
     pub sighash_type: Option<u32>,
     /// Derivation path (for HD wallets)
     pub derivation_path: Option<String>,
+    /// Redeem script for P2SH or witness script for P2WSH (hex encoded)
+    #[serde(default)]
+    pub redeem_script: Option<String>,
+    /// Leaf script for a Taproot script-path spend (hex encoded)
+    #[serde(default)]
+    pub tap_leaf_script: Option<String>,
+    /// Control block for a Taproot script-path spend (hex encoded)
+    #[serde(default)]
+    pub control_block: Option<String>,
+    /// Script tree merkle root used to tweak a Taproot key-path spend (hex encoded)
+    #[serde(default)]
+    pub tap_merkle_root: Option<String>,
 }
 
 /// Supported script types for signing
@@ -67,6 +75,24 @@ pub enum ScriptType {
     P2TR,
 }
 
+/// Signature produced for a single input
+#[derive(Debug, Clone)]
+struct InputSignature {
+    /// Signature as placed in the scriptSig or witness, including the sighash byte
+    signature: Vec<u8>,
+    /// Public key the signature verifies against
+    public_key: Vec<u8>,
+}
+
+/// Previous output being spent together with the key that unlocks it
+#[derive(Debug, Clone)]
+struct SpendingInput {
+    index: usize,
+    private_key: SecretKey,
+    prev_script: ScriptBuf,
+    prev_amount: Amount,
+}
+
 /// Signing result for a single input
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InputSigningResult {
@@ -187,25 +213,28 @@ impl TransactionSigner {
             ));
         }
 
+        // Taproot sighashes commit to every spent output
+        let prevouts = Self::collect_prevouts(transaction.input.len(), &signing_info);
+
         // Sign each input
-        for (i, info) in signing_info.iter().enumerate() {
-            let result = self.sign_input(&mut transaction, info);
-            
-            match &result {
-                Ok(_) => {
+        for info in &signing_info {
+            let result = self.sign_input(&mut transaction, info, prevouts.as_deref());
+
+            match result {
+                Ok(signed) => {
                     signed_inputs += 1;
                     input_results.push(InputSigningResult {
-                        input_index: i,
+                        input_index: info.input_index,
                         success: true,
                         error: None,
-                        signature: Some("signed".to_string()), // Placeholder
-                        public_key: Some("pubkey".to_string()), // Placeholder
+                        signature: Some(hex::encode(signed.signature)),
+                        public_key: Some(hex::encode(signed.public_key)),
                         script_type: info.script_type,
                     });
                 }
                 Err(e) => {
                     input_results.push(InputSigningResult {
-                        input_index: i,
+                        input_index: info.input_index,
                         success: false,
                         error: Some(e.to_string()),
                         signature: None,
@@ -245,35 +274,163 @@ impl TransactionSigner {
         })
     }
 
+    /// Collect the spent outputs ordered by input index.
+    ///
+    /// Returns `None` unless every input is described exactly once with a valid script.
+    fn collect_prevouts(input_count: usize, signing_info: &[InputSigningInfo]) -> Option<Vec<TxOut>> {
+        let mut prevouts: Vec<Option<TxOut>> = vec![None; input_count];
+
+        for info in signing_info {
+            let slot = prevouts.get_mut(info.input_index)?;
+            if slot.is_some() {
+                return None;
+            }
+            *slot = Some(TxOut {
+                value: Amount::from_sat(info.prev_amount),
+                script_pubkey: ScriptBuf::from_hex(&info.prev_script).ok()?,
+            });
+        }
+
+        prevouts.into_iter().collect()
+    }
+
     /// Sign a single input
     fn sign_input(
         &self,
         transaction: &mut Transaction,
         info: &InputSigningInfo,
-    ) -> BitcoinResult<()> {
+        prevouts: Option<&[TxOut]>,
+    ) -> BitcoinResult<InputSignature> {
+        if info.input_index >= transaction.input.len() {
+            return Err(BitcoinError::SigningError(format!(
+                "Input index {} out of range",
+                info.input_index
+            )));
+        }
+
         // Parse private key
         let private_key = SecretKey::from_str(&info.private_key)
             .map_err(|e| BitcoinError::SigningError(format!("Invalid private key: {}", e)))?;
 
-        // Get public key
-        let public_key = PublicKey::from_private_key(&self.context.secp, &PrivateKey::new(private_key, self.context.network.into()));
-
         // Parse previous script
         let prev_script = ScriptBuf::from_hex(&info.prev_script)
             .map_err(|e| BitcoinError::SigningError(format!("Invalid previous script: {}", e)))?;
 
+        let input = SpendingInput {
+            index: info.input_index,
+            private_key,
+            prev_script,
+            prev_amount: Amount::from_sat(info.prev_amount),
+        };
+
+        if info.script_type.is_taproot() {
+            return self.sign_taproot_input(transaction, info, &input, prevouts);
+        }
+
+        // Get public key
+        let public_key = PublicKey::from_private_key(
+            &self.context.secp,
+            &PrivateKey::new(private_key, self.context.network.into()),
+        );
+
         // Get sighash type
-        let sighash_type = info.sighash_type
-            .map(|st| EcdsaSighashType::from_consensus(st))
+        let sighash_type = info
+            .sighash_type
+            .map(EcdsaSighashType::from_consensus)
             .unwrap_or(self.context.default_sighash_type);
 
         // Sign based on script type
         match info.script_type {
-            ScriptType::P2PKH => self.sign_p2pkh(transaction, info.input_index, &private_key, &public_key, sighash_type),
-            ScriptType::P2WPKH => self.sign_p2wpkh(transaction, info.input_index, &private_key, &public_key, info.prev_amount, sighash_type),
-            ScriptType::P2SH => self.sign_p2sh(transaction, info.input_index, &private_key, &public_key, &prev_script, sighash_type),
-            ScriptType::P2WSH => self.sign_p2wsh(transaction, info.input_index, &private_key, &public_key, &prev_script, info.prev_amount, sighash_type),
-            ScriptType::P2TR => self.sign_p2tr(transaction, info.input_index, &private_key, info.prev_amount),
+            ScriptType::P2PKH => self.sign_p2pkh(transaction, &input, &public_key, sighash_type),
+            ScriptType::P2WPKH => self.sign_p2wpkh(transaction, &input, &public_key, sighash_type),
+            ScriptType::P2SH => {
+                let redeem_script = self.embedded_script(info, "P2SH redeem script")?;
+                self.sign_p2sh(transaction, &input, &public_key, &redeem_script, sighash_type)
+            }
+            ScriptType::P2WSH => {
+                let witness_script = self.embedded_script(info, "P2WSH witness script")?;
+                self.sign_p2wsh(transaction, &input, &public_key, &witness_script, sighash_type)
+            }
+            ScriptType::P2TR => unreachable!("Taproot inputs are signed via sign_taproot_input"),
+        }
+    }
+
+    /// Pick the Taproot key path or script path from the signing info
+    fn sign_taproot_input(
+        &self,
+        transaction: &mut Transaction,
+        info: &InputSigningInfo,
+        input: &SpendingInput,
+        prevouts: Option<&[TxOut]>,
+    ) -> BitcoinResult<InputSignature> {
+        let prevouts = prevouts.ok_or_else(|| {
+            BitcoinError::SigningError(
+                "Taproot signing requires the previous output of every input".to_string(),
+            )
+        })?;
+        let sighash_type = info
+            .sighash_type
+            .map(|st| {
+                u8::try_from(st)
+                    .ok()
+                    .and_then(|st| TapSighashType::from_consensus_u8(st).ok())
+                    .ok_or_else(|| {
+                        BitcoinError::SigningError(format!("Invalid Taproot sighash type: {}", st))
+                    })
+            })
+            .transpose()?
+            .unwrap_or(TapSighashType::Default);
+
+        match (&info.tap_leaf_script, &info.control_block) {
+            (Some(leaf_script), Some(control_block)) => {
+                let leaf_script = ScriptBuf::from_hex(leaf_script).map_err(|e| {
+                    BitcoinError::SigningError(format!("Invalid Taproot leaf script: {}", e))
+                })?;
+                let control_block = ControlBlock::decode(&decode_hex(control_block, "control block")?)
+                    .map_err(|e| BitcoinError::SigningError(format!("Invalid control block: {}", e)))?;
+                self.sign_p2tr_script_path(transaction, input, &leaf_script, &control_block, prevouts, sighash_type)
+            }
+            (None, None) => {
+                let merkle_root = info
+                    .tap_merkle_root
+                    .as_deref()
+                    .map(|root| {
+                        <[u8; 32]>::try_from(decode_hex(root, "merkle root")?.as_slice())
+                            .map(TapNodeHash::from_byte_array)
+                            .map_err(|_| {
+                                BitcoinError::SigningError("Taproot merkle root must be 32 bytes".to_string())
+                            })
+                    })
+                    .transpose()?;
+                self.sign_p2tr(transaction, input, merkle_root, prevouts, sighash_type)
+            }
+            _ => Err(BitcoinError::SigningError(
+                "Taproot script-path spends need both a leaf script and a control block".to_string(),
+            )),
+        }
+    }
+
+    /// Parse the redeem or witness script carried alongside the input
+    fn embedded_script(&self, info: &InputSigningInfo, what: &str) -> BitcoinResult<ScriptBuf> {
+        let script = info
+            .redeem_script
+            .as_deref()
+            .ok_or_else(|| BitcoinError::SigningError(format!("Missing {}", what)))?;
+        ScriptBuf::from_hex(script)
+            .map_err(|e| BitcoinError::SigningError(format!("Invalid {}: {}", what, e)))
+    }
+
+    /// Produce a DER signature with the sighash byte appended
+    fn sign_ecdsa(
+        &self,
+        sighash: [u8; 32],
+        private_key: &SecretKey,
+        sighash_type: EcdsaSighashType,
+    ) -> ecdsa::Signature {
+        let message = Message::from_digest(sighash);
+        ecdsa::Signature {
+            sig: self.context.secp.sign_ecdsa(&message, private_key),
+            hash_ty: sighash_type,
         }
     }
 
@@ -281,100 +438,249 @@ impl TransactionSigner {
     fn sign_p2pkh(
         &self,
         transaction: &mut Transaction,
-        input_index: usize,
-        private_key: &SecretKey,
+        input: &SpendingInput,
         public_key: &PublicKey,
         sighash_type: EcdsaSighashType,
-    ) -> BitcoinResult<()> {
-        debug!("Signing P2PKH input {}", input_index);
-        
-        // For P2PKH, we need to create the signature script
-        // This is a simplified implementation - real implementation would compute proper sighash
-        let script_sig = ScriptBuf::new(); // Placeholder
-        transaction.input[input_index].script_sig = script_sig;
-        
-        Ok(())
+    ) -> BitcoinResult<InputSignature> {
+        debug!("Signing P2PKH input {}", input.index);
+
+        if input.prev_script != ScriptBuf::new_p2pkh(&public_key.pubkey_hash()) {
+            return Err(key_mismatch());
+        }
+
+        let sighash = SighashCache::new(&*transaction)
+            .legacy_signature_hash(input.index, &input.prev_script, sighash_type.to_u32())
+            .map_err(sighash_error)?;
+        let signature = self.sign_ecdsa(sighash.to_byte_array(), &input.private_key, sighash_type);
+
+        transaction.input[input.index].script_sig = Builder::new()
+            .push_slice(signature.serialize())
+            .push_key(public_key)
+            .into_script();
+
+        Ok(InputSignature {
+            signature: signature.to_vec(),
+            public_key: public_key.to_bytes(),
+        })
     }
 
     /// Sign P2WPKH input (SegWit v0)
     fn sign_p2wpkh(
         &self,
         transaction: &mut Transaction,
-        input_index: usize,
-        private_key: &SecretKey,
+        input: &SpendingInput,
         public_key: &PublicKey,
-        prev_amount: u64,
         sighash_type: EcdsaSighashType,
-    ) -> BitcoinResult<()> {
-        debug!("Signing P2WPKH input {}", input_index);
-        
+    ) -> BitcoinResult<InputSignature> {
+        debug!("Signing P2WPKH input {}", input.index);
+
+        let expected = public_key
+            .wpubkey_hash()
+            .map(|hash| ScriptBuf::new_p2wpkh(&hash))
+            .ok_or_else(|| BitcoinError::SigningError("P2WPKH requires a compressed key".to_string()))?;
+        if input.prev_script != expected {
+            return Err(key_mismatch());
+        }
+
+        let sighash = SighashCache::new(&*transaction)
+            .p2wpkh_signature_hash(input.index, &input.prev_script, input.prev_amount, sighash_type)
+            .map_err(sighash_error)?;
+        let signature = self.sign_ecdsa(sighash.to_byte_array(), &input.private_key, sighash_type);
+
         // For P2WPKH, signature goes in witness
-        let mut witness = Witness::new();
-        witness.push(&[0u8; 64]); // Placeholder signature
-        witness.push(public_key.to_bytes());
-        transaction.input[input_index].witness = witness;
-        
-        Ok(())
+        transaction.input[input.index].witness = Witness::p2wpkh(&signature, &public_key.inner);
+
+        Ok(InputSignature {
+            signature: signature.to_vec(),
+            public_key: public_key.to_bytes(),
+        })
     }
 
     /// Sign P2SH input
+    ///
+    /// A redeem script that is itself a P2WPKH program is signed as nested SegWit;
+    /// any other redeem script gets a single signature followed by the script.
     fn sign_p2sh(
         &self,
         transaction: &mut Transaction,
-        input_index: usize,
-        private_key: &SecretKey,
+        input: &SpendingInput,
         public_key: &PublicKey,
-        redeem_script: &ScriptBuf,
+        redeem_script: &Script,
         sighash_type: EcdsaSighashType,
-    ) -> BitcoinResult<()> {
-        debug!("Signing P2SH input {}", input_index);
-        
-        // P2SH signing depends on the redeem script
-        // This is a simplified implementation
-        let script_sig = ScriptBuf::new(); // Placeholder
-        transaction.input[input_index].script_sig = script_sig;
-        
-        Ok(())
+    ) -> BitcoinResult<InputSignature> {
+        debug!("Signing P2SH input {}", input.index);
+
+        if input.prev_script != ScriptBuf::new_p2sh(&redeem_script.script_hash()) {
+            return Err(BitcoinError::SigningError(
+                "Redeem script does not match previous output script".to_string(),
+            ));
+        }
+
+        let redeem_push = PushBytesBuf::try_from(redeem_script.to_bytes())
+            .map_err(|_| BitcoinError::SigningError("Redeem script too large".to_string()))?;
+
+        if redeem_script.is_p2wpkh() {
+            let nested = SpendingInput {
+                prev_script: redeem_script.to_owned(),
+                ..input.clone()
+            };
+            let signed = self.sign_p2wpkh(transaction, &nested, public_key, sighash_type)?;
+            transaction.input[input.index].script_sig =
+                Builder::new().push_slice(redeem_push).into_script();
+            return Ok(signed);
+        }
+
+        let sighash = SighashCache::new(&*transaction)
+            .legacy_signature_hash(input.index, redeem_script, sighash_type.to_u32())
+            .map_err(sighash_error)?;
+        let signature = self.sign_ecdsa(sighash.to_byte_array(), &input.private_key, sighash_type);
+
+        transaction.input[input.index].script_sig = Builder::new()
+            .push_slice(signature.serialize())
+            .push_slice(redeem_push)
+            .into_script();
+
+        Ok(InputSignature {
+            signature: signature.to_vec(),
+            public_key: public_key.to_bytes(),
+        })
     }
 
     /// Sign P2WSH input (SegWit v0)
     fn sign_p2wsh(
         &self,
         transaction: &mut Transaction,
-        input_index: usize,
-        private_key: &SecretKey,
+        input: &SpendingInput,
         public_key: &PublicKey,
-        witness_script: &ScriptBuf,
-        prev_amount: u64,
+        witness_script: &Script,
         sighash_type: EcdsaSighashType,
-    ) -> BitcoinResult<()> {
-        debug!("Signing P2WSH input {}", input_index);
-        
+    ) -> BitcoinResult<InputSignature> {
+        debug!("Signing P2WSH input {}", input.index);
+
+        if input.prev_script != ScriptBuf::new_p2wsh(&witness_script.wscript_hash()) {
+            return Err(BitcoinError::SigningError(
+                "Witness script does not match previous output script".to_string(),
+            ));
+        }
+
+        let sighash = SighashCache::new(&*transaction)
+            .p2wsh_signature_hash(input.index, witness_script, input.prev_amount, sighash_type)
+            .map_err(sighash_error)?;
+        let signature = self.sign_ecdsa(sighash.to_byte_array(), &input.private_key, sighash_type);
+
         // P2WSH signing with witness script
         let mut witness = Witness::new();
-        witness.push(&[0u8; 64]); // Placeholder signature
+        witness.push(signature.to_vec());
         witness.push(witness_script.as_bytes());
-        transaction.input[input_index].witness = witness;
-        
-        Ok(())
+        transaction.input[input.index].witness = witness;
+
+        Ok(InputSignature {
+            signature: signature.to_vec(),
+            public_key: public_key.to_bytes(),
+        })
     }
 
-    /// Sign P2TR input (Taproot)
+    /// Sign P2TR input via the key path (BIP-341)
+    ///
+    /// The key is tweaked with the script tree merkle root (if any) before signing.
     fn sign_p2tr(
         &self,
         transaction: &mut Transaction,
-        input_index: usize,
-        private_key: &SecretKey,
-        prev_amount: u64,
-    ) -> BitcoinResult<()> {
-        debug!("Signing P2TR input {}", input_index);
-        
-        // Taproot signing uses Schnorr signatures
-        // This is a simplified implementation
+        input: &SpendingInput,
+        merkle_root: Option<TapNodeHash>,
+        prevouts: &[TxOut],
+        sighash_type: TapSighashType,
+    ) -> BitcoinResult<InputSignature> {
+        debug!("Signing P2TR key-path input {}", input.index);
+
+        let secp = &self.context.secp;
+        let keypair = Keypair::from_secret_key(secp, &input.private_key);
+        let (internal_key, _) = keypair.x_only_public_key();
+        if input.prev_script != ScriptBuf::new_p2tr(secp, internal_key, merkle_root) {
+            return Err(key_mismatch());
+        }
+
+        let sighash = SighashCache::new(&*transaction)
+            .taproot_key_spend_signature_hash(input.index, &Prevouts::All(prevouts), sighash_type)
+            .map_err(sighash_error)?;
+        let tweaked = keypair.tap_tweak(secp, merkle_root).to_inner();
+        let signature = taproot::Signature {
+            sig: secp.sign_schnorr(&Message::from_digest(sighash.to_byte_array()), &tweaked),
+            hash_ty: sighash_type,
+        };
+
+        transaction.input[input.index].witness = Witness::from_slice(&[signature.to_vec()]);
+
+        Ok(InputSignature {
+            signature: signature.to_vec(),
+            public_key: tweaked.x_only_public_key().0.serialize().to_vec(),
+        })
+    }
+
+    /// Sign P2TR input via a script path (BIP-341/342)
+    ///
+    /// The key signs untweaked; the witness carries the leaf script and control block.
+    fn sign_p2tr_script_path(
+        &self,
+        transaction: &mut Transaction,
+        input: &SpendingInput,
+        leaf_script: &Script,
+        control_block: &ControlBlock,
+        prevouts: &[TxOut],
+        sighash_type: TapSighashType,
+    ) -> BitcoinResult<InputSignature> {
+        debug!("Signing P2TR script-path input {}", input.index);
+
+        let secp = &self.context.secp;
+        let output_key = input
+            .prev_script
+            .is_p2tr()
+            .then(|| XOnlyPublicKey::from_slice(&input.prev_script.as_bytes()[2..]).ok())
+            .flatten()
+            .ok_or_else(|| BitcoinError::SigningError("Previous output is not P2TR".to_string()))?;
+        if !control_block.verify_taproot_commitment(secp, output_key, leaf_script) {
+            return Err(BitcoinError::SigningError(
+                "Control block does not commit to the leaf script".to_string(),
+            ));
+        }
+
+        let keypair = Keypair::from_secret_key(secp, &input.private_key);
+        let leaf_hash = TapLeafHash::from_script(leaf_script, control_block.leaf_version);
+        let sighash = SighashCache::new(&*transaction)
+            .taproot_script_spend_signature_hash(
+                input.index,
+                &Prevouts::All(prevouts),
+                leaf_hash,
+                sighash_type,
+            )
+            .map_err(sighash_error)?;
+        let signature = taproot::Signature {
+            sig: secp.sign_schnorr(&Message::from_digest(sighash.to_byte_array()), &keypair),
+            hash_ty: sighash_type,
+        };
+
         let mut witness = Witness::new();
-        witness.push(&[0u8; 64]); // Placeholder Schnorr signature
-        transaction.input[input_index].witness = witness;
-        
-        Ok(())
+        witness.push(signature.to_vec());
+        witness.push(leaf_script.as_bytes());
+        witness.push(control_block.serialize());
+        transaction.input[input.index].witness = witness;
+
+        Ok(InputSignature {
+            signature: signature.to_vec(),
+            public_key: keypair.x_only_public_key().0.serialize().to_vec(),
+        })
     }
 }
+
+/// Decode a hex field, naming it in the error
+fn decode_hex(value: &str, what: &str) -> BitcoinResult<Vec<u8>> {
+    hex::decode(value).map_err(|e| BitcoinError::SigningError(format!("Invalid {}: {}", what, e)))
+}
+
+fn key_mismatch() -> BitcoinError {
+    BitcoinError::SigningError("Private key does not match previous output script".to_string())
+}
+
+fn sighash_error(e: bitcoin::sighash::Error) -> BitcoinError {
+    BitcoinError::SigningError(format!("Sighash computation failed: {}", e))
+}
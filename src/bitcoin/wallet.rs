@@ -1,11 +1,16 @@
 //! Bitcoin wallet management
 
 use super::{
-    core::{BitcoinCore, BitcoinUtxo}, AddressType, Amount, BitcoinConfig, BitcoinError, BitcoinResult, Network,
+    core::{BitcoinCore, BitcoinTransactionInfo, BitcoinUtxo},
+    fee_bump::{FeeBump, FeeBumpConfig, FeeBumpMethod, FeeBumper},
+    security_validator::SecurityValidator,
+    AddressType, Amount, BitcoinConfig, BitcoinError, BitcoinResult, Network, Utxo,
 };
 use anyhow::{Context, Result};
+use bitcoin::{Address, Transaction, TxOut};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::str::FromStr;
 use tracing::{debug, error, info, warn};
 
 /// Bitcoin wallet representation
@@ -243,6 +248,128 @@ impl WalletManager {
         Ok(utxos)
     }
 
+    /// Bump the fee of an unconfirmed transaction with a BIP 125 replacement
+    ///
+    /// The higher fee is paid from the change output, topped up with
+    /// confirmed wallet UTXOs when needed.
+    pub async fn bump_fee(&self, wallet: &BitcoinWallet, txid: &str, fee_rate: f64) -> BitcoinResult<FeeBumpResult> {
+        let (original, tx_info) = self.unconfirmed_transaction(txid).await?;
+
+        let mut spent_outputs: Vec<TxOut> = Vec::new();
+        for input in &original.input {
+            let outpoint = input.previous_output;
+            let previous = self.bitcoin_core.get_raw_transaction(&outpoint.txid.to_string()).await?;
+            let spent = previous
+                .output
+                .get(outpoint.vout as usize)
+                .cloned()
+                .ok_or_else(|| BitcoinError::TransactionNotFound(outpoint.to_string()))?;
+            spent_outputs.push(spent);
+        }
+
+        let change_index = change_output(&original, &tx_info)
+            .ok_or_else(|| BitcoinError::InvalidInput(format!("Transaction {} has no change output", txid)))?;
+
+        // Confirmed UTXOs only (BIP 125 rule 2)
+        let extra_utxos: Vec<Utxo> = self
+            .list_utxos(wallet, Some(1))
+            .await?
+            .into_iter()
+            .map(Utxo::from)
+            .collect();
+
+        let bump = self
+            .fee_bumper()
+            .await
+            .bump_rbf(&original, &spent_outputs, change_index, fee_rate, &extra_utxos)?;
+        self.broadcast_fee_bump(txid, bump).await
+    }
+
+    /// Bump the fee of an unconfirmed transaction with a child spending
+    /// its change output (CPFP)
+    pub async fn bump_fee_cpfp(&self, wallet: &BitcoinWallet, txid: &str, fee_rate: f64) -> BitcoinResult<FeeBumpResult> {
+        let (parent, tx_info) = self.unconfirmed_transaction(txid).await?;
+
+        let parent_fee = tx_info
+            .fee
+            .ok_or_else(|| BitcoinError::InvalidInput(format!("Transaction {} was not sent by this wallet", txid)))?;
+        let change_index = change_output(&parent, &tx_info)
+            .ok_or_else(|| BitcoinError::InvalidInput(format!("Transaction {} has no change output", txid)))?;
+
+        let destination = self.bitcoin_core.generate_address(AddressType::Bech32, None).await?;
+        let destination = Address::from_str(&destination)
+            .map_err(|e| e.to_string())
+            .and_then(|address| address.require_network(wallet.network.into()).map_err(|e| e.to_string()))
+            .map_err(|e| BitcoinError::InvalidAddress(format!("{}: {}", destination, e)))?;
+
+        let bump = self.fee_bumper().await.bump_cpfp(
+            &parent,
+            parent_fee,
+            change_index as u32,
+            &destination.script_pubkey(),
+            fee_rate,
+        )?;
+        self.broadcast_fee_bump(txid, bump).await
+    }
+
+    async fn unconfirmed_transaction(&self, txid: &str) -> BitcoinResult<(Transaction, BitcoinTransactionInfo)> {
+        let tx_info = self.bitcoin_core.get_transaction(txid).await?;
+        if tx_info.confirmations != 0 {
+            return Err(BitcoinError::InvalidInput(format!(
+                "Transaction {} is not in the mempool ({} confirmations)",
+                txid, tx_info.confirmations
+            )));
+        }
+
+        let bytes = hex::decode(&tx_info.hex)
+            .map_err(|e| BitcoinError::Rpc(format!("Invalid transaction hex for {}: {}", txid, e)))?;
+        let transaction = bitcoin::consensus::encode::deserialize(&bytes)
+            .map_err(|e| BitcoinError::Rpc(format!("Invalid transaction {}: {}", txid, e)))?;
+
+        Ok((transaction, tx_info))
+    }
+
+    async fn fee_bumper(&self) -> FeeBumper {
+        let incremental_relay_fee = match self.bitcoin_core.get_incremental_relay_fee().await {
+            Ok(fee) => fee,
+            Err(e) => {
+                warn!("Using default incremental relay fee: {}", e);
+                FeeBumpConfig::default().incremental_relay_fee
+            }
+        };
+
+        let config = FeeBumpConfig {
+            incremental_relay_fee,
+            ..FeeBumpConfig::default()
+        };
+        FeeBumper::with_config(config, SecurityValidator::new(self.config.network))
+    }
+
+    async fn broadcast_fee_bump(&self, original_txid: &str, bump: FeeBump) -> BitcoinResult<FeeBumpResult> {
+        if bump.validation.requires_confirmation {
+            return Err(BitcoinError::SecurityValidation(format!(
+                "Fee bump of {} requires manual review ({} risk): {}",
+                original_txid,
+                bump.validation.security_level.as_str(),
+                bump.validation.warnings.join("; ")
+            )));
+        }
+
+        let txid = self.bitcoin_core.sign_and_send_transaction(&bump.transaction).await?;
+
+        info!(
+            "Bumped fee of {} with {:?} (txid: {}, fee: {}, {:.2} sat/vB)",
+            original_txid, bump.method, txid, bump.fee, bump.fee_rate
+        );
+        Ok(FeeBumpResult {
+            original_txid: original_txid.to_string(),
+            txid,
+            method: bump.method,
+            fee: bump.fee,
+            fee_rate: bump.fee_rate,
+        })
+    }
+
     /// Get wallet transaction history
     pub async fn get_transaction_history(
         &self,
@@ -307,6 +434,16 @@ pub struct SyncResult {
     pub errors: Vec<String>,
 }
 
+/// Fee bump result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeBumpResult {
+    pub original_txid: String,
+    pub txid: String,
+    pub method: FeeBumpMethod,
+    pub fee: Amount,
+    pub fee_rate: f64,
+}
+
 /// Wallet transaction
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletTransaction {
@@ -343,6 +480,21 @@ pub struct WalletInfo {
     pub last_sync: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+/// Index of the change output: the only output not listed as a payment
+fn change_output(transaction: &Transaction, tx_info: &BitcoinTransactionInfo) -> Option<usize> {
+    let mut candidates = (0..transaction.output.len()).filter(|vout| {
+        !tx_info
+            .details
+            .iter()
+            .any(|detail| detail.category == "send" && detail.vout as usize == *vout)
+    });
+
+    match (candidates.next(), candidates.next()) {
+        (Some(index), None) => Some(index),
+        _ => None,
+    }
+}
+
 impl BitcoinWallet {
     /// Get total balance (confirmed + unconfirmed)
     pub fn total_balance(&self) -> Amount {
@@ -0,0 +1,99 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Konfiguracja połączenia z giełdą (handel na żywo)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExchangeConfig {
+    /// Adapter giełdy (obecnie `binance_futures`)
+    pub adapter: String,
+
+    /// Bazowy adres REST API
+    pub rest_url: String,
+
+    /// Bazowy adres strumieni WebSocket
+    pub ws_url: String,
+
+    /// Klucz API (lepiej użyć `api_key_env`)
+    pub api_key: Option<String>,
+
+    /// Zmienna środowiskowa z kluczem API (ma pierwszeństwo)
+    pub api_key_env: Option<String>,
+
+    /// Sekret API (lepiej użyć `api_secret_env`)
+    pub api_secret: Option<String>,
+
+    /// Zmienna środowiskowa z sekretem API (ma pierwszeństwo)
+    pub api_secret_env: Option<String>,
+
+    /// Waluta kwotowania kontraktów (token `BONK` -> symbol `BONKUSDT`)
+    pub quote_asset: String,
+
+    /// Okno ważności podpisanych żądań (w milisekundach)
+    pub recv_window_ms: u64,
+
+    /// Timeout żądań HTTP (w sekundach)
+    pub request_timeout: u64,
+}
+
+impl Default for ExchangeConfig {
+    fn default() -> Self {
+        Self {
+            adapter: "binance_futures".to_string(),
+            rest_url: "https://fapi.binance.com".to_string(),
+            ws_url: "wss://fstream.binance.com".to_string(),
+            api_key: None,
+            api_key_env: Some("BINANCE_API_KEY".to_string()),
+            api_secret: None,
+            api_secret_env: Some("BINANCE_API_SECRET".to_string()),
+            quote_asset: "USDT".to_string(),
+            recv_window_ms: 5000,
+            request_timeout: 10,
+        }
+    }
+}
+
+impl ExchangeConfig {
+    /// Zwraca klucz API (zmienna środowiskowa ma pierwszeństwo)
+    pub fn resolve_api_key(&self) -> Option<String> {
+        resolve(&self.api_key_env, &self.api_key)
+    }
+
+    /// Zwraca sekret API (zmienna środowiskowa ma pierwszeństwo)
+    pub fn resolve_api_secret(&self) -> Option<String> {
+        resolve(&self.api_secret_env, &self.api_secret)
+    }
+
+    /// Waliduje konfigurację giełdy
+    pub fn validate(&self) -> Result<()> {
+        if self.adapter != "binance_futures" {
+            anyhow::bail!("Unsupported exchange adapter: {}", self.adapter);
+        }
+
+        for (name, url) in [("rest_url", &self.rest_url), ("ws_url", &self.ws_url)] {
+            url::Url::parse(url)
+                .map_err(|e| anyhow::anyhow!("exchange {} is invalid: {}", name, e))?;
+        }
+
+        if self.quote_asset.is_empty() {
+            anyhow::bail!("exchange quote_asset cannot be empty");
+        }
+
+        if self.recv_window_ms == 0 || self.recv_window_ms > 60_000 {
+            anyhow::bail!("exchange recv_window_ms must be between 1 and 60000");
+        }
+
+        if self.request_timeout == 0 {
+            anyhow::bail!("exchange request_timeout must be greater than 0");
+        }
+
+        Ok(())
+    }
+}
+
+fn resolve(env: &Option<String>, value: &Option<String>) -> Option<String> {
+    env.as_ref()
+        .and_then(|name| std::env::var(name).ok())
+        .or_else(|| value.clone())
+        .filter(|value| !value.is_empty())
+}
@@ -4,15 +4,25 @@ use std::path::Path;
 
 pub mod alerts;
 pub mod database;
+pub mod exchange;
 pub mod monitoring;
 pub mod risk;
+pub mod signals;
+pub mod strategy;
 pub mod trading;
 
 pub use alerts::AlertsConfig;
 pub use database::DatabaseConfig;
+pub use exchange::ExchangeConfig;
 pub use monitoring::MonitoringConfig;
-pub use risk::RiskConfig;
-pub use trading::TradingConfig;
+pub use risk::{
+    ConcentrationConfig, MarginConfig, MarginTierConfig, RiskConfig, TradeLimitsConfig,
+};
+pub use signals::{ScreeningConfig, SignalsConfig};
+pub use strategy::{PhaseConfig, StrategyConfig};
+pub use trading::{
+    PaperTradingConfig, PriceFeedConfig, SizingConfig, SizingPolicyConfig, TradingConfig,
+};
 
 /// Główna konfiguracja aplikacji Cerberus
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +42,18 @@ pub struct Config {
     /// Konfiguracja alertów
     pub alerts: AlertsConfig,
 
+    /// Konfiguracja przetwarzania sygnałów
+    #[serde(default)]
+    pub signals: SignalsConfig,
+
+    /// Konfiguracja giełdy (handel na żywo)
+    #[serde(default)]
+    pub exchange: ExchangeConfig,
+
+    /// Profile strategii zależne od fazy (wielkości portfela)
+    #[serde(default)]
+    pub strategy: StrategyConfig,
+
     /// Konfiguracja Sentry
     pub sentry: SentryConfig,
 
@@ -126,6 +148,35 @@ impl Config {
             .validate()
             .context("Risk configuration validation failed")?;
 
+        // Walidacja konfiguracji sygnałów
+        self.signals
+            .validate()
+            .context("Signals configuration validation failed")?;
+
+        // Walidacja konfiguracji giełdy
+        self.exchange
+            .validate()
+            .context("Exchange configuration validation failed")?;
+
+        // Walidacja faz strategii (parametry fazy zastępują tradingowe i ryzyka)
+        self.strategy
+            .validate()
+            .context("Strategy configuration validation failed")?;
+        if self.strategy.enabled {
+            for phase in &self.strategy.phases {
+                if phase.max_leverage < self.trading.min_leverage {
+                    anyhow::bail!(
+                        "Strategy phase {} max_leverage is below trading min_leverage",
+                        phase.name
+                    );
+                }
+
+                phase.risk_config(&self.risk).validate().with_context(|| {
+                    format!("Strategy phase {} risk levels are invalid", phase.name)
+                })?;
+            }
+        }
+
         // Walidacja portów
         if self.http_port == self.metrics_port {
             anyhow::bail!("HTTP port and metrics port cannot be the same");
@@ -187,6 +238,9 @@ impl Default for Config {
             risk: RiskConfig::default(),
             monitoring: MonitoringConfig::default(),
             alerts: AlertsConfig::default(),
+            signals: SignalsConfig::default(),
+            exchange: ExchangeConfig::default(),
+            strategy: StrategyConfig::default(),
             sentry: SentryConfig::default(),
             environment: "development".to_string(),
             http_port: 8080,
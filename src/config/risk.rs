@@ -37,6 +37,106 @@ pub struct RiskConfig {
 
     /// Czy włączyć automatyczne zarządzanie ryzykiem
     pub enable_auto_risk_management: bool,
+
+    /// Czy zadziałanie circuit breakera uruchamia emergency stop
+    #[serde(default)]
+    pub emergency_stop_on_circuit_breaker: bool,
+
+    /// Model marży używany do wyznaczania cen likwidacji
+    #[serde(default)]
+    pub margin: MarginConfig,
+
+    /// Limity koncentracji na token i grupę skorelowanych tokenów
+    #[serde(default)]
+    pub concentration: ConcentrationConfig,
+
+    /// Dzienne limity transakcji i blokady ponownego wejścia w token
+    #[serde(default)]
+    pub trade_limits: TradeLimitsConfig,
+}
+
+/// Konfiguracja limitów liczby transakcji
+///
+/// Liczone są otwarcia pozycji w dniu UTC; blokady po stop-lossie
+/// i likwidacji dotyczą tylko tokena, na którym wystąpiły.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TradeLimitsConfig {
+    /// Czy egzekwować limity
+    pub enabled: bool,
+
+    /// Maksymalna liczba otwartych pozycji na dzień (wszystkie tokeny)
+    pub max_daily_trades: u32,
+
+    /// Maksymalna liczba otwartych pozycji na token na dzień
+    pub max_daily_trades_per_token: u32,
+
+    /// Przerwa przed ponownym wejściem w token po stop-lossie (w sekundach)
+    pub stop_loss_cooldown: u64,
+
+    /// Zakaz ponownego wejścia w token po likwidacji (w sekundach)
+    pub liquidation_ban: u64,
+}
+
+/// Konfiguracja limitów koncentracji i korelacji
+///
+/// Ekspozycja to wartość nominalna pozycji względem equity portfela.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConcentrationConfig {
+    /// Czy stosować limity koncentracji
+    pub enabled: bool,
+
+    /// Maksymalna ekspozycja na jeden token (w procentach equity)
+    pub max_token_exposure_percent: Decimal,
+
+    /// Maksymalna ekspozycja na grupę skorelowanych tokenów (w procentach equity)
+    pub max_cluster_exposure_percent: Decimal,
+
+    /// Korelacja stóp zwrotu, od której tokeny należą do jednej grupy
+    pub correlation_threshold: Decimal,
+
+    /// Interwał próbkowania cen do korelacji (w sekundach)
+    pub sample_interval: u64,
+
+    /// Liczba próbek w oknie korelacji
+    pub window: usize,
+
+    /// Minimalna liczba wspólnych próbek do wyznaczenia korelacji
+    pub min_samples: usize,
+}
+
+/// Konfiguracja modelu marży
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarginConfig {
+    /// Tryb marży: "isolated" lub "cross"
+    pub mode: String,
+
+    /// Progi marży utrzymaniowej według wartości nominalnej (rosnąco)
+    pub tiers: Vec<MarginTierConfig>,
+
+    /// Opłata likwidacyjna (w procentach wartości nominalnej)
+    pub liquidation_fee_percent: Decimal,
+
+    /// Wskaźnik marży konta (cross), od którego manager ryzyka redukuje pozycje
+    pub deleverage_margin_ratio: Decimal,
+
+    /// Wskaźnik marży, do którego redukowane są pozycje
+    pub deleverage_target_ratio: Decimal,
+}
+
+/// Próg marży utrzymaniowej
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarginTierConfig {
+    /// Górna granica wartości nominalnej pozycji w progu (w USD)
+    pub max_notional: Decimal,
+
+    /// Stawka marży utrzymaniowej (w procentach)
+    pub maintenance_margin_rate: Decimal,
+
+    /// Maksymalna dźwignia dostępna w progu
+    pub max_leverage: u8,
 }
 
 /// Konfiguracja stop-loss
@@ -88,6 +188,63 @@ impl Default for RiskConfig {
             stop_loss: StopLossConfig::default(),
             take_profit: TakeProfitConfig::default(),
             enable_auto_risk_management: true,
+            emergency_stop_on_circuit_breaker: false,
+            margin: MarginConfig::default(),
+            concentration: ConcentrationConfig::default(),
+            trade_limits: TradeLimitsConfig::default(),
+        }
+    }
+}
+
+impl Default for TradeLimitsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_daily_trades: 10,
+            max_daily_trades_per_token: 2,
+            stop_loss_cooldown: 3600, // 1 godzina
+            liquidation_ban: 86400,   // 24 godziny
+        }
+    }
+}
+
+impl Default for ConcentrationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_token_exposure_percent: Decimal::new(50, 2), // 50%
+            max_cluster_exposure_percent: Decimal::new(70, 2), // 70%
+            correlation_threshold: Decimal::new(70, 2),      // 0.7
+            sample_interval: 60,                             // 1 minuta
+            window: 120,                                     // 2 godziny
+            min_samples: 30,
+        }
+    }
+}
+
+impl Default for MarginConfig {
+    fn default() -> Self {
+        // Typowe progi kontraktów USDT-M na altcoiny
+        let tier = |max_notional: i64, rate: i64, max_leverage: u8| MarginTierConfig {
+            max_notional: Decimal::new(max_notional, 0),
+            maintenance_margin_rate: Decimal::new(rate, 3),
+            max_leverage,
+        };
+
+        Self {
+            mode: "isolated".to_string(),
+            tiers: vec![
+                tier(5_000, 5, 50),       // 0.5%
+                tier(25_000, 10, 25),     // 1%
+                tier(100_000, 20, 20),    // 2%
+                tier(250_000, 50, 10),    // 5%
+                tier(1_000_000, 100, 5),  // 10%
+                tier(5_000_000, 125, 4),  // 12.5%
+                tier(10_000_000, 250, 2), // 25%
+            ],
+            liquidation_fee_percent: Decimal::new(5, 3), // 0.5%
+            deleverage_margin_ratio: Decimal::new(80, 2), // 80%
+            deleverage_target_ratio: Decimal::new(50, 2), // 50%
         }
     }
 }
@@ -173,6 +330,93 @@ impl RiskConfig {
         // Walidacja take-profit
         self.take_profit.validate()?;
 
+        // Walidacja modelu marży
+        self.margin.validate()?;
+
+        // Walidacja limitów koncentracji
+        self.concentration.validate()?;
+
+        // Walidacja limitów transakcji
+        self.trade_limits.validate()?;
+
+        Ok(())
+    }
+}
+
+impl MarginConfig {
+    /// Waliduje konfigurację modelu marży
+    pub fn validate(&self) -> Result<()> {
+        if !matches!(self.mode.as_str(), "isolated" | "cross") {
+            anyhow::bail!(
+                "margin mode must be \"isolated\" or \"cross\", got \"{}\"",
+                self.mode
+            );
+        }
+
+        if self.tiers.is_empty() {
+            anyhow::bail!("margin tiers must not be empty");
+        }
+
+        let mut previous: Option<&MarginTierConfig> = None;
+        for tier in &self.tiers {
+            if tier.max_notional <= Decimal::ZERO {
+                anyhow::bail!("margin tier max_notional must be greater than 0");
+            }
+
+            if tier.maintenance_margin_rate <= Decimal::ZERO {
+                anyhow::bail!("margin tier maintenance_margin_rate must be greater than 0");
+            }
+
+            if tier.max_leverage == 0 {
+                anyhow::bail!("margin tier max_leverage must be greater than 0");
+            }
+
+            // Marża utrzymaniowa musi być niższa od początkowej (1 / dźwignia)
+            if tier.maintenance_margin_rate * Decimal::from(tier.max_leverage) >= Decimal::ONE {
+                anyhow::bail!(
+                    "margin tier up to {} USD: maintenance_margin_rate must be below 1 / max_leverage",
+                    tier.max_notional
+                );
+            }
+
+            if let Some(previous) = previous {
+                if tier.max_notional <= previous.max_notional {
+                    anyhow::bail!("margin tiers must be sorted by ascending max_notional");
+                }
+
+                if tier.maintenance_margin_rate < previous.maintenance_margin_rate
+                    || tier.max_leverage > previous.max_leverage
+                {
+                    anyhow::bail!(
+                        "margin tiers must not lower maintenance_margin_rate or raise max_leverage"
+                    );
+                }
+            }
+            previous = Some(tier);
+        }
+
+        if self.liquidation_fee_percent < Decimal::ZERO {
+            anyhow::bail!("liquidation_fee_percent must not be negative");
+        }
+
+        if self.liquidation_fee_percent >= Decimal::new(5, 2) {
+            anyhow::bail!("liquidation_fee_percent should not exceed 5%");
+        }
+
+        if self.deleverage_margin_ratio <= Decimal::ZERO
+            || self.deleverage_margin_ratio >= Decimal::ONE
+        {
+            anyhow::bail!("deleverage_margin_ratio must be between 0 and 100%");
+        }
+
+        if self.deleverage_target_ratio <= Decimal::ZERO
+            || self.deleverage_target_ratio >= self.deleverage_margin_ratio
+        {
+            anyhow::bail!(
+                "deleverage_target_ratio must be greater than 0 and less than deleverage_margin_ratio"
+            );
+        }
+
         Ok(())
     }
 }
@@ -238,3 +482,66 @@ impl TakeProfitConfig {
         Ok(())
     }
 }
+
+impl ConcentrationConfig {
+    /// Waliduje konfigurację limitów koncentracji
+    pub fn validate(&self) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        if self.max_token_exposure_percent <= Decimal::ZERO {
+            anyhow::bail!("max_token_exposure_percent must be greater than 0");
+        }
+
+        if self.max_cluster_exposure_percent < self.max_token_exposure_percent {
+            anyhow::bail!(
+                "max_cluster_exposure_percent must not be less than max_token_exposure_percent"
+            );
+        }
+
+        if self.correlation_threshold <= Decimal::ZERO || self.correlation_threshold > Decimal::ONE
+        {
+            anyhow::bail!("correlation_threshold must be in (0, 1]");
+        }
+
+        if self.sample_interval == 0 {
+            anyhow::bail!("concentration sample_interval must be greater than 0");
+        }
+
+        if self.min_samples < 3 {
+            anyhow::bail!("concentration min_samples must be at least 3");
+        }
+
+        if self.window < self.min_samples {
+            anyhow::bail!("concentration window must not be less than min_samples");
+        }
+
+        Ok(())
+    }
+}
+
+impl TradeLimitsConfig {
+    /// Waliduje konfigurację limitów transakcji
+    pub fn validate(&self) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        if self.max_daily_trades == 0 {
+            anyhow::bail!("trade_limits max_daily_trades must be greater than 0");
+        }
+
+        if self.max_daily_trades_per_token == 0 {
+            anyhow::bail!("trade_limits max_daily_trades_per_token must be greater than 0");
+        }
+
+        if self.max_daily_trades_per_token > self.max_daily_trades {
+            anyhow::bail!(
+                "trade_limits max_daily_trades_per_token must not exceed max_daily_trades"
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+use anyhow::Result;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Konfiguracja przetwarzania sygnałów
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SignalsConfig {
+    /// Interwał odpytywania źródeł (w sekundach)
+    pub poll_interval: u64,
+
+    /// Maksymalny wiek sygnału (w sekundach)
+    pub max_signal_age: i64,
+
+    /// Okno deduplikacji sygnałów o tym samym hashu (w sekundach)
+    pub dedup_window: i64,
+
+    /// Minimalny wynik sygnału (0.0 - 1.0)
+    pub min_score: Decimal,
+
+    /// Domyślna wiarygodność nieznanego źródła (0.0 - 1.0)
+    pub default_source_reliability: Decimal,
+
+    /// Wiarygodność poszczególnych źródeł (0.0 - 1.0)
+    pub source_reliability: HashMap<String, Decimal>,
+
+    /// Źródła webhook (np. alerty TradingView)
+    pub webhooks: Vec<WebhookConfig>,
+
+    /// Kontrola bezpieczeństwa tokena przed oceną ryzyka
+    pub screening: ScreeningConfig,
+}
+
+/// Konfiguracja kontroli bezpieczeństwa tokena (honeypoty, płytkie pule)
+///
+/// Dane tokena pochodzą z RPC łańcucha EVM dla adresu kontraktu z sygnału
+/// (`contract`). Bez skonfigurowanego RPC używane są metadane sygnału
+/// (`liquidity_usd`, `top_holders_percent`, `created_at`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScreeningConfig {
+    /// Czy sprawdzać tokeny
+    pub enabled: bool,
+
+    /// Minimalna płynność puli (w USD)
+    pub min_liquidity_usd: Decimal,
+
+    /// Maksymalny udział największych posiadaczy w podaży (0.0 - 1.0)
+    pub max_top_holders_percent: Decimal,
+
+    /// Minimalny wiek kontraktu (w sekundach)
+    pub min_contract_age: i64,
+
+    /// Czy odrzucać tokeny, dla których nie udało się ustalić parametru
+    pub reject_unknown: bool,
+
+    /// Czas ważności werdyktu on-chain dla kontraktu (w sekundach, 0 = bez pamięci)
+    pub cache_ttl: i64,
+
+    /// Tokeny (symbole lub adresy kontraktów) zawsze odrzucane
+    pub denylist: Vec<String>,
+
+    /// Tokeny (symbole lub adresy kontraktów) przyjmowane bez kontroli
+    pub allowlist: Vec<String>,
+
+    /// Łańcuch kontroli on-chain ("ethereum", "bsc", "polygon")
+    pub chain: String,
+
+    /// Adres RPC (lepiej użyć `rpc_url_env`; brak adresu - bez kontroli on-chain)
+    pub rpc_url: Option<String>,
+
+    /// Zmienna środowiskowa z adresem RPC (ma pierwszeństwo)
+    pub rpc_url_env: Option<String>,
+}
+
+/// Konfiguracja źródła sygnałów typu webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Nazwa źródła (ścieżka `/api/webhooks/<name>` i `Signal::source`)
+    pub name: String,
+
+    /// Sekret HMAC-SHA256 (lepiej użyć `secret_env`)
+    #[serde(default)]
+    pub secret: Option<String>,
+
+    /// Zmienna środowiskowa z sekretem HMAC-SHA256 (ma pierwszeństwo)
+    #[serde(default)]
+    pub secret_env: Option<String>,
+
+    /// Limit żądań na minutę (0 = bez limitu)
+    #[serde(default = "default_webhook_rate_limit")]
+    pub rate_limit_per_minute: u32,
+
+    /// Maksymalna różnica między podpisanym czasem żądania a zegarem (w sekundach)
+    #[serde(default = "default_webhook_max_clock_skew")]
+    pub max_clock_skew: i64,
+
+    /// Maksymalna liczba sygnałów czekających na odpytanie
+    #[serde(default = "default_webhook_max_pending")]
+    pub max_pending: usize,
+
+    /// Mapowanie pól payloadu na sygnał
+    #[serde(default)]
+    pub template: WebhookTemplate,
+}
+
+/// Szablon payloadu webhooka - ścieżki JSON Pointer (RFC 6901)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookTemplate {
+    /// Ścieżka do symbolu tokena
+    pub token: String,
+
+    /// Ścieżka do kierunku (buy/long, sell/short)
+    pub side: String,
+
+    /// Ścieżka do ceny
+    pub price: String,
+
+    /// Ścieżka do poziomu pewności (opcjonalne pole w payloadzie)
+    pub confidence: String,
+
+    /// Ścieżka do wolumenu (opcjonalne pole w payloadzie)
+    pub volume: String,
+
+    /// Pewność używana, gdy payload jej nie zawiera
+    pub default_confidence: String,
+}
+
+fn default_webhook_rate_limit() -> u32 {
+    60
+}
+
+fn default_webhook_max_clock_skew() -> i64 {
+    300
+}
+
+fn default_webhook_max_pending() -> usize {
+    1000
+}
+
+impl Default for WebhookTemplate {
+    fn default() -> Self {
+        Self {
+            token: "/token".to_string(),
+            side: "/side".to_string(),
+            price: "/price".to_string(),
+            confidence: "/confidence".to_string(),
+            volume: "/volume".to_string(),
+            default_confidence: "medium".to_string(),
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Zwraca sekret (zmienna środowiskowa ma pierwszeństwo)
+    pub fn resolve_secret(&self) -> Option<String> {
+        self.secret_env
+            .as_ref()
+            .and_then(|name| std::env::var(name).ok())
+            .or_else(|| self.secret.clone())
+            .filter(|secret| !secret.is_empty())
+    }
+}
+
+impl Default for SignalsConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: 5,
+            max_signal_age: 300,
+            dedup_window: 600,
+            min_score: Decimal::new(25, 2),                 // 0.25
+            default_source_reliability: Decimal::new(5, 1), // 0.5
+            source_reliability: HashMap::new(),
+            webhooks: Vec::new(),
+            screening: ScreeningConfig::default(),
+        }
+    }
+}
+
+impl Default for ScreeningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_liquidity_usd: Decimal::new(10_000, 0),
+            max_top_holders_percent: Decimal::new(50, 2), // 50%
+            min_contract_age: 3600,                       // 1 godzina
+            reject_unknown: false,
+            cache_ttl: 900, // 15 minut
+            denylist: Vec::new(),
+            allowlist: Vec::new(),
+            chain: "ethereum".to_string(),
+            rpc_url: None,
+            rpc_url_env: None,
+        }
+    }
+}
+
+impl ScreeningConfig {
+    /// Zwraca adres RPC (zmienna środowiskowa ma pierwszeństwo)
+    pub fn resolve_rpc_url(&self) -> Option<String> {
+        self.rpc_url_env
+            .as_ref()
+            .and_then(|name| std::env::var(name).ok())
+            .or_else(|| self.rpc_url.clone())
+            .filter(|url| !url.is_empty())
+    }
+
+    /// Waliduje konfigurację kontroli tokenów
+    pub fn validate(&self) -> Result<()> {
+        if self.min_liquidity_usd < Decimal::ZERO {
+            anyhow::bail!("signals screening min_liquidity_usd cannot be negative");
+        }
+
+        if self.max_top_holders_percent <= Decimal::ZERO
+            || self.max_top_holders_percent > Decimal::ONE
+        {
+            anyhow::bail!("signals screening max_top_holders_percent must be in (0, 1]");
+        }
+
+        if self.min_contract_age < 0 {
+            anyhow::bail!("signals screening min_contract_age cannot be negative");
+        }
+
+        if self.cache_ttl < 0 {
+            anyhow::bail!("signals screening cache_ttl cannot be negative");
+        }
+
+        if !matches!(self.chain.as_str(), "ethereum" | "bsc" | "polygon") {
+            anyhow::bail!("signals screening chain must be ethereum, bsc or polygon");
+        }
+
+        if let Some(token) = self
+            .denylist
+            .iter()
+            .find(|token| self.allowlist.iter().any(|t| t.eq_ignore_ascii_case(token)))
+        {
+            anyhow::bail!(
+                "signals screening token {} is both allowlisted and denylisted",
+                token
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl SignalsConfig {
+    /// Waliduje konfigurację sygnałów
+    pub fn validate(&self) -> Result<()> {
+        if self.poll_interval == 0 {
+            anyhow::bail!("signals poll_interval must be greater than 0");
+        }
+
+        if self.max_signal_age <= 0 {
+            anyhow::bail!("signals max_signal_age must be greater than 0");
+        }
+
+        if self.dedup_window < 0 {
+            anyhow::bail!("signals dedup_window cannot be negative");
+        }
+
+        if self.min_score < Decimal::ZERO || self.min_score > Decimal::ONE {
+            anyhow::bail!("signals min_score must be between 0 and 1");
+        }
+
+        let reliabilities = std::iter::once(("default", &self.default_source_reliability)).chain(
+            self.source_reliability
+                .iter()
+                .map(|(source, value)| (source.as_str(), value)),
+        );
+        for (source, value) in reliabilities {
+            if *value < Decimal::ZERO || *value > Decimal::ONE {
+                anyhow::bail!(
+                    "signals reliability for source {} must be between 0 and 1",
+                    source
+                );
+            }
+        }
+
+        self.screening.validate()?;
+
+        let mut names = std::collections::HashSet::new();
+        for webhook in &self.webhooks {
+            if webhook.name.is_empty() {
+                anyhow::bail!("signals webhook name cannot be empty");
+            }
+
+            if !names.insert(webhook.name.as_str()) {
+                anyhow::bail!("signals webhook {} is defined twice", webhook.name);
+            }
+
+            webhook
+                .template
+                .default_confidence
+                .parse::<crate::signals::Confidence>()
+                .map_err(|e| anyhow::anyhow!("signals webhook {}: {}", webhook.name, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Zwraca wiarygodność źródła
+    pub fn reliability_for(&self, source: &str) -> Decimal {
+        self.source_reliability
+            .get(source)
+            .copied()
+            .unwrap_or(self.default_source_reliability)
+    }
+}
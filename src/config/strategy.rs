@@ -0,0 +1,193 @@
+use anyhow::Result;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::RiskConfig;
+
+/// Konfiguracja profili strategii zależnych od fazy (wielkości portfela)
+///
+/// Aktywna faza wynika z equity portfela; faza zastępuje limity rozmiaru
+/// pozycji, dźwigni, poziomy stop-loss/take-profit, dzienny limit transakcji
+/// i minimalną pewność sygnału.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyConfig {
+    /// Czy przełączać parametry według fazy
+    pub enabled: bool,
+
+    /// Histereza granic faz (w procentach granicy)
+    pub hysteresis_percent: Decimal,
+
+    /// Fazy posortowane rosnąco według `min_equity`
+    pub phases: Vec<PhaseConfig>,
+}
+
+/// Profil parametrów fazy
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PhaseConfig {
+    /// Nazwa fazy (np. "survival")
+    pub name: String,
+
+    /// Equity, od którego zaczyna się faza (w USD)
+    pub min_equity: Decimal,
+
+    /// Maksymalny rozmiar pozycji (w procentach equity)
+    pub max_position_size_percent: Decimal,
+
+    /// Mnożnik dźwigni z `trading.leverage_config`
+    pub leverage_multiplier: Decimal,
+
+    /// Maksymalna dźwignia w fazie
+    pub max_leverage: u8,
+
+    /// Poziom stop-loss (w procentach zwrotu z marży)
+    pub stop_loss_percent: Decimal,
+
+    /// Poziom take-profit (w procentach zwrotu z marży)
+    pub take_profit_percent: Decimal,
+
+    /// Maksymalna liczba otwartych pozycji na dzień UTC
+    pub max_daily_trades: u32,
+
+    /// Minimalna pewność sygnału ("low", "medium", "high", "extreme")
+    pub min_confidence: String,
+}
+
+impl Default for StrategyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hysteresis_percent: Decimal::new(5, 2), // 5%
+            phases: vec![
+                PhaseConfig {
+                    name: "survival".to_string(),
+                    min_equity: Decimal::ZERO,
+                    max_position_size_percent: Decimal::new(10, 2), // 10%
+                    leverage_multiplier: Decimal::new(4, 1),
+                    max_leverage: 2,
+                    stop_loss_percent: Decimal::new(20, 2), // 20%
+                    take_profit_percent: Decimal::new(50, 2), // 50%
+                    max_daily_trades: 3,
+                    min_confidence: "high".to_string(),
+                },
+                PhaseConfig {
+                    name: "building".to_string(),
+                    min_equity: Decimal::new(100, 0),
+                    max_position_size_percent: Decimal::new(20, 2), // 20%
+                    leverage_multiplier: Decimal::new(7, 1),
+                    max_leverage: 10,
+                    stop_loss_percent: Decimal::new(15, 2), // 15%
+                    take_profit_percent: Decimal::new(40, 2), // 40%
+                    max_daily_trades: 5,
+                    min_confidence: "medium".to_string(),
+                },
+                PhaseConfig {
+                    name: "acceleration".to_string(),
+                    min_equity: Decimal::new(300, 0),
+                    max_position_size_percent: Decimal::new(33, 2), // 33%
+                    leverage_multiplier: Decimal::ONE,
+                    max_leverage: 20,
+                    stop_loss_percent: Decimal::new(10, 2), // 10%
+                    take_profit_percent: Decimal::new(30, 2), // 30%
+                    max_daily_trades: 3,
+                    min_confidence: "high".to_string(),
+                },
+            ],
+        }
+    }
+}
+
+impl StrategyConfig {
+    /// Waliduje konfigurację faz
+    pub fn validate(&self) -> Result<()> {
+        if self.hysteresis_percent < Decimal::ZERO || self.hysteresis_percent >= Decimal::new(5, 1)
+        {
+            anyhow::bail!("strategy hysteresis_percent must be in [0, 0.5)");
+        }
+
+        if self.enabled && self.phases.is_empty() {
+            anyhow::bail!("strategy phases cannot be empty when enabled");
+        }
+
+        for (i, phase) in self.phases.iter().enumerate() {
+            phase.validate()?;
+
+            if self.phases[..i].iter().any(|p| p.name == phase.name) {
+                anyhow::bail!("strategy phase \"{}\" is defined twice", phase.name);
+            }
+
+            if i > 0 && phase.min_equity <= self.phases[i - 1].min_equity {
+                anyhow::bail!("strategy phases must be sorted by strictly increasing min_equity");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl PhaseConfig {
+    /// Konfiguracja ryzyka z poziomami stop-loss i take-profit fazy
+    pub fn risk_config(&self, base: &RiskConfig) -> RiskConfig {
+        let mut risk = base.clone();
+        risk.stop_loss.default_percent = self.stop_loss_percent;
+        risk.take_profit.default_percent = self.take_profit_percent;
+        risk
+    }
+
+    /// Waliduje profil fazy
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            anyhow::bail!("strategy phase name cannot be empty");
+        }
+
+        if self.min_equity < Decimal::ZERO {
+            anyhow::bail!("phase {}: min_equity cannot be negative", self.name);
+        }
+
+        if self.max_position_size_percent <= Decimal::ZERO
+            || self.max_position_size_percent > Decimal::ONE
+        {
+            anyhow::bail!(
+                "phase {}: max_position_size_percent must be in (0, 1]",
+                self.name
+            );
+        }
+
+        if self.leverage_multiplier <= Decimal::ZERO {
+            anyhow::bail!(
+                "phase {}: leverage_multiplier must be greater than 0",
+                self.name
+            );
+        }
+
+        if self.max_leverage == 0 || self.max_leverage > 100 {
+            anyhow::bail!("phase {}: max_leverage must be in [1, 100]", self.name);
+        }
+
+        if self.stop_loss_percent <= Decimal::ZERO || self.take_profit_percent <= Decimal::ZERO {
+            anyhow::bail!(
+                "phase {}: stop_loss_percent and take_profit_percent must be greater than 0",
+                self.name
+            );
+        }
+
+        if self.max_daily_trades == 0 {
+            anyhow::bail!(
+                "phase {}: max_daily_trades must be greater than 0",
+                self.name
+            );
+        }
+
+        if !matches!(
+            self.min_confidence.as_str(),
+            "low" | "medium" | "high" | "extreme"
+        ) {
+            anyhow::bail!(
+                "phase {}: min_confidence must be low, medium, high or extreme",
+                self.name
+            );
+        }
+
+        Ok(())
+    }
+}
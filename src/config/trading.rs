@@ -1,6 +1,7 @@
 use anyhow::Result;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Konfiguracja systemu tradingu
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +35,23 @@ pub struct TradingConfig {
 
     /// Konfiguracja dźwigni dla różnych poziomów pewności
     pub leverage_config: LeverageConfig,
+
+    /// Konfiguracja symulatora giełdy (paper trading)
+    #[serde(default)]
+    pub paper: PaperTradingConfig,
+
+    /// Konfiguracja strumienia cen w czasie rzeczywistym
+    #[serde(default)]
+    pub price_feed: PriceFeedConfig,
+
+    /// Polityki wyznaczania rozmiaru pozycji
+    #[serde(default)]
+    pub sizing: SizingConfig,
+
+    /// Czy zamykać pozycje i anulować zlecenia przy zwykłym zamknięciu aplikacji
+    /// (domyślnie pozycje przetrwają restart)
+    #[serde(default)]
+    pub flatten_on_shutdown: bool,
 }
 
 /// Konfiguracja dźwigni dla różnych poziomów pewności sygnałów
@@ -52,6 +70,87 @@ pub struct LeverageConfig {
     pub extreme_confidence: u8,
 }
 
+/// Konfiguracja symulatora giełdy używanego w trybie paper trading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaperTradingConfig {
+    /// Poślizg cenowy dla zleceń rynkowych (w procentach)
+    pub slippage_percent: Decimal,
+
+    /// Opłata taker (w procentach wartości zlecenia)
+    pub taker_fee_percent: Decimal,
+
+    /// Opłata maker dla zleceń limit (w procentach wartości zlecenia)
+    pub maker_fee_percent: Decimal,
+
+    /// Stawka finansowania za okres (dodatnia = Long płaci Short)
+    pub funding_rate_percent: Decimal,
+
+    /// Co ile godzin rozliczane jest finansowanie
+    pub funding_interval_hours: u64,
+}
+
+/// Konfiguracja polityk rozmiaru pozycji
+///
+/// Strategię identyfikuje źródło sygnału (`Signal::source`); źródła bez
+/// własnej polityki używają polityki domyślnej. Wynik zawsze ogranicza
+/// `max_position_size_percent` i dostępna marża.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SizingConfig {
+    /// Polityka domyślna
+    pub default: SizingPolicyConfig,
+
+    /// Polityki dla poszczególnych strategii (źródeł sygnałów)
+    pub strategies: HashMap<String, SizingPolicyConfig>,
+}
+
+/// Polityka rozmiaru pozycji
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SizingPolicyConfig {
+    /// "fixed_fractional", "atr" lub "kelly"
+    pub policy: String,
+
+    /// Stały ułamek equity jako wartość nominalna (także fallback pozostałych polityk)
+    pub fraction: Decimal,
+
+    /// Ryzyko na transakcję przy odległości `atr_multiple` ATR (w procentach equity)
+    pub risk_per_trade_percent: Decimal,
+
+    /// Wielokrotność ATR traktowana jako oczekiwany niekorzystny ruch
+    pub atr_multiple: Decimal,
+
+    /// Liczba okresów ATR
+    pub atr_periods: usize,
+
+    /// Ułamek pełnego kryterium Kelly'ego (0.5 = half Kelly)
+    pub kelly_fraction: Decimal,
+
+    /// Minimalna liczba zamkniętych transakcji do użycia Kelly'ego
+    pub min_trades: u64,
+}
+
+/// Konfiguracja serwisu cen (strumień WebSocket z fallbackiem REST)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriceFeedConfig {
+    /// Czy uruchomić strumień cen
+    pub enabled: bool,
+
+    /// Wiek ceny, po którym uznaje się ją za nieaktualną (w sekundach)
+    pub max_price_age: u64,
+
+    /// Opóźnienie ponownego połączenia strumienia (w sekundach)
+    pub reconnect_delay: u64,
+
+    /// Interwał odpytywania fallbacku CoinStats (w sekundach)
+    pub fallback_poll_interval: u64,
+
+    /// Zmienna środowiskowa z kluczem API CoinStats (bez klucza brak fallbacku)
+    pub coinstats_api_key_env: Option<String>,
+}
+
 impl Default for TradingConfig {
     fn default() -> Self {
         Self {
@@ -65,6 +164,48 @@ impl Default for TradingConfig {
             position_check_interval: 5,
             paper_trading: true, // Domyślnie tryb testowy
             leverage_config: LeverageConfig::default(),
+            paper: PaperTradingConfig::default(),
+            price_feed: PriceFeedConfig::default(),
+            sizing: SizingConfig::default(),
+            flatten_on_shutdown: false,
+        }
+    }
+}
+
+impl Default for SizingPolicyConfig {
+    fn default() -> Self {
+        Self {
+            policy: "fixed_fractional".to_string(),
+            fraction: Decimal::new(33, 2),              // 33%
+            risk_per_trade_percent: Decimal::new(1, 2), // 1%
+            atr_multiple: Decimal::new(2, 0),
+            atr_periods: 14,
+            kelly_fraction: Decimal::new(5, 1), // half Kelly
+            min_trades: 20,
+        }
+    }
+}
+
+impl Default for PriceFeedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_price_age: 30,
+            reconnect_delay: 5,
+            fallback_poll_interval: 15,
+            coinstats_api_key_env: Some("COINSTATS_API_KEY".to_string()),
+        }
+    }
+}
+
+impl Default for PaperTradingConfig {
+    fn default() -> Self {
+        Self {
+            slippage_percent: Decimal::new(1, 3),     // 0.1%
+            taker_fee_percent: Decimal::new(6, 4),    // 0.06%
+            maker_fee_percent: Decimal::new(2, 4),    // 0.02%
+            funding_rate_percent: Decimal::new(1, 4), // 0.01%
+            funding_interval_hours: 8,
         }
     }
 }
@@ -135,6 +276,15 @@ impl TradingConfig {
         // Walidacja konfiguracji dźwigni
         self.leverage_config.validate(self.max_leverage)?;
 
+        // Walidacja symulatora giełdy
+        self.paper.validate()?;
+
+        // Walidacja strumienia cen
+        self.price_feed.validate()?;
+
+        // Walidacja polityk rozmiaru pozycji
+        self.sizing.validate()?;
+
         Ok(())
     }
 
@@ -190,3 +340,114 @@ impl LeverageConfig {
         Ok(())
     }
 }
+
+impl PaperTradingConfig {
+    /// Waliduje konfigurację symulatora giełdy
+    pub fn validate(&self) -> Result<()> {
+        if self.slippage_percent < Decimal::ZERO {
+            anyhow::bail!("paper slippage_percent cannot be negative");
+        }
+
+        if self.slippage_percent >= Decimal::new(10, 2) {
+            anyhow::bail!("paper slippage_percent should not exceed 10%");
+        }
+
+        if self.taker_fee_percent < Decimal::ZERO || self.maker_fee_percent < Decimal::ZERO {
+            anyhow::bail!("paper fees cannot be negative");
+        }
+
+        if self.taker_fee_percent >= Decimal::new(1, 2)
+            || self.maker_fee_percent >= Decimal::new(1, 2)
+        {
+            anyhow::bail!("paper fees should not exceed 1%");
+        }
+
+        if self.funding_rate_percent.abs() >= Decimal::new(1, 2) {
+            anyhow::bail!("paper funding_rate_percent should not exceed 1% per period");
+        }
+
+        if self.funding_interval_hours == 0 || self.funding_interval_hours > 24 {
+            anyhow::bail!("paper funding_interval_hours must be between 1 and 24");
+        }
+
+        Ok(())
+    }
+}
+
+impl PriceFeedConfig {
+    /// Waliduje konfigurację strumienia cen
+    pub fn validate(&self) -> Result<()> {
+        if self.max_price_age == 0 {
+            anyhow::bail!("price_feed max_price_age must be greater than 0");
+        }
+
+        if self.reconnect_delay == 0 {
+            anyhow::bail!("price_feed reconnect_delay must be greater than 0");
+        }
+
+        if self.fallback_poll_interval == 0 {
+            anyhow::bail!("price_feed fallback_poll_interval must be greater than 0");
+        }
+
+        if self.fallback_poll_interval > self.max_price_age {
+            anyhow::bail!("price_feed fallback_poll_interval should not exceed max_price_age");
+        }
+
+        Ok(())
+    }
+}
+
+impl SizingConfig {
+    /// Waliduje politykę domyślną i polityki strategii
+    pub fn validate(&self) -> Result<()> {
+        self.default.validate("default")?;
+        for (strategy, policy) in &self.strategies {
+            policy.validate(strategy)?;
+        }
+        Ok(())
+    }
+}
+
+impl SizingPolicyConfig {
+    /// Waliduje politykę rozmiaru pozycji (`name` - nazwa w komunikatach)
+    pub fn validate(&self, name: &str) -> Result<()> {
+        if !matches!(self.policy.as_str(), "fixed_fractional" | "atr" | "kelly") {
+            anyhow::bail!(
+                "sizing {}: unknown policy \"{}\" (expected fixed_fractional, atr or kelly)",
+                name,
+                self.policy
+            );
+        }
+
+        if self.fraction <= Decimal::ZERO || self.fraction > Decimal::ONE {
+            anyhow::bail!("sizing {}: fraction must be in (0, 1]", name);
+        }
+
+        if self.risk_per_trade_percent <= Decimal::ZERO
+            || self.risk_per_trade_percent > Decimal::new(10, 2)
+        {
+            anyhow::bail!(
+                "sizing {}: risk_per_trade_percent must be in (0, 10%]",
+                name
+            );
+        }
+
+        if self.atr_multiple <= Decimal::ZERO {
+            anyhow::bail!("sizing {}: atr_multiple must be greater than 0", name);
+        }
+
+        if self.atr_periods < 2 {
+            anyhow::bail!("sizing {}: atr_periods must be at least 2", name);
+        }
+
+        if self.kelly_fraction <= Decimal::ZERO || self.kelly_fraction > Decimal::ONE {
+            anyhow::bail!("sizing {}: kelly_fraction must be in (0, 1]", name);
+        }
+
+        if self.min_trades == 0 {
+            anyhow::bail!("sizing {}: min_trades must be greater than 0", name);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,687 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, Sqlite, SqliteConnection, SqlitePool};
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::risk::{Position, PositionStatus, TradeSide};
+use crate::trading::{ExecutionResult, FundingPayment, OrderStatus, OrderType, TradeOrder};
+
+/// Domyślny rozmiar strony
+const DEFAULT_PAGE_LIMIT: u32 = 50;
+
+/// Maksymalny rozmiar strony
+const MAX_PAGE_LIMIT: u32 = 500;
+
+/// Zapytanie o stronę historii z opcjonalnym zakresem czasu
+#[derive(Debug, Clone, Default)]
+pub struct LedgerQuery {
+    /// Numer strony (od 1)
+    pub page: Option<u32>,
+
+    /// Liczba wpisów na stronie
+    pub limit: Option<u32>,
+
+    /// Początek zakresu (Unix timestamp, włącznie)
+    pub from: Option<i64>,
+
+    /// Koniec zakresu (Unix timestamp, włącznie)
+    pub to: Option<i64>,
+}
+
+impl LedgerQuery {
+    pub(super) fn page(&self) -> u32 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub(super) fn limit(&self) -> u32 {
+        self.limit
+            .unwrap_or(DEFAULT_PAGE_LIMIT)
+            .clamp(1, MAX_PAGE_LIMIT)
+    }
+
+    pub(super) fn offset(&self) -> i64 {
+        ((self.page() - 1) * self.limit()) as i64
+    }
+
+    pub(super) fn range_start(&self) -> i64 {
+        self.from.unwrap_or(i64::MIN)
+    }
+
+    pub(super) fn range_end(&self) -> i64 {
+        self.to.unwrap_or(i64::MAX)
+    }
+}
+
+/// Strona wyników
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerPage<T> {
+    /// Wpisy na stronie
+    pub items: Vec<T>,
+
+    /// Całkowita liczba wpisów spełniających kryteria
+    pub total: u64,
+
+    /// Numer strony
+    pub page: u32,
+
+    /// Rozmiar strony
+    pub limit: u32,
+}
+
+/// Zapisane zlecenie wraz z aktualnym statusem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderRecord {
+    pub order: TradeOrder,
+    pub status: OrderStatus,
+    pub updated_at: i64,
+}
+
+/// Zmiana statusu zlecenia
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderEvent {
+    pub order_id: String,
+    pub status: OrderStatus,
+    pub message: Option<String>,
+    pub created_at: i64,
+}
+
+/// Wykonanie zlecenia (fill lub odrzucenie)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeRecord {
+    pub id: i64,
+    pub order_id: String,
+    pub token: String,
+    pub side: TradeSide,
+    pub order_type: OrderType,
+    pub leverage: u8,
+    pub result: ExecutionResult,
+}
+
+/// Zapisana pozycja wraz z wynikiem zamknięcia
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionRecord {
+    pub position: Position,
+    pub exit_price: Option<f64>,
+    pub realized_pnl: Option<f64>,
+    pub updated_at: i64,
+}
+
+/// Trwały rejestr zleceń, wykonań i pozycji
+pub struct TradeLedger {
+    db: Arc<SqlitePool>,
+}
+
+impl TradeLedger {
+    /// Tworzy rejestr na istniejącej puli połączeń (tabele tworzą migracje)
+    pub fn new(db: Arc<SqlitePool>) -> Self {
+        Self { db }
+    }
+
+    /// Zapisuje zlecenie z podanym statusem (lub aktualizuje istniejące)
+    pub async fn record_order(&self, order: &TradeOrder, status: &OrderStatus) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+
+        Self::insert_order(&*self.db, order, status, now, true).await?;
+        Self::insert_order_event(&*self.db, &order.id, status, None, now).await
+    }
+
+    /// Zapisuje zmianę statusu zlecenia
+    pub async fn update_order_status(
+        &self,
+        order_id: &str,
+        status: &OrderStatus,
+        message: Option<&str>,
+    ) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+        Self::set_order_status(&mut tx, order_id, status, message).await?;
+        tx.commit().await.context("Failed to commit order status")?;
+        Ok(())
+    }
+
+    /// Wstawia zlecenie; istniejące aktualizuje (`update_existing`) lub pomija.
+    /// Zwraca `true`, gdy wiersz został zapisany.
+    async fn insert_order<'e, E>(
+        executor: E,
+        order: &TradeOrder,
+        status: &OrderStatus,
+        now: i64,
+        update_existing: bool,
+    ) -> Result<bool>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        let on_conflict = if update_existing {
+            "DO UPDATE SET status = excluded.status, updated_at = excluded.updated_at"
+        } else {
+            "DO NOTHING"
+        };
+        let sql = format!(
+            r#"
+            INSERT INTO trade_orders
+            (id, token, side, size, leverage, order_type, price, stop_price, status, timeout,
+             created_at, updated_at, metadata)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) {}
+            "#,
+            on_conflict
+        );
+
+        let inserted = sqlx::query(&sql)
+            .bind(&order.id)
+            .bind(&order.token)
+            .bind(serde_json::to_string(&order.side)?)
+            .bind(order.size)
+            .bind(order.leverage as i64)
+            .bind(serde_json::to_string(&order.order_type)?)
+            .bind(order.price)
+            .bind(order.stop_price)
+            .bind(serde_json::to_string(status)?)
+            .bind(order.timeout as i64)
+            .bind(order.created_at)
+            .bind(now)
+            .bind(serde_json::to_string(&order.metadata)?)
+            .execute(executor)
+            .await
+            .context("Failed to save order to database")?;
+
+        Ok(inserted.rows_affected() > 0)
+    }
+
+    async fn set_order_status(
+        conn: &mut SqliteConnection,
+        order_id: &str,
+        status: &OrderStatus,
+        message: Option<&str>,
+    ) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+
+        let updated =
+            sqlx::query("UPDATE trade_orders SET status = ?, updated_at = ? WHERE id = ?")
+                .bind(serde_json::to_string(status)?)
+                .bind(now)
+                .bind(order_id)
+                .execute(&mut *conn)
+                .await
+                .context("Failed to update order status")?;
+
+        if updated.rows_affected() == 0 {
+            anyhow::bail!("Order {} not found in ledger", order_id);
+        }
+
+        Self::insert_order_event(&mut *conn, order_id, status, message, now).await
+    }
+
+    async fn insert_order_event<'e, E>(
+        executor: E,
+        order_id: &str,
+        status: &OrderStatus,
+        message: Option<&str>,
+        created_at: i64,
+    ) -> Result<()>
+    where
+        E: sqlx::Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query(
+            "INSERT INTO order_events (order_id, status, message, created_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(order_id)
+        .bind(serde_json::to_string(status)?)
+        .bind(message)
+        .bind(created_at)
+        .execute(executor)
+        .await
+        .context("Failed to save order event")?;
+
+        debug!("Order {} -> {:?}", order_id, status);
+        Ok(())
+    }
+
+    /// Zapisuje wynik wykonania i odpowiadającą mu zmianę statusu zlecenia
+    ///
+    /// Zlecenie nieznane rejestrowi zapisywane jest najpierw jako `Pending`;
+    /// istniejącemu nie zmienia się statusu przed wykonaniem. Zlecenie
+    /// zaakceptowane do księgi (bez ceny wykonania) nie trafia do historii
+    /// wykonań. Cały zapis odbywa się w jednej transakcji.
+    pub async fn record_execution(
+        &self,
+        order: &TradeOrder,
+        result: &ExecutionResult,
+    ) -> Result<()> {
+        let status = if !result.success {
+            OrderStatus::Rejected
+        } else if result.executed_price.is_none() {
+            OrderStatus::Pending
+        } else if result.is_partial(order.size) {
+            OrderStatus::PartiallyFilled
+        } else {
+            OrderStatus::Filled
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let mut tx = self.db.begin().await?;
+
+        if Self::insert_order(&mut *tx, order, &OrderStatus::Pending, now, false).await? {
+            Self::insert_order_event(&mut *tx, &order.id, &OrderStatus::Pending, None, now).await?;
+        }
+
+        if status != OrderStatus::Pending {
+            sqlx::query(
+                r#"
+                INSERT INTO trade_executions
+                (order_id, transaction_id, success, executed_price, executed_size, fees,
+                 executed_at, error_message, metadata)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&order.id)
+            .bind(&result.transaction_id)
+            .bind(result.success)
+            .bind(result.executed_price)
+            .bind(result.executed_size)
+            .bind(result.fees)
+            .bind(result.executed_at.unwrap_or(now))
+            .bind(&result.error_message)
+            .bind(serde_json::to_string(&result.metadata)?)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to save execution to database")?;
+
+            Self::set_order_status(&mut tx, &order.id, &status, result.error_message.as_deref())
+                .await?;
+        }
+
+        tx.commit().await.context("Failed to commit execution")?;
+        Ok(())
+    }
+
+    /// Zapisuje (lub aktualizuje) stan pozycji
+    pub async fn save_position(&self, position: &Position) -> Result<()> {
+        self.upsert_position(position, None, None).await
+    }
+
+    /// Zapisuje zamknięcie pozycji wraz z ceną wyjścia i zrealizowanym P&L
+    pub async fn record_position_close(
+        &self,
+        position: &Position,
+        exit_price: f64,
+        realized_pnl: f64,
+    ) -> Result<()> {
+        self.upsert_position(position, Some(exit_price), Some(realized_pnl))
+            .await
+    }
+
+    async fn upsert_position(
+        &self,
+        position: &Position,
+        exit_price: Option<f64>,
+        realized_pnl: Option<f64>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO positions
+            (id, token, side, size, leverage, entry_price, current_price, pnl, liquidation_price,
+             status, exit_price, realized_pnl, opened_at, closed_at, updated_at,
+             fees_paid, funding_paid, realized_costs, last_funding_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                size = excluded.size,
+                current_price = excluded.current_price,
+                pnl = excluded.pnl,
+                liquidation_price = excluded.liquidation_price,
+                fees_paid = excluded.fees_paid,
+                funding_paid = excluded.funding_paid,
+                realized_costs = excluded.realized_costs,
+                last_funding_at = excluded.last_funding_at,
+                status = excluded.status,
+                exit_price = COALESCE(excluded.exit_price, positions.exit_price),
+                realized_pnl = COALESCE(excluded.realized_pnl, positions.realized_pnl),
+                closed_at = excluded.closed_at,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&position.id)
+        .bind(&position.token)
+        .bind(serde_json::to_string(&position.side)?)
+        .bind(position.size)
+        .bind(position.leverage as i64)
+        .bind(position.entry_price)
+        .bind(position.current_price)
+        .bind(position.pnl)
+        .bind(position.liquidation_price)
+        .bind(serde_json::to_string(&position.status)?)
+        .bind(exit_price)
+        .bind(realized_pnl)
+        .bind(position.opened_at)
+        .bind(position.closed_at)
+        .bind(chrono::Utc::now().timestamp())
+        .bind(position.fees_paid)
+        .bind(position.funding_paid)
+        .bind(position.realized_costs)
+        .bind(position.last_funding_at)
+        .execute(&*self.db)
+        .await
+        .context("Failed to save position to database")?;
+
+        Ok(())
+    }
+
+    /// Zapisuje rozliczoną płatność finansowania
+    ///
+    /// Zwraca `false`, jeśli płatność za ten okres była już zapisana.
+    pub async fn record_funding(&self, payment: &FundingPayment) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO funding_events
+            (position_id, token, rate, notional, amount, settled_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&payment.position_id)
+        .bind(&payment.token)
+        .bind(payment.rate)
+        .bind(payment.notional)
+        .bind(payment.amount)
+        .bind(payment.settled_at)
+        .execute(&*self.db)
+        .await
+        .context("Failed to save funding event to database")?;
+
+        debug!(
+            "Funding {:.6} recorded for position {}",
+            payment.amount, payment.position_id
+        );
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Zwraca płatności finansowania pozycji (od najstarszej)
+    pub async fn position_funding(&self, position_id: &str) -> Result<Vec<FundingPayment>> {
+        let rows =
+            sqlx::query("SELECT * FROM funding_events WHERE position_id = ? ORDER BY settled_at")
+                .bind(position_id)
+                .fetch_all(&*self.db)
+                .await
+                .context("Failed to load funding events")?;
+
+        Ok(rows
+            .iter()
+            .map(|row| FundingPayment {
+                position_id: row.get("position_id"),
+                token: row.get("token"),
+                rate: row.get("rate"),
+                notional: row.get("notional"),
+                amount: row.get("amount"),
+                settled_at: row.get("settled_at"),
+            })
+            .collect())
+    }
+
+    /// Ładuje otwarte pozycje (np. po restarcie)
+    pub async fn load_open_positions(&self) -> Result<Vec<Position>> {
+        let rows = sqlx::query("SELECT * FROM positions WHERE status = ? ORDER BY opened_at")
+            .bind(serde_json::to_string(&PositionStatus::Open)?)
+            .fetch_all(&*self.db)
+            .await
+            .context("Failed to load open positions")?;
+
+        rows.iter()
+            .map(|row| Self::position_from_row(row).map(|record| record.position))
+            .collect()
+    }
+
+    /// Zrealizowany P&L zamkniętych pozycji w kolejności zamknięcia
+    pub async fn closed_position_pnls(&self) -> Result<Vec<f64>> {
+        sqlx::query_scalar(
+            r#"SELECT realized_pnl FROM positions
+               WHERE status = ? AND realized_pnl IS NOT NULL
+               ORDER BY closed_at, id"#,
+        )
+        .bind(serde_json::to_string(&PositionStatus::Closed)?)
+        .fetch_all(&*self.db)
+        .await
+        .context("Failed to load closed positions")
+    }
+
+    /// Zwraca pozycję według ID
+    pub async fn get_position(&self, position_id: &str) -> Result<Option<PositionRecord>> {
+        let row = sqlx::query("SELECT * FROM positions WHERE id = ?")
+            .bind(position_id)
+            .fetch_optional(&*self.db)
+            .await
+            .context("Failed to load position")?;
+
+        row.as_ref().map(Self::position_from_row).transpose()
+    }
+
+    /// Zwraca stronę pozycji (opcjonalnie o podanym statusie) w zadanym zakresie czasu
+    pub async fn list_positions(
+        &self,
+        status: Option<PositionStatus>,
+        query: &LedgerQuery,
+    ) -> Result<LedgerPage<PositionRecord>> {
+        let status = status.map(|s| serde_json::to_string(&s)).transpose()?;
+
+        let total: i64 = sqlx::query_scalar(
+            r#"SELECT COUNT(*) FROM positions
+               WHERE opened_at BETWEEN ? AND ? AND (? IS NULL OR status = ?)"#,
+        )
+        .bind(query.range_start())
+        .bind(query.range_end())
+        .bind(&status)
+        .bind(&status)
+        .fetch_one(&*self.db)
+        .await
+        .context("Failed to count positions")?;
+
+        let rows = sqlx::query(
+            r#"SELECT * FROM positions
+               WHERE opened_at BETWEEN ? AND ? AND (? IS NULL OR status = ?)
+               ORDER BY opened_at DESC, id LIMIT ? OFFSET ?"#,
+        )
+        .bind(query.range_start())
+        .bind(query.range_end())
+        .bind(&status)
+        .bind(&status)
+        .bind(query.limit() as i64)
+        .bind(query.offset())
+        .fetch_all(&*self.db)
+        .await
+        .context("Failed to list positions")?;
+
+        Ok(LedgerPage {
+            items: rows
+                .iter()
+                .map(Self::position_from_row)
+                .collect::<Result<_>>()?,
+            total: total as u64,
+            page: query.page(),
+            limit: query.limit(),
+        })
+    }
+
+    /// Zwraca stronę wykonań w zadanym zakresie czasu (najnowsze pierwsze)
+    pub async fn list_trades(&self, query: &LedgerQuery) -> Result<LedgerPage<TradeRecord>> {
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM trade_executions WHERE executed_at BETWEEN ? AND ?",
+        )
+        .bind(query.range_start())
+        .bind(query.range_end())
+        .fetch_one(&*self.db)
+        .await
+        .context("Failed to count trades")?;
+
+        let rows = sqlx::query(
+            r#"SELECT e.*, o.token, o.side, o.order_type, o.leverage
+               FROM trade_executions e JOIN trade_orders o ON o.id = e.order_id
+               WHERE e.executed_at BETWEEN ? AND ?
+               ORDER BY e.executed_at DESC, e.id DESC LIMIT ? OFFSET ?"#,
+        )
+        .bind(query.range_start())
+        .bind(query.range_end())
+        .bind(query.limit() as i64)
+        .bind(query.offset())
+        .fetch_all(&*self.db)
+        .await
+        .context("Failed to list trades")?;
+
+        Ok(LedgerPage {
+            items: rows
+                .iter()
+                .map(Self::trade_from_row)
+                .collect::<Result<_>>()?,
+            total: total as u64,
+            page: query.page(),
+            limit: query.limit(),
+        })
+    }
+
+    /// Zwraca zlecenie według ID
+    pub async fn get_order(&self, order_id: &str) -> Result<Option<OrderRecord>> {
+        let row = sqlx::query("SELECT * FROM trade_orders WHERE id = ?")
+            .bind(order_id)
+            .fetch_optional(&*self.db)
+            .await
+            .context("Failed to load order")?;
+
+        row.as_ref().map(Self::order_from_row).transpose()
+    }
+
+    /// Zwraca zlecenia oczekujące na wykonanie (Pending / PartiallyFilled)
+    pub async fn open_orders(&self) -> Result<Vec<OrderRecord>> {
+        let rows = sqlx::query(
+            "SELECT * FROM trade_orders WHERE status IN (?, ?) ORDER BY created_at, id",
+        )
+        .bind(serde_json::to_string(&OrderStatus::Pending)?)
+        .bind(serde_json::to_string(&OrderStatus::PartiallyFilled)?)
+        .fetch_all(&*self.db)
+        .await
+        .context("Failed to load open orders")?;
+
+        rows.iter().map(Self::order_from_row).collect()
+    }
+
+    /// Zwraca historię zmian statusu zlecenia
+    pub async fn order_history(&self, order_id: &str) -> Result<Vec<OrderEvent>> {
+        let rows =
+            sqlx::query("SELECT * FROM order_events WHERE order_id = ? ORDER BY created_at, id")
+                .bind(order_id)
+                .fetch_all(&*self.db)
+                .await
+                .context("Failed to load order history")?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(OrderEvent {
+                    order_id: row.get("order_id"),
+                    status: serde_json::from_str(&row.get::<String, _>("status"))?,
+                    message: row.get("message"),
+                    created_at: row.get("created_at"),
+                })
+            })
+            .collect()
+    }
+
+    /// Zwraca wykonania zlecenia
+    pub async fn order_executions(&self, order_id: &str) -> Result<Vec<TradeRecord>> {
+        let rows = sqlx::query(
+            r#"SELECT e.*, o.token, o.side, o.order_type, o.leverage
+               FROM trade_executions e JOIN trade_orders o ON o.id = e.order_id
+               WHERE e.order_id = ? ORDER BY e.executed_at, e.id"#,
+        )
+        .bind(order_id)
+        .fetch_all(&*self.db)
+        .await
+        .context("Failed to load order executions")?;
+
+        rows.iter().map(Self::trade_from_row).collect()
+    }
+
+    fn order_from_row(row: &SqliteRow) -> Result<OrderRecord> {
+        let metadata = row
+            .get::<Option<String>, _>("metadata")
+            .map(|m| serde_json::from_str(&m))
+            .transpose()
+            .context("Invalid order metadata JSON in DB")?
+            .unwrap_or(serde_json::Value::Null);
+
+        Ok(OrderRecord {
+            order: TradeOrder {
+                id: row.get("id"),
+                token: row.get("token"),
+                side: serde_json::from_str(&row.get::<String, _>("side"))
+                    .context("Invalid side in DB")?,
+                size: row.get("size"),
+                leverage: row.get::<i64, _>("leverage") as u8,
+                order_type: serde_json::from_str(&row.get::<String, _>("order_type"))
+                    .context("Invalid order_type in DB")?,
+                price: row.get("price"),
+                stop_price: row.get("stop_price"),
+                created_at: row.get("created_at"),
+                timeout: row.get::<i64, _>("timeout") as u64,
+                metadata,
+            },
+            status: serde_json::from_str(&row.get::<String, _>("status"))
+                .context("Invalid order status in DB")?,
+            updated_at: row.get("updated_at"),
+        })
+    }
+
+    fn trade_from_row(row: &SqliteRow) -> Result<TradeRecord> {
+        let metadata = row
+            .get::<Option<String>, _>("metadata")
+            .map(|m| serde_json::from_str(&m))
+            .transpose()
+            .context("Invalid execution metadata JSON in DB")?
+            .unwrap_or(serde_json::Value::Null);
+
+        Ok(TradeRecord {
+            id: row.get("id"),
+            order_id: row.get("order_id"),
+            token: row.get("token"),
+            side: serde_json::from_str(&row.get::<String, _>("side"))
+                .context("Invalid side in DB")?,
+            order_type: serde_json::from_str(&row.get::<String, _>("order_type"))
+                .context("Invalid order_type in DB")?,
+            leverage: row.get::<i64, _>("leverage") as u8,
+            result: ExecutionResult {
+                success: row.get("success"),
+                transaction_id: row.get("transaction_id"),
+                executed_price: row.get("executed_price"),
+                executed_size: row.get("executed_size"),
+                fees: row.get("fees"),
+                executed_at: row.get("executed_at"),
+                error_message: row.get("error_message"),
+                metadata,
+            },
+        })
+    }
+
+    fn position_from_row(row: &SqliteRow) -> Result<PositionRecord> {
+        Ok(PositionRecord {
+            position: Position {
+                id: row.get("id"),
+                token: row.get("token"),
+                side: serde_json::from_str(&row.get::<String, _>("side"))
+                    .context("Invalid side in DB")?,
+                size: row.get("size"),
+                leverage: row.get::<i64, _>("leverage") as u8,
+                entry_price: row.get("entry_price"),
+                current_price: row.get("current_price"),
+                pnl: row.get("pnl"),
+                fees_paid: row.get("fees_paid"),
+                funding_paid: row.get("funding_paid"),
+                realized_costs: row.get("realized_costs"),
+                last_funding_at: row.get("last_funding_at"),
+                liquidation_price: row.get("liquidation_price"),
+                status: serde_json::from_str(&row.get::<String, _>("status"))
+                    .context("Invalid position status in DB")?,
+                opened_at: row.get("opened_at"),
+                closed_at: row.get("closed_at"),
+            },
+            exit_price: row.get("exit_price"),
+            realized_pnl: row.get("realized_pnl"),
+            updated_at: row.get("updated_at"),
+        })
+    }
+}
@@ -1,5 +1,5 @@
 use anyhow::{Context, Result};
-use sqlx::{Sqlite, SqlitePool};
+use sqlx::{migrate::Migrator, Sqlite, SqlitePool};
 use std::sync::Arc;
 use tracing::{error, info, warn};
 
@@ -8,8 +8,19 @@ use crate::config::DatabaseConfig;
 // pub mod schema;
 // pub mod migrations;
 // pub mod operations;
+pub mod ledger;
+pub mod signals;
+pub mod state;
 
 // pub use operations::*;
+pub use ledger::{
+    LedgerPage, LedgerQuery, OrderEvent, OrderRecord, PositionRecord, TradeLedger, TradeRecord,
+};
+pub use signals::SignalStore;
+pub use state::StateStore;
+
+/// Wersja sqlx migracji zapisanych pod nazwami `20250808_00000N_*`
+const LEGACY_MIGRATION_VERSION: i64 = 20250808;
 
 /// Manager bazy danych z integracją Sentry
 pub struct DatabaseManager {
@@ -82,7 +93,9 @@ impl DatabaseManager {
 
         // SQLx migrator expects a folder named `migrations` at project root
         // This will apply all pending migrations in order
-        sqlx::migrate!("./migrations")
+        let migrator = sqlx::migrate!("./migrations");
+        self.renumber_legacy_migration(&migrator).await?;
+        migrator
             .run(&*self.pool)
             .await
             .context("Failed to run database migrations")?;
@@ -91,6 +104,53 @@ impl DatabaseManager {
         Ok(())
     }
 
+    /// Przenosi wpis migracji zapisany pod starą nazwą pliku (`20250808_00000N_*`)
+    ///
+    /// sqlx bierze wersję sprzed pierwszego `_`, więc stare pliki miały wspólną
+    /// wersję 20250808, której nie ma już w katalogu migracji (`VersionMissing`).
+    /// Wpis dostaje wersję migracji o tej samej sumie kontrolnej.
+    async fn renumber_legacy_migration(&self, migrator: &Migrator) -> Result<()> {
+        let table: Option<String> = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+        if table.is_none() {
+            return Ok(());
+        }
+
+        let checksum: Option<Vec<u8>> =
+            sqlx::query_scalar("SELECT checksum FROM _sqlx_migrations WHERE version = ?")
+                .bind(LEGACY_MIGRATION_VERSION)
+                .fetch_optional(&*self.pool)
+                .await?;
+        let Some(checksum) = checksum else {
+            return Ok(());
+        };
+
+        let Some(migration) = migrator.iter().find(|m| *m.checksum == *checksum) else {
+            warn!(
+                "Legacy migration {} does not match any current migration",
+                LEGACY_MIGRATION_VERSION
+            );
+            return Ok(());
+        };
+
+        sqlx::query("UPDATE _sqlx_migrations SET version = ?, description = ? WHERE version = ?")
+            .bind(migration.version)
+            .bind(migration.description.as_ref())
+            .bind(LEGACY_MIGRATION_VERSION)
+            .execute(&*self.pool)
+            .await
+            .context("Failed to renumber legacy migration")?;
+
+        info!(
+            "Renumbered legacy migration {} to {}",
+            LEGACY_MIGRATION_VERSION, migration.version
+        );
+        Ok(())
+    }
+
     /// Sprawdza stan bazy danych
     pub async fn health_check(&self) -> Result<()> {
         let start = std::time::Instant::now();
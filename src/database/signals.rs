@@ -0,0 +1,133 @@
+use anyhow::{Context, Result};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use std::sync::Arc;
+
+use super::ledger::{LedgerPage, LedgerQuery};
+use crate::signals::{ScoredSignal, Signal};
+
+/// Magazyn zaakceptowanych sygnałów w tabeli `signals`
+#[derive(Clone)]
+pub struct SignalStore {
+    db: Arc<SqlitePool>,
+}
+
+impl SignalStore {
+    /// Tworzy magazyn na istniejącej puli połączeń
+    pub fn new(db: Arc<SqlitePool>) -> Self {
+        Self { db }
+    }
+
+    /// Zapisuje oceniony sygnał
+    pub async fn save(&self, scored: &ScoredSignal) -> Result<()> {
+        let signal = &scored.signal;
+        sqlx::query(
+            r#"
+            INSERT INTO signals
+                (id, hash, token, source, confidence, price, volume, score, timestamp, received_at, metadata)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&signal.id)
+        .bind(signal.hash.clone().unwrap_or_else(|| signal.calculate_hash()))
+        .bind(&signal.token)
+        .bind(&signal.source)
+        .bind(signal.confidence.to_string())
+        .bind(signal.price)
+        .bind(signal.volume)
+        .bind(scored.score)
+        .bind(signal.timestamp)
+        .bind(scored.received_at)
+        .bind(serde_json::to_string(&signal.metadata)?)
+        .execute(&*self.db)
+        .await
+        .context("Failed to save signal")?;
+
+        Ok(())
+    }
+
+    /// Sprawdza czy sygnał o danym hashu został przyjęty od podanego momentu
+    pub async fn seen_since(&self, hash: &str, since: i64) -> Result<bool> {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM signals WHERE hash = ? AND received_at >= ?")
+                .bind(hash)
+                .bind(since)
+                .fetch_one(&*self.db)
+                .await
+                .context("Failed to check signal hash")?;
+
+        Ok(count > 0)
+    }
+
+    /// Zwraca sygnał według ID
+    pub async fn get(&self, signal_id: &str) -> Result<Option<ScoredSignal>> {
+        let row = sqlx::query("SELECT * FROM signals WHERE id = ?")
+            .bind(signal_id)
+            .fetch_optional(&*self.db)
+            .await
+            .context("Failed to load signal")?;
+
+        row.as_ref().map(Self::signal_from_row).transpose()
+    }
+
+    /// Zwraca stronę sygnałów (najnowsze pierwsze) w zakresie czasu przyjęcia
+    pub async fn list(&self, query: &LedgerQuery) -> Result<LedgerPage<ScoredSignal>> {
+        let total: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM signals WHERE received_at BETWEEN ? AND ?")
+                .bind(query.range_start())
+                .bind(query.range_end())
+                .fetch_one(&*self.db)
+                .await
+                .context("Failed to count signals")?;
+
+        let rows = sqlx::query(
+            r#"SELECT * FROM signals WHERE received_at BETWEEN ? AND ?
+               ORDER BY received_at DESC, timestamp DESC LIMIT ? OFFSET ?"#,
+        )
+        .bind(query.range_start())
+        .bind(query.range_end())
+        .bind(query.limit() as i64)
+        .bind(query.offset())
+        .fetch_all(&*self.db)
+        .await
+        .context("Failed to list signals")?;
+
+        Ok(LedgerPage {
+            items: rows
+                .iter()
+                .map(Self::signal_from_row)
+                .collect::<Result<_>>()?,
+            total: total as u64,
+            page: query.page(),
+            limit: query.limit(),
+        })
+    }
+
+    fn signal_from_row(row: &SqliteRow) -> Result<ScoredSignal> {
+        let metadata = row
+            .get::<Option<String>, _>("metadata")
+            .map(|m| serde_json::from_str(&m))
+            .transpose()
+            .context("Invalid signal metadata JSON in DB")?
+            .unwrap_or(serde_json::Value::Null);
+
+        Ok(ScoredSignal {
+            signal: Signal {
+                id: row.get("id"),
+                token: row.get("token"),
+                source: row.get("source"),
+                confidence: row
+                    .get::<String, _>("confidence")
+                    .parse()
+                    .context("Invalid confidence in DB")?,
+                price: row.get("price"),
+                volume: row.get("volume"),
+                timestamp: row.get("timestamp"),
+                metadata,
+                hash: Some(row.get("hash")),
+            },
+            score: row.get("score"),
+            received_at: row.get("received_at"),
+        })
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+use std::sync::Arc;
+
+/// Magazyn stanu komponentów ryzyka (klucz -> JSON) w tabeli `risk_state`
+#[derive(Clone)]
+pub struct StateStore {
+    db: Arc<SqlitePool>,
+}
+
+impl StateStore {
+    /// Tworzy magazyn na istniejącej puli połączeń
+    pub fn new(db: Arc<SqlitePool>) -> Self {
+        Self { db }
+    }
+
+    /// Odczytuje stan zapisany pod kluczem
+    pub async fn load<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let row = sqlx::query("SELECT state FROM risk_state WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&*self.db)
+            .await
+            .context("Failed to load risk state")?;
+
+        row.map(|row| {
+            serde_json::from_str(&row.get::<String, _>("state"))
+                .with_context(|| format!("Invalid risk state JSON for key {}", key))
+        })
+        .transpose()
+    }
+
+    /// Zapisuje stan pod kluczem (nadpisuje poprzedni)
+    pub async fn save<T: Serialize>(&self, key: &str, state: &T) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO risk_state (key, state, updated_at) VALUES (?, ?, ?)
+            ON CONFLICT(key) DO UPDATE SET state = excluded.state, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(key)
+        .bind(serde_json::to_string(state)?)
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&*self.db)
+        .await
+        .context("Failed to save risk state")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1132 @@
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+use super::{is_reducing, Balance, ExchangeAdapter, ExchangeOrder, ExchangePosition, FundingRate};
+use crate::config::ExchangeConfig;
+use crate::errors::{CerberusError, CerberusResult};
+use crate::risk::TradeSide;
+use crate::trading::feed::PriceStream;
+use crate::trading::{OrderStatus, OrderType, PriceTick, TradeOrder};
+
+/// Nazwa adaptera (także `ExchangeConfig::adapter`)
+const ADAPTER_NAME: &str = "binance_futures";
+
+/// Nagłówek z kluczem API
+const API_KEY_HEADER: &str = "X-MBX-APIKEY";
+
+/// Odświeżanie listenKey strumienia użytkownika (wygasa po 60 minutach)
+const LISTEN_KEY_KEEPALIVE: Duration = Duration::from_secs(30 * 60);
+
+/// Pojemność kanału aktualizacji zleceń
+const ORDER_UPDATES_BUFFER: usize = 256;
+
+/// Bufor ticków strumienia cen mark
+const PRICE_TICKS_BUFFER: usize = 1024;
+
+/// Kody błędów Binance oznaczające problem z kluczem lub podpisem
+const AUTH_ERROR_CODES: [i64; 3] = [-1022, -2014, -2015];
+
+/// Kod błędu Binance dla przekroczonego limitu żądań
+const RATE_LIMIT_ERROR_CODE: i64 = -1003;
+
+/// Sposób autoryzacji żądania
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Auth {
+    /// Endpoint publiczny
+    None,
+    /// Tylko nagłówek z kluczem API (np. listenKey)
+    ApiKey,
+    /// Klucz API i podpis HMAC-SHA256 parametrów
+    Signed,
+}
+
+/// Reguły handlu symbolem z `exchangeInfo`
+#[derive(Debug, Clone)]
+struct SymbolRules {
+    step_size: f64,
+    tick_size: f64,
+    min_qty: f64,
+}
+
+impl SymbolRules {
+    /// Zaokrągla wielkość w dół do kroku symbolu
+    fn round_quantity(&self, quantity: f64) -> f64 {
+        (quantity / self.step_size + 1e-9).floor() * self.step_size
+    }
+
+    fn format_quantity(&self, quantity: f64) -> String {
+        format!("{:.*}", decimals(self.step_size), quantity)
+    }
+
+    /// Zaokrągla cenę do najbliższego ticku
+    fn format_price(&self, price: f64) -> String {
+        let price = (price / self.tick_size).round() * self.tick_size;
+        format!("{:.*}", decimals(self.tick_size), price)
+    }
+}
+
+/// Adapter Binance USDⓈ-M Futures (REST + strumień użytkownika WebSocket)
+///
+/// Działa w trybie one-way (jedna pozycja netto na symbol). Każde żądanie
+/// prywatne jest podpisywane HMAC-SHA256 sekretem API. Zgodny adres REST/WS
+/// (np. testnet lub `MockPerpExchange`) ustawia się w `ExchangeConfig`.
+pub struct BinanceFuturesAdapter {
+    config: ExchangeConfig,
+    client: Client,
+    api_key: String,
+    key: ring::hmac::Key,
+    rules: Mutex<HashMap<String, SymbolRules>>,
+    leverage: Mutex<HashMap<String, u8>>,
+}
+
+impl BinanceFuturesAdapter {
+    /// Tworzy adapter; wymaga klucza i sekretu API w konfiguracji
+    pub fn new(config: ExchangeConfig) -> CerberusResult<Self> {
+        let api_key = config
+            .resolve_api_key()
+            .ok_or_else(|| CerberusError::Authentication {
+                message: "Exchange API key not configured".to_string(),
+            })?;
+        let secret = config
+            .resolve_api_secret()
+            .ok_or_else(|| CerberusError::Authentication {
+                message: "Exchange API secret not configured".to_string(),
+            })?;
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout))
+            .build()?;
+
+        Ok(Self {
+            key: ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes()),
+            config,
+            client,
+            api_key,
+            rules: Mutex::new(HashMap::new()),
+            leverage: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Wysyła żądanie i dekoduje odpowiedź JSON
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        params: Vec<(&str, String)>,
+        auth: Auth,
+    ) -> CerberusResult<T> {
+        let query = self.query_string(&params, auth);
+        let mut url = format!("{}{}", self.config.rest_url.trim_end_matches('/'), path);
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query);
+        }
+
+        let mut request = self.client.request(method.clone(), url);
+        if auth != Auth::None {
+            request = request.header(API_KEY_HEADER, &self.api_key);
+        }
+
+        debug!("Binance {} {}", method, path);
+        parse_response(request.send().await?).await
+    }
+
+    /// Parametry żądania; żądania podpisane dostają czas, okno i podpis
+    fn query_string(&self, params: &[(&str, String)], auth: Auth) -> String {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        query.extend_pairs(params.iter().map(|(name, value)| (*name, value.as_str())));
+        if auth != Auth::Signed {
+            return query.finish();
+        }
+
+        query.append_pair(
+            "timestamp",
+            &chrono::Utc::now().timestamp_millis().to_string(),
+        );
+        query.append_pair("recvWindow", &self.config.recv_window_ms.to_string());
+        let mut query = query.finish();
+
+        let signature = ring::hmac::sign(&self.key, query.as_bytes());
+        query.push_str("&signature=");
+        query.push_str(&hex::encode(signature.as_ref()));
+        query
+    }
+
+    /// Reguły symbolu (ładowane raz z `exchangeInfo`)
+    async fn symbol_rules(&self, symbol: &str) -> CerberusResult<SymbolRules> {
+        let mut rules = self.rules.lock().await;
+
+        if rules.is_empty() {
+            let info: ExchangeInfo = self
+                .send(Method::GET, "/fapi/v1/exchangeInfo", Vec::new(), Auth::None)
+                .await?;
+            rules.extend(
+                info.symbols
+                    .into_iter()
+                    .filter_map(|s| s.rules().map(|r| (s.symbol, r))),
+            );
+        }
+
+        rules
+            .get(symbol)
+            .cloned()
+            .ok_or_else(|| CerberusError::Validation {
+                message: format!("Unknown exchange symbol: {}", symbol),
+            })
+    }
+
+    /// Ustawia dźwignię symbolu, jeśli różni się od ostatnio ustawionej
+    async fn ensure_leverage(&self, symbol: &str, leverage: u8) -> CerberusResult<()> {
+        let mut current = self.leverage.lock().await;
+        if current.get(symbol) == Some(&leverage) {
+            return Ok(());
+        }
+
+        let _: serde_json::Value = self
+            .send(
+                Method::POST,
+                "/fapi/v1/leverage",
+                vec![
+                    ("symbol", symbol.to_string()),
+                    ("leverage", leverage.to_string()),
+                ],
+                Auth::Signed,
+            )
+            .await?;
+
+        current.insert(symbol.to_string(), leverage);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ExchangeAdapter for BinanceFuturesAdapter {
+    fn name(&self) -> &str {
+        ADAPTER_NAME
+    }
+
+    fn symbol_for(&self, token: &str) -> String {
+        symbol_for(token, &self.config.quote_asset)
+    }
+
+    async fn authenticate(&self) -> CerberusResult<()> {
+        let account: AccountResponse = self
+            .send(Method::GET, "/fapi/v2/account", Vec::new(), Auth::Signed)
+            .await?;
+
+        if !account.can_trade {
+            return Err(CerberusError::Authentication {
+                message: "Exchange API key is not permitted to trade".to_string(),
+            });
+        }
+
+        info!("Authenticated with {}", ADAPTER_NAME);
+        Ok(())
+    }
+
+    async fn get_balances(&self) -> CerberusResult<Vec<Balance>> {
+        let balances: Vec<BalanceResponse> = self
+            .send(Method::GET, "/fapi/v2/balance", Vec::new(), Auth::Signed)
+            .await?;
+
+        Ok(balances
+            .into_iter()
+            .map(|b| Balance {
+                asset: b.asset,
+                wallet_balance: b.balance,
+                available_balance: b.available_balance,
+                unrealized_pnl: b.cross_un_pnl,
+            })
+            .collect())
+    }
+
+    async fn get_positions(&self) -> CerberusResult<Vec<ExchangePosition>> {
+        let positions: Vec<PositionRisk> = self
+            .send(
+                Method::GET,
+                "/fapi/v2/positionRisk",
+                Vec::new(),
+                Auth::Signed,
+            )
+            .await?;
+
+        Ok(positions
+            .into_iter()
+            .filter(|p| p.position_amt != 0.0)
+            .map(|p| ExchangePosition {
+                token: token_for(&p.symbol, &self.config.quote_asset),
+                side: if p.position_amt > 0.0 {
+                    TradeSide::Long
+                } else {
+                    TradeSide::Short
+                },
+                quantity: p.position_amt.abs(),
+                entry_price: p.entry_price,
+                mark_price: p.mark_price,
+                leverage: p.leverage.clamp(1.0, u8::MAX as f64) as u8,
+                unrealized_pnl: p.un_realized_profit,
+                liquidation_price: Some(p.liquidation_price).filter(|price| *price > 0.0),
+                symbol: p.symbol,
+            })
+            .collect())
+    }
+
+    async fn get_funding_rate(&self, token: &str) -> CerberusResult<FundingRate> {
+        let symbol = self.symbol_for(token);
+        let index: PremiumIndex = self
+            .send(
+                Method::GET,
+                "/fapi/v1/premiumIndex",
+                vec![("symbol", symbol.clone())],
+                Auth::None,
+            )
+            .await?;
+
+        Ok(FundingRate {
+            token: token_for(&symbol, &self.config.quote_asset),
+            symbol,
+            rate: index.last_funding_rate,
+            mark_price: index.mark_price,
+            next_funding_time: index.next_funding_time / 1000,
+        })
+    }
+
+    async fn place_order(&self, order: &TradeOrder) -> CerberusResult<ExchangeOrder> {
+        order.validate().map_err(|e| CerberusError::Validation {
+            message: e.to_string(),
+        })?;
+
+        let symbol = self.symbol_for(&order.token);
+        let rules = self.symbol_rules(&symbol).await?;
+        let reducing = is_reducing(order);
+        if !reducing {
+            self.ensure_leverage(&symbol, order.leverage).await?;
+        }
+
+        // Rozmiar zlecenia to marża; wartość nominalna (marża x dźwignia)
+        // przeliczana po cenie zlecenia lub aktualnej cenie mark
+        let reference_price = match order.order_type {
+            OrderType::Limit => order.price,
+            OrderType::StopLoss | OrderType::TakeProfit => order.stop_price,
+            OrderType::Market => None,
+        };
+        let reference_price = match reference_price {
+            Some(price) => price,
+            None => self.get_funding_rate(&order.token).await?.mark_price,
+        };
+
+        let quantity = rules.round_quantity(order.size * order.leverage as f64 / reference_price);
+        if quantity <= 0.0 || quantity < rules.min_qty {
+            return Err(CerberusError::Validation {
+                message: format!(
+                    "Order size {:.4} is below the minimum quantity {} of {}",
+                    order.size, rules.min_qty, symbol
+                ),
+            });
+        }
+
+        let buying = (order.side == TradeSide::Long) != reducing;
+        let mut params = vec![
+            ("symbol", symbol.clone()),
+            ("side", if buying { "BUY" } else { "SELL" }.to_string()),
+            ("quantity", rules.format_quantity(quantity)),
+            ("newClientOrderId", order.id.clone()),
+            ("newOrderRespType", "RESULT".to_string()),
+        ];
+        match order.order_type {
+            OrderType::Market => params.push(("type", "MARKET".to_string())),
+            OrderType::Limit => {
+                params.push(("type", "LIMIT".to_string()));
+                params.push(("timeInForce", "GTC".to_string()));
+                params.push(("price", rules.format_price(reference_price)));
+            }
+            OrderType::StopLoss | OrderType::TakeProfit => {
+                let order_type = if order.order_type == OrderType::StopLoss {
+                    "STOP_MARKET"
+                } else {
+                    "TAKE_PROFIT_MARKET"
+                };
+                params.push(("type", order_type.to_string()));
+                params.push(("stopPrice", rules.format_price(reference_price)));
+            }
+        }
+        if reducing {
+            params.push(("reduceOnly", "true".to_string()));
+        }
+
+        let response: OrderResponse = self
+            .send(Method::POST, "/fapi/v1/order", params, Auth::Signed)
+            .await?;
+        let placed = parse_order(response, &self.config.quote_asset)?;
+
+        info!(
+            "{} order {} ({:?} {:?} {} {}) is {:?}",
+            ADAPTER_NAME,
+            placed.order_id,
+            placed.order_type,
+            placed.side,
+            placed.quantity,
+            symbol,
+            placed.status
+        );
+        Ok(placed)
+    }
+
+    async fn get_order(&self, token: &str, order_id: &str) -> CerberusResult<ExchangeOrder> {
+        let response: OrderResponse = self
+            .send(
+                Method::GET,
+                "/fapi/v1/order",
+                vec![
+                    ("symbol", self.symbol_for(token)),
+                    ("origClientOrderId", order_id.to_string()),
+                ],
+                Auth::Signed,
+            )
+            .await?;
+
+        parse_order(response, &self.config.quote_asset)
+    }
+
+    async fn cancel_order(&self, token: &str, order_id: &str) -> CerberusResult<ExchangeOrder> {
+        let response: OrderResponse = self
+            .send(
+                Method::DELETE,
+                "/fapi/v1/order",
+                vec![
+                    ("symbol", self.symbol_for(token)),
+                    ("origClientOrderId", order_id.to_string()),
+                ],
+                Auth::Signed,
+            )
+            .await?;
+
+        info!("{} order {} cancelled", ADAPTER_NAME, order_id);
+        parse_order(response, &self.config.quote_asset)
+    }
+
+    /// Strumień `ORDER_TRADE_UPDATE`; kanał zamyka się po zerwaniu połączenia
+    async fn subscribe_order_updates(&self) -> CerberusResult<mpsc::Receiver<ExchangeOrder>> {
+        let ListenKey { listen_key } = self
+            .send(Method::POST, "/fapi/v1/listenKey", Vec::new(), Auth::ApiKey)
+            .await?;
+
+        let url = format!(
+            "{}/ws/{}",
+            self.config.ws_url.trim_end_matches('/'),
+            listen_key
+        );
+        let (mut stream, _) = tokio_tungstenite::connect_async(url.as_str())
+            .await
+            .map_err(|e| CerberusError::Network {
+                message: format!("User data stream connection failed: {}", e),
+            })?;
+
+        let (sender, receiver) = mpsc::channel(ORDER_UPDATES_BUFFER);
+        let client = self.client.clone();
+        let keepalive_url = format!(
+            "{}/fapi/v1/listenKey",
+            self.config.rest_url.trim_end_matches('/')
+        );
+        let api_key = self.api_key.clone();
+        let quote_asset = self.config.quote_asset.clone();
+
+        tokio::spawn(async move {
+            let mut keepalive = tokio::time::interval_at(
+                tokio::time::Instant::now() + LISTEN_KEY_KEEPALIVE,
+                LISTEN_KEY_KEEPALIVE,
+            );
+
+            loop {
+                tokio::select! {
+                    message = stream.next() => match message {
+                        Some(Ok(Message::Text(text))) => {
+                            let Some(order) = parse_order_event(&text, &quote_asset) else {
+                                continue;
+                            };
+                            if sender.send(order).await.is_err() {
+                                break;
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => {
+                            warn!("{} user data stream closed", ADAPTER_NAME);
+                            break;
+                        }
+                        Some(Ok(_)) => {}
+                        Some(Err(e)) => {
+                            warn!("{} user data stream error: {}", ADAPTER_NAME, e);
+                            break;
+                        }
+                    },
+                    _ = keepalive.tick() => {
+                        let result = client
+                            .put(&keepalive_url)
+                            .header(API_KEY_HEADER, &api_key)
+                            .send()
+                            .await;
+                        if let Err(e) = result {
+                            warn!("{} listenKey keepalive failed: {}", ADAPTER_NAME, e);
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(receiver)
+    }
+}
+
+/// Dekoduje odpowiedź lub mapuje błąd HTTP/Binance na `CerberusError`
+async fn parse_response<T: DeserializeOwned>(response: reqwest::Response) -> CerberusResult<T> {
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    let body = response.text().await?;
+
+    if status.is_success() {
+        return serde_json::from_str(&body).map_err(|e| CerberusError::Parse {
+            message: format!("Invalid {} response: {}", ADAPTER_NAME, e),
+        });
+    }
+
+    Err(api_error(status, retry_after, &body))
+}
+
+fn api_error(status: StatusCode, retry_after: Option<u64>, body: &str) -> CerberusError {
+    let (code, message) = serde_json::from_str::<ApiErrorBody>(body)
+        .map(|error| (error.code, error.msg))
+        .unwrap_or_else(|_| (0, body.to_string()));
+
+    if status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::IM_A_TEAPOT
+        || code == RATE_LIMIT_ERROR_CODE
+    {
+        return CerberusError::RateLimit {
+            message: match retry_after {
+                Some(seconds) => format!("{} (retry after {}s)", message, seconds),
+                None => message,
+            },
+        };
+    }
+
+    if status == StatusCode::UNAUTHORIZED
+        || status == StatusCode::FORBIDDEN
+        || AUTH_ERROR_CODES.contains(&code)
+    {
+        return CerberusError::Authentication { message };
+    }
+
+    if status.is_server_error() {
+        return CerberusError::ExternalService {
+            service: ADAPTER_NAME.to_string(),
+            message: format!("HTTP {}: {}", status.as_u16(), message),
+        };
+    }
+
+    CerberusError::Trading {
+        message: format!("{} error {}: {}", ADAPTER_NAME, code, message),
+    }
+}
+
+/// Strumień cen mark Binance Futures
+///
+/// Strumień rynkowy jest publiczny, więc nie wymaga kluczy API. Ceny
+/// przychodzą co sekundę w zdarzeniach `markPriceUpdate` strumienia
+/// złożonego `/stream?streams=<symbol>@markPrice@1s/...`.
+pub struct BinanceMarkPriceStream {
+    ws_url: String,
+    quote_asset: String,
+}
+
+impl BinanceMarkPriceStream {
+    /// Tworzy strumień dla adresu WS i waluty kwotowania z konfiguracji
+    pub fn new(config: &ExchangeConfig) -> Self {
+        Self {
+            ws_url: config.ws_url.trim_end_matches('/').to_string(),
+            quote_asset: config.quote_asset.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl PriceStream for BinanceMarkPriceStream {
+    fn name(&self) -> &str {
+        ADAPTER_NAME
+    }
+
+    async fn subscribe(&self, tokens: &[String]) -> anyhow::Result<mpsc::Receiver<PriceTick>> {
+        let streams = tokens
+            .iter()
+            .map(|token| {
+                format!(
+                    "{}@markPrice@1s",
+                    symbol_for(token, &self.quote_asset).to_lowercase()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        let url = format!("{}/stream?streams={}", self.ws_url, streams);
+        let (mut stream, _) = tokio_tungstenite::connect_async(url.as_str())
+            .await
+            .map_err(|e| CerberusError::Network {
+                message: format!("Mark price stream connection failed: {}", e),
+            })?;
+
+        let (sender, receiver) = mpsc::channel(PRICE_TICKS_BUFFER);
+        let quote_asset = self.quote_asset.clone();
+        tokio::spawn(async move {
+            while let Some(message) = stream.next().await {
+                match message {
+                    Ok(Message::Text(text)) => {
+                        let Some(tick) = parse_mark_price_event(&text, &quote_asset) else {
+                            continue;
+                        };
+                        if sender.send(tick).await.is_err() {
+                            return;
+                        }
+                    }
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!("{} mark price stream error: {}", ADAPTER_NAME, e);
+                        return;
+                    }
+                }
+            }
+            warn!("{} mark price stream closed", ADAPTER_NAME);
+        });
+
+        Ok(receiver)
+    }
+}
+
+/// Symbol kontraktu dla tokena (`bonk` -> `BONKUSDT`)
+fn symbol_for(token: &str, quote_asset: &str) -> String {
+    let token = token.trim().to_uppercase();
+    if token.ends_with(quote_asset) {
+        token
+    } else {
+        format!("{}{}", token, quote_asset)
+    }
+}
+
+/// Token dla symbolu kontraktu (`BONKUSDT` -> `BONK`)
+fn token_for(symbol: &str, quote_asset: &str) -> String {
+    symbol
+        .strip_suffix(quote_asset)
+        .filter(|token| !token.is_empty())
+        .unwrap_or(symbol)
+        .to_string()
+}
+
+/// Liczba miejsc po przecinku kroku (np. 0.001 -> 3)
+fn decimals(step: f64) -> usize {
+    (0..12)
+        .find(|&places| {
+            let scaled = step * 10f64.powi(places as i32);
+            (scaled - scaled.round()).abs() < 1e-9
+        })
+        .unwrap_or(12)
+}
+
+fn parse_order(response: OrderResponse, quote_asset: &str) -> CerberusResult<ExchangeOrder> {
+    let order_type = match response.order_type.as_str() {
+        "MARKET" => OrderType::Market,
+        "LIMIT" => OrderType::Limit,
+        "STOP" | "STOP_MARKET" => OrderType::StopLoss,
+        "TAKE_PROFIT" | "TAKE_PROFIT_MARKET" => OrderType::TakeProfit,
+        other => {
+            return Err(CerberusError::Parse {
+                message: format!("Unsupported order type: {}", other),
+            })
+        }
+    };
+    let status = match response.status.as_str() {
+        "NEW" => OrderStatus::Pending,
+        "PARTIALLY_FILLED" => OrderStatus::PartiallyFilled,
+        "FILLED" => OrderStatus::Filled,
+        "CANCELED" => OrderStatus::Cancelled,
+        "REJECTED" => OrderStatus::Rejected,
+        "EXPIRED" | "EXPIRED_IN_MATCH" => OrderStatus::Expired,
+        other => {
+            return Err(CerberusError::Parse {
+                message: format!("Unsupported order status: {}", other),
+            })
+        }
+    };
+
+    // Kupno otwiera Long lub redukuje Short
+    let buying = response.side == "BUY";
+    let side = if buying != response.reduce_only {
+        TradeSide::Long
+    } else {
+        TradeSide::Short
+    };
+    let price = match order_type {
+        OrderType::Limit => response.price,
+        OrderType::StopLoss | OrderType::TakeProfit => response.stop_price,
+        OrderType::Market => 0.0,
+    };
+
+    Ok(ExchangeOrder {
+        order_id: response.client_order_id,
+        exchange_order_id: response.order_id.to_string(),
+        token: token_for(&response.symbol, quote_asset),
+        symbol: response.symbol,
+        side,
+        reduce_only: response.reduce_only,
+        order_type,
+        status,
+        quantity: response.orig_qty,
+        executed_quantity: response.executed_qty,
+        price: Some(price).filter(|price| *price > 0.0),
+        average_price: Some(response.avg_price).filter(|price| *price > 0.0),
+        updated_at: response.update_time / 1000,
+    })
+}
+
+/// Zlecenie ze zdarzenia strumienia użytkownika (inne zdarzenia są pomijane)
+fn parse_order_event(text: &str, quote_asset: &str) -> Option<ExchangeOrder> {
+    let event: UserDataEvent = match serde_json::from_str(text) {
+        Ok(event) => event,
+        Err(e) => {
+            debug!("Ignoring {} stream message: {}", ADAPTER_NAME, e);
+            return None;
+        }
+    };
+    if event.event_type != "ORDER_TRADE_UPDATE" {
+        return None;
+    }
+
+    let order = event.order?;
+    match parse_order(order.into(), quote_asset) {
+        Ok(order) => Some(order),
+        Err(e) => {
+            warn!("Invalid {} order update: {}", ADAPTER_NAME, e);
+            None
+        }
+    }
+}
+
+/// Tick ze zdarzenia `markPriceUpdate` strumienia złożonego
+fn parse_mark_price_event(text: &str, quote_asset: &str) -> Option<PriceTick> {
+    let message: CombinedStreamMessage = match serde_json::from_str(text) {
+        Ok(message) => message,
+        Err(e) => {
+            debug!("Ignoring {} market stream message: {}", ADAPTER_NAME, e);
+            return None;
+        }
+    };
+    let event = message.data;
+    if event.event_type != "markPriceUpdate" {
+        return None;
+    }
+
+    Some(PriceTick {
+        token: token_for(&event.symbol, quote_asset),
+        price: event.mark_price,
+        timestamp: event.event_time / 1000,
+    })
+}
+
+/// Liczba z JSON (Binance zwraca większość liczb jako tekst)
+fn number<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(f64),
+        Text(String),
+    }
+
+    match Raw::deserialize(deserializer)? {
+        Raw::Number(value) => Ok(value),
+        Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    code: i64,
+    msg: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountResponse {
+    can_trade: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BalanceResponse {
+    asset: String,
+    #[serde(deserialize_with = "number")]
+    balance: f64,
+    #[serde(deserialize_with = "number")]
+    available_balance: f64,
+    #[serde(deserialize_with = "number")]
+    cross_un_pnl: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PositionRisk {
+    symbol: String,
+    #[serde(deserialize_with = "number")]
+    position_amt: f64,
+    #[serde(deserialize_with = "number")]
+    entry_price: f64,
+    #[serde(deserialize_with = "number")]
+    mark_price: f64,
+    #[serde(deserialize_with = "number")]
+    un_realized_profit: f64,
+    #[serde(deserialize_with = "number")]
+    liquidation_price: f64,
+    #[serde(deserialize_with = "number")]
+    leverage: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PremiumIndex {
+    #[serde(deserialize_with = "number")]
+    mark_price: f64,
+    #[serde(deserialize_with = "number")]
+    last_funding_rate: f64,
+    next_funding_time: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExchangeInfo {
+    symbols: Vec<SymbolInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SymbolInfo {
+    symbol: String,
+    filters: Vec<serde_json::Value>,
+}
+
+impl SymbolInfo {
+    fn rules(&self) -> Option<SymbolRules> {
+        let filter = |kind: &str, field: &str| -> Option<f64> {
+            self.filters
+                .iter()
+                .find(|f| f.get("filterType").and_then(|t| t.as_str()) == Some(kind))?
+                .get(field)?
+                .as_str()?
+                .parse()
+                .ok()
+        };
+
+        Some(SymbolRules {
+            step_size: filter("LOT_SIZE", "stepSize").filter(|step| *step > 0.0)?,
+            min_qty: filter("LOT_SIZE", "minQty").unwrap_or(0.0),
+            tick_size: filter("PRICE_FILTER", "tickSize").filter(|tick| *tick > 0.0)?,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListenKey {
+    listen_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OrderResponse {
+    order_id: i64,
+    client_order_id: String,
+    symbol: String,
+    status: String,
+    side: String,
+    #[serde(rename = "type")]
+    order_type: String,
+    #[serde(deserialize_with = "number")]
+    orig_qty: f64,
+    #[serde(deserialize_with = "number")]
+    executed_qty: f64,
+    #[serde(default, deserialize_with = "number")]
+    avg_price: f64,
+    #[serde(deserialize_with = "number")]
+    price: f64,
+    #[serde(default, deserialize_with = "number")]
+    stop_price: f64,
+    #[serde(default)]
+    reduce_only: bool,
+    update_time: i64,
+}
+
+/// Koperta strumienia złożonego (`{"stream": ..., "data": ...}`)
+#[derive(Debug, Deserialize)]
+struct CombinedStreamMessage {
+    data: MarkPriceEvent,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarkPriceEvent {
+    #[serde(rename = "e")]
+    event_type: String,
+    #[serde(rename = "E")]
+    event_time: i64,
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "p", deserialize_with = "number")]
+    mark_price: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserDataEvent {
+    #[serde(rename = "e")]
+    event_type: String,
+    #[serde(rename = "o")]
+    order: Option<OrderEvent>,
+}
+
+/// Zlecenie w zdarzeniu `ORDER_TRADE_UPDATE` (skrócone nazwy pól)
+#[derive(Debug, Deserialize)]
+struct OrderEvent {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "c")]
+    client_order_id: String,
+    #[serde(rename = "S")]
+    side: String,
+    #[serde(rename = "o")]
+    order_type: String,
+    #[serde(rename = "q", deserialize_with = "number")]
+    quantity: f64,
+    #[serde(rename = "p", deserialize_with = "number")]
+    price: f64,
+    #[serde(rename = "ap", deserialize_with = "number")]
+    average_price: f64,
+    #[serde(rename = "sp", deserialize_with = "number")]
+    stop_price: f64,
+    #[serde(rename = "X")]
+    status: String,
+    #[serde(rename = "i")]
+    order_id: i64,
+    #[serde(rename = "z", deserialize_with = "number")]
+    executed_quantity: f64,
+    #[serde(rename = "R")]
+    reduce_only: bool,
+    #[serde(rename = "T")]
+    trade_time: i64,
+}
+
+impl From<OrderEvent> for OrderResponse {
+    fn from(event: OrderEvent) -> Self {
+        Self {
+            order_id: event.order_id,
+            client_order_id: event.client_order_id,
+            symbol: event.symbol,
+            status: event.status,
+            side: event.side,
+            order_type: event.order_type,
+            orig_qty: event.quantity,
+            executed_qty: event.executed_quantity,
+            avg_price: event.average_price,
+            price: event.price,
+            stop_price: event.stop_price,
+            reduce_only: event.reduce_only,
+            update_time: event.trade_time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchanges::MockPerpExchange;
+
+    const API_KEY: &str = "test-key";
+    const API_SECRET: &str = "test-secret";
+
+    async fn exchange() -> (MockPerpExchange, BinanceFuturesAdapter) {
+        let exchange = MockPerpExchange::start(API_KEY, API_SECRET, 1000.0)
+            .await
+            .unwrap();
+        exchange.add_market("BTCUSDT", 50_000.0, 0.0001).await;
+
+        let adapter = BinanceFuturesAdapter::new(exchange.config()).unwrap();
+        (exchange, adapter)
+    }
+
+    #[tokio::test]
+    async fn test_order_lifecycle_against_local_exchange() {
+        let (exchange, adapter) = exchange().await;
+        adapter.authenticate().await.unwrap();
+        let mut updates = adapter.subscribe_order_updates().await.unwrap();
+
+        let funding = adapter.get_funding_rate("btc").await.unwrap();
+        assert_eq!(funding.symbol, "BTCUSDT");
+        assert_eq!(funding.token, "BTC");
+        assert_eq!(funding.rate, 0.0001);
+
+        // Rynkowe otwarcie: $100 marży przy 10x = $1000 nominalnie = 0.02 BTC
+        let entry = TradeOrder::market_order("BTC".to_string(), TradeSide::Long, 100.0, 10);
+        let filled = adapter.place_order(&entry).await.unwrap();
+        assert_eq!(filled.order_id, entry.id);
+        assert_eq!(filled.status, OrderStatus::Filled);
+        assert_eq!(filled.side, TradeSide::Long);
+        assert!((filled.executed_quantity - 0.02).abs() < 1e-9);
+        assert_eq!(filled.average_price, Some(50_000.0));
+
+        let positions = adapter.get_positions().await.unwrap();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].token, "BTC");
+        assert_eq!(positions[0].side, TradeSide::Long);
+        assert_eq!(positions[0].leverage, 10);
+
+        // Stop-loss czeka w księdze, take-profit realizuje się po wzroście ceny
+        let stop =
+            TradeOrder::stop_loss_order("BTC".to_string(), TradeSide::Long, 100.0, 10, 45_000.0);
+        let stop = adapter.place_order(&stop).await.unwrap();
+        assert_eq!(stop.status, OrderStatus::Pending);
+        assert!(stop.reduce_only);
+        assert_eq!(stop.side, TradeSide::Long);
+        assert_eq!(stop.price, Some(45_000.0));
+
+        let take_profit =
+            TradeOrder::take_profit_order("BTC".to_string(), TradeSide::Long, 55.0, 10, 55_000.0);
+        let take_profit = adapter.place_order(&take_profit).await.unwrap();
+        assert_eq!(take_profit.status, OrderStatus::Pending);
+
+        exchange.set_mark_price("BTCUSDT", 55_000.0).await;
+
+        let update = loop {
+            let update = tokio::time::timeout(Duration::from_secs(5), updates.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            if update.order_id == take_profit.order_id && update.status == OrderStatus::Filled {
+                break update;
+            }
+        };
+        assert!((update.executed_quantity - 0.01).abs() < 1e-9);
+
+        let take_profit = adapter
+            .get_order("BTC", &take_profit.order_id)
+            .await
+            .unwrap();
+        assert_eq!(take_profit.status, OrderStatus::Filled);
+        assert_eq!(take_profit.average_price, Some(55_000.0));
+
+        let cancelled = adapter.cancel_order("BTC", &stop.order_id).await.unwrap();
+        assert_eq!(cancelled.status, OrderStatus::Cancelled);
+
+        // Zamknięcie reszty pozycji zleceniem redukującym
+        let mut close = TradeOrder::market_order("BTC".to_string(), TradeSide::Long, 55.0, 10);
+        close.metadata = serde_json::json!({ "position_id": "btc-long" });
+        let closed = adapter.place_order(&close).await.unwrap();
+        assert_eq!(closed.status, OrderStatus::Filled);
+        assert!(closed.reduce_only);
+        assert!(adapter.get_positions().await.unwrap().is_empty());
+
+        // +$100 zysku z 0.02 BTC minus opłaty taker
+        let balances = adapter.get_balances().await.unwrap();
+        let usdt = balances.iter().find(|b| b.asset == "USDT").unwrap();
+        assert!(usdt.wallet_balance > 1090.0 && usdt.wallet_balance < 1100.0);
+    }
+
+    #[tokio::test]
+    async fn test_rejections_and_limit_orders() {
+        let (exchange, adapter) = exchange().await;
+
+        let limit =
+            TradeOrder::limit_order("BTC".to_string(), TradeSide::Short, 100.0, 5, 52_000.0);
+        let limit = adapter.place_order(&limit).await.unwrap();
+        assert_eq!(limit.status, OrderStatus::Pending);
+        assert_eq!(limit.side, TradeSide::Short);
+
+        exchange.set_mark_price("BTCUSDT", 52_500.0).await;
+        let limit = adapter.get_order("BTC", &limit.order_id).await.unwrap();
+        assert_eq!(limit.status, OrderStatus::Filled);
+        assert_eq!(limit.average_price, Some(52_500.0));
+
+        // Za duża pozycja względem marginesu
+        let huge = TradeOrder::market_order("BTC".to_string(), TradeSide::Long, 100_000.0, 5);
+        let err = adapter.place_order(&huge).await.unwrap_err();
+        assert!(matches!(err, CerberusError::Trading { .. }), "{:?}", err);
+
+        // Poniżej minimalnej wielkości kontraktu
+        let dust = TradeOrder::market_order("BTC".to_string(), TradeSide::Long, 0.2, 5);
+        let err = adapter.place_order(&dust).await.unwrap_err();
+        assert!(matches!(err, CerberusError::Validation { .. }));
+
+        let err = adapter.get_order("BTC", "unknown").await.unwrap_err();
+        assert!(matches!(err, CerberusError::Trading { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_errors_map_to_cerberus_variants() {
+        let (exchange, adapter) = exchange().await;
+
+        exchange.throttle(1).await;
+        let err = adapter.get_balances().await.unwrap_err();
+        assert!(matches!(err, CerberusError::RateLimit { .. }), "{:?}", err);
+        assert!(err.to_string().contains("retry after"));
+        assert!(adapter.get_balances().await.is_ok());
+
+        let mut config = exchange.config();
+        config.api_secret = Some("wrong-secret".to_string());
+        let unsigned = BinanceFuturesAdapter::new(config).unwrap();
+        let err = unsigned.authenticate().await.unwrap_err();
+        assert!(
+            matches!(err, CerberusError::Authentication { .. }),
+            "{:?}",
+            err
+        );
+
+        let mut config = exchange.config();
+        config.rest_url = "http://127.0.0.1:9".to_string();
+        let offline = BinanceFuturesAdapter::new(config).unwrap();
+        let err = offline.get_funding_rate("BTC").await.unwrap_err();
+        assert!(matches!(err, CerberusError::Network { .. }), "{:?}", err);
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn test_symbol_mapping_and_rounding() {
+        let adapter = BinanceFuturesAdapter::new(ExchangeConfig {
+            api_key: Some("key".to_string()),
+            api_key_env: None,
+            api_secret: Some("secret".to_string()),
+            api_secret_env: None,
+            ..ExchangeConfig::default()
+        })
+        .unwrap();
+
+        assert_eq!(adapter.symbol_for("bonk"), "BONKUSDT");
+        assert_eq!(adapter.symbol_for("BONKUSDT"), "BONKUSDT");
+        assert_eq!(token_for("BONKUSDT", "USDT"), "BONK");
+
+        let rules = SymbolRules {
+            step_size: 0.001,
+            tick_size: 0.1,
+            min_qty: 0.001,
+        };
+        assert_eq!(
+            rules.format_quantity(rules.round_quantity(0.02999)),
+            "0.029"
+        );
+        assert_eq!(rules.format_price(45_000.04), "45000.0");
+        assert_eq!(decimals(1.0), 0);
+    }
+}
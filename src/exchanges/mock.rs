@@ -0,0 +1,993 @@
+use anyhow::{Context, Result};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, RawQuery, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use crate::config::ExchangeConfig;
+
+/// Krok wielkości kontraktów na lokalnej giełdzie
+const STEP_SIZE: &str = "0.001";
+
+/// Tick ceny na lokalnej giełdzie
+const TICK_SIZE: &str = "0.1";
+
+/// Opłata taker (0.04% wartości)
+const TAKER_FEE: f64 = 0.0004;
+
+/// Dźwignia symbolu, dopóki klient jej nie zmieni
+const DEFAULT_LEVERAGE: u8 = 20;
+
+/// Waluta rozliczeniowa konta
+const MARGIN_ASSET: &str = "USDT";
+
+/// Błąd API: status HTTP, kod Binance i komunikat
+type ApiError = (StatusCode, i64, String);
+type Reply = std::result::Result<Value, ApiError>;
+type Shared = Arc<Mutex<MockState>>;
+
+/// Lokalna giełda perpetual zgodna z podzbiorem API Binance Futures
+///
+/// Serwer HTTP/WebSocket na 127.0.0.1 pozwala przejść pełny cykl życia
+/// zleceń bez sieci: weryfikuje podpisy HMAC, realizuje zlecenia po cenie
+/// mark ustawianej przez test, prowadzi pozycje netto (one-way), wysyła
+/// `ORDER_TRADE_UPDATE` strumieniem użytkownika i `markPriceUpdate`
+/// strumieniem rynkowym. Serwer zatrzymuje się razem z obiektem.
+pub struct MockPerpExchange {
+    addr: SocketAddr,
+    api_key: String,
+    api_secret: String,
+    state: Shared,
+    server: JoinHandle<()>,
+}
+
+impl MockPerpExchange {
+    /// Uruchamia giełdę na wolnym porcie z saldem USDT
+    pub async fn start(api_key: &str, api_secret: &str, balance: f64) -> Result<Self> {
+        let (events, _) = broadcast::channel(1024);
+        let (prices, _) = broadcast::channel(1024);
+        let (disconnects, _) = broadcast::channel(16);
+        let state = Arc::new(Mutex::new(MockState {
+            api_key: api_key.to_string(),
+            key: ring::hmac::Key::new(ring::hmac::HMAC_SHA256, api_secret.as_bytes()),
+            balance,
+            markets: HashMap::new(),
+            positions: HashMap::new(),
+            orders: HashMap::new(),
+            next_order_id: 1,
+            throttled: 0,
+            listen_keys: HashSet::new(),
+            events,
+            prices,
+            disconnects,
+        }));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .context("Failed to bind mock exchange")?;
+        let addr = listener.local_addr()?;
+        let app = router(state.clone());
+        let server = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                debug!("Mock exchange stopped: {}", e);
+            }
+        });
+
+        Ok(Self {
+            addr,
+            api_key: api_key.to_string(),
+            api_secret: api_secret.to_string(),
+            state,
+            server,
+        })
+    }
+
+    /// Adres REST API
+    pub fn rest_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Adres strumieni WebSocket
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// Konfiguracja adaptera wskazująca na tę giełdę
+    pub fn config(&self) -> ExchangeConfig {
+        ExchangeConfig {
+            rest_url: self.rest_url(),
+            ws_url: self.ws_url(),
+            api_key: Some(self.api_key.clone()),
+            api_key_env: None,
+            api_secret: Some(self.api_secret.clone()),
+            api_secret_env: None,
+            ..ExchangeConfig::default()
+        }
+    }
+
+    /// Dodaje kontrakt z ceną mark i stawką finansowania
+    pub async fn add_market(&self, symbol: &str, mark_price: f64, funding_rate: f64) {
+        self.state.lock().await.markets.insert(
+            symbol.to_string(),
+            MockMarket {
+                mark_price,
+                funding_rate,
+                leverage: DEFAULT_LEVERAGE,
+            },
+        );
+    }
+
+    /// Ustawia cenę mark, publikuje ją i realizuje aktywowane zlecenia
+    pub async fn set_mark_price(&self, symbol: &str, mark_price: f64) {
+        let mut state = self.state.lock().await;
+        if let Some(market) = state.markets.get_mut(symbol) {
+            market.mark_price = mark_price;
+            let event = json!({
+                "e": "markPriceUpdate",
+                "E": chrono::Utc::now().timestamp_millis(),
+                "s": symbol,
+                "p": mark_price.to_string(),
+                "r": market.funding_rate.to_string(),
+            });
+            let _ = state.prices.send((symbol.to_string(), event));
+            state.match_orders(symbol);
+        }
+    }
+
+    /// Ustawia stawkę finansowania kontraktu
+    pub async fn set_funding_rate(&self, symbol: &str, funding_rate: f64) {
+        if let Some(market) = self.state.lock().await.markets.get_mut(symbol) {
+            market.funding_rate = funding_rate;
+        }
+    }
+
+    /// Odrzuca kolejne `requests` żądań kodem 429 (limit żądań)
+    pub async fn throttle(&self, requests: u32) {
+        self.state.lock().await.throttled = requests;
+    }
+
+    /// Saldo portfela USDT (bez niezrealizowanego P&L)
+    pub async fn balance(&self) -> f64 {
+        self.state.lock().await.balance
+    }
+
+    /// Zrywa wszystkie otwarte połączenia WebSocket
+    pub async fn disconnect_streams(&self) {
+        let _ = self.state.lock().await.disconnects.send(());
+    }
+}
+
+impl Drop for MockPerpExchange {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MockMarket {
+    mark_price: f64,
+    funding_rate: f64,
+    leverage: u8,
+}
+
+/// Pozycja netto (ujemna wielkość = Short)
+#[derive(Debug, Clone, Default)]
+struct MockPosition {
+    amount: f64,
+    entry_price: f64,
+}
+
+#[derive(Debug, Clone)]
+struct MockOrder {
+    order_id: i64,
+    client_order_id: String,
+    symbol: String,
+    side: String,
+    order_type: String,
+    quantity: f64,
+    executed: f64,
+    price: f64,
+    stop_price: f64,
+    average_price: f64,
+    reduce_only: bool,
+    status: String,
+    update_time: i64,
+}
+
+impl MockOrder {
+    fn buying(&self) -> bool {
+        self.side == "BUY"
+    }
+
+    /// Czy zlecenie aktywuje się przy danej cenie mark
+    fn is_triggered(&self, mark: f64) -> bool {
+        match (self.order_type.as_str(), self.buying()) {
+            ("MARKET", _) => true,
+            ("LIMIT", true) => mark <= self.price,
+            ("LIMIT", false) => mark >= self.price,
+            ("STOP_MARKET", true) | ("TAKE_PROFIT_MARKET", false) => mark >= self.stop_price,
+            ("STOP_MARKET", false) | ("TAKE_PROFIT_MARKET", true) => mark <= self.stop_price,
+            _ => false,
+        }
+    }
+
+    /// Cena wykonania: limit nie gorzej niż cena limitu, reszta po cenie mark
+    fn fill_price(&self, mark: f64) -> f64 {
+        match (self.order_type.as_str(), self.buying()) {
+            ("LIMIT", true) => mark.min(self.price),
+            ("LIMIT", false) => mark.max(self.price),
+            _ => mark,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "orderId": self.order_id,
+            "clientOrderId": self.client_order_id,
+            "symbol": self.symbol,
+            "status": self.status,
+            "side": self.side,
+            "type": self.order_type,
+            "origQty": self.quantity.to_string(),
+            "executedQty": self.executed.to_string(),
+            "avgPrice": self.average_price.to_string(),
+            "price": self.price.to_string(),
+            "stopPrice": self.stop_price.to_string(),
+            "reduceOnly": self.reduce_only,
+            "updateTime": self.update_time,
+        })
+    }
+
+    fn to_event(&self) -> Value {
+        json!({
+            "e": "ORDER_TRADE_UPDATE",
+            "E": self.update_time,
+            "T": self.update_time,
+            "o": {
+                "s": self.symbol,
+                "c": self.client_order_id,
+                "S": self.side,
+                "o": self.order_type,
+                "q": self.quantity.to_string(),
+                "p": self.price.to_string(),
+                "ap": self.average_price.to_string(),
+                "sp": self.stop_price.to_string(),
+                "X": self.status,
+                "i": self.order_id,
+                "z": self.executed.to_string(),
+                "R": self.reduce_only,
+                "T": self.update_time,
+            }
+        })
+    }
+}
+
+struct MockState {
+    api_key: String,
+    key: ring::hmac::Key,
+    balance: f64,
+    markets: HashMap<String, MockMarket>,
+    positions: HashMap<String, MockPosition>,
+    orders: HashMap<String, MockOrder>,
+    next_order_id: i64,
+    throttled: u32,
+    listen_keys: HashSet<String>,
+    events: broadcast::Sender<String>,
+
+    /// Zdarzenia `markPriceUpdate` (symbol, zdarzenie)
+    prices: broadcast::Sender<(String, Value)>,
+
+    /// Sygnał zerwania połączeń WebSocket
+    disconnects: broadcast::Sender<()>,
+}
+
+impl MockState {
+    /// Limit żądań ustawiony przez `throttle`
+    fn admit(&mut self) -> std::result::Result<(), ApiError> {
+        if self.throttled > 0 {
+            self.throttled -= 1;
+            return Err(api_error(
+                StatusCode::TOO_MANY_REQUESTS,
+                -1003,
+                "Too many requests; current limit is exceeded.",
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_api_key(&self, headers: &HeaderMap) -> std::result::Result<(), ApiError> {
+        let api_key = headers.get("x-mbx-apikey").and_then(|v| v.to_str().ok());
+        if api_key != Some(self.api_key.as_str()) {
+            return Err(api_error(
+                StatusCode::UNAUTHORIZED,
+                -2015,
+                "Invalid API-key, IP, or permissions for action.",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Weryfikuje klucz, podpis i okno czasowe; zwraca parametry żądania
+    fn signed(
+        &mut self,
+        headers: &HeaderMap,
+        query: Option<&str>,
+    ) -> std::result::Result<HashMap<String, String>, ApiError> {
+        self.admit()?;
+        self.check_api_key(headers)?;
+
+        let query = query.unwrap_or_default();
+        let invalid_signature = || {
+            api_error(
+                StatusCode::BAD_REQUEST,
+                -1022,
+                "Signature for this request is not valid.",
+            )
+        };
+        let (payload, signature) = query
+            .rsplit_once("&signature=")
+            .ok_or_else(invalid_signature)?;
+        let signature = hex::decode(signature).map_err(|_| invalid_signature())?;
+        ring::hmac::verify(&self.key, payload.as_bytes(), &signature)
+            .map_err(|_| invalid_signature())?;
+
+        let params = parse_query(Some(payload));
+        let timestamp: i64 = required(&params, "timestamp")?;
+        let recv_window = params
+            .get("recvWindow")
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(5000);
+        if (chrono::Utc::now().timestamp_millis() - timestamp).abs() > recv_window {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                -1021,
+                "Timestamp for this request is outside of the recvWindow.",
+            ));
+        }
+
+        Ok(params)
+    }
+
+    fn market(&self, symbol: &str) -> std::result::Result<&MockMarket, ApiError> {
+        self.markets
+            .get(symbol)
+            .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, -1121, "Invalid symbol."))
+    }
+
+    fn unrealized_pnl(&self, symbol: &str, position: &MockPosition) -> f64 {
+        let mark = self.markets.get(symbol).map_or(0.0, |m| m.mark_price);
+        (mark - position.entry_price) * position.amount
+    }
+
+    fn used_margin(&self) -> f64 {
+        self.positions
+            .iter()
+            .map(|(symbol, position)| {
+                let leverage = self.markets.get(symbol).map_or(1, |m| m.leverage);
+                position.amount.abs() * position.entry_price / leverage as f64
+            })
+            .sum()
+    }
+
+    fn account(&self) -> Value {
+        let unrealized: f64 = self
+            .positions
+            .iter()
+            .map(|(symbol, position)| self.unrealized_pnl(symbol, position))
+            .sum();
+
+        json!({
+            "canTrade": true,
+            "totalWalletBalance": self.balance.to_string(),
+            "totalUnrealizedProfit": unrealized.to_string(),
+            "availableBalance": (self.balance - self.used_margin()).to_string(),
+        })
+    }
+
+    fn balances(&self) -> Value {
+        let unrealized: f64 = self
+            .positions
+            .iter()
+            .map(|(symbol, position)| self.unrealized_pnl(symbol, position))
+            .sum();
+
+        json!([{
+            "asset": MARGIN_ASSET,
+            "balance": self.balance.to_string(),
+            "crossUnPnl": unrealized.to_string(),
+            "availableBalance": (self.balance - self.used_margin()).to_string(),
+        }])
+    }
+
+    fn position_risk(&self) -> Value {
+        let positions: Vec<Value> = self
+            .positions
+            .iter()
+            .map(|(symbol, position)| {
+                let market = self.markets.get(symbol);
+                json!({
+                    "symbol": symbol,
+                    "positionAmt": position.amount.to_string(),
+                    "entryPrice": position.entry_price.to_string(),
+                    "markPrice": market.map_or(0.0, |m| m.mark_price).to_string(),
+                    "unRealizedProfit": self.unrealized_pnl(symbol, position).to_string(),
+                    "liquidationPrice": "0",
+                    "leverage": market.map_or(1, |m| m.leverage).to_string(),
+                })
+            })
+            .collect();
+        Value::Array(positions)
+    }
+
+    fn set_leverage(&mut self, params: &HashMap<String, String>) -> Reply {
+        let symbol = required::<String>(params, "symbol")?;
+        let leverage: u8 = required(params, "leverage")?;
+        if !(1..=125).contains(&leverage) {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                -4028,
+                "Leverage is not valid.",
+            ));
+        }
+
+        self.market(&symbol)?;
+        if let Some(market) = self.markets.get_mut(&symbol) {
+            market.leverage = leverage;
+        }
+        Ok(json!({ "symbol": symbol, "leverage": leverage }))
+    }
+
+    fn new_order(&mut self, params: &HashMap<String, String>) -> Reply {
+        let symbol = required::<String>(params, "symbol")?;
+        let mark = self.market(&symbol)?.mark_price;
+        let side = required::<String>(params, "side")?;
+        let order_type = required::<String>(params, "type")?;
+        let quantity: f64 = required(params, "quantity")?;
+        let reduce_only = params.get("reduceOnly").map(String::as_str) == Some("true");
+
+        if side != "BUY" && side != "SELL" {
+            return Err(api_error(StatusCode::BAD_REQUEST, -1117, "Invalid side."));
+        }
+        let (price, stop_price) = match order_type.as_str() {
+            "MARKET" => (0.0, 0.0),
+            "LIMIT" => (required(params, "price")?, 0.0),
+            "STOP_MARKET" | "TAKE_PROFIT_MARKET" => (0.0, required(params, "stopPrice")?),
+            _ => {
+                return Err(api_error(
+                    StatusCode::BAD_REQUEST,
+                    -1116,
+                    "Invalid orderType.",
+                ))
+            }
+        };
+        if quantity <= 0.0 {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                -4003,
+                "Quantity less than or equal to zero.",
+            ));
+        }
+
+        let order_id = self.next_order_id;
+        let client_order_id = params
+            .get("newClientOrderId")
+            .cloned()
+            .unwrap_or_else(|| format!("mock-{}", order_id));
+        if self.orders.contains_key(&client_order_id) {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                -4015,
+                "Client order id is not valid.",
+            ));
+        }
+
+        let order = MockOrder {
+            order_id,
+            client_order_id: client_order_id.clone(),
+            symbol,
+            side,
+            order_type,
+            quantity,
+            executed: 0.0,
+            price,
+            stop_price,
+            average_price: 0.0,
+            reduce_only,
+            status: "NEW".to_string(),
+            update_time: chrono::Utc::now().timestamp_millis(),
+        };
+
+        // Zlecenia natychmiastowe są odrzucane bez wpisu do księgi
+        if order.is_triggered(mark) {
+            let fill = self.check_fill(&order, order.fill_price(mark))?;
+            self.next_order_id += 1;
+            self.orders.insert(client_order_id.clone(), order);
+            self.apply_fill(&client_order_id, fill);
+        } else {
+            self.next_order_id += 1;
+            self.publish(&order);
+            self.orders.insert(client_order_id.clone(), order);
+        }
+
+        Ok(self.orders[&client_order_id].to_json())
+    }
+
+    fn find_order(
+        &self,
+        params: &HashMap<String, String>,
+    ) -> std::result::Result<String, ApiError> {
+        let not_found = || api_error(StatusCode::BAD_REQUEST, -2013, "Order does not exist.");
+
+        if let Some(client_order_id) = params.get("origClientOrderId") {
+            return self
+                .orders
+                .contains_key(client_order_id)
+                .then(|| client_order_id.clone())
+                .ok_or_else(not_found);
+        }
+
+        let order_id: i64 = required(params, "orderId")?;
+        self.orders
+            .values()
+            .find(|order| order.order_id == order_id)
+            .map(|order| order.client_order_id.clone())
+            .ok_or_else(not_found)
+    }
+
+    fn query_order(&self, params: &HashMap<String, String>) -> Reply {
+        let id = self.find_order(params)?;
+        Ok(self.orders[&id].to_json())
+    }
+
+    fn cancel_order(&mut self, params: &HashMap<String, String>) -> Reply {
+        let id = self.find_order(params)?;
+        let order = self.orders.get_mut(&id).expect("order exists");
+        if order.status != "NEW" {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                -2011,
+                "Unknown order sent.",
+            ));
+        }
+
+        order.status = "CANCELED".to_string();
+        order.update_time = chrono::Utc::now().timestamp_millis();
+        let order = order.clone();
+        self.publish(&order);
+        Ok(order.to_json())
+    }
+
+    /// Sprawdza margines i reduce-only; zwraca (wielkość ze znakiem, cena)
+    fn check_fill(
+        &self,
+        order: &MockOrder,
+        price: f64,
+    ) -> std::result::Result<(f64, f64), ApiError> {
+        let position = self
+            .positions
+            .get(&order.symbol)
+            .cloned()
+            .unwrap_or_default();
+        let direction = if order.buying() { 1.0 } else { -1.0 };
+        let mut quantity = order.quantity;
+
+        if order.reduce_only {
+            if position.amount * direction >= 0.0 {
+                return Err(api_error(
+                    StatusCode::BAD_REQUEST,
+                    -2022,
+                    "ReduceOnly Order is rejected.",
+                ));
+            }
+            quantity = quantity.min(position.amount.abs());
+        }
+
+        // Margines potrzebny tylko na część zwiększającą pozycję
+        let closing = if position.amount * direction < 0.0 {
+            quantity.min(position.amount.abs())
+        } else {
+            0.0
+        };
+        let leverage = self.market(&order.symbol)?.leverage as f64;
+        let required_margin =
+            (quantity - closing) * price / leverage + quantity * price * TAKER_FEE;
+        if required_margin > self.balance - self.used_margin() {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                -2019,
+                "Margin is insufficient.",
+            ));
+        }
+
+        Ok((quantity * direction, price))
+    }
+
+    /// Aktualizuje pozycję i saldo po wykonaniu zlecenia
+    fn apply_fill(&mut self, client_order_id: &str, (signed_quantity, price): (f64, f64)) {
+        let Some(order) = self.orders.get_mut(client_order_id) else {
+            return;
+        };
+        order.executed = signed_quantity.abs();
+        order.average_price = price;
+        order.status = "FILLED".to_string();
+        order.update_time = chrono::Utc::now().timestamp_millis();
+        let order = order.clone();
+
+        let position = self.positions.entry(order.symbol.clone()).or_default();
+        let mut realized = 0.0;
+        if position.amount * signed_quantity >= 0.0 {
+            let amount = position.amount + signed_quantity;
+            position.entry_price =
+                (position.amount * position.entry_price + signed_quantity * price) / amount;
+            position.amount = amount;
+        } else {
+            let closing = signed_quantity.abs().min(position.amount.abs());
+            realized = closing * (price - position.entry_price) * position.amount.signum();
+            let amount = position.amount + signed_quantity;
+            if amount.abs() < 1e-12 {
+                position.amount = 0.0;
+            } else {
+                // Odwrócenie pozycji otwiera nową po cenie wykonania
+                if amount.signum() != position.amount.signum() {
+                    position.entry_price = price;
+                }
+                position.amount = amount;
+            }
+        }
+        if position.amount == 0.0 {
+            self.positions.remove(&order.symbol);
+        }
+
+        self.balance += realized - order.executed * price * TAKER_FEE;
+        self.publish(&order);
+    }
+
+    /// Realizuje lub wygasza zlecenia z księgi aktywowane ceną mark
+    fn match_orders(&mut self, symbol: &str) {
+        let Some(mark) = self.markets.get(symbol).map(|m| m.mark_price) else {
+            return;
+        };
+
+        let mut triggered: Vec<(i64, String)> = self
+            .orders
+            .values()
+            .filter(|o| o.symbol == symbol && o.status == "NEW" && o.is_triggered(mark))
+            .map(|o| (o.order_id, o.client_order_id.clone()))
+            .collect();
+        triggered.sort();
+
+        for (_, id) in triggered {
+            let order = self.orders[&id].clone();
+            match self.check_fill(&order, order.fill_price(mark)) {
+                Ok(fill) => self.apply_fill(&id, fill),
+                Err((_, code, message)) => {
+                    debug!("Mock order {} expired ({}): {}", id, code, message);
+                    if let Some(order) = self.orders.get_mut(&id) {
+                        order.status = "EXPIRED".to_string();
+                        order.update_time = chrono::Utc::now().timestamp_millis();
+                        let order = order.clone();
+                        self.publish(&order);
+                    }
+                }
+            }
+        }
+    }
+
+    fn publish(&self, order: &MockOrder) {
+        // Brak subskrybentów nie jest błędem
+        let _ = self.events.send(order.to_event().to_string());
+    }
+}
+
+fn router(state: Shared) -> Router {
+    Router::new()
+        .route("/fapi/v1/exchangeInfo", get(exchange_info))
+        .route("/fapi/v1/premiumIndex", get(premium_index))
+        .route("/fapi/v2/account", get(account))
+        .route("/fapi/v2/balance", get(balances))
+        .route("/fapi/v2/positionRisk", get(position_risk))
+        .route("/fapi/v1/leverage", post(leverage))
+        .route(
+            "/fapi/v1/order",
+            post(new_order).get(query_order).delete(cancel_order),
+        )
+        .route(
+            "/fapi/v1/listenKey",
+            post(new_listen_key).put(keepalive_listen_key),
+        )
+        .route("/ws/:listen_key", get(user_stream))
+        .route("/stream", get(market_stream))
+        .with_state(state)
+}
+
+fn api_error(status: StatusCode, code: i64, message: &str) -> ApiError {
+    (status, code, message.to_string())
+}
+
+fn respond(reply: Reply) -> Response {
+    match reply {
+        Ok(body) => Json(body).into_response(),
+        Err((status, code, msg)) => {
+            let body = Json(json!({ "code": code, "msg": msg }));
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                (status, [(header::RETRY_AFTER, "1")], body).into_response()
+            } else {
+                (status, body).into_response()
+            }
+        }
+    }
+}
+
+fn parse_query(query: Option<&str>) -> HashMap<String, String> {
+    url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+        .into_owned()
+        .collect()
+}
+
+fn required<T: std::str::FromStr>(
+    params: &HashMap<String, String>,
+    name: &str,
+) -> std::result::Result<T, ApiError> {
+    let value = params.get(name).ok_or_else(|| {
+        api_error(
+            StatusCode::BAD_REQUEST,
+            -1102,
+            &format!("Mandatory parameter '{}' was not sent.", name),
+        )
+    })?;
+    value.parse().map_err(|_| {
+        api_error(
+            StatusCode::BAD_REQUEST,
+            -1100,
+            &format!("Illegal characters found in parameter '{}'.", name),
+        )
+    })
+}
+
+async fn exchange_info(State(state): State<Shared>) -> Response {
+    let mut state = state.lock().await;
+    respond(state.admit().map(|_| {
+        let symbols: Vec<Value> = state
+            .markets
+            .keys()
+            .map(|symbol| {
+                json!({
+                    "symbol": symbol,
+                    "filters": [
+                        { "filterType": "PRICE_FILTER", "tickSize": TICK_SIZE },
+                        { "filterType": "LOT_SIZE", "stepSize": STEP_SIZE, "minQty": STEP_SIZE },
+                    ],
+                })
+            })
+            .collect();
+        json!({ "symbols": symbols })
+    }))
+}
+
+async fn premium_index(State(state): State<Shared>, RawQuery(query): RawQuery) -> Response {
+    let mut state = state.lock().await;
+    let reply = state.admit().and_then(|_| {
+        let symbol = required::<String>(&parse_query(query.as_deref()), "symbol")?;
+        let market = state.market(&symbol)?;
+        let now = chrono::Utc::now().timestamp_millis();
+        let period = 8 * 3600 * 1000;
+
+        Ok(json!({
+            "symbol": symbol,
+            "markPrice": market.mark_price.to_string(),
+            "lastFundingRate": market.funding_rate.to_string(),
+            "nextFundingTime": (now / period + 1) * period,
+            "time": now,
+        }))
+    });
+    respond(reply)
+}
+
+async fn account(
+    State(state): State<Shared>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    let mut state = state.lock().await;
+    respond(
+        state
+            .signed(&headers, query.as_deref())
+            .map(|_| state.account()),
+    )
+}
+
+async fn balances(
+    State(state): State<Shared>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    let mut state = state.lock().await;
+    respond(
+        state
+            .signed(&headers, query.as_deref())
+            .map(|_| state.balances()),
+    )
+}
+
+async fn position_risk(
+    State(state): State<Shared>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    let mut state = state.lock().await;
+    respond(
+        state
+            .signed(&headers, query.as_deref())
+            .map(|_| state.position_risk()),
+    )
+}
+
+async fn leverage(
+    State(state): State<Shared>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    let mut state = state.lock().await;
+    respond(
+        state
+            .signed(&headers, query.as_deref())
+            .and_then(|params| state.set_leverage(&params)),
+    )
+}
+
+async fn new_order(
+    State(state): State<Shared>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    let mut state = state.lock().await;
+    respond(
+        state
+            .signed(&headers, query.as_deref())
+            .and_then(|params| state.new_order(&params)),
+    )
+}
+
+async fn query_order(
+    State(state): State<Shared>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    let mut state = state.lock().await;
+    respond(
+        state
+            .signed(&headers, query.as_deref())
+            .and_then(|params| state.query_order(&params)),
+    )
+}
+
+async fn cancel_order(
+    State(state): State<Shared>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    let mut state = state.lock().await;
+    respond(
+        state
+            .signed(&headers, query.as_deref())
+            .and_then(|params| state.cancel_order(&params)),
+    )
+}
+
+async fn new_listen_key(State(state): State<Shared>, headers: HeaderMap) -> Response {
+    let mut state = state.lock().await;
+    let reply = state.admit().and_then(|_| {
+        state.check_api_key(&headers)?;
+        let listen_key = uuid::Uuid::new_v4().simple().to_string();
+        state.listen_keys.insert(listen_key.clone());
+        Ok(json!({ "listenKey": listen_key }))
+    });
+    respond(reply)
+}
+
+async fn keepalive_listen_key(State(state): State<Shared>, headers: HeaderMap) -> Response {
+    let mut state = state.lock().await;
+    let reply = state
+        .admit()
+        .and_then(|_| state.check_api_key(&headers))
+        .map(|_| json!({}));
+    respond(reply)
+}
+
+async fn user_stream(
+    ws: WebSocketUpgrade,
+    Path(listen_key): Path<String>,
+    State(state): State<Shared>,
+) -> Response {
+    let (events, disconnects) = {
+        let state = state.lock().await;
+        if !state.listen_keys.contains(&listen_key) {
+            return respond(Err(api_error(
+                StatusCode::BAD_REQUEST,
+                -1125,
+                "This listenKey does not exist.",
+            )));
+        }
+        (state.events.subscribe(), state.disconnects.subscribe())
+    };
+
+    ws.on_upgrade(move |socket| forward_events(socket, events, disconnects, Some))
+}
+
+/// Strumień rynkowy `/stream?streams=<symbol>@markPrice@1s/...`
+async fn market_stream(
+    ws: WebSocketUpgrade,
+    RawQuery(query): RawQuery,
+    State(state): State<Shared>,
+) -> Response {
+    let streams: HashMap<String, String> = parse_query(query.as_deref())
+        .get("streams")
+        .map(|streams| {
+            streams
+                .split('/')
+                .filter_map(|name| {
+                    let (symbol, kind) = name.split_once('@')?;
+                    kind.starts_with("markPrice")
+                        .then(|| (symbol.to_uppercase(), name.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if streams.is_empty() {
+        return respond(Err(api_error(
+            StatusCode::BAD_REQUEST,
+            -1100,
+            "Illegal characters found in parameter 'streams'.",
+        )));
+    }
+
+    let (prices, disconnects) = {
+        let state = state.lock().await;
+        (state.prices.subscribe(), state.disconnects.subscribe())
+    };
+
+    ws.on_upgrade(move |socket| {
+        forward_events(socket, prices, disconnects, move |(symbol, event)| {
+            let stream = streams.get(&symbol)?;
+            Some(json!({ "stream": stream, "data": event }).to_string())
+        })
+    })
+}
+
+/// Przekazuje zdarzenia do klienta do czasu zerwania połączenia
+async fn forward_events<T: Clone>(
+    mut socket: WebSocket,
+    mut events: broadcast::Receiver<T>,
+    mut disconnects: broadcast::Receiver<()>,
+    render: impl Fn(T) -> Option<String>,
+) {
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => event,
+            _ = disconnects.recv() => break,
+        };
+        match event {
+            Ok(event) => {
+                let Some(text) = render(event) else { continue };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                debug!("Mock stream skipped {} events", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+    let _ = socket.close().await;
+}
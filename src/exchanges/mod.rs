@@ -0,0 +1,167 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use crate::config::ExchangeConfig;
+use crate::errors::{CerberusError, CerberusResult};
+use crate::risk::TradeSide;
+use crate::trading::{OrderStatus, OrderType, TradeOrder};
+
+pub mod binance;
+pub mod mock;
+
+pub use binance::{BinanceFuturesAdapter, BinanceMarkPriceStream};
+pub use mock::MockPerpExchange;
+
+/// Saldo aktywa na koncie giełdy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Balance {
+    /// Aktywo (np. USDT)
+    pub asset: String,
+
+    /// Saldo portfela (bez niezrealizowanego P&L)
+    pub wallet_balance: f64,
+
+    /// Środki dostępne pod nowe pozycje
+    pub available_balance: f64,
+
+    /// Niezrealizowany P&L otwartych pozycji
+    pub unrealized_pnl: f64,
+}
+
+/// Otwarta pozycja na giełdzie
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangePosition {
+    /// Token (symbol bez waluty kwotowania)
+    pub token: String,
+
+    /// Symbol kontraktu na giełdzie
+    pub symbol: String,
+
+    pub side: TradeSide,
+
+    /// Wielkość pozycji w jednostkach kontraktu
+    pub quantity: f64,
+
+    pub entry_price: f64,
+    pub mark_price: f64,
+    pub leverage: u8,
+    pub unrealized_pnl: f64,
+
+    /// Cena likwidacji podana przez giełdę (jeśli znana)
+    pub liquidation_price: Option<f64>,
+}
+
+/// Stawka finansowania kontraktu perpetual
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingRate {
+    pub token: String,
+    pub symbol: String,
+
+    /// Ostatnia stawka finansowania (0.0001 = 0.01% za okres)
+    pub rate: f64,
+
+    pub mark_price: f64,
+
+    /// Czas następnego rozliczenia (Unix timestamp)
+    pub next_funding_time: i64,
+}
+
+/// Zlecenie w stanie raportowanym przez giełdę
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeOrder {
+    /// Identyfikator nadany przez nas (`TradeOrder::id`)
+    pub order_id: String,
+
+    /// Identyfikator nadany przez giełdę
+    pub exchange_order_id: String,
+
+    pub token: String,
+    pub symbol: String,
+
+    /// Strona pozycji, której dotyczy zlecenie
+    pub side: TradeSide,
+
+    /// Czy zlecenie tylko redukuje pozycję
+    pub reduce_only: bool,
+
+    pub order_type: OrderType,
+    pub status: OrderStatus,
+
+    /// Wielkość zlecenia w jednostkach kontraktu
+    pub quantity: f64,
+
+    pub executed_quantity: f64,
+
+    /// Cena limitu lub aktywacji zlecenia stop
+    pub price: Option<f64>,
+
+    /// Średnia cena wykonania
+    pub average_price: Option<f64>,
+
+    /// Czas ostatniej zmiany (Unix timestamp)
+    pub updated_at: i64,
+}
+
+/// Adapter giełdy kontraktów perpetual
+///
+/// Adaptery przyjmują tokeny (np. `BONK`) i same mapują je na symbole
+/// giełdy. Rozmiar `TradeOrder::size` to wartość nominalna w walucie
+/// kwotowania; adapter przelicza ją na wielkość kontraktu. Zlecenia
+/// z `metadata.position_id` oraz zlecenia StopLoss/TakeProfit redukują
+/// pozycję po stronie `TradeOrder::side`.
+///
+/// Przekroczenie limitów giełdy zwraca `CerberusError::RateLimit`,
+/// a błędy połączenia `CerberusError::Network`.
+#[async_trait]
+pub trait ExchangeAdapter: Send + Sync {
+    /// Nazwa adaptera
+    fn name(&self) -> &str;
+
+    /// Symbol kontraktu dla tokena
+    fn symbol_for(&self, token: &str) -> String;
+
+    /// Weryfikuje klucze API i uprawnienia do handlu
+    async fn authenticate(&self) -> CerberusResult<()>;
+
+    /// Pobiera salda konta
+    async fn get_balances(&self) -> CerberusResult<Vec<Balance>>;
+
+    /// Pobiera otwarte pozycje
+    async fn get_positions(&self) -> CerberusResult<Vec<ExchangePosition>>;
+
+    /// Pobiera aktualną stawkę finansowania tokena
+    async fn get_funding_rate(&self, token: &str) -> CerberusResult<FundingRate>;
+
+    /// Składa zlecenie (ustawia też dźwignię symbolu)
+    async fn place_order(&self, order: &TradeOrder) -> CerberusResult<ExchangeOrder>;
+
+    /// Pobiera stan zlecenia
+    async fn get_order(&self, token: &str, order_id: &str) -> CerberusResult<ExchangeOrder>;
+
+    /// Anuluje zlecenie
+    async fn cancel_order(&self, token: &str, order_id: &str) -> CerberusResult<ExchangeOrder>;
+
+    /// Subskrybuje strumień zmian stanu zleceń konta
+    async fn subscribe_order_updates(&self) -> CerberusResult<mpsc::Receiver<ExchangeOrder>>;
+}
+
+/// Tworzy adapter wskazany w konfiguracji
+pub fn create_adapter(config: &ExchangeConfig) -> CerberusResult<Arc<dyn ExchangeAdapter>> {
+    match config.adapter.as_str() {
+        "binance_futures" => Ok(Arc::new(BinanceFuturesAdapter::new(config.clone())?)),
+        other => Err(CerberusError::Configuration {
+            message: format!("Unsupported exchange adapter: {}", other),
+        }),
+    }
+}
+
+/// Czy zlecenie redukuje istniejącą pozycję
+pub(crate) fn is_reducing(order: &TradeOrder) -> bool {
+    order.metadata.get("position_id").is_some()
+        || matches!(
+            order.order_type,
+            OrderType::StopLoss | OrderType::TakeProfit
+        )
+}
@@ -1,11 +1,14 @@
 //! CoinStats API client for price data
 
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::{debug, error, info, warn};
 
+use crate::trading::PriceSnapshotSource;
+
 /// CoinStats API client
 pub struct CoinStatsClient {
     api_key: String,
@@ -262,6 +265,18 @@ impl CoinStatsClient {
     }
 }
 
+/// REST fallback for the streaming price feed
+#[async_trait]
+impl PriceSnapshotSource for CoinStatsClient {
+    fn name(&self) -> &str {
+        "coinstats"
+    }
+
+    async fn fetch_prices(&self, tokens: &[String]) -> Result<HashMap<String, f64>> {
+        self.get_prices(tokens).await
+    }
+}
+
 /// Portfolio value calculation result
 #[derive(Debug, Serialize)]
 pub struct PortfolioValue {
@@ -2,6 +2,7 @@
 
 use anyhow::{Context, Result};
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
@@ -215,6 +216,166 @@ impl EvmIndexer {
         Ok(block_number)
     }
 
+    /// Sends a JSON-RPC request to the chain's configured endpoint
+    async fn rpc_call<T: DeserializeOwned>(
+        &self,
+        chain: &Chain,
+        method: &str,
+        params: Value,
+    ) -> Result<T> {
+        let rpc_url = self
+            .config
+            .rpc_urls
+            .get(chain)
+            .ok_or_else(|| anyhow::anyhow!("No RPC URL configured for {:?}", chain))?;
+
+        let request = RpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id: 1,
+        };
+
+        let response: RpcResponse<T> = self
+            .http_client
+            .post(rpc_url)
+            .json(&request)
+            .send()
+            .await
+            .with_context(|| format!("Failed to send {} request", method))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse {} response", method))?;
+
+        if let Some(error) = response.error {
+            return Err(anyhow::anyhow!(
+                "RPC error: {} ({})",
+                error.message,
+                error.code
+            ));
+        }
+
+        response
+            .result
+            .ok_or_else(|| anyhow::anyhow!("No result in {} response", method))
+    }
+
+    /// Gets contract bytecode at a block (latest if `None`); "0x" for non-contracts
+    pub async fn get_code(
+        &self,
+        chain: &Chain,
+        address: &str,
+        block_number: Option<u64>,
+    ) -> Result<String> {
+        let block = block_number
+            .map(|b| format!("0x{:x}", b))
+            .unwrap_or_else(|| "latest".to_string());
+        self.rpc_call(chain, "eth_getCode", json!([address, block]))
+            .await
+    }
+
+    /// Finds the block in which a contract was deployed
+    ///
+    /// Binary search over `eth_getCode`, so historical state (an archive
+    /// node) is required. Returns `None` if there is no code at the address.
+    pub async fn find_deployment_block(&self, chain: &Chain, address: &str) -> Result<Option<u64>> {
+        let latest = self.get_current_block(chain).await?;
+        if !has_code(&self.get_code(chain, address, Some(latest)).await?) {
+            return Ok(None);
+        }
+
+        let (mut low, mut high) = (0, latest);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if has_code(&self.get_code(chain, address, Some(middle)).await?) {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+
+        Ok(Some(low))
+    }
+
+    /// Gets the timestamp of a block
+    pub async fn get_block_timestamp(&self, chain: &Chain, block_number: u64) -> Result<i64> {
+        let block: Value = self
+            .rpc_call(
+                chain,
+                "eth_getBlockByNumber",
+                json!([format!("0x{:x}", block_number), false]),
+            )
+            .await?;
+
+        let timestamp = block
+            .get("timestamp")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Block {} has no timestamp", block_number))?;
+        Ok(parse_hex_u128(timestamp)? as i64)
+    }
+
+    /// Calls a read-only contract function at the latest block
+    pub async fn call_contract(&self, chain: &Chain, to: &str, data: &str) -> Result<String> {
+        self.rpc_call(
+            chain,
+            "eth_call",
+            json!([{ "to": to, "data": data }, "latest"]),
+        )
+        .await
+    }
+
+    /// Gets ERC-20 total supply (raw units)
+    pub async fn get_total_supply(&self, chain: &Chain, token_address: &str) -> Result<u128> {
+        // totalSupply()
+        let result = self.call_contract(chain, token_address, "0x18160ddd").await?;
+        parse_hex_u128(&result)
+    }
+
+    /// Gets ERC-20 decimals
+    pub async fn get_token_decimals(&self, chain: &Chain, token_address: &str) -> Result<u8> {
+        // decimals()
+        let result = self.call_contract(chain, token_address, "0x313ce567").await?;
+        u8::try_from(parse_hex_u128(&result)?).context("Token decimals out of range")
+    }
+
+    /// Gets ERC-20 balance of a holder (raw units) at the latest block
+    pub async fn get_holder_balance(
+        &self,
+        chain: &Chain,
+        token_address: &str,
+        holder: &str,
+    ) -> Result<u128> {
+        // balanceOf(address)
+        let data = format!("0x70a08231{:0>64}", holder.trim_start_matches("0x"));
+        let result = self.call_contract(chain, token_address, &data).await?;
+        parse_hex_u128(&result)
+    }
+
+    /// Gets Uniswap V2 style pair reserves as (token0, reserve0, reserve1)
+    pub async fn get_pair_reserves(
+        &self,
+        chain: &Chain,
+        pair_address: &str,
+    ) -> Result<(String, u128, u128)> {
+        // token0()
+        let token0 = self.call_contract(chain, pair_address, "0x0dfe1681").await?;
+        let token0 = word(&token0, 0)
+            .map(|w| format!("0x{}", &w[24..]))
+            .ok_or_else(|| anyhow::anyhow!("Invalid token0 response from {}", pair_address))?;
+
+        // getReserves() -> (uint112, uint112, uint32)
+        let reserves = self.call_contract(chain, pair_address, "0x0902f1ac").await?;
+        let reserve0 = word(&reserves, 0).map(parse_hex_u128).transpose()?;
+        let reserve1 = word(&reserves, 1).map(parse_hex_u128).transpose()?;
+        match (reserve0, reserve1) {
+            (Some(reserve0), Some(reserve1)) => Ok((token0, reserve0, reserve1)),
+            _ => Err(anyhow::anyhow!(
+                "Invalid getReserves response from {}",
+                pair_address
+            )),
+        }
+    }
+
     /// Gets token balance for ERC-20 token
     pub async fn get_token_balance(
         &self,
@@ -380,3 +541,42 @@ impl EvmIndexer {
         Ok(gas_limit)
     }
 }
+
+/// Whether `eth_getCode` returned contract bytecode
+fn has_code(code: &str) -> bool {
+    !code.trim_start_matches("0x").is_empty()
+}
+
+/// Parses a hex quantity or ABI word ("0x..."); empty data is zero
+fn parse_hex_u128(hex: &str) -> Result<u128> {
+    let digits = hex.trim_start_matches("0x").trim_start_matches('0');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u128::from_str_radix(digits, 16).with_context(|| format!("Failed to parse hex value {}", hex))
+}
+
+/// Returns the n-th 32-byte word of ABI-encoded call data
+fn word(data: &str, index: usize) -> Option<&str> {
+    let data = data.trim_start_matches("0x");
+    data.get(index * 64..(index + 1) * 64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abi_words_and_hex_values() {
+        let reserves = format!("0x{:0>64x}{:0>64x}{:0>64x}", 1_500u64, 42u64, 1_700_000_000u64);
+        assert_eq!(word(&reserves, 1).map(parse_hex_u128).unwrap().unwrap(), 42);
+        assert!(word(&reserves, 3).is_none());
+
+        assert_eq!(parse_hex_u128("0x").unwrap(), 0);
+        assert_eq!(parse_hex_u128("0x1b").unwrap(), 27);
+        assert!(parse_hex_u128("0xzz").is_err());
+
+        assert!(has_code("0x6080604052"));
+        assert!(!has_code("0x"));
+    }
+}
@@ -22,6 +22,7 @@ pub mod cache;
 pub mod config;
 pub mod database;
 pub mod errors;
+pub mod exchanges;
 pub mod indexer;
 pub mod monitoring;
 // pub mod redis; // temporarily disabled until productionization
@@ -1,5 +1,6 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use sentry::integrations::tracing::EventFilter;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::signal;
 use tracing::{error, info, warn};
@@ -7,10 +8,13 @@ use tracing_subscriber::prelude::*;
 
 mod alerts;
 mod api;
+mod bitcoin;
 mod cache;
 mod config;
 mod database;
 mod errors;
+mod exchanges;
+mod indexer;
 mod monitoring;
 mod risk;
 mod security;
@@ -18,16 +22,32 @@ mod signals;
 mod trading;
 mod wallets;
 
+use alerts::AlertManager;
 use config::Config;
-use database::DatabaseManager;
+use database::{DatabaseManager, SignalStore, StateStore, TradeLedger};
 use monitoring::SystemMetrics;
+use risk::{CircuitBreaker, EmergencyStop, EmergencyTrigger, RiskManager, TradeLimiter};
+use signals::{SignalProcessor, TokenScreener};
+use tokio::sync::RwLock;
+use trading::{
+    Backtester, PaperExchange, PriceFeed, PriceQuote, TradeExecutorTrait, TradingEngine,
+};
 // use api::ApiServer;
 
 /// Główna struktura aplikacji Cerberus
 pub struct CerberusApp {
     config: Arc<Config>,
     db_manager: Arc<DatabaseManager>,
-    metrics: Arc<SystemMetrics>,
+    ledger: Arc<TradeLedger>,
+    risk_manager: Arc<RiskManager>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    alert_manager: Arc<tokio::sync::Mutex<AlertManager>>,
+    emergency_stop: Arc<EmergencyStop>,
+    signal_processor: Arc<SignalProcessor>,
+    paper_exchange: Option<Arc<PaperExchange>>,
+    price_feed: Option<Arc<PriceFeed>>,
+    trading_engine: Arc<TradingEngine>,
+    metrics: Arc<RwLock<SystemMetrics>>,
 }
 
 impl CerberusApp {
@@ -39,18 +59,155 @@ impl CerberusApp {
         // Inicjalizacja bazy danych
         let db_manager = Arc::new(DatabaseManager::new(&config.database).await?);
 
-        // Inicjalizacja systemu metryk
-        let metrics = Arc::new(SystemMetrics::new());
+        // Rejestr zleceń i pozycji (stan przetrwa restart)
+        let ledger = Arc::new(TradeLedger::new(Arc::new(db_manager.pool().clone())));
+        let open_positions = ledger.load_open_positions().await?;
+        info!(
+            "Restored {} open positions from ledger",
+            open_positions.len()
+        );
+
+        // Strumień cen obserwuje tokeny otwartych pozycji
+        let price_feed = if config.trading.price_feed.enabled {
+            let feed = Arc::new(Self::create_price_feed(&config).await?);
+            for position in &open_positions {
+                feed.watch(&position.token).await;
+            }
+            Some(feed)
+        } else {
+            None
+        };
+
+        // Circuit breaker z trwałym stanem
+        let circuit_breaker = Arc::new(
+            CircuitBreaker::with_store(
+                &config.risk,
+                StateStore::new(Arc::new(db_manager.pool().clone())),
+            )
+            .await?,
+        );
+
+        // Limity transakcji z trwałymi licznikami i blokadami tokenów
+        let trade_limiter = Arc::new(
+            TradeLimiter::with_store(
+                &config.risk.trade_limits,
+                StateStore::new(Arc::new(db_manager.pool().clone())),
+            )
+            .await?,
+        );
+
+        // Inicjalizacja managera ryzyka z odtworzonymi pozycjami
+        let risk_manager = Arc::new(
+            RiskManager::from_config(&config)
+                .with_circuit_breaker(circuit_breaker.clone())
+                .with_trade_limiter(trade_limiter),
+        );
+        let mut portfolio = risk_manager.portfolio().await;
+        for position in open_positions.iter().cloned() {
+            portfolio.add_position(position);
+        }
+        risk_manager.update_portfolio(&portfolio).await;
+
+        // Historia zamkniętych pozycji dla polityki rozmiaru Kelly'ego
+        for pnl in ledger.closed_position_pnls().await? {
+            risk_manager.record_trade_outcome(pnl).await;
+        }
+
+        // Inicjalizacja systemu metryk i alertów
+        let metrics = Arc::new(RwLock::new(SystemMetrics::new()));
+        let alert_manager = Arc::new(tokio::sync::Mutex::new(AlertManager::from_config(
+            &config.alerts,
+        )));
+
+        // Executor transakcji (na razie tylko symulator paper trading)
+        if !config.trading.paper_trading {
+            anyhow::bail!("Live trading executor not configured; enable trading.paper_trading");
+        }
+        let paper_exchange = Arc::new(PaperExchange::from_config(&config.trading));
+        paper_exchange.restore_positions(open_positions).await;
+        let executor: Arc<dyn TradeExecutorTrait> = paper_exchange.clone();
+
+        // Emergency stop działa na tym samym executorze co trading
+        let emergency_stop = Arc::new(
+            EmergencyStop::new(
+                executor.clone(),
+                ledger.clone(),
+                circuit_breaker.clone(),
+                Arc::new(db_manager.pool().clone()),
+                config.risk.emergency_stop_on_circuit_breaker,
+            )
+            .await?
+            .with_risk_manager(risk_manager.clone()),
+        );
+        if emergency_stop.is_active().await {
+            warn!("Emergency stop is active - signals will be refused until reset");
+        }
+
+        // Procesor sygnałów z kontrolą tokenów (webhooki rejestrowane przy starcie API)
+        let signal_processor = Arc::new(
+            SignalProcessor::new(
+                config.signals.clone(),
+                SignalStore::new(Arc::new(db_manager.pool().clone())),
+            )
+            .with_screener(TokenScreener::from_config(&config.signals.screening).await?),
+        );
+
+        // Silnik decyzyjny pętli tradingu
+        let trading_engine = Arc::new(TradingEngine::new(
+            executor,
+            risk_manager.clone(),
+            ledger.clone(),
+            circuit_breaker.clone(),
+            emergency_stop.clone(),
+            alert_manager.clone(),
+            metrics.clone(),
+        ));
+        let restored_plans = trading_engine.restore_exit_plans().await?;
+        info!("Restored exit plans for {} positions", restored_plans);
 
         info!("Cerberus application initialized successfully");
 
         Ok(Self {
             config,
             db_manager,
+            ledger,
+            risk_manager,
+            circuit_breaker,
+            alert_manager,
+            emergency_stop,
+            signal_processor,
+            paper_exchange: Some(paper_exchange),
+            price_feed,
+            trading_engine,
             metrics,
         })
     }
 
+    /// Tworzy serwis cen: mark price z giełdy i fallback CoinStats
+    async fn create_price_feed(config: &Config) -> Result<PriceFeed> {
+        let settings = &config.trading.price_feed;
+        let mut feed = PriceFeed::new(settings.clone()).with_stream(Arc::new(
+            exchanges::BinanceMarkPriceStream::new(&config.exchange),
+        ));
+
+        let coinstats_key = settings
+            .coinstats_api_key_env
+            .as_ref()
+            .and_then(|name| std::env::var(name).ok())
+            .filter(|key| !key.is_empty());
+        match coinstats_key {
+            Some(api_key) => {
+                let client =
+                    indexer::coinstats::CoinStatsClient::new(api_key, reqwest::Client::new())
+                        .await?;
+                feed = feed.with_fallback(Arc::new(client));
+            }
+            None => warn!("CoinStats API key not set - price feed runs without REST fallback"),
+        }
+
+        Ok(feed)
+    }
+
     /// Uruchamia główną pętlę aplikacji
     pub async fn run(&self) -> Result<()> {
         info!("Starting Cerberus v4.0 - Leverage Trading Framework");
@@ -99,16 +256,17 @@ impl CerberusApp {
             metrics: self.metrics.clone(),
             wallet_manager,
             wallet_sync,
+            ledger: self.ledger.clone(),
+            risk_manager: self.risk_manager.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
+            alert_manager: self.alert_manager.clone(),
+            emergency_stop: self.emergency_stop.clone(),
+            signal_processor: self.signal_processor.clone(),
+            webhooks: Arc::new(self.signal_processor.register_webhooks().await),
         };
 
         let api_future = tokio::spawn(async move {
-            let mut api_server = match api::ApiServer::new(
-                api_state.config.clone(),
-                api_state.db_manager.clone(),
-                api_state.metrics.clone(),
-            )
-            .await
-            {
+            let mut api_server = match api::ApiServer::new_with_state(api_state).await {
                 Ok(server) => server,
                 Err(e) => {
                     error!("Failed to create API server: {}", e);
@@ -121,6 +279,12 @@ impl CerberusApp {
             }
         });
 
+        // Cykliczne odpytywanie źródeł sygnałów
+        let signal_future = tokio::spawn(self.signal_processor.clone().run());
+
+        // Strumień cen w czasie rzeczywistym
+        let price_future = self.price_feed.clone().map(|feed| tokio::spawn(feed.run()));
+
         // Główna pętla z graceful shutdown
         tokio::select! {
             _ = signal::ctrl_c() => {
@@ -134,15 +298,120 @@ impl CerberusApp {
             }
         }
 
+        signal_future.abort();
+        if let Some(price_future) = price_future {
+            price_future.abort();
+        }
+
         self.shutdown().await?;
         Ok(())
     }
 
-    /// Główna pętla tradingu (placeholder)
+    /// Główna pętla tradingu
+    ///
+    /// Przyjęte sygnały trafiają do silnika decyzyjnego, a co
+    /// `position_check_interval` sekund otwarte pozycje są wyceniane
+    /// i zamykane po osiągnięciu warunków wyjścia. Notowania ze strumienia
+    /// cen na bieżąco aktualizują symulator giełdy i korelacje tokenów.
     async fn run_trading_loop(&self) -> Result<()> {
+        let mut signals = self.signal_processor.subscribe();
+        let mut quotes = self.price_feed.as_ref().map(|feed| feed.subscribe());
+        let mut position_check = tokio::time::interval(tokio::time::Duration::from_secs(
+            self.config.trading.position_check_interval.max(1),
+        ));
+
         loop {
-            // Placeholder dla głównej logiki tradingu
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+            tokio::select! {
+                received = signals.recv() => match received {
+                    Ok(scored) => {
+                        let streamed = match &self.price_feed {
+                            Some(feed) => {
+                                feed.watch(&scored.signal.token).await;
+                                feed.fresh_price(&scored.signal.token).await.is_ok()
+                            }
+                            None => false,
+                        };
+
+                        // Bez aktualnej ceny ze strumienia cena sygnału jest
+                        // ostatnią znaną ceną rynkową
+                        if let Some(paper) = self.paper_exchange.as_ref().filter(|_| !streamed) {
+                            paper.update_price(&scored.signal.token, scored.signal.price).await;
+                        }
+
+                        if let Err(e) = self.trading_engine.handle_signal(&scored).await {
+                            error!("Failed to handle signal {}: {}", scored.signal.id, e);
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Trading loop lagged behind, {} signals skipped", skipped);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                        anyhow::bail!("Signal channel closed");
+                    }
+                },
+                quote = next_quote(&mut quotes) => {
+                    if let Some(paper) = &self.paper_exchange {
+                        paper.update_price(&quote.token, quote.price).await;
+                    }
+                    self.risk_manager
+                        .record_price(&quote.token, quote.price, quote.timestamp)
+                        .await;
+                }
+                _ = position_check.tick() => {
+                    // Circuit breaker może wymusić emergency stop (jeśli włączone w konfiguracji)
+                    match self.emergency_stop.check_circuit_breaker().await {
+                        Ok(Some(report)) => warn!(
+                            "Emergency stop triggered by circuit breaker: {} orders cancelled, {} positions closed",
+                            report.cancelled_count(),
+                            report.closed_count()
+                        ),
+                        Ok(None) => {}
+                        Err(e) => error!("Circuit breaker check failed: {}", e),
+                    }
+
+                    match self.trading_engine.check_positions().await {
+                        Ok(exits) => {
+                            for exit in exits {
+                                info!(
+                                    "Position {} ({}) closed by {} @ {:.8}, P&L {:.4}",
+                                    exit.position_id,
+                                    exit.token,
+                                    exit.reason,
+                                    exit.exit_price,
+                                    exit.realized_pnl
+                                );
+                            }
+                        }
+                        Err(e) => error!("Position check failed: {}", e),
+                    }
+
+                    self.sync_price_feed().await;
+                }
+            }
+        }
+    }
+
+    /// Ogranicza obserwowane tokeny do otwartych pozycji i ostrzega
+    /// o pozycjach wycenianych nieaktualną ceną
+    async fn sync_price_feed(&self) {
+        let Some(feed) = &self.price_feed else {
+            return;
+        };
+
+        let portfolio = self.risk_manager.portfolio().await;
+        for token in feed.watched().await {
+            let open = portfolio
+                .open_positions
+                .iter()
+                .any(|position| position.token.eq_ignore_ascii_case(&token));
+            if !open {
+                feed.unwatch(&token).await;
+            }
+        }
+
+        let stale = feed.stale_tokens().await;
+        if !stale.is_empty() {
+            warn!("Stale prices for open positions: {}", stale.join(", "));
         }
     }
 
@@ -150,9 +419,34 @@ impl CerberusApp {
     async fn shutdown(&self) -> Result<()> {
         info!("Shutting down Cerberus application...");
 
-        // Zamknięcie wszystkich pozycji (jeśli są otwarte)
-        // Zapisanie stanu do bazy danych
-        // Zamknięcie połączeń
+        // Pozycje przetrwają restart - zamykane są tylko przy aktywnym emergency
+        // stopie lub gdy tak skonfigurowano
+        let flatten =
+            self.config.trading.flatten_on_shutdown || self.emergency_stop.is_active().await;
+        let open_positions = self.ledger.load_open_positions().await?;
+        let open_orders = self.ledger.open_orders().await?;
+        if flatten && (!open_positions.is_empty() || !open_orders.is_empty()) {
+            let report = self
+                .emergency_stop
+                .flatten(EmergencyTrigger::Shutdown, "application shutdown")
+                .await?;
+            info!(
+                "Shutdown flatten: {} orders cancelled, {} positions closed",
+                report.cancelled_count(),
+                report.closed_count()
+            );
+            if !report.is_complete() {
+                warn!("Some orders or positions could not be closed during shutdown");
+            }
+        } else if !open_positions.is_empty() {
+            info!(
+                "Keeping {} open positions across restart",
+                open_positions.len()
+            );
+        }
+
+        // Stan jest zapisywany na bieżąco - zamknięcie połączeń z bazą
+        self.db_manager.pool().close().await;
 
         info!("Cerberus application shut down successfully");
         Ok(())
@@ -219,11 +513,93 @@ fn init_observability() -> Result<sentry::ClientInitGuard> {
     Ok(_guard)
 }
 
+/// Następne notowanie ze strumienia cen (bez strumienia czeka bez końca)
+async fn next_quote(
+    quotes: &mut Option<tokio::sync::broadcast::Receiver<PriceQuote>>,
+) -> PriceQuote {
+    loop {
+        let Some(receiver) = quotes else {
+            return std::future::pending().await;
+        };
+        match receiver.recv().await {
+            Ok(quote) => return quote,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!(
+                    "Trading loop lagged behind, {} price updates skipped",
+                    skipped
+                );
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => *quotes = None,
+        }
+    }
+}
+
+/// Tryb backtestu:
+/// `cerberus-bot backtest --candles <plik> --signals <plik> [--output <raport.json>]`
+///
+/// Działa bez sieci: logi tylko na konsolę, alerty wyłączone, baza w pamięci.
+async fn run_backtest(args: &[String]) -> Result<()> {
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_target(true))
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "cerberus=info,warn".into()),
+        )
+        .init();
+
+    let mut candles = None;
+    let mut signals = None;
+    let mut output = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .map(PathBuf::from)
+            .with_context(|| format!("Missing value for {}", arg))?;
+        match arg.as_str() {
+            "--candles" => candles = Some(value),
+            "--signals" => signals = Some(value),
+            "--output" => output = Some(value),
+            other => anyhow::bail!("Unknown backtest option: {}", other),
+        }
+    }
+
+    let candles = candles.context("Missing --candles <file>")?;
+    let signals = signals.context("Missing --signals <file>")?;
+
+    let config = Config::load()?;
+    config.validate()?;
+
+    let candles = trading::backtest::load_candles(&candles)?;
+    let signals = trading::backtest::load_signals(&signals)?;
+    let report = Backtester::new(config).run(candles, signals).await?;
+
+    println!("{}", report.summary());
+    if let Some(output) = output {
+        std::fs::write(&output, serde_json::to_string_pretty(&report)?)
+            .with_context(|| format!("Failed to write report to {}", output.display()))?;
+        info!("Backtest report written to {}", output.display());
+    }
+
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Ładowanie zmiennych środowiskowych
     dotenvy::dotenv().ok();
 
+    // Backtest uruchamiany jako podkomenda, bez Sentry i połączeń sieciowych
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("backtest") {
+        if let Err(e) = run_backtest(&args[1..]).await {
+            eprintln!("Backtest failed: {:?}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Inicjalizacja systemu obserwacji (logging + Sentry)
     let _guard = init_observability()?;
 
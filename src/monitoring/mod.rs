@@ -16,10 +16,10 @@ pub struct SystemMetrics {
     /// Całkowita liczba sygnałów
     pub total_signals: u64,
 
-    /// Liczba udanych transakcji
+    /// Liczba transakcji zamkniętych z zyskiem
     pub successful_trades: u64,
 
-    /// Liczba nieudanych transakcji
+    /// Liczba transakcji zamkniętych ze stratą
     pub failed_trades: u64,
 
     /// Aktualny balans
@@ -0,0 +1,481 @@
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+use crate::config::RiskConfig;
+use crate::database::StateStore;
+use crate::monitoring::{AlertLevel, SystemAlert};
+use crate::trading::{ExecutionResult, FundingPayment, TradingStats};
+
+/// Klucz stanu circuit breakera w `risk_state`
+const STATE_KEY: &str = "circuit_breaker";
+
+/// Komponent zgłaszany w alertach
+const COMPONENT: &str = "circuit_breaker";
+
+/// Powód zadziałania circuit breakera
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TripReason {
+    /// Dzienna zrealizowana strata przekroczyła próg
+    DailyLoss,
+
+    /// Zbyt wiele kolejnych nieudanych transakcji
+    ConsecutiveFailures,
+
+    /// Ręczne zatrzymanie (np. emergency stop)
+    Manual(String),
+}
+
+impl std::fmt::Display for TripReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TripReason::DailyLoss => write!(f, "daily loss limit"),
+            TripReason::ConsecutiveFailures => write!(f, "consecutive failures"),
+            TripReason::Manual(reason) => write!(f, "manual: {}", reason),
+        }
+    }
+}
+
+/// Trwały stan circuit breakera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerState {
+    /// Czy handel jest zablokowany
+    pub tripped: bool,
+
+    /// Powód blokady
+    pub reason: Option<TripReason>,
+
+    /// Czas zadziałania
+    pub tripped_at: Option<i64>,
+
+    /// Bieżący dzień handlowy (UTC, YYYY-MM-DD)
+    pub trading_day: String,
+
+    /// Zrealizowany P&L w bieżącym dniu
+    pub daily_realized_pnl: f64,
+
+    /// Liczba kolejnych nieudanych transakcji
+    pub consecutive_failures: u32,
+
+    /// Liczba transakcji w bieżącym dniu
+    pub daily_trades: u64,
+
+    /// Liczba nieudanych transakcji w bieżącym dniu
+    pub daily_failures: u64,
+}
+
+impl CircuitBreakerState {
+    fn new(now: i64) -> Self {
+        Self {
+            tripped: false,
+            reason: None,
+            tripped_at: None,
+            trading_day: utc_day(now),
+            daily_realized_pnl: 0.0,
+            consecutive_failures: 0,
+            daily_trades: 0,
+            daily_failures: 0,
+        }
+    }
+}
+
+/// Status circuit breakera dla API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerStatus {
+    pub active: bool,
+    pub reason: Option<String>,
+    pub tripped_at: Option<i64>,
+    pub trading_day: String,
+    pub daily_loss: f64,
+    pub daily_loss_limit: f64,
+    pub consecutive_failures: u32,
+    pub max_consecutive_failures: u32,
+    pub daily_trades: u64,
+    pub daily_failures: u64,
+    pub daily_fees: f64,
+    pub daily_funding: f64,
+}
+
+/// Circuit breaker blokujący handel po dziennej stracie lub serii niepowodzeń
+///
+/// Blokada trwa do zmiany dnia UTC albo ręcznego resetu (blokada ręczna
+/// tylko do ręcznego resetu). Stan jest zapisywany
+/// w bazie (jeśli podano `StateStore`), więc restart nie zdejmuje blokady.
+pub struct CircuitBreaker {
+    loss_threshold: f64,
+    max_consecutive_failures: u32,
+    state: Arc<RwLock<CircuitBreakerState>>,
+
+    /// Dzienne statystyki (tylko w pamięci)
+    daily_stats: Arc<RwLock<TradingStats>>,
+
+    store: Option<StateStore>,
+}
+
+impl CircuitBreaker {
+    /// Tworzy circuit breaker bez trwałego stanu
+    pub fn new(config: &RiskConfig) -> Self {
+        Self {
+            loss_threshold: config.circuit_breaker_threshold.to_f64().unwrap_or(0.0),
+            max_consecutive_failures: config.max_consecutive_failures,
+            state: Arc::new(RwLock::new(CircuitBreakerState::new(
+                chrono::Utc::now().timestamp(),
+            ))),
+            daily_stats: Arc::new(RwLock::new(TradingStats::default())),
+            store: None,
+        }
+    }
+
+    /// Tworzy circuit breaker z trwałym stanem i odtwarza zapisany stan
+    pub async fn with_store(config: &RiskConfig, store: StateStore) -> Result<Self> {
+        let mut breaker = Self::new(config);
+
+        if let Some(state) = store.load::<CircuitBreakerState>(STATE_KEY).await? {
+            if state.tripped {
+                warn!(
+                    "Circuit breaker restored in tripped state ({})",
+                    state
+                        .reason
+                        .as_ref()
+                        .map(|r| r.to_string())
+                        .unwrap_or_default()
+                );
+            }
+            *breaker.state.write().await = state;
+        }
+
+        breaker.store = Some(store);
+        Ok(breaker)
+    }
+
+    /// Rejestruje wynik transakcji i zrealizowany P&L
+    ///
+    /// Zwraca alert, jeśli circuit breaker zmienił stan.
+    pub async fn record_trade(
+        &self,
+        result: &ExecutionResult,
+        execution_time_ms: f64,
+        pnl: f64,
+    ) -> Result<Vec<SystemAlert>> {
+        self.record_trade_at(
+            result,
+            execution_time_ms,
+            pnl,
+            chrono::Utc::now().timestamp(),
+        )
+        .await
+    }
+
+    /// Jak `record_trade`, z podanym czasem (dla symulacji i testów)
+    pub async fn record_trade_at(
+        &self,
+        result: &ExecutionResult,
+        execution_time_ms: f64,
+        pnl: f64,
+        now: i64,
+    ) -> Result<Vec<SystemAlert>> {
+        let mut alerts = Vec::new();
+        let mut state = self.state.write().await;
+        let mut daily_stats = self.daily_stats.write().await;
+
+        if let Some(alert) = self.roll_over(&mut state, &mut daily_stats, now) {
+            alerts.push(alert);
+        }
+
+        daily_stats.update_for_trade(result, execution_time_ms, pnl);
+
+        state.daily_trades += 1;
+        state.daily_realized_pnl += pnl;
+        if result.success {
+            state.consecutive_failures = 0;
+        } else {
+            state.consecutive_failures += 1;
+            state.daily_failures += 1;
+        }
+
+        if !state.tripped {
+            let reason = if state.daily_realized_pnl <= -self.loss_threshold {
+                Some(TripReason::DailyLoss)
+            } else if state.consecutive_failures >= self.max_consecutive_failures {
+                Some(TripReason::ConsecutiveFailures)
+            } else {
+                None
+            };
+
+            if let Some(reason) = reason {
+                alerts.push(self.trip_state(&mut state, reason, now));
+            }
+        }
+
+        self.persist(&state).await?;
+        Ok(alerts)
+    }
+
+    /// Ręcznie aktywuje blokadę handlu
+    pub async fn trip(&self, reason: String) -> Result<Option<SystemAlert>> {
+        let now = chrono::Utc::now().timestamp();
+        let mut state = self.state.write().await;
+        let mut daily_stats = self.daily_stats.write().await;
+
+        self.roll_over(&mut state, &mut daily_stats, now);
+        if state.tripped {
+            return Ok(None);
+        }
+
+        let alert = self.trip_state(&mut state, TripReason::Manual(reason), now);
+        self.persist(&state).await?;
+        Ok(Some(alert))
+    }
+
+    /// Ręczny reset blokady (liczniki dzienne pozostają bez zmian)
+    pub async fn reset(&self, reason: &str) -> Result<Option<SystemAlert>> {
+        let mut state = self.state.write().await;
+        if !state.tripped {
+            return Ok(None);
+        }
+
+        state.tripped = false;
+        state.reason = None;
+        state.tripped_at = None;
+        state.consecutive_failures = 0;
+        self.persist(&state).await?;
+
+        info!("Circuit breaker reset: {}", reason);
+        Ok(Some(SystemAlert::new(
+            AlertLevel::Medium,
+            "Circuit breaker reset".to_string(),
+            format!("Trading re-enabled manually: {}", reason),
+            COMPONENT.to_string(),
+        )))
+    }
+
+    /// Sprawdza czy handel jest dozwolony (uwzględnia zmianę dnia UTC)
+    pub async fn is_trading_allowed(&self) -> bool {
+        self.is_trading_allowed_at(chrono::Utc::now().timestamp())
+            .await
+            .0
+    }
+
+    /// Jak `is_trading_allowed`, z podanym czasem; zwraca też alert o
+    /// automatycznym zdjęciu blokady po zmianie dnia
+    pub async fn is_trading_allowed_at(&self, now: i64) -> (bool, Option<SystemAlert>) {
+        let mut state = self.state.write().await;
+        let mut daily_stats = self.daily_stats.write().await;
+
+        let alert = self.roll_over(&mut state, &mut daily_stats, now);
+        if alert.is_some() {
+            if let Err(e) = self.persist(&state).await {
+                error!("Failed to persist circuit breaker state: {:?}", e);
+            }
+        }
+
+        (!state.tripped, alert)
+    }
+
+    /// Dolicza rozliczone finansowanie do statystyk dnia
+    pub async fn record_funding(&self, payment: &FundingPayment) {
+        self.daily_stats.write().await.update_for_funding(payment);
+    }
+
+    /// Zwraca status circuit breakera
+    pub async fn status(&self) -> CircuitBreakerStatus {
+        let state = self.state.read().await;
+        let daily_stats = self.daily_stats.read().await;
+
+        CircuitBreakerStatus {
+            active: state.tripped,
+            reason: state.reason.as_ref().map(|r| r.to_string()),
+            tripped_at: state.tripped_at,
+            trading_day: state.trading_day.clone(),
+            daily_loss: (-state.daily_realized_pnl).max(0.0),
+            daily_loss_limit: self.loss_threshold,
+            consecutive_failures: state.consecutive_failures,
+            max_consecutive_failures: self.max_consecutive_failures,
+            daily_trades: state.daily_trades,
+            daily_failures: state.daily_failures,
+            daily_fees: daily_stats.total_fees,
+            daily_funding: daily_stats.total_funding,
+        }
+    }
+
+    /// Zwraca kopię stanu
+    pub async fn state(&self) -> CircuitBreakerState {
+        self.state.read().await.clone()
+    }
+
+    fn trip_state(
+        &self,
+        state: &mut CircuitBreakerState,
+        reason: TripReason,
+        now: i64,
+    ) -> SystemAlert {
+        error!("Circuit breaker tripped: {}", reason);
+
+        state.tripped = true;
+        state.tripped_at = Some(now);
+        state.reason = Some(reason.clone());
+
+        SystemAlert::new(
+            AlertLevel::Critical,
+            "Circuit breaker tripped".to_string(),
+            format!(
+                "Trading halted ({}) until UTC day rollover or manual reset",
+                reason
+            ),
+            COMPONENT.to_string(),
+        )
+        .with_metric("daily_realized_pnl".to_string(), state.daily_realized_pnl)
+        .with_metric(
+            "consecutive_failures".to_string(),
+            state.consecutive_failures as f64,
+        )
+    }
+
+    /// Zeruje liczniki dzienne po zmianie dnia UTC i zdejmuje blokadę
+    fn roll_over(
+        &self,
+        state: &mut CircuitBreakerState,
+        daily_stats: &mut TradingStats,
+        now: i64,
+    ) -> Option<SystemAlert> {
+        let today = utc_day(now);
+        if state.trading_day == today {
+            return None;
+        }
+
+        let was_tripped = state.tripped;
+        let mut next = CircuitBreakerState::new(now);
+        *daily_stats = TradingStats::default();
+
+        // Ręczna blokada (np. emergency stop) wymaga ręcznego resetu
+        if let Some(TripReason::Manual(_)) = state.reason {
+            next.tripped = true;
+            next.reason = state.reason.take();
+            next.tripped_at = state.tripped_at;
+            *state = next;
+            return None;
+        }
+
+        *state = next;
+
+        if was_tripped {
+            info!("Circuit breaker reset on UTC day rollover ({})", today);
+            Some(SystemAlert::new(
+                AlertLevel::Medium,
+                "Circuit breaker reset".to_string(),
+                format!("Trading re-enabled on UTC day rollover ({})", today),
+                COMPONENT.to_string(),
+            ))
+        } else {
+            None
+        }
+    }
+
+    async fn persist(&self, state: &CircuitBreakerState) -> Result<()> {
+        if let Some(store) = &self.store {
+            store.save(STATE_KEY, state).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Dzień UTC dla timestampu
+fn utc_day(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2025-08-08 12:00:00 UTC
+    const NOON: i64 = 1_754_654_400;
+
+    fn success() -> ExecutionResult {
+        ExecutionResult::success("tx".to_string(), 1.0, 10.0, 0.01)
+    }
+
+    fn failure() -> ExecutionResult {
+        ExecutionResult::error("rejected".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_trips_on_daily_loss() {
+        let breaker = CircuitBreaker::new(&RiskConfig::default());
+        breaker.is_trading_allowed_at(NOON).await;
+
+        let alerts = breaker
+            .record_trade_at(&success(), 5.0, -10.0, NOON)
+            .await
+            .unwrap();
+        assert!(alerts.is_empty());
+        assert!(breaker.is_trading_allowed_at(NOON).await.0);
+
+        let alerts = breaker
+            .record_trade_at(&success(), 5.0, -6.0, NOON + 60)
+            .await
+            .unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].level, AlertLevel::Critical);
+        assert!(!breaker.is_trading_allowed_at(NOON + 120).await.0);
+        assert_eq!(breaker.state().await.reason, Some(TripReason::DailyLoss));
+    }
+
+    #[tokio::test]
+    async fn test_trips_on_consecutive_failures_and_success_resets_count() {
+        let breaker = CircuitBreaker::new(&RiskConfig::default());
+        breaker.is_trading_allowed_at(NOON).await;
+
+        for _ in 0..4 {
+            breaker
+                .record_trade_at(&failure(), 1.0, 0.0, NOON)
+                .await
+                .unwrap();
+        }
+        breaker
+            .record_trade_at(&success(), 1.0, 1.0, NOON)
+            .await
+            .unwrap();
+        assert_eq!(breaker.state().await.consecutive_failures, 0);
+
+        for _ in 0..5 {
+            breaker
+                .record_trade_at(&failure(), 1.0, 0.0, NOON)
+                .await
+                .unwrap();
+        }
+        assert!(!breaker.is_trading_allowed_at(NOON).await.0);
+        assert_eq!(
+            breaker.state().await.reason,
+            Some(TripReason::ConsecutiveFailures)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resets_on_day_rollover_and_manually() {
+        let breaker = CircuitBreaker::new(&RiskConfig::default());
+        breaker.is_trading_allowed_at(NOON).await;
+        breaker
+            .record_trade_at(&success(), 1.0, -20.0, NOON)
+            .await
+            .unwrap();
+        assert!(!breaker.is_trading_allowed_at(NOON + 3600).await.0);
+
+        let (allowed, alert) = breaker.is_trading_allowed_at(NOON + 12 * 3600).await;
+        assert!(allowed);
+        assert!(alert.is_some());
+        assert_eq!(breaker.state().await.daily_realized_pnl, 0.0);
+
+        breaker.trip("test".to_string()).await.unwrap();
+        assert!(!breaker.is_trading_allowed().await);
+        let tomorrow = chrono::Utc::now().timestamp() + 24 * 3600;
+        assert!(!breaker.is_trading_allowed_at(tomorrow).await.0);
+        assert!(breaker.reset("operator").await.unwrap().is_some());
+        assert!(breaker.is_trading_allowed().await);
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::config::ConcentrationConfig;
+
+/// Śledzenie korelacji stóp zwrotu między tokenami
+///
+/// Ceny są próbkowane w przedziałach `sample_interval` sekund (ostatnia cena
+/// przedziału), a korelacja to współczynnik Pearsona logarytmicznych stóp
+/// zwrotu z ostatnich `window` przedziałów wspólnych dla obu tokenów.
+#[derive(Debug, Clone)]
+pub struct CorrelationTracker {
+    sample_interval: i64,
+    window: usize,
+    min_samples: usize,
+
+    /// Token -> (numer przedziału, ostatnia cena w przedziale)
+    series: HashMap<String, VecDeque<(i64, f64)>>,
+}
+
+impl CorrelationTracker {
+    /// Tworzy tracker z konfiguracji limitów koncentracji
+    pub fn new(config: &ConcentrationConfig) -> Self {
+        Self {
+            sample_interval: config.sample_interval.max(1) as i64,
+            window: config.window.max(2),
+            min_samples: config.min_samples.max(2),
+            series: HashMap::new(),
+        }
+    }
+
+    /// Zapisuje cenę tokena z chwili `timestamp`
+    pub fn record(&mut self, token: &str, price: f64, timestamp: i64) {
+        if !price.is_finite() || price <= 0.0 {
+            return;
+        }
+
+        let bucket = timestamp.div_euclid(self.sample_interval);
+        let series = self.series.entry(normalize(token)).or_default();
+        match series.back_mut() {
+            Some((last, last_price)) if *last == bucket => *last_price = price,
+            // Notowania spóźnione względem ostatniego przedziału są pomijane
+            Some((last, _)) if *last > bucket => {}
+            _ => series.push_back((bucket, price)),
+        }
+
+        while series.len() > self.window + 1 {
+            series.pop_front();
+        }
+    }
+
+    /// Liczba zapisanych przedziałów tokena
+    pub fn samples(&self, token: &str) -> usize {
+        self.series.get(&normalize(token)).map_or(0, |s| s.len())
+    }
+
+    /// Korelacja stóp zwrotu dwóch tokenów (`None` przy zbyt krótkiej historii)
+    pub fn correlation(&self, a: &str, b: &str) -> Option<f64> {
+        let (a, b) = (normalize(a), normalize(b));
+        if a == b {
+            return Some(1.0);
+        }
+
+        let returns_a = self.returns(&a);
+        let returns_b = self.returns(&b);
+        let pairs: Vec<(f64, f64)> = returns_a
+            .iter()
+            .filter_map(|(bucket, ra)| returns_b.get(bucket).map(|rb| (*ra, *rb)))
+            .collect();
+        if pairs.len() < self.min_samples {
+            return None;
+        }
+
+        let n = pairs.len() as f64;
+        let mean_a = pairs.iter().map(|(ra, _)| ra).sum::<f64>() / n;
+        let mean_b = pairs.iter().map(|(_, rb)| rb).sum::<f64>() / n;
+
+        let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+        for (ra, rb) in &pairs {
+            covariance += (ra - mean_a) * (rb - mean_b);
+            variance_a += (ra - mean_a).powi(2);
+            variance_b += (rb - mean_b).powi(2);
+        }
+
+        if variance_a <= f64::EPSILON || variance_b <= f64::EPSILON {
+            return None;
+        }
+        Some((covariance / (variance_a * variance_b).sqrt()).clamp(-1.0, 1.0))
+    }
+
+    /// Średni bezwzględny ruch ceny z ostatnich `periods` przedziałów jako
+    /// ułamek ostatniej ceny (ATR z cen zamknięcia przedziałów)
+    pub fn atr_percent(&self, token: &str, periods: usize) -> Option<f64> {
+        let series = self.series.get(&normalize(token))?;
+        let (_, last_price) = series.back()?;
+        if periods == 0 || series.len() <= periods {
+            return None;
+        }
+
+        let recent = series.iter().skip(series.len() - periods - 1);
+        let true_range: f64 = recent
+            .clone()
+            .zip(recent.skip(1))
+            .map(|((_, previous), (_, price))| (price - previous).abs())
+            .sum();
+        Some(true_range / periods as f64 / last_price)
+    }
+
+    /// Logarytmiczne stopy zwrotu między kolejnymi przedziałami
+    fn returns(&self, token: &str) -> HashMap<i64, f64> {
+        let Some(series) = self.series.get(token) else {
+            return HashMap::new();
+        };
+
+        series
+            .iter()
+            .zip(series.iter().skip(1))
+            .filter(|((previous, _), (bucket, _))| bucket - previous == 1)
+            .map(|((_, previous_price), (bucket, price))| (*bucket, (price / previous_price).ln()))
+            .collect()
+    }
+}
+
+fn normalize(token: &str) -> String {
+    token.trim().to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> CorrelationTracker {
+        CorrelationTracker::new(&ConcentrationConfig {
+            sample_interval: 60,
+            window: 50,
+            min_samples: 10,
+            ..ConcentrationConfig::default()
+        })
+    }
+
+    /// Deterministyczna "losowa" ścieżka zmian ceny
+    fn moves(seed: u64, len: usize) -> Vec<f64> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                ((state >> 33) as f64 / (1u64 << 31) as f64 - 0.5) * 0.02
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_correlation_of_related_and_unrelated_tokens() {
+        let mut tracker = tracker();
+        let base = moves(1, 40);
+        let other = moves(7, 40);
+
+        let (mut a, mut b, mut c, mut d) = (1.0, 2.0, 3.0, 4.0);
+        for (i, (m, o)) in base.iter().zip(&other).enumerate() {
+            let ts = i as i64 * 60;
+            a *= 1.0 + m;
+            b *= 1.0 + m * 1.5 + o * 0.1;
+            c *= 1.0 + o;
+            d *= 1.0 - m;
+            tracker.record("AAA", a, ts);
+            tracker.record("bbb", b, ts + 30);
+            tracker.record("CCC", c, ts);
+            tracker.record("DDD", d, ts);
+        }
+
+        assert!(tracker.correlation("AAA", "BBB").unwrap() > 0.9);
+        assert!(tracker.correlation("AAA", "CCC").unwrap().abs() < 0.5);
+        assert!(tracker.correlation("AAA", "DDD").unwrap() < -0.9);
+        assert_eq!(tracker.correlation("aaa", "AAA"), Some(1.0));
+        assert_eq!(tracker.correlation("AAA", "ZZZ"), None);
+    }
+
+    #[test]
+    fn test_samples_are_bucketed_and_windowed() {
+        let mut tracker = tracker();
+        tracker.record("AAA", 1.0, 0);
+        tracker.record("AAA", 1.1, 30);
+        tracker.record("AAA", 0.0, 45);
+        assert_eq!(tracker.samples("AAA"), 1);
+
+        // Spóźnione notowanie nie cofa historii
+        tracker.record("AAA", 1.2, 120);
+        tracker.record("AAA", 1.3, 60);
+        assert_eq!(tracker.samples("AAA"), 2);
+
+        for i in 3..100 {
+            tracker.record("AAA", 1.0 + i as f64 * 0.01, i * 60);
+        }
+        assert_eq!(tracker.samples("AAA"), 51);
+
+        // Ruch 0.01 na przedział przy ostatniej cenie 1.99
+        let atr = tracker.atr_percent("aaa", 14).unwrap();
+        assert!((atr - 0.01 / 1.99).abs() < 1e-9);
+        assert_eq!(tracker.atr_percent("AAA", 60), None);
+
+        // Za mało wspólnych przedziałów
+        for i in 95..100 {
+            tracker.record("BBB", 1.0 + (i % 3) as f64 * 0.01, i * 60);
+        }
+        assert_eq!(tracker.correlation("AAA", "BBB"), None);
+    }
+}
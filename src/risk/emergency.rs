@@ -0,0 +1,505 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+use super::{CircuitBreaker, RiskManager, TradeSide, TripReason};
+use crate::database::{StateStore, TradeLedger};
+use crate::trading::{OrderStatus, TradeExecutorTrait, TradeOrder};
+
+/// Klucz stanu emergency stop w `risk_state`
+const STATE_KEY: &str = "emergency_stop";
+
+/// Źródło aktywacji emergency stop
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum EmergencyTrigger {
+    /// Żądanie przez API
+    Api,
+
+    /// Sygnał awaryjny
+    Signal,
+
+    /// Zadziałanie circuit breakera
+    CircuitBreaker,
+
+    /// Zamknięcie aplikacji
+    Shutdown,
+}
+
+impl std::fmt::Display for EmergencyTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmergencyTrigger::Api => write!(f, "api"),
+            EmergencyTrigger::Signal => write!(f, "signal"),
+            EmergencyTrigger::CircuitBreaker => write!(f, "circuit_breaker"),
+            EmergencyTrigger::Shutdown => write!(f, "shutdown"),
+        }
+    }
+}
+
+/// Anulowane zlecenie
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelledOrder {
+    pub order_id: String,
+    pub token: String,
+    pub error: Option<String>,
+}
+
+/// Zamknięta pozycja
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClosedPosition {
+    pub position_id: String,
+    pub token: String,
+    pub side: TradeSide,
+    pub size: f64,
+    pub entry_price: f64,
+    pub exit_price: Option<f64>,
+    pub realized_pnl: Option<f64>,
+    pub error: Option<String>,
+}
+
+/// Raport z zamknięcia pozycji i anulowania zleceń
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyReport {
+    pub trigger: EmergencyTrigger,
+    pub reason: String,
+    pub started_at: i64,
+    pub completed_at: i64,
+    pub orders_cancelled: Vec<CancelledOrder>,
+    pub positions_closed: Vec<ClosedPosition>,
+}
+
+impl EmergencyReport {
+    /// Liczba skutecznie anulowanych zleceń
+    pub fn cancelled_count(&self) -> u32 {
+        self.orders_cancelled
+            .iter()
+            .filter(|o| o.error.is_none())
+            .count() as u32
+    }
+
+    /// Liczba skutecznie zamkniętych pozycji
+    pub fn closed_count(&self) -> u32 {
+        self.positions_closed
+            .iter()
+            .filter(|p| p.error.is_none())
+            .count() as u32
+    }
+
+    /// Czy wszystkie operacje się powiodły
+    pub fn is_complete(&self) -> bool {
+        self.cancelled_count() as usize == self.orders_cancelled.len()
+            && self.closed_count() as usize == self.positions_closed.len()
+    }
+}
+
+/// Trwały stan emergency stop
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmergencyState {
+    pub active: bool,
+    pub trigger: Option<EmergencyTrigger>,
+    pub reason: Option<String>,
+    pub activated_at: Option<i64>,
+}
+
+/// Wpis audytu emergency stop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub event: String,
+    pub trigger_source: String,
+    pub reason: String,
+    pub details: serde_json::Value,
+    pub created_at: i64,
+}
+
+/// Emergency stop: anuluje zlecenia, zamyka pozycje po cenie rynkowej
+/// i blokuje nowe sygnały do czasu potwierdzonego resetu
+pub struct EmergencyStop {
+    executor: Arc<dyn TradeExecutorTrait>,
+    ledger: Arc<TradeLedger>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    db: Arc<SqlitePool>,
+    store: StateStore,
+    state: Arc<RwLock<EmergencyState>>,
+
+    /// Manager ryzyka, którego portfel księguje zrealizowany P&L zamknięć
+    risk_manager: Option<Arc<RiskManager>>,
+
+    /// Czy zadziałanie circuit breakera uruchamia emergency stop
+    on_circuit_breaker: bool,
+}
+
+impl EmergencyStop {
+    /// Tworzy emergency stop i odtwarza zapisany stan
+    pub async fn new(
+        executor: Arc<dyn TradeExecutorTrait>,
+        ledger: Arc<TradeLedger>,
+        circuit_breaker: Arc<CircuitBreaker>,
+        db: Arc<SqlitePool>,
+        on_circuit_breaker: bool,
+    ) -> Result<Self> {
+        let store = StateStore::new(db.clone());
+        let state = store
+            .load::<EmergencyState>(STATE_KEY)
+            .await?
+            .unwrap_or_default();
+
+        if state.active {
+            warn!(
+                "Emergency stop restored in active state ({})",
+                state.reason.clone().unwrap_or_default()
+            );
+        }
+
+        Ok(Self {
+            executor,
+            ledger,
+            circuit_breaker,
+            db,
+            store,
+            state: Arc::new(RwLock::new(state)),
+            risk_manager: None,
+            on_circuit_breaker,
+        })
+    }
+
+    /// Dołącza manager ryzyka (zamknięcia zmieniają saldo jego portfela)
+    pub fn with_risk_manager(mut self, risk_manager: Arc<RiskManager>) -> Self {
+        self.risk_manager = Some(risk_manager);
+        self
+    }
+
+    /// Czy emergency stop jest aktywny
+    pub async fn is_active(&self) -> bool {
+        self.state.read().await.active
+    }
+
+    /// Zwraca kopię stanu
+    pub async fn state(&self) -> EmergencyState {
+        self.state.read().await.clone()
+    }
+
+    /// Zwraca błąd, jeśli nowe sygnały są blokowane
+    pub async fn ensure_accepting_signals(&self) -> Result<()> {
+        let state = self.state.read().await;
+        if state.active {
+            anyhow::bail!(
+                "Emergency stop active since {} ({}); signals are refused until reset",
+                state.activated_at.unwrap_or_default(),
+                state.reason.clone().unwrap_or_default()
+            );
+        }
+        Ok(())
+    }
+
+    /// Aktywuje emergency stop: blokuje sygnały, zatrzymuje handel,
+    /// anuluje zlecenia i zamyka wszystkie pozycje
+    pub async fn activate(
+        &self,
+        trigger: EmergencyTrigger,
+        reason: &str,
+    ) -> Result<EmergencyReport> {
+        error!("EMERGENCY STOP activated via {}: {}", trigger, reason);
+
+        {
+            let mut state = self.state.write().await;
+            if !state.active {
+                *state = EmergencyState {
+                    active: true,
+                    trigger: Some(trigger),
+                    reason: Some(reason.to_string()),
+                    activated_at: Some(chrono::Utc::now().timestamp()),
+                };
+                self.store.save(STATE_KEY, &*state).await?;
+            }
+        }
+
+        self.audit("activated", &trigger, reason, serde_json::Value::Null)
+            .await?;
+
+        if let Err(e) = self
+            .circuit_breaker
+            .trip(format!("emergency stop ({})", trigger))
+            .await
+        {
+            error!("Failed to trip circuit breaker: {:?}", e);
+        }
+
+        self.flatten(trigger, reason).await
+    }
+
+    /// Anuluje wszystkie otwarte zlecenia i zamyka wszystkie pozycje rynkowo
+    ///
+    /// Nie zmienia stanu blokady - używane też przy zamknięciu aplikacji.
+    pub async fn flatten(
+        &self,
+        trigger: EmergencyTrigger,
+        reason: &str,
+    ) -> Result<EmergencyReport> {
+        let started_at = chrono::Utc::now().timestamp();
+
+        let mut orders_cancelled = Vec::new();
+        for record in self.ledger.open_orders().await? {
+            let order = record.order;
+            // Zlecenia ochronne managera wyjść nie trafiły jeszcze do executora
+            let managed = order.metadata.get("managed").and_then(|v| v.as_bool()) == Some(true);
+            let cancelled = if managed {
+                Ok(())
+            } else {
+                self.executor.cancel_order(&order.id).await
+            };
+            let error = match cancelled {
+                Ok(()) => {
+                    self.ledger
+                        .update_order_status(
+                            &order.id,
+                            &OrderStatus::Cancelled,
+                            Some(&format!("emergency stop: {}", reason)),
+                        )
+                        .await?;
+                    None
+                }
+                Err(e) => {
+                    warn!("Failed to cancel order {}: {}", order.id, e);
+                    Some(e.to_string())
+                }
+            };
+
+            orders_cancelled.push(CancelledOrder {
+                order_id: order.id,
+                token: order.token,
+                error,
+            });
+        }
+
+        let mut positions_closed = Vec::new();
+        for position in self.ledger.load_open_positions().await? {
+            positions_closed.push(self.close_at_market(position, reason).await?);
+        }
+
+        let report = EmergencyReport {
+            trigger,
+            reason: reason.to_string(),
+            started_at,
+            completed_at: chrono::Utc::now().timestamp(),
+            orders_cancelled,
+            positions_closed,
+        };
+
+        info!(
+            "Flattened: {} orders cancelled, {} positions closed",
+            report.cancelled_count(),
+            report.closed_count()
+        );
+
+        self.audit(
+            "flattened",
+            &trigger,
+            reason,
+            serde_json::to_value(&report)?,
+        )
+        .await?;
+
+        Ok(report)
+    }
+
+    /// Zdejmuje blokadę po potwierdzeniu operatora
+    ///
+    /// Zwraca `false`, jeśli emergency stop nie był aktywny.
+    pub async fn reset(&self, confirmed: bool, reason: &str) -> Result<bool> {
+        if !confirmed {
+            anyhow::bail!("Emergency stop reset requires explicit confirmation");
+        }
+
+        let previous = {
+            let mut state = self.state.write().await;
+            if !state.active {
+                return Ok(false);
+            }
+
+            let previous = state.clone();
+            *state = EmergencyState::default();
+            self.store.save(STATE_KEY, &*state).await?;
+            previous
+        };
+
+        self.circuit_breaker.reset(reason).await?;
+
+        let trigger = previous.trigger.unwrap_or(EmergencyTrigger::Api);
+        self.audit("reset", &trigger, reason, serde_json::to_value(&previous)?)
+            .await?;
+
+        warn!("Emergency stop reset: {}", reason);
+        Ok(true)
+    }
+
+    /// Aktywuje emergency stop, jeśli circuit breaker zadziałał
+    /// automatycznie (i jest to włączone w konfiguracji)
+    pub async fn check_circuit_breaker(&self) -> Result<Option<EmergencyReport>> {
+        if !self.on_circuit_breaker || self.is_active().await {
+            return Ok(None);
+        }
+
+        let breaker = self.circuit_breaker.state().await;
+        match breaker.reason {
+            Some(reason) if breaker.tripped && !matches!(reason, TripReason::Manual(_)) => {
+                let reason = format!("circuit breaker tripped: {}", reason);
+                self.activate(EmergencyTrigger::CircuitBreaker, &reason)
+                    .await
+                    .map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Zwraca ostatnie wpisy audytu (najnowsze pierwsze)
+    pub async fn audit_trail(&self, limit: u32) -> Result<Vec<AuditEntry>> {
+        let rows = sqlx::query("SELECT * FROM emergency_audit ORDER BY id DESC LIMIT ?")
+            .bind(limit as i64)
+            .fetch_all(&*self.db)
+            .await
+            .context("Failed to load emergency audit trail")?;
+
+        rows.iter()
+            .map(|row| {
+                let details = row
+                    .get::<Option<String>, _>("details")
+                    .map(|d| serde_json::from_str(&d))
+                    .transpose()
+                    .context("Invalid audit details JSON in DB")?
+                    .unwrap_or(serde_json::Value::Null);
+
+                Ok(AuditEntry {
+                    id: row.get("id"),
+                    event: row.get("event"),
+                    trigger_source: row.get("trigger_source"),
+                    reason: row.get("reason"),
+                    details,
+                    created_at: row.get("created_at"),
+                })
+            })
+            .collect()
+    }
+
+    /// Zamyka pozycję zleceniem rynkowym (fallback: `close_position` executora)
+    async fn close_at_market(
+        &self,
+        mut position: super::Position,
+        reason: &str,
+    ) -> Result<ClosedPosition> {
+        let mut order = TradeOrder::market_order(
+            position.token.clone(),
+            position.side.clone(),
+            position.size,
+            position.leverage,
+        );
+        order.metadata = serde_json::json!({
+            "position_id": position.id,
+            "reason": "emergency_stop",
+        });
+
+        self.ledger
+            .record_order(&order, &OrderStatus::Pending)
+            .await?;
+
+        let mut fees = 0.0;
+        let exit_price = match self.executor.execute_trade(&order).await {
+            Ok(result) => {
+                self.ledger.record_execution(&order, &result).await?;
+                fees = result.fees.unwrap_or(0.0);
+                match result.executed_price {
+                    Some(price) if result.success => Ok(price),
+                    _ => self.close_via_executor(&position).await,
+                }
+            }
+            Err(e) => {
+                warn!("Market close order for {} failed: {}", position.id, e);
+                self.ledger
+                    .update_order_status(&order.id, &OrderStatus::Rejected, Some(&e.to_string()))
+                    .await?;
+                self.close_via_executor(&position).await
+            }
+        };
+
+        let mut closed = ClosedPosition {
+            position_id: position.id.clone(),
+            token: position.token.clone(),
+            side: position.side.clone(),
+            size: position.size,
+            entry_price: position.entry_price,
+            exit_price: None,
+            realized_pnl: None,
+            error: None,
+        };
+
+        match exit_price {
+            Ok(exit_price) => {
+                let realized_pnl = position.realize(position.size, exit_price, fees);
+                position.close();
+
+                self.ledger
+                    .record_position_close(&position, exit_price, realized_pnl)
+                    .await?;
+                self.book_close(&position.id, realized_pnl).await;
+
+                closed.exit_price = Some(exit_price);
+                closed.realized_pnl = Some(realized_pnl);
+            }
+            Err(e) => {
+                error!("Failed to close position {}: {}", position.id, e);
+                closed.error = Some(e.to_string());
+            }
+        }
+
+        Ok(closed)
+    }
+
+    /// Księguje zrealizowany P&L w portfelu managera ryzyka (jak zamknięcia silnika)
+    async fn book_close(&self, position_id: &str, realized_pnl: f64) {
+        let Some(risk_manager) = &self.risk_manager else {
+            return;
+        };
+
+        let mut portfolio = risk_manager.portfolio().await;
+        portfolio.remove_position(position_id);
+        portfolio.balance += realized_pnl;
+        portfolio.daily_pnl += realized_pnl;
+        portfolio.update();
+        risk_manager.update_portfolio(&portfolio).await;
+        risk_manager.record_trade_outcome(realized_pnl).await;
+    }
+
+    async fn close_via_executor(&self, position: &super::Position) -> Result<f64> {
+        let price = self.executor.get_current_price(&position.token).await?;
+        self.executor.close_position(&position.id).await?;
+        Ok(price)
+    }
+
+    async fn audit(
+        &self,
+        event: &str,
+        trigger: &EmergencyTrigger,
+        reason: &str,
+        details: serde_json::Value,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO emergency_audit (event, trigger_source, reason, details, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(event)
+        .bind(trigger.to_string())
+        .bind(reason)
+        .bind(serde_json::to_string(&details)?)
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&*self.db)
+        .await
+        .context("Failed to save emergency audit entry")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,662 @@
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{Position, TradeSide};
+use crate::config::RiskConfig;
+use crate::trading::TradeOrder;
+
+/// Tolerancja przy porównywaniu pozostałego rozmiaru pozycji
+const SIZE_EPSILON: f64 = 1e-9;
+
+/// Rola zlecenia ochronnego
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtectiveRole {
+    /// Stop-loss (stały, trailing lub na break-even)
+    StopLoss,
+
+    /// Częściowe zamknięcie na pierwszym poziomie take-profit
+    PartialTakeProfit,
+
+    /// Zamknięcie reszty pozycji na docelowym take-profit
+    TakeProfit,
+}
+
+/// Zlecenie ochronne przypięte do pozycji
+///
+/// Zlecenia są zarządzane programowo: trafiają do rejestru jako `Pending`
+/// (z `metadata.managed = true`), a do executora dopiero po aktywacji.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectiveOrder {
+    pub role: ProtectiveRole,
+    pub order: TradeOrder,
+}
+
+impl ProtectiveOrder {
+    fn new(role: ProtectiveRole, position_id: &str, mut order: TradeOrder) -> Self {
+        order.metadata = serde_json::json!({
+            "position_id": position_id,
+            "exit_role": role,
+            "managed": true,
+        });
+        Self { role, order }
+    }
+
+    /// Odczytuje zlecenie ochronne zapisane w rejestrze (`metadata.managed`)
+    pub fn from_order(order: TradeOrder) -> Option<Self> {
+        let metadata = &order.metadata;
+        if metadata.get("managed").and_then(|v| v.as_bool()) != Some(true) {
+            return None;
+        }
+        let role = serde_json::from_value(metadata.get("exit_role")?.clone()).ok()?;
+        Some(Self { role, order })
+    }
+
+    /// ID pozycji chronionej przez zlecenie
+    pub fn position_id(&self) -> Option<&str> {
+        self.order
+            .metadata
+            .get("position_id")
+            .and_then(|v| v.as_str())
+    }
+
+    /// Cena aktywacji zlecenia
+    pub fn trigger_price(&self) -> f64 {
+        self.order.stop_price.unwrap_or(0.0)
+    }
+
+    /// Czy cena aktywuje zlecenie
+    pub fn is_triggered(&self, price: f64) -> bool {
+        let trigger = self.trigger_price();
+        let protects_downside = self.role == ProtectiveRole::StopLoss;
+
+        match (&self.order.side, protects_downside) {
+            (TradeSide::Long, true) | (TradeSide::Short, false) => price <= trigger,
+            (TradeSide::Long, false) | (TradeSide::Short, true) => price >= trigger,
+        }
+    }
+}
+
+/// Stan wyjścia z jednej pozycji
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitPlan {
+    pub position_id: String,
+    pub token: String,
+    pub side: TradeSide,
+    pub leverage: u8,
+    pub entry_price: f64,
+
+    /// Rozmiar pozostały po częściowych zamknięciach
+    pub remaining_size: f64,
+
+    /// Najlepsza cena od otwarcia (punkt odniesienia trailing stop)
+    pub best_price: f64,
+
+    /// Zysk zrealizowany przez częściowe zamknięcia
+    pub realized_pnl: f64,
+
+    /// Czy stop przesunięto na break-even
+    pub break_even: bool,
+
+    pub stop: Option<ProtectiveOrder>,
+    pub partial: Option<ProtectiveOrder>,
+    pub take_profit: Option<ProtectiveOrder>,
+}
+
+impl ExitPlan {
+    /// Aktywne zlecenia ochronne
+    pub fn orders(&self) -> impl Iterator<Item = &ProtectiveOrder> {
+        self.stop
+            .iter()
+            .chain(self.partial.iter())
+            .chain(self.take_profit.iter())
+    }
+
+    /// Cena odpowiadająca zwrotowi z marży (ruch ceny × dźwignia) względem `reference`
+    fn price_at(&self, reference: f64, leveraged_return: f64) -> f64 {
+        let price_move = leveraged_return / self.leverage.max(1) as f64;
+        match self.side {
+            TradeSide::Long => reference * (1.0 + price_move),
+            TradeSide::Short => reference * (1.0 - price_move),
+        }
+    }
+
+    /// Czy `price` jest korzystniejsza dla pozycji niż `other`
+    fn is_better(&self, price: f64, other: f64) -> bool {
+        match self.side {
+            TradeSide::Long => price > other,
+            TradeSide::Short => price < other,
+        }
+    }
+
+    fn order(&self, role: ProtectiveRole, size: f64, trigger_price: f64) -> ProtectiveOrder {
+        let order = match role {
+            ProtectiveRole::StopLoss => TradeOrder::stop_loss_order(
+                self.token.clone(),
+                self.side.clone(),
+                size,
+                self.leverage,
+                trigger_price,
+            ),
+            ProtectiveRole::PartialTakeProfit | ProtectiveRole::TakeProfit => {
+                TradeOrder::take_profit_order(
+                    self.token.clone(),
+                    self.side.clone(),
+                    size,
+                    self.leverage,
+                    trigger_price,
+                )
+            }
+        };
+        ProtectiveOrder::new(role, &self.position_id, order)
+    }
+
+    /// Ustawia stop na nowej cenie (zastępuje istniejący lub tworzy nowy)
+    fn set_stop(&mut self, trigger_price: f64) -> ExitAction {
+        let stop = self.order(ProtectiveRole::StopLoss, self.remaining_size, trigger_price);
+        match self.stop.replace(stop.clone()) {
+            Some(previous) => ExitAction::Replace {
+                cancelled: previous.order.id,
+                order: stop,
+            },
+            None => ExitAction::Place(stop),
+        }
+    }
+}
+
+/// Zmiana zleceń ochronnych do odzwierciedlenia w rejestrze i u executora
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ExitAction {
+    /// Nowe zlecenie ochronne
+    Place(ProtectiveOrder),
+
+    /// Zlecenie zastąpione nowym (przesunięty stop, zmieniony rozmiar)
+    Replace {
+        cancelled: String,
+        order: ProtectiveOrder,
+    },
+
+    /// Cena aktywowała zlecenie - należy je wykonać
+    Trigger(ProtectiveOrder),
+
+    /// Zlecenie traci ważność
+    Cancel(String),
+}
+
+/// Manager wyjść z pozycji: stop-loss, trailing stop i częściowy take-profit
+///
+/// Poziomy z `RiskConfig` to zwrot z marży, więc odległość ceny od wejścia
+/// to procent podzielony przez dźwignię. Manager nie wykonuje zleceń -
+/// zwraca akcje, które wykonuje silnik tradingu.
+pub struct ExitManager {
+    config: RiskConfig,
+    plans: HashMap<String, ExitPlan>,
+}
+
+impl ExitManager {
+    /// Tworzy manager dla konfiguracji ryzyka
+    pub fn new(config: RiskConfig) -> Self {
+        Self {
+            config,
+            plans: HashMap::new(),
+        }
+    }
+
+    /// Zmienia konfigurację dla kolejnych planów (np. po zmianie fazy strategii)
+    ///
+    /// Istniejące plany zachowują swoje poziomy.
+    pub fn set_config(&mut self, config: RiskConfig) {
+        self.config = config;
+    }
+
+    /// Plan wyjścia pozycji
+    pub fn plan(&self, position_id: &str) -> Option<&ExitPlan> {
+        self.plans.get(position_id)
+    }
+
+    /// Czy pozycja ma plan wyjścia
+    pub fn is_managed(&self, position_id: &str) -> bool {
+        self.plans.contains_key(position_id)
+    }
+
+    /// Przypina zlecenia ochronne do otwartej pozycji
+    pub fn attach(&mut self, position: &Position) -> Vec<ExitAction> {
+        let mut plan = ExitPlan {
+            position_id: position.id.clone(),
+            token: position.token.clone(),
+            side: position.side.clone(),
+            leverage: position.leverage,
+            entry_price: position.entry_price,
+            remaining_size: position.size,
+            best_price: position.entry_price,
+            realized_pnl: 0.0,
+            break_even: false,
+            stop: None,
+            partial: None,
+            take_profit: None,
+        };
+
+        let stop_loss = &self.config.stop_loss;
+        if stop_loss.enabled {
+            let stop_price = plan.price_at(plan.entry_price, -percent(stop_loss.default_percent));
+            plan.stop = Some(plan.order(ProtectiveRole::StopLoss, plan.remaining_size, stop_price));
+        }
+
+        let take_profit = &self.config.take_profit;
+        if take_profit.enabled {
+            let partial_fraction = percent(take_profit.partial_percent).clamp(0.0, 1.0);
+            if take_profit.use_partial && partial_fraction > 0.0 {
+                let level =
+                    plan.price_at(plan.entry_price, percent(take_profit.first_partial_level));
+                plan.partial = Some(plan.order(
+                    ProtectiveRole::PartialTakeProfit,
+                    plan.remaining_size * partial_fraction,
+                    level,
+                ));
+            }
+
+            let level = plan.price_at(plan.entry_price, percent(take_profit.default_percent));
+            plan.take_profit =
+                Some(plan.order(ProtectiveRole::TakeProfit, plan.remaining_size, level));
+        }
+
+        let actions = plan.orders().cloned().map(ExitAction::Place).collect();
+        self.plans.insert(plan.position_id.clone(), plan);
+        actions
+    }
+
+    /// Odtwarza plan pozycji z zapisanych zleceń ochronnych (np. po restarcie)
+    ///
+    /// Stop nie gorszy od ceny wejścia oznacza przejście na break-even.
+    /// Zysk z częściowych zamknięć sprzed restartu nie jest odtwarzany.
+    /// Zwraca `false`, gdy pozycja nie ma żadnego zlecenia ochronnego.
+    pub fn restore(&mut self, position: &Position, orders: Vec<ProtectiveOrder>) -> bool {
+        let mut plan = ExitPlan {
+            position_id: position.id.clone(),
+            token: position.token.clone(),
+            side: position.side.clone(),
+            leverage: position.leverage,
+            entry_price: position.entry_price,
+            remaining_size: position.size,
+            best_price: position.entry_price,
+            realized_pnl: 0.0,
+            break_even: false,
+            stop: None,
+            partial: None,
+            take_profit: None,
+        };
+        if plan.is_better(position.current_price, plan.best_price) {
+            plan.best_price = position.current_price;
+        }
+
+        for order in orders {
+            match order.role {
+                ProtectiveRole::StopLoss => plan.stop = Some(order),
+                ProtectiveRole::PartialTakeProfit => plan.partial = Some(order),
+                ProtectiveRole::TakeProfit => plan.take_profit = Some(order),
+            }
+        }
+        if plan.orders().next().is_none() {
+            return false;
+        }
+
+        plan.break_even = plan
+            .stop
+            .as_ref()
+            .is_some_and(|stop| !plan.is_better(plan.entry_price, stop.trigger_price()));
+        self.plans.insert(plan.position_id.clone(), plan);
+        true
+    }
+
+    /// Wystawia ponownie (z nowym ID) aktywowane zlecenie, którego nie wykonano
+    ///
+    /// Poprzednie zlecenie pozostaje w rejestrze jako odrzucone; zwraca
+    /// akcję `Place` z nowym zleceniem lub nic, jeśli plan go nie zawiera.
+    pub fn reissue(&mut self, position_id: &str, order_id: &str) -> Vec<ExitAction> {
+        let Some(plan) = self.plans.get_mut(position_id) else {
+            return Vec::new();
+        };
+        let Some(previous) = plan.orders().find(|o| o.order.id == order_id).cloned() else {
+            return Vec::new();
+        };
+
+        let order = plan.order(previous.role, previous.order.size, previous.trigger_price());
+        let slot = match previous.role {
+            ProtectiveRole::StopLoss => &mut plan.stop,
+            ProtectiveRole::PartialTakeProfit => &mut plan.partial,
+            ProtectiveRole::TakeProfit => &mut plan.take_profit,
+        };
+        *slot = Some(order.clone());
+        vec![ExitAction::Place(order)]
+    }
+
+    /// Reaguje na nową cenę: aktywuje zlecenia lub podciąga trailing stop
+    ///
+    /// Gdy cena przeskoczy oba poziomy take-profit, aktywowany jest tylko
+    /// docelowy (zamyka całość).
+    pub fn on_price(&mut self, position_id: &str, price: f64) -> Vec<ExitAction> {
+        let Some(plan) = self.plans.get_mut(position_id) else {
+            return Vec::new();
+        };
+
+        let triggered = [&plan.stop, &plan.take_profit, &plan.partial]
+            .into_iter()
+            .flatten()
+            .find(|order| order.is_triggered(price));
+        if let Some(order) = triggered {
+            return vec![ExitAction::Trigger(order.clone())];
+        }
+
+        if plan.is_better(price, plan.best_price) {
+            plan.best_price = price;
+        }
+
+        let stop_loss = &self.config.stop_loss;
+        if !(stop_loss.enabled && stop_loss.use_trailing) {
+            return Vec::new();
+        }
+
+        let trailing = plan.price_at(plan.best_price, -percent(stop_loss.trailing_distance));
+        match &plan.stop {
+            Some(stop) if plan.is_better(trailing, stop.trigger_price()) => {
+                vec![plan.set_stop(trailing)]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Odnotowuje wykonanie zlecenia zamykającego (ochronnego lub rynkowego)
+    ///
+    /// Po częściowym take-profit stop przechodzi na break-even, a pozostałe
+    /// zlecenia dostają nowy rozmiar. Po zamknięciu całości plan jest usuwany,
+    /// a pozostałe zlecenia anulowane.
+    pub fn on_fill(
+        &mut self,
+        position_id: &str,
+        order_id: &str,
+        filled_size: f64,
+        realized_pnl: f64,
+    ) -> Vec<ExitAction> {
+        let Some(plan) = self.plans.get_mut(position_id) else {
+            return Vec::new();
+        };
+
+        plan.remaining_size -= filled_size;
+        plan.realized_pnl += realized_pnl;
+
+        let closes_position = plan
+            .orders()
+            .any(|o| o.order.id == order_id && o.role != ProtectiveRole::PartialTakeProfit);
+        if closes_position || plan.remaining_size <= SIZE_EPSILON {
+            return self
+                .detach(position_id)
+                .into_iter()
+                .filter(|action| !matches!(action, ExitAction::Cancel(id) if id == order_id))
+                .collect();
+        }
+
+        let mut actions = Vec::new();
+        if plan
+            .partial
+            .as_ref()
+            .is_some_and(|partial| partial.order.id == order_id)
+        {
+            plan.partial = None;
+            plan.break_even = true;
+        }
+
+        // Po częściowym zamknięciu stop nie niżej niż break-even
+        let mut stop_price = plan.stop.as_ref().map(|stop| stop.trigger_price());
+        if plan.break_even {
+            stop_price = match stop_price {
+                Some(price) if plan.is_better(price, plan.entry_price) => Some(price),
+                _ => Some(plan.entry_price),
+            };
+        }
+        if let Some(price) = stop_price {
+            actions.push(plan.set_stop(price));
+        }
+
+        if let Some(take_profit) = &plan.take_profit {
+            let resized = plan.order(
+                ProtectiveRole::TakeProfit,
+                plan.remaining_size,
+                take_profit.trigger_price(),
+            );
+            let cancelled = take_profit.order.id.clone();
+            plan.take_profit = Some(resized.clone());
+            actions.push(ExitAction::Replace {
+                cancelled,
+                order: resized,
+            });
+        }
+
+        actions
+    }
+
+    /// Usuwa plan pozycji i anuluje jej zlecenia ochronne
+    pub fn detach(&mut self, position_id: &str) -> Vec<ExitAction> {
+        self.plans
+            .remove(position_id)
+            .map(|plan| {
+                plan.orders()
+                    .map(|o| ExitAction::Cancel(o.order.id.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Usuwa plany pozycji zamkniętych poza managerem (np. przez emergency stop)
+    pub fn retain_open(&mut self, open_position_ids: &[String]) {
+        self.plans
+            .retain(|position_id, _| open_position_ids.contains(position_id));
+    }
+}
+
+fn percent(value: rust_decimal::Decimal) -> f64 {
+    value.to_f64().unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    /// Long 10x @ 1.0: SL 10% -> 0.99, partial 15% -> 1.015, TP 20% -> 1.02
+    fn long_position() -> Position {
+        Position::new("BONK".to_string(), TradeSide::Long, 100.0, 10, 1.0)
+    }
+
+    /// Przepuszcza serię cen przez manager i zbiera aktywowane zlecenia
+    fn run(
+        manager: &mut ExitManager,
+        position_id: &str,
+        prices: &[f64],
+    ) -> Vec<(f64, ProtectiveOrder)> {
+        let mut triggered = Vec::new();
+        for &price in prices {
+            for action in manager.on_price(position_id, price) {
+                if let ExitAction::Trigger(order) = action {
+                    triggered.push((price, order));
+                }
+            }
+        }
+        triggered
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_attach_places_stop_and_take_profit_levels() {
+        let mut manager = ExitManager::new(RiskConfig::default());
+        let position = long_position();
+
+        let actions = manager.attach(&position);
+        assert_eq!(actions.len(), 3);
+
+        let plan = manager.plan(&position.id).unwrap();
+        let stop = plan.stop.as_ref().unwrap();
+        let partial = plan.partial.as_ref().unwrap();
+        let take_profit = plan.take_profit.as_ref().unwrap();
+
+        assert_close(stop.trigger_price(), 0.99);
+        assert_close(stop.order.size, 100.0);
+        assert_close(partial.trigger_price(), 1.015);
+        assert_close(partial.order.size, 50.0);
+        assert_close(take_profit.trigger_price(), 1.02);
+        assert_eq!(stop.order.metadata["position_id"], position.id.as_str());
+        assert_eq!(stop.order.metadata["managed"], true);
+
+        // Short: poziomy lustrzane
+        let short = Position::new("WIF".to_string(), TradeSide::Short, 100.0, 10, 1.0);
+        manager.attach(&short);
+        let plan = manager.plan(&short.id).unwrap();
+        assert_close(plan.stop.as_ref().unwrap().trigger_price(), 1.01);
+        assert_close(plan.take_profit.as_ref().unwrap().trigger_price(), 0.98);
+    }
+
+    #[test]
+    fn test_trailing_stop_ratchets_only_in_favour() {
+        let mut config = RiskConfig::default();
+        config.stop_loss.use_trailing = true;
+        config.stop_loss.trailing_distance = Decimal::new(5, 2);
+        config.take_profit.enabled = false;
+
+        let mut manager = ExitManager::new(config);
+        let position = long_position();
+        manager.attach(&position);
+
+        // Trailing 5% / 10x = 0.5% od najlepszej ceny
+        let triggered = run(&mut manager, &position.id, &[1.0, 1.01, 1.008, 1.02]);
+        assert!(triggered.is_empty());
+        let plan = manager.plan(&position.id).unwrap();
+        assert_close(plan.best_price, 1.02);
+        assert_close(plan.stop.as_ref().unwrap().trigger_price(), 1.02 * 0.995);
+
+        // Cofnięcie nie obniża stopu, przebicie go aktywuje
+        let triggered = run(&mut manager, &position.id, &[1.016, 1.0149]);
+        assert_eq!(triggered.len(), 1);
+        assert_close(triggered[0].0, 1.0149);
+        assert_eq!(triggered[0].1.role, ProtectiveRole::StopLoss);
+    }
+
+    #[test]
+    fn test_partial_take_profit_moves_stop_to_break_even() {
+        let mut manager = ExitManager::new(RiskConfig::default());
+        let position = long_position();
+        manager.attach(&position);
+
+        let triggered = run(&mut manager, &position.id, &[1.005, 1.012, 1.016]);
+        assert_eq!(triggered.len(), 1);
+        let partial = triggered[0].1.clone();
+        assert_eq!(partial.role, ProtectiveRole::PartialTakeProfit);
+
+        let actions = manager.on_fill(&position.id, &partial.order.id, partial.order.size, 0.8);
+        assert_eq!(actions.len(), 2);
+        assert!(actions
+            .iter()
+            .all(|a| matches!(a, ExitAction::Replace { .. })));
+
+        let plan = manager.plan(&position.id).unwrap();
+        assert!(plan.break_even);
+        assert!(plan.partial.is_none());
+        assert_close(plan.remaining_size, 50.0);
+        assert_close(plan.realized_pnl, 0.8);
+        assert_close(plan.stop.as_ref().unwrap().trigger_price(), 1.0);
+        assert_close(plan.stop.as_ref().unwrap().order.size, 50.0);
+        assert_close(plan.take_profit.as_ref().unwrap().order.size, 50.0);
+
+        // Powrót do ceny wejścia zamyka resztę na break-even
+        let take_profit_id = plan.take_profit.as_ref().unwrap().order.id.clone();
+        let triggered = run(&mut manager, &position.id, &[1.01, 1.0]);
+        assert_eq!(triggered.len(), 1);
+        let stop = triggered[0].1.clone();
+        assert_eq!(stop.role, ProtectiveRole::StopLoss);
+
+        let actions = manager.on_fill(&position.id, &stop.order.id, stop.order.size, 0.0);
+        assert_eq!(actions.len(), 1);
+        assert!(matches!(&actions[0], ExitAction::Cancel(id) if *id == take_profit_id));
+        assert!(!manager.is_managed(&position.id));
+    }
+
+    #[test]
+    fn test_unfilled_trigger_is_reissued_with_new_id() {
+        let mut manager = ExitManager::new(RiskConfig::default());
+        let position = long_position();
+        manager.attach(&position);
+
+        let stop = run(&mut manager, &position.id, &[0.98]).remove(0).1;
+        let actions = manager.reissue(&position.id, &stop.order.id);
+        let reissued = match actions.as_slice() {
+            [ExitAction::Place(order)] => order.clone(),
+            other => panic!("expected place, got {:?}", other),
+        };
+        assert_ne!(reissued.order.id, stop.order.id);
+        assert_eq!(reissued.role, ProtectiveRole::StopLoss);
+        assert_close(reissued.trigger_price(), stop.trigger_price());
+
+        // Kolejna aktywacja dotyczy nowego zlecenia
+        let triggered = run(&mut manager, &position.id, &[0.98]);
+        assert_eq!(triggered[0].1.order.id, reissued.order.id);
+        assert!(manager.reissue(&position.id, &stop.order.id).is_empty());
+    }
+
+    #[test]
+    fn test_restore_rebuilds_plan_from_saved_orders() {
+        let mut manager = ExitManager::new(RiskConfig::default());
+        let position = long_position();
+        let saved: Vec<ProtectiveOrder> = manager
+            .attach(&position)
+            .into_iter()
+            .filter_map(|action| match action {
+                ExitAction::Place(order) => Some(order),
+                _ => None,
+            })
+            .collect();
+
+        // Restart: nowy manager odczytuje zlecenia z rejestru
+        let mut restored = ExitManager::new(RiskConfig::default());
+        let orders = saved
+            .iter()
+            .map(|o| ProtectiveOrder::from_order(o.order.clone()).unwrap())
+            .collect();
+        assert!(restored.restore(&position, orders));
+
+        let plan = restored.plan(&position.id).unwrap();
+        assert!(!plan.break_even);
+        assert_close(plan.stop.as_ref().unwrap().trigger_price(), 0.99);
+        assert_close(plan.partial.as_ref().unwrap().trigger_price(), 1.015);
+        assert_eq!(
+            plan.take_profit.as_ref().unwrap().order.id,
+            saved[2].order.id
+        );
+
+        let triggered = run(&mut restored, &position.id, &[1.03]);
+        assert_eq!(triggered[0].1.role, ProtectiveRole::TakeProfit);
+
+        // Zlecenia bez `managed` nie należą do planu
+        let plain = TradeOrder::market_order("BONK".to_string(), TradeSide::Long, 1.0, 10);
+        assert!(ProtectiveOrder::from_order(plain).is_none());
+        assert!(!restored.restore(&long_position(), Vec::new()));
+    }
+
+    #[test]
+    fn test_gap_through_both_levels_takes_full_profit() {
+        let mut manager = ExitManager::new(RiskConfig::default());
+        let position = long_position();
+        manager.attach(&position);
+
+        let triggered = run(&mut manager, &position.id, &[1.03]);
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(triggered[0].1.role, ProtectiveRole::TakeProfit);
+        assert_close(triggered[0].1.order.size, 100.0);
+    }
+}
@@ -0,0 +1,1393 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use rust_decimal::prelude::ToPrimitive;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use super::{
+    CircuitBreaker, CorrelationTracker, MarginModel, PhaseChange, PhaseTracker, Portfolio,
+    Position, PositionSizer, RiskAssessment, RiskManagerTrait, SizingDecision, SizingInputs,
+    TokenBlockReason, TradeLimiter, TradeLimitsState, TradeSide,
+};
+use crate::config::{Config, PhaseConfig, RiskConfig, StrategyConfig, TradingConfig};
+use crate::signals::{Confidence, Signal};
+use crate::trading::{Clock, ExitReason, TradingEngine, TradingStats};
+
+/// Redukcja pozycji zaplanowana przed likwidacją konta (cross margin)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleverageStep {
+    pub position_id: String,
+    pub token: String,
+
+    /// Rozmiar do zamknięcia (cała pozycja lub jej część)
+    pub size: f64,
+
+    /// Niezrealizowany P&L pozycji w chwili planowania
+    pub pnl: f64,
+}
+
+/// Ekspozycja portfela, do której doliczyłaby się nowa pozycja
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConcentrationExposure {
+    /// Wartość nominalna pozycji na ten sam token w tym samym kierunku
+    pub token: f64,
+
+    /// Wartość nominalna pozycji w grupie skorelowanej (łącznie z tokenem)
+    pub cluster: f64,
+
+    /// Tokeny otwartych pozycji należące do grupy
+    pub cluster_tokens: Vec<String>,
+}
+
+/// Manager ryzyka oparty o `RiskConfig` i `TradingConfig`
+///
+/// Rozmiar pozycji (`size`) to marża pozycji, a wartość nominalna to
+/// `size * leverage` - tak samo jak w `Portfolio::update`.
+pub struct RiskManager {
+    risk: RiskConfig,
+    trading: TradingConfig,
+
+    /// Model marży z `RiskConfig::margin`
+    margin: MarginModel,
+
+    /// Ostatni znany stan portfela (dla metod bez jawnego portfela)
+    portfolio: Arc<RwLock<Portfolio>>,
+
+    /// Circuit breaker blokujący nowe pozycje
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+
+    /// Korelacje stóp zwrotu tokenów (z notowań strumienia cen i wycen pozycji)
+    correlation: RwLock<CorrelationTracker>,
+
+    /// Polityki rozmiaru pozycji według strategii (źródła sygnału)
+    sizer: PositionSizer,
+
+    /// Wyniki zamkniętych pozycji (dla polityki Kelly'ego)
+    trade_stats: RwLock<TradingStats>,
+
+    /// Aktywna faza strategii według equity portfela
+    phases: PhaseTracker,
+
+    /// Dzienne limity transakcji i blokady ponownego wejścia w token
+    trade_limits: Arc<TradeLimiter>,
+
+    /// Zegar (systemowy lub symulacji w backteście)
+    clock: Clock,
+}
+
+impl RiskManager {
+    /// Tworzy manager ryzyka z konfiguracji
+    pub fn new(risk: RiskConfig, trading: TradingConfig) -> Self {
+        let initial_balance = trading.initial_balance.to_f64().unwrap_or(0.0);
+        let margin = MarginModel::from_config(&risk.margin);
+        let portfolio = Portfolio::new(initial_balance).with_margin_model(margin.clone());
+        let correlation = CorrelationTracker::new(&risk.concentration);
+        let sizer = PositionSizer::from_config(&trading.sizing);
+        let phases = PhaseTracker::new(&StrategyConfig::default(), initial_balance);
+        let trade_limits = Arc::new(TradeLimiter::new(&risk.trade_limits));
+
+        Self {
+            risk,
+            trading,
+            margin,
+            portfolio: Arc::new(RwLock::new(portfolio)),
+            circuit_breaker: None,
+            correlation: RwLock::new(correlation),
+            sizer,
+            trade_stats: RwLock::new(TradingStats::default()),
+            phases,
+            trade_limits,
+            clock: Clock::System,
+        }
+    }
+
+    /// Dołącza circuit breaker sprawdzany przed otwarciem pozycji
+    pub fn with_circuit_breaker(mut self, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Ustawia limiter transakcji (np. z trwałym stanem)
+    pub fn with_trade_limiter(mut self, trade_limits: Arc<TradeLimiter>) -> Self {
+        self.trade_limits = trade_limits;
+        self
+    }
+
+    /// Ustawia zegar używany przy limitach czasowych
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Włącza profile faz strategii; faza początkowa wynika z `initial_balance`
+    pub fn with_strategy(mut self, config: &StrategyConfig) -> Self {
+        let equity = to_f64(self.trading.initial_balance);
+        self.phases = PhaseTracker::new(config, equity);
+        self
+    }
+
+    /// Tworzy manager ryzyka z pełnej konfiguracji aplikacji
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.risk.clone(), config.trading.clone()).with_strategy(&config.strategy)
+    }
+
+    /// Aktualizuje stan portfela używany przy ocenie ryzyka
+    ///
+    /// Portfel zawsze używa modelu marży managera.
+    pub async fn update_portfolio(&self, portfolio: &Portfolio) {
+        let mut portfolio = portfolio.clone();
+        portfolio.margin_model = self.margin.clone();
+        *self.portfolio.write().await = portfolio;
+    }
+
+    /// Zwraca kopię ostatniego znanego stanu portfela
+    pub async fn portfolio(&self) -> Portfolio {
+        self.portfolio.read().await.clone()
+    }
+
+    /// Zwraca konfigurację ryzyka
+    pub fn risk_config(&self) -> &RiskConfig {
+        &self.risk
+    }
+
+    /// Zwraca konfigurację tradingu
+    pub fn trading_config(&self) -> &TradingConfig {
+        &self.trading
+    }
+
+    /// Zwraca model marży
+    pub fn margin_model(&self) -> &MarginModel {
+        &self.margin
+    }
+
+    /// Aktywna faza strategii (`None` przy wyłączonych profilach)
+    pub fn active_phase(&self) -> Option<&PhaseConfig> {
+        self.phases.current()
+    }
+
+    /// Aktualizuje fazę strategii dla equity portfela; zwraca zmianę fazy
+    pub fn update_phase(&self, equity: f64) -> Option<PhaseChange> {
+        let change = self.phases.update(equity)?;
+        info!(
+            "Strategy phase changed from {} to {} at equity {:.2} USD",
+            change.from, change.to, change.equity
+        );
+        Some(change)
+    }
+
+    /// Konfiguracja ryzyka z poziomami stop-loss i take-profit aktywnej fazy
+    pub fn active_risk_config(&self) -> RiskConfig {
+        match self.active_phase() {
+            Some(phase) => phase.risk_config(&self.risk),
+            None => self.risk.clone(),
+        }
+    }
+
+    /// Dźwignia dla sygnału ograniczona do zakresu min/max z konfiguracji
+    ///
+    /// Aktywna faza mnoży dźwignię dla poziomu pewności i obniża maksimum.
+    pub fn leverage_for_signal(&self, signal: &Signal) -> u8 {
+        let leverage = self
+            .trading
+            .get_leverage_for_confidence(&signal.confidence.to_string());
+
+        let (leverage, max_leverage) = match self.active_phase() {
+            Some(phase) => (
+                (leverage as f64 * to_f64(phase.leverage_multiplier)).round() as u8,
+                phase.max_leverage.min(self.trading.max_leverage),
+            ),
+            None => (leverage, self.trading.max_leverage),
+        };
+
+        leverage.min(max_leverage).max(self.trading.min_leverage)
+    }
+
+    /// Względna odległość ceny likwidacji nowej pozycji od ceny wejścia
+    ///
+    /// W trybie cross nową pozycję zabezpiecza equity portfela pomniejszone
+    /// o marżę utrzymaniową otwartych pozycji.
+    pub fn liquidation_distance(
+        &self,
+        portfolio: &Portfolio,
+        side: &TradeSide,
+        entry_price: f64,
+        leverage: u8,
+        notional: f64,
+    ) -> f64 {
+        if entry_price <= 0.0 {
+            return 0.0;
+        }
+
+        let liquidation_price = if self.margin.is_cross() {
+            let collateral = portfolio.equity - portfolio.maintenance_margin;
+            self.margin
+                .liquidation_price(side, entry_price, notional, collateral)
+        } else {
+            self.margin
+                .isolated_liquidation_price(side, entry_price, leverage, notional)
+        };
+        (entry_price - liquidation_price).abs() / entry_price
+    }
+
+    /// Najwyższa dźwignia nie większa niż podana, dozwolona przez próg marży
+    /// i przy której odległość od likwidacji spełnia `min_liquidation_distance`
+    pub fn safe_leverage(
+        &self,
+        portfolio: &Portfolio,
+        side: &TradeSide,
+        entry_price: f64,
+        leverage: u8,
+    ) -> Option<u8> {
+        let min_distance = self.min_liquidation_distance();
+
+        (self.trading.min_leverage..=leverage).rev().find(|&lev| {
+            let notional = self.max_position_size(portfolio) * lev as f64;
+            lev <= self.margin.max_leverage(notional)
+                && self.liquidation_distance(portfolio, side, entry_price, lev, notional)
+                    >= min_distance - 1e-12
+        })
+    }
+
+    /// Maksymalny rozmiar pozycji (marża) dla portfela
+    pub fn max_position_size(&self, portfolio: &Portfolio) -> f64 {
+        let max_percent = self
+            .active_phase()
+            .map_or(self.trading.max_position_size_percent, |p| {
+                p.max_position_size_percent
+            });
+        let by_percent = portfolio.equity * to_f64(max_percent);
+        let by_margin = portfolio.margin_available.max(0.0);
+
+        by_percent.min(by_margin).max(0.0)
+    }
+
+    /// Rozmiar pozycji według polityki strategii sygnału, ograniczony
+    /// przez `max_position_size`
+    ///
+    /// ATR pochodzi z `metadata.atr` sygnału (w jednostkach ceny) lub
+    /// z historii notowań tokena.
+    pub async fn position_sizing(
+        &self,
+        signal: &Signal,
+        portfolio: &Portfolio,
+        leverage: u8,
+    ) -> SizingDecision {
+        let policy = self.sizer.policy_for(&signal.source);
+
+        let atr_percent = match policy.atr_periods() {
+            Some(periods) => match signal.metadata.get("atr").and_then(|v| v.as_f64()) {
+                Some(atr) if signal.price > 0.0 => Some(atr / signal.price),
+                _ => self
+                    .correlation
+                    .read()
+                    .await
+                    .atr_percent(&signal.token, periods),
+            },
+            None => None,
+        };
+
+        let stats = self.trade_stats.read().await;
+        let mut decision = policy.size(&SizingInputs {
+            equity: portfolio.equity,
+            leverage,
+            atr_percent,
+            stats: &stats,
+        });
+
+        let max_size = self.max_position_size(portfolio);
+        if decision.size > max_size {
+            decision.size = max_size;
+            decision.basis.push_str(", capped by position limits");
+        }
+        decision.size = decision.size.max(0.0);
+        decision
+    }
+
+    /// Zapisuje otwarcie pozycji do dziennych limitów transakcji
+    pub async fn record_position_opened(&self, token: &str) -> Result<()> {
+        self.trade_limits.record_open(token, self.clock.now()).await
+    }
+
+    /// Zapisuje zamknięcie pozycji; stop-loss i likwidacja blokują ponowne wejście w token
+    pub async fn record_position_exit(&self, token: &str, reason: &ExitReason) -> Result<()> {
+        let block = match reason {
+            ExitReason::StopLoss | ExitReason::MaxLoss => TokenBlockReason::StopLoss,
+            ExitReason::NearLiquidation => TokenBlockReason::Liquidation,
+            _ => return Ok(()),
+        };
+        self.trade_limits
+            .record_block(token, block, self.clock.now())
+            .await
+    }
+
+    /// Liczba pozycji otwartych w bieżącym dniu UTC
+    pub async fn trades_today(&self) -> u32 {
+        self.trade_limits.daily_trades(self.clock.now()).await
+    }
+
+    /// Liczniki dziennych limitów i aktywne blokady tokenów
+    pub async fn trade_limits(&self) -> TradeLimitsState {
+        self.trade_limits.state(self.clock.now()).await
+    }
+
+    /// Powód blokady z limitów transakcji i dziennego limitu aktywnej fazy
+    ///
+    /// Bez tokena sprawdzane są tylko limity globalne.
+    async fn daily_trades_blocker(&self, token: Option<&str>) -> Option<String> {
+        if let Some(reason) = self.trade_limits.blocker(token, self.clock.now()).await {
+            return Some(reason);
+        }
+
+        let phase = self.active_phase()?;
+        let trades = self.trades_today().await;
+        (trades >= phase.max_daily_trades).then(|| {
+            format!(
+                "Daily trade limit of {} phase reached ({}/{})",
+                phase.name, trades, phase.max_daily_trades
+            )
+        })
+    }
+
+    /// Zapisuje zrealizowany wynik zamkniętej pozycji
+    pub async fn record_trade_outcome(&self, pnl: f64) {
+        self.trade_stats.write().await.record_outcome(pnl);
+    }
+
+    /// Zwraca statystyki zamkniętych pozycji używane przy wyznaczaniu rozmiaru
+    pub async fn trading_stats(&self) -> TradingStats {
+        self.trade_stats.read().await.clone()
+    }
+
+    /// Zapisuje notowanie tokena do śledzenia korelacji
+    pub async fn record_price(&self, token: &str, price: f64, timestamp: i64) {
+        self.correlation
+            .write()
+            .await
+            .record(token, price, timestamp);
+    }
+
+    /// Korelacja stóp zwrotu dwóch tokenów (jeśli historia jest wystarczająca)
+    pub async fn correlation(&self, a: &str, b: &str) -> Option<f64> {
+        self.correlation.read().await.correlation(a, b)
+    }
+
+    /// Ekspozycja otwartych pozycji działających w tym samym kierunku co nowa
+    /// pozycja: na ten sam token i na tokeny skorelowane powyżej progu
+    ///
+    /// Pozycja przeciwna na dodatnio skorelowany token (lub zgodna na ujemnie
+    /// skorelowany) zabezpiecza ryzyko i nie jest doliczana.
+    pub async fn exposure(
+        &self,
+        portfolio: &Portfolio,
+        token: &str,
+        side: &TradeSide,
+    ) -> ConcentrationExposure {
+        let threshold = to_f64(self.risk.concentration.correlation_threshold);
+        let tracker = self.correlation.read().await;
+        let mut exposure = ConcentrationExposure::default();
+
+        for position in &portfolio.open_positions {
+            let same_token = position.token.eq_ignore_ascii_case(token);
+            let correlation = if same_token {
+                1.0
+            } else {
+                tracker.correlation(&position.token, token).unwrap_or(0.0)
+            };
+
+            let aligned = (position.side == *side) == (correlation > 0.0);
+            if !aligned || correlation.abs() < threshold {
+                continue;
+            }
+
+            let notional = position.notional();
+            if same_token {
+                exposure.token += notional;
+            }
+            exposure.cluster += notional;
+            if !exposure.cluster_tokens.contains(&position.token) {
+                exposure.cluster_tokens.push(position.token.clone());
+            }
+        }
+
+        exposure
+    }
+
+    /// Maksymalna wartość nominalna nowej pozycji dopuszczona przez limity
+    /// koncentracji (`f64::INFINITY`, gdy limity są wyłączone)
+    pub async fn concentration_headroom(
+        &self,
+        portfolio: &Portfolio,
+        token: &str,
+        side: &TradeSide,
+    ) -> f64 {
+        let limits = &self.risk.concentration;
+        if !limits.enabled {
+            return f64::INFINITY;
+        }
+
+        let equity = portfolio.equity.max(0.0);
+        let exposure = self.exposure(portfolio, token, side).await;
+        let by_token = equity * to_f64(limits.max_token_exposure_percent) - exposure.token;
+        let by_cluster = equity * to_f64(limits.max_cluster_exposure_percent) - exposure.cluster;
+
+        by_token.min(by_cluster).max(0.0)
+    }
+
+    /// Pozycje do zredukowania, gdy wskaźnik marży konta przekroczy próg
+    ///
+    /// Dotyczy tylko trybu cross. Najpierw redukowane są pozycje z najgorszym
+    /// P&L, aż marża utrzymaniowa spadnie do `deleverage_target_ratio` equity.
+    /// Przy equity <= 0 zamykane są wszystkie pozycje.
+    pub fn deleverage_plan(&self, portfolio: &Portfolio) -> Vec<DeleverageStep> {
+        if !self.margin.is_cross()
+            || portfolio.open_positions.is_empty()
+            || portfolio.margin_ratio < to_f64(self.risk.margin.deleverage_margin_ratio)
+        {
+            return Vec::new();
+        }
+
+        let target = to_f64(self.risk.margin.deleverage_target_ratio);
+        let mut excess = if portfolio.equity > 0.0 {
+            portfolio.maintenance_margin - target * portfolio.equity
+        } else {
+            f64::INFINITY
+        };
+
+        let mut worst_first: Vec<&Position> = portfolio.open_positions.iter().collect();
+        worst_first.sort_by(|a, b| {
+            a.pnl
+                .partial_cmp(&b.pnl)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(
+                    b.size
+                        .partial_cmp(&a.size)
+                        .unwrap_or(std::cmp::Ordering::Equal),
+                )
+        });
+
+        let mut steps = Vec::new();
+        for position in worst_first {
+            if excess <= 0.0 {
+                break;
+            }
+
+            let maintenance = self.margin.maintenance_margin(position.notional());
+            if maintenance <= 0.0 {
+                continue;
+            }
+
+            // Marża utrzymaniowa maleje co najmniej proporcjonalnie do rozmiaru
+            let share = (excess / maintenance).min(1.0);
+            excess -= maintenance * share;
+            steps.push(DeleverageStep {
+                position_id: position.id.clone(),
+                token: position.token.clone(),
+                size: position.size * share,
+                pnl: position.pnl,
+            });
+        }
+
+        if !steps.is_empty() {
+            warn!(
+                "Margin ratio {:.2}% above {:.2}%, deleveraging {} position(s)",
+                portfolio.margin_ratio * 100.0,
+                to_f64(self.risk.margin.deleverage_margin_ratio) * 100.0,
+                steps.len()
+            );
+        }
+        steps
+    }
+
+    /// Powód, dla którego nie można otworzyć nowej pozycji (jeśli istnieje)
+    pub fn opening_blocker(&self, portfolio: &Portfolio) -> Option<String> {
+        let max_positions = self.trading.max_concurrent_positions as usize;
+        if portfolio.open_positions.len() >= max_positions {
+            return Some(format!(
+                "Max concurrent positions reached ({}/{})",
+                portfolio.open_positions.len(),
+                max_positions
+            ));
+        }
+
+        let max_daily_loss = to_f64(self.risk.max_daily_loss);
+        if portfolio.daily_pnl <= -max_daily_loss {
+            return Some(format!(
+                "Daily loss limit reached ({:.2} / -{:.2} USD)",
+                portfolio.daily_pnl, max_daily_loss
+            ));
+        }
+
+        if !portfolio.is_healthy() {
+            return Some("Portfolio is not healthy (equity or available margin depleted)".into());
+        }
+
+        None
+    }
+
+    async fn circuit_breaker_allows(&self) -> bool {
+        match &self.circuit_breaker {
+            Some(breaker) => breaker.is_trading_allowed_at(self.clock.now()).await.0,
+            None => true,
+        }
+    }
+
+    fn min_liquidation_distance(&self) -> f64 {
+        to_f64(self.risk.min_liquidation_distance)
+    }
+
+    fn rejection(&self, leverage: u8, reasoning: String, warnings: Vec<String>) -> RiskAssessment {
+        debug!("Risk rejected: {}", reasoning);
+
+        RiskAssessment {
+            approved: false,
+            max_leverage: leverage,
+            position_size: 0.0,
+            reasoning,
+            risk_score: 100,
+            warnings,
+            assessed_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// Wynik ryzyka 0-100: dźwignia, wykorzystanie marży, liczba pozycji i dzienna strata
+    fn risk_score(&self, portfolio: &Portfolio, leverage: u8, position_size: f64) -> u8 {
+        let leverage_ratio = leverage as f64 / self.trading.max_leverage.max(1) as f64;
+
+        // Rozmiar pozycji to już marża
+        let margin_after = portfolio.margin_used + position_size;
+        let utilization = if portfolio.equity > 0.0 {
+            (margin_after / portfolio.equity).min(1.0)
+        } else {
+            1.0
+        };
+
+        let positions_ratio = (portfolio.open_positions.len() + 1) as f64
+            / self.trading.max_concurrent_positions.max(1) as f64;
+
+        let max_daily_loss = to_f64(self.risk.max_daily_loss);
+        let loss_ratio = if max_daily_loss > 0.0 {
+            (-portfolio.daily_pnl / max_daily_loss).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let score = leverage_ratio * 40.0
+            + utilization * 30.0
+            + positions_ratio.min(1.0) * 15.0
+            + loss_ratio * 15.0;
+
+        score.round().clamp(0.0, 100.0) as u8
+    }
+}
+
+#[async_trait]
+impl RiskManagerTrait for RiskManager {
+    async fn evaluate_risk(&self, signal: &Signal, portfolio: &Portfolio) -> RiskAssessment {
+        let requested_leverage = self.leverage_for_signal(signal);
+        let mut warnings = Vec::new();
+
+        if signal.price <= 0.0 {
+            return self.rejection(
+                requested_leverage,
+                format!("Invalid signal price: {}", signal.price),
+                warnings,
+            );
+        }
+
+        if !self.circuit_breaker_allows().await {
+            return self.rejection(
+                requested_leverage,
+                "Circuit breaker is active".to_string(),
+                warnings,
+            );
+        }
+
+        if let Some(reason) = self.opening_blocker(portfolio) {
+            return self.rejection(requested_leverage, reason, warnings);
+        }
+
+        if let Some(reason) = self.daily_trades_blocker(Some(&signal.token)).await {
+            return self.rejection(requested_leverage, reason, warnings);
+        }
+
+        if let Some(phase) = self.active_phase() {
+            let min_confidence = phase
+                .min_confidence
+                .parse::<Confidence>()
+                .unwrap_or(Confidence::Low);
+            if signal.confidence < min_confidence {
+                return self.rejection(
+                    requested_leverage,
+                    format!(
+                        "Signal confidence {} below {} phase minimum {}",
+                        signal.confidence, phase.name, min_confidence
+                    ),
+                    warnings,
+                );
+            }
+        }
+
+        let side = TradingEngine::side_for_signal(signal);
+        let leverage = match self.safe_leverage(portfolio, &side, signal.price, requested_leverage)
+        {
+            Some(leverage) => leverage,
+            None => {
+                return self.rejection(
+                    requested_leverage,
+                    format!(
+                        "Liquidation distance below {:.2}% or margin tier exceeded even at {}x leverage",
+                        self.min_liquidation_distance() * 100.0,
+                        self.trading.min_leverage
+                    ),
+                    warnings,
+                );
+            }
+        };
+
+        if leverage < requested_leverage {
+            warnings.push(format!(
+                "Leverage reduced from {}x to {}x to keep liquidation distance >= {:.2}% within margin tier",
+                requested_leverage,
+                leverage,
+                self.min_liquidation_distance() * 100.0
+            ));
+        }
+
+        let sizing = self.position_sizing(signal, portfolio, leverage).await;
+        let mut position_size = sizing.size;
+
+        // Limity koncentracji na token i grupę skorelowanych tokenów
+        let headroom = self
+            .concentration_headroom(portfolio, &signal.token, &side)
+            .await;
+        if headroom < position_size * leverage as f64 {
+            let exposure = self.exposure(portfolio, &signal.token, &side).await;
+            let message = format!(
+                "Concentration limit for {} (token exposure {:.2} USD, cluster [{}] {:.2} USD)",
+                signal.token,
+                exposure.token,
+                exposure.cluster_tokens.join(", "),
+                exposure.cluster
+            );
+
+            // Limit dotyczy wartości nominalnej, rozmiar pozycji to marża
+            let reduced = headroom / leverage as f64;
+            if reduced < to_f64(self.trading.min_position_size) {
+                return self.rejection(leverage, message, warnings);
+            }
+
+            warnings.push(format!(
+                "{}: size reduced from {:.2} to {:.2} USD",
+                message, position_size, reduced
+            ));
+            position_size = reduced;
+        }
+
+        let liquidation_distance = self.liquidation_distance(
+            portfolio,
+            &side,
+            signal.price,
+            leverage,
+            position_size * leverage as f64,
+        );
+        let stop_loss = self.active_risk_config().stop_loss;
+        if stop_loss.enabled && to_f64(stop_loss.default_percent) >= liquidation_distance {
+            warnings.push(format!(
+                "Stop-loss ({:.2}%) is beyond liquidation distance ({:.2}%)",
+                to_f64(stop_loss.default_percent) * 100.0,
+                liquidation_distance * 100.0
+            ));
+        }
+
+        let min_size = to_f64(self.trading.min_position_size);
+        if position_size < min_size {
+            return self.rejection(
+                leverage,
+                format!(
+                    "Position size {:.2} USD below minimum {:.2} USD",
+                    position_size, min_size
+                ),
+                warnings,
+            );
+        }
+
+        let risk_score = self.risk_score(portfolio, leverage, position_size);
+        if risk_score >= 80 {
+            warnings.push(format!("High risk score: {}", risk_score));
+        }
+
+        RiskAssessment {
+            approved: true,
+            max_leverage: leverage,
+            position_size,
+            reasoning: format!(
+                "{} confidence signal: {:.2} USD at {}x ({}), liquidation distance {:.2}%",
+                signal.confidence,
+                position_size,
+                leverage,
+                sizing.basis,
+                liquidation_distance * 100.0
+            ),
+            risk_score,
+            warnings,
+            assessed_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    async fn check_position_limits(&self, new_position: &Position) -> bool {
+        if !self.circuit_breaker_allows().await {
+            return false;
+        }
+
+        let portfolio = self.portfolio.read().await;
+
+        if self.opening_blocker(&portfolio).is_some()
+            || self
+                .daily_trades_blocker(Some(&new_position.token))
+                .await
+                .is_some()
+        {
+            return false;
+        }
+
+        let headroom = self
+            .concentration_headroom(&portfolio, &new_position.token, &new_position.side)
+            .await;
+        if new_position.notional() > headroom + 1e-9 {
+            return false;
+        }
+
+        if new_position.size < to_f64(self.trading.min_position_size)
+            || new_position.size > self.max_position_size(&portfolio)
+        {
+            return false;
+        }
+
+        if new_position.leverage < self.trading.min_leverage
+            || new_position.leverage > self.trading.max_leverage
+            || self
+                .active_phase()
+                .is_some_and(|phase| new_position.leverage > phase.max_leverage)
+            || new_position.leverage
+                > self
+                    .margin
+                    .max_leverage(new_position.size * new_position.leverage as f64)
+        {
+            return false;
+        }
+
+        let distance = if new_position.current_price > 0.0 {
+            (new_position.current_price - new_position.liquidation_price).abs()
+                / new_position.current_price
+        } else {
+            0.0
+        };
+
+        distance >= self.min_liquidation_distance()
+    }
+
+    async fn calculate_position_size(&self, signal: &Signal, leverage: u8) -> f64 {
+        let portfolio = self.portfolio().await;
+        self.position_sizing(signal, &portfolio, leverage)
+            .await
+            .size
+    }
+
+    async fn can_open_position(&self, portfolio: &Portfolio) -> bool {
+        self.circuit_breaker_allows().await
+            && self.opening_blocker(portfolio).is_none()
+            && self.daily_trades_blocker(None).await.is_none()
+    }
+
+    async fn should_close_position(&self, position: &Position) -> bool {
+        if position.is_near_liquidation(to_f64(self.risk.liquidation_buffer)) {
+            warn!("Position {} is near liquidation", position.id);
+            return true;
+        }
+
+        // Strata względem marży: niekorzystny ruch ceny razy dźwignia
+        if position.entry_price > 0.0 {
+            let price_change =
+                (position.current_price - position.entry_price) / position.entry_price;
+            let adverse_move = match position.side {
+                TradeSide::Long => -price_change,
+                TradeSide::Short => price_change,
+            };
+
+            if adverse_move * position.leverage as f64
+                >= to_f64(self.risk.max_position_loss_percent)
+            {
+                warn!("Position {} exceeded max position loss", position.id);
+                return true;
+            }
+        }
+
+        let max_duration = self.risk.max_position_duration as i64 * 3600;
+        position.duration_seconds_at(self.clock.now()) > max_duration
+    }
+}
+
+fn to_f64(value: rust_decimal::Decimal) -> f64 {
+    value.to_f64().unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SizingPolicyConfig;
+    use crate::signals::Confidence;
+    use rust_decimal::Decimal;
+
+    fn manager() -> RiskManager {
+        RiskManager::new(RiskConfig::default(), TradingConfig::default())
+    }
+
+    fn signal(confidence: Confidence) -> Signal {
+        Signal::new(
+            "BONK".to_string(),
+            "test".to_string(),
+            confidence,
+            1.0,
+            1000.0,
+            serde_json::Value::Null,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_risk_approves_and_caps_size() {
+        let mut risk = RiskConfig::default();
+        risk.concentration.enabled = false;
+        let unlimited = RiskManager::new(risk, TradingConfig::default());
+        let portfolio = Portfolio::new(1000.0);
+
+        let assessment = unlimited
+            .evaluate_risk(&signal(Confidence::Medium), &portfolio)
+            .await;
+
+        assert!(assessment.approved, "{}", assessment.reasoning);
+        assert_eq!(assessment.max_leverage, 10);
+        // 33% z 1000 USD
+        assert!((assessment.position_size - 330.0).abs() < 1e-9);
+
+        // Limit koncentracji 50% equity dotyczy wartości nominalnej:
+        // 500 USD przy 10x to 50 USD marży
+        let assessment = manager()
+            .evaluate_risk(&signal(Confidence::Medium), &portfolio)
+            .await;
+        assert!(assessment.approved, "{}", assessment.reasoning);
+        assert!((assessment.position_size - 50.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_leverage_reduced_for_liquidation_distance() {
+        let manager = manager();
+        let portfolio = Portfolio::new(1000.0);
+
+        // 30x => odległość (1/30 - 1%) / 0.99 = 2.4% < 5%; przy marży utrzymaniowej 0.5%
+        // i opłacie likwidacyjnej 0.5% najwyższa bezpieczna dźwignia to 16x
+        let assessment = manager
+            .evaluate_risk(&signal(Confidence::Extreme), &portfolio)
+            .await;
+
+        assert!(assessment.approved);
+        assert_eq!(assessment.max_leverage, 16);
+        assert!(!assessment.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_short_checked_against_short_liquidation_price() {
+        let mut risk = RiskConfig::default();
+        // 16x: long (1/16 - 1%) / 0.99 = 5.30%, short (1/16 - 1%) / 1.01 = 5.20%
+        risk.min_liquidation_distance = Decimal::new(525, 4);
+        let manager = RiskManager::new(risk, TradingConfig::default());
+        let portfolio = Portfolio::new(1000.0);
+        let extreme = |side: &str| {
+            Signal::new(
+                "BONK".to_string(),
+                "test".to_string(),
+                Confidence::Extreme,
+                1.0,
+                1000.0,
+                serde_json::json!({ "side": side }),
+            )
+        };
+
+        let long = manager.evaluate_risk(&extreme("long"), &portfolio).await;
+        assert!(long.approved, "{}", long.reasoning);
+        assert_eq!(long.max_leverage, 16);
+
+        let short = manager.evaluate_risk(&extreme("short"), &portfolio).await;
+        assert!(short.approved, "{}", short.reasoning);
+        assert_eq!(short.max_leverage, 15);
+    }
+
+    #[tokio::test]
+    async fn test_leverage_capped_by_margin_tier() {
+        let mut risk = RiskConfig::default();
+        risk.margin.tiers[0].max_leverage = 8;
+        let manager = RiskManager::new(risk, TradingConfig::default());
+
+        // 330 USD mieści się w pierwszym progu z limitem 8x
+        let assessment = manager
+            .evaluate_risk(&signal(Confidence::Medium), &Portfolio::new(1000.0))
+            .await;
+
+        assert!(assessment.approved);
+        assert_eq!(assessment.max_leverage, 8);
+
+        manager.update_portfolio(&Portfolio::new(1000.0)).await;
+        let position = Position::new("X".to_string(), TradeSide::Long, 100.0, 10, 1.0);
+        assert!(!manager.check_position_limits(&position).await);
+    }
+
+    #[tokio::test]
+    async fn test_cross_margin_keeps_requested_leverage() {
+        let mut risk = RiskConfig::default();
+        risk.margin.mode = "cross".to_string();
+        let manager = RiskManager::new(risk, TradingConfig::default());
+
+        // Saldo 1000 USD zabezpiecza pozycję 330 USD x 20 - likwidacja poza zasięgiem
+        // (marża izolowana przy 20x dałaby odległość poniżej 5%)
+        let assessment = manager
+            .evaluate_risk(&signal(Confidence::High), &Portfolio::new(1000.0))
+            .await;
+
+        assert!(assessment.approved, "{}", assessment.reasoning);
+        assert_eq!(assessment.max_leverage, 20);
+
+        manager.update_portfolio(&Portfolio::new(1000.0)).await;
+        assert!(manager.portfolio().await.margin_model.is_cross());
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_risk_leaves_state_unchanged() {
+        let manager = manager();
+        let mut live = Portfolio::new(1000.0);
+        live.daily_pnl = -20.0;
+        live.open_positions.push(Position::new(
+            "WIF".to_string(),
+            TradeSide::Long,
+            50.0,
+            5,
+            2.0,
+        ));
+        live.update();
+        manager.update_portfolio(&live).await;
+
+        // Ocena dla hipotetycznego salda, jak w POST /api/risk/assess
+        let assessment = manager
+            .evaluate_risk(&signal(Confidence::Medium), &Portfolio::new(5000.0))
+            .await;
+        assert!(assessment.approved, "{}", assessment.reasoning);
+
+        let portfolio = manager.portfolio().await;
+        assert_eq!(portfolio.balance, 1000.0);
+        assert_eq!(portfolio.daily_pnl, -20.0);
+        assert_eq!(portfolio.open_positions.len(), 1);
+        assert_eq!(manager.correlation.read().await.samples("BONK"), 0);
+    }
+
+    #[test]
+    fn test_risk_score_counts_position_size_as_margin() {
+        let manager = manager();
+        let portfolio = Portfolio::new(1000.0);
+
+        // 500 USD marży to 50% equity: 0.5 x 30 punktów za wykorzystanie marży
+        let empty = manager.risk_score(&portfolio, 10, 0.0);
+        assert_eq!(manager.risk_score(&portfolio, 10, 500.0), empty + 15);
+    }
+
+    #[test]
+    fn test_deleverage_plan_reduces_worst_positions_first() {
+        let mut risk = RiskConfig::default();
+        risk.margin.mode = "cross".to_string();
+        let manager = RiskManager::new(risk, TradingConfig::default());
+
+        let mut portfolio = Portfolio::new(50.0).with_margin_model(manager.margin_model().clone());
+        for (token, price) in [("A", 0.99), ("B", 0.95), ("C", 1.01)] {
+            let mut position = Position::new(token.to_string(), TradeSide::Long, 1_000.0, 2, 1.0);
+            position.update_price(price);
+            portfolio.add_position(position);
+        }
+
+        // P&L: A -20, B -100, C +20 => equity -50, zamykane wszystko od najgorszej
+        let steps = manager.deleverage_plan(&portfolio);
+        let tokens: Vec<&str> = steps.iter().map(|s| s.token.as_str()).collect();
+        assert_eq!(tokens, vec!["B", "A", "C"]);
+        assert!(steps.iter().all(|s| s.size == 1_000.0));
+
+        // Marża utrzymaniowa 0.5% wartości nominalnej: A 9.9, B 9.5, C 10.1 USD
+        let maintenance = |token: &str| {
+            let position = portfolio
+                .open_positions
+                .iter()
+                .find(|p| p.token == token)
+                .unwrap();
+            manager
+                .margin_model()
+                .maintenance_margin(position.notional())
+        };
+        let (a, b) = (maintenance("A"), maintenance("B"));
+        assert!((a - 9.9).abs() < 1e-9 && (b - 9.5).abs() < 1e-9);
+        assert!((portfolio.maintenance_margin - 29.5).abs() < 1e-9);
+
+        // Equity 1000 USD, marża utrzymaniowa 29.5 USD (2.95%) - pod progiem 80%
+        portfolio.balance = 1_100.0;
+        portfolio.update();
+        assert!(manager.deleverage_plan(&portfolio).is_empty());
+
+        // Equity 35 USD: 29.5 / 35 = 84% => redukcja do 17.5 USD marży,
+        // całe B (9.5) i część A (pozostałe 2.5 z 9.9)
+        portfolio.balance = 135.0;
+        portfolio.update();
+        let steps = manager.deleverage_plan(&portfolio);
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].token, "B");
+        assert_eq!(steps[0].size, 1_000.0);
+        assert_eq!(steps[1].token, "A");
+        let share = (29.5 - 0.5 * 35.0 - b) / a;
+        assert!((steps[1].size - 1_000.0 * share).abs() < 1e-6);
+
+        // W trybie izolowanym konto nie ma wspólnego progu likwidacji
+        let isolated = RiskManager::new(RiskConfig::default(), TradingConfig::default());
+        assert!(isolated.deleverage_plan(&portfolio).is_empty());
+    }
+
+    fn signal_for(token: &str, side: &str) -> Signal {
+        Signal::new(
+            token.to_string(),
+            "test".to_string(),
+            Confidence::Medium,
+            1.0,
+            1000.0,
+            serde_json::json!({ "side": side }),
+        )
+    }
+
+    /// AAA i BBB poruszają się razem, CCC nie ma historii cen
+    async fn correlated_manager() -> RiskManager {
+        let manager = manager();
+        let (mut aaa, mut bbb) = (1.0, 2.0);
+        for i in 0..40i64 {
+            let change = ((i * 7919) % 13 - 6) as f64 / 1000.0;
+            aaa *= 1.0 + change;
+            bbb *= 1.0 + change * 1.2;
+            manager.record_price("AAA", aaa, i * 60).await;
+            manager.record_price("BBB", bbb, i * 60).await;
+        }
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_concentration_limits_downsize_correlated_positions() {
+        let manager = correlated_manager().await;
+        assert!(manager.correlation("AAA", "BBB").await.unwrap() > 0.99);
+
+        let mut portfolio = Portfolio::new(1000.0);
+        portfolio.add_position(Position::new(
+            "AAA".to_string(),
+            TradeSide::Long,
+            60.0,
+            10,
+            1.0,
+        ));
+
+        // Grupa AAA+BBB: limit 70% z 1000 USD, zajęte 600 USD nominalnie;
+        // 100 USD nominalnie przy 10x to 10 USD marży
+        let assessment = manager
+            .evaluate_risk(&signal_for("BBB", "long"), &portfolio)
+            .await;
+        assert!(assessment.approved, "{}", assessment.reasoning);
+        assert!((assessment.position_size - 10.0).abs() < 1e-9);
+        assert!(assessment
+            .warnings
+            .iter()
+            .any(|w| w.contains("Concentration limit")));
+
+        // Ten sam token: limit 50%, zajęte 600 USD
+        let assessment = manager
+            .evaluate_risk(&signal_for("AAA", "long"), &portfolio)
+            .await;
+        assert!(!assessment.approved);
+
+        // Short na skorelowany token zabezpiecza pozycję, token bez historii jest niezależny
+        for (token, side) in [("BBB", "short"), ("CCC", "long")] {
+            let assessment = manager
+                .evaluate_risk(&signal_for(token, side), &portfolio)
+                .await;
+            // Tylko limit na token: 500 USD nominalnie
+            assert!(assessment.approved);
+            assert!((assessment.position_size - 50.0).abs() < 1e-9);
+        }
+
+        let exposure = manager.exposure(&portfolio, "BBB", &TradeSide::Long).await;
+        assert_eq!(exposure.token, 0.0);
+        assert_eq!(exposure.cluster, 600.0);
+        assert_eq!(exposure.cluster_tokens, vec!["AAA".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_check_position_limits_enforces_concentration() {
+        let manager = correlated_manager().await;
+        let mut portfolio = Portfolio::new(1000.0);
+        portfolio.add_position(Position::new(
+            "AAA".to_string(),
+            TradeSide::Long,
+            60.0,
+            10,
+            1.0,
+        ));
+        manager.update_portfolio(&portfolio).await;
+
+        let within = Position::new("BBB".to_string(), TradeSide::Long, 10.0, 10, 1.0);
+        let above = Position::new("BBB".to_string(), TradeSide::Long, 15.0, 10, 1.0);
+        assert!(manager.check_position_limits(&within).await);
+        assert!(!manager.check_position_limits(&above).await);
+    }
+
+    #[tokio::test]
+    async fn test_position_size_follows_strategy_policy() {
+        let mut trading = TradingConfig::default();
+        trading.sizing.strategies.insert(
+            "volatility".to_string(),
+            SizingPolicyConfig {
+                policy: "atr".to_string(),
+                ..SizingPolicyConfig::default()
+            },
+        );
+        trading.sizing.strategies.insert(
+            "kelly".to_string(),
+            SizingPolicyConfig {
+                policy: "kelly".to_string(),
+                kelly_fraction: Decimal::new(5, 2),
+                ..SizingPolicyConfig::default()
+            },
+        );
+        // Bez limitów koncentracji - test dotyczy tylko polityki rozmiaru
+        let mut risk = RiskConfig::default();
+        risk.concentration.enabled = false;
+        let manager = RiskManager::new(risk, trading);
+        let portfolio = Portfolio::new(1000.0);
+        let signal = |source: &str, metadata: serde_json::Value| {
+            Signal::new(
+                "BONK".to_string(),
+                source.to_string(),
+                Confidence::Medium,
+                1.0,
+                1000.0,
+                metadata,
+            )
+        };
+
+        // ATR 5% ceny: 1% z 1000 USD / (2 x 5%) = 100 USD nominalnie, 10 USD marży przy 10x
+        let assessment = manager
+            .evaluate_risk(
+                &signal("volatility", serde_json::json!({ "atr": 0.05 })),
+                &portfolio,
+            )
+            .await;
+        assert!(assessment.approved, "{}", assessment.reasoning);
+        assert!((assessment.position_size - 10.0).abs() < 1e-9);
+        assert!(assessment.reasoning.contains("ATR"));
+
+        // Bez historii transakcji Kelly używa ułamka zastępczego 33%
+        let assessment = manager
+            .evaluate_risk(&signal("kelly", serde_json::Value::Null), &portfolio)
+            .await;
+        assert!((assessment.position_size - 330.0).abs() < 1e-9);
+
+        // W = 0.6, R = 2: Kelly 40% x 0.05 = 2% equity jako marża
+        for _ in 0..12 {
+            manager.record_trade_outcome(20.0).await;
+        }
+        for _ in 0..8 {
+            manager.record_trade_outcome(-10.0).await;
+        }
+        assert_eq!(manager.trading_stats().await.closed_trades(), 20);
+
+        let assessment = manager
+            .evaluate_risk(&signal("kelly", serde_json::Value::Null), &portfolio)
+            .await;
+        assert!(assessment.approved, "{}", assessment.reasoning);
+        assert!((assessment.position_size - 20.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_strategy_phase_overrides_limits() {
+        let strategy = StrategyConfig {
+            enabled: true,
+            ..StrategyConfig::default()
+        };
+        let mut risk = RiskConfig::default();
+        risk.concentration.enabled = false;
+        let manager = RiskManager::new(risk, TradingConfig::default()).with_strategy(&strategy);
+        assert_eq!(manager.active_phase().unwrap().name, "survival");
+
+        // Survival: tylko sygnały high i wyższe, 20x * 0.4 ograniczone do 2x, 10% equity
+        let portfolio = Portfolio::new(60.0);
+        let rejected = manager
+            .evaluate_risk(&signal(Confidence::Medium), &portfolio)
+            .await;
+        assert!(!rejected.approved);
+        assert!(
+            rejected.reasoning.contains("survival"),
+            "{}",
+            rejected.reasoning
+        );
+
+        let assessment = manager
+            .evaluate_risk(&signal(Confidence::High), &portfolio)
+            .await;
+        assert!(assessment.approved, "{}", assessment.reasoning);
+        assert_eq!(assessment.max_leverage, 2);
+        assert!((assessment.position_size - 6.0).abs() < 1e-9);
+
+        for token in ["WIF", "PEPE", "POPCAT"] {
+            manager.record_position_opened(token).await.unwrap();
+        }
+        let limited = manager
+            .evaluate_risk(&signal(Confidence::High), &portfolio)
+            .await;
+        assert!(!limited.approved);
+        assert!(limited.reasoning.contains("Daily trade limit of survival"));
+
+        // Building: 10x * 0.7 = 7x, 20% equity, limit 5 transakcji dziennie
+        let change = manager.update_phase(200.0).unwrap();
+        assert_eq!(change.to, "building");
+        assert_eq!(
+            manager.active_risk_config().stop_loss.default_percent,
+            Decimal::new(15, 2)
+        );
+
+        let portfolio = Portfolio::new(200.0);
+        let assessment = manager
+            .evaluate_risk(&signal(Confidence::Medium), &portfolio)
+            .await;
+        assert!(assessment.approved, "{}", assessment.reasoning);
+        assert_eq!(assessment.max_leverage, 7);
+        assert!((assessment.position_size - 40.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_trade_limits_block_reentry() {
+        let clock = Clock::simulated(1_700_000_000);
+        let manager = manager().with_clock(clock.clone());
+        let portfolio = Portfolio::new(1000.0);
+
+        manager
+            .record_position_exit("BONK", &ExitReason::TakeProfit)
+            .await
+            .unwrap();
+        assert!(
+            manager
+                .evaluate_risk(&signal(Confidence::High), &portfolio)
+                .await
+                .approved
+        );
+
+        // Stop-loss: cooldown 1h na BONK, inne tokeny bez zmian
+        manager
+            .record_position_exit("BONK", &ExitReason::StopLoss)
+            .await
+            .unwrap();
+        let rejected = manager
+            .evaluate_risk(&signal(Confidence::High), &portfolio)
+            .await;
+        assert!(!rejected.approved);
+        assert!(rejected.reasoning.contains("stop-loss cooldown"));
+        assert!(manager.can_open_position(&portfolio).await);
+
+        clock.set(1_700_000_000 + 3600);
+        assert!(
+            manager
+                .evaluate_risk(&signal(Confidence::High), &portfolio)
+                .await
+                .approved
+        );
+
+        // Limit 2 otwarć dziennie na token
+        for _ in 0..2 {
+            manager.record_position_opened("bonk").await.unwrap();
+        }
+        let limited = manager
+            .evaluate_risk(&signal(Confidence::High), &portfolio)
+            .await;
+        assert!(limited.reasoning.contains("Daily trade limit for BONK"));
+        assert_eq!(manager.trade_limits().await.token_trades["BONK"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_when_max_positions_reached() {
+        let manager = manager();
+        let mut portfolio = Portfolio::new(1000.0);
+        for _ in 0..3 {
+            portfolio.add_position(Position::new(
+                "X".to_string(),
+                TradeSide::Long,
+                10.0,
+                5,
+                1.0,
+            ));
+        }
+
+        let assessment = manager
+            .evaluate_risk(&signal(Confidence::High), &portfolio)
+            .await;
+
+        assert!(!assessment.approved);
+        assert!(!manager.can_open_position(&portfolio).await);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_on_daily_loss_and_small_size() {
+        let manager = manager();
+
+        let mut portfolio = Portfolio::new(1000.0);
+        portfolio.daily_pnl = -15.0;
+        let assessment = manager
+            .evaluate_risk(&signal(Confidence::Low), &portfolio)
+            .await;
+        assert!(!assessment.approved);
+
+        // 33% z 10 USD = 3.3 USD < minimum 5 USD
+        let assessment = manager
+            .evaluate_risk(&signal(Confidence::Low), &Portfolio::new(10.0))
+            .await;
+        assert!(!assessment.approved);
+        assert_eq!(assessment.position_size, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_when_circuit_breaker_active() {
+        let breaker = Arc::new(CircuitBreaker::new(&RiskConfig::default()));
+        let manager = manager().with_circuit_breaker(breaker.clone());
+        let portfolio = Portfolio::new(1000.0);
+
+        breaker.trip("test".to_string()).await.unwrap();
+        let assessment = manager
+            .evaluate_risk(&signal(Confidence::Medium), &portfolio)
+            .await;
+        assert!(!assessment.approved);
+
+        breaker.reset("test").await.unwrap();
+        assert!(manager.can_open_position(&portfolio).await);
+    }
+
+    #[tokio::test]
+    async fn test_should_close_position() {
+        let manager = manager();
+
+        let mut position = Position::new("X".to_string(), TradeSide::Long, 100.0, 10, 1.0);
+        position.update_price(0.995);
+        assert!(!manager.should_close_position(&position).await);
+
+        // -1% przy 10x = -10% marży => max_position_loss_percent
+        position.update_price(0.99);
+        assert!(manager.should_close_position(&position).await);
+
+        let mut short = Position::new("X".to_string(), TradeSide::Short, 100.0, 5, 1.0);
+        short.opened_at -= 25 * 3600;
+        assert!(manager.should_close_position(&short).await);
+    }
+}
@@ -0,0 +1,254 @@
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use super::TradeSide;
+use crate::config::MarginConfig;
+
+/// Tryb marży
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum MarginMode {
+    /// Każda pozycja ma własną marżę; strata ograniczona do marży pozycji
+    Isolated,
+
+    /// Całe saldo konta zabezpiecza wszystkie pozycje
+    Cross,
+}
+
+/// Próg marży utrzymaniowej
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarginTier {
+    /// Górna granica wartości nominalnej w progu
+    pub max_notional: f64,
+
+    /// Stawka marży utrzymaniowej
+    pub maintenance_rate: f64,
+
+    /// Kwota korygująca (jak "cum" na Binance), wyrównuje marżę na granicach progów
+    pub maintenance_amount: f64,
+
+    /// Maksymalna dźwignia w progu
+    pub max_leverage: u8,
+}
+
+/// Model marży: tryb, progi marży utrzymaniowej i opłata likwidacyjna
+///
+/// Rozmiar pozycji (`Position::size`) to marża pozycji, a wartość nominalna
+/// to `size * leverage` - tak samo jak w `Position::price_pnl`. Progi i marża
+/// utrzymaniowa liczone są od wartości nominalnej.
+/// Cena likwidacji to cena, przy której zabezpieczenie pozycji powiększone
+/// o niezrealizowany P&L spada do marży utrzymaniowej powiększonej o opłatę
+/// likwidacyjną (wzór Binance USDT-M z kwotą korygującą progu).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarginModel {
+    pub mode: MarginMode,
+    pub tiers: Vec<MarginTier>,
+    pub liquidation_fee: f64,
+}
+
+impl MarginModel {
+    /// Tworzy model z konfiguracji; kwoty korygujące liczone są z progów
+    pub fn from_config(config: &MarginConfig) -> Self {
+        let mode = match config.mode.as_str() {
+            "cross" => MarginMode::Cross,
+            _ => MarginMode::Isolated,
+        };
+
+        let mut tiers: Vec<MarginTier> = Vec::with_capacity(config.tiers.len());
+        for tier in &config.tiers {
+            let maintenance_rate = tier.maintenance_margin_rate.to_f64().unwrap_or(0.0);
+            let maintenance_amount = match tiers.last() {
+                Some(previous) => {
+                    previous.maintenance_amount
+                        + previous.max_notional * (maintenance_rate - previous.maintenance_rate)
+                }
+                None => 0.0,
+            };
+
+            tiers.push(MarginTier {
+                max_notional: tier.max_notional.to_f64().unwrap_or(0.0),
+                maintenance_rate,
+                maintenance_amount,
+                max_leverage: tier.max_leverage,
+            });
+        }
+
+        Self {
+            mode,
+            tiers,
+            liquidation_fee: config.liquidation_fee_percent.to_f64().unwrap_or(0.0),
+        }
+    }
+
+    /// Czy model działa w trybie cross margin
+    pub fn is_cross(&self) -> bool {
+        self.mode == MarginMode::Cross
+    }
+
+    /// Próg dla wartości nominalnej (powyżej ostatniego - ostatni próg)
+    pub fn tier(&self, notional: f64) -> Option<&MarginTier> {
+        self.tiers
+            .iter()
+            .find(|tier| notional <= tier.max_notional)
+            .or_else(|| self.tiers.last())
+    }
+
+    /// Marża utrzymaniowa pozycji o podanej wartości nominalnej
+    pub fn maintenance_margin(&self, notional: f64) -> f64 {
+        match self.tier(notional) {
+            Some(tier) => (notional * tier.maintenance_rate - tier.maintenance_amount).max(0.0),
+            None => 0.0,
+        }
+    }
+
+    /// Maksymalna dźwignia dla wartości nominalnej
+    pub fn max_leverage(&self, notional: f64) -> u8 {
+        self.tier(notional)
+            .map(|tier| tier.max_leverage)
+            .unwrap_or(u8::MAX)
+    }
+
+    /// Cena likwidacji pozycji zabezpieczonej kwotą `collateral`
+    ///
+    /// W trybie izolowanym zabezpieczeniem jest marża pozycji, w cross -
+    /// saldo konta z P&L pozostałych pozycji pomniejszone o ich marżę
+    /// utrzymaniową.
+    pub fn liquidation_price(
+        &self,
+        side: &TradeSide,
+        entry_price: f64,
+        notional: f64,
+        collateral: f64,
+    ) -> f64 {
+        if entry_price <= 0.0 || notional <= 0.0 {
+            return 0.0;
+        }
+
+        let (rate, amount) = self
+            .tier(notional)
+            .map(|tier| (tier.maintenance_rate, tier.maintenance_amount))
+            .unwrap_or((0.0, 0.0));
+        let rate = rate + self.liquidation_fee;
+        let quantity = notional / entry_price;
+
+        match side {
+            TradeSide::Long => {
+                ((notional - collateral - amount) / (quantity * (1.0 - rate))).max(0.0)
+            }
+            TradeSide::Short => (notional + collateral + amount) / (quantity * (1.0 + rate)),
+        }
+    }
+
+    /// Cena likwidacji przy marży izolowanej `notional / leverage`
+    pub fn isolated_liquidation_price(
+        &self,
+        side: &TradeSide,
+        entry_price: f64,
+        leverage: u8,
+        notional: f64,
+    ) -> f64 {
+        let collateral = notional / leverage.max(1) as f64;
+        self.liquidation_price(side, entry_price, notional, collateral)
+    }
+}
+
+impl Default for MarginModel {
+    fn default() -> Self {
+        Self::from_config(&MarginConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MarginTierConfig;
+    use rust_decimal::Decimal;
+
+    /// Progi BTCUSDT z Binance USDT-M (0.4% / 0.5% / 1%, cum 0 / 50 / 2550)
+    fn binance_btc() -> MarginModel {
+        let tier = |max_notional: i64, rate: i64, max_leverage: u8| MarginTierConfig {
+            max_notional: Decimal::new(max_notional, 0),
+            maintenance_margin_rate: Decimal::new(rate, 3),
+            max_leverage,
+        };
+
+        MarginModel::from_config(&MarginConfig {
+            mode: "isolated".to_string(),
+            tiers: vec![
+                tier(50_000, 4, 125),
+                tier(500_000, 5, 100),
+                tier(8_000_000, 10, 50),
+            ],
+            liquidation_fee_percent: Decimal::ZERO,
+            ..MarginConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_maintenance_amounts_match_exchange_brackets() {
+        let model = binance_btc();
+
+        assert_eq!(model.tiers[0].maintenance_amount, 0.0);
+        assert!((model.tiers[1].maintenance_amount - 50.0).abs() < 1e-9);
+        assert!((model.tiers[2].maintenance_amount - 2_550.0).abs() < 1e-9);
+
+        // Marża utrzymaniowa jest ciągła na granicy progów
+        let below = model.maintenance_margin(50_000.0);
+        let above = model.maintenance_margin(50_000.0 + 1e-6);
+        assert!((below - 200.0).abs() < 1e-6);
+        assert!((above - below).abs() < 1e-6);
+
+        assert_eq!(model.max_leverage(10_000.0), 125);
+        assert_eq!(model.max_leverage(1_000_000.0), 50);
+        assert_eq!(model.max_leverage(100_000_000.0), 50);
+    }
+
+    #[test]
+    fn test_isolated_liquidation_matches_binance_formula() {
+        let model = binance_btc();
+
+        // Long 1 BTC @ 10 000, 100x: (10 000 - 100) / (1 * (1 - 0.004)) = 9939.76
+        let price = model.isolated_liquidation_price(&TradeSide::Long, 10_000.0, 100, 10_000.0);
+        assert!((price - 9_939.759).abs() < 0.01);
+
+        // Short 1 BTC @ 10 000, 100x: (10 000 + 100) / (1 * (1 + 0.004)) = 10 059.76
+        let price = model.isolated_liquidation_price(&TradeSide::Short, 10_000.0, 100, 10_000.0);
+        assert!((price - 10_059.761).abs() < 0.01);
+
+        // Long 2 BTC @ 50 000, 20x (drugi próg): (100 000 - 5 000 - 50) / (2 * 0.995)
+        let price = model.isolated_liquidation_price(&TradeSide::Long, 50_000.0, 20, 100_000.0);
+        assert!((price - 47_713.568).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_liquidation_fee_and_tiers_pull_price_closer() {
+        let mut model = binance_btc();
+        let small = model.isolated_liquidation_price(&TradeSide::Long, 1.0, 50, 10_000.0);
+        let large = model.isolated_liquidation_price(&TradeSide::Long, 1.0, 50, 1_000_000.0);
+        assert!(large > small);
+
+        model.liquidation_fee = 0.005;
+        let with_fee = model.isolated_liquidation_price(&TradeSide::Long, 1.0, 50, 10_000.0);
+        assert!(with_fee > small);
+
+        // Przy 50x i 0.9% łącznej stawki zostaje ~1.1% ruchu ceny
+        assert!((1.0 - with_fee - 0.011).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_cross_collateral_moves_liquidation_away() {
+        let model = MarginModel {
+            mode: MarginMode::Cross,
+            ..binance_btc()
+        };
+
+        let isolated = model.isolated_liquidation_price(&TradeSide::Long, 100.0, 20, 1_000.0);
+        let cross = model.liquidation_price(&TradeSide::Long, 100.0, 1_000.0, 500.0);
+        assert!(cross < isolated);
+
+        // Zabezpieczenie większe niż wartość nominalna - long nie może zostać zlikwidowany
+        assert_eq!(
+            model.liquidation_price(&TradeSide::Long, 100.0, 1_000.0, 2_000.0),
+            0.0
+        );
+    }
+}
@@ -4,12 +4,26 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 /// Moduł zarządzania ryzykiem z integracją Sentry
-// pub mod manager;
-// pub mod circuit_breaker;
+pub mod circuit_breaker;
+pub mod correlation;
+pub mod emergency;
+pub mod exits;
+pub mod manager;
+pub mod margin;
+pub mod phase;
+pub mod sizing;
+pub mod trade_limits;
 // pub mod calculator;
 
-// pub use manager::RiskManager;
-// pub use circuit_breaker::CircuitBreaker;
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerStatus, TripReason};
+pub use correlation::CorrelationTracker;
+pub use emergency::{EmergencyReport, EmergencyStop, EmergencyTrigger};
+pub use exits::{ExitAction, ExitManager, ExitPlan, ProtectiveOrder, ProtectiveRole};
+pub use manager::{ConcentrationExposure, DeleverageStep, RiskManager};
+pub use margin::{MarginMode, MarginModel, MarginTier};
+pub use phase::{PhaseChange, PhaseTracker};
+pub use sizing::{PositionSizer, SizingDecision, SizingInputs, SizingMethod, SizingPolicy};
+pub use trade_limits::{TokenBlock, TokenBlockReason, TradeLimiter, TradeLimitsState};
 // pub use calculator::LeverageCalculator;
 
 /// Struktura portfela
@@ -21,12 +35,24 @@ pub struct Portfolio {
     /// Equity (balans + unrealized PnL)
     pub equity: f64,
 
+    /// Niezrealizowany P&L otwartych pozycji
+    #[serde(default)]
+    pub unrealized_pnl: f64,
+
     /// Używana marża
     pub margin_used: f64,
 
+    /// Marża utrzymaniowa otwartych pozycji
+    #[serde(default)]
+    pub maintenance_margin: f64,
+
     /// Dostępna marża
     pub margin_available: f64,
 
+    /// Wskaźnik marży konta: marża utrzymaniowa / equity (1.0 = likwidacja)
+    #[serde(default)]
+    pub margin_ratio: f64,
+
     /// Dzienny P&L
     pub daily_pnl: f64,
 
@@ -35,6 +61,10 @@ pub struct Portfolio {
 
     /// Ostatnia aktualizacja
     pub last_updated: i64,
+
+    /// Model marży wyznaczający ceny likwidacji pozycji
+    #[serde(skip)]
+    pub margin_model: MarginModel,
 }
 
 /// Struktura pozycji
@@ -49,7 +79,7 @@ pub struct Position {
     /// Strona transakcji (Long/Short)
     pub side: TradeSide,
 
-    /// Rozmiar pozycji
+    /// Rozmiar pozycji - zaangażowana marża (wartość nominalna = size * leverage)
     pub size: f64,
 
     /// Dźwignia
@@ -61,9 +91,25 @@ pub struct Position {
     /// Aktualna cena
     pub current_price: f64,
 
-    /// P&L (realized + unrealized)
+    /// P&L netto otwartej części (ruch ceny minus nierozliczone koszty)
     pub pnl: f64,
 
+    /// Opłaty transakcyjne zapłacone na pozycji (wejście i wyjścia)
+    #[serde(default)]
+    pub fees_paid: f64,
+
+    /// Suma płatności finansowania (dodatnia = zapłacone, ujemna = otrzymane)
+    #[serde(default)]
+    pub funding_paid: f64,
+
+    /// Część opłat i finansowania ujęta już w zrealizowanym P&L
+    #[serde(default)]
+    pub realized_costs: f64,
+
+    /// Czas ostatniego rozliczenia finansowania (Unix timestamp)
+    #[serde(default)]
+    pub last_funding_at: Option<i64>,
+
     /// Cena likwidacji
     pub liquidation_price: f64,
 
@@ -122,6 +168,9 @@ pub struct RiskAssessment {
 #[async_trait]
 pub trait RiskManagerTrait: Send + Sync {
     /// Ocenia ryzyko dla sygnału
+    ///
+    /// Ocena nie zmienia stanu managera - portfel i notowania aktualizuje
+    /// silnik tradingowy.
     async fn evaluate_risk(&self, signal: &Signal, portfolio: &Portfolio) -> RiskAssessment;
 
     /// Sprawdza limity pozycji
@@ -143,35 +192,102 @@ impl Portfolio {
         Self {
             balance: initial_balance,
             equity: initial_balance,
+            unrealized_pnl: 0.0,
             margin_used: 0.0,
+            maintenance_margin: 0.0,
             margin_available: initial_balance,
+            margin_ratio: 0.0,
             daily_pnl: 0.0,
             open_positions: Vec::new(),
             last_updated: chrono::Utc::now().timestamp(),
+            margin_model: MarginModel::default(),
         }
     }
 
+    /// Ustawia model marży (tryb, progi, opłata likwidacyjna)
+    pub fn with_margin_model(mut self, margin_model: MarginModel) -> Self {
+        self.margin_model = margin_model;
+        self.update();
+        self
+    }
+
     /// Aktualizuje portfel
+    ///
+    /// W trybie cross niezrealizowany P&L wszystkich pozycji zwiększa lub
+    /// zmniejsza dostępną marżę; w trybie izolowanym liczą się tylko straty.
     pub fn update(&mut self) {
         // Obliczenie unrealized PnL
         let unrealized_pnl: f64 = self.open_positions.iter().map(|pos| pos.pnl).sum();
+        self.unrealized_pnl = unrealized_pnl;
 
         // Aktualizacja equity
         self.equity = self.balance + unrealized_pnl;
 
         // Obliczenie używanej marży
-        self.margin_used = self
+        self.margin_used = self.open_positions.iter().map(|pos| pos.size).sum();
+
+        // Marża utrzymaniowa (od bieżącej wartości nominalnej) i ceny likwidacji
+        let maintenance: Vec<f64> = self
             .open_positions
             .iter()
-            .map(|pos| pos.size / pos.leverage as f64)
-            .sum();
+            .map(|pos| self.margin_model.maintenance_margin(pos.notional()))
+            .collect();
+        self.maintenance_margin = maintenance.iter().sum();
+
+        for (pos, own_maintenance) in self.open_positions.iter_mut().zip(&maintenance) {
+            pos.liquidation_price = if self.margin_model.is_cross() {
+                // Pozycję zabezpiecza saldo z P&L i marżą utrzymaniową pozostałych
+                let collateral = self.balance + (unrealized_pnl - pos.pnl)
+                    - (self.maintenance_margin - own_maintenance);
+                self.margin_model.liquidation_price(
+                    &pos.side,
+                    pos.entry_price,
+                    pos.size * pos.leverage as f64,
+                    collateral,
+                )
+            } else {
+                self.margin_model.isolated_liquidation_price(
+                    &pos.side,
+                    pos.entry_price,
+                    pos.leverage,
+                    pos.size * pos.leverage as f64,
+                )
+            };
+        }
 
         // Aktualizacja dostępnej marży
-        self.margin_available = self.equity - self.margin_used;
+        self.margin_available = if self.margin_model.is_cross() {
+            self.equity - self.margin_used
+        } else {
+            let unrealized_losses: f64 =
+                self.open_positions.iter().map(|pos| pos.pnl.min(0.0)).sum();
+            self.balance + unrealized_losses - self.margin_used
+        };
+
+        // Wskaźnik marży konta
+        self.margin_ratio = if self.maintenance_margin <= 0.0 {
+            0.0
+        } else if self.equity <= 0.0 {
+            1.0
+        } else {
+            (self.maintenance_margin / self.equity).min(1.0)
+        };
 
         self.last_updated = chrono::Utc::now().timestamp();
     }
 
+    /// Wycenia pozycje tokena i przelicza portfel (w tym wskaźnik marży)
+    pub fn update_price(&mut self, token: &str, price: f64) {
+        for position in self
+            .open_positions
+            .iter_mut()
+            .filter(|position| position.token == token)
+        {
+            position.update_price(price);
+        }
+        self.update();
+    }
+
     /// Dodaje pozycję do portfela
     pub fn add_position(&mut self, position: Position) {
         self.open_positions.push(position);
@@ -201,7 +317,7 @@ impl Portfolio {
 
     /// Sprawdza czy portfel jest w dobrej kondycji
     pub fn is_healthy(&self) -> bool {
-        self.equity > 0.0 && self.margin_available >= 0.0
+        self.equity > 0.0 && self.equity > self.maintenance_margin && self.margin_available >= 0.0
     }
 
     /// Zwraca współczynnik wykorzystania marży
@@ -214,9 +330,15 @@ impl Portfolio {
 }
 
 impl Position {
-    /// Tworzy nową pozycję
+    /// Tworzy nową pozycję (cena likwidacji według domyślnego modelu marży)
     pub fn new(token: String, side: TradeSide, size: f64, leverage: u8, entry_price: f64) -> Self {
-        let liquidation_price = Self::calculate_liquidation_price(&side, entry_price, leverage);
+        // Wartość nominalna przy wejściu: marża razy dźwignia
+        let liquidation_price = MarginModel::default().isolated_liquidation_price(
+            &side,
+            entry_price,
+            leverage,
+            size * leverage as f64,
+        );
 
         Self {
             id: uuid::Uuid::new_v4().to_string(),
@@ -227,6 +349,10 @@ impl Position {
             entry_price,
             current_price: entry_price,
             pnl: 0.0,
+            fees_paid: 0.0,
+            funding_paid: 0.0,
+            realized_costs: 0.0,
+            last_funding_at: None,
             liquidation_price,
             status: PositionStatus::Open,
             opened_at: chrono::Utc::now().timestamp(),
@@ -240,8 +366,13 @@ impl Position {
         self.pnl = self.calculate_pnl();
     }
 
-    /// Oblicza P&L pozycji
+    /// Oblicza P&L pozycji netto (po opłatach i finansowaniu)
     pub fn calculate_pnl(&self) -> f64 {
+        self.price_pnl() - self.open_costs()
+    }
+
+    /// P&L wynikający wyłącznie z ruchu ceny
+    pub fn price_pnl(&self) -> f64 {
         let price_change = match self.side {
             TradeSide::Long => self.current_price - self.entry_price,
             TradeSide::Short => self.entry_price - self.current_price,
@@ -250,14 +381,108 @@ impl Position {
         (price_change / self.entry_price) * self.size * self.leverage as f64
     }
 
-    /// Oblicza cenę likwidacji
-    pub fn calculate_liquidation_price(side: &TradeSide, entry_price: f64, leverage: u8) -> f64 {
-        let liquidation_threshold = 0.95; // 95% marży
+    /// Opłaty i finansowanie nieujęte jeszcze w zrealizowanym P&L
+    pub fn open_costs(&self) -> f64 {
+        self.fees_paid + self.funding_paid - self.realized_costs
+    }
+
+    /// Wartość nominalna pozycji po bieżącej cenie (jak w `calculate_pnl`)
+    pub fn notional(&self) -> f64 {
+        if self.entry_price <= 0.0 {
+            return 0.0;
+        }
+        self.size * self.leverage as f64 * self.current_price / self.entry_price
+    }
+
+    /// Dolicza opłatę transakcyjną
+    pub fn add_fee(&mut self, fee: f64) {
+        self.fees_paid += fee;
+        self.pnl = self.calculate_pnl();
+    }
 
-        match side {
-            TradeSide::Long => entry_price * (1.0 - liquidation_threshold / leverage as f64),
-            TradeSide::Short => entry_price * (1.0 + liquidation_threshold / leverage as f64),
+    /// Nalicza finansowanie po stawce `rate` od bieżącej wartości nominalnej
+    ///
+    /// Przy dodatniej stawce Long płaci, a Short otrzymuje. Zwraca płatność
+    /// (dodatnia = zapłacona).
+    pub fn apply_funding(&mut self, rate: f64, timestamp: i64) -> f64 {
+        let payment = match self.side {
+            TradeSide::Long => self.notional() * rate,
+            TradeSide::Short => -self.notional() * rate,
+        };
+        self.record_funding(payment, timestamp);
+        payment
+    }
+
+    /// Zapisuje rozliczoną płatność finansowania (dodatnia = zapłacona)
+    pub fn record_funding(&mut self, payment: f64, timestamp: i64) {
+        self.funding_paid += payment;
+        self.last_funding_at = Some(timestamp);
+        self.pnl = self.calculate_pnl();
+    }
+
+    /// Momenty rozliczeń finansowania od ostatniego rozliczenia do `now`
+    ///
+    /// Rozliczenia wypadają co `interval` sekund od początku epoki
+    /// (np. 00:00, 08:00, 16:00 UTC); pozycja musi być otwarta w chwili
+    /// rozliczenia.
+    pub fn funding_times_due(&self, now: i64, interval: i64) -> Vec<i64> {
+        if interval <= 0 {
+            return Vec::new();
         }
+
+        let since = self.last_funding_at.unwrap_or(self.opened_at);
+        let until = self.closed_at.unwrap_or(now).min(now);
+        let mut next = (since.div_euclid(interval) + 1) * interval;
+        let mut times = Vec::new();
+        while next <= until {
+            times.push(next);
+            next += interval;
+        }
+        times
+    }
+
+    /// Realizuje P&L zamykanej części pozycji po cenie `exit_price`
+    ///
+    /// Zwraca P&L ruchu ceny tej części pomniejszony o proporcjonalną część
+    /// nierozliczonych kosztów i o opłatę zamknięcia `fee`. Rozmiar pozycji
+    /// zmniejsza wywołujący.
+    pub fn realize(&mut self, closed_size: f64, exit_price: f64, fee: f64) -> f64 {
+        self.current_price = exit_price;
+        let share = if self.size > 0.0 {
+            (closed_size / self.size).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+
+        let costs = self.open_costs() * share;
+        let realized = self.price_pnl() * share - costs - fee;
+
+        self.fees_paid += fee;
+        self.realized_costs += costs + fee;
+        self.pnl = self.calculate_pnl();
+        realized
+    }
+
+    /// Przelicza cenę likwidacji marży izolowanej według modelu marży
+    ///
+    /// W trybie cross cenę likwidacji wyznacza `Portfolio::update`.
+    pub fn apply_margin_model(&mut self, model: &MarginModel) {
+        self.liquidation_price = model.isolated_liquidation_price(
+            &self.side,
+            self.entry_price,
+            self.leverage,
+            self.size * self.leverage as f64,
+        );
+    }
+
+    /// Oblicza cenę likwidacji według domyślnego modelu marży
+    ///
+    /// Bez wielkości pozycji liczy dla jednej jednostki, czyli najniższego progu.
+    #[deprecated(
+        note = "użyj MarginModel::isolated_liquidation_price z wartością nominalną pozycji"
+    )]
+    pub fn calculate_liquidation_price(side: &TradeSide, entry_price: f64, leverage: u8) -> f64 {
+        MarginModel::default().isolated_liquidation_price(side, entry_price, leverage, entry_price)
     }
 
     /// Sprawdza czy pozycja jest bliska likwidacji
@@ -278,10 +503,12 @@ impl Position {
 
     /// Zwraca czas trwania pozycji w sekundach
     pub fn duration_seconds(&self) -> i64 {
-        let end_time = self
-            .closed_at
-            .unwrap_or_else(|| chrono::Utc::now().timestamp());
-        end_time - self.opened_at
+        self.duration_seconds_at(chrono::Utc::now().timestamp())
+    }
+
+    /// Czas trwania pozycji względem podanego czasu (np. zegara symulacji)
+    pub fn duration_seconds_at(&self, now: i64) -> i64 {
+        self.closed_at.unwrap_or(now) - self.opened_at
     }
 }
 
@@ -310,8 +537,8 @@ mod tests {
         portfolio.add_position(position);
 
         assert_eq!(portfolio.open_positions.len(), 1);
-        assert_eq!(portfolio.margin_used, 20.0); // 100 / 5 = 20
-        assert_eq!(portfolio.margin_available, 980.0); // 1000 - 20
+        assert_eq!(portfolio.margin_used, 100.0); // marża pozycji (500 USD nominalnie przy 5x)
+        assert_eq!(portfolio.margin_available, 900.0); // 1000 - 100
     }
 
     #[test]
@@ -342,9 +569,58 @@ mod tests {
 
         portfolio.add_position(position);
 
-        // Margin used = 200 / 10 = 20
-        // Margin utilization = 20 / 1000 = 0.02 (2%)
-        assert!((portfolio.margin_utilization() - 0.02).abs() < 0.001);
+        // Margin used = 200 (2000 USD notional at 10x)
+        // Margin utilization = 200 / 1000 = 0.2 (20%)
+        assert!((portfolio.margin_utilization() - 0.2).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_cross_margin_counts_unrealized_profit() {
+        let cross_model = MarginModel {
+            mode: MarginMode::Cross,
+            ..MarginModel::default()
+        };
+        let mut isolated = Portfolio::new(1000.0);
+        let mut cross = Portfolio::new(1000.0).with_margin_model(cross_model);
+
+        for portfolio in [&mut isolated, &mut cross] {
+            portfolio.add_position(Position::new(
+                "A".to_string(),
+                TradeSide::Long,
+                200.0,
+                10,
+                1.0,
+            ));
+            portfolio.add_position(Position::new(
+                "B".to_string(),
+                TradeSide::Short,
+                200.0,
+                10,
+                1.0,
+            ));
+            portfolio.update_price("A", 1.02);
+            portfolio.update_price("B", 1.01);
+        }
+
+        // P&L z `price_pnl`: A +2% x 200 x 10 = +40, B +1% przeciw short => -20
+        let [a, b] = [&cross.open_positions[0], &cross.open_positions[1]];
+        assert!((a.price_pnl() - 40.0).abs() < 1e-9);
+        assert!((b.price_pnl() + 20.0).abs() < 1e-9);
+
+        assert!((cross.unrealized_pnl - 20.0).abs() < 1e-9);
+        assert!((cross.equity - 1020.0).abs() < 1e-9);
+        // Marża 2 x 200 USD; izolowany tryb nie liczy niezrealizowanego zysku
+        assert!((cross.margin_available - 620.0).abs() < 1e-9);
+        assert!((isolated.margin_available - 580.0).abs() < 1e-9);
+
+        // Marża utrzymaniowa 0.5% wartości nominalnej (2040 + 2020 USD) względem equity
+        let maintenance = 0.005 * (a.notional() + b.notional());
+        assert!((maintenance - 20.3).abs() < 1e-9);
+        assert!((cross.maintenance_margin - maintenance).abs() < 1e-9);
+        assert!((cross.margin_ratio - maintenance / 1020.0).abs() < 1e-9);
+        cross.update_price("A", 0.4);
+        assert_eq!(cross.margin_ratio, 1.0);
+        assert!(!cross.is_healthy());
     }
 
     #[test]
@@ -386,20 +662,41 @@ mod tests {
 
     #[test]
     fn test_liquidation_price_calculation_long() {
-        let liquidation_price = Position::calculate_liquidation_price(&TradeSide::Long, 0.001, 5);
+        let position = Position::new("TEST".to_string(), TradeSide::Long, 100.0, 5, 0.001);
 
-        // For long position with 5x leverage, liquidation at ~81% of entry price
-        // 0.001 * (1 - 0.95/5) = 0.001 * 0.81 = 0.00081
-        assert!((liquidation_price - 0.00081).abs() < 0.00001);
+        // Long 5x, pierwszy próg: marża utrzymaniowa 0.5% + opłata likwidacyjna 0.5%
+        // 0.001 * (1 - 1/5) / (1 - 0.01) = 0.000808
+        assert!((position.liquidation_price - 0.000808).abs() < 0.000001);
     }
 
     #[test]
     fn test_liquidation_price_calculation_short() {
-        let liquidation_price = Position::calculate_liquidation_price(&TradeSide::Short, 0.001, 5);
+        let position = Position::new("TEST".to_string(), TradeSide::Short, 100.0, 5, 0.001);
 
-        // For short position with 5x leverage, liquidation at ~119% of entry price
-        // 0.001 * (1 + 0.95/5) = 0.001 * 1.19 = 0.00119
-        assert!((liquidation_price - 0.00119).abs() < 0.00001);
+        // Short 5x: 0.001 * (1 + 1/5) / (1 + 0.01) = 0.001188
+        assert!((position.liquidation_price - 0.001188).abs() < 0.000001);
+    }
+
+    #[test]
+    fn test_liquidation_price_uses_margin_tiers() {
+        let mut position = Position::new("TEST".to_string(), TradeSide::Long, 100.0, 10, 1.0);
+        let small = position.liquidation_price;
+
+        // Ta sama dźwignia przy marży 30k USD, czyli 300k USD wartości nominalnej
+        // (próg 10%), likwiduje znacznie wcześniej
+        position.size = 30_000.0;
+        position.apply_margin_model(&MarginModel::default());
+        assert!((small - 0.9091).abs() < 0.0001);
+        assert!(position.liquidation_price > small + 0.03);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_liquidation_price_matches_margin_model() {
+        let position = Position::new("TEST".to_string(), TradeSide::Short, 100.0, 5, 0.001);
+
+        let price = Position::calculate_liquidation_price(&TradeSide::Short, 0.001, 5);
+        assert!((price - position.liquidation_price).abs() < 1e-12);
     }
 
     #[test]
@@ -407,12 +704,55 @@ mod tests {
         let mut position = Position::new("TEST".to_string(), TradeSide::Long, 100.0, 5, 0.001);
 
         // Set price close to liquidation
-        position.update_price(0.00082); // Just above liquidation price
+        position.update_price(0.000815); // Just above liquidation price
 
         assert!(position.is_near_liquidation(0.01)); // 1% buffer
         assert!(!position.is_near_liquidation(0.001)); // 0.1% buffer
     }
 
+    #[test]
+    fn test_cross_margin_portfolio_liquidation() {
+        let mut isolated = Portfolio::new(1000.0);
+        isolated.add_position(Position::new(
+            "TEST".to_string(),
+            TradeSide::Long,
+            200.0,
+            20,
+            1.0,
+        ));
+
+        let mut cross = Portfolio::new(1000.0).with_margin_model(MarginModel {
+            mode: MarginMode::Cross,
+            ..MarginModel::default()
+        });
+        cross.add_position(Position::new(
+            "TEST".to_string(),
+            TradeSide::Long,
+            200.0,
+            20,
+            1.0,
+        ));
+
+        // Marża 200 USD przy 4000 USD nominalnie, marża utrzymaniowa 0.5% = 20 USD
+        assert!((cross.maintenance_margin - 20.0).abs() < 1e-9);
+
+        // W cenie likwidacji zabezpieczenie z P&L (`price_pnl`) spada do marży
+        // utrzymaniowej i opłaty likwidacyjnej (0.5% + 0.5% bieżącej wartości nominalnej)
+        let remaining = |portfolio: &Portfolio, collateral: f64| {
+            let mut position = portfolio.open_positions[0].clone();
+            position.update_price(position.liquidation_price);
+            collateral + position.price_pnl() - 0.01 * position.notional()
+        };
+
+        // Marża izolowana 200 USD: likwidacja po ~4% spadku
+        assert!((isolated.open_positions[0].liquidation_price - 0.9596).abs() < 0.0001);
+        assert!(remaining(&isolated, 200.0).abs() < 1e-6);
+
+        // Całe saldo 1000 USD jako zabezpieczenie: 3000 / 3960 = ~0.7576
+        assert!((cross.open_positions[0].liquidation_price - 0.7576).abs() < 0.0001);
+        assert!(remaining(&cross, 1000.0).abs() < 1e-6);
+    }
+
     #[test]
     fn test_position_close() {
         let mut position = Position::new("TEST".to_string(), TradeSide::Long, 100.0, 5, 0.001);
@@ -426,6 +766,60 @@ mod tests {
         assert!(position.closed_at.is_some());
     }
 
+    #[test]
+    fn test_position_funding_and_fees_reduce_pnl() {
+        let mut long = Position::new("TEST".to_string(), TradeSide::Long, 100.0, 5, 1.0);
+        let mut short = Position::new("TEST".to_string(), TradeSide::Short, 100.0, 5, 1.0);
+
+        // Przy dodatniej stawce Long płaci, Short otrzymuje
+        assert!((long.apply_funding(0.001, 1_000) - 0.5).abs() < 1e-9);
+        assert!((short.apply_funding(0.001, 1_000) + 0.5).abs() < 1e-9);
+        assert_eq!(long.last_funding_at, Some(1_000));
+
+        long.add_fee(0.1);
+        long.update_price(1.02);
+        assert!((long.price_pnl() - 10.0).abs() < 1e-9);
+        assert!((long.calculate_pnl() - 9.4).abs() < 1e-9);
+        assert!((short.calculate_pnl() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_position_realize_splits_costs_proportionally() {
+        let mut position = Position::new("TEST".to_string(), TradeSide::Long, 100.0, 5, 1.0);
+        position.add_fee(0.2);
+        position.record_funding(0.4, 1_000);
+
+        // Połowa pozycji: 5.0 z ruchu ceny - 0.3 kosztów - 0.1 opłaty
+        let realized = position.realize(50.0, 1.02, 0.1);
+        assert!((realized - 4.6).abs() < 1e-9);
+        assert!((position.open_costs() - 0.3).abs() < 1e-9);
+
+        position.size = 50.0;
+        let realized = position.realize(50.0, 1.02, 0.1);
+        assert!((realized - 4.6).abs() < 1e-9);
+        assert!(position.open_costs().abs() < 1e-9);
+        assert!((position.fees_paid - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_funding_times_due() {
+        let mut position = Position::new("TEST".to_string(), TradeSide::Long, 100.0, 5, 1.0);
+        position.opened_at = 3_600;
+
+        assert!(position.funding_times_due(28_799, 28_800).is_empty());
+        assert_eq!(
+            position.funding_times_due(60_000, 28_800),
+            vec![28_800, 57_600]
+        );
+
+        position.record_funding(0.0, 28_800);
+        assert_eq!(position.funding_times_due(60_000, 28_800), vec![57_600]);
+        assert!(position.funding_times_due(60_000, 0).is_empty());
+
+        position.closed_at = Some(50_000);
+        assert!(position.funding_times_due(60_000, 28_800).is_empty());
+    }
+
     #[test]
     fn test_position_duration() {
         let position = Position::new("TEST".to_string(), TradeSide::Long, 100.0, 5, 0.001);
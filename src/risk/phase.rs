@@ -0,0 +1,183 @@
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::config::{PhaseConfig, StrategyConfig};
+use crate::monitoring::{AlertLevel, SystemAlert};
+
+const COMPONENT: &str = "strategy_phase";
+
+/// Zmiana aktywnej fazy strategii
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PhaseChange {
+    pub from: String,
+    pub to: String,
+
+    /// Equity portfela, przy którym nastąpiła zmiana
+    pub equity: f64,
+
+    /// Czy nowa faza jest wyższa (większy portfel)
+    pub promoted: bool,
+}
+
+impl PhaseChange {
+    /// Alert informujący o zmianie fazy
+    pub fn alert(&self) -> SystemAlert {
+        let (level, direction) = if self.promoted {
+            (AlertLevel::Medium, "promoted")
+        } else {
+            (AlertLevel::High, "demoted")
+        };
+
+        SystemAlert::new(
+            level,
+            format!("Strategy phase: {}", self.to),
+            format!(
+                "Strategy {} from {} to {} at equity {:.2} USD",
+                direction, self.from, self.to, self.equity
+            ),
+            COMPONENT.to_string(),
+        )
+    }
+}
+
+/// Wybór aktywnej fazy strategii według equity portfela
+///
+/// Faza obowiązuje od swojego `min_equity` do `min_equity` kolejnej fazy.
+/// Przejście wymaga przekroczenia granicy o `hysteresis_percent`, żeby
+/// equity oscylujące przy granicy nie przełączało parametrów co wycenę.
+#[derive(Debug)]
+pub struct PhaseTracker {
+    phases: Vec<PhaseConfig>,
+    hysteresis: f64,
+    current: AtomicUsize,
+}
+
+impl PhaseTracker {
+    /// Tworzy tracker z fazą odpowiadającą początkowemu equity
+    ///
+    /// Przy wyłączonych profilach tracker nie ma faz.
+    pub fn new(config: &StrategyConfig, equity: f64) -> Self {
+        let phases = if config.enabled {
+            config.phases.clone()
+        } else {
+            Vec::new()
+        };
+
+        let tracker = Self {
+            phases,
+            hysteresis: config.hysteresis_percent.to_f64().unwrap_or(0.0),
+            current: AtomicUsize::new(0),
+        };
+        tracker
+            .current
+            .store(tracker.phase_index(equity), Ordering::Relaxed);
+        tracker
+    }
+
+    /// Aktywna faza (`None` przy wyłączonych profilach)
+    pub fn current(&self) -> Option<&PhaseConfig> {
+        self.phases.get(self.current.load(Ordering::Relaxed))
+    }
+
+    /// Aktualizuje fazę dla nowego equity; zwraca zmianę, jeśli nastąpiła
+    pub fn update(&self, equity: f64) -> Option<PhaseChange> {
+        let from = self.current.load(Ordering::Relaxed);
+        let mut to = from;
+
+        while to + 1 < self.phases.len()
+            && equity >= self.boundary(to + 1) * (1.0 + self.hysteresis)
+        {
+            to += 1;
+        }
+        if to == from {
+            while to > 0 && equity < self.boundary(to) * (1.0 - self.hysteresis) {
+                to -= 1;
+            }
+        }
+
+        if to == from {
+            return None;
+        }
+        self.current.store(to, Ordering::Relaxed);
+
+        Some(PhaseChange {
+            from: self.phases[from].name.clone(),
+            to: self.phases[to].name.clone(),
+            equity,
+            promoted: to > from,
+        })
+    }
+
+    /// Faza dla equity bez histerezy
+    fn phase_index(&self, equity: f64) -> usize {
+        (1..self.phases.len())
+            .rev()
+            .find(|&i| equity >= self.boundary(i))
+            .unwrap_or(0)
+    }
+
+    fn boundary(&self, index: usize) -> f64 {
+        self.phases[index].min_equity.to_f64().unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(equity: f64) -> PhaseTracker {
+        PhaseTracker::new(
+            &StrategyConfig {
+                enabled: true,
+                ..StrategyConfig::default()
+            },
+            equity,
+        )
+    }
+
+    fn phase(tracker: &PhaseTracker) -> &str {
+        &tracker.current().unwrap().name
+    }
+
+    #[test]
+    fn test_initial_phase_follows_equity() {
+        assert_eq!(phase(&tracker(50.0)), "survival");
+        assert_eq!(phase(&tracker(100.0)), "building");
+        assert_eq!(phase(&tracker(5_000.0)), "acceleration");
+
+        let disabled = PhaseTracker::new(&StrategyConfig::default(), 500.0);
+        assert!(disabled.current().is_none());
+        assert!(disabled.update(50.0).is_none());
+    }
+
+    #[test]
+    fn test_phase_changes_with_hysteresis() {
+        let tracker = tracker(90.0);
+
+        // Granica 100 USD, histereza 5%: awans dopiero od 105 USD
+        assert!(tracker.update(102.0).is_none());
+        let change = tracker.update(106.0).unwrap();
+        assert_eq!(
+            (change.from.as_str(), change.to.as_str()),
+            ("survival", "building")
+        );
+        assert!(change.promoted);
+
+        // Spadek poniżej granicy, ale powyżej 95 USD - faza bez zmian
+        assert!(tracker.update(97.0).is_none());
+        assert_eq!(phase(&tracker), "building");
+
+        let change = tracker.update(94.0).unwrap();
+        assert_eq!(change.to, "survival");
+        assert!(!change.promoted);
+        assert_eq!(change.alert().level, AlertLevel::High);
+    }
+
+    #[test]
+    fn test_phase_skips_several_boundaries() {
+        let tracker = tracker(50.0);
+        assert_eq!(tracker.update(1_000.0).unwrap().to, "acceleration");
+        assert_eq!(tracker.update(60.0).unwrap().to, "survival");
+    }
+}
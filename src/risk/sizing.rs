@@ -0,0 +1,271 @@
+use rust_decimal::prelude::ToPrimitive;
+use std::collections::HashMap;
+
+use crate::config::{SizingConfig, SizingPolicyConfig};
+use crate::trading::TradingStats;
+
+/// Metoda wyznaczania rozmiaru pozycji
+#[derive(Debug, Clone, PartialEq)]
+pub enum SizingMethod {
+    /// Stały ułamek equity
+    FixedFractional,
+
+    /// Docelowa zmienność: ruch o `atr_multiple` ATR kosztuje `risk_per_trade` equity
+    Atr {
+        risk_per_trade: f64,
+        atr_multiple: f64,
+        periods: usize,
+    },
+
+    /// Ułamkowe kryterium Kelly'ego ze statystyk zamkniętych transakcji
+    Kelly {
+        kelly_fraction: f64,
+        min_trades: u64,
+    },
+}
+
+/// Polityka rozmiaru pozycji wraz z ułamkiem zastępczym
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizingPolicy {
+    pub method: SizingMethod,
+
+    /// Ułamek equity dla `FixedFractional` oraz gdy brakuje danych (ATR, historia transakcji)
+    pub fraction: f64,
+}
+
+/// Dane wejściowe do wyznaczenia rozmiaru pozycji
+#[derive(Debug, Clone, Copy)]
+pub struct SizingInputs<'a> {
+    pub equity: f64,
+    pub leverage: u8,
+
+    /// ATR jako ułamek ceny (jeśli znany)
+    pub atr_percent: Option<f64>,
+
+    /// Historia zamkniętych transakcji
+    pub stats: &'a TradingStats,
+}
+
+/// Wyznaczony rozmiar pozycji (marża, przed limitami managera ryzyka)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizingDecision {
+    pub size: f64,
+
+    /// Opis podstawy wyliczenia (do uzasadnienia oceny ryzyka)
+    pub basis: String,
+}
+
+impl SizingPolicy {
+    /// Tworzy politykę z konfiguracji (nieznana nazwa - stały ułamek)
+    pub fn from_config(config: &SizingPolicyConfig) -> Self {
+        let method = match config.policy.as_str() {
+            "atr" => SizingMethod::Atr {
+                risk_per_trade: to_f64(config.risk_per_trade_percent),
+                atr_multiple: to_f64(config.atr_multiple),
+                periods: config.atr_periods,
+            },
+            "kelly" => SizingMethod::Kelly {
+                kelly_fraction: to_f64(config.kelly_fraction),
+                min_trades: config.min_trades,
+            },
+            _ => SizingMethod::FixedFractional,
+        };
+
+        Self {
+            method,
+            fraction: to_f64(config.fraction),
+        }
+    }
+
+    /// Liczba okresów ATR potrzebna polityce (tylko `Atr`)
+    pub fn atr_periods(&self) -> Option<usize> {
+        match self.method {
+            SizingMethod::Atr { periods, .. } => Some(periods),
+            _ => None,
+        }
+    }
+
+    /// Wyznacza rozmiar pozycji
+    ///
+    /// Wynik nie uwzględnia `max_position_size_percent` ani dostępnej marży -
+    /// te limity nakłada `RiskManager`.
+    pub fn size(&self, inputs: &SizingInputs) -> SizingDecision {
+        let equity = inputs.equity.max(0.0);
+        let fallback = |reason: &str| SizingDecision {
+            size: equity * self.fraction,
+            basis: format!("fixed fraction {:.2}% ({})", self.fraction * 100.0, reason),
+        };
+
+        match &self.method {
+            SizingMethod::FixedFractional => SizingDecision {
+                size: equity * self.fraction,
+                basis: format!("fixed fraction {:.2}%", self.fraction * 100.0),
+            },
+            SizingMethod::Atr {
+                risk_per_trade,
+                atr_multiple,
+                ..
+            } => match inputs.atr_percent {
+                // Strata na stopie ATR to wartość nominalna razy ruch ceny
+                Some(atr) if atr > 0.0 => SizingDecision {
+                    size: equity * risk_per_trade
+                        / (atr_multiple * atr)
+                        / inputs.leverage.max(1) as f64,
+                    basis: format!(
+                        "ATR {:.3}%, risking {:.2}% at {}x ATR",
+                        atr * 100.0,
+                        risk_per_trade * 100.0,
+                        atr_multiple
+                    ),
+                },
+                _ => fallback("ATR unavailable"),
+            },
+            SizingMethod::Kelly {
+                kelly_fraction,
+                min_trades,
+            } => {
+                let closed = inputs.stats.closed_trades();
+                match inputs.stats.kelly_fraction() {
+                    Some(kelly) if closed >= *min_trades => {
+                        // Kelly określa część equity narażoną jako marża
+                        SizingDecision {
+                            size: equity * kelly_fraction * kelly.max(0.0),
+                            basis: format!(
+                                "Kelly {:.2}% x {:.2} over {} trades",
+                                kelly * 100.0,
+                                kelly_fraction,
+                                closed
+                            ),
+                        }
+                    }
+                    _ => fallback(&format!("{} of {} trades for Kelly", closed, min_trades)),
+                }
+            }
+        }
+    }
+}
+
+/// Wybór polityki rozmiaru pozycji według strategii (źródła sygnału)
+#[derive(Debug, Clone)]
+pub struct PositionSizer {
+    default: SizingPolicy,
+    strategies: HashMap<String, SizingPolicy>,
+}
+
+impl PositionSizer {
+    /// Tworzy sizer z konfiguracji `trading.sizing`
+    pub fn from_config(config: &SizingConfig) -> Self {
+        Self {
+            default: SizingPolicy::from_config(&config.default),
+            strategies: config
+                .strategies
+                .iter()
+                .map(|(strategy, policy)| (strategy.clone(), SizingPolicy::from_config(policy)))
+                .collect(),
+        }
+    }
+
+    /// Polityka dla strategii (lub domyślna)
+    pub fn policy_for(&self, strategy: &str) -> &SizingPolicy {
+        self.strategies.get(strategy).unwrap_or(&self.default)
+    }
+}
+
+fn to_f64(value: rust_decimal::Decimal) -> f64 {
+    value.to_f64().unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(name: &str) -> SizingPolicy {
+        SizingPolicy::from_config(&SizingPolicyConfig {
+            policy: name.to_string(),
+            ..SizingPolicyConfig::default()
+        })
+    }
+
+    fn stats(wins: u64, losses: u64) -> TradingStats {
+        let mut stats = TradingStats::default();
+        for _ in 0..wins {
+            stats.record_outcome(20.0);
+        }
+        for _ in 0..losses {
+            stats.record_outcome(-10.0);
+        }
+        stats
+    }
+
+    fn inputs(atr_percent: Option<f64>, stats: &TradingStats) -> SizingInputs<'_> {
+        SizingInputs {
+            equity: 1000.0,
+            leverage: 5,
+            atr_percent,
+            stats,
+        }
+    }
+
+    #[test]
+    fn test_fixed_fractional_ignores_volatility() {
+        let stats = TradingStats::default();
+        let decision = policy("fixed_fractional").size(&inputs(Some(0.05), &stats));
+        assert!((decision.size - 330.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_atr_targets_risk_per_trade() {
+        let stats = TradingStats::default();
+        let atr = policy("atr");
+
+        // 1% z 1000 USD przy ruchu 2 x 0.5% = 1000 USD nominalnie, 200 USD marży przy 5x
+        let calm = atr.size(&inputs(Some(0.005), &stats));
+        assert!((calm.size - 200.0).abs() < 1e-9);
+
+        // Dwukrotnie większa zmienność - połowa rozmiaru
+        let volatile = atr.size(&inputs(Some(0.01), &stats));
+        assert!((volatile.size - 100.0).abs() < 1e-9);
+
+        // Bez historii cen - ułamek zastępczy
+        let unknown = atr.size(&inputs(None, &stats));
+        assert!((unknown.size - 330.0).abs() < 1e-9);
+        assert!(unknown.basis.contains("ATR unavailable"));
+    }
+
+    #[test]
+    fn test_kelly_uses_trade_history() {
+        let kelly = policy("kelly");
+
+        // Za mało transakcji - ułamek zastępczy
+        let short = stats(6, 4);
+        assert!((kelly.size(&inputs(None, &short)).size - 330.0).abs() < 1e-9);
+
+        // W = 0.6, R = 2: Kelly 40%, half Kelly 20% equity jako marża
+        let history = stats(12, 8);
+        let decision = kelly.size(&inputs(None, &history));
+        assert!((decision.size - 200.0).abs() < 1e-9);
+
+        // Brak przewagi - zerowy rozmiar
+        let negative = stats(4, 16);
+        assert_eq!(kelly.size(&inputs(None, &negative)).size, 0.0);
+    }
+
+    #[test]
+    fn test_policy_selected_per_strategy() {
+        let mut config = SizingConfig::default();
+        config.strategies.insert(
+            "tradingview".to_string(),
+            SizingPolicyConfig {
+                policy: "atr".to_string(),
+                ..SizingPolicyConfig::default()
+            },
+        );
+        let sizer = PositionSizer::from_config(&config);
+
+        assert_eq!(sizer.policy_for("tradingview").atr_periods(), Some(14));
+        assert_eq!(
+            sizer.policy_for("pump_fun").method,
+            SizingMethod::FixedFractional
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::config::TradeLimitsConfig;
+use crate::database::StateStore;
+
+/// Klucz stanu limitów transakcji w `risk_state`
+const STATE_KEY: &str = "trade_limits";
+
+const DAY_SECONDS: i64 = 24 * 3600;
+
+/// Powód blokady ponownego wejścia w token
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum TokenBlockReason {
+    /// Pozycja zamknięta stop-lossem
+    StopLoss,
+
+    /// Pozycja zamknięta przy likwidacji
+    Liquidation,
+}
+
+impl std::fmt::Display for TokenBlockReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenBlockReason::StopLoss => write!(f, "stop-loss cooldown"),
+            TokenBlockReason::Liquidation => write!(f, "liquidation ban"),
+        }
+    }
+}
+
+/// Blokada tokena do chwili `until`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TokenBlock {
+    pub reason: TokenBlockReason,
+
+    /// Timestamp końca blokady
+    pub until: i64,
+}
+
+/// Trwały stan limitów transakcji
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TradeLimitsState {
+    /// Numer dnia UTC, którego dotyczą liczniki
+    pub trading_day: i64,
+
+    /// Pozycje otwarte w dniu (wszystkie tokeny)
+    pub daily_trades: u32,
+
+    /// Pozycje otwarte w dniu według tokena
+    pub token_trades: HashMap<String, u32>,
+
+    /// Aktywne blokady ponownego wejścia według tokena
+    pub blocks: HashMap<String, TokenBlock>,
+}
+
+impl TradeLimitsState {
+    /// Zeruje liczniki po zmianie dnia i usuwa wygasłe blokady
+    fn roll(&mut self, now: i64) {
+        let today = now.div_euclid(DAY_SECONDS);
+        if self.trading_day != today {
+            self.trading_day = today;
+            self.daily_trades = 0;
+            self.token_trades.clear();
+        }
+        self.blocks.retain(|_, block| block.until > now);
+    }
+}
+
+/// Dzienne limity transakcji i blokady ponownego wejścia w token
+///
+/// Liczniki dotyczą dnia UTC. Stan jest zapisywany w bazie (jeśli podano
+/// `StateStore`), więc restart nie zeruje liczników ani nie zdejmuje blokad.
+pub struct TradeLimiter {
+    config: TradeLimitsConfig,
+    state: RwLock<TradeLimitsState>,
+    store: Option<StateStore>,
+}
+
+impl TradeLimiter {
+    /// Tworzy limiter bez trwałego stanu
+    pub fn new(config: &TradeLimitsConfig) -> Self {
+        Self {
+            config: config.clone(),
+            state: RwLock::new(TradeLimitsState::default()),
+            store: None,
+        }
+    }
+
+    /// Tworzy limiter z trwałym stanem i odtwarza zapisane liczniki
+    pub async fn with_store(config: &TradeLimitsConfig, store: StateStore) -> Result<Self> {
+        let mut limiter = Self::new(config);
+
+        if let Some(state) = store.load::<TradeLimitsState>(STATE_KEY).await? {
+            if !state.blocks.is_empty() {
+                info!(
+                    "Trade limits restored with {} blocked token(s)",
+                    state.blocks.len()
+                );
+            }
+            *limiter.state.write().await = state;
+        }
+
+        limiter.store = Some(store);
+        Ok(limiter)
+    }
+
+    /// Powód blokady nowej pozycji (`token` = `None` - tylko limit globalny)
+    pub async fn blocker(&self, token: Option<&str>, now: i64) -> Option<String> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let mut state = self.state.read().await.clone();
+        state.roll(now);
+
+        if state.daily_trades >= self.config.max_daily_trades {
+            return Some(format!(
+                "Daily trade limit reached ({}/{})",
+                state.daily_trades, self.config.max_daily_trades
+            ));
+        }
+
+        let token = normalize(token?);
+        if let Some(block) = state.blocks.get(&token) {
+            return Some(format!(
+                "{} blocked by {} for another {}s",
+                token,
+                block.reason,
+                block.until - now
+            ));
+        }
+
+        let trades = state.token_trades.get(&token).copied().unwrap_or(0);
+        (trades >= self.config.max_daily_trades_per_token).then(|| {
+            format!(
+                "Daily trade limit for {} reached ({}/{})",
+                token, trades, self.config.max_daily_trades_per_token
+            )
+        })
+    }
+
+    /// Liczba pozycji otwartych w dniu UTC chwili `now`
+    pub async fn daily_trades(&self, now: i64) -> u32 {
+        let state = self.state.read().await;
+        if state.trading_day == now.div_euclid(DAY_SECONDS) {
+            state.daily_trades
+        } else {
+            0
+        }
+    }
+
+    /// Zapisuje otwarcie pozycji na tokenie
+    pub async fn record_open(&self, token: &str, now: i64) -> Result<()> {
+        let mut state = self.state.write().await;
+        state.roll(now);
+        state.daily_trades += 1;
+        *state.token_trades.entry(normalize(token)).or_default() += 1;
+        self.persist(&state).await
+    }
+
+    /// Blokuje ponowne wejście w token (dłuższa z blokad pozostaje)
+    pub async fn record_block(
+        &self,
+        token: &str,
+        reason: TokenBlockReason,
+        now: i64,
+    ) -> Result<()> {
+        let duration = match reason {
+            TokenBlockReason::StopLoss => self.config.stop_loss_cooldown,
+            TokenBlockReason::Liquidation => self.config.liquidation_ban,
+        };
+        if duration == 0 {
+            return Ok(());
+        }
+
+        let until = now + duration as i64;
+        let token = normalize(token);
+        let mut state = self.state.write().await;
+        state.roll(now);
+        if state.blocks.get(&token).is_some_and(|b| b.until >= until) {
+            return Ok(());
+        }
+
+        info!("{} blocked until {} ({})", token, until, reason);
+        state.blocks.insert(token, TokenBlock { reason, until });
+        self.persist(&state).await
+    }
+
+    /// Stan liczników i blokad w chwili `now`
+    pub async fn state(&self, now: i64) -> TradeLimitsState {
+        let mut state = self.state.read().await.clone();
+        state.roll(now);
+        state
+    }
+
+    async fn persist(&self, state: &TradeLimitsState) -> Result<()> {
+        if let Some(store) = &self.store {
+            store.save(STATE_KEY, state).await?;
+        }
+        Ok(())
+    }
+}
+
+fn normalize(token: &str) -> String {
+    token.trim().to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOON: i64 = 19_000 * DAY_SECONDS + 12 * 3600;
+
+    fn limiter() -> TradeLimiter {
+        TradeLimiter::new(&TradeLimitsConfig {
+            max_daily_trades: 3,
+            max_daily_trades_per_token: 2,
+            ..TradeLimitsConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_daily_limits_per_token_and_global() {
+        let limiter = limiter();
+
+        limiter.record_open("bonk", NOON).await.unwrap();
+        assert!(limiter.blocker(Some("BONK"), NOON).await.is_none());
+        limiter.record_open("BONK", NOON + 60).await.unwrap();
+
+        let reason = limiter.blocker(Some("bonk"), NOON + 120).await.unwrap();
+        assert!(reason.contains("BONK"));
+        assert!(limiter.blocker(Some("WIF"), NOON + 120).await.is_none());
+
+        limiter.record_open("WIF", NOON + 180).await.unwrap();
+        assert!(limiter.blocker(None, NOON + 240).await.is_some());
+        assert_eq!(limiter.daily_trades(NOON + 240).await, 3);
+
+        // Nowy dzień UTC zeruje liczniki
+        let tomorrow = NOON + DAY_SECONDS;
+        assert!(limiter.blocker(Some("BONK"), tomorrow).await.is_none());
+        assert_eq!(limiter.daily_trades(tomorrow).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_and_liquidation_ban_expire() {
+        let limiter = limiter();
+
+        limiter
+            .record_block("WIF", TokenBlockReason::StopLoss, NOON)
+            .await
+            .unwrap();
+        let reason = limiter.blocker(Some("wif"), NOON + 60).await.unwrap();
+        assert!(reason.contains("stop-loss cooldown"));
+        assert!(limiter.blocker(Some("WIF"), NOON + 3600).await.is_none());
+
+        // Dłuższa blokada po likwidacji nie jest skracana przez cooldown
+        limiter
+            .record_block("PEPE", TokenBlockReason::Liquidation, NOON)
+            .await
+            .unwrap();
+        limiter
+            .record_block("PEPE", TokenBlockReason::StopLoss, NOON + 60)
+            .await
+            .unwrap();
+        let state = limiter.state(NOON + 7200).await;
+        assert_eq!(
+            state.blocks["PEPE"],
+            TokenBlock {
+                reason: TokenBlockReason::Liquidation,
+                until: NOON + DAY_SECONDS,
+            }
+        );
+        assert!(!state.blocks.contains_key("WIF"));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_limits_never_block() {
+        let limiter = TradeLimiter::new(&TradeLimitsConfig {
+            enabled: false,
+            max_daily_trades: 1,
+            max_daily_trades_per_token: 1,
+            ..TradeLimitsConfig::default()
+        });
+
+        limiter.record_open("BONK", NOON).await.unwrap();
+        limiter
+            .record_block("BONK", TokenBlockReason::Liquidation, NOON)
+            .await
+            .unwrap();
+        assert!(limiter.blocker(Some("BONK"), NOON).await.is_none());
+    }
+}
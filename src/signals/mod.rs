@@ -2,16 +2,27 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::risk::TradeSide;
+
 /// Moduł do przetwarzania sygnałów tradingowych z integracją Sentry
-// pub mod processor;
+pub mod processor;
+pub mod screening;
 // pub mod validator;
-// pub mod sources;
-
-// pub use processor::SignalProcessor;
+pub mod sources;
+
+pub use processor::{ScoredSignal, SignalDecision, SignalProcessor, SignalRejection};
+pub use screening::{
+    EvmTokenData, ScreeningVerdict, TokenDataProvider, TokenFacts, TokenQuery, TokenScreener,
+    SCREENING_METADATA_KEY,
+};
+pub use sources::{WebhookError, WebhookSource};
 // pub use validator::SignalValidator;
 
+/// Wartość `metadata.action` sygnału awaryjnego (emergency stop)
+pub const EMERGENCY_STOP_ACTION: &str = "emergency_stop";
+
 /// Poziom pewności sygnału
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd)]
 pub enum Confidence {
     Low,     // 5x base leverage
     Medium,  // 10x base leverage
@@ -104,23 +115,43 @@ impl Signal {
         signal
     }
 
-    /// Oblicza hash sygnału dla cache
+    /// Oblicza hash sygnału dla deduplikacji (SHA-256, hex)
+    ///
+    /// Hash jest stabilny między uruchomieniami (zapisywany w bazie) i obejmuje
+    /// token, źródło, pewność, stronę, cenę i wolumen (zaokrąglone do 3 miejsc).
     pub fn calculate_hash(&self) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+        let side = match self.side() {
+            TradeSide::Long => "long",
+            TradeSide::Short => "short",
+        };
+        let key = format!(
+            "{}|{}|{}|{}|{}|{}",
+            self.token,
+            self.source,
+            self.confidence,
+            side,
+            (self.price * 1000.0) as u64,
+            (self.volume * 1000.0) as u64
+        );
 
-        let mut hasher = DefaultHasher::new();
-        self.token.hash(&mut hasher);
-        self.source.hash(&mut hasher);
-        self.confidence.to_string().hash(&mut hasher);
-        ((self.price * 1000.0) as u64).hash(&mut hasher); // Zaokrąglenie do 3 miejsc po przecinku
-        ((self.volume * 1000.0) as u64).hash(&mut hasher);
+        hex::encode(ring::digest::digest(&ring::digest::SHA256, key.as_bytes()))
+    }
 
-        format!("{:x}", hasher.finish())
+    /// Strona transakcji z `metadata.side` (domyślnie Long)
+    pub fn side(&self) -> TradeSide {
+        match self.metadata.get("side").and_then(|s| s.as_str()) {
+            Some(side) if side.eq_ignore_ascii_case("short") => TradeSide::Short,
+            _ => TradeSide::Long,
+        }
     }
 
     /// Waliduje sygnał
     pub fn validate(&self) -> Result<()> {
+        self.validate_at(chrono::Utc::now().timestamp())
+    }
+
+    /// Waliduje sygnał względem podanego czasu (np. zegara symulacji)
+    pub fn validate_at(&self, now: i64) -> Result<()> {
         if self.token.is_empty() {
             return Err(anyhow::anyhow!("Token cannot be empty"));
         }
@@ -142,7 +173,6 @@ impl Signal {
         }
 
         // Sprawdzenie czy sygnał nie jest zbyt stary (maksymalnie 1 godzina)
-        let now = chrono::Utc::now().timestamp();
         if now - self.timestamp > 3600 {
             return Err(anyhow::anyhow!("Signal is too old"));
         }
@@ -160,6 +190,14 @@ impl Signal {
         self.age_seconds() <= max_age_seconds
     }
 
+    /// Sprawdza czy sygnał żąda emergency stop (`metadata.action` lub
+    /// `metadata.payload.action` - oryginalny payload webhooka)
+    pub fn is_emergency_stop(&self) -> bool {
+        ["/action", "/payload/action"].iter().any(|pointer| {
+            self.metadata.pointer(pointer).and_then(|a| a.as_str()) == Some(EMERGENCY_STOP_ACTION)
+        })
+    }
+
     /// Tworzy sygnał z określonym timestampem (dla testów)
     pub fn new_with_timestamp(
         token: String,
@@ -250,6 +288,10 @@ pub struct SourceStats {
 
     /// Czy źródło jest aktywne
     pub is_active: bool,
+
+    /// Liczba żądań odrzuconych przez limit
+    #[serde(default)]
+    pub rate_limited: u64,
 }
 
 impl Default for SignalStats {
@@ -298,6 +340,7 @@ impl SignalStats {
                 errors: 0,
                 last_activity: signal.timestamp,
                 is_active: true,
+                rate_limited: 0,
             });
         source_stats.count += 1;
         source_stats.last_activity = signal.timestamp;
@@ -323,11 +366,28 @@ impl SignalStats {
                     errors: 0,
                     last_activity: chrono::Utc::now().timestamp(),
                     is_active: false,
+                    rate_limited: 0,
                 });
             source_stats.errors += 1;
         }
     }
 
+    /// Aktualizuje statystyki po odrzuceniu żądania przez limit źródła
+    pub fn update_for_rate_limit(&mut self, source: &str) {
+        let source_stats =
+            self.by_source
+                .entry(source.to_string())
+                .or_insert_with(|| SourceStats {
+                    count: 0,
+                    errors: 0,
+                    last_activity: chrono::Utc::now().timestamp(),
+                    is_active: true,
+                    rate_limited: 0,
+                });
+        source_stats.rate_limited += 1;
+        source_stats.last_activity = chrono::Utc::now().timestamp();
+    }
+
     /// Zwraca współczynnik sukcesu
     pub fn success_rate(&self) -> f64 {
         if self.total_processed == 0 {
@@ -503,6 +563,35 @@ mod tests {
         assert_eq!(hash1, hash2);
     }
 
+    #[test]
+    fn test_signal_hash_is_stable_and_includes_side() {
+        let long = Signal::new(
+            "TEST".to_string(),
+            "test_source".to_string(),
+            Confidence::Medium,
+            0.001,
+            1000.0,
+            json!({"side": "long"}),
+        );
+        let mut short = long.clone();
+        short.metadata = json!({"side": "short"});
+
+        // Hash trafia do bazy - nie może zależeć od procesu
+        assert_eq!(
+            long.calculate_hash(),
+            hex::encode(ring::digest::digest(
+                &ring::digest::SHA256,
+                b"TEST|test_source|medium|long|1|1000000"
+            ))
+        );
+        assert_ne!(long.calculate_hash(), short.calculate_hash());
+
+        // Brak strony w metadanych oznacza Long
+        let mut unspecified = long.clone();
+        unspecified.metadata = json!({});
+        assert_eq!(unspecified.calculate_hash(), long.calculate_hash());
+    }
+
     #[test]
     fn test_confidence_display() {
         assert_eq!(Confidence::Low.to_string(), "low");
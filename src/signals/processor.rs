@@ -0,0 +1,711 @@
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, error, info, warn};
+
+use super::{
+    Confidence, Signal, SignalSource, SignalStats, TokenScreener, WebhookSource,
+    SCREENING_METADATA_KEY,
+};
+use crate::config::SignalsConfig;
+use crate::database::SignalStore;
+
+/// Pojemność kanału rozgłaszającego przyjęte sygnały
+const BROADCAST_CAPACITY: usize = 256;
+
+/// Źródło współdzielone między procesorem a resztą aplikacji
+pub type SharedSignalSource = Arc<RwLock<Box<dyn SignalSource>>>;
+
+/// Sygnał przyjęty przez procesor wraz z oceną
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoredSignal {
+    /// Przyjęty sygnał
+    pub signal: Signal,
+
+    /// Wynik (0.0 - 1.0): wiarygodność źródła × waga pewności
+    pub score: f64,
+
+    /// Moment przyjęcia (Unix timestamp)
+    pub received_at: i64,
+}
+
+/// Powód odrzucenia sygnału
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SignalRejection {
+    /// Sygnał nie przeszedł walidacji
+    Invalid(String),
+
+    /// Sygnał jest starszy niż `max_signal_age`
+    Stale { age_seconds: i64 },
+
+    /// Ten sam sygnał (hash) przyjęto już w oknie deduplikacji
+    Duplicate { hash: String },
+
+    /// Wynik poniżej `min_score`
+    LowScore { score: f64 },
+
+    /// Token nie przeszedł kontroli bezpieczeństwa
+    Unsafe { reasons: Vec<String> },
+}
+
+impl std::fmt::Display for SignalRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignalRejection::Invalid(reason) => write!(f, "invalid signal: {}", reason),
+            SignalRejection::Stale { age_seconds } => {
+                write!(f, "stale signal ({}s old)", age_seconds)
+            }
+            SignalRejection::Duplicate { hash } => write!(f, "duplicate signal ({})", hash),
+            SignalRejection::LowScore { score } => write!(f, "score {:.3} below minimum", score),
+            SignalRejection::Unsafe { reasons } => {
+                write!(f, "token screening failed: {}", reasons.join("; "))
+            }
+        }
+    }
+}
+
+/// Wynik przetworzenia pojedynczego sygnału
+#[derive(Debug, Clone)]
+pub enum SignalDecision {
+    Accepted(ScoredSignal),
+    Rejected(SignalRejection),
+}
+
+/// Procesor sygnałów: odpytuje źródła, odrzuca duplikaty, przeterminowane
+/// sygnały i niebezpieczne tokeny, ocenia je i zapisuje do bazy
+pub struct SignalProcessor {
+    config: SignalsConfig,
+    store: SignalStore,
+
+    /// Kontrola bezpieczeństwa tokena (werdykt w `metadata.screening`)
+    screener: TokenScreener,
+    sources: RwLock<Vec<SharedSignalSource>>,
+
+    /// Hash -> moment przyjęcia (okno deduplikacji)
+    recent: RwLock<HashMap<String, i64>>,
+    stats: RwLock<SignalStats>,
+    sender: broadcast::Sender<ScoredSignal>,
+}
+
+impl SignalProcessor {
+    /// Tworzy procesor bez źródeł
+    pub fn new(config: SignalsConfig, store: SignalStore) -> Self {
+        let (sender, _) = broadcast::channel(BROADCAST_CAPACITY);
+
+        Self {
+            screener: TokenScreener::new(config.screening.clone()),
+            config,
+            store,
+            sources: RwLock::new(Vec::new()),
+            recent: RwLock::new(HashMap::new()),
+            stats: RwLock::new(SignalStats::default()),
+            sender,
+        }
+    }
+
+    /// Ustawia kontrolę bezpieczeństwa tokena (np. z danymi on-chain)
+    pub fn with_screener(mut self, screener: TokenScreener) -> Self {
+        self.screener = screener;
+        self
+    }
+
+    /// Rejestruje źródło sygnałów
+    pub async fn add_source(&self, source: Box<dyn SignalSource>) -> SharedSignalSource {
+        info!("Registered signal source: {}", source.source_name());
+        let shared = Arc::new(RwLock::new(source));
+        self.sources.write().await.push(shared.clone());
+        shared
+    }
+
+    /// Rejestruje źródła webhook z konfiguracji
+    ///
+    /// Zwraca uchwyty (wg nazwy) dla endpointu API, który zasila ich kolejki.
+    pub async fn register_webhooks(&self) -> HashMap<String, WebhookSource> {
+        let mut webhooks = HashMap::new();
+        for config in &self.config.webhooks {
+            let source = WebhookSource::new(config.clone());
+            self.add_source(Box::new(source.clone())).await;
+            webhooks.insert(config.name.clone(), source);
+        }
+        webhooks
+    }
+
+    /// Odnotowuje żądanie odrzucone przez limit źródła
+    pub async fn record_rate_limited(&self, source: &str) {
+        self.stats.write().await.update_for_rate_limit(source);
+    }
+
+    /// Odnotowuje błąd źródła (np. niepoprawny podpis lub payload)
+    pub async fn record_source_error(&self, source: &str) {
+        self.stats.write().await.update_for_error(Some(source));
+    }
+
+    /// Liczba zarejestrowanych źródeł
+    pub async fn source_count(&self) -> usize {
+        self.sources.read().await.len()
+    }
+
+    /// Subskrypcja przyjętych sygnałów
+    pub fn subscribe(&self) -> broadcast::Receiver<ScoredSignal> {
+        self.sender.subscribe()
+    }
+
+    /// Zwraca kopię statystyk
+    pub async fn stats(&self) -> SignalStats {
+        self.stats.read().await.clone()
+    }
+
+    /// Magazyn przyjętych sygnałów
+    pub fn store(&self) -> &SignalStore {
+        &self.store
+    }
+
+    /// Waga poziomu pewności w wyniku sygnału
+    pub fn confidence_weight(confidence: &Confidence) -> f64 {
+        match confidence {
+            Confidence::Low => 0.25,
+            Confidence::Medium => 0.5,
+            Confidence::High => 0.75,
+            Confidence::Extreme => 1.0,
+        }
+    }
+
+    /// Ocena sygnału: wiarygodność źródła × waga pewności
+    pub fn score(&self, signal: &Signal) -> f64 {
+        let reliability = self
+            .config
+            .reliability_for(&signal.source)
+            .to_f64()
+            .unwrap_or(0.0);
+        reliability * Self::confidence_weight(&signal.confidence)
+    }
+
+    /// Odpytuje równolegle wszystkie źródła i przetwarza otrzymane sygnały
+    ///
+    /// Zwraca przyjęte sygnały. Błąd pojedynczego źródła lub sygnału nie
+    /// przerywa przetwarzania pozostałych.
+    pub async fn poll_sources(&self) -> Result<Vec<ScoredSignal>> {
+        let sources = self.sources.read().await.clone();
+
+        let results = futures::future::join_all(sources.iter().map(Self::fetch_from)).await;
+
+        let mut accepted = Vec::new();
+        for (name, result) in results {
+            match result {
+                Ok(signals) => {
+                    debug!("Source {} returned {} signals", name, signals.len());
+                    for signal in signals {
+                        let signal_id = signal.id.clone();
+                        match self.process(signal).await {
+                            Ok(SignalDecision::Accepted(scored)) => accepted.push(scored),
+                            Ok(SignalDecision::Rejected(_)) => {}
+                            Err(e) => {
+                                warn!("Signal {} from {} failed: {}", signal_id, name, e);
+                                self.stats.write().await.update_for_error(Some(&name));
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("Signal source {} failed: {}", name, e);
+                    self.stats.write().await.update_for_error(Some(&name));
+                }
+            }
+        }
+
+        Ok(accepted)
+    }
+
+    /// Przetwarza pojedynczy sygnał (np. przesłany przez API)
+    pub async fn process(&self, signal: Signal) -> Result<SignalDecision> {
+        self.process_at(signal, chrono::Utc::now().timestamp())
+            .await
+    }
+
+    /// Przetwarza sygnał względem podanego czasu (Unix timestamp)
+    pub async fn process_at(&self, mut signal: Signal, now: i64) -> Result<SignalDecision> {
+        let started = std::time::Instant::now();
+        let signal_id = signal.id.clone();
+
+        let decision = match self.check(&mut signal, now).await? {
+            Some(rejection) => SignalDecision::Rejected(rejection),
+            None => {
+                let scored = ScoredSignal {
+                    score: self.score(&signal),
+                    signal,
+                    received_at: now,
+                };
+                let hash = scored.signal.hash.clone().unwrap_or_default();
+                if scored.score < self.config.min_score.to_f64().unwrap_or(0.0) {
+                    self.release(&hash).await;
+                    SignalDecision::Rejected(SignalRejection::LowScore {
+                        score: scored.score,
+                    })
+                } else if let Err(e) = self.accept(&scored).await {
+                    self.release(&hash).await;
+                    return Err(e);
+                } else {
+                    SignalDecision::Accepted(scored)
+                }
+            }
+        };
+
+        let mut stats = self.stats.write().await;
+        match &decision {
+            SignalDecision::Accepted(scored) => {
+                stats.update_for_signal(
+                    &scored.signal,
+                    started.elapsed().as_secs_f64() * 1000.0,
+                    false,
+                );
+            }
+            SignalDecision::Rejected(rejection) => {
+                debug!("Signal {} rejected: {}", signal_id, rejection);
+                stats.update_for_error(None);
+            }
+        }
+
+        Ok(decision)
+    }
+
+    /// Uruchamia cykliczne odpytywanie źródeł
+    pub async fn run(self: Arc<Self>) {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(self.config.poll_interval));
+
+        loop {
+            interval.tick().await;
+            match self.poll_sources().await {
+                Ok(accepted) if !accepted.is_empty() => {
+                    info!("Accepted {} new signals", accepted.len())
+                }
+                Ok(_) => {}
+                Err(e) => error!("Signal polling failed: {}", e),
+            }
+        }
+    }
+
+    /// Walidacja, świeżość, deduplikacja i kontrola tokena; zwraca powód odrzucenia
+    async fn check(&self, signal: &mut Signal, now: i64) -> Result<Option<SignalRejection>> {
+        if let Err(e) = signal.validate_at(now) {
+            return Ok(Some(SignalRejection::Invalid(e.to_string())));
+        }
+
+        let age_seconds = now - signal.timestamp;
+        if age_seconds > self.config.max_signal_age {
+            return Ok(Some(SignalRejection::Stale { age_seconds }));
+        }
+
+        // Hash liczony lokalnie - nie ufamy wartości przesłanej przez źródło
+        let hash = signal.calculate_hash();
+        signal.hash = Some(hash.clone());
+
+        if !self.reserve(&hash, now).await? {
+            return Ok(Some(SignalRejection::Duplicate { hash }));
+        }
+
+        let rejection = self.screen(signal, now).await;
+        if rejection.is_some() {
+            self.release(&hash).await;
+        }
+        Ok(rejection)
+    }
+
+    /// Rezerwuje hash w oknie deduplikacji; `false`, jeśli sygnał już widziano
+    ///
+    /// Sprawdzenie i rezerwacja odbywają się pod jedną blokadą, więc
+    /// równoległe kopie tego samego sygnału nie przejdą obie.
+    async fn reserve(&self, hash: &str, now: i64) -> Result<bool> {
+        let since = now - self.config.dedup_window;
+        let mut recent = self.recent.write().await;
+        recent.retain(|_, seen_at| *seen_at >= since);
+
+        if recent.contains_key(hash) || self.store.seen_since(hash, since).await? {
+            return Ok(false);
+        }
+
+        recent.insert(hash.to_string(), now);
+        Ok(true)
+    }
+
+    /// Zwalnia rezerwację sygnału, który ostatecznie nie został przyjęty
+    async fn release(&self, hash: &str) {
+        self.recent.write().await.remove(hash);
+    }
+
+    /// Kontrola bezpieczeństwa tokena; werdykt trafia do metadanych sygnału
+    async fn screen(&self, signal: &mut Signal, now: i64) -> Option<SignalRejection> {
+        if !self.screener.is_enabled() || signal.is_emergency_stop() {
+            return None;
+        }
+
+        let verdict = self.screener.screen(signal, now).await;
+        for warning in &verdict.warnings {
+            debug!("Signal {} screening: {}", signal.id, warning);
+        }
+
+        match signal.metadata.take() {
+            serde_json::Value::Null => signal.metadata = serde_json::json!({}),
+            value @ serde_json::Value::Object(_) => signal.metadata = value,
+            value => signal.metadata = serde_json::json!({ "value": value }),
+        }
+        signal.metadata[SCREENING_METADATA_KEY] =
+            serde_json::to_value(&verdict).unwrap_or_default();
+
+        (!verdict.passed).then_some(SignalRejection::Unsafe {
+            reasons: verdict.reasons,
+        })
+    }
+
+    /// Zapisuje przyjęty sygnał (hash zarezerwowano w `check`) i rozgłasza go
+    async fn accept(&self, scored: &ScoredSignal) -> Result<()> {
+        self.store.save(scored).await?;
+
+        // Brak subskrybentów nie jest błędem
+        let _ = self.sender.send(scored.clone());
+
+        info!(
+            "Signal accepted: {} {} from {} (score {:.3})",
+            scored.signal.token, scored.signal.confidence, scored.signal.source, scored.score
+        );
+        Ok(())
+    }
+
+    async fn fetch_from(source: &SharedSignalSource) -> (String, Result<Vec<Signal>>) {
+        {
+            let mut source = source.write().await;
+            if !source.is_connected() {
+                if let Err(e) = source.connect().await {
+                    return (source.source_name().to_string(), Err(e));
+                }
+            }
+        }
+
+        let source = source.read().await;
+        (source.source_name().to_string(), source.get_signals().await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use serde_json::json;
+
+    struct StaticSource {
+        name: String,
+        signals: Vec<Signal>,
+        fail: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl SignalSource for StaticSource {
+        async fn get_signals(&self) -> Result<Vec<Signal>> {
+            if self.fail {
+                anyhow::bail!("source offline");
+            }
+            Ok(self.signals.clone())
+        }
+
+        fn source_name(&self) -> &str {
+            &self.name
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn connect(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    async fn processor() -> SignalProcessor {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let mut config = SignalsConfig::default();
+        config
+            .source_reliability
+            .insert("trusted".to_string(), Decimal::ONE);
+        SignalProcessor::new(config, SignalStore::new(Arc::new(pool)))
+    }
+
+    fn signal(source: &str, confidence: Confidence, timestamp: i64) -> Signal {
+        Signal::new_with_timestamp(
+            "BONK".to_string(),
+            source.to_string(),
+            confidence,
+            0.001,
+            1000.0,
+            json!({}),
+            timestamp,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_and_stale_signals_are_rejected() {
+        let processor = processor().await;
+        let now = chrono::Utc::now().timestamp();
+
+        let first = processor
+            .process_at(signal("trusted", Confidence::High, now), now)
+            .await
+            .unwrap();
+        assert!(matches!(first, SignalDecision::Accepted(_)));
+
+        // Ten sam sygnał z nowym ID to wciąż duplikat
+        let duplicate = processor
+            .process_at(signal("trusted", Confidence::High, now), now + 1)
+            .await
+            .unwrap();
+        assert!(matches!(
+            duplicate,
+            SignalDecision::Rejected(SignalRejection::Duplicate { .. })
+        ));
+
+        let stale = processor
+            .process_at(signal("trusted", Confidence::Medium, now - 600), now)
+            .await
+            .unwrap();
+        assert!(matches!(
+            stale,
+            SignalDecision::Rejected(SignalRejection::Stale { age_seconds: 600 })
+        ));
+
+        let stats = processor.stats().await;
+        assert_eq!(stats.valid_signals, 1);
+        assert_eq!(stats.invalid_signals, 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_duplicates_accept_only_one() {
+        let processor = processor().await;
+        let now = chrono::Utc::now().timestamp();
+
+        let decisions = futures::future::join_all(
+            (0..4).map(|_| processor.process_at(signal("trusted", Confidence::High, now), now)),
+        )
+        .await;
+
+        let accepted = decisions
+            .into_iter()
+            .filter(|d| matches!(d, Ok(SignalDecision::Accepted(_))))
+            .count();
+        assert_eq!(accepted, 1);
+    }
+
+    #[tokio::test]
+    async fn test_opposite_sides_are_not_duplicates() {
+        let processor = processor().await;
+        let now = chrono::Utc::now().timestamp();
+
+        let mut long = signal("trusted", Confidence::High, now);
+        long.metadata = json!({"side": "long"});
+        let mut short = signal("trusted", Confidence::High, now);
+        short.metadata = json!({"side": "short"});
+
+        for signal in [long, short] {
+            let decision = processor.process_at(signal, now).await.unwrap();
+            assert!(matches!(decision, SignalDecision::Accepted(_)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_score_uses_source_reliability_and_confidence() {
+        let processor = processor().await;
+        let now = chrono::Utc::now().timestamp();
+
+        assert_eq!(
+            processor.score(&signal("trusted", Confidence::High, now)),
+            0.75
+        );
+        assert_eq!(
+            processor.score(&signal("unknown", Confidence::Medium, now)),
+            0.25
+        );
+
+        // 0.5 × 0.25 < min_score 0.25
+        let decision = processor
+            .process_at(signal("unknown", Confidence::Low, now), now)
+            .await
+            .unwrap();
+        assert!(matches!(
+            decision,
+            SignalDecision::Rejected(SignalRejection::LowScore { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_screening_verdict_recorded_in_metadata() {
+        let mut processor = processor().await;
+        processor.config.screening.denylist = vec!["SCAM".to_string()];
+        let screener = TokenScreener::new(processor.config.screening.clone());
+        let processor = processor.with_screener(screener);
+        let now = chrono::Utc::now().timestamp();
+
+        let mut unsafe_signal = signal("trusted", Confidence::High, now);
+        unsafe_signal.token = "SCAM".to_string();
+        let decision = processor.process_at(unsafe_signal, now).await.unwrap();
+        match decision {
+            SignalDecision::Rejected(SignalRejection::Unsafe { reasons }) => {
+                assert!(reasons[0].contains("denylisted"))
+            }
+            other => panic!("expected unsafe rejection, got {:?}", other),
+        }
+
+        let mut thin = signal("trusted", Confidence::High, now);
+        thin.metadata = json!({"liquidity_usd": 500, "created_at": now - 86_400});
+        assert!(matches!(
+            processor.process_at(thin, now).await.unwrap(),
+            SignalDecision::Rejected(SignalRejection::Unsafe { .. })
+        ));
+
+        let decision = processor
+            .process_at(signal("trusted", Confidence::High, now), now)
+            .await
+            .unwrap();
+        let scored = match decision {
+            SignalDecision::Accepted(scored) => scored,
+            other => panic!("expected accept, got {:?}", other),
+        };
+        let verdict = &scored.signal.metadata[SCREENING_METADATA_KEY];
+        assert_eq!(verdict["passed"], true);
+        assert_eq!(verdict["warnings"].as_array().unwrap().len(), 3);
+
+        // Werdykt zapisany razem z sygnałem
+        let stored = processor
+            .store()
+            .get(&scored.signal.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.signal.metadata[SCREENING_METADATA_KEY], *verdict);
+    }
+
+    #[tokio::test]
+    async fn test_poll_sources_persists_and_survives_failing_source() {
+        let processor = processor().await;
+        let now = chrono::Utc::now().timestamp();
+        let mut receiver = processor.subscribe();
+
+        processor
+            .add_source(Box::new(StaticSource {
+                name: "trusted".to_string(),
+                signals: vec![
+                    signal("trusted", Confidence::Extreme, now),
+                    signal("trusted", Confidence::Extreme, now),
+                ],
+                fail: false,
+            }))
+            .await;
+        processor
+            .add_source(Box::new(StaticSource {
+                name: "broken".to_string(),
+                signals: vec![],
+                fail: true,
+            }))
+            .await;
+
+        let accepted = processor.poll_sources().await.unwrap();
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0].score, 1.0);
+
+        let received = receiver.recv().await.unwrap();
+        assert_eq!(received.signal.id, accepted[0].signal.id);
+
+        let stored = processor
+            .store()
+            .get(&accepted[0].signal.id)
+            .await
+            .unwrap()
+            .expect("signal persisted");
+        assert_eq!(stored.signal.confidence, Confidence::Extreme);
+        assert_eq!(stored.signal.hash, accepted[0].signal.hash);
+
+        let stats = processor.stats().await;
+        assert_eq!(stats.by_source["broken"].errors, 1);
+    }
+
+    #[tokio::test]
+    async fn test_poll_sources_continues_after_failed_signal() {
+        let processor = processor().await;
+        let now = chrono::Utc::now().timestamp();
+
+        // Ten sam identyfikator przy innej treści - zapis drugiego sygnału się nie powiedzie
+        let first = signal("trusted", Confidence::Extreme, now);
+        let mut conflicting = signal("trusted", Confidence::Extreme, now);
+        conflicting.id = first.id.clone();
+        conflicting.token = "WIF".to_string();
+        let mut last = signal("trusted", Confidence::Extreme, now);
+        last.token = "PEPE".to_string();
+
+        processor
+            .add_source(Box::new(StaticSource {
+                name: "trusted".to_string(),
+                signals: vec![first, conflicting, last],
+                fail: false,
+            }))
+            .await;
+
+        let accepted = processor.poll_sources().await.unwrap();
+        let tokens: Vec<_> = accepted.iter().map(|s| s.signal.token.as_str()).collect();
+        assert_eq!(tokens, vec!["BONK", "PEPE"]);
+
+        let stats = processor.stats().await;
+        assert_eq!(stats.by_source["trusted"].errors, 1);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_signals_flow_through_pipeline() {
+        let mut processor = processor().await;
+        processor
+            .config
+            .webhooks
+            .push(crate::config::signals::WebhookConfig {
+                name: "trusted".to_string(),
+                secret: Some("s3cret".to_string()),
+                secret_env: None,
+                rate_limit_per_minute: 1,
+                max_clock_skew: 300,
+                max_pending: 100,
+                template: Default::default(),
+            });
+
+        let webhooks = processor.register_webhooks().await;
+        assert_eq!(processor.source_count().await, 1);
+
+        let body = br#"{"token":"WIF","side":"sell","price":2.5,"confidence":"extreme"}"#;
+        let sent_at = chrono::Utc::now().timestamp().to_string();
+        let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, b"s3cret");
+        let message = [format!("{}.", sent_at).as_bytes(), &body[..]].concat();
+        let signature = hex::encode(ring::hmac::sign(&key, &message).as_ref());
+        webhooks["trusted"]
+            .ingest(body, Some(&sent_at), Some(&signature))
+            .await
+            .unwrap();
+
+        let accepted = processor.poll_sources().await.unwrap();
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0].signal.source, "trusted");
+        assert_eq!(accepted[0].signal.metadata["side"], "short");
+
+        assert!(webhooks["trusted"]
+            .ingest(body, Some(&sent_at), Some(&signature))
+            .await
+            .is_err());
+        processor.record_rate_limited("trusted").await;
+        assert_eq!(processor.stats().await.by_source["trusted"].rate_limited, 1);
+    }
+}
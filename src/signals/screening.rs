@@ -0,0 +1,573 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use super::Signal;
+use crate::config::ScreeningConfig;
+use crate::indexer::evm::EvmIndexer;
+use crate::indexer::IndexerConfig;
+use crate::wallets::Chain;
+
+/// Klucz werdyktu w `Signal::metadata`
+pub const SCREENING_METADATA_KEY: &str = "screening";
+
+/// Parametry tokena oceniane przy kontroli bezpieczeństwa
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TokenFacts {
+    /// Płynność puli (w USD)
+    pub liquidity_usd: Option<f64>,
+
+    /// Udział największych posiadaczy w podaży (0.0 - 1.0)
+    pub top_holders_percent: Option<f64>,
+
+    /// Moment utworzenia kontraktu (Unix timestamp)
+    pub created_at: Option<i64>,
+}
+
+/// Zapytanie o parametry tokena on-chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenQuery {
+    /// Adres kontraktu tokena
+    pub contract: String,
+
+    /// Adres puli płynności (para Uniswap V2)
+    pub pair: Option<String>,
+
+    /// Adresy największych posiadaczy
+    pub holders: Vec<String>,
+
+    /// Cena tokena w USD (z sygnału)
+    pub price: f64,
+}
+
+/// Źródło parametrów tokena (np. RPC łańcucha)
+#[async_trait]
+pub trait TokenDataProvider: Send + Sync {
+    /// Pobiera parametry tokena; nieustalone pozostają `None`
+    async fn token_facts(&self, query: &TokenQuery) -> Result<TokenFacts>;
+}
+
+/// Parametry tokena z RPC łańcucha EVM (`EvmIndexer`)
+pub struct EvmTokenData {
+    indexer: EvmIndexer,
+    chain: Chain,
+}
+
+impl EvmTokenData {
+    /// Tworzy źródło dla łańcucha obsługiwanego przez indexer
+    pub fn new(indexer: EvmIndexer, chain: Chain) -> Self {
+        Self { indexer, chain }
+    }
+
+    async fn liquidity_usd(&self, query: &TokenQuery, pair: &str) -> Result<f64> {
+        let (token0, reserve0, reserve1) = self.indexer.get_pair_reserves(&self.chain, pair).await?;
+        let reserve = if token0.eq_ignore_ascii_case(&query.contract) {
+            reserve0
+        } else {
+            reserve1
+        };
+        let decimals = self
+            .indexer
+            .get_token_decimals(&self.chain, &query.contract)
+            .await?;
+
+        // Para V2 ma równą wartość po obu stronach
+        Ok(2.0 * reserve as f64 / 10f64.powi(decimals as i32) * query.price)
+    }
+
+    async fn top_holders_percent(&self, query: &TokenQuery) -> Result<f64> {
+        let supply = self
+            .indexer
+            .get_total_supply(&self.chain, &query.contract)
+            .await?;
+        if supply == 0 {
+            anyhow::bail!("token {} has zero total supply", query.contract);
+        }
+
+        let mut held = 0u128;
+        for holder in &query.holders {
+            held = held.saturating_add(
+                self.indexer
+                    .get_holder_balance(&self.chain, &query.contract, holder)
+                    .await?,
+            );
+        }
+        Ok(held as f64 / supply as f64)
+    }
+
+    async fn created_at(&self, query: &TokenQuery) -> Result<Option<i64>> {
+        match self
+            .indexer
+            .find_deployment_block(&self.chain, &query.contract)
+            .await?
+        {
+            Some(block) => Ok(Some(
+                self.indexer.get_block_timestamp(&self.chain, block).await?,
+            )),
+            None => Ok(None),
+        }
+    }
+}
+
+#[async_trait]
+impl TokenDataProvider for EvmTokenData {
+    async fn token_facts(&self, query: &TokenQuery) -> Result<TokenFacts> {
+        let mut facts = TokenFacts::default();
+
+        // Pojedyncze błędy RPC nie przekreślają pozostałych parametrów
+        if let Some(pair) = &query.pair {
+            match self.liquidity_usd(query, pair).await {
+                Ok(liquidity) => facts.liquidity_usd = Some(liquidity),
+                Err(e) => warn!("Liquidity lookup for {} failed: {}", query.contract, e),
+            }
+        }
+
+        if !query.holders.is_empty() {
+            match self.top_holders_percent(query).await {
+                Ok(percent) => facts.top_holders_percent = Some(percent),
+                Err(e) => warn!("Holder lookup for {} failed: {}", query.contract, e),
+            }
+        }
+
+        match self.created_at(query).await {
+            Ok(created_at) => facts.created_at = created_at,
+            Err(e) => warn!("Deployment lookup for {} failed: {}", query.contract, e),
+        }
+
+        Ok(facts)
+    }
+}
+
+/// Werdykt kontroli bezpieczeństwa tokena
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScreeningVerdict {
+    /// Czy token przeszedł kontrolę
+    pub passed: bool,
+
+    /// Powody odrzucenia (lub przyjęcia bez kontroli)
+    pub reasons: Vec<String>,
+
+    /// Parametry, których nie udało się ustalić
+    pub warnings: Vec<String>,
+
+    /// Ocenione parametry tokena
+    pub facts: TokenFacts,
+
+    /// Moment kontroli (Unix timestamp)
+    pub screened_at: i64,
+}
+
+/// Kontrola bezpieczeństwa tokena między walidacją sygnału a oceną ryzyka
+///
+/// Sprawdza lokalną listę zablokowanych i dozwolonych tokenów, płynność puli,
+/// koncentrację posiadaczy i wiek kontraktu. Werdykty on-chain są pamiętane
+/// per kontrakt przez `cache_ttl` sekund.
+pub struct TokenScreener {
+    config: ScreeningConfig,
+    provider: Option<Arc<dyn TokenDataProvider>>,
+    cache: RwLock<HashMap<String, ScreeningVerdict>>,
+}
+
+impl TokenScreener {
+    /// Tworzy kontrolę bez źródła danych on-chain
+    pub fn new(config: ScreeningConfig) -> Self {
+        Self {
+            config,
+            provider: None,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Tworzy kontrolę z konfiguracji; z adresem RPC dołącza `EvmTokenData`
+    pub async fn from_config(config: &ScreeningConfig) -> Result<Self> {
+        let screener = Self::new(config.clone());
+        let Some(rpc_url) = config.resolve_rpc_url() else {
+            return Ok(screener);
+        };
+
+        let chain = match config.chain.as_str() {
+            "bsc" => Chain::BinanceSmartChain,
+            "polygon" => Chain::Polygon,
+            _ => Chain::Ethereum,
+        };
+        let indexer_config = IndexerConfig {
+            rpc_urls: HashMap::from([(chain.clone(), rpc_url)]),
+            ..IndexerConfig::default()
+        };
+        let http_client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(
+                indexer_config.timeout_seconds,
+            ))
+            .build()?;
+        let indexer = EvmIndexer::new(indexer_config, http_client).await?;
+
+        info!("Token screening uses on-chain data from {:?}", chain);
+        Ok(screener.with_provider(Arc::new(EvmTokenData::new(indexer, chain))))
+    }
+
+    /// Dołącza źródło parametrów tokena on-chain
+    pub fn with_provider(mut self, provider: Arc<dyn TokenDataProvider>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Czy kontrola jest włączona
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Ocenia token sygnału w chwili `now`
+    pub async fn screen(&self, signal: &Signal, now: i64) -> ScreeningVerdict {
+        let contract = metadata_str(signal, "contract");
+        let mut verdict = ScreeningVerdict {
+            passed: true,
+            reasons: Vec::new(),
+            warnings: Vec::new(),
+            facts: TokenFacts::default(),
+            screened_at: now,
+        };
+
+        let listed = |list: &[String]| {
+            list.iter().any(|entry| {
+                entry.eq_ignore_ascii_case(&signal.token)
+                    || contract.is_some_and(|c| entry.eq_ignore_ascii_case(c))
+            })
+        };
+        if listed(&self.config.denylist) {
+            verdict.passed = false;
+            verdict.reasons.push(format!("{} is denylisted", signal.token));
+            return verdict;
+        }
+        if listed(&self.config.allowlist) {
+            verdict
+                .reasons
+                .push(format!("{} is allowlisted", signal.token));
+            return verdict;
+        }
+
+        // Metadane podaje nadawca sygnału - przy dostępnym RPC nie mają znaczenia
+        match (&self.provider, contract) {
+            (None, _) => {
+                verdict.facts = TokenFacts {
+                    liquidity_usd: metadata_f64(signal, "liquidity_usd"),
+                    top_holders_percent: metadata_f64(signal, "top_holders_percent"),
+                    created_at: metadata_f64(signal, "created_at").map(|t| t as i64),
+                };
+            }
+            (Some(_), None) => verdict
+                .warnings
+                .push("no contract address for on-chain lookup".to_string()),
+            (Some(provider), Some(contract)) => {
+                let key = contract.to_ascii_lowercase();
+                if let Some(cached) = self.cached(&key, now).await {
+                    debug!("Using cached screening verdict for {}", contract);
+                    return cached;
+                }
+
+                let query = TokenQuery {
+                    contract: contract.to_string(),
+                    pair: metadata_str(signal, "pair").map(str::to_string),
+                    holders: metadata_list(signal, "top_holders"),
+                    price: signal.price,
+                };
+                match provider.token_facts(&query).await {
+                    Ok(facts) => {
+                        verdict.facts = facts;
+                        self.check(&mut verdict, now);
+                        self.remember(key, &verdict).await;
+                        return verdict;
+                    }
+                    Err(e) => verdict
+                        .warnings
+                        .push(format!("on-chain lookup failed: {}", e)),
+                }
+            }
+        }
+
+        self.check(&mut verdict, now);
+        verdict
+    }
+
+    /// Werdykt kontraktu zapamiętany w ciągu ostatnich `cache_ttl` sekund
+    async fn cached(&self, contract: &str, now: i64) -> Option<ScreeningVerdict> {
+        self.cache
+            .read()
+            .await
+            .get(contract)
+            .filter(|verdict| now - verdict.screened_at < self.config.cache_ttl)
+            .cloned()
+    }
+
+    /// Zapamiętuje werdykt kontraktu, usuwając przeterminowane
+    async fn remember(&self, contract: String, verdict: &ScreeningVerdict) {
+        if self.config.cache_ttl <= 0 {
+            return;
+        }
+
+        let mut cache = self.cache.write().await;
+        cache.retain(|_, cached| verdict.screened_at - cached.screened_at < self.config.cache_ttl);
+        cache.insert(contract, verdict.clone());
+    }
+
+    /// Porównuje parametry z progami konfiguracji
+    fn check(&self, verdict: &mut ScreeningVerdict, now: i64) {
+        let facts = verdict.facts.clone();
+        let mut unknown = Vec::new();
+
+        let min_liquidity = self.config.min_liquidity_usd.to_f64().unwrap_or(0.0);
+        match facts.liquidity_usd {
+            Some(liquidity) if liquidity < min_liquidity => verdict.reasons.push(format!(
+                "liquidity {:.0} USD below minimum {:.0} USD",
+                liquidity, min_liquidity
+            )),
+            Some(_) => {}
+            None => unknown.push("liquidity"),
+        }
+
+        let max_holders = self.config.max_top_holders_percent.to_f64().unwrap_or(1.0);
+        match facts.top_holders_percent {
+            Some(percent) if percent > max_holders => verdict.reasons.push(format!(
+                "top holders own {:.1}% of supply (max {:.1}%)",
+                percent * 100.0,
+                max_holders * 100.0
+            )),
+            Some(_) => {}
+            None => unknown.push("holder concentration"),
+        }
+
+        match facts.created_at {
+            Some(created_at) if now - created_at < self.config.min_contract_age => {
+                verdict.reasons.push(format!(
+                    "contract is {}s old (minimum {}s)",
+                    now - created_at,
+                    self.config.min_contract_age
+                ))
+            }
+            Some(_) => {}
+            None => unknown.push("contract age"),
+        }
+
+        for parameter in unknown {
+            let message = format!("{} unknown", parameter);
+            if self.config.reject_unknown {
+                verdict.reasons.push(message);
+            } else {
+                verdict.warnings.push(message);
+            }
+        }
+
+        verdict.passed = verdict.reasons.is_empty();
+    }
+}
+
+/// Wartość z metadanych sygnału lub z oryginalnego payloadu webhooka
+fn metadata_value<'a>(signal: &'a Signal, key: &str) -> Option<&'a Value> {
+    signal
+        .metadata
+        .get(key)
+        .or_else(|| signal.metadata.get("payload")?.get(key))
+}
+
+fn metadata_str<'a>(signal: &'a Signal, key: &str) -> Option<&'a str> {
+    metadata_value(signal, key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn metadata_f64(signal: &Signal, key: &str) -> Option<f64> {
+    match metadata_value(signal, key)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|v: &f64| v.is_finite())
+}
+
+fn metadata_list(signal: &Signal, key: &str) -> Vec<String> {
+    metadata_value(signal, key)
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::Confidence;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const NOW: i64 = 1_700_000_000;
+
+    struct StaticData(TokenFacts, AtomicUsize);
+
+    #[async_trait]
+    impl TokenDataProvider for StaticData {
+        async fn token_facts(&self, query: &TokenQuery) -> Result<TokenFacts> {
+            assert_eq!(query.holders.len(), 2);
+            self.1.fetch_add(1, Ordering::SeqCst);
+            Ok(self.0.clone())
+        }
+    }
+
+    fn provider() -> Arc<StaticData> {
+        Arc::new(StaticData(
+            TokenFacts {
+                liquidity_usd: Some(1_000_000.0),
+                top_holders_percent: Some(0.1),
+                created_at: Some(NOW - 600),
+            },
+            AtomicUsize::new(0),
+        ))
+    }
+
+    fn signal(token: &str, metadata: Value) -> Signal {
+        Signal::new(
+            token.to_string(),
+            "pump_fun".to_string(),
+            Confidence::High,
+            0.01,
+            1000.0,
+            metadata,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_metadata_facts_are_checked() {
+        let screener = TokenScreener::new(ScreeningConfig::default());
+
+        let safe = screener
+            .screen(
+                &signal(
+                    "BONK",
+                    json!({
+                        "liquidity_usd": 250_000,
+                        "top_holders_percent": "0.2",
+                        "created_at": NOW - 86_400
+                    }),
+                ),
+                NOW,
+            )
+            .await;
+        assert!(safe.passed, "{:?}", safe.reasons);
+        assert!(safe.warnings.is_empty());
+
+        let honeypot = screener
+            .screen(
+                &signal(
+                    "SCAM",
+                    json!({
+                        "payload": {
+                            "liquidity_usd": 2_000,
+                            "top_holders_percent": 0.9,
+                            "created_at": NOW - 60
+                        }
+                    }),
+                ),
+                NOW,
+            )
+            .await;
+        assert!(!honeypot.passed);
+        assert_eq!(honeypot.reasons.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_lists_and_unknown_facts() {
+        let mut config = ScreeningConfig {
+            denylist: vec!["0xdead".to_string()],
+            allowlist: vec!["eth".to_string()],
+            ..ScreeningConfig::default()
+        };
+        let screener = TokenScreener::new(config.clone());
+
+        let denied = screener
+            .screen(&signal("PEPE2", json!({"contract": "0xDEAD"})), NOW)
+            .await;
+        assert!(!denied.passed);
+        assert!(denied.reasons[0].contains("denylisted"));
+
+        let allowed = screener.screen(&signal("ETH", Value::Null), NOW).await;
+        assert!(allowed.passed);
+
+        // Brak danych - ostrzeżenia albo odrzucenie przy `reject_unknown`
+        let unknown = screener.screen(&signal("NEW", Value::Null), NOW).await;
+        assert!(unknown.passed);
+        assert_eq!(unknown.warnings.len(), 3);
+
+        config.reject_unknown = true;
+        let strict = TokenScreener::new(config)
+            .screen(&signal("NEW", Value::Null), NOW)
+            .await;
+        assert!(!strict.passed);
+    }
+
+    #[tokio::test]
+    async fn test_provider_facts_override_metadata() {
+        let screener = TokenScreener::new(ScreeningConfig::default()).with_provider(provider());
+
+        let verdict = screener
+            .screen(
+                &signal(
+                    "FRESH",
+                    json!({
+                        "contract": "0xabc",
+                        "top_holders": ["0x1", "0x2"],
+                        "liquidity_usd": 5_000,
+                        "created_at": NOW - 86_400
+                    }),
+                ),
+                NOW,
+            )
+            .await;
+
+        // Przy dostępnym RPC metadane sygnału są pomijane
+        assert_eq!(verdict.facts.liquidity_usd, Some(1_000_000.0));
+        assert_eq!(verdict.facts.created_at, Some(NOW - 600));
+        assert!(!verdict.passed);
+        assert_eq!(verdict.reasons.len(), 1);
+
+        // Bez adresu kontraktu nie ma czego sprawdzić on-chain
+        let anonymous = screener
+            .screen(&signal("ANON", json!({"liquidity_usd": 5_000_000})), NOW)
+            .await;
+        assert_eq!(anonymous.facts, TokenFacts::default());
+        assert_eq!(anonymous.warnings.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_verdict_cached_per_contract() {
+        let data = provider();
+        let screener = TokenScreener::new(ScreeningConfig::default()).with_provider(data.clone());
+        let metadata =
+            |contract: &str| json!({"contract": contract, "top_holders": ["0x1", "0x2"]});
+
+        let first = screener
+            .screen(&signal("FRESH", metadata("0xabc")), NOW)
+            .await;
+        let again = screener
+            .screen(&signal("FRESH", metadata("0xABC")), NOW + 60)
+            .await;
+        assert_eq!(again, first);
+        assert_eq!(data.1.load(Ordering::SeqCst), 1);
+
+        // Po czasie ważności kontrakt jest sprawdzany ponownie
+        let expired = screener
+            .screen(&signal("FRESH", metadata("0xabc")), NOW + 900)
+            .await;
+        assert_eq!(expired.screened_at, NOW + 900);
+        assert_eq!(data.1.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,477 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+use super::{Confidence, Signal, SignalSource};
+use crate::config::signals::WebhookConfig;
+
+/// Nagłówek z podpisem HMAC-SHA256 żądania (hex, opcjonalny prefiks `sha256=`)
+///
+/// Podpisywana jest wiadomość `<timestamp>.<body>`, gdzie `timestamp` to
+/// wartość nagłówka `WEBHOOK_TIMESTAMP_HEADER`.
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "x-signature";
+
+/// Nagłówek z czasem wysłania żądania (Unix timestamp, objęty podpisem)
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "x-signature-timestamp";
+
+/// Okno limitu żądań (w sekundach)
+const RATE_LIMIT_WINDOW: i64 = 60;
+
+/// Błędy przyjmowania webhooków
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    /// Brak lub niepoprawny podpis
+    #[error("Unauthorized webhook: {0}")]
+    Unauthorized(String),
+
+    /// Przekroczony limit żądań źródła
+    #[error("Rate limit exceeded, retry in {retry_after}s")]
+    RateLimited { retry_after: i64 },
+
+    /// Payload niezgodny z szablonem
+    #[error("Invalid webhook payload: {0}")]
+    InvalidPayload(String),
+
+    /// Kolejka sygnałów źródła jest pełna
+    #[error("Webhook queue full ({0} pending signals)")]
+    QueueFull(usize),
+}
+
+/// Źródło sygnałów zasilane webhookami (np. alerty TradingView)
+///
+/// Endpoint API przyjmuje i weryfikuje żądania, a przetworzone sygnały
+/// czekają w kolejce na odpytanie przez `SignalProcessor`. Klony dzielą
+/// kolejkę i licznik limitu.
+///
+/// Podpis obejmuje czas wysłania żądania; żądania spoza okna
+/// `max_clock_skew` są odrzucane (ochrona przed powtórzeniem). Limit
+/// żądań liczy tylko żądania z poprawnym podpisem.
+#[derive(Clone)]
+pub struct WebhookSource {
+    config: WebhookConfig,
+    key: Option<ring::hmac::Key>,
+    queue: Arc<Mutex<VecDeque<Signal>>>,
+    requests: Arc<Mutex<VecDeque<i64>>>,
+}
+
+impl WebhookSource {
+    /// Tworzy źródło na podstawie konfiguracji
+    pub fn new(config: WebhookConfig) -> Self {
+        let key = config
+            .resolve_secret()
+            .map(|secret| ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes()));
+
+        if key.is_none() {
+            warn!(
+                "Webhook {} has no secret configured - requests will be rejected",
+                config.name
+            );
+        }
+
+        Self {
+            config,
+            key,
+            queue: Arc::new(Mutex::new(VecDeque::new())),
+            requests: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Nazwa źródła
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// Liczba sygnałów oczekujących na odpytanie
+    pub async fn pending(&self) -> usize {
+        self.queue.lock().await.len()
+    }
+
+    /// Przyjmuje żądanie webhooka: podpis, limit, mapowanie na sygnał
+    pub async fn ingest(
+        &self,
+        body: &[u8],
+        timestamp: Option<&str>,
+        signature: Option<&str>,
+    ) -> Result<Signal, WebhookError> {
+        self.ingest_at(body, timestamp, signature, chrono::Utc::now().timestamp())
+            .await
+    }
+
+    /// Przyjmuje żądanie webhooka względem podanego czasu (Unix timestamp)
+    pub async fn ingest_at(
+        &self,
+        body: &[u8],
+        timestamp: Option<&str>,
+        signature: Option<&str>,
+        now: i64,
+    ) -> Result<Signal, WebhookError> {
+        let sent_at = self.verify_signature(body, timestamp, signature, now)?;
+        self.check_rate_limit(now).await?;
+
+        let mut signal = self.parse_payload(body)?;
+        signal.timestamp = sent_at;
+        signal.hash = Some(signal.calculate_hash());
+
+        let mut queue = self.queue.lock().await;
+        if self.config.max_pending > 0 && queue.len() >= self.config.max_pending {
+            return Err(WebhookError::QueueFull(queue.len()));
+        }
+        queue.push_back(signal.clone());
+
+        debug!(
+            "Webhook {} queued signal {} for {}",
+            self.config.name, signal.id, signal.token
+        );
+        Ok(signal)
+    }
+
+    /// Weryfikuje podpis HMAC-SHA256 wiadomości `<timestamp>.<body>`
+    /// (porównanie w stałym czasie) i świeżość podpisanego czasu
+    ///
+    /// Zwraca podpisany czas wysłania żądania.
+    pub fn verify_signature(
+        &self,
+        body: &[u8],
+        timestamp: Option<&str>,
+        signature: Option<&str>,
+        now: i64,
+    ) -> Result<i64, WebhookError> {
+        let key = self.key.as_ref().ok_or_else(|| {
+            WebhookError::Unauthorized("webhook secret not configured".to_string())
+        })?;
+        let signature =
+            signature.ok_or_else(|| WebhookError::Unauthorized("missing signature".to_string()))?;
+        let timestamp = timestamp
+            .map(str::trim)
+            .ok_or_else(|| WebhookError::Unauthorized("missing timestamp".to_string()))?;
+        let sent_at: i64 = timestamp
+            .parse()
+            .map_err(|_| WebhookError::Unauthorized("malformed timestamp".to_string()))?;
+
+        let signature = signature.trim();
+        let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+        let tag = hex::decode(signature)
+            .map_err(|_| WebhookError::Unauthorized("malformed signature".to_string()))?;
+
+        let mut message = Vec::with_capacity(timestamp.len() + 1 + body.len());
+        message.extend_from_slice(timestamp.as_bytes());
+        message.push(b'.');
+        message.extend_from_slice(body);
+        ring::hmac::verify(key, &message, &tag)
+            .map_err(|_| WebhookError::Unauthorized("invalid signature".to_string()))?;
+
+        if (now - sent_at).abs() > self.config.max_clock_skew {
+            return Err(WebhookError::Unauthorized(format!(
+                "stale timestamp {} (now {})",
+                sent_at, now
+            )));
+        }
+
+        Ok(sent_at)
+    }
+
+    /// Mapuje payload na sygnał zgodnie z szablonem
+    pub fn parse_payload(&self, body: &[u8]) -> Result<Signal, WebhookError> {
+        let payload: Value = serde_json::from_slice(body)
+            .map_err(|e| WebhookError::InvalidPayload(format!("not JSON: {}", e)))?;
+        let template = &self.config.template;
+
+        let token = payload
+            .pointer(&template.token)
+            .and_then(Value::as_str)
+            .map(|t| t.trim().to_uppercase())
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| {
+                WebhookError::InvalidPayload(format!("missing token at {}", template.token))
+            })?;
+
+        let side = match payload
+            .pointer(&template.side)
+            .and_then(Value::as_str)
+            .map(|s| s.trim().to_lowercase())
+            .as_deref()
+        {
+            Some("buy") | Some("long") => "long",
+            Some("sell") | Some("short") => "short",
+            other => {
+                return Err(WebhookError::InvalidPayload(format!(
+                    "invalid side at {}: {:?}",
+                    template.side, other
+                )))
+            }
+        };
+
+        let price = number_at(&payload, &template.price)
+            .filter(|p| *p > 0.0)
+            .ok_or_else(|| {
+                WebhookError::InvalidPayload(format!("missing price at {}", template.price))
+            })?;
+
+        let confidence = match payload
+            .pointer(&template.confidence)
+            .and_then(Value::as_str)
+        {
+            Some(raw) => raw
+                .parse::<Confidence>()
+                .map_err(|e| WebhookError::InvalidPayload(e.to_string()))?,
+            None => template
+                .default_confidence
+                .parse::<Confidence>()
+                .map_err(|e| WebhookError::InvalidPayload(e.to_string()))?,
+        };
+
+        let volume = number_at(&payload, &template.volume).unwrap_or(0.0);
+
+        Ok(Signal::new(
+            token,
+            self.config.name.clone(),
+            confidence,
+            price,
+            volume,
+            serde_json::json!({
+                "side": side,
+                "webhook": self.config.name,
+                "payload": payload
+            }),
+        ))
+    }
+
+    async fn check_rate_limit(&self, now: i64) -> Result<(), WebhookError> {
+        let limit = self.config.rate_limit_per_minute as usize;
+        if limit == 0 {
+            return Ok(());
+        }
+
+        let mut requests = self.requests.lock().await;
+        while requests
+            .front()
+            .is_some_and(|t| *t <= now - RATE_LIMIT_WINDOW)
+        {
+            requests.pop_front();
+        }
+
+        if requests.len() >= limit {
+            let oldest = requests.front().copied().unwrap_or(now);
+            return Err(WebhookError::RateLimited {
+                retry_after: (oldest + RATE_LIMIT_WINDOW - now).max(1),
+            });
+        }
+
+        requests.push_back(now);
+        Ok(())
+    }
+}
+
+/// Odczytuje liczbę (lub liczbę zapisaną jako tekst, np. `"{{close}}"` w TradingView)
+fn number_at(payload: &Value, pointer: &str) -> Option<f64> {
+    match payload.pointer(pointer)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+#[async_trait::async_trait]
+impl SignalSource for WebhookSource {
+    async fn get_signals(&self) -> Result<Vec<Signal>> {
+        Ok(self.queue.lock().await.drain(..).collect())
+    }
+
+    fn source_name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn is_connected(&self) -> bool {
+        self.key.is_some()
+    }
+
+    async fn connect(&mut self) -> Result<()> {
+        if self.key.is_none() {
+            anyhow::bail!("Webhook {} has no secret configured", self.config.name);
+        }
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        self.queue.lock().await.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::signals::WebhookTemplate;
+
+    fn source(rate_limit_per_minute: u32) -> WebhookSource {
+        WebhookSource::new(WebhookConfig {
+            name: "tradingview".to_string(),
+            secret: Some("s3cret".to_string()),
+            secret_env: None,
+            rate_limit_per_minute,
+            max_clock_skew: 300,
+            max_pending: 3,
+            template: WebhookTemplate {
+                token: "/ticker".to_string(),
+                side: "/strategy/order_action".to_string(),
+                price: "/close".to_string(),
+                ..WebhookTemplate::default()
+            },
+        })
+    }
+
+    const SENT_AT: i64 = 1_000;
+
+    fn sign_at(body: &[u8], timestamp: i64) -> String {
+        let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, b"s3cret");
+        let message = [format!("{}.", timestamp).as_bytes(), body].concat();
+        format!(
+            "sha256={}",
+            hex::encode(ring::hmac::sign(&key, &message).as_ref())
+        )
+    }
+
+    fn sign(body: &[u8]) -> String {
+        sign_at(body, SENT_AT)
+    }
+
+    async fn ingest(
+        source: &WebhookSource,
+        body: &[u8],
+        signature: Option<&str>,
+        now: i64,
+    ) -> Result<Signal, WebhookError> {
+        source
+            .ingest_at(body, Some(&SENT_AT.to_string()), signature, now)
+            .await
+    }
+
+    const BODY: &[u8] =
+        br#"{"ticker":"bonk","close":"0.0021","strategy":{"order_action":"buy"},"confidence":"high"}"#;
+
+    #[tokio::test]
+    async fn test_signed_payload_is_mapped_and_queued() {
+        let source = source(10);
+
+        let signal = ingest(&source, BODY, Some(&sign(BODY)), SENT_AT + 5)
+            .await
+            .unwrap();
+        assert_eq!(signal.token, "BONK");
+        assert_eq!(signal.source, "tradingview");
+        assert_eq!(signal.confidence, Confidence::High);
+        assert_eq!(signal.price, 0.0021);
+        assert_eq!(signal.metadata["side"], "long");
+        assert_eq!(signal.timestamp, SENT_AT);
+
+        assert_eq!(source.pending().await, 1);
+        let polled = source.get_signals().await.unwrap();
+        assert_eq!(polled[0].id, signal.id);
+        assert_eq!(source.pending().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_bad_signature_and_payload_are_rejected() {
+        let source = source(10);
+
+        assert!(matches!(
+            ingest(&source, BODY, None, SENT_AT).await,
+            Err(WebhookError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            ingest(&source, BODY, Some(&sign(b"other body")), SENT_AT).await,
+            Err(WebhookError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            source
+                .ingest_at(BODY, None, Some(&sign(BODY)), SENT_AT)
+                .await,
+            Err(WebhookError::Unauthorized(_))
+        ));
+
+        // Podpis obejmuje czas wysłania - podmiana nagłówka unieważnia podpis
+        assert!(matches!(
+            source
+                .ingest_at(BODY, Some("1001"), Some(&sign(BODY)), SENT_AT)
+                .await,
+            Err(WebhookError::Unauthorized(_))
+        ));
+
+        let body = br#"{"ticker":"BONK","close":1.0,"strategy":{"order_action":"hold"}}"#;
+        assert!(matches!(
+            ingest(&source, body, Some(&sign(body)), SENT_AT).await,
+            Err(WebhookError::InvalidPayload(_))
+        ));
+        assert_eq!(source.pending().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_stale_request_is_rejected() {
+        let source = source(10);
+        let signature = sign(BODY);
+
+        // Powtórzenie przechwyconego żądania po upływie okna
+        assert!(matches!(
+            ingest(&source, BODY, Some(&signature), SENT_AT + 301).await,
+            Err(WebhookError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            ingest(&source, BODY, Some(&signature), SENT_AT - 301).await,
+            Err(WebhookError::Unauthorized(_))
+        ));
+        assert_eq!(source.pending().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_window() {
+        let source = source(2);
+        let signature = sign(BODY);
+
+        // Niepodpisane żądania nie zużywają limitu
+        for _ in 0..3 {
+            assert!(matches!(
+                ingest(&source, BODY, None, 1_000).await,
+                Err(WebhookError::Unauthorized(_))
+            ));
+        }
+
+        ingest(&source, BODY, Some(&signature), 1_000)
+            .await
+            .unwrap();
+        ingest(&source, BODY, Some(&signature), 1_010)
+            .await
+            .unwrap();
+        assert!(matches!(
+            ingest(&source, BODY, Some(&signature), 1_020).await,
+            Err(WebhookError::RateLimited { retry_after: 40 })
+        ));
+
+        // Po minucie od pierwszego żądania zwalnia się miejsce
+        ingest(&source, BODY, Some(&signature), 1_060)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_queue_is_bounded() {
+        let source = source(0);
+        let signature = sign(BODY);
+
+        for _ in 0..3 {
+            ingest(&source, BODY, Some(&signature), SENT_AT)
+                .await
+                .unwrap();
+        }
+        assert!(matches!(
+            ingest(&source, BODY, Some(&signature), SENT_AT).await,
+            Err(WebhookError::QueueFull(3))
+        ));
+
+        source.get_signals().await.unwrap();
+        ingest(&source, BODY, Some(&signature), SENT_AT)
+            .await
+            .unwrap();
+    }
+}
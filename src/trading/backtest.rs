@@ -0,0 +1,866 @@
+use anyhow::{Context, Result};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, info};
+
+use super::{
+    Clock, ExitReason, PaperExchange, PositionExit, TradeDecision, TradeExecutorTrait,
+    TradingEngine, TradingStats,
+};
+use crate::alerts::AlertManager;
+use crate::config::{AlertsConfig, Config};
+use crate::database::{SignalStore, TradeLedger};
+use crate::monitoring::SystemMetrics;
+use crate::risk::{CircuitBreaker, EmergencyStop, Position, RiskManager, TradeSide};
+use crate::signals::{Confidence, Signal, SignalDecision, SignalProcessor};
+
+/// Źródło sygnałów wczytanych z pliku bez kolumny `source`
+const DEFAULT_SOURCE: &str = "backtest";
+
+/// Świeca OHLCV
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+    /// Token/symbol
+    pub token: String,
+
+    /// Początek świecy (Unix timestamp lub RFC 3339 w pliku)
+    #[serde(deserialize_with = "deserialize_timestamp")]
+    pub timestamp: i64,
+
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+
+    #[serde(default)]
+    pub volume: f64,
+}
+
+impl Candle {
+    /// Przybliżona ścieżka ceny wewnątrz świecy
+    ///
+    /// Świeca wzrostowa: open -> low -> high -> close,
+    /// spadkowa: open -> high -> low -> close.
+    pub fn price_path(&self) -> [f64; 4] {
+        if self.close >= self.open {
+            [self.open, self.low, self.high, self.close]
+        } else {
+            [self.open, self.high, self.low, self.close]
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.token.is_empty() {
+            anyhow::bail!("Candle token cannot be empty");
+        }
+        if [self.open, self.high, self.low, self.close]
+            .iter()
+            .any(|price| *price <= 0.0)
+        {
+            anyhow::bail!("Candle prices must be greater than 0");
+        }
+        if self.low > self.high {
+            anyhow::bail!("Candle low {} above high {}", self.low, self.high);
+        }
+        Ok(())
+    }
+}
+
+/// Sygnał zapisany w pliku historycznym
+#[derive(Debug, Clone, Deserialize)]
+struct SignalRecord {
+    #[serde(deserialize_with = "deserialize_timestamp")]
+    timestamp: i64,
+    token: String,
+    #[serde(default)]
+    source: Option<String>,
+    confidence: String,
+    price: f64,
+    #[serde(default)]
+    volume: f64,
+    #[serde(default)]
+    side: Option<String>,
+}
+
+impl SignalRecord {
+    fn into_signal(self) -> Result<Signal> {
+        let side = match self
+            .side
+            .as_deref()
+            .map(|s| s.trim().to_lowercase())
+            .as_deref()
+        {
+            None | Some("") | Some("buy") | Some("long") => "long",
+            Some("sell") | Some("short") => "short",
+            Some(other) => anyhow::bail!("Invalid signal side: {}", other),
+        };
+
+        Ok(Signal::new_with_timestamp(
+            self.token.trim().to_uppercase(),
+            self.source.unwrap_or_else(|| DEFAULT_SOURCE.to_string()),
+            self.confidence.parse::<Confidence>()?,
+            self.price,
+            self.volume,
+            serde_json::json!({ "side": side, "backtest": true }),
+            self.timestamp,
+        ))
+    }
+}
+
+/// Wczytuje świece z pliku JSON (tablica obiektów) lub CSV z nagłówkiem
+/// `timestamp,token,open,high,low,close[,volume]`
+pub fn load_candles(path: &Path) -> Result<Vec<Candle>> {
+    let candles: Vec<Candle> = if is_json(path) {
+        serde_json::from_str(&read(path)?)
+            .with_context(|| format!("Failed to parse candles from {}", path.display()))?
+    } else {
+        let content = read(path)?;
+        CsvTable::parse(&content)?
+            .rows()
+            .map(|row| {
+                Ok(Candle {
+                    token: row.text("token")?.to_uppercase(),
+                    timestamp: parse_timestamp(row.text("timestamp")?)?,
+                    open: row.number("open")?,
+                    high: row.number("high")?,
+                    low: row.number("low")?,
+                    close: row.number("close")?,
+                    volume: row.optional_number("volume")?.unwrap_or(0.0),
+                })
+            })
+            .collect::<Result<_>>()
+            .with_context(|| format!("Failed to parse candles from {}", path.display()))?
+    };
+
+    for candle in &candles {
+        candle
+            .validate()
+            .with_context(|| format!("Invalid candle {} @ {}", candle.token, candle.timestamp))?;
+    }
+    Ok(candles)
+}
+
+/// Wczytuje sygnały z pliku JSON (tablica obiektów) lub CSV z nagłówkiem
+/// `timestamp,token,confidence,price[,volume][,side][,source]`
+pub fn load_signals(path: &Path) -> Result<Vec<Signal>> {
+    let records: Vec<SignalRecord> = if is_json(path) {
+        serde_json::from_str(&read(path)?)
+            .with_context(|| format!("Failed to parse signals from {}", path.display()))?
+    } else {
+        let content = read(path)?;
+        CsvTable::parse(&content)?
+            .rows()
+            .map(|row| {
+                Ok(SignalRecord {
+                    timestamp: parse_timestamp(row.text("timestamp")?)?,
+                    token: row.text("token")?.to_string(),
+                    source: row.optional_text("source").map(str::to_string),
+                    confidence: row.text("confidence")?.to_string(),
+                    price: row.number("price")?,
+                    volume: row.optional_number("volume")?.unwrap_or(0.0),
+                    side: row.optional_text("side").map(str::to_string),
+                })
+            })
+            .collect::<Result<_>>()
+            .with_context(|| format!("Failed to parse signals from {}", path.display()))?
+    };
+
+    records.into_iter().map(SignalRecord::into_signal).collect()
+}
+
+/// Transakcja zamknięta (w całości lub częściowo) w trakcie backtestu
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestTrade {
+    pub position_id: String,
+    pub token: String,
+    pub side: TradeSide,
+    pub leverage: u8,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    pub opened_at: i64,
+    pub closed_at: i64,
+    pub reason: ExitReason,
+
+    /// Zrealizowany P&L po opłacie zamknięcia
+    pub realized_pnl: f64,
+}
+
+/// Punkt krzywej kapitału
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub timestamp: i64,
+    pub equity: f64,
+}
+
+/// Raport z backtestu
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    /// Zakres odtworzonych danych (Unix timestamp)
+    pub started_at: i64,
+    pub finished_at: i64,
+
+    pub candles: usize,
+    pub signals_received: usize,
+    pub signals_accepted: usize,
+    pub positions_opened: usize,
+
+    /// Pozycje otwarte na końcu danych (wycenione w `final_equity`)
+    pub open_positions: usize,
+
+    pub initial_balance: f64,
+    pub final_equity: f64,
+
+    /// Zwrot względem balansu początkowego (0.1 = 10%)
+    pub total_return: f64,
+
+    /// Największy spadek kapitału od szczytu (0.1 = 10%)
+    pub max_drawdown: f64,
+
+    /// Roczny współczynnik Sharpe'a z krzywej kapitału (bez stopy wolnej od ryzyka)
+    pub sharpe_ratio: f64,
+
+    /// Udział zamknięć z dodatnim P&L
+    pub win_rate: f64,
+
+    /// Opłaty za otwarcia i zamknięcia
+    pub total_fees: f64,
+
+    /// Statystyki zamknięć (P&L, najlepsza/najgorsza transakcja)
+    pub stats: TradingStats,
+
+    pub trades: Vec<BacktestTrade>,
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+impl BacktestReport {
+    /// Krótkie podsumowanie do wypisania w konsoli
+    pub fn summary(&self) -> String {
+        format!(
+            "Backtest {} -> {} ({} candles)\n\
+             Signals: {} received, {} accepted, {} positions opened ({} still open)\n\
+             Equity: {:.2} -> {:.2} ({:+.2}%)\n\
+             Trades: {} (win rate {:.1}%), P&L {:.2}, fees {:.2}, funding {:.2}\n\
+             Max drawdown: {:.2}%, Sharpe: {:.2}",
+            format_timestamp(self.started_at),
+            format_timestamp(self.finished_at),
+            self.candles,
+            self.signals_received,
+            self.signals_accepted,
+            self.positions_opened,
+            self.open_positions,
+            self.initial_balance,
+            self.final_equity,
+            self.total_return * 100.0,
+            self.trades.len(),
+            self.win_rate * 100.0,
+            self.stats.total_pnl,
+            self.total_fees,
+            self.stats.total_funding,
+            self.max_drawdown * 100.0,
+            self.sharpe_ratio,
+        )
+    }
+}
+
+/// Backtest: odtwarza świece i sygnały przez ten sam pipeline co na żywo
+///
+/// Sygnały przechodzą przez `SignalProcessor`, decyzje i wyjścia podejmuje
+/// `TradingEngine` z managerem ryzyka, a zlecenia realizuje `PaperExchange`.
+/// Wszystkie komponenty działają na zegarze symulacji i bazie w pamięci,
+/// bez dostępu do sieci. Sygnał z czasem nie późniejszym niż początek świecy
+/// jest przetwarzany przed nią, po ostatniej znanej cenie.
+pub struct Backtester {
+    config: Config,
+}
+
+impl Backtester {
+    /// Tworzy backtester dla konfiguracji aplikacji
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+
+    /// Uruchamia backtest na wczytanych danych
+    pub async fn run(
+        &self,
+        mut candles: Vec<Candle>,
+        mut signals: Vec<Signal>,
+    ) -> Result<BacktestReport> {
+        if candles.is_empty() {
+            anyhow::bail!("Backtest requires at least one candle");
+        }
+        candles.sort_by(|a, b| {
+            a.timestamp
+                .cmp(&b.timestamp)
+                .then_with(|| a.token.cmp(&b.token))
+        });
+        signals.sort_by_key(|signal| signal.timestamp);
+
+        let started_at = candles[0]
+            .timestamp
+            .min(signals.first().map_or(i64::MAX, |signal| signal.timestamp));
+        let clock = Clock::simulated(started_at);
+        let simulation = Simulation::new(&self.config, clock.clone()).await?;
+
+        info!(
+            "Backtesting {} candles and {} signals from {}",
+            candles.len(),
+            signals.len(),
+            format_timestamp(started_at)
+        );
+
+        let mut opened: HashMap<String, Position> = HashMap::new();
+        let mut trades = Vec::new();
+        let mut equity_curve: Vec<EquityPoint> = Vec::new();
+        let mut signals_accepted = 0;
+        let mut pending = signals.iter().peekable();
+
+        for candle in &candles {
+            while let Some(signal) = pending.next_if(|s| s.timestamp <= candle.timestamp) {
+                simulation.set_time(signal.timestamp).await;
+                match simulation.handle_signal(signal).await? {
+                    Some(TradeDecision::Opened { position, .. }) => {
+                        signals_accepted += 1;
+                        opened.insert(position.id.clone(), position);
+                    }
+                    Some(_) => signals_accepted += 1,
+                    None => {}
+                }
+            }
+
+            simulation.set_time(candle.timestamp).await;
+            for price in candle.price_path() {
+                simulation.exchange.update_price(&candle.token, price).await;
+                for exit in simulation.engine.check_positions().await? {
+                    trades.push(trade_record(&opened, exit, candle.timestamp));
+                }
+            }
+
+            let point = EquityPoint {
+                timestamp: candle.timestamp,
+                equity: simulation.exchange.equity().await,
+            };
+            match equity_curve.last_mut() {
+                Some(last) if last.timestamp == point.timestamp => *last = point,
+                _ => equity_curve.push(point),
+            }
+        }
+
+        // Sygnały po ostatniej świecy nie mają już danych cenowych
+        let skipped = pending.count();
+        if skipped > 0 {
+            debug!(
+                "{} signals after the last candle were not replayed",
+                skipped
+            );
+        }
+
+        let initial_balance = self.config.trading.initial_balance.to_f64().unwrap_or(0.0);
+        let final_equity = simulation.exchange.equity().await;
+        let stats = simulation.closing_stats().await;
+        let wins = trades.iter().filter(|t| t.realized_pnl > 0.0).count();
+
+        Ok(BacktestReport {
+            started_at,
+            finished_at: candles.last().map_or(started_at, |c| c.timestamp),
+            candles: candles.len(),
+            signals_received: signals.len(),
+            signals_accepted,
+            positions_opened: opened.len(),
+            open_positions: simulation.exchange.open_positions().await.len(),
+            initial_balance,
+            final_equity,
+            total_return: if initial_balance > 0.0 {
+                final_equity / initial_balance - 1.0
+            } else {
+                0.0
+            },
+            max_drawdown: max_drawdown(&equity_curve),
+            sharpe_ratio: sharpe_ratio(&equity_curve),
+            win_rate: if trades.is_empty() {
+                0.0
+            } else {
+                wins as f64 / trades.len() as f64
+            },
+            total_fees: simulation.exchange.total_fees().await,
+            stats,
+            trades,
+            equity_curve,
+        })
+    }
+}
+
+/// Komponenty pipeline'u złożone na zegarze symulacji
+struct Simulation {
+    clock: Clock,
+    exchange: Arc<PaperExchange>,
+    processor: SignalProcessor,
+    engine: TradingEngine,
+}
+
+impl Simulation {
+    async fn new(config: &Config, clock: Clock) -> Result<Self> {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .context("Failed to open in-memory database")?;
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .context("Failed to run database migrations")?;
+        let pool = Arc::new(pool);
+
+        let exchange = Arc::new(PaperExchange::from_config(&config.trading));
+        exchange.set_time(clock.now()).await;
+
+        let ledger = Arc::new(TradeLedger::new(pool.clone()));
+        let circuit_breaker = Arc::new(CircuitBreaker::new(&config.risk));
+        let risk_manager = Arc::new(
+            RiskManager::from_config(config)
+                .with_circuit_breaker(circuit_breaker.clone())
+                .with_clock(clock.clone()),
+        );
+        let emergency_stop = Arc::new(
+            EmergencyStop::new(
+                exchange.clone(),
+                ledger.clone(),
+                circuit_breaker.clone(),
+                pool.clone(),
+                false,
+            )
+            .await?
+            .with_risk_manager(risk_manager.clone()),
+        );
+
+        // Alerty wyłączone - backtest działa bez sieci
+        let alert_manager = AlertManager::from_config(&AlertsConfig {
+            enabled: false,
+            ..config.alerts.clone()
+        });
+
+        let engine = TradingEngine::new(
+            exchange.clone(),
+            risk_manager,
+            ledger,
+            circuit_breaker,
+            emergency_stop,
+            Arc::new(Mutex::new(alert_manager)),
+            Arc::new(RwLock::new(SystemMetrics::new())),
+        )
+        .with_clock(clock.clone());
+
+        let mut signals_config = config.signals.clone();
+        signals_config.webhooks.clear();
+        let processor = SignalProcessor::new(signals_config, SignalStore::new(pool));
+
+        Ok(Self {
+            clock,
+            exchange,
+            processor,
+            engine,
+        })
+    }
+
+    async fn set_time(&self, timestamp: i64) {
+        self.clock.set(timestamp);
+        self.exchange.set_time(timestamp).await;
+    }
+
+    /// Przepuszcza sygnał przez procesor i silnik
+    ///
+    /// Zwraca decyzję silnika albo `None`, jeśli procesor odrzucił sygnał.
+    async fn handle_signal(&self, signal: &Signal) -> Result<Option<TradeDecision>> {
+        // Przed pierwszą świecą tokena jedyną znaną ceną jest cena sygnału
+        if self
+            .exchange
+            .get_current_price(&signal.token)
+            .await
+            .is_err()
+        {
+            self.exchange
+                .update_price(&signal.token, signal.price)
+                .await;
+        }
+
+        let scored = match self
+            .processor
+            .process_at(signal.clone(), signal.timestamp)
+            .await?
+        {
+            SignalDecision::Accepted(scored) => scored,
+            SignalDecision::Rejected(rejection) => {
+                debug!("Signal {} rejected: {}", signal.id, rejection);
+                return Ok(None);
+            }
+        };
+
+        Ok(Some(self.engine.handle_signal(&scored).await?))
+    }
+
+    /// Statystyki zamknięć na podstawie wykonań symulatora
+    async fn closing_stats(&self) -> TradingStats {
+        let mut stats = TradingStats::default();
+
+        for fill in self.exchange.fills().await {
+            let closing = matches!(
+                fill.metadata.get("action").and_then(|a| a.as_str()),
+                Some("close") | Some("reduce")
+            );
+            if !fill.success || !closing {
+                continue;
+            }
+
+            let realized = fill
+                .metadata
+                .get("realized_pnl")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0);
+            stats.update_for_trade(&fill, 0.0, realized - fill.fees.unwrap_or(0.0));
+        }
+
+        stats.total_fees = self.exchange.total_fees().await;
+        stats.total_funding = self.exchange.total_funding().await;
+        stats.last_updated = self.clock.now();
+        stats
+    }
+}
+
+fn trade_record(opened: &HashMap<String, Position>, exit: PositionExit, at: i64) -> BacktestTrade {
+    let position = opened.get(&exit.position_id);
+
+    BacktestTrade {
+        side: position.map_or(TradeSide::Long, |p| p.side.clone()),
+        leverage: position.map_or(1, |p| p.leverage),
+        entry_price: position.map_or(0.0, |p| p.entry_price),
+        opened_at: position.map_or(at, |p| p.opened_at),
+        closed_at: at,
+        position_id: exit.position_id,
+        token: exit.token,
+        exit_price: exit.exit_price,
+        reason: exit.reason,
+        realized_pnl: exit.realized_pnl,
+    }
+}
+
+/// Największy względny spadek kapitału od poprzedniego szczytu
+fn max_drawdown(curve: &[EquityPoint]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut drawdown: f64 = 0.0;
+
+    for point in curve {
+        peak = peak.max(point.equity);
+        if peak > 0.0 {
+            drawdown = drawdown.max((peak - point.equity) / peak);
+        }
+    }
+    drawdown
+}
+
+/// Roczny współczynnik Sharpe'a ze zwrotów między punktami krzywej
+///
+/// Liczba okresów w roku wynika z mediany odstępu między punktami.
+fn sharpe_ratio(curve: &[EquityPoint]) -> f64 {
+    let returns: Vec<f64> = curve
+        .windows(2)
+        .filter(|w| w[0].equity > 0.0)
+        .map(|w| w[1].equity / w[0].equity - 1.0)
+        .collect();
+    if returns.len() < 2 {
+        return 0.0;
+    }
+
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance =
+        returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    let std_dev = variance.sqrt();
+    if std_dev <= f64::EPSILON {
+        return 0.0;
+    }
+
+    let mut intervals: Vec<i64> = curve
+        .windows(2)
+        .map(|w| w[1].timestamp - w[0].timestamp)
+        .filter(|interval| *interval > 0)
+        .collect();
+    intervals.sort_unstable();
+    let periods_per_year = match intervals.get(intervals.len() / 2) {
+        Some(interval) => 365.0 * 24.0 * 3600.0 / *interval as f64,
+        None => return 0.0,
+    };
+
+    mean / std_dev * periods_per_year.sqrt()
+}
+
+fn read(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// Unix timestamp (sekundy) lub data RFC 3339
+fn parse_timestamp(value: &str) -> Result<i64> {
+    let value = value.trim();
+    if let Ok(timestamp) = value.parse::<i64>() {
+        return Ok(timestamp);
+    }
+
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.timestamp())
+        .with_context(|| format!("Invalid timestamp: {}", value))
+}
+
+fn deserialize_timestamp<'de, D>(deserializer: D) -> std::result::Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Unix(i64),
+        Text(String),
+    }
+
+    match Raw::deserialize(deserializer)? {
+        Raw::Unix(timestamp) => Ok(timestamp),
+        Raw::Text(text) => parse_timestamp(&text).map_err(serde::de::Error::custom),
+    }
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+/// Prosta tabela CSV: nagłówek w pierwszej linii, separator `,`, bez cudzysłowów.
+/// Puste linie i linie zaczynające się od `#` są pomijane.
+struct CsvTable<'a> {
+    columns: HashMap<String, usize>,
+    rows: Vec<(usize, Vec<&'a str>)>,
+}
+
+impl<'a> CsvTable<'a> {
+    fn parse(content: &'a str) -> Result<Self> {
+        let mut lines = content
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let (_, header) = lines.next().context("CSV file is empty")?;
+        let columns = header
+            .split(',')
+            .enumerate()
+            .map(|(index, name)| (name.trim().to_lowercase(), index))
+            .collect();
+        let rows = lines
+            .map(|(line, row)| (line, row.split(',').map(str::trim).collect()))
+            .collect();
+
+        Ok(Self { columns, rows })
+    }
+
+    fn rows(&self) -> impl Iterator<Item = CsvRow<'_>> {
+        self.rows.iter().map(|(line, values)| CsvRow {
+            line: *line,
+            columns: &self.columns,
+            values,
+        })
+    }
+}
+
+/// Wiersz tabeli CSV dostępny po nazwach kolumn z nagłówka
+struct CsvRow<'t> {
+    line: usize,
+    columns: &'t HashMap<String, usize>,
+    values: &'t [&'t str],
+}
+
+impl CsvRow<'_> {
+    fn optional_text(&self, column: &str) -> Option<&str> {
+        self.columns
+            .get(column)
+            .and_then(|&index| self.values.get(index))
+            .copied()
+            .filter(|value| !value.is_empty())
+    }
+
+    fn text(&self, column: &str) -> Result<&str> {
+        self.optional_text(column)
+            .with_context(|| format!("line {}: missing value for '{}'", self.line, column))
+    }
+
+    fn optional_number(&self, column: &str) -> Result<Option<f64>> {
+        self.optional_text(column)
+            .map(|value| {
+                value.parse::<f64>().with_context(|| {
+                    format!(
+                        "line {}: invalid number '{}' in '{}'",
+                        self.line, value, column
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    fn number(&self, column: &str) -> Result<f64> {
+        self.optional_number(column)?
+            .with_context(|| format!("line {}: missing value for '{}'", self.line, column))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(token: &str, timestamp: i64, open: f64, high: f64, low: f64, close: f64) -> Candle {
+        Candle {
+            token: token.to_string(),
+            timestamp,
+            open,
+            high,
+            low,
+            close,
+            volume: 1000.0,
+        }
+    }
+
+    #[test]
+    fn test_csv_and_json_inputs_are_parsed() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let candles_csv = dir.path().join("candles.csv");
+        std::fs::write(
+            &candles_csv,
+            "# BONK 1h\ntimestamp,token,open,high,low,close,volume\n\
+             1700000000,bonk,1.0,1.1,0.9,1.05,500\n\
+             2023-11-14T23:13:20Z,BONK,1.05,1.2,1.0,1.1,\n",
+        )
+        .unwrap();
+        let candles = load_candles(&candles_csv).unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].token, "BONK");
+        assert_eq!(candles[1].timestamp, 1_700_003_600);
+        assert_eq!(candles[1].volume, 0.0);
+
+        let signals_json = dir.path().join("signals.json");
+        std::fs::write(
+            &signals_json,
+            r#"[{"timestamp": "2023-11-14T22:13:20Z", "token": "wif", "confidence": "high",
+                 "price": 2.5, "side": "sell", "source": "tradingview"}]"#,
+        )
+        .unwrap();
+        let signals = load_signals(&signals_json).unwrap();
+        assert_eq!(signals[0].token, "WIF");
+        assert_eq!(signals[0].timestamp, 1_700_000_000);
+        assert_eq!(signals[0].confidence, Confidence::High);
+        assert_eq!(signals[0].metadata["side"], "short");
+
+        let bad = dir.path().join("bad.csv");
+        std::fs::write(
+            &bad,
+            "timestamp,token,open,high,low,close\n1,BONK,1,0.5,0.9,1\n",
+        )
+        .unwrap();
+        assert!(load_candles(&bad).is_err());
+    }
+
+    #[test]
+    fn test_drawdown_and_sharpe() {
+        let curve: Vec<EquityPoint> = [100.0, 110.0, 99.0, 104.5, 120.0]
+            .iter()
+            .enumerate()
+            .map(|(i, equity)| EquityPoint {
+                timestamp: i as i64 * 86_400,
+                equity: *equity,
+            })
+            .collect();
+
+        assert!((max_drawdown(&curve) - 0.1).abs() < 1e-9);
+        assert!(sharpe_ratio(&curve) > 0.0);
+        assert_eq!(sharpe_ratio(&curve[..2]), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_replay_opens_and_closes_on_simulated_clock() {
+        let mut config = Config::default();
+        config.trading.initial_balance = rust_decimal::Decimal::new(1000, 0);
+
+        // Dane sprzed lat - działają tylko na zegarze symulacji
+        let start = 1_600_000_000;
+        let candles = vec![
+            candle("BONK", start, 1.0, 1.0, 1.0, 1.0),
+            candle("BONK", start + 3600, 1.0, 1.005, 0.998, 1.004),
+            candle("BONK", start + 7200, 1.004, 1.018, 1.0, 1.017),
+            candle("WIF", start + 7200, 2.0, 2.0, 2.0, 2.0),
+            candle("BONK", start + 10800, 1.017, 1.03, 1.015, 1.028),
+            candle("WIF", start + 10800, 2.0, 2.01, 1.9, 1.92),
+        ];
+        let side = |side: &str| serde_json::json!({ "side": side });
+        let signals = vec![
+            Signal::new_with_timestamp(
+                "BONK".to_string(),
+                "backtest".to_string(),
+                Confidence::Medium,
+                1.0,
+                0.0,
+                side("long"),
+                start + 60,
+            ),
+            Signal::new_with_timestamp(
+                "WIF".to_string(),
+                "backtest".to_string(),
+                Confidence::Medium,
+                2.0,
+                0.0,
+                side("long"),
+                start + 7300,
+            ),
+            // Zbyt niski wynik (0.5 × 0.25) - odrzucony przez procesor
+            Signal::new_with_timestamp(
+                "POPCAT".to_string(),
+                "backtest".to_string(),
+                Confidence::Low,
+                0.5,
+                0.0,
+                side("long"),
+                start + 120,
+            ),
+        ];
+
+        let report = Backtester::new(config).run(candles, signals).await.unwrap();
+
+        assert_eq!(report.signals_received, 3);
+        assert_eq!(report.signals_accepted, 2);
+        assert_eq!(report.positions_opened, 2);
+        assert_eq!(report.open_positions, 0);
+
+        // BONK: częściowy TP, potem reszta na TP; WIF: stop-loss
+        let reasons: Vec<_> = report.trades.iter().map(|t| t.reason.clone()).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ExitReason::PartialTakeProfit,
+                ExitReason::TakeProfit,
+                ExitReason::StopLoss
+            ]
+        );
+        assert!(report.trades.iter().all(|t| t.closed_at >= start));
+        assert_eq!(report.trades[2].token, "WIF");
+        assert!(report.trades[2].realized_pnl < 0.0);
+
+        assert_eq!(report.stats.total_trades, 3);
+        assert!((report.win_rate - 2.0 / 3.0).abs() < 1e-9);
+        assert!(report.total_fees > 0.0);
+        assert_eq!(report.equity_curve.len(), 4);
+        assert!(report.max_drawdown > 0.0);
+        assert!((report.final_equity - report.equity_curve.last().unwrap().equity).abs() < 1e-9);
+    }
+}
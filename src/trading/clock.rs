@@ -0,0 +1,35 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+/// Źródło czasu dla logiki tradingu i ryzyka
+///
+/// Na żywo to zegar systemowy; backtest przestawia zegar symulacji zgodnie
+/// z odtwarzanymi danymi. Klony zegara symulacji dzielą ten sam czas.
+#[derive(Debug, Clone, Default)]
+pub enum Clock {
+    #[default]
+    System,
+    Simulated(Arc<AtomicI64>),
+}
+
+impl Clock {
+    /// Tworzy zegar symulacji ustawiony na podany czas
+    pub fn simulated(timestamp: i64) -> Self {
+        Clock::Simulated(Arc::new(AtomicI64::new(timestamp)))
+    }
+
+    /// Aktualny czas (Unix timestamp)
+    pub fn now(&self) -> i64 {
+        match self {
+            Clock::System => chrono::Utc::now().timestamp(),
+            Clock::Simulated(time) => time.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Przestawia zegar symulacji (dla zegara systemowego bez efektu)
+    pub fn set(&self, timestamp: i64) {
+        if let Clock::Simulated(time) = self {
+            time.store(timestamp, Ordering::SeqCst);
+        }
+    }
+}
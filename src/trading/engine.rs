@@ -0,0 +1,1260 @@
+use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, error, info, warn};
+
+use super::{Clock, OrderStatus, TradeExecutorTrait, TradeOrder};
+use crate::alerts::AlertManager;
+use crate::database::TradeLedger;
+use crate::monitoring::{SystemAlert, SystemMetrics};
+use crate::risk::{
+    CircuitBreaker, EmergencyReport, EmergencyStop, EmergencyTrigger, ExitAction, ExitManager,
+    Position, ProtectiveOrder, ProtectiveRole, RiskManager, RiskManagerTrait, TradeSide,
+};
+use crate::signals::{ScoredSignal, Signal};
+
+/// Tolerancja przy porównywaniu rozmiaru zamknięcia z rozmiarem pozycji
+const SIZE_EPSILON: f64 = 1e-9;
+
+/// Decyzja podjęta dla sygnału
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TradeDecision {
+    /// Zlecenie wykonane, pozycja otwarta
+    Opened {
+        order_id: String,
+        position: Position,
+    },
+
+    /// Ocena ryzyka lub wykonanie odrzuciły sygnał
+    Rejected { reason: String },
+
+    /// Sygnał pominięty (handel wstrzymany, pozycja już otwarta)
+    Skipped { reason: String },
+
+    /// Sygnał awaryjny uruchomił emergency stop
+    EmergencyStop { report: EmergencyReport },
+}
+
+/// Powód zamknięcia pozycji przez pętlę tradingu
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ExitReason {
+    StopLoss,
+    TakeProfit,
+    PartialTakeProfit,
+    MaxDuration,
+    NearLiquidation,
+    MaxLoss,
+    Deleverage,
+}
+
+impl std::fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExitReason::StopLoss => write!(f, "stop_loss"),
+            ExitReason::TakeProfit => write!(f, "take_profit"),
+            ExitReason::PartialTakeProfit => write!(f, "partial_take_profit"),
+            ExitReason::MaxDuration => write!(f, "max_duration"),
+            ExitReason::NearLiquidation => write!(f, "near_liquidation"),
+            ExitReason::MaxLoss => write!(f, "max_loss"),
+            ExitReason::Deleverage => write!(f, "deleverage"),
+        }
+    }
+}
+
+/// Pozycja zamknięta (w całości lub częściowo) przez pętlę tradingu
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionExit {
+    pub position_id: String,
+    pub token: String,
+    pub reason: ExitReason,
+    pub exit_price: f64,
+    pub realized_pnl: f64,
+}
+
+/// Silnik decyzyjny: sygnał -> ocena ryzyka -> zlecenie -> pozycja,
+/// oraz cykliczna wycena i zamykanie otwartych pozycji
+pub struct TradingEngine {
+    executor: Arc<dyn TradeExecutorTrait>,
+    risk_manager: Arc<RiskManager>,
+    ledger: Arc<TradeLedger>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    emergency_stop: Arc<EmergencyStop>,
+    alert_manager: Arc<Mutex<AlertManager>>,
+    metrics: Arc<RwLock<SystemMetrics>>,
+    exits: Mutex<ExitManager>,
+    clock: Clock,
+
+    /// Dzień UTC dziennego P&L portfela (numer dnia od epoki)
+    trading_day: Mutex<Option<i64>>,
+}
+
+impl TradingEngine {
+    /// Tworzy silnik na współdzielonych komponentach aplikacji
+    pub fn new(
+        executor: Arc<dyn TradeExecutorTrait>,
+        risk_manager: Arc<RiskManager>,
+        ledger: Arc<TradeLedger>,
+        circuit_breaker: Arc<CircuitBreaker>,
+        emergency_stop: Arc<EmergencyStop>,
+        alert_manager: Arc<Mutex<AlertManager>>,
+        metrics: Arc<RwLock<SystemMetrics>>,
+    ) -> Self {
+        let exits = Mutex::new(ExitManager::new(risk_manager.risk_config().clone()));
+
+        Self {
+            executor,
+            risk_manager,
+            ledger,
+            circuit_breaker,
+            emergency_stop,
+            alert_manager,
+            metrics,
+            exits,
+            clock: Clock::System,
+            trading_day: Mutex::new(None),
+        }
+    }
+
+    /// Ustawia zegar (np. zegar symulacji w backteście)
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Strona transakcji z `metadata.side` sygnału (domyślnie Long)
+    pub fn side_for_signal(signal: &Signal) -> TradeSide {
+        signal.side()
+    }
+
+    /// Odtwarza plany wyjścia otwartych pozycji z zleceń ochronnych w rejestrze
+    ///
+    /// Wywoływane po restarcie; pozycje bez zapisanych zleceń ochronnych
+    /// zamykane są wg warunków z `exit_reason`. Zwraca liczbę odtworzonych planów.
+    pub async fn restore_exit_plans(&self) -> Result<usize> {
+        let mut orders: HashMap<String, Vec<ProtectiveOrder>> = HashMap::new();
+        for record in self.ledger.open_orders().await? {
+            let Some(protective) = ProtectiveOrder::from_order(record.order) else {
+                continue;
+            };
+            if let Some(position_id) = protective.position_id() {
+                orders
+                    .entry(position_id.to_string())
+                    .or_default()
+                    .push(protective);
+            }
+        }
+
+        let mut exits = self.exits.lock().await;
+        let mut restored = 0;
+        for position in self.ledger.load_open_positions().await? {
+            let protective = orders.remove(&position.id).unwrap_or_default();
+            if exits.restore(&position, protective) {
+                restored += 1;
+            }
+        }
+        Ok(restored)
+    }
+
+    /// Przetwarza przyjęty sygnał; czas decyzji trafia do `SystemMetrics`
+    pub async fn handle_signal(&self, scored: &ScoredSignal) -> Result<TradeDecision> {
+        let started = std::time::Instant::now();
+        let decision = self.decide(&scored.signal).await;
+        let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+        {
+            let mut metrics = self.metrics.write().await;
+            metrics.total_signals += 1;
+            metrics.add_decision_time(elapsed_ms);
+        }
+
+        match &decision {
+            Ok(TradeDecision::Opened { position, .. }) => info!(
+                "Signal {} -> opened {:?} {} size {:.2} @ {:.8} ({}x) in {:.2}ms",
+                scored.signal.id,
+                position.side,
+                position.token,
+                position.size,
+                position.entry_price,
+                position.leverage,
+                elapsed_ms
+            ),
+            Ok(TradeDecision::Rejected { reason }) => {
+                info!("Signal {} rejected: {}", scored.signal.id, reason)
+            }
+            Ok(TradeDecision::Skipped { reason }) => {
+                debug!("Signal {} skipped: {}", scored.signal.id, reason)
+            }
+            Ok(TradeDecision::EmergencyStop { report }) => warn!(
+                "Signal {} -> emergency stop: {} orders cancelled, {} positions closed",
+                scored.signal.id,
+                report.cancelled_count(),
+                report.closed_count()
+            ),
+            Err(e) => error!("Signal {} failed: {}", scored.signal.id, e),
+        }
+
+        decision
+    }
+
+    /// Wycenia otwarte pozycje i zamyka te, które spełniają warunki wyjścia
+    ///
+    /// Zlecenia ochronne (stop-loss, trailing stop, częściowy take-profit)
+    /// aktywuje `ExitManager`; częściowe zamknięcia również trafiają do wyniku.
+    /// Po wycenie wszystkich pozycji sprawdzany jest wskaźnik marży konta
+    /// i w razie potrzeby redukowane są najgorsze pozycje.
+    pub async fn check_positions(&self) -> Result<Vec<PositionExit>> {
+        let mut exits = Vec::new();
+
+        let mut positions = self.sync_portfolio().await?.open_positions;
+        let open_ids: Vec<String> = positions.iter().map(|p| p.id.clone()).collect();
+        self.exits.lock().await.retain_open(&open_ids);
+        self.settle_funding(&mut positions).await?;
+
+        for mut position in positions {
+            let price = match self.executor.get_current_price(&position.token).await {
+                Ok(price) => price,
+                Err(e) => {
+                    warn!("No price for {} ({}): {}", position.token, position.id, e);
+                    continue;
+                }
+            };
+
+            position.update_price(price);
+            self.ledger.save_position(&position).await?;
+            self.risk_manager
+                .record_price(&position.token, price, self.clock.now())
+                .await;
+
+            // Aktywowane zlecenie ochronne ma pierwszeństwo przed pozostałymi warunkami
+            let exit =
+                if let Some(protective) = self.protective_trigger(&position.id, price).await? {
+                    let reason = match protective.role {
+                        ProtectiveRole::StopLoss => ExitReason::StopLoss,
+                        ProtectiveRole::PartialTakeProfit => ExitReason::PartialTakeProfit,
+                        ProtectiveRole::TakeProfit => ExitReason::TakeProfit,
+                    };
+                    self.execute_exit(position, protective.order, reason).await
+                } else if let Some(reason) = self.exit_reason(&position).await {
+                    let order = close_order(&position, position.size, &reason);
+                    self.execute_exit(position, order, reason).await
+                } else {
+                    continue;
+                };
+
+            match exit {
+                Ok(Some(exit)) => exits.push(exit),
+                Ok(None) => {}
+                Err(e) => error!("Failed to close position: {}", e),
+            }
+        }
+
+        exits.extend(self.deleverage().await?);
+        self.sync_portfolio().await?;
+        Ok(exits)
+    }
+
+    /// Redukuje pozycje wskazane przez managera ryzyka przed likwidacją konta
+    async fn deleverage(&self) -> Result<Vec<PositionExit>> {
+        let portfolio = self.sync_portfolio().await?;
+        let mut exits = Vec::new();
+
+        for step in self.risk_manager.deleverage_plan(&portfolio) {
+            let Some(position) = portfolio
+                .open_positions
+                .iter()
+                .find(|p| p.id == step.position_id)
+                .cloned()
+            else {
+                continue;
+            };
+
+            let order = close_order(&position, step.size, &ExitReason::Deleverage);
+            match self
+                .execute_exit(position, order, ExitReason::Deleverage)
+                .await
+            {
+                Ok(Some(exit)) => exits.push(exit),
+                Ok(None) => {}
+                Err(e) => error!("Failed to deleverage position {}: {}", step.position_id, e),
+            }
+        }
+
+        Ok(exits)
+    }
+
+    /// Warunek wyjścia dla wycenionej pozycji
+    ///
+    /// Stop-loss i take-profit pozycji z planem wyjścia obsługują zlecenia
+    /// ochronne - tutaj sprawdzane są tylko dla pozycji bez planu.
+    pub async fn exit_reason(&self, position: &Position) -> Option<ExitReason> {
+        let risk = self.risk_manager.active_risk_config();
+        let managed = self.exits.lock().await.is_managed(&position.id);
+
+        if position.is_near_liquidation(risk.liquidation_buffer.to_f64().unwrap_or(0.0)) {
+            return Some(ExitReason::NearLiquidation);
+        }
+
+        // Zwrot z marży: ruch ceny razy dźwignia
+        let leveraged_return = leveraged_return(position);
+        if !managed
+            && risk.stop_loss.enabled
+            && leveraged_return <= -risk.stop_loss.default_percent.to_f64().unwrap_or(f64::MAX)
+        {
+            return Some(ExitReason::StopLoss);
+        }
+
+        if !managed
+            && risk.take_profit.enabled
+            && leveraged_return
+                >= risk
+                    .take_profit
+                    .default_percent
+                    .to_f64()
+                    .unwrap_or(f64::MAX)
+        {
+            return Some(ExitReason::TakeProfit);
+        }
+
+        if position.duration_seconds_at(self.clock.now()) > risk.max_position_duration as i64 * 3600
+        {
+            return Some(ExitReason::MaxDuration);
+        }
+
+        if self.risk_manager.should_close_position(position).await {
+            return Some(ExitReason::MaxLoss);
+        }
+
+        None
+    }
+
+    async fn decide(&self, signal: &Signal) -> Result<TradeDecision> {
+        // Sygnał awaryjny nie otwiera pozycji - zamyka wszystkie
+        if signal.is_emergency_stop() {
+            let report = self
+                .emergency_stop
+                .activate(
+                    EmergencyTrigger::Signal,
+                    &format!("emergency signal from {}", signal.source),
+                )
+                .await?;
+            self.sync_portfolio().await?;
+            return Ok(TradeDecision::EmergencyStop { report });
+        }
+
+        if self.emergency_stop.is_active().await {
+            return Ok(TradeDecision::Skipped {
+                reason: "emergency stop active".to_string(),
+            });
+        }
+
+        let portfolio = self.sync_portfolio().await?;
+        if portfolio
+            .open_positions
+            .iter()
+            .any(|p| p.token == signal.token)
+        {
+            return Ok(TradeDecision::Skipped {
+                reason: format!("position in {} already open", signal.token),
+            });
+        }
+
+        self.risk_manager
+            .record_price(&signal.token, signal.price, self.clock.now())
+            .await;
+        let assessment = self.risk_manager.evaluate_risk(signal, &portfolio).await;
+        for warning in &assessment.warnings {
+            warn!("Signal {}: {}", signal.id, warning);
+        }
+        if !assessment.approved {
+            return Ok(TradeDecision::Rejected {
+                reason: assessment.reasoning,
+            });
+        }
+
+        let side = Self::side_for_signal(signal);
+        let mut order = TradeOrder::market_order(
+            signal.token.clone(),
+            side.clone(),
+            assessment.position_size,
+            assessment.max_leverage,
+        );
+        order.timeout = self.risk_manager.trading_config().execution_timeout;
+        order.metadata = serde_json::json!({
+            "signal_id": signal.id,
+            "signal_source": signal.source,
+            "risk_score": assessment.risk_score,
+        });
+        order.validate()?;
+
+        self.ledger
+            .record_order(&order, &OrderStatus::Pending)
+            .await?;
+
+        let started = std::time::Instant::now();
+        let result = match self.executor.execute_trade(&order).await {
+            Ok(result) => result,
+            Err(e) => {
+                self.ledger
+                    .update_order_status(&order.id, &OrderStatus::Rejected, Some(&e.to_string()))
+                    .await?;
+                super::ExecutionResult::error(e.to_string())
+            }
+        };
+        let execution_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+        self.ledger.record_execution(&order, &result).await?;
+        let alerts = self
+            .circuit_breaker
+            .record_trade_at(&result, execution_ms, 0.0, self.clock.now())
+            .await?;
+        self.send_alerts(alerts).await;
+
+        let price = match result.executed_price {
+            Some(price) if result.success => price,
+            _ => {
+                return Ok(TradeDecision::Rejected {
+                    reason: result
+                        .error_message
+                        .unwrap_or_else(|| "order not filled".to_string()),
+                })
+            }
+        };
+
+        let mut position = Position::new(
+            signal.token.clone(),
+            side,
+            result.executed_size.unwrap_or(order.size),
+            order.leverage,
+            price,
+        );
+        // Pozycja w rejestrze ma ten sam ID co u executora
+        if let Some(position_id) = result.metadata.get("position_id").and_then(|v| v.as_str()) {
+            position.id = position_id.to_string();
+        }
+        if let Some(executed_at) = result.executed_at {
+            position.opened_at = executed_at;
+        }
+        position.apply_margin_model(self.risk_manager.margin_model());
+        position.add_fee(result.fees.unwrap_or(0.0));
+
+        self.ledger.save_position(&position).await?;
+        self.risk_manager
+            .record_position_opened(&position.token)
+            .await?;
+        let actions = {
+            let mut exits = self.exits.lock().await;
+            exits.set_config(self.risk_manager.active_risk_config());
+            exits.attach(&position)
+        };
+        self.apply_exit_actions(actions).await?;
+        self.sync_portfolio().await?;
+
+        Ok(TradeDecision::Opened {
+            order_id: order.id,
+            position,
+        })
+    }
+
+    /// Wykonuje zlecenie zamykające pozycję (rynkowe lub aktywowane ochronne)
+    ///
+    /// Zamknięcie części pozycji zmniejsza jej rozmiar w rejestrze.
+    async fn execute_exit(
+        &self,
+        mut position: Position,
+        order: TradeOrder,
+        reason: ExitReason,
+    ) -> Result<Option<PositionExit>> {
+        info!(
+            "Closing {:.4} of position {} ({}) @ {:.8}: {}",
+            order.size, position.id, position.token, position.current_price, reason
+        );
+
+        self.ledger
+            .record_order(&order, &OrderStatus::Pending)
+            .await?;
+
+        let started = std::time::Instant::now();
+        let result = match self.executor.execute_trade(&order).await {
+            Ok(result) => result,
+            Err(e) => {
+                self.ledger
+                    .update_order_status(&order.id, &OrderStatus::Rejected, Some(&e.to_string()))
+                    .await?;
+                super::ExecutionResult::error(e.to_string())
+            }
+        };
+        let execution_ms = started.elapsed().as_secs_f64() * 1000.0;
+        self.ledger.record_execution(&order, &result).await?;
+
+        let exit_price = match result.executed_price {
+            Some(price) if result.success => price,
+            _ => {
+                warn!(
+                    "Close order for {} not filled: {}",
+                    position.id,
+                    result.error_message.as_deref().unwrap_or("unknown error")
+                );
+                // Aktywowane zlecenie ochronne wraca do planu z nowym ID
+                let actions = self.exits.lock().await.reissue(&position.id, &order.id);
+                self.apply_exit_actions(actions).await?;
+                let alerts = self
+                    .circuit_breaker
+                    .record_trade_at(&result, execution_ms, 0.0, self.clock.now())
+                    .await?;
+                self.send_alerts(alerts).await;
+                return Ok(None);
+            }
+        };
+
+        let closed_size = result
+            .executed_size
+            .unwrap_or(order.size)
+            .min(position.size);
+        let realized_pnl = position.realize(closed_size, exit_price, result.fees.unwrap_or(0.0));
+
+        let (managed, realized_before, actions) = {
+            let mut exits = self.exits.lock().await;
+            let managed = exits.is_managed(&position.id);
+            let realized_before = exits
+                .plan(&position.id)
+                .map_or(0.0, |plan| plan.realized_pnl);
+            let actions = exits.on_fill(&position.id, &order.id, closed_size, realized_pnl);
+            (managed, realized_before, actions)
+        };
+
+        let fully_closed = position.size - closed_size <= SIZE_EPSILON;
+        if fully_closed {
+            position.close();
+            self.ledger
+                .record_position_close(&position, exit_price, realized_before + realized_pnl)
+                .await?;
+            self.risk_manager
+                .record_trade_outcome(realized_before + realized_pnl)
+                .await;
+            self.risk_manager
+                .record_position_exit(&position.token, &reason)
+                .await?;
+            if !managed {
+                self.cancel_protective_orders(&position.id).await?;
+            }
+        } else {
+            position.size -= closed_size;
+            position.update_price(exit_price);
+            self.ledger.save_position(&position).await?;
+        }
+        self.apply_exit_actions(actions).await?;
+
+        let alerts = self
+            .circuit_breaker
+            .record_trade_at(&result, execution_ms, realized_pnl, self.clock.now())
+            .await?;
+        self.send_alerts(alerts).await;
+
+        let mut portfolio = self.risk_manager.portfolio().await;
+        portfolio.balance += realized_pnl;
+        portfolio.daily_pnl += realized_pnl;
+        self.risk_manager.update_portfolio(&portfolio).await;
+
+        {
+            let mut metrics = self.metrics.write().await;
+            metrics.daily_pnl += realized_pnl;
+            if fully_closed {
+                if realized_before + realized_pnl > 0.0 {
+                    metrics.successful_trades += 1;
+                } else {
+                    metrics.failed_trades += 1;
+                }
+            }
+        }
+
+        Ok(Some(PositionExit {
+            position_id: position.id,
+            token: position.token,
+            reason,
+            exit_price,
+            realized_pnl,
+        }))
+    }
+
+    /// Nalicza pozycjom płatności finansowania rozliczone przez executor
+    ///
+    /// Płatności trafiają do rejestru i statystyk dnia; P&L portfela
+    /// obejmuje je przy zamknięciu pozycji (`Position::realize`).
+    async fn settle_funding(&self, positions: &mut [Position]) -> Result<()> {
+        let Some(since) = positions
+            .iter()
+            .map(|p| p.last_funding_at.unwrap_or(p.opened_at))
+            .min()
+        else {
+            return Ok(());
+        };
+
+        let payments = match self.executor.funding_payments(since).await {
+            Ok(payments) => payments,
+            Err(e) => {
+                warn!("Failed to fetch funding payments: {}", e);
+                return Ok(());
+            }
+        };
+
+        for payment in payments {
+            let Some(position) = positions.iter_mut().find(|p| p.id == payment.position_id) else {
+                continue;
+            };
+            if payment.settled_at <= position.last_funding_at.unwrap_or(position.opened_at) {
+                continue;
+            }
+
+            position.record_funding(payment.amount, payment.settled_at);
+            if self.ledger.record_funding(&payment).await? {
+                self.circuit_breaker.record_funding(&payment).await;
+            }
+            self.ledger.save_position(position).await?;
+            debug!(
+                "Funding {:.6} settled for {} ({}), total {:.6}",
+                payment.amount, position.id, position.token, position.funding_paid
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Przekazuje cenę managerowi wyjść; zwraca aktywowane zlecenie ochronne
+    async fn protective_trigger(
+        &self,
+        position_id: &str,
+        price: f64,
+    ) -> Result<Option<ProtectiveOrder>> {
+        let actions = self.exits.lock().await.on_price(position_id, price);
+
+        let mut triggered = None;
+        let mut changes = Vec::new();
+        for action in actions {
+            match action {
+                ExitAction::Trigger(order) => triggered = Some(order),
+                other => changes.push(other),
+            }
+        }
+
+        self.apply_exit_actions(changes).await?;
+        Ok(triggered)
+    }
+
+    /// Odzwierciedla zmiany zleceń ochronnych w rejestrze
+    async fn apply_exit_actions(&self, actions: Vec<ExitAction>) -> Result<()> {
+        for action in actions {
+            match action {
+                ExitAction::Place(protective) => {
+                    self.ledger
+                        .record_order(&protective.order, &OrderStatus::Pending)
+                        .await?
+                }
+                ExitAction::Replace { cancelled, order } => {
+                    debug!(
+                        "Protective {:?} for {} moved to {:.8}",
+                        order.role,
+                        order.order.token,
+                        order.trigger_price()
+                    );
+                    self.ledger
+                        .update_order_status(&cancelled, &OrderStatus::Cancelled, Some("replaced"))
+                        .await?;
+                    self.ledger
+                        .record_order(&order.order, &OrderStatus::Pending)
+                        .await?;
+                }
+                ExitAction::Cancel(order_id) => {
+                    self.ledger
+                        .update_order_status(
+                            &order_id,
+                            &OrderStatus::Cancelled,
+                            Some("position closed"),
+                        )
+                        .await?
+                }
+                // Aktywowane zlecenia wykonuje `check_positions`
+                ExitAction::Trigger(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Anuluje w rejestrze zlecenia ochronne pozycji bez planu wyjścia
+    /// (np. pozostałe po restarcie aplikacji)
+    async fn cancel_protective_orders(&self, position_id: &str) -> Result<()> {
+        for record in self.ledger.open_orders().await? {
+            let metadata = &record.order.metadata;
+            let managed = metadata.get("managed").and_then(|v| v.as_bool()) == Some(true);
+            if managed && metadata.get("position_id").and_then(|v| v.as_str()) == Some(position_id)
+            {
+                self.ledger
+                    .update_order_status(
+                        &record.order.id,
+                        &OrderStatus::Cancelled,
+                        Some("position closed"),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Odświeża pozycje portfela managera ryzyka z rejestru
+    ///
+    /// Dzienny P&L zeruje się po zmianie dnia UTC (jak w circuit breakerze).
+    async fn sync_portfolio(&self) -> Result<crate::risk::Portfolio> {
+        let mut portfolio = self.risk_manager.portfolio().await;
+        portfolio.open_positions = self.ledger.load_open_positions().await?;
+
+        let day = self.clock.now().div_euclid(24 * 3600);
+        let new_day = self
+            .trading_day
+            .lock()
+            .await
+            .replace(day)
+            .is_some_and(|d| d != day);
+        if new_day {
+            portfolio.daily_pnl = 0.0;
+        }
+
+        portfolio.update();
+        self.risk_manager.update_portfolio(&portfolio).await;
+        if let Some(change) = self.risk_manager.update_phase(portfolio.equity) {
+            self.send_alerts(vec![change.alert()]).await;
+        }
+
+        let mut metrics = self.metrics.write().await;
+        metrics.current_balance = portfolio.balance;
+        if new_day {
+            metrics.daily_pnl = 0.0;
+        }
+        Ok(portfolio)
+    }
+
+    async fn send_alerts(&self, alerts: Vec<SystemAlert>) {
+        if alerts.is_empty() {
+            return;
+        }
+
+        let mut alert_manager = self.alert_manager.lock().await;
+        for alert in alerts {
+            if let Err(e) = alert_manager.send_alert(&alert).await {
+                error!("Failed to send alert: {}", e);
+            }
+        }
+    }
+}
+
+/// Ruch ceny względem wejścia razy dźwignia (dodatni = zysk)
+fn leveraged_return(position: &Position) -> f64 {
+    if position.entry_price <= 0.0 {
+        return 0.0;
+    }
+
+    let price_change = (position.current_price - position.entry_price) / position.entry_price;
+    let directional = match position.side {
+        TradeSide::Long => price_change,
+        TradeSide::Short => -price_change,
+    };
+    directional * position.leverage as f64
+}
+
+/// Zlecenie rynkowe zamykające `size` pozycji
+fn close_order(position: &Position, size: f64, reason: &ExitReason) -> TradeOrder {
+    let mut order = TradeOrder::market_order(
+        position.token.clone(),
+        position.side.clone(),
+        size,
+        position.leverage,
+    );
+    order.metadata = serde_json::json!({
+        "position_id": position.id,
+        "reason": reason.to_string(),
+    });
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AlertsConfig, RiskConfig, TradingConfig};
+    use crate::signals::Confidence;
+    use crate::trading::PaperExchange;
+
+    async fn engine() -> (TradingEngine, Arc<PaperExchange>, Arc<TradeLedger>) {
+        engine_with_risk(RiskConfig::default()).await
+    }
+
+    async fn engine_with_risk(
+        risk: RiskConfig,
+    ) -> (TradingEngine, Arc<PaperExchange>, Arc<TradeLedger>) {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        engine_on(Arc::new(pool), risk).await
+    }
+
+    /// Silnik na istniejącej bazie (np. po restarcie aplikacji)
+    async fn engine_on(
+        pool: Arc<sqlx::SqlitePool>,
+        risk: RiskConfig,
+    ) -> (TradingEngine, Arc<PaperExchange>, Arc<TradeLedger>) {
+        let trading = TradingConfig {
+            initial_balance: rust_decimal::Decimal::new(1000, 0),
+            ..TradingConfig::default()
+        };
+        let exchange = Arc::new(PaperExchange::from_config(&trading));
+        let ledger = Arc::new(TradeLedger::new(pool.clone()));
+        let circuit_breaker = Arc::new(CircuitBreaker::new(&risk));
+        let risk_manager =
+            Arc::new(RiskManager::new(risk, trading).with_circuit_breaker(circuit_breaker.clone()));
+        let emergency_stop = Arc::new(
+            EmergencyStop::new(
+                exchange.clone(),
+                ledger.clone(),
+                circuit_breaker.clone(),
+                pool,
+                false,
+            )
+            .await
+            .unwrap()
+            .with_risk_manager(risk_manager.clone()),
+        );
+
+        let engine = TradingEngine::new(
+            exchange.clone(),
+            risk_manager,
+            ledger.clone(),
+            circuit_breaker,
+            emergency_stop,
+            Arc::new(Mutex::new(AlertManager::from_config(
+                &AlertsConfig::default(),
+            ))),
+            Arc::new(RwLock::new(SystemMetrics::new())),
+        );
+        (engine, exchange, ledger)
+    }
+
+    fn scored(token: &str, price: f64) -> ScoredSignal {
+        ScoredSignal {
+            signal: Signal::new(
+                token.to_string(),
+                "test".to_string(),
+                Confidence::Medium,
+                price,
+                1000.0,
+                serde_json::json!({"side": "long"}),
+            ),
+            score: 0.5,
+            received_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signal_opens_position_and_records_latency() {
+        let (engine, exchange, ledger) = engine().await;
+        exchange.update_price("BONK", 1.0).await;
+
+        let decision = engine.handle_signal(&scored("BONK", 1.0)).await.unwrap();
+        let position = match decision {
+            TradeDecision::Opened { position, .. } => position,
+            other => panic!("expected open, got {:?}", other),
+        };
+
+        assert!(exchange.get_position(&position.id).await.is_some());
+        assert_eq!(ledger.load_open_positions().await.unwrap().len(), 1);
+
+        // Drugi sygnał na ten sam token jest pomijany
+        let decision = engine.handle_signal(&scored("BONK", 1.0)).await.unwrap();
+        assert!(matches!(decision, TradeDecision::Skipped { .. }));
+
+        // Otwarcie pozycji to jeszcze nie zakończona transakcja
+        let metrics = engine.metrics.read().await;
+        assert_eq!(metrics.total_signals, 2);
+        assert_eq!(metrics.successful_trades, 0);
+        assert_eq!(metrics.decision_times.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_check_positions_takes_profit_and_stops_loss() {
+        let (engine, exchange, ledger) = engine().await;
+        exchange.update_price("BONK", 1.0).await;
+        exchange.update_price("WIF", 1.0).await;
+
+        let bonk = match engine.handle_signal(&scored("BONK", 1.0)).await.unwrap() {
+            TradeDecision::Opened { position, .. } => position,
+            other => panic!("expected open, got {:?}", other),
+        };
+        engine.handle_signal(&scored("WIF", 1.0)).await.unwrap();
+        assert!(engine.check_positions().await.unwrap().is_empty());
+
+        // 10x: +3% ceny = +30% marży (TP 20%), -2% = -20% (SL 10%)
+        exchange.update_price("BONK", 1.03).await;
+        exchange.update_price("WIF", 0.98).await;
+
+        let mut exits = engine.check_positions().await.unwrap();
+        exits.sort_by(|a, b| a.token.cmp(&b.token));
+        assert_eq!(exits.len(), 2);
+        assert_eq!(exits[0].reason, ExitReason::TakeProfit);
+        assert!(exits[0].realized_pnl > 0.0);
+        assert_eq!(exits[1].reason, ExitReason::StopLoss);
+        assert!(exits[1].realized_pnl < 0.0);
+
+        // Wyniki zamkniętych pozycji zasilają statystyki polityki Kelly'ego
+        let stats = engine.risk_manager.trading_stats().await;
+        assert_eq!((stats.winning_trades, stats.losing_trades), (1, 1));
+        let metrics = engine.metrics.read().await.clone();
+        assert_eq!((metrics.successful_trades, metrics.failed_trades), (1, 1));
+
+        // Po stop-lossie ponowne wejście w WIF czeka na koniec cooldownu
+        let decision = engine.handle_signal(&scored("WIF", 0.98)).await.unwrap();
+        match decision {
+            TradeDecision::Rejected { reason } => {
+                assert!(reason.contains("cooldown"), "{}", reason)
+            }
+            other => panic!("expected rejection, got {:?}", other),
+        }
+
+        assert!(ledger.load_open_positions().await.unwrap().is_empty());
+        assert!(exchange.open_positions().await.is_empty());
+        let closed = ledger.get_position(&bonk.id).await.unwrap().unwrap();
+        assert!(closed.exit_price.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_restored_position_closes_after_restart() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let pool = Arc::new(pool);
+
+        let (engine, exchange, _) = engine_on(pool.clone(), RiskConfig::default()).await;
+        exchange.update_price("BONK", 1.0).await;
+        let position = match engine.handle_signal(&scored("BONK", 1.0)).await.unwrap() {
+            TradeDecision::Opened { position, .. } => position,
+            other => panic!("expected open, got {:?}", other),
+        };
+        drop(engine);
+
+        // Restart: nowy symulator odtwarza pozycje, a silnik plany wyjścia z rejestru
+        let (engine, exchange, ledger) = engine_on(pool, RiskConfig::default()).await;
+        exchange
+            .restore_positions(ledger.load_open_positions().await.unwrap())
+            .await;
+        assert!(exchange.get_position(&position.id).await.is_some());
+        assert_eq!(engine.restore_exit_plans().await.unwrap(), 1);
+        let take_profit = engine
+            .exits
+            .lock()
+            .await
+            .plan(&position.id)
+            .and_then(|plan| plan.take_profit.clone())
+            .expect("take-profit restored");
+
+        exchange.update_price("BONK", 1.03).await;
+        let exits = engine.check_positions().await.unwrap();
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].position_id, position.id);
+        assert_eq!(exits[0].reason, ExitReason::TakeProfit);
+
+        // Wykonane zostało zapisane zlecenie ochronne, reszta anulowana
+        let record = ledger
+            .get_order(&take_profit.order.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.status, OrderStatus::Filled);
+        assert!(exchange.open_positions().await.is_empty());
+        assert!(ledger.load_open_positions().await.unwrap().is_empty());
+        assert!(ledger.open_orders().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unfilled_protective_order_retries_with_new_id() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let pool = Arc::new(pool);
+
+        let (engine, exchange, _) = engine_on(pool.clone(), RiskConfig::default()).await;
+        exchange.update_price("BONK", 1.0).await;
+        let position = match engine.handle_signal(&scored("BONK", 1.0)).await.unwrap() {
+            TradeDecision::Opened { position, .. } => position,
+            other => panic!("expected open, got {:?}", other),
+        };
+        drop(engine);
+
+        // Symulator bez pozycji: wykonanie aktywowanego take-profit się nie powiedzie
+        let (engine, exchange, ledger) = engine_on(pool, RiskConfig::default()).await;
+        engine.restore_exit_plans().await.unwrap();
+        let take_profit_id = |engine: &TradingEngine| {
+            engine
+                .exits
+                .try_lock()
+                .unwrap()
+                .plan(&position.id)
+                .and_then(|plan| plan.take_profit.as_ref())
+                .map(|tp| tp.order.id.clone())
+                .expect("take-profit in plan")
+        };
+        let first = take_profit_id(&engine);
+
+        exchange.update_price("BONK", 1.03).await;
+        assert!(engine.check_positions().await.unwrap().is_empty());
+
+        let rejected = ledger.get_order(&first).await.unwrap().unwrap();
+        assert_eq!(rejected.status, OrderStatus::Rejected);
+        let retry = take_profit_id(&engine);
+        assert_ne!(retry, first);
+        let pending = ledger.get_order(&retry).await.unwrap().unwrap();
+        assert_eq!(pending.status, OrderStatus::Pending);
+
+        // Po odtworzeniu pozycji kolejna próba używa nowego ID i się udaje
+        exchange
+            .restore_positions(ledger.load_open_positions().await.unwrap())
+            .await;
+        let exits = engine.check_positions().await.unwrap();
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].reason, ExitReason::TakeProfit);
+        let record = ledger.get_order(&retry).await.unwrap().unwrap();
+        assert_eq!(record.status, OrderStatus::Filled);
+    }
+
+    #[tokio::test]
+    async fn test_check_positions_records_funding() {
+        let (engine, exchange, ledger) = engine().await;
+        exchange.update_price("BONK", 1.0).await;
+
+        let position = match engine.handle_signal(&scored("BONK", 1.0)).await.unwrap() {
+            TradeDecision::Opened { position, .. } => position,
+            other => panic!("expected open, got {:?}", other),
+        };
+
+        // Doba później: trzy rozliczenia finansowania co 8h
+        exchange
+            .set_time(chrono::Utc::now().timestamp() + 86_400)
+            .await;
+        assert!(engine.check_positions().await.unwrap().is_empty());
+        engine.check_positions().await.unwrap();
+
+        let events = ledger.position_funding(&position.id).await.unwrap();
+        assert_eq!(events.len(), 3);
+        let funding: f64 = events.iter().map(|e| e.amount).sum();
+        assert!(funding > 0.0);
+
+        let stored = ledger
+            .get_position(&position.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .position;
+        assert!((stored.funding_paid - funding).abs() < 1e-9);
+        assert!(stored.fees_paid > 0.0);
+        assert!(stored.pnl < stored.price_pnl());
+    }
+
+    #[tokio::test]
+    async fn test_check_positions_deleverages_worst_position() {
+        // Dwie pozycje po 330 USD przy 10x: marża utrzymaniowa 2 x 0.5% x 3300 USD
+        // = 33 USD przy equity ~1000 USD
+        let mut risk = RiskConfig::default();
+        risk.margin.mode = "cross".to_string();
+        risk.margin.deleverage_margin_ratio = rust_decimal::Decimal::new(3, 2);
+        risk.margin.deleverage_target_ratio = rust_decimal::Decimal::new(2, 2);
+        risk.concentration.enabled = false;
+        let (engine, exchange, ledger) = engine_with_risk(risk).await;
+        exchange.update_price("BONK", 1.0).await;
+        exchange.update_price("WIF", 1.0).await;
+
+        let bonk = match engine.handle_signal(&scored("BONK", 1.0)).await.unwrap() {
+            TradeDecision::Opened { position, .. } => position,
+            other => panic!("expected open, got {:?}", other),
+        };
+        let wif = match engine.handle_signal(&scored("WIF", 1.0)).await.unwrap() {
+            TradeDecision::Opened { position, .. } => position,
+            other => panic!("expected open, got {:?}", other),
+        };
+
+        exchange.update_price("WIF", 0.995).await;
+        let exits = engine.check_positions().await.unwrap();
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].reason, ExitReason::Deleverage);
+        assert_eq!(exits[0].position_id, wif.id);
+
+        let reduced = ledger
+            .get_position(&wif.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .position;
+        assert!(reduced.size > 0.0 && reduced.size < wif.size);
+        let untouched = ledger
+            .get_position(&bonk.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .position;
+        assert_eq!(untouched.size, bonk.size);
+
+        // Wskaźnik marży wrócił poniżej progu
+        assert!(engine.check_positions().await.unwrap().is_empty());
+        assert!(engine.risk_manager.portfolio().await.margin_ratio < 0.03);
+    }
+
+    #[tokio::test]
+    async fn test_partial_take_profit_then_break_even_stop() {
+        let (engine, exchange, ledger) = engine().await;
+        exchange.update_price("BONK", 1.0).await;
+
+        let position = match engine.handle_signal(&scored("BONK", 1.0)).await.unwrap() {
+            TradeDecision::Opened { position, .. } => position,
+            other => panic!("expected open, got {:?}", other),
+        };
+        // Stop-loss, częściowy i docelowy take-profit czekają w rejestrze
+        assert_eq!(ledger.open_orders().await.unwrap().len(), 3);
+
+        // 10x: +1.8% ceny = +18% marży - między pierwszym poziomem (15%) a TP (20%)
+        // (wejście jest o poślizg wyżej niż 1.0)
+        exchange.update_price("BONK", 1.018).await;
+        let exits = engine.check_positions().await.unwrap();
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].reason, ExitReason::PartialTakeProfit);
+        assert!(exits[0].realized_pnl > 0.0);
+
+        let remaining = ledger.load_open_positions().await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert!((remaining[0].size - position.size / 2.0).abs() < 1e-6);
+
+        let open_orders = ledger.open_orders().await.unwrap();
+        assert_eq!(open_orders.len(), 2);
+        let stop = open_orders
+            .iter()
+            .find(|o| o.order.order_type == crate::trading::OrderType::StopLoss)
+            .unwrap();
+        assert_eq!(stop.order.stop_price, Some(position.entry_price));
+
+        // Powrót do ceny wejścia zamyka resztę na break-even
+        exchange.update_price("BONK", position.entry_price).await;
+        let exits = engine.check_positions().await.unwrap();
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].reason, ExitReason::StopLoss);
+
+        assert!(ledger.load_open_positions().await.unwrap().is_empty());
+        assert!(ledger.open_orders().await.unwrap().is_empty());
+        assert!(exchange.open_positions().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_emergency_flatten_cancels_protective_orders() {
+        let (engine, exchange, ledger) = engine().await;
+        exchange.update_price("BONK", 1.0).await;
+        let position = match engine.handle_signal(&scored("BONK", 1.0)).await.unwrap() {
+            TradeDecision::Opened { position, .. } => position,
+            other => panic!("expected open, got {:?}", other),
+        };
+
+        let report = engine
+            .emergency_stop
+            .flatten(crate::risk::EmergencyTrigger::Api, "test")
+            .await
+            .unwrap();
+        assert_eq!(report.cancelled_count(), 3);
+        assert!(ledger.open_orders().await.unwrap().is_empty());
+
+        // Plan zamkniętej pozycji znika przy kolejnej wycenie
+        assert!(engine.check_positions().await.unwrap().is_empty());
+        assert!(!engine.exits.lock().await.is_managed(&position.id));
+    }
+
+    #[tokio::test]
+    async fn test_emergency_signal_from_processor_flattens_instead_of_opening() {
+        use crate::config::SignalsConfig;
+        use crate::database::SignalStore;
+        use crate::signals::{SignalDecision, SignalProcessor, TokenScreener};
+
+        let (engine, exchange, ledger) = engine().await;
+        exchange.update_price("BONK", 1.0).await;
+        engine.handle_signal(&scored("BONK", 1.0)).await.unwrap();
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let mut config = SignalsConfig::default();
+        config
+            .source_reliability
+            .insert("tradingview".to_string(), rust_decimal::Decimal::ONE);
+        // Kontrola tokenów odrzuciłaby BONK - sygnał awaryjny jest z niej zwolniony
+        config.screening.denylist = vec!["BONK".to_string()];
+        let processor = SignalProcessor::new(config.clone(), SignalStore::new(Arc::new(pool)))
+            .with_screener(TokenScreener::new(config.screening));
+
+        // Sygnał z webhooka: akcja w oryginalnym payloadzie, strona domyślna
+        let signal = Signal::new(
+            "BONK".to_string(),
+            "tradingview".to_string(),
+            Confidence::High,
+            1.0,
+            0.0,
+            serde_json::json!({
+                "side": "long",
+                "webhook": "tradingview",
+                "payload": {"ticker": "BONK", "action": "emergency_stop"}
+            }),
+        );
+        let scored = match processor.process(signal).await.unwrap() {
+            SignalDecision::Accepted(scored) => scored,
+            other => panic!("expected accept, got {:?}", other),
+        };
+
+        match engine.handle_signal(&scored).await.unwrap() {
+            TradeDecision::EmergencyStop { report } => assert_eq!(report.closed_count(), 1),
+            other => panic!("expected emergency stop, got {:?}", other),
+        }
+        assert!(engine.emergency_stop.is_active().await);
+        assert!(exchange.open_positions().await.is_empty());
+        assert!(ledger.load_open_positions().await.unwrap().is_empty());
+        assert!(engine
+            .risk_manager
+            .portfolio()
+            .await
+            .open_positions
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_emergency_flatten_books_realized_pnl() {
+        let (engine, exchange, _ledger) = engine().await;
+        exchange.update_price("BONK", 1.0).await;
+        engine.handle_signal(&scored("BONK", 1.0)).await.unwrap();
+        let balance = engine.risk_manager.portfolio().await.balance;
+
+        exchange.update_price("BONK", 1.05).await;
+        let report = engine
+            .emergency_stop
+            .flatten(crate::risk::EmergencyTrigger::Api, "test")
+            .await
+            .unwrap();
+        let realized = report.positions_closed[0].realized_pnl.unwrap();
+        assert!(realized > 0.0);
+
+        let portfolio = engine.risk_manager.portfolio().await;
+        assert!(portfolio.open_positions.is_empty());
+        assert!((portfolio.balance - (balance + realized)).abs() < 1e-9);
+        assert!((portfolio.daily_pnl - realized).abs() < 1e-9);
+    }
+}
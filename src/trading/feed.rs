@@ -0,0 +1,481 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, watch, RwLock};
+use tracing::{debug, info, warn};
+
+use super::{Clock, PriceTick};
+use crate::config::PriceFeedConfig;
+
+/// Pojemność kanału rozsyłającego notowania
+const UPDATES_CAPACITY: usize = 1024;
+
+/// Notowanie w tablicy cen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceQuote {
+    /// Token (wielkie litery)
+    pub token: String,
+
+    pub price: f64,
+
+    /// Czas notowania (Unix timestamp)
+    pub timestamp: i64,
+
+    /// Źródło notowania (strumień lub fallback REST)
+    pub source: String,
+}
+
+/// Strumieniowe źródło cen (np. WebSocket giełdy)
+#[async_trait]
+pub trait PriceStream: Send + Sync {
+    /// Nazwa źródła
+    fn name(&self) -> &str;
+
+    /// Otwiera strumień ticków dla tokenów
+    ///
+    /// Kanał zamyka się po zerwaniu połączenia.
+    async fn subscribe(&self, tokens: &[String]) -> Result<mpsc::Receiver<PriceTick>>;
+}
+
+/// Źródło cen typu zapytanie/odpowiedź (fallback dla strumienia)
+#[async_trait]
+pub trait PriceSnapshotSource: Send + Sync {
+    /// Nazwa źródła
+    fn name(&self) -> &str;
+
+    /// Pobiera ceny tokenów (klucze wielkimi literami)
+    async fn fetch_prices(&self, tokens: &[String]) -> Result<HashMap<String, f64>>;
+}
+
+/// Serwis cen w czasie rzeczywistym
+///
+/// Utrzymuje subskrypcję strumienia dla obserwowanych tokenów, normalizuje
+/// ticki do wspólnej tablicy cen i rozsyła każde nowe notowanie kanałem
+/// broadcast. Notowania starsze niż `max_price_age` są oznaczane jako
+/// nieaktualne. Gdy strumień jest rozłączony (albo milczy dla części
+/// tokenów), ceny są odpytywane ze źródła fallback.
+pub struct PriceFeed {
+    config: PriceFeedConfig,
+    stream: Option<Arc<dyn PriceStream>>,
+    fallback: Option<Arc<dyn PriceSnapshotSource>>,
+    prices: RwLock<HashMap<String, PriceQuote>>,
+    watched: RwLock<BTreeSet<String>>,
+    /// Wersja zbioru obserwowanych tokenów (zmiana przepina strumień)
+    watch_version: watch::Sender<u64>,
+    streaming: AtomicBool,
+    updates: broadcast::Sender<PriceQuote>,
+    clock: Clock,
+}
+
+impl PriceFeed {
+    /// Tworzy serwis bez źródeł (ceny tylko przez `record`)
+    pub fn new(config: PriceFeedConfig) -> Self {
+        let (updates, _) = broadcast::channel(UPDATES_CAPACITY);
+        Self {
+            config,
+            stream: None,
+            fallback: None,
+            prices: RwLock::new(HashMap::new()),
+            watched: RwLock::new(BTreeSet::new()),
+            watch_version: watch::channel(0).0,
+            streaming: AtomicBool::new(false),
+            updates,
+            clock: Clock::System,
+        }
+    }
+
+    /// Ustawia strumieniowe źródło cen
+    pub fn with_stream(mut self, stream: Arc<dyn PriceStream>) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    /// Ustawia źródło fallback używane, gdy strumień nie dostarcza cen
+    pub fn with_fallback(mut self, fallback: Arc<dyn PriceSnapshotSource>) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    /// Ustawia zegar używany do oceny wieku notowań
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Subskrybuje nowe notowania
+    pub fn subscribe(&self) -> broadcast::Receiver<PriceQuote> {
+        self.updates.subscribe()
+    }
+
+    /// Dodaje token do obserwowanych (strumień zostanie przepięty)
+    pub async fn watch(&self, token: &str) {
+        let token = normalize(token);
+        if !token.is_empty() && self.watched.write().await.insert(token.clone()) {
+            debug!("Price feed watching {}", token);
+            self.watch_version.send_modify(|version| *version += 1);
+        }
+    }
+
+    /// Usuwa token z obserwowanych
+    pub async fn unwatch(&self, token: &str) {
+        if self.watched.write().await.remove(&normalize(token)) {
+            self.watch_version.send_modify(|version| *version += 1);
+        }
+    }
+
+    /// Obserwowane tokeny
+    pub async fn watched(&self) -> Vec<String> {
+        self.watched.read().await.iter().cloned().collect()
+    }
+
+    /// Czy strumień jest połączony
+    pub fn is_streaming(&self) -> bool {
+        self.streaming.load(Ordering::Relaxed)
+    }
+
+    /// Zapisuje notowanie w tablicy i rozsyła je subskrybentom
+    ///
+    /// Ceny niedodatnie i notowania starsze niż bieżące są odrzucane.
+    /// Zwraca `true`, jeśli tablica została zaktualizowana.
+    pub async fn record(&self, token: &str, price: f64, timestamp: i64, source: &str) -> bool {
+        let token = normalize(token);
+        if token.is_empty() || !price.is_finite() || price <= 0.0 {
+            debug!(
+                "Ignoring invalid {} tick for '{}': {}",
+                source, token, price
+            );
+            return false;
+        }
+
+        let quote = PriceQuote {
+            token: token.clone(),
+            price,
+            timestamp,
+            source: source.to_string(),
+        };
+
+        {
+            let mut prices = self.prices.write().await;
+            if let Some(current) = prices.get(&token) {
+                if current.timestamp > timestamp {
+                    return false;
+                }
+            }
+            prices.insert(token, quote.clone());
+        }
+
+        // Brak subskrybentów nie jest błędem
+        let _ = self.updates.send(quote);
+        true
+    }
+
+    /// Ostatnie notowanie tokena (także nieaktualne)
+    pub async fn quote(&self, token: &str) -> Option<PriceQuote> {
+        self.prices.read().await.get(&normalize(token)).cloned()
+    }
+
+    /// Czy notowanie jest starsze niż `max_price_age`
+    pub fn is_stale(&self, quote: &PriceQuote) -> bool {
+        self.clock.now() - quote.timestamp > self.config.max_price_age as i64
+    }
+
+    /// Aktualna cena tokena; błąd, gdy brak notowania lub jest nieaktualne
+    pub async fn fresh_price(&self, token: &str) -> Result<f64> {
+        let quote = self
+            .quote(token)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No price for {}", token))?;
+
+        if self.is_stale(&quote) {
+            anyhow::bail!(
+                "Price for {} is stale ({}s old, source {})",
+                quote.token,
+                self.clock.now() - quote.timestamp,
+                quote.source
+            );
+        }
+
+        Ok(quote.price)
+    }
+
+    /// Obserwowane tokeny bez aktualnej ceny
+    pub async fn stale_tokens(&self) -> Vec<String> {
+        let watched = self.watched().await;
+        let prices = self.prices.read().await;
+        watched
+            .into_iter()
+            .filter(|token| prices.get(token).map_or(true, |quote| self.is_stale(quote)))
+            .collect()
+    }
+
+    /// Uruchamia subskrypcję strumienia i odpytywanie fallbacku
+    pub async fn run(self: Arc<Self>) {
+        info!(
+            "Price feed started (stream: {}, fallback: {}, max age {}s)",
+            self.stream.as_ref().map_or("none", |s| s.name()),
+            self.fallback.as_ref().map_or("none", |f| f.name()),
+            self.config.max_price_age
+        );
+
+        tokio::join!(self.run_stream(), self.run_fallback());
+    }
+
+    /// Utrzymuje subskrypcję strumienia (ponawia po zerwaniu połączenia)
+    async fn run_stream(&self) {
+        let Some(stream) = self.stream.clone() else {
+            return;
+        };
+        let reconnect_delay = Duration::from_secs(self.config.reconnect_delay);
+        let mut watch_changes = self.watch_version.subscribe();
+
+        loop {
+            watch_changes.borrow_and_update();
+            let tokens = self.watched().await;
+            if tokens.is_empty() {
+                let _ = watch_changes.changed().await;
+                continue;
+            }
+
+            let mut resubscribe = false;
+            match stream.subscribe(&tokens).await {
+                Ok(mut ticks) => {
+                    info!(
+                        "{} price stream connected ({} tokens)",
+                        stream.name(),
+                        tokens.len()
+                    );
+                    self.streaming.store(true, Ordering::Relaxed);
+
+                    loop {
+                        tokio::select! {
+                            tick = ticks.recv() => match tick {
+                                Some(tick) => {
+                                    self.record(&tick.token, tick.price, tick.timestamp, stream.name())
+                                        .await;
+                                }
+                                None => break,
+                            },
+                            _ = watch_changes.changed() => {
+                                resubscribe = true;
+                                break;
+                            }
+                        }
+                    }
+
+                    self.streaming.store(false, Ordering::Relaxed);
+                    if !resubscribe {
+                        warn!("{} price stream disconnected", stream.name());
+                    }
+                }
+                Err(e) => warn!("{} price stream subscription failed: {}", stream.name(), e),
+            }
+
+            if !resubscribe {
+                tokio::time::sleep(reconnect_delay).await;
+            }
+        }
+    }
+
+    /// Cyklicznie uzupełnia brakujące ceny ze źródła fallback
+    async fn run_fallback(&self) {
+        if self.fallback.is_none() {
+            return;
+        }
+
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(self.config.fallback_poll_interval));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.poll_fallback().await {
+                warn!("Price fallback poll failed: {}", e);
+            }
+        }
+    }
+
+    /// Odpytuje fallback o ceny, których strumień nie dostarcza
+    ///
+    /// Bez połączonego strumienia odpytywane są wszystkie obserwowane
+    /// tokeny, w przeciwnym razie tylko nieaktualne. Zwraca liczbę
+    /// zaktualizowanych notowań.
+    pub async fn poll_fallback(&self) -> Result<usize> {
+        let Some(fallback) = &self.fallback else {
+            return Ok(0);
+        };
+
+        let tokens = if self.is_streaming() {
+            self.stale_tokens().await
+        } else {
+            self.watched().await
+        };
+        if tokens.is_empty() {
+            return Ok(0);
+        }
+
+        let prices = fallback.fetch_prices(&tokens).await?;
+        let timestamp = self.clock.now();
+        let mut updated = 0;
+        for token in &tokens {
+            match prices.get(token) {
+                Some(&price) => {
+                    if self.record(token, price, timestamp, fallback.name()).await {
+                        updated += 1;
+                    }
+                }
+                None => debug!("{} returned no price for {}", fallback.name(), token),
+            }
+        }
+
+        debug!(
+            "{} fallback refreshed {}/{} prices",
+            fallback.name(),
+            updated,
+            tokens.len()
+        );
+        Ok(updated)
+    }
+}
+
+fn normalize(token: &str) -> String {
+    token.trim().to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchanges::{BinanceMarkPriceStream, MockPerpExchange};
+
+    /// Fallback zwracający stałe ceny
+    struct FixedPrices(HashMap<String, f64>);
+
+    #[async_trait]
+    impl PriceSnapshotSource for FixedPrices {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn fetch_prices(&self, tokens: &[String]) -> Result<HashMap<String, f64>> {
+            Ok(tokens
+                .iter()
+                .filter_map(|token| self.0.get(token).map(|price| (token.clone(), *price)))
+                .collect())
+        }
+    }
+
+    fn config() -> PriceFeedConfig {
+        PriceFeedConfig {
+            enabled: true,
+            max_price_age: 30,
+            reconnect_delay: 1,
+            fallback_poll_interval: 3600,
+            ..PriceFeedConfig::default()
+        }
+    }
+
+    async fn next_quote(updates: &mut broadcast::Receiver<PriceQuote>) -> PriceQuote {
+        tokio::time::timeout(Duration::from_secs(5), updates.recv())
+            .await
+            .expect("no price update")
+            .unwrap()
+    }
+
+    async fn wait_for(condition: impl Fn() -> bool) {
+        for _ in 0..100 {
+            if condition() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("condition not met");
+    }
+
+    #[tokio::test]
+    async fn test_record_normalizes_and_flags_stale_prices() {
+        let clock = Clock::simulated(1_700_000_000);
+        let feed = PriceFeed::new(config()).with_clock(clock.clone());
+        let mut updates = feed.subscribe();
+        feed.watch("bonk").await;
+        feed.watch("wif").await;
+
+        assert!(feed.record(" bonk ", 0.00002, 1_700_000_000, "test").await);
+        let quote = updates.recv().await.unwrap();
+        assert_eq!(quote.token, "BONK");
+        assert_eq!(feed.fresh_price("Bonk").await.unwrap(), 0.00002);
+
+        // Ceny niedodatnie i spóźnione ticki nie nadpisują tablicy
+        assert!(!feed.record("BONK", 0.0, 1_700_000_001, "test").await);
+        assert!(!feed.record("BONK", 0.00001, 1_699_999_990, "test").await);
+        assert_eq!(feed.quote("BONK").await.unwrap().price, 0.00002);
+        assert!(updates.try_recv().is_err());
+
+        assert_eq!(feed.stale_tokens().await, vec!["WIF".to_string()]);
+
+        clock.set(1_700_000_031);
+        assert!(feed.fresh_price("BONK").await.is_err());
+        assert_eq!(
+            feed.stale_tokens().await,
+            vec!["BONK".to_string(), "WIF".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_updates_and_fallback_after_disconnect() {
+        let exchange = MockPerpExchange::start("key", "secret", 1000.0)
+            .await
+            .unwrap();
+        exchange.add_market("BTCUSDT", 50_000.0, 0.0001).await;
+
+        let feed = Arc::new(
+            PriceFeed::new(config())
+                .with_stream(Arc::new(BinanceMarkPriceStream::new(&exchange.config())))
+                .with_fallback(Arc::new(FixedPrices(HashMap::from([(
+                    "BTC".to_string(),
+                    49_000.0,
+                )])))),
+        );
+        let mut updates = feed.subscribe();
+        feed.watch("BTC").await;
+        let runner = tokio::spawn(feed.clone().run());
+
+        // Fallback startuje przed połączeniem strumienia
+        let quote = next_quote(&mut updates).await;
+        assert_eq!(quote.source, "fixed");
+
+        let streaming = feed.clone();
+        wait_for(move || streaming.is_streaming()).await;
+        assert_eq!(feed.poll_fallback().await.unwrap(), 0);
+
+        exchange.set_mark_price("BTCUSDT", 51_000.0).await;
+        let quote = next_quote(&mut updates).await;
+        assert_eq!(quote.token, "BTC");
+        assert_eq!(quote.price, 51_000.0);
+        assert_eq!(quote.source, "binance_futures");
+        assert_eq!(feed.fresh_price("BTC").await.unwrap(), 51_000.0);
+
+        // Po zerwaniu strumienia ceny dostarcza fallback
+        exchange.disconnect_streams().await;
+        let streaming = feed.clone();
+        wait_for(move || !streaming.is_streaming()).await;
+        assert_eq!(feed.poll_fallback().await.unwrap(), 1);
+        let quote = feed.quote("BTC").await.unwrap();
+        assert_eq!(quote.price, 49_000.0);
+        assert_eq!(quote.source, "fixed");
+
+        // Strumień łączy się ponownie po `reconnect_delay`
+        let streaming = feed.clone();
+        wait_for(move || streaming.is_streaming()).await;
+        exchange.set_mark_price("BTCUSDT", 52_000.0).await;
+        loop {
+            let quote = next_quote(&mut updates).await;
+            if quote.source == "binance_futures" {
+                assert_eq!(quote.price, 52_000.0);
+                break;
+            }
+        }
+
+        runner.abort();
+    }
+}
@@ -8,10 +8,20 @@ use crate::risk::TradeSide;
 // pub mod executor;
 // pub mod orders;
 // pub mod manager;
+pub mod backtest;
+pub mod clock;
+pub mod engine;
+pub mod feed;
+pub mod paper;
 
 // pub use executor::TradeExecutor;
 // pub use orders::*;
 // pub use manager::PositionManager;
+pub use backtest::{BacktestReport, Backtester, Candle};
+pub use clock::Clock;
+pub use engine::{ExitReason, PositionExit, TradeDecision, TradingEngine};
+pub use feed::{PriceFeed, PriceQuote, PriceSnapshotSource, PriceStream};
+pub use paper::{PaperExchange, PriceTick};
 
 /// Typ zlecenia
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -87,6 +97,28 @@ pub struct ExecutionResult {
     pub metadata: serde_json::Value,
 }
 
+/// Rozliczona płatność finansowania pozycji perpetual
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingPayment {
+    /// Pozycja, której dotyczy płatność
+    pub position_id: String,
+
+    /// Token/symbol
+    pub token: String,
+
+    /// Stawka finansowania za okres
+    pub rate: f64,
+
+    /// Wartość nominalna pozycji w chwili rozliczenia
+    pub notional: f64,
+
+    /// Kwota płatności (dodatnia = zapłacona, ujemna = otrzymana)
+    pub amount: f64,
+
+    /// Czas rozliczenia (Unix timestamp)
+    pub settled_at: i64,
+}
+
 /// Trait dla executora transakcji
 #[async_trait]
 pub trait TradeExecutorTrait: Send + Sync {
@@ -104,6 +136,13 @@ pub trait TradeExecutorTrait: Send + Sync {
 
     /// Anuluje zlecenie
     async fn cancel_order(&self, order_id: &str) -> Result<()>;
+
+    /// Płatności finansowania rozliczone po `since` (Unix timestamp)
+    ///
+    /// Domyślnie brak płatności - dla wykonawców bez rynku perpetual.
+    async fn funding_payments(&self, _since: i64) -> Result<Vec<FundingPayment>> {
+        Ok(Vec::new())
+    }
 }
 
 /// Status zlecenia
@@ -181,9 +220,27 @@ impl TradeOrder {
         }
     }
 
+    /// Tworzy zlecenie take-profit
+    pub fn take_profit_order(
+        token: String,
+        side: TradeSide,
+        size: f64,
+        leverage: u8,
+        stop_price: f64,
+    ) -> Self {
+        Self {
+            order_type: OrderType::TakeProfit,
+            ..Self::stop_loss_order(token, side, size, leverage, stop_price)
+        }
+    }
+
     /// Sprawdza czy zlecenie wygasło
     pub fn is_expired(&self) -> bool {
-        let now = chrono::Utc::now().timestamp();
+        self.is_expired_at(chrono::Utc::now().timestamp())
+    }
+
+    /// Sprawdza czy zlecenie wygasło względem podanego czasu (np. zegara symulacji)
+    pub fn is_expired_at(&self, now: i64) -> bool {
         now - self.created_at > self.timeout as i64
     }
 
@@ -302,9 +359,29 @@ pub struct TradingStats {
     /// Całkowite opłaty
     pub total_fees: f64,
 
+    /// Suma rozliczonego finansowania (dodatnia = zapłacone)
+    #[serde(default)]
+    pub total_funding: f64,
+
     /// Współczynnik sukcesu
     pub success_rate: f64,
 
+    /// Liczba zamkniętych transakcji z zyskiem
+    #[serde(default)]
+    pub winning_trades: u64,
+
+    /// Liczba zamkniętych transakcji ze stratą
+    #[serde(default)]
+    pub losing_trades: u64,
+
+    /// Suma zysków transakcji zyskownych
+    #[serde(default)]
+    pub gross_profit: f64,
+
+    /// Suma strat transakcji stratnych (wartość dodatnia)
+    #[serde(default)]
+    pub gross_loss: f64,
+
     /// Ostatnia aktualizacja
     pub last_updated: i64,
 }
@@ -320,7 +397,12 @@ impl Default for TradingStats {
             worst_trade: f64::INFINITY,
             avg_execution_time: 0.0,
             total_fees: 0.0,
+            total_funding: 0.0,
             success_rate: 0.0,
+            winning_trades: 0,
+            losing_trades: 0,
+            gross_profit: 0.0,
+            gross_loss: 0.0,
             last_updated: chrono::Utc::now().timestamp(),
         }
     }
@@ -354,6 +436,7 @@ impl TradingStats {
             if let Some(fees) = result.fees {
                 self.total_fees += fees;
             }
+            self.record_outcome(pnl);
         } else {
             self.failed_trades += 1;
         }
@@ -368,6 +451,64 @@ impl TradingStats {
 
         self.last_updated = chrono::Utc::now().timestamp();
     }
+
+    /// Zapisuje wynik zamkniętej transakcji (zysk lub strata)
+    ///
+    /// Transakcje z zerowym P&L (np. otwarcia) nie wpływają na statystyki wygranych.
+    pub fn record_outcome(&mut self, pnl: f64) {
+        if pnl > 0.0 {
+            self.winning_trades += 1;
+            self.gross_profit += pnl;
+        } else if pnl < 0.0 {
+            self.losing_trades += 1;
+            self.gross_loss += -pnl;
+        }
+    }
+
+    /// Liczba zamkniętych transakcji z niezerowym wynikiem
+    pub fn closed_trades(&self) -> u64 {
+        self.winning_trades + self.losing_trades
+    }
+
+    /// Odsetek transakcji zyskownych (`None` bez zamkniętych transakcji)
+    pub fn win_rate(&self) -> Option<f64> {
+        match self.closed_trades() {
+            0 => None,
+            closed => Some(self.winning_trades as f64 / closed as f64),
+        }
+    }
+
+    /// Stosunek średniego zysku do średniej straty (`None` bez zysków lub strat)
+    pub fn payoff_ratio(&self) -> Option<f64> {
+        if self.winning_trades == 0 || self.losing_trades == 0 || self.gross_loss <= 0.0 {
+            return None;
+        }
+
+        let average_win = self.gross_profit / self.winning_trades as f64;
+        let average_loss = self.gross_loss / self.losing_trades as f64;
+        Some(average_win / average_loss)
+    }
+
+    /// Pełne kryterium Kelly'ego `W - (1 - W) / R`
+    ///
+    /// Wartość ujemna oznacza brak przewagi. Bez strat zwraca 1.0, bez zysków 0.0.
+    pub fn kelly_fraction(&self) -> Option<f64> {
+        let win_rate = self.win_rate()?;
+        Some(match self.payoff_ratio() {
+            Some(payoff) => win_rate - (1.0 - win_rate) / payoff,
+            None if self.losing_trades == 0 => 1.0,
+            None => 0.0,
+        })
+    }
+
+    /// Dolicza rozliczoną płatność finansowania
+    ///
+    /// P&L pozycji obejmuje finansowanie dopiero przy zamknięciu
+    /// (w `update_for_trade`), więc `total_pnl` się tu nie zmienia.
+    pub fn update_for_funding(&mut self, payment: &FundingPayment) {
+        self.total_funding += payment.amount;
+        self.last_updated = chrono::Utc::now().timestamp();
+    }
 }
 
 #[cfg(test)]
@@ -610,6 +751,31 @@ mod tests {
         assert!((stats.success_rate - 0.6666666666666666).abs() < 0.0001); // 2/3
     }
 
+    #[test]
+    fn test_trading_stats_kelly_fraction() {
+        let mut stats = TradingStats::default();
+        assert_eq!(stats.kelly_fraction(), None);
+
+        // 6 wygranych po 20 USD, 4 przegrane po 10 USD: W = 0.6, R = 2
+        for _ in 0..6 {
+            stats.record_outcome(20.0);
+        }
+        for _ in 0..4 {
+            stats.record_outcome(-10.0);
+        }
+        stats.record_outcome(0.0);
+
+        assert_eq!(stats.closed_trades(), 10);
+        assert!((stats.win_rate().unwrap() - 0.6).abs() < 1e-9);
+        assert!((stats.payoff_ratio().unwrap() - 2.0).abs() < 1e-9);
+        // 0.6 - 0.4 / 2 = 0.4
+        assert!((stats.kelly_fraction().unwrap() - 0.4).abs() < 1e-9);
+
+        let mut losing = TradingStats::default();
+        losing.record_outcome(-5.0);
+        assert_eq!(losing.kelly_fraction(), Some(0.0));
+    }
+
     #[test]
     fn test_order_type_equality() {
         assert_eq!(OrderType::Market, OrderType::Market);
@@ -0,0 +1,1080 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use super::{
+    ExecutionResult, FundingPayment, OrderStatus, OrderType, TradeExecutorTrait, TradeOrder,
+};
+use crate::config::{PaperTradingConfig, TradingConfig};
+use crate::risk::{Position, TradeSide};
+
+/// Tolerancja przy porównywaniu rozmiarów pozycji
+const SIZE_EPSILON: f64 = 1e-9;
+
+/// Liczba zakończonych zleceń przechowywanych w księdze (do `get_order_status`)
+const MAX_FINISHED_ORDERS: usize = 1_000;
+
+/// Tick cenowy z zasilania (na żywo lub odtwarzanego z historii)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceTick {
+    /// Token/symbol
+    pub token: String,
+
+    /// Cena
+    pub price: f64,
+
+    /// Timestamp (Unix timestamp)
+    pub timestamp: i64,
+}
+
+/// Wpis w księdze zleceń symulatora
+#[derive(Debug, Clone)]
+struct BookEntry {
+    order: TradeOrder,
+    status: OrderStatus,
+    /// Pozycja, którą zlecenie redukuje (stop-loss, take-profit, zamknięcie)
+    position_id: Option<String>,
+}
+
+impl BookEntry {
+    /// Czy zlecenie nie czeka już w księdze
+    fn is_finished(&self) -> bool {
+        !matches!(
+            self.status,
+            OrderStatus::Pending | OrderStatus::PartiallyFilled
+        )
+    }
+
+    /// Czy identyfikator zlecenia można wysłać ponownie
+    fn can_resubmit(&self) -> bool {
+        self.is_finished() && self.status != OrderStatus::Filled
+    }
+}
+
+/// Wewnętrzny stan symulatora
+#[derive(Debug, Default)]
+struct ExchangeState {
+    balance: f64,
+    prices: HashMap<String, f64>,
+    orders: HashMap<String, BookEntry>,
+    positions: HashMap<String, Position>,
+    fills: Vec<ExecutionResult>,
+    clock: Option<i64>,
+    total_fees: f64,
+    realized_pnl: f64,
+    funding_rates: HashMap<String, f64>,
+    funding: Vec<FundingPayment>,
+}
+
+impl ExchangeState {
+    fn now(&self) -> i64 {
+        self.clock.unwrap_or_else(|| chrono::Utc::now().timestamp())
+    }
+
+    fn used_margin(&self) -> f64 {
+        self.positions.values().map(|pos| pos.size).sum()
+    }
+
+    /// Usuwa najstarsze zakończone zlecenia ponad `MAX_FINISHED_ORDERS`
+    fn prune_orders(&mut self) {
+        let mut finished: Vec<(i64, String)> = self
+            .orders
+            .values()
+            .filter(|entry| entry.is_finished())
+            .map(|entry| (entry.order.created_at, entry.order.id.clone()))
+            .collect();
+
+        if finished.len() <= MAX_FINISHED_ORDERS {
+            return;
+        }
+
+        finished.sort();
+        let excess = finished.len() - MAX_FINISHED_ORDERS;
+        for (_, id) in finished.into_iter().take(excess) {
+            self.orders.remove(&id);
+        }
+    }
+}
+
+/// Symulator giełdy działający w procesie (paper trading)
+///
+/// Zlecenia rynkowe są realizowane natychmiast po ostatniej znanej cenie
+/// z uwzględnieniem poślizgu i opłat. Zlecenia Limit, StopLoss i TakeProfit
+/// czekają w księdze do momentu, gdy cena z zasilania je aktywuje lub
+/// upłynie ich `timeout`.
+///
+/// Zlecenie z `metadata.position_id` redukuje wskazaną pozycję zamiast
+/// otwierać nową; jego `side` to strona chronionej pozycji.
+///
+/// Rozmiar zlecenia (`size`) to marża pozycji, tak jak `Position::size`.
+/// Opłaty liczone są od wartości nominalnej (`size * leverage`), a strata
+/// pozycji nie przekracza jej marży (marża izolowana).
+///
+/// Identyfikator zlecenia odrzuconego, anulowanego lub wygasłego można
+/// wysłać ponownie; zakończone zlecenia są z czasem usuwane z księgi.
+///
+/// Finansowanie jest rozliczane z balansu co `funding_interval_hours`
+/// (licząc od początku epoki) wg stawki domyślnej lub ustawionej dla tokena.
+pub struct PaperExchange {
+    slippage: f64,
+    taker_fee: f64,
+    maker_fee: f64,
+    funding_rate: f64,
+    funding_interval: i64,
+    state: Arc<RwLock<ExchangeState>>,
+}
+
+impl PaperExchange {
+    /// Tworzy nowy symulator z podanym balansem początkowym
+    pub fn new(config: &PaperTradingConfig, initial_balance: f64) -> Self {
+        Self {
+            slippage: config.slippage_percent.to_f64().unwrap_or(0.0),
+            taker_fee: config.taker_fee_percent.to_f64().unwrap_or(0.0),
+            maker_fee: config.maker_fee_percent.to_f64().unwrap_or(0.0),
+            funding_rate: config.funding_rate_percent.to_f64().unwrap_or(0.0),
+            funding_interval: config.funding_interval_hours as i64 * 3600,
+            state: Arc::new(RwLock::new(ExchangeState {
+                balance: initial_balance,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Tworzy symulator na podstawie konfiguracji tradingu
+    pub fn from_config(config: &TradingConfig) -> Self {
+        Self::new(
+            &config.paper,
+            config.initial_balance.to_f64().unwrap_or(0.0),
+        )
+    }
+
+    /// Ustawia zegar symulacji (dla odtwarzania historii)
+    pub async fn set_time(&self, timestamp: i64) {
+        let mut state = self.state.write().await;
+        state.clock = Some(timestamp);
+        self.settle_funding_locked(&mut state);
+    }
+
+    /// Ustawia stawkę finansowania tokena (zamiast domyślnej z konfiguracji)
+    pub async fn set_funding_rate(&self, token: &str, rate: f64) {
+        self.state
+            .write()
+            .await
+            .funding_rates
+            .insert(token.to_string(), rate);
+    }
+
+    /// Odtwarza otwarte pozycje (np. z rejestru po restarcie aplikacji)
+    ///
+    /// Marża pozycji nie jest ponownie pobierana z balansu; dla tokenów bez
+    /// znanej ceny przyjmowana jest ostatnia cena pozycji.
+    pub async fn restore_positions<I>(&self, positions: I)
+    where
+        I: IntoIterator<Item = Position>,
+    {
+        let mut state = self.state.write().await;
+        for position in positions {
+            state
+                .prices
+                .entry(position.token.clone())
+                .or_insert(position.current_price);
+            state.positions.insert(position.id.clone(), position);
+        }
+    }
+
+    /// Zwraca aktualny czas symulatora
+    pub async fn now(&self) -> i64 {
+        self.state.read().await.now()
+    }
+
+    /// Przyjmuje nową cenę i realizuje aktywowane zlecenia
+    pub async fn update_price(&self, token: &str, price: f64) -> Vec<ExecutionResult> {
+        if price <= 0.0 {
+            warn!("Ignoring invalid price {} for {}", price, token);
+            return Vec::new();
+        }
+
+        let mut state = self.state.write().await;
+        self.settle_funding_locked(&mut state);
+        state.prices.insert(token.to_string(), price);
+
+        for position in state
+            .positions
+            .values_mut()
+            .filter(|pos| pos.token == token)
+        {
+            position.update_price(price);
+        }
+
+        Self::expire_locked(&mut state);
+        self.match_orders_locked(&mut state, token, price)
+    }
+
+    /// Odtwarza sekwencję ticków, przesuwając zegar symulacji
+    pub async fn replay<I>(&self, ticks: I) -> Vec<ExecutionResult>
+    where
+        I: IntoIterator<Item = PriceTick>,
+    {
+        let mut fills = Vec::new();
+
+        for tick in ticks {
+            self.set_time(tick.timestamp).await;
+            fills.extend(self.update_price(&tick.token, tick.price).await);
+        }
+
+        fills
+    }
+
+    /// Oznacza przeterminowane zlecenia jako wygasłe, zwraca ich identyfikatory
+    pub async fn expire_orders(&self) -> Vec<String> {
+        let mut state = self.state.write().await;
+        Self::expire_locked(&mut state)
+    }
+
+    /// Zwraca aktualny balans (bez niezrealizowanego P&L)
+    pub async fn balance(&self) -> f64 {
+        self.state.read().await.balance
+    }
+
+    /// Zwraca equity (balans + niezrealizowany P&L)
+    pub async fn equity(&self) -> f64 {
+        let state = self.state.read().await;
+        state.balance
+            + state
+                .positions
+                .values()
+                .map(|pos| pos.price_pnl().max(-pos.size))
+                .sum::<f64>()
+    }
+
+    /// Zwraca sumę pobranych opłat
+    pub async fn total_fees(&self) -> f64 {
+        self.state.read().await.total_fees
+    }
+
+    /// Zwraca sumę rozliczonego finansowania (dodatnia = zapłacone)
+    pub async fn total_funding(&self) -> f64 {
+        self.state
+            .read()
+            .await
+            .funding
+            .iter()
+            .map(|payment| payment.amount)
+            .sum()
+    }
+
+    /// Zwraca zrealizowany P&L (przed opłatami)
+    pub async fn realized_pnl(&self) -> f64 {
+        self.state.read().await.realized_pnl
+    }
+
+    /// Zwraca otwarte pozycje
+    pub async fn open_positions(&self) -> Vec<Position> {
+        self.state
+            .read()
+            .await
+            .positions
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Zwraca pozycję według ID
+    pub async fn get_position(&self, position_id: &str) -> Option<Position> {
+        self.state.read().await.positions.get(position_id).cloned()
+    }
+
+    /// Zwraca zlecenia oczekujące w księdze
+    pub async fn open_orders(&self) -> Vec<TradeOrder> {
+        self.state
+            .read()
+            .await
+            .orders
+            .values()
+            .filter(|entry| entry.status == OrderStatus::Pending)
+            .map(|entry| entry.order.clone())
+            .collect()
+    }
+
+    /// Zwraca historię wykonań
+    pub async fn fills(&self) -> Vec<ExecutionResult> {
+        self.state.read().await.fills.clone()
+    }
+
+    fn expire_locked(state: &mut ExchangeState) -> Vec<String> {
+        let now = state.now();
+        let mut expired = Vec::new();
+
+        for (id, entry) in state.orders.iter_mut() {
+            if entry.status == OrderStatus::Pending && entry.order.is_expired_at(now) {
+                entry.status = OrderStatus::Expired;
+                expired.push(id.clone());
+            }
+        }
+
+        for id in &expired {
+            debug!("Paper order {} expired", id);
+        }
+
+        expired
+    }
+
+    /// Sprawdza czy zlecenie jest aktywowane przy danej cenie
+    fn is_triggered(entry: &BookEntry, price: f64) -> bool {
+        let order = &entry.order;
+        let reducing = entry.position_id.is_some();
+
+        match order.order_type {
+            OrderType::Market => true,
+            OrderType::Limit => {
+                let limit = order.price.unwrap_or(0.0);
+                // Kupno: Long przy otwieraniu, Short przy redukcji
+                let buying = (order.side == TradeSide::Long) != reducing;
+                if buying {
+                    price <= limit
+                } else {
+                    price >= limit
+                }
+            }
+            OrderType::StopLoss => {
+                let stop = order.stop_price.unwrap_or(0.0);
+                match order.side {
+                    TradeSide::Long => price <= stop,
+                    TradeSide::Short => price >= stop,
+                }
+            }
+            OrderType::TakeProfit => {
+                let stop = order.stop_price.unwrap_or(0.0);
+                match order.side {
+                    TradeSide::Long => price >= stop,
+                    TradeSide::Short => price <= stop,
+                }
+            }
+        }
+    }
+
+    /// Cena wykonania zlecenia aktywnego po cenie rynkowej (z poślizgiem)
+    fn taker_price(&self, side: &TradeSide, reducing: bool, market_price: f64) -> f64 {
+        let buying = (*side == TradeSide::Long) != reducing;
+        if buying {
+            market_price * (1.0 + self.slippage)
+        } else {
+            market_price * (1.0 - self.slippage)
+        }
+    }
+
+    fn match_orders_locked(
+        &self,
+        state: &mut ExchangeState,
+        token: &str,
+        price: f64,
+    ) -> Vec<ExecutionResult> {
+        let mut triggered: Vec<String> = state
+            .orders
+            .values()
+            .filter(|entry| {
+                entry.status == OrderStatus::Pending
+                    && entry.order.token == token
+                    && Self::is_triggered(entry, price)
+            })
+            .map(|entry| entry.order.id.clone())
+            .collect();
+
+        // Deterministyczna kolejność: najstarsze zlecenia pierwsze
+        triggered.sort_by_key(|id| {
+            state
+                .orders
+                .get(id)
+                .map(|entry| entry.order.created_at)
+                .unwrap_or_default()
+        });
+
+        let mut results = Vec::new();
+        for id in triggered {
+            // Zlecenie mogło zostać anulowane przez zamknięcie pozycji
+            let entry = match state.orders.get(&id) {
+                Some(entry) if entry.status == OrderStatus::Pending => entry.clone(),
+                _ => continue,
+            };
+
+            let result = self.fill_locked(state, &entry, price, true);
+            results.push(result);
+        }
+
+        results
+    }
+
+    /// Realizuje zlecenie z księgi lub zlecenie rynkowe
+    fn fill_locked(
+        &self,
+        state: &mut ExchangeState,
+        entry: &BookEntry,
+        market_price: f64,
+        from_book: bool,
+    ) -> ExecutionResult {
+        let order = &entry.order;
+        let reducing = entry.position_id.is_some();
+
+        // Zlecenia limit z księgi realizowane są po cenie limitu jako maker
+        let (fill_price, fee_rate) = match order.order_type {
+            OrderType::Limit if from_book => (order.price.unwrap_or(market_price), self.maker_fee),
+            _ => (
+                self.taker_price(&order.side, reducing, market_price),
+                self.taker_fee,
+            ),
+        };
+
+        let result = match &entry.position_id {
+            Some(position_id) => {
+                self.reduce_position_locked(state, order, position_id, fill_price, fee_rate)
+            }
+            None => self.open_position_locked(state, order, fill_price, fee_rate),
+        };
+
+        let status = if result.success {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::Rejected
+        };
+
+        if let Some(book_entry) = state.orders.get_mut(&order.id) {
+            book_entry.status = status;
+        }
+
+        state.fills.push(result.clone());
+        result
+    }
+
+    /// Rozlicza finansowanie otwartych pozycji do bieżącego czasu
+    fn settle_funding_locked(&self, state: &mut ExchangeState) {
+        let now = state.now();
+        let mut settled = Vec::new();
+
+        for position in state.positions.values_mut() {
+            let rate = state
+                .funding_rates
+                .get(&position.token)
+                .copied()
+                .unwrap_or(self.funding_rate);
+
+            for settled_at in position.funding_times_due(now, self.funding_interval) {
+                let notional = position.notional();
+                let amount = position.apply_funding(rate, settled_at);
+                settled.push(FundingPayment {
+                    position_id: position.id.clone(),
+                    token: position.token.clone(),
+                    rate,
+                    notional,
+                    amount,
+                    settled_at,
+                });
+            }
+        }
+
+        for payment in settled {
+            debug!(
+                "Paper funding for {} ({}): {:.6} @ rate {}",
+                payment.position_id, payment.token, payment.amount, payment.rate
+            );
+            state.balance -= payment.amount;
+            state.funding.push(payment);
+        }
+    }
+
+    fn open_position_locked(
+        &self,
+        state: &mut ExchangeState,
+        order: &TradeOrder,
+        fill_price: f64,
+        fee_rate: f64,
+    ) -> ExecutionResult {
+        let margin = order.size;
+        let fee = order.size * order.leverage as f64 * fee_rate;
+        let available = state.balance - state.used_margin();
+
+        if margin + fee > available {
+            return ExecutionResult::error(format!(
+                "Insufficient margin: required {:.4}, available {:.4}",
+                margin + fee,
+                available
+            ));
+        }
+
+        let now = state.now();
+        let mut position = Position::new(
+            order.token.clone(),
+            order.side.clone(),
+            order.size,
+            order.leverage,
+            fill_price,
+        );
+        position.opened_at = now;
+
+        state.balance -= fee;
+        state.total_fees += fee;
+
+        info!(
+            "Paper {:?} position {} opened: {} size {:.4} @ {:.8} ({}x)",
+            position.side, position.id, position.token, position.size, fill_price, order.leverage
+        );
+
+        let mut result = ExecutionResult::success(order.id.clone(), fill_price, order.size, fee);
+        result.executed_at = Some(now);
+        result.metadata = serde_json::json!({
+            "order_id": order.id,
+            "position_id": position.id,
+            "order_type": order.order_type,
+            "action": "open",
+        });
+
+        state.positions.insert(position.id.clone(), position);
+        result
+    }
+
+    fn reduce_position_locked(
+        &self,
+        state: &mut ExchangeState,
+        order: &TradeOrder,
+        position_id: &str,
+        fill_price: f64,
+        fee_rate: f64,
+    ) -> ExecutionResult {
+        let now = state.now();
+        let (close_size, realized, notional, fully_closed) =
+            match state.positions.get_mut(position_id) {
+                Some(position) => {
+                    let close_size = order.size.min(position.size);
+                    let share = close_size / position.size;
+                    position.update_price(fill_price);
+                    // Opłaty i finansowanie są już rozliczone z balansu; strata
+                    // nie przekracza zamykanej części marży
+                    let realized = position.price_pnl().max(-position.size) * share;
+                    let notional = position.notional() * share;
+                    let fully_closed = position.size - close_size <= SIZE_EPSILON;
+
+                    if fully_closed {
+                        position.close();
+                        position.closed_at = Some(now);
+                    } else {
+                        position.size -= close_size;
+                        position.update_price(fill_price);
+                    }
+
+                    (close_size, realized, notional, fully_closed)
+                }
+                None => {
+                    return ExecutionResult::error(format!("Position {} not found", position_id));
+                }
+            };
+
+        let fee = notional * fee_rate;
+        state.balance += realized - fee;
+        state.realized_pnl += realized;
+        state.total_fees += fee;
+
+        if fully_closed {
+            state.positions.remove(position_id);
+
+            // Pozostałe zlecenia ochronne tej pozycji tracą sens
+            for entry in state.orders.values_mut() {
+                if entry.status == OrderStatus::Pending
+                    && entry.position_id.as_deref() == Some(position_id)
+                    && entry.order.id != order.id
+                {
+                    entry.status = OrderStatus::Cancelled;
+                }
+            }
+        }
+
+        info!(
+            "Paper position {} reduced by {:.4} @ {:.8}, realized P&L {:.4}",
+            position_id, close_size, fill_price, realized
+        );
+
+        let mut result = ExecutionResult::success(order.id.clone(), fill_price, close_size, fee);
+        result.executed_at = Some(now);
+        result.metadata = serde_json::json!({
+            "order_id": order.id,
+            "position_id": position_id,
+            "order_type": order.order_type,
+            "action": if fully_closed { "close" } else { "reduce" },
+            "realized_pnl": realized,
+        });
+        result
+    }
+
+    /// Ustala pozycję, którą redukuje zlecenie (jeśli dotyczy)
+    fn resolve_position(state: &ExchangeState, order: &TradeOrder) -> Result<Option<String>> {
+        if let Some(position_id) = order.metadata.get("position_id").and_then(|v| v.as_str()) {
+            if !state.positions.contains_key(position_id) {
+                anyhow::bail!("Position {} not found", position_id);
+            }
+            return Ok(Some(position_id.to_string()));
+        }
+
+        match order.order_type {
+            OrderType::StopLoss | OrderType::TakeProfit => state
+                .positions
+                .values()
+                .find(|pos| pos.token == order.token && pos.side == order.side)
+                .map(|pos| Some(pos.id.clone()))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Stop orders require an open {:?} position in {}",
+                        order.side,
+                        order.token
+                    )
+                }),
+            _ => Ok(None),
+        }
+    }
+
+    fn reject_locked(
+        state: &mut ExchangeState,
+        order: &TradeOrder,
+        reason: String,
+    ) -> ExecutionResult {
+        warn!("Paper order {} rejected: {}", order.id, reason);
+        state.orders.insert(
+            order.id.clone(),
+            BookEntry {
+                order: order.clone(),
+                status: OrderStatus::Rejected,
+                position_id: None,
+            },
+        );
+        ExecutionResult::error(reason)
+    }
+}
+
+#[async_trait]
+impl TradeExecutorTrait for PaperExchange {
+    async fn execute_trade(&self, order: &TradeOrder) -> Result<ExecutionResult> {
+        let mut state = self.state.write().await;
+
+        // Ponowne wysłanie dozwolone tylko dla zleceń odrzuconych, anulowanych lub wygasłych
+        if state
+            .orders
+            .get(&order.id)
+            .is_some_and(|entry| !entry.can_resubmit())
+        {
+            anyhow::bail!("Order {} already submitted", order.id);
+        }
+        state.prune_orders();
+
+        if let Err(e) = order.validate() {
+            return Ok(Self::reject_locked(&mut state, order, e.to_string()));
+        }
+
+        let position_id = match Self::resolve_position(&state, order) {
+            Ok(position_id) => position_id,
+            Err(e) => return Ok(Self::reject_locked(&mut state, order, e.to_string())),
+        };
+
+        let market_price = state.prices.get(&order.token).copied();
+        let entry = BookEntry {
+            order: order.clone(),
+            status: OrderStatus::Pending,
+            position_id,
+        };
+
+        match market_price {
+            Some(price) if Self::is_triggered(&entry, price) => {
+                state.orders.insert(order.id.clone(), entry.clone());
+                Ok(self.fill_locked(&mut state, &entry, price, false))
+            }
+            None if order.order_type == OrderType::Market => Ok(Self::reject_locked(
+                &mut state,
+                order,
+                format!("No price available for {}", order.token),
+            )),
+            _ => {
+                debug!(
+                    "Paper order {} ({:?}) resting in book",
+                    order.id, order.order_type
+                );
+                state.orders.insert(order.id.clone(), entry);
+
+                Ok(ExecutionResult {
+                    success: true,
+                    transaction_id: Some(order.id.clone()),
+                    executed_price: None,
+                    executed_size: None,
+                    fees: None,
+                    executed_at: None,
+                    error_message: None,
+                    metadata: serde_json::json!({
+                        "order_id": order.id,
+                        "status": OrderStatus::Pending,
+                    }),
+                })
+            }
+        }
+    }
+
+    async fn close_position(&self, position_id: &str) -> Result<()> {
+        let mut state = self.state.write().await;
+
+        let position = state
+            .positions
+            .get(position_id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Position {} not found", position_id))?;
+
+        let price = state
+            .prices
+            .get(&position.token)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("No price available for {}", position.token))?;
+
+        let mut order = TradeOrder::market_order(
+            position.token.clone(),
+            position.side.clone(),
+            position.size,
+            position.leverage,
+        );
+        order.created_at = state.now();
+        order.metadata = serde_json::json!({ "position_id": position_id });
+
+        let entry = BookEntry {
+            order: order.clone(),
+            status: OrderStatus::Pending,
+            position_id: Some(position_id.to_string()),
+        };
+        state.prune_orders();
+        state.orders.insert(order.id.clone(), entry.clone());
+
+        let result = self.fill_locked(&mut state, &entry, price, false);
+        if !result.success {
+            anyhow::bail!(
+                "Failed to close position {}: {}",
+                position_id,
+                result.error_message.unwrap_or_default()
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn get_current_price(&self, token: &str) -> Result<f64> {
+        self.state
+            .read()
+            .await
+            .prices
+            .get(token)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("No price available for {}", token))
+    }
+
+    async fn get_order_status(&self, order_id: &str) -> Result<OrderStatus> {
+        let mut state = self.state.write().await;
+        Self::expire_locked(&mut state);
+
+        state
+            .orders
+            .get(order_id)
+            .map(|entry| entry.status.clone())
+            .ok_or_else(|| anyhow::anyhow!("Order {} not found", order_id))
+    }
+
+    async fn funding_payments(&self, since: i64) -> Result<Vec<FundingPayment>> {
+        let mut state = self.state.write().await;
+        self.settle_funding_locked(&mut state);
+
+        Ok(state
+            .funding
+            .iter()
+            .filter(|payment| payment.settled_at > since)
+            .cloned()
+            .collect())
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        let mut state = self.state.write().await;
+        Self::expire_locked(&mut state);
+
+        let entry = state
+            .orders
+            .get_mut(order_id)
+            .ok_or_else(|| anyhow::anyhow!("Order {} not found", order_id))?;
+
+        if entry.status != OrderStatus::Pending {
+            anyhow::bail!(
+                "Order {} cannot be cancelled in status {:?}",
+                order_id,
+                entry.status
+            );
+        }
+
+        entry.status = OrderStatus::Cancelled;
+        debug!("Paper order {} cancelled", order_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn exchange() -> PaperExchange {
+        let config = PaperTradingConfig {
+            slippage_percent: Decimal::new(1, 2),  // 1%
+            taker_fee_percent: Decimal::new(1, 3), // 0.1%
+            maker_fee_percent: Decimal::ZERO,
+            funding_rate_percent: Decimal::ZERO,
+            funding_interval_hours: 8,
+        };
+        PaperExchange::new(&config, 1000.0)
+    }
+
+    #[tokio::test]
+    async fn test_market_order_fills_with_slippage_and_fee() {
+        let exchange = exchange();
+        exchange.update_price("BONK", 1.0).await;
+
+        let order = TradeOrder::market_order("BONK".to_string(), TradeSide::Long, 100.0, 5);
+        let result = exchange.execute_trade(&order).await.unwrap();
+
+        // Marża 100 USD przy 5x: opłata 0.1% od 500 USD wartości nominalnej
+        assert!(result.success);
+        assert!((result.executed_price.unwrap() - 1.01).abs() < 1e-9);
+        assert!((result.fees.unwrap() - 0.5).abs() < 1e-9);
+        assert!((exchange.balance().await - 999.5).abs() < 1e-9);
+        assert_eq!(exchange.open_positions().await.len(), 1);
+        assert_eq!(
+            exchange.get_order_status(&order.id).await.unwrap(),
+            OrderStatus::Filled
+        );
+    }
+
+    #[tokio::test]
+    async fn test_market_order_without_price_is_rejected() {
+        let exchange = exchange();
+        let order = TradeOrder::market_order("NOPE".to_string(), TradeSide::Long, 10.0, 2);
+
+        let result = exchange.execute_trade(&order).await.unwrap();
+
+        assert!(!result.success);
+        assert_eq!(
+            exchange.get_order_status(&order.id).await.unwrap(),
+            OrderStatus::Rejected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_limit_order_rests_then_fills() {
+        let exchange = exchange();
+        exchange.update_price("BONK", 1.0).await;
+
+        let order = TradeOrder::limit_order("BONK".to_string(), TradeSide::Long, 50.0, 2, 0.9);
+        let result = exchange.execute_trade(&order).await.unwrap();
+        assert!(result.success);
+        assert!(result.executed_price.is_none());
+        assert_eq!(exchange.open_orders().await.len(), 1);
+
+        let fills = exchange.update_price("BONK", 0.95).await;
+        assert!(fills.is_empty());
+
+        let fills = exchange.update_price("BONK", 0.89).await;
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].executed_price, Some(0.9));
+        assert_eq!(
+            exchange.get_order_status(&order.id).await.unwrap(),
+            OrderStatus::Filled
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stop_loss_closes_position() {
+        let exchange = exchange();
+        exchange.update_price("WIF", 2.0).await;
+
+        let entry = TradeOrder::market_order("WIF".to_string(), TradeSide::Long, 100.0, 5);
+        exchange.execute_trade(&entry).await.unwrap();
+
+        let stop = TradeOrder::stop_loss_order("WIF".to_string(), TradeSide::Long, 100.0, 5, 1.8);
+        let accepted = exchange.execute_trade(&stop).await.unwrap();
+        assert!(accepted.success);
+
+        let fills = exchange.update_price("WIF", 1.7).await;
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].metadata["action"], "close");
+        assert!(exchange.open_positions().await.is_empty());
+        assert!(exchange.balance().await < 1000.0);
+    }
+
+    #[tokio::test]
+    async fn test_stop_order_without_position_is_rejected() {
+        let exchange = exchange();
+        exchange.update_price("WIF", 2.0).await;
+
+        let stop = TradeOrder::stop_loss_order("WIF".to_string(), TradeSide::Long, 10.0, 5, 1.8);
+        let result = exchange.execute_trade(&stop).await.unwrap();
+
+        assert!(!result.success);
+    }
+
+    #[tokio::test]
+    async fn test_orders_expire_on_simulated_clock() {
+        let exchange = exchange();
+        exchange.set_time(1_000).await;
+        exchange.update_price("BONK", 1.0).await;
+
+        let mut order = TradeOrder::limit_order("BONK".to_string(), TradeSide::Long, 10.0, 2, 0.5);
+        order.created_at = 1_000;
+        exchange.execute_trade(&order).await.unwrap();
+
+        exchange
+            .replay(vec![PriceTick {
+                token: "BONK".to_string(),
+                price: 0.9,
+                timestamp: 1_000 + order.timeout as i64 + 1,
+            }])
+            .await;
+
+        assert_eq!(
+            exchange.get_order_status(&order.id).await.unwrap(),
+            OrderStatus::Expired
+        );
+        assert!(exchange.cancel_order(&order.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_order() {
+        let exchange = exchange();
+        exchange.update_price("BONK", 1.0).await;
+
+        let order = TradeOrder::limit_order("BONK".to_string(), TradeSide::Short, 10.0, 2, 1.5);
+        exchange.execute_trade(&order).await.unwrap();
+        exchange.cancel_order(&order.id).await.unwrap();
+
+        assert_eq!(
+            exchange.get_order_status(&order.id).await.unwrap(),
+            OrderStatus::Cancelled
+        );
+        assert!(exchange.update_price("BONK", 1.6).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_close_position_realizes_pnl() {
+        let exchange = exchange();
+        exchange.update_price("BONK", 1.0).await;
+
+        let order = TradeOrder::market_order("BONK".to_string(), TradeSide::Short, 100.0, 2);
+        let result = exchange.execute_trade(&order).await.unwrap();
+        let position_id = result.metadata["position_id"].as_str().unwrap().to_string();
+
+        exchange.update_price("BONK", 0.8).await;
+        exchange.close_position(&position_id).await.unwrap();
+
+        assert!(exchange.get_position(&position_id).await.is_none());
+        assert!(exchange.realized_pnl().await > 0.0);
+        assert!(exchange.close_position(&position_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_funding_settles_on_simulated_clock() {
+        let exchange = exchange();
+        exchange.set_time(3_600).await;
+        exchange.update_price("BONK", 1.0).await;
+        exchange.set_funding_rate("BONK", 0.001).await;
+
+        let order = TradeOrder::market_order("BONK".to_string(), TradeSide::Long, 100.0, 2);
+        let result = exchange.execute_trade(&order).await.unwrap();
+        let position_id = result.metadata["position_id"].as_str().unwrap().to_string();
+        let balance = exchange.balance().await;
+
+        exchange.set_time(28_799).await;
+        assert!(exchange.funding_payments(0).await.unwrap().is_empty());
+
+        // Dwa rozliczenia (08:00 i 16:00) od wartości nominalnej po cenie wejścia
+        exchange.set_time(57_600).await;
+        let payments = exchange.funding_payments(0).await.unwrap();
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[0].position_id, position_id);
+        assert_eq!(payments[1].settled_at, 57_600);
+        assert!(payments[0].amount > 0.0);
+
+        let funding = exchange.total_funding().await;
+        assert!((exchange.balance().await - (balance - funding)).abs() < 1e-9);
+        assert_eq!(exchange.funding_payments(28_800).await.unwrap().len(), 1);
+
+        let position = exchange.get_position(&position_id).await.unwrap();
+        assert!((position.funding_paid - funding).abs() < 1e-9);
+        assert!(position.calculate_pnl() < position.price_pnl());
+    }
+
+    #[tokio::test]
+    async fn test_adverse_move_loses_at_most_posted_margin() {
+        let exchange = exchange();
+        exchange.update_price("BONK", 1.0).await;
+
+        let order = TradeOrder::market_order("BONK".to_string(), TradeSide::Long, 100.0, 20);
+        let result = exchange.execute_trade(&order).await.unwrap();
+        let position_id = result.metadata["position_id"].as_str().unwrap().to_string();
+        let open_fee = result.fees.unwrap();
+        assert!((open_fee - 2.0).abs() < 1e-9);
+
+        // Spadek o 90% przy 20x to strata 18x marży - ograniczona do marży 100 USD
+        exchange.update_price("BONK", 0.1).await;
+        assert!((exchange.equity().await - (1000.0 - open_fee - 100.0)).abs() < 1e-9);
+
+        exchange.close_position(&position_id).await.unwrap();
+        assert!((exchange.realized_pnl().await + 100.0).abs() < 1e-9);
+        let close_fee = exchange.total_fees().await - open_fee;
+        assert!(exchange.balance().await >= 1000.0 - 100.0 - open_fee - close_fee - 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_order_id_can_be_resubmitted() {
+        let exchange = exchange();
+
+        let order = TradeOrder::market_order("BONK".to_string(), TradeSide::Long, 10.0, 2);
+        assert!(!exchange.execute_trade(&order).await.unwrap().success);
+
+        exchange.update_price("BONK", 1.0).await;
+        assert!(exchange.execute_trade(&order).await.unwrap().success);
+        assert_eq!(
+            exchange.get_order_status(&order.id).await.unwrap(),
+            OrderStatus::Filled
+        );
+
+        // Zrealizowanego zlecenia nie można wykonać drugi raz
+        assert!(exchange.execute_trade(&order).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_finished_orders_are_pruned() {
+        let exchange = exchange();
+        let resting = TradeOrder::limit_order("BONK".to_string(), TradeSide::Long, 10.0, 2, 0.5);
+        exchange.update_price("BONK", 1.0).await;
+        exchange.execute_trade(&resting).await.unwrap();
+
+        for _ in 0..MAX_FINISHED_ORDERS + 10 {
+            let order = TradeOrder::market_order("NOPE".to_string(), TradeSide::Long, 10.0, 2);
+            exchange.execute_trade(&order).await.unwrap();
+        }
+
+        let state = exchange.state.read().await;
+        assert!(state.orders.len() <= MAX_FINISHED_ORDERS + 2);
+        assert_eq!(state.orders[&resting.id].status, OrderStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_margin_is_rejected() {
+        let exchange = exchange();
+        exchange.update_price("BONK", 1.0).await;
+
+        let order = TradeOrder::market_order("BONK".to_string(), TradeSide::Long, 10_000.0, 2);
+        let result = exchange.execute_trade(&order).await.unwrap();
+
+        assert!(!result.success);
+        assert!(exchange.open_positions().await.is_empty());
+    }
+}
@@ -61,6 +61,7 @@ impl WalletManager {
                 chain TEXT NOT NULL,
                 status TEXT NOT NULL,
                 xpub TEXT,
+                descriptors TEXT, -- JSON array
                 tags TEXT, -- JSON array
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL,
@@ -73,6 +74,20 @@ impl WalletManager {
         .await
         .context("Failed to create wallets table")?;
 
+        // Tables created before descriptor support lack the column
+        let has_descriptors = sqlx::query(
+            "SELECT name FROM pragma_table_info('wallets') WHERE name = 'descriptors'",
+        )
+        .fetch_optional(&*self.db)
+        .await?
+        .is_some();
+        if !has_descriptors {
+            sqlx::query("ALTER TABLE wallets ADD COLUMN descriptors TEXT")
+                .execute(&*self.db)
+                .await
+                .context("Failed to add descriptors column")?;
+        }
+
         // Addresses table
         sqlx::query(
             r#"
@@ -230,6 +245,32 @@ impl WalletManager {
         Ok(wallet)
     }
 
+    /// Starts tracking additional addresses for a wallet
+    pub async fn add_addresses(&self, id: Uuid, addresses: Vec<Address>) -> Result<Wallet> {
+        let mut wallet = self
+            .get_wallet(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Wallet not found"))?;
+
+        for address in addresses {
+            if wallet.addresses.iter().any(|a| a.address == address.address) {
+                continue;
+            }
+            address.validate_for_chain(&wallet.chain)?;
+            self.save_address_to_db(&wallet.id, &address).await?;
+            wallet.addresses.push(address);
+        }
+        wallet.updated_at = chrono::Utc::now();
+
+        // Update cache
+        {
+            let mut cache = self.cache.write().await;
+            cache.insert(id, wallet.clone());
+        }
+
+        Ok(wallet)
+    }
+
     /// Deletes a wallet
     pub async fn delete_wallet(&self, id: Uuid) -> Result<()> {
         // Delete from database
@@ -346,12 +387,13 @@ impl WalletManager {
     async fn save_wallet_to_db(&self, wallet: &Wallet) -> Result<()> {
         let tags_json = serde_json::to_string(&wallet.tags)?;
         let metadata_json = serde_json::to_string(&wallet.metadata)?;
+        let descriptors_json = serde_json::to_string(&wallet.descriptors)?;
 
         sqlx::query(
             r#"
             INSERT OR REPLACE INTO wallets
-            (id, name, wallet_type, chain, status, xpub, tags, created_at, updated_at, last_sync, metadata)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            (id, name, wallet_type, chain, status, xpub, descriptors, tags, created_at, updated_at, last_sync, metadata)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(wallet.id.to_string())
@@ -360,6 +402,7 @@ impl WalletManager {
         .bind(serde_json::to_string(&wallet.chain)?)
         .bind(serde_json::to_string(&wallet.status)?)
         .bind(&wallet.xpub)
+        .bind(descriptors_json)
         .bind(tags_json)
         .bind(wallet.created_at.to_rfc3339())
         .bind(wallet.updated_at.to_rfc3339())
@@ -421,7 +464,7 @@ impl WalletManager {
     /// Load wallet from database
     async fn load_wallet_from_db(&self, id: Uuid) -> Result<Option<Wallet>> {
         let row = sqlx::query(
-            r#"SELECT id, name, wallet_type, chain, status, xpub, tags, created_at, updated_at, last_sync, metadata
+            r#"SELECT id, name, wallet_type, chain, status, xpub, descriptors, tags, created_at, updated_at, last_sync, metadata
                FROM wallets WHERE id = ?"#,
         )
         .bind(id.to_string())
@@ -444,6 +487,11 @@ impl WalletManager {
                 .unwrap_or_else(|| "[]".into()),
         )
         .context("Invalid tags JSON in DB")?;
+        let descriptors: Vec<String> = serde_json::from_str(
+            &row.get::<Option<String>, _>("descriptors")
+                .unwrap_or_else(|| "[]".into()),
+        )
+        .context("Invalid descriptors JSON in DB")?;
         let metadata: HashMap<String, String> = serde_json::from_str(
             &row.get::<Option<String>, _>("metadata")
                 .unwrap_or_else(|| "{}".into()),
@@ -477,6 +525,7 @@ impl WalletManager {
             status,
             addresses,
             xpub,
+            descriptors,
             tags,
             created_at,
             updated_at,
@@ -494,7 +543,7 @@ impl WalletManager {
         _pagination: Option<Pagination>,
     ) -> Result<Vec<Wallet>> {
         let rows = sqlx::query(
-            r#"SELECT id, name, wallet_type, chain, status, xpub, tags, created_at, updated_at, last_sync, metadata FROM wallets"#
+            r#"SELECT id, name, wallet_type, chain, status, xpub, descriptors, tags, created_at, updated_at, last_sync, metadata FROM wallets"#
         )
         .fetch_all(&*self.db)
         .await
@@ -516,6 +565,10 @@ impl WalletManager {
                 &row.get::<Option<String>, _>("metadata")
                     .unwrap_or_else(|| "{}".into()),
             )?;
+            let descriptors: Vec<String> = serde_json::from_str(
+                &row.get::<Option<String>, _>("descriptors")
+                    .unwrap_or_else(|| "[]".into()),
+            )?;
             let created_at_str: String = row.get("created_at");
             let updated_at_str: String = row.get("updated_at");
             let created_at =
@@ -539,6 +592,7 @@ impl WalletManager {
                 status,
                 addresses,
                 xpub,
+                descriptors,
                 tags,
                 created_at,
                 updated_at,
@@ -6,8 +6,12 @@ use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::Range;
 use uuid::Uuid;
 
+use crate::bitcoin::descriptor::Descriptor;
+use bitcoin::secp256k1::{Secp256k1, Verification};
+
 pub mod cache;
 pub mod manager;
 pub mod models;
@@ -97,6 +101,8 @@ pub struct Wallet {
     pub status: WalletStatus,
     pub addresses: Vec<Address>,
     pub xpub: Option<String>, // For BTC hierarchical wallets
+    #[serde(default)]
+    pub descriptors: Vec<String>, // BIP-380 output descriptors with checksum
     pub tags: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -122,6 +128,7 @@ impl Wallet {
             status: WalletStatus::Active,
             addresses,
             xpub,
+            descriptors: Vec::new(),
             tags: Vec::new(),
             created_at: now,
             updated_at: now,
@@ -136,8 +143,8 @@ impl Wallet {
             return Err(anyhow::anyhow!("Wallet name cannot be empty"));
         }
 
-        if self.addresses.is_empty() && self.xpub.is_none() {
-            return Err(anyhow::anyhow!("Wallet must have addresses or xpub"));
+        if self.addresses.is_empty() && self.xpub.is_none() && self.descriptors.is_empty() {
+            return Err(anyhow::anyhow!("Wallet must have addresses, xpub or descriptors"));
         }
 
         // Validate addresses for the chain
@@ -153,9 +160,78 @@ impl Wallet {
             self.validate_xpub(xpub)?;
         }
 
+        // Validate descriptors for Bitcoin
+        if !self.descriptors.is_empty() && self.chain != Chain::Bitcoin {
+            return Err(anyhow::anyhow!("Descriptors only supported for Bitcoin"));
+        }
+        for descriptor in &self.descriptors {
+            Self::parse_descriptor(descriptor)?;
+        }
+
+        Ok(())
+    }
+
+    /// Parses a descriptor and checks it derives mainnet addresses
+    fn parse_descriptor(descriptor: &str) -> Result<Descriptor> {
+        let descriptor = Descriptor::parse(descriptor)
+            .map_err(|e| anyhow::anyhow!("Invalid descriptor: {}", e))?;
+        descriptor.check_network(crate::bitcoin::Network::Mainnet)?;
+        Ok(descriptor)
+    }
+
+    /// Imports a BIP-380 output descriptor, verifying its checksum if present
+    pub fn import_descriptor(&mut self, descriptor: &str) -> Result<()> {
+        if self.chain != Chain::Bitcoin {
+            return Err(anyhow::anyhow!("Descriptors only supported for Bitcoin"));
+        }
+
+        let canonical = Self::parse_descriptor(descriptor)?.to_string();
+        if !self.descriptors.contains(&canonical) {
+            self.descriptors.push(canonical);
+            self.updated_at = Utc::now();
+        }
         Ok(())
     }
 
+    /// Exports the wallet's descriptors with checksums
+    pub fn export_descriptors(&self) -> Vec<String> {
+        self.descriptors.clone()
+    }
+
+    /// Derives the address at `index` from a descriptor
+    pub fn descriptor_address<C: Verification>(
+        secp: &Secp256k1<C>,
+        descriptor: &Descriptor,
+        index: u32,
+    ) -> Result<Address> {
+        let address = descriptor.address(secp, index, crate::bitcoin::Network::Mainnet)?;
+        let mut address = Address::new(address.to_string(), &Chain::Bitcoin)?;
+        address.derivation_path = descriptor
+            .keys()
+            .first()
+            .and_then(|key| key.full_path(index))
+            .map(|path| path.to_string());
+        Ok(address)
+    }
+
+    /// Derives addresses for `range` from every descriptor
+    ///
+    /// Non-ranged descriptors yield their single address.
+    pub fn descriptor_addresses(&self, range: Range<u32>) -> Result<Vec<Address>> {
+        let secp = Secp256k1::verification_only();
+        let mut addresses = Vec::new();
+
+        for descriptor in &self.descriptors {
+            let descriptor = Self::parse_descriptor(descriptor)?;
+            let indexes = if descriptor.is_ranged() { range.clone() } else { 0..1 };
+            for index in indexes {
+                addresses.push(Self::descriptor_address(&secp, &descriptor, index)?);
+            }
+        }
+
+        Ok(addresses)
+    }
+
     /// Validates Bitcoin xpub format
     fn validate_xpub(&self, xpub: &str) -> Result<()> {
         if !xpub.starts_with("xpub") && !xpub.starts_with("ypub") && !xpub.starts_with("zpub") {
@@ -233,6 +309,8 @@ pub struct CreateWalletRequest {
     pub chain: Chain,
     pub addresses: Vec<String>,
     pub xpub: Option<String>,
+    #[serde(default)]
+    pub descriptors: Option<Vec<String>>,
     pub tags: Option<Vec<String>>,
     pub metadata: Option<HashMap<String, String>>,
 }
@@ -254,6 +332,10 @@ impl CreateWalletRequest {
             self.xpub,
         );
 
+        for descriptor in self.descriptors.iter().flatten() {
+            wallet.import_descriptor(descriptor)?;
+        }
+
         if let Some(tags) = self.tags {
             wallet.tags = tags;
         }
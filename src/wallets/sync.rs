@@ -1,15 +1,36 @@
 //! Wallet synchronization module
 
 use anyhow::Result;
+use async_trait::async_trait;
+use bitcoin::secp256k1::Secp256k1;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{error, info, warn};
 
 use super::{manager::WalletManager, models::*, Chain, Wallet};
+use crate::bitcoin::{descriptor::Descriptor, BitcoinCore};
+
+/// Unused addresses derived past the last used one for each descriptor
+const DESCRIPTOR_GAP_LIMIT: u32 = 20;
+
+/// Source of address usage, e.g. a Bitcoin node
+#[async_trait]
+pub trait AddressHistory: Send + Sync {
+    /// Whether any transaction has ever involved the address
+    async fn has_history(&self, address: &str) -> Result<bool>;
+}
+
+#[async_trait]
+impl AddressHistory for BitcoinCore {
+    async fn has_history(&self, address: &str) -> Result<bool> {
+        Ok(self.address_has_history(address).await?)
+    }
+}
 
 /// Wallet synchronizer
 pub struct WalletSynchronizer {
     manager: Arc<WalletManager>,
+    history: Option<Arc<dyn AddressHistory>>,
     is_running: Arc<RwLock<bool>>,
 }
 
@@ -18,10 +39,17 @@ impl WalletSynchronizer {
     pub fn new(manager: Arc<WalletManager>) -> Self {
         Self {
             manager,
+            history: None,
             is_running: Arc::new(RwLock::new(false)),
         }
     }
 
+    /// Attaches the node used to find used descriptor addresses
+    pub fn with_history(mut self, history: Arc<dyn AddressHistory>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
     /// Starts background sync loop
     pub async fn start(&self) -> Result<()> {
         {
@@ -81,8 +109,63 @@ impl WalletSynchronizer {
 
     /// Syncs Bitcoin wallet
     async fn sync_bitcoin_wallet(&self, wallet: &Wallet, stats: &mut SyncStats) -> Result<()> {
-        // Bitcoin sync implementation would go here
-        stats.addresses_synced = wallet.addresses.len() as u32;
+        let mut tracked = wallet.addresses.len();
+
+        // Watch-only wallets discover their addresses from descriptors
+        if !wallet.descriptors.is_empty() {
+            let discovered = self.discover_descriptor_addresses(wallet).await?;
+            if !discovered.is_empty() {
+                info!(
+                    "Discovered {} new addresses from descriptors for wallet {}",
+                    discovered.len(),
+                    wallet.id
+                );
+                tracked = self.manager.add_addresses(wallet.id, discovered).await?.addresses.len();
+            }
+        }
+
+        // Bitcoin balance sync would go here
+        stats.addresses_synced = tracked as u32;
         Ok(())
     }
+
+    /// Derives descriptor addresses up to the gap limit past the last used one
+    ///
+    /// Without a node no address is known to be used, so only the first
+    /// window is derived.
+    async fn discover_descriptor_addresses(&self, wallet: &Wallet) -> Result<Vec<Address>> {
+        let secp = Secp256k1::verification_only();
+        let mut discovered = Vec::new();
+
+        for descriptor in &wallet.descriptors {
+            let descriptor = Descriptor::parse(descriptor)?;
+            let mut end = if descriptor.is_ranged() { DESCRIPTOR_GAP_LIMIT } else { 1 };
+            let mut index = 0;
+
+            while index < end {
+                let address = Wallet::descriptor_address(&secp, &descriptor, index)?;
+                if descriptor.is_ranged() && self.has_history(&address.address).await? {
+                    end = index + 1 + DESCRIPTOR_GAP_LIMIT;
+                }
+                let known = wallet
+                    .addresses
+                    .iter()
+                    .any(|a| a.address == address.address);
+                if !known {
+                    discovered.push(address);
+                }
+                index += 1;
+            }
+        }
+
+        Ok(discovered)
+    }
+
+    /// Whether the node has seen a transaction involving the address
+    async fn has_history(&self, address: &str) -> Result<bool> {
+        match &self.history {
+            Some(history) => history.has_history(address).await,
+            None => Ok(false),
+        }
+    }
 }
@@ -188,6 +188,7 @@ async fn test_database_operations() -> Result<()> {
     let temp_dir = TempDir::new()?;
     let mut config = Config::default();
     config.database.path = temp_dir.path().join("test.db");
+    config.database.backup_directory = temp_dir.path().to_path_buf();
 
     let db_manager = DatabaseManager::new(&config.database).await?;
 
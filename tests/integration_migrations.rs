@@ -7,10 +7,12 @@ use cerberus::{
     wallets::{
         manager::WalletManager,
         models::{Balance, SyncStats},
+        sync::{AddressHistory, WalletSynchronizer},
         Chain, CreateWalletRequest, WalletType,
     },
 };
 use std::path::PathBuf;
+use std::sync::Arc;
 use tempfile::TempDir;
 use uuid;
 
@@ -43,22 +45,22 @@ async fn test_migrations_and_wallet_crud() -> Result<()> {
 
     // Create tables manually (simulating migrations)
     sqlx::query(include_str!(
-        "../migrations/20250808_000001_create_wallets.sql"
+        "../migrations/20250808000001_create_wallets.sql"
     ))
     .execute(pool)
     .await?;
     sqlx::query(include_str!(
-        "../migrations/20250808_000002_create_addresses.sql"
+        "../migrations/20250808000002_create_addresses.sql"
     ))
     .execute(pool)
     .await?;
     sqlx::query(include_str!(
-        "../migrations/20250808_000003_create_transactions.sql"
+        "../migrations/20250808000003_create_transactions.sql"
     ))
     .execute(pool)
     .await?;
     sqlx::query(include_str!(
-        "../migrations/20250808_000004_create_sync_stats.sql"
+        "../migrations/20250808000004_create_sync_stats.sql"
     ))
     .execute(pool)
     .await?;
@@ -72,6 +74,7 @@ async fn test_migrations_and_wallet_crud() -> Result<()> {
         chain: Chain::Ethereum,
         addresses: vec!["0x742d35Cc6634C0532925a3b8D4C9db96C4b4d8b6".to_string()],
         xpub: None,
+        descriptors: None,
         tags: Some(vec!["test".to_string()]),
         metadata: None,
     };
@@ -144,22 +147,22 @@ async fn test_address_with_balance() -> Result<()> {
     // Run migrations manually
     let pool = db_manager.pool();
     sqlx::query(include_str!(
-        "../migrations/20250808_000001_create_wallets.sql"
+        "../migrations/20250808000001_create_wallets.sql"
     ))
     .execute(pool)
     .await?;
     sqlx::query(include_str!(
-        "../migrations/20250808_000002_create_addresses.sql"
+        "../migrations/20250808000002_create_addresses.sql"
     ))
     .execute(pool)
     .await?;
     sqlx::query(include_str!(
-        "../migrations/20250808_000003_create_transactions.sql"
+        "../migrations/20250808000003_create_transactions.sql"
     ))
     .execute(pool)
     .await?;
     sqlx::query(include_str!(
-        "../migrations/20250808_000004_create_sync_stats.sql"
+        "../migrations/20250808000004_create_sync_stats.sql"
     ))
     .execute(pool)
     .await?;
@@ -173,6 +176,7 @@ async fn test_address_with_balance() -> Result<()> {
         chain: Chain::Ethereum,
         addresses: vec!["0x742d35Cc6634C0532925a3b8D4C9db96C4b4d8b6".to_string()],
         xpub: None,
+        descriptors: None,
         tags: None,
         metadata: None,
     };
@@ -201,3 +205,117 @@ async fn test_address_with_balance() -> Result<()> {
     println!("✅ Address creation test passed!");
     Ok(())
 }
+
+/// Node stub reporting history for a fixed set of addresses
+struct UsedAddresses(Vec<String>);
+
+#[async_trait::async_trait]
+impl AddressHistory for UsedAddresses {
+    async fn has_history(&self, address: &str) -> Result<bool> {
+        Ok(self.0.iter().any(|used| used == address))
+    }
+}
+
+#[tokio::test]
+async fn test_descriptor_watch_only_wallet() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let db_path = temp_dir
+        .path()
+        .join(format!("test_descriptor_{}.db", uuid::Uuid::new_v4()));
+
+    let db_config = DatabaseConfig {
+        path: db_path,
+        max_connections: 5,
+        connection_timeout: 30,
+        enable_wal: false,
+        cache_size: 1000,
+        enable_foreign_keys: true,
+        query_timeout: 30,
+        enable_backup: false,
+        backup_interval: 60,
+        backup_directory: PathBuf::from("/tmp"),
+    };
+
+    let db_manager = DatabaseManager::new_without_migrations(&db_config).await?;
+
+    // Wallets table from before descriptor support
+    let pool = db_manager.pool();
+    sqlx::query(include_str!(
+        "../migrations/20250808000001_create_wallets.sql"
+    ))
+    .execute(pool)
+    .await?;
+    sqlx::query(include_str!(
+        "../migrations/20250808000002_create_addresses.sql"
+    ))
+    .execute(pool)
+    .await?;
+
+    let wallet_manager = Arc::new(WalletManager::new(db_manager.pool().clone().into()).await?);
+
+    // 2-of-2 multisig plus a taproot account, no addresses or private keys
+    let multisig = "wsh(sortedmulti(2,xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/0/*,xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8/0/*))";
+    let taproot = "tr([73c5da0a/86'/0'/0']xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ/0/*)";
+    let request = CreateWalletRequest {
+        name: "Descriptor Vault".to_string(),
+        wallet_type: WalletType::WatchOnly,
+        chain: Chain::Bitcoin,
+        addresses: vec![],
+        xpub: None,
+        descriptors: Some(vec![multisig.to_string(), taproot.to_string()]),
+        tags: None,
+        metadata: None,
+    };
+
+    let wallet = wallet_manager.create_wallet(request).await?;
+    let exported = wallet.export_descriptors();
+    assert_eq!(exported.len(), 2);
+    assert!(exported.iter().all(|d| d.contains('#')));
+    assert!(exported[0].starts_with(multisig));
+
+    // Sync discovers the gap-limit window for each descriptor
+    let synchronizer = WalletSynchronizer::new(wallet_manager.clone());
+    let stats = synchronizer.sync_wallet(&wallet).await?;
+    assert_eq!(stats.addresses_synced, 40);
+
+    // Descriptors and discovered addresses persist
+    let reloaded = WalletManager::new(db_manager.pool().clone().into())
+        .await?
+        .get_wallet(wallet.id)
+        .await?
+        .unwrap();
+    assert_eq!(reloaded.descriptors, exported);
+    assert_eq!(reloaded.addresses.len(), 40);
+    assert!(reloaded
+        .addresses
+        .iter()
+        .any(|a| a.address == "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+            && a.derivation_path.as_deref() == Some("m/86'/0'/0'/0/0")));
+
+    // A cached balance alone does not extend the window
+    let mut funded = reloaded.clone();
+    let last_taproot = funded
+        .addresses
+        .iter_mut()
+        .find(|a| a.derivation_path.as_deref() == Some("m/86'/0'/0'/0/19"))
+        .unwrap();
+    last_taproot.balance = Some(Balance::new("0.1".to_string()));
+    let used = last_taproot.address.clone();
+    let stats = synchronizer.sync_wallet(&funded).await?;
+    assert_eq!(stats.addresses_synced, 40);
+
+    // An address with history on the node extends the window past it
+    let synchronizer = WalletSynchronizer::new(wallet_manager.clone())
+        .with_history(Arc::new(UsedAddresses(vec![used])));
+    let stats = synchronizer.sync_wallet(&reloaded).await?;
+    assert_eq!(stats.addresses_synced, 60);
+
+    // Descriptors with a bad checksum are rejected on import
+    let mut bad = reloaded;
+    let mut corrupted = exported[1].clone();
+    let last = corrupted.pop().unwrap();
+    corrupted.push(if last == 'q' { 'p' } else { 'q' });
+    assert!(bad.import_descriptor(&corrupted).is_err());
+
+    Ok(())
+}
@@ -11,7 +11,7 @@
 use anyhow::Result;
 use cerberus::{
     cache::{CacheEntry, CacheStats},
-    risk::{Portfolio, Position, TradeSide},
+    risk::{MarginModel, Portfolio, Position, TradeSide},
     signals::{Confidence, Signal},
     trading::{OrderType, TradeOrder},
 };
@@ -365,16 +365,18 @@ fn test_cache_stats_update_performance() {
 
 #[test]
 fn test_liquidation_price_calculation_performance() {
+    let model = MarginModel::default();
     let start = Instant::now();
 
     for i in 0..10000 {
         let price = 0.001 + (i as f64 * 0.0001);
         let leverage = (i % 50) + 1; // 1-50x leverage
+        let notional = 1000.0 + i as f64;
 
         let _long_liq =
-            Position::calculate_liquidation_price(&TradeSide::Long, price, leverage as u8);
+            model.isolated_liquidation_price(&TradeSide::Long, price, leverage as u8, notional);
         let _short_liq =
-            Position::calculate_liquidation_price(&TradeSide::Short, price, leverage as u8);
+            model.isolated_liquidation_price(&TradeSide::Short, price, leverage as u8, notional);
     }
 
     let duration = start.elapsed();
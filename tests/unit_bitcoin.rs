@@ -13,6 +13,7 @@ use cerberus::bitcoin::transaction_signer::ScriptType as TxScriptType;
 use cerberus::bitcoin::script_types::ScriptType;
 use cerberus::bitcoin::hardware_signer::AddressVerificationRequest;
 use anyhow::Result;
+use bitcoin::hashes::Hash;
 use base64::prelude::*;
 
 /// Test Bitcoin configuration
@@ -324,33 +325,35 @@ async fn test_psbt_builder_amounts() -> Result<()> {
     
     // Create mock UTXOs
     let utxo1 = Utxo {
-        txid: "tx1".to_string(),
+        txid: "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string(),
         vout: 0,
         amount: Amount::from_btc(1.0),
-        script_pubkey: "script1".to_string(),
+        script_pubkey: "0014751e76e8199196d454941c45d1b3a323f1433bd6".to_string(),
         address: Some("addr1".to_string()),
         confirmations: 6,
         spendable: true,
         safe: true,
+        label: None,
     };
     
     let utxo2 = Utxo {
-        txid: "tx2".to_string(),
+        txid: "abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890".to_string(),
         vout: 1,
         amount: Amount::from_btc(0.5),
-        script_pubkey: "script2".to_string(),
+        script_pubkey: "0014751e76e8199196d454941c45d1b3a323f1433bd6".to_string(),
         address: Some("addr2".to_string()),
         confirmations: 3,
         spendable: true,
         safe: true,
+        label: None,
     };
     
     let utxos = vec![utxo1, utxo2];
     
     // Create outputs
     let mut outputs = HashMap::new();
-    outputs.insert("recipient1".to_string(), Amount::from_btc(0.8));
-    outputs.insert("recipient2".to_string(), Amount::from_btc(0.2));
+    outputs.insert("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string(), Amount::from_btc(0.8));
+    outputs.insert("tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7".to_string(), Amount::from_btc(0.2));
     
     // Build PSBT
     let builder = PsbtBuilder::from_utxos_and_outputs(utxos, outputs)?;
@@ -362,6 +365,11 @@ async fn test_psbt_builder_amounts() -> Result<()> {
     
     let fee = builder.calculate_fee();
     assert_eq!(fee.to_btc(), 0.5);
+
+    // Invalid recipient addresses are rejected
+    let mut invalid = std::collections::HashMap::new();
+    invalid.insert("recipient1".to_string(), Amount::from_btc(0.8));
+    assert!(PsbtBuilder::from_utxos_and_outputs(Vec::new(), invalid).is_err());
     
     Ok(())
 }
@@ -373,7 +381,7 @@ async fn test_psbt_serialization() -> Result<()> {
     // Build PSBT string
     let psbt_string = builder.build()?;
     assert!(!psbt_string.is_empty());
-    assert!(psbt_string.starts_with("base64_"));
+    assert!(psbt_string.starts_with("cHNidP8")); // "psbt" magic bytes
     
     // Parse PSBT back
     let parsed_builder = PsbtBuilder::parse(&psbt_string)?;
@@ -383,6 +391,520 @@ async fn test_psbt_serialization() -> Result<()> {
     Ok(())
 }
 
+/// Valid PSBTs from the BIP 174 test vectors
+const BIP174_VALID_VECTORS: [(&str, &str); 6] = [
+    (
+        "One P2PKH input, outputs empty",
+        "70736274ff0100750200000001268171371edff285e937adeea4b37b78000c0566cbb3ad64641713ca42171bf60000000000feffffff02d3dff505000000001976a914d0c59903c5bac2868760e90fd521a4665aa7652088ac00e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787b32e1300000100fda5010100000000010289a3c71eab4d20e0371bbba4cc698fa295c9463afa2e397f8533ccb62f9567e50100000017160014be18d152a9b012039daf3da7de4f53349eecb985ffffffff86f8aa43a71dff1448893a530a7237ef6b4608bbb2dd2d0171e63aec6a4890b40100000017160014fe3e9ef1a745e974d902c4355943abcb34bd5353ffffffff0200c2eb0b000000001976a91485cff1097fd9e008bb34af709c62197b38978a4888ac72fef84e2c00000017a914339725ba21efd62ac753a9bcd067d6c7a6a39d05870247304402202712be22e0270f394f568311dc7ca9a68970b8025fdd3b240229f07f8a5f3a240220018b38d7dcd314e734c9276bd6fb40f673325bc4baa144c800d2f2f02db2765c012103d2e15674941bad4a996372cb87e1856d3652606d98562fe39c5e9e7e413f210502483045022100d12b852d85dcd961d2f5f4ab660654df6eedcc794c0c33ce5cc309ffb5fce58d022067338a8e0e1725c197fb1a88af59f51e44e4255b20167c8684031c05d1f2592a01210223b72beef0965d10be0778efecd61fcac6f79a4ea169393380734464f84f2ab300000000000000",
+    ),
+    (
+        "Finalized P2PKH input and P2SH-P2WPKH input",
+        "70736274ff0100a00200000002ab0949a08c5af7c49b8212f417e2f15ab3f5c33dcf153821a8139f877a5b7be40000000000feffffffab0949a08c5af7c49b8212f417e2f15ab3f5c33dcf153821a8139f877a5b7be40100000000feffffff02603bea0b000000001976a914768a40bbd740cbe81d988e71de2a4d5c71396b1d88ac8e240000000000001976a9146f4620b553fa095e721b9ee0efe9fa039cca459788ac000000000001076a47304402204759661797c01b036b25928948686218347d89864b719e1f7fcf57d1e511658702205309eabf56aa4d8891ffd111fdf1336f3a29da866d7f8486d75546ceedaf93190121035cdc61fc7ba971c0b501a646a2a83b102cb43881217ca682dc86e2d73fa882920001012000e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787010416001485d13537f2e265405a34dbafa9e3dda01fb82308000000",
+    ),
+    (
+        "P2PKH input with sighash type",
+        "70736274ff0100750200000001268171371edff285e937adeea4b37b78000c0566cbb3ad64641713ca42171bf60000000000feffffff02d3dff505000000001976a914d0c59903c5bac2868760e90fd521a4665aa7652088ac00e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787b32e1300000100fda5010100000000010289a3c71eab4d20e0371bbba4cc698fa295c9463afa2e397f8533ccb62f9567e50100000017160014be18d152a9b012039daf3da7de4f53349eecb985ffffffff86f8aa43a71dff1448893a530a7237ef6b4608bbb2dd2d0171e63aec6a4890b40100000017160014fe3e9ef1a745e974d902c4355943abcb34bd5353ffffffff0200c2eb0b000000001976a91485cff1097fd9e008bb34af709c62197b38978a4888ac72fef84e2c00000017a914339725ba21efd62ac753a9bcd067d6c7a6a39d05870247304402202712be22e0270f394f568311dc7ca9a68970b8025fdd3b240229f07f8a5f3a240220018b38d7dcd314e734c9276bd6fb40f673325bc4baa144c800d2f2f02db2765c012103d2e15674941bad4a996372cb87e1856d3652606d98562fe39c5e9e7e413f210502483045022100d12b852d85dcd961d2f5f4ab660654df6eedcc794c0c33ce5cc309ffb5fce58d022067338a8e0e1725c197fb1a88af59f51e44e4255b20167c8684031c05d1f2592a01210223b72beef0965d10be0778efecd61fcac6f79a4ea169393380734464f84f2ab30000000001030401000000000000",
+    ),
+    (
+        "P2PKH and P2SH-P2WPKH inputs, outputs with BIP32 derivations",
+        "70736274ff0100a00200000002ab0949a08c5af7c49b8212f417e2f15ab3f5c33dcf153821a8139f877a5b7be40000000000feffffffab0949a08c5af7c49b8212f417e2f15ab3f5c33dcf153821a8139f877a5b7be40100000000feffffff02603bea0b000000001976a914768a40bbd740cbe81d988e71de2a4d5c71396b1d88ac8e240000000000001976a9146f4620b553fa095e721b9ee0efe9fa039cca459788ac00000000000100df0200000001268171371edff285e937adeea4b37b78000c0566cbb3ad64641713ca42171bf6000000006a473044022070b2245123e6bf474d60c5b50c043d4c691a5d2435f09a34a7662a9dc251790a022001329ca9dacf280bdf30740ec0390422422c81cb45839457aeb76fc12edd95b3012102657d118d3357b8e0f4c2cd46db7b39f6d9c38d9a70abcb9b2de5dc8dbfe4ce31feffffff02d3dff505000000001976a914d0c59903c5bac2868760e90fd521a4665aa7652088ac00e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787b32e13000001012000e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787010416001485d13537f2e265405a34dbafa9e3dda01fb8230800220202ead596687ca806043edc3de116cdf29d5e9257c196cd055cf698c8d02bf24e9910b4a6ba670000008000000080020000800022020394f62be9df19952c5587768aeb7698061ad2c4a25c894f47d8c162b4d7213d0510b4a6ba6700000080010000800200008000",
+    ),
+    (
+        "P2SH-P2WSH multisig input with a partial signature",
+        "70736274ff0100550200000001279a2323a5dfb51fc45f220fa58b0fc13e1e3342792a85d7e36cd6333b5cbc390000000000ffffffff01a05aea0b000000001976a914ffe9c0061097cc3b636f2cb0460fa4fc427d2b4588ac0000000000010120955eea0b0000000017a9146345200f68d189e1adc0df1c4d16ea8f14c0dbeb87220203b1341ccba7683b6af4f1238cd6e97e7167d569fac47f1e48d47541844355bd4646304302200424b58effaaa694e1559ea5c93bbfd4a89064224055cdf070b6771469442d07021f5c8eb0fea6516d60b8acb33ad64ede60e8785bfb3aa94b99bdf86151db9a9a010104220020771fd18ad459666dd49f3d564e3dbc42f4c84774e360ada16816a8ed488d5681010547522103b1341ccba7683b6af4f1238cd6e97e7167d569fac47f1e48d47541844355bd462103de55d1e1dac805e3f8a58c1fbf9b94c02f3dbaafe127fefca4995f26f82083bd52ae220603b1341ccba7683b6af4f1238cd6e97e7167d569fac47f1e48d47541844355bd4610b4a6ba67000000800000008004000080220603de55d1e1dac805e3f8a58c1fbf9b94c02f3dbaafe127fefca4995f26f82083bd10b4a6ba670000008000000080050000800000",
+    ),
+    (
+        "Unknown input key-value pair",
+        "70736274ff01003f0200000001ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0000000000ffffffff010000000000000000036a010000000000000a0f0102030405060708090f0102030405060708090a0b0c0d0e0f0000",
+    ),
+];
+
+#[tokio::test]
+async fn test_psbt_bip174_vectors_round_trip() -> Result<()> {
+    for (name, vector) in BIP174_VALID_VECTORS {
+        let bytes = hex::decode(vector)?;
+        let builder = PsbtBuilder::deserialize(&bytes)?;
+        assert_eq!(hex::encode(builder.serialize()?), vector, "{}", name);
+
+        // Base64 form parses to the same PSBT
+        let reparsed = PsbtBuilder::parse(&builder.build()?)?;
+        assert_eq!(hex::encode(reparsed.serialize()?), vector, "{}", name);
+    }
+
+    // Field mapping of the P2SH-P2WSH vector
+    let builder = PsbtBuilder::deserialize(&hex::decode(BIP174_VALID_VECTORS[4].1)?)?;
+    let input = &builder.inputs()[0];
+    assert_eq!(input.prev_amount.to_sat(), 199_909_013);
+    assert_eq!(input.sequence, 0xffffffff);
+    assert_eq!(input.partial_sigs.len(), 1);
+    assert_eq!(input.bip32_derivation.len(), 2);
+    assert_eq!(
+        input.bip32_derivation["03b1341ccba7683b6af4f1238cd6e97e7167d569fac47f1e48d47541844355bd46"],
+        "b4a6ba67/0'/0'/4'"
+    );
+    assert!(input.witness_script.is_some());
+
+    // Unknown pairs are kept
+    let builder = PsbtBuilder::deserialize(&hex::decode(BIP174_VALID_VECTORS[5].1)?)?;
+    assert_eq!(
+        builder.inputs()[0].unknown["0f010203040506070809"],
+        "0102030405060708090a0b0c0d0e0f"
+    );
+
+    Ok(())
+}
+
+/// Invalid PSBTs from the BIP 174 test vectors
+const BIP174_INVALID_VECTORS: [(&str, &str); 5] = [
+    (
+        "Network transaction, not PSBT format",
+        "AgAAAAEmgXE3Ht/yhek3re6ks3t4AAwFZsuzrWRkFxPKQhcb9gAAAABqRzBEAiBwsiRRI+a/R01gxbUMBD1MaRpdJDXwmjSnZiqdwlF5CgIgATKcqdrPKAvfMHQOwDkEIkIsgctFg5RXrrdvwS7dlbMBIQJlfRGNM1e44PTCzUbbezn22cONmnCry5st5dyNv+TOMf7///8C09/1BQAAAAAZdqkU0MWZA8W6woaHYOkP1SGkZlqnZSCIrADh9QUAAAAAF6kUNUXm4zuDLEcFDyTT7rk8nAOUi8eHsy4TAA==",
+    ),
+    (
+        "PSBT missing outputs",
+        "cHNidP8BAHUCAAAAASaBcTce3/KF6Tet7qSze3gADAVmy7OtZGQXE8pCFxv2AAAAAAD+////AtPf9QUAAAAAGXapFNDFmQPFusKGh2DpD9UhpGZap2UgiKwA4fUFAAAAABepFDVF5uM7gyxHBQ8k0+65PJwDlIvHh7MuEwAAAQD9pQEBAAAAAAECiaPHHqtNIOA3G7ukzGmPopXJRjr6Ljl/hTPMti+VZ+UBAAAAFxYAFL4Y0VKpsBIDna89p95PUzSe7LmF/////4b4qkOnHf8USIk6UwpyN+9rRgi7st0tAXHmOuxqSJC0AQAAABcWABT+Pp7xp0XpdNkCxDVZQ6vLNL1TU/////8CAMLrCwAAAAAZdqkUhc/xCX/Z4Ai7NK9wnGIZeziXikiIrHL++E4sAAAAF6kUM5cluiHv1irHU6m80GfWx6ajnQWHAkcwRAIgJxK+IuAnDzlPVoMR3HyppolwuAJf3TskAinwf4pfOiQCIAGLONfc0xTnNMkna9b7QPZzMlvEuqFEyADS8vAtsnZcASED0uFWdJQbrUqZY3LLh+GFbTZSYG2YVi/jnF6efkE/IQUCSDBFAiEA0SuFLYXc2WHS9fSrZgZU327tzHlMDDPOXMMJ/7X85Y0CIGczio4OFyXBl/saiK9Z9R5E5CVbIBZ8hoQDHAXR8lkqASECI7cr7vCWXRC+B3jv7NYfysb3mk6haTkzgHNEZPhPKrMAAAAAAA==",
+    ),
+    (
+        "PSBT where one input has a filled scriptSig in the unsigned tx",
+        "cHNidP8BAP0KAQIAAAACqwlJoIxa98SbghL0F+LxWrP1wz3PFTghqBOfh3pbe+QAAAAAakcwRAIgR1lmF5fAGwNrJZKJSGhiGDR9iYZLcZ4ff89X0eURZYcCIFMJ6r9Wqk2Ikf/REf3xM286KdqGbX+EhtdVRs7tr5MZASEDXNxh/HupccC1AaZGoqg7ECy0OIEhfKaC3Ibi1z+ogpL+////qwlJoIxa98SbghL0F+LxWrP1wz3PFTghqBOfh3pbe+QBAAAAAP7///8CYDvqCwAAAAAZdqkUdopAu9dAy+gdmI5x3ipNXHE5ax2IrI4kAAAAAAAAGXapFG9GILVT+glechue4O/p+gOcykWXiKwAAAAAAAABASAA4fUFAAAAABepFDVF5uM7gyxHBQ8k0+65PJwDlIvHhwEEFgAUhdE1N/LiZUBaNNuvqePdoB+4IwgAAAA=",
+    ),
+    (
+        "PSBT where inputs and outputs are provided but without an unsigned tx",
+        "cHNidP8AAQD9pQEBAAAAAAECiaPHHqtNIOA3G7ukzGmPopXJRjr6Ljl/hTPMti+VZ+UBAAAAFxYAFL4Y0VKpsBIDna89p95PUzSe7LmF/////4b4qkOnHf8USIk6UwpyN+9rRgi7st0tAXHmOuxqSJC0AQAAABcWABT+Pp7xp0XpdNkCxDVZQ6vLNL1TU/////8CAMLrCwAAAAAZdqkUhc/xCX/Z4Ai7NK9wnGIZeziXikiIrHL++E4sAAAAF6kUM5cluiHv1irHU6m80GfWx6ajnQWHAkcwRAIgJxK+IuAnDzlPVoMR3HyppolwuAJf3TskAinwf4pfOiQCIAGLONfc0xTnNMkna9b7QPZzMlvEuqFEyADS8vAtsnZcASED0uFWdJQbrUqZY3LLh+GFbTZSYG2YVi/jnF6efkE/IQUCSDBFAiEA0SuFLYXc2WHS9fSrZgZU327tzHlMDDPOXMMJ/7X85Y0CIGczio4OFyXBl/saiK9Z9R5E5CVbIBZ8hoQDHAXR8lkqASECI7cr7vCWXRC+B3jv7NYfysb3mk6haTkzgHNEZPhPKrMAAAAAAA==",
+    ),
+    (
+        "PSBT with duplicate keys in an input",
+        "cHNidP8BAHUCAAAAASaBcTce3/KF6Tet7qSze3gADAVmy7OtZGQXE8pCFxv2AAAAAAD+////AtPf9QUAAAAAGXapFNDFmQPFusKGh2DpD9UhpGZap2UgiKwA4fUFAAAAABepFDVF5uM7gyxHBQ8k0+65PJwDlIvHh7MuEwAAAQD9pQEBAAAAAAECiaPHHqtNIOA3G7ukzGmPopXJRjr6Ljl/hTPMti+VZ+UBAAAAFxYAFL4Y0VKpsBIDna89p95PUzSe7LmF/////4b4qkOnHf8USIk6UwpyN+9rRgi7st0tAXHmOuxqSJC0AQAAABcWABT+Pp7xp0XpdNkCxDVZQ6vLNL1TU/////8CAMLrCwAAAAAZdqkUhc/xCX/Z4Ai7NK9wnGIZeziXikiIrHL++E4sAAAAF6kUM5cluiHv1irHU6m80GfWx6ajnQWHAkcwRAIgJxK+IuAnDzlPVoMR3HyppolwuAJf3TskAinwf4pfOiQCIAGLONfc0xTnNMkna9b7QPZzMlvEuqFEyADS8vAtsnZcASED0uFWdJQbrUqZY3LLh+GFbTZSYG2YVi/jnF6efkE/IQUCSDBFAiEA0SuFLYXc2WHS9fSrZgZU327tzHlMDDPOXMMJ/7X85Y0CIGczio4OFyXBl/saiK9Z9R5E5CVbIBZ8hoQDHAXR8lkqASECI7cr7vCWXRC+B3jv7NYfysb3mk6haTkzgHNEZPhPKrMAAAAAAQA/AgAAAAH//////////////////////////////////////////wAAAAAA/////wEAAAAAAAAAAANqAQAAAAAAAAAA",
+    ),
+];
+
+#[tokio::test]
+async fn test_psbt_bip174_invalid_vectors() -> Result<()> {
+    for (name, vector) in BIP174_INVALID_VECTORS {
+        assert!(PsbtBuilder::parse(vector).is_err(), "{}", name);
+    }
+
+    // Legacy JSON payloads are no longer accepted
+    assert!(PsbtBuilder::parse("base64_7b7d").is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_psbt_v2_round_trip() -> Result<()> {
+    for (name, vector) in BIP174_VALID_VECTORS {
+        let builder = PsbtBuilder::deserialize(&hex::decode(vector)?)?;
+
+        let v2 = builder.serialize_v2()?;
+        let parsed = PsbtBuilder::deserialize(&v2)?;
+        assert_eq!(hex::encode(parsed.serialize()?), vector, "{}", name);
+        assert_eq!(
+            PsbtBuilder::parse(&builder.build_v2()?)?.serialize()?,
+            builder.serialize()?,
+            "{}",
+            name
+        );
+    }
+
+    // v2 starts with PSBT_GLOBAL_TX_VERSION instead of the unsigned transaction
+    let builder = PsbtBuilder::deserialize(&hex::decode(BIP174_VALID_VECTORS[3].1)?)?;
+    let v2 = hex::encode(builder.serialize_v2()?);
+    assert!(v2.starts_with("70736274ff01020402000000"));
+    // PSBT_GLOBAL_VERSION = 2
+    assert!(v2.contains("01fb0402000000"));
+
+    // A v2 PSBT must not carry an unsigned transaction
+    let mut bytes = builder.serialize()?;
+    bytes.splice(5..5, hex::decode("01fb0402000000")?);
+    assert!(PsbtBuilder::deserialize(&bytes).is_err());
+
+    Ok(())
+}
+
+/// BIP 370 valid vector: 1 input, 2 output PSBTv2, required fields only
+const BIP370_REQUIRED_FIELDS_ONLY: &str = "cHNidP8BAgQCAAAAAQQBAQEFAQIB+wQCAAAAAAEOIAsK2SFBnByHGXNdctxzn56p4GONH+TB7vD5lECEgV/IAQ8EAAAAAAABAwgACK8vAAAAAAEEFgAUxDD2TEdW2jENvRoIVXLvKZkmJywAAQMIi73rCwAAAAABBBYAFE3Rk6yWSlasG54cyoRU/i9HT4UTAA==";
+
+#[tokio::test]
+async fn test_psbt_bip370_vectors() -> Result<()> {
+    let builder = PsbtBuilder::parse(BIP370_REQUIRED_FIELDS_ONLY)?;
+    assert_eq!(builder.version(), 2);
+    assert_eq!(builder.lock_time(), 0);
+    assert_eq!(builder.inputs().len(), 1);
+    assert_eq!(
+        builder.inputs()[0].prev_txid,
+        "c85f81844094f9f0eec1e41f8d63e0a99e9f73dc725d7319871c9c4121d90a0b"
+    );
+    assert_eq!(builder.inputs()[0].prev_vout, 0);
+    assert_eq!(builder.inputs()[0].sequence, 0xffffffff);
+    let amounts: Vec<u64> = builder
+        .outputs()
+        .iter()
+        .map(|output| output.amount.to_sat())
+        .collect();
+    assert_eq!(amounts, vec![800_000_000, 199_998_859]);
+    assert_eq!(
+        builder.outputs()[0].script_pubkey,
+        "0014c430f64c4756da310dbd1a085572ef299926272c"
+    );
+    assert_eq!(
+        PsbtBuilder::parse(&builder.build_v2()?)?.serialize()?,
+        builder.serialize()?
+    );
+
+    // Cases from the BIP 370 vectors applied to the vector above
+    let vector = hex::encode(BASE64_STANDARD.decode(BIP370_REQUIRED_FIELDS_ONLY)?);
+    let without = |pair: &str| {
+        assert!(vector.contains(pair), "{}", pair);
+        hex::decode(vector.replacen(pair, "", 1))
+    };
+    let with_input_field =
+        |pair: &str| hex::decode(vector.replacen("010e20", &format!("{}010e20", pair), 1));
+
+    // PSBT_IN_REQUIRED_HEIGHT_LOCKTIME = 10000
+    let builder = PsbtBuilder::deserialize(&with_input_field("01120410270000")?)?;
+    assert_eq!(builder.lock_time(), 10_000);
+    // PSBT_IN_REQUIRED_TIME_LOCKTIME = 1657048460
+    let builder = PsbtBuilder::deserialize(&with_input_field("0111048c8dc462")?)?;
+    assert_eq!(builder.lock_time(), 1_657_048_460);
+
+    let invalid = [
+        ("missing PSBT_GLOBAL_TX_VERSION", without("01020402000000")?),
+        ("missing PSBT_GLOBAL_INPUT_COUNT", without("01040101")?),
+        ("missing PSBT_GLOBAL_OUTPUT_COUNT", without("01050102")?),
+        (
+            "missing PSBT_IN_PREVIOUS_TXID",
+            without("010e200b0ad921419c1c8719735d72dc739f9ea9e0638d1fe4c1eef0f9944084815fc8")?,
+        ),
+        ("missing PSBT_IN_OUTPUT_INDEX", without("010f0400000000")?),
+        (
+            "missing PSBT_OUT_AMOUNT",
+            without("0103080008af2f00000000")?,
+        ),
+        (
+            "missing PSBT_OUT_SCRIPT",
+            without("0104160014c430f64c4756da310dbd1a085572ef299926272c")?,
+        ),
+        (
+            "PSBT_IN_REQUIRED_TIME_LOCKTIME below 500000000",
+            with_input_field("01110410270000")?,
+        ),
+        (
+            "PSBT_IN_REQUIRED_HEIGHT_LOCKTIME of 500000000",
+            with_input_field("0112040065cd1d")?,
+        ),
+    ];
+    for (name, bytes) in invalid {
+        assert!(PsbtBuilder::deserialize(&bytes).is_err(), "{}", name);
+    }
+
+    Ok(())
+}
+
+/// Valid PSBTs from the BIP 371 (taproot) test vectors
+const BIP371_VALID_VECTORS: [(&str, &str); 6] = [
+    (
+        "Key path input",
+        "70736274ff010052020000000127744ababf3027fe0d6cf23a96eee2efb188ef52301954585883e69b6624b2420000000000ffffffff0148e6052a01000000160014768e1eeb4cf420866033f80aceff0f9720744969000000000001012b00f2052a010000002251205a2c2cf5b52cf31f83ad2e8da63ff03183ecd8f609c7510ae8a48e03910a07572116fe349064c98d6e2a853fa3c9b12bd8b304a19c195c60efa7ee2393046d3fa2321900772b2da75600008001000080000000800100000000000000011720fe349064c98d6e2a853fa3c9b12bd8b304a19c195c60efa7ee2393046d3fa232002202036b772a6db74d8753c98a827958de6c78ab3312109f37d3e0304484242ece73d818772b2da7540000800100008000000080000000000000000000",
+    ),
+    (
+        "Key path input with signature",
+        "70736274ff010052020000000127744ababf3027fe0d6cf23a96eee2efb188ef52301954585883e69b6624b2420000000000ffffffff0148e6052a01000000160014768e1eeb4cf420866033f80aceff0f9720744969000000000001012b00f2052a010000002251205a2c2cf5b52cf31f83ad2e8da63ff03183ecd8f609c7510ae8a48e03910a0757011340bb53ec917bad9d906af1ba87181c48b86ace5aae2b53605a725ca74625631476fc6f5baedaf4f2ee0f477f36f58f3970d5b8273b7e497b97af2e3f125c97af342116fe349064c98d6e2a853fa3c9b12bd8b304a19c195c60efa7ee2393046d3fa2321900772b2da75600008001000080000000800100000000000000011720fe349064c98d6e2a853fa3c9b12bd8b304a19c195c60efa7ee2393046d3fa232002202036b772a6db74d8753c98a827958de6c78ab3312109f37d3e0304484242ece73d818772b2da7540000800100008000000080000000000000000000",
+    ),
+    (
+        "Output with internal key",
+        "70736274ff01005e020000000127744ababf3027fe0d6cf23a96eee2efb188ef52301954585883e69b6624b2420000000000ffffffff0148e6052a0100000022512083698e458c6664e1595d75da2597de1e22ee97d798e706c4c0a4b5a9823cd743000000000001012b00f2052a010000002251205a2c2cf5b52cf31f83ad2e8da63ff03183ecd8f609c7510ae8a48e03910a07572116fe349064c98d6e2a853fa3c9b12bd8b304a19c195c60efa7ee2393046d3fa2321900772b2da75600008001000080000000800100000000000000011720fe349064c98d6e2a853fa3c9b12bd8b304a19c195c60efa7ee2393046d3fa232000105201124da7aec92ccd06c954562647f437b138b95721a84be2bf2276bbddab3e67121071124da7aec92ccd06c954562647f437b138b95721a84be2bf2276bbddab3e6711900772b2da7560000800100008000000080000000000500000000",
+    ),
+    (
+        "Script path input",
+        "70736274ff01005e02000000019bd48765230bf9a72e662001f972556e54f0c6f97feb56bcb5600d817f6995260100000000ffffffff0148e6052a0100000022512083698e458c6664e1595d75da2597de1e22ee97d798e706c4c0a4b5a9823cd743000000000001012b00f2052a01000000225120c2247efbfd92ac47f6f40b8d42d169175a19fa9fa10e4a25d7f35eb4dd85b6926215c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac06f7d62059e9497a1a4a267569d9876da60101aff38e3529b9b939ce7f91ae970115f2e490af7cc45c4f78511f36057ce5c5a5c56325a29fb44dfc203f356e1f823202cb13ac68248de806aa6a3659cf3c03eb6821d09c8114a4e868febde865bb6d2acc04215c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac097c6e6fea5ff714ff5724499990810e406e98aa10f5bf7e5f6784bc1d0a9a6ce23204320b0bf16f011b53ea7be615924aa7f27e5d29ad20ea1155d848676c3bad1b2acc06215c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0cd970e15f53fc0c82f950fd560ffa919b76172be017368a89913af074f400b09115f2e490af7cc45c4f78511f36057ce5c5a5c56325a29fb44dfc203f356e1f82320fa0f7a3cef3b1d0c0a6ce7d26e17ada0b2e5c92d19efad48b41859cb8a451ca9acc021162cb13ac68248de806aa6a3659cf3c03eb6821d09c8114a4e868febde865bb6d23901cd970e15f53fc0c82f950fd560ffa919b76172be017368a89913af074f400b09772b2da7560000800100008002000080000000000000000021164320b0bf16f011b53ea7be615924aa7f27e5d29ad20ea1155d848676c3bad1b23901115f2e490af7cc45c4f78511f36057ce5c5a5c56325a29fb44dfc203f356e1f8772b2da75600008001000080010000800000000000000000211650929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac005007c461e5d2116fa0f7a3cef3b1d0c0a6ce7d26e17ada0b2e5c92d19efad48b41859cb8a451ca939016f7d62059e9497a1a4a267569d9876da60101aff38e3529b9b939ce7f91ae970772b2da7560000800100008003000080000000000000000001172050929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0011820f0362e2f75a6f420a5bde3eb221d96ae6720cf25f81890c95b1d775acb515e65000105201124da7aec92ccd06c954562647f437b138b95721a84be2bf2276bbddab3e67121071124da7aec92ccd06c954562647f437b138b95721a84be2bf2276bbddab3e6711900772b2da7560000800100008000000080000000000500000000",
+    ),
+    (
+        "Output with taproot tree",
+        "70736274ff01005e020000000127744ababf3027fe0d6cf23a96eee2efb188ef52301954585883e69b6624b2420000000000ffffffff0148e6052a010000002251200a8cbdc86de1ce1c0f9caeb22d6df7ced3683fe423e05d1e402a879341d6f6f5000000000001012b00f2052a010000002251205a2c2cf5b52cf31f83ad2e8da63ff03183ecd8f609c7510ae8a48e03910a07572116fe349064c98d6e2a853fa3c9b12bd8b304a19c195c60efa7ee2393046d3fa2321900772b2da75600008001000080000000800100000000000000011720fe349064c98d6e2a853fa3c9b12bd8b304a19c195c60efa7ee2393046d3fa2320001052050929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac001066f02c02220736e572900fe1252589a2143c8f3c79f71a0412d2353af755e9701c782694a02ac02c02220631c5f3b5832b8fbdebfb19704ceeb323c21f40f7a24f43d68ef0cc26b125969ac01c0222044faa49a0338de488c8dfffecdfb6f329f380bd566ef20c8df6d813eab1c4273ac210744faa49a0338de488c8dfffecdfb6f329f380bd566ef20c8df6d813eab1c42733901f06b798b92a10ed9a9d0bbfd3af173a53b1617da3a4159ca008216cd856b2e0e772b2da75600008001000080010000800000000003000000210750929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac005007c461e5d2107631c5f3b5832b8fbdebfb19704ceeb323c21f40f7a24f43d68ef0cc26b125969390118ace409889785e0ea70ceebb8e1ca892a7a78eaede0f2e296cf435961a8f4ca772b2da756000080010000800200008000000000030000002107736e572900fe1252589a2143c8f3c79f71a0412d2353af755e9701c782694a02390129a5b4915090162d759afd3fe0f93fa3326056d0b4088cb933cae7826cb8d82c772b2da7560000800100008003000080000000000300000000",
+    ),
+    (
+        "Script path input with signatures",
+        "70736274ff01005e02000000019bd48765230bf9a72e662001f972556e54f0c6f97feb56bcb5600d817f6995260100000000ffffffff0148e6052a0100000022512083698e458c6664e1595d75da2597de1e22ee97d798e706c4c0a4b5a9823cd743000000000001012b00f2052a01000000225120c2247efbfd92ac47f6f40b8d42d169175a19fa9fa10e4a25d7f35eb4dd85b69241142cb13ac68248de806aa6a3659cf3c03eb6821d09c8114a4e868febde865bb6d2cd970e15f53fc0c82f950fd560ffa919b76172be017368a89913af074f400b0940bf818d9757d6ffeb538ba057fb4c1fc4e0f5ef186e765beb564791e02af5fd3d5e2551d4e34e33d86f276b82c99c79aed3f0395a081efcd2cc2c65dd7e693d7941144320b0bf16f011b53ea7be615924aa7f27e5d29ad20ea1155d848676c3bad1b2115f2e490af7cc45c4f78511f36057ce5c5a5c56325a29fb44dfc203f356e1f840e1f1ab6fabfa26b236f21833719dc1d428ab768d80f91f9988d8abef47bfb863bb1f2a529f768c15f00ce34ec283cdc07e88f8428be28f6ef64043c32911811a4114fa0f7a3cef3b1d0c0a6ce7d26e17ada0b2e5c92d19efad48b41859cb8a451ca96f7d62059e9497a1a4a267569d9876da60101aff38e3529b9b939ce7f91ae97040ec1f0379206461c83342285423326708ab031f0da4a253ee45aafa5b8c92034d8b605490f8cd13e00f989989b97e215faa36f12dee3693d2daccf3781c1757f66215c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac06f7d62059e9497a1a4a267569d9876da60101aff38e3529b9b939ce7f91ae970115f2e490af7cc45c4f78511f36057ce5c5a5c56325a29fb44dfc203f356e1f823202cb13ac68248de806aa6a3659cf3c03eb6821d09c8114a4e868febde865bb6d2acc04215c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac097c6e6fea5ff714ff5724499990810e406e98aa10f5bf7e5f6784bc1d0a9a6ce23204320b0bf16f011b53ea7be615924aa7f27e5d29ad20ea1155d848676c3bad1b2acc06215c150929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0cd970e15f53fc0c82f950fd560ffa919b76172be017368a89913af074f400b09115f2e490af7cc45c4f78511f36057ce5c5a5c56325a29fb44dfc203f356e1f82320fa0f7a3cef3b1d0c0a6ce7d26e17ada0b2e5c92d19efad48b41859cb8a451ca9acc021162cb13ac68248de806aa6a3659cf3c03eb6821d09c8114a4e868febde865bb6d23901cd970e15f53fc0c82f950fd560ffa919b76172be017368a89913af074f400b09772b2da7560000800100008002000080000000000000000021164320b0bf16f011b53ea7be615924aa7f27e5d29ad20ea1155d848676c3bad1b23901115f2e490af7cc45c4f78511f36057ce5c5a5c56325a29fb44dfc203f356e1f8772b2da75600008001000080010000800000000000000000211650929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac005007c461e5d2116fa0f7a3cef3b1d0c0a6ce7d26e17ada0b2e5c92d19efad48b41859cb8a451ca939016f7d62059e9497a1a4a267569d9876da60101aff38e3529b9b939ce7f91ae970772b2da7560000800100008003000080000000000000000001172050929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0011820f0362e2f75a6f420a5bde3eb221d96ae6720cf25f81890c95b1d775acb515e65000105201124da7aec92ccd06c954562647f437b138b95721a84be2bf2276bbddab3e67121071124da7aec92ccd06c954562647f437b138b95721a84be2bf2276bbddab3e6711900772b2da7560000800100008000000080000000000500000000",
+    ),
+];
+
+#[tokio::test]
+async fn test_psbt_bip371_vectors_round_trip() -> Result<()> {
+    for (name, vector) in BIP371_VALID_VECTORS {
+        let bytes = hex::decode(vector)?;
+        let psbt = bitcoin::Psbt::deserialize(&bytes)?;
+
+        let builder = PsbtBuilder::deserialize(&bytes)?;
+        assert_eq!(builder.to_psbt()?, psbt, "{}", name);
+        assert_eq!(
+            PsbtBuilder::deserialize(&builder.serialize_v2()?)?.to_psbt()?,
+            psbt,
+            "{}",
+            name
+        );
+    }
+
+    // PSBT_IN_TAP_KEY_SIG has no builder field and is kept as a raw pair
+    let builder = PsbtBuilder::deserialize(&hex::decode(BIP371_VALID_VECTORS[1].1)?)?;
+    assert!(builder.inputs()[0].unknown.contains_key("13"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_psbt_bip371_vectors_finalize() -> Result<()> {
+    use bitcoin::key::XOnlyPublicKey;
+    use bitcoin::secp256k1::Secp256k1;
+    use bitcoin::taproot::TapLeafHash;
+    use cerberus::bitcoin::PsbtSigner;
+
+    let signer = PsbtSigner::new();
+
+    // Key path: the witness is the key signature alone
+    let mut key_path = bitcoin::Psbt::deserialize(&hex::decode(BIP371_VALID_VECTORS[1].1)?)?;
+    let signature = key_path.inputs[0].tap_key_sig.expect("key path signature");
+    signer.finalize(&mut key_path)?;
+
+    let input = &key_path.inputs[0];
+    let witness = input.final_script_witness.as_ref().expect("finalized");
+    assert_eq!(witness.to_vec(), vec![signature.to_vec()]);
+    assert!(input.final_script_sig.is_none());
+    assert!(input.tap_key_sig.is_none());
+    assert!(input.tap_internal_key.is_none());
+    assert!(input.tap_key_origins.is_empty());
+
+    // Script path: signature, leaf script and control block of one signed leaf
+    let mut script_path = bitcoin::Psbt::deserialize(&hex::decode(BIP371_VALID_VECTORS[5].1)?)?;
+    let signed = script_path.inputs[0].clone();
+    assert!(signed.tap_scripts.len() > 1);
+    signer.finalize(&mut script_path)?;
+
+    let input = &script_path.inputs[0];
+    let witness = input.final_script_witness.as_ref().expect("finalized");
+    let witness = witness.to_vec();
+    assert_eq!(witness.len(), 3);
+    let (control_block, (script, version)) = signed
+        .tap_scripts
+        .iter()
+        .find(|(control_block, _)| control_block.serialize() == witness[2])
+        .expect("control block of a signed leaf");
+    assert_eq!(witness[1], script.to_bytes());
+    let key = XOnlyPublicKey::from_slice(&script.as_bytes()[1..33])?;
+    let signature = signed.tap_script_sigs[&(key, TapLeafHash::from_script(script, *version))];
+    assert_eq!(witness[0], signature.to_vec());
+
+    let spent = signed.witness_utxo.as_ref().expect("witness utxo");
+    let output_key = XOnlyPublicKey::from_slice(&spent.script_pubkey.as_bytes()[2..])?;
+    let secp = Secp256k1::verification_only();
+    assert!(control_block.verify_taproot_commitment(&secp, output_key, script));
+    assert!(input.tap_script_sigs.is_empty());
+    assert!(input.tap_scripts.is_empty());
+
+    // Without any taproot signature the input cannot be finalized
+    let mut unsigned = bitcoin::Psbt::deserialize(&hex::decode(BIP371_VALID_VECTORS[3].1)?)?;
+    assert!(signer.finalize(&mut unsigned).is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_psbt_combine_keeps_taproot_and_proprietary_fields() -> Result<()> {
+    use bitcoin::bip32::{DerivationPath, Fingerprint, Xpub};
+    use bitcoin::psbt::raw::ProprietaryKey;
+    use cerberus::bitcoin::PsbtSigner;
+    use std::str::FromStr;
+
+    let signed = bitcoin::Psbt::deserialize(&hex::decode(BIP371_VALID_VECTORS[1].1)?)?;
+    let mut unsigned = signed.clone();
+    unsigned.inputs[0].tap_key_sig = None;
+
+    // The creator's copy also carries a global xpub and proprietary pairs
+    let xpub = Xpub::from_str("xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8")?;
+    let proprietary = ProprietaryKey {
+        prefix: b"cerberus".to_vec(),
+        subtype: 0,
+        key: vec![1],
+    };
+    unsigned.xpub.insert(
+        xpub,
+        (Fingerprint::from_str("d90c6a4f")?, DerivationPath::master()),
+    );
+    unsigned.proprietary.insert(proprietary.clone(), vec![2]);
+    unsigned.outputs[0].proprietary.insert(proprietary, vec![3]);
+
+    let mut expected = unsigned.clone();
+    expected.inputs[0].tap_key_sig = signed.inputs[0].tap_key_sig;
+
+    let signer = PsbtSigner::new();
+    let combined = signer.combine_psbts(vec![
+        PsbtBuilder::from_psbt(&unsigned)?,
+        PsbtBuilder::from_psbt(&signed)?,
+    ])?;
+    assert_eq!(combined.to_psbt()?, expected);
+    assert_eq!(signer.combine(vec![unsigned, signed])?, expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_psbt_combine_and_finalize_p2wpkh() -> Result<()> {
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use bitcoin::sighash::SighashCache;
+    use cerberus::bitcoin::{PsbtSigner, Utxo};
+    use std::collections::HashMap;
+
+    let secp = Secp256k1::new();
+    let secret = SecretKey::from_slice(&[0x11; 32])?;
+    let pubkey = bitcoin::PublicKey::new(secret.public_key(&secp));
+    let script_pubkey = bitcoin::ScriptBuf::new_p2wpkh(&pubkey.wpubkey_hash().unwrap());
+
+    let utxo = Utxo {
+        txid: "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string(),
+        vout: 0,
+        amount: Amount::from_sat(100_000),
+        script_pubkey: hex::encode(script_pubkey.as_bytes()),
+        address: None,
+        confirmations: 6,
+        spendable: true,
+        safe: true,
+        label: None,
+    };
+    let mut outputs = HashMap::new();
+    outputs.insert("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string(), Amount::from_sat(90_000));
+    let unsigned = PsbtBuilder::from_utxos_and_outputs(vec![utxo], outputs)?;
+
+    // The signer works on its own copy of the PSBT
+    let mut psbt = unsigned.to_psbt()?;
+    let (message, hash_ty) = psbt.sighash_ecdsa(0, &mut SighashCache::new(&psbt.unsigned_tx))?;
+    let signature = bitcoin::ecdsa::Signature { sig: secp.sign_ecdsa(&message, &secret), hash_ty };
+    psbt.inputs[0].partial_sigs.insert(pubkey, signature);
+    let proprietary = bitcoin::psbt::raw::ProprietaryKey {
+        prefix: b"cerberus".to_vec(),
+        subtype: 0,
+        key: vec![],
+    };
+    psbt.proprietary.insert(proprietary.clone(), vec![1]);
+    let signed = PsbtBuilder::from_psbt(&psbt)?;
+
+    let signer = PsbtSigner::new();
+    assert!(!unsigned.is_complete());
+    let combined = signer.combine_psbts(vec![unsigned.clone(), signed])?;
+    assert!(combined.is_complete());
+    assert_eq!(
+        combined.inputs()[0].partial_sigs[&pubkey.to_string()],
+        hex::encode(signature.to_vec())
+    );
+
+    let tx_hex = signer.finalize_psbt(&combined)?;
+    let tx: bitcoin::Transaction = bitcoin::consensus::encode::deserialize(&hex::decode(&tx_hex)?)?;
+    assert_eq!(tx.input[0].witness.len(), 2);
+    assert_eq!(tx.input[0].witness.nth(1), Some(&pubkey.to_bytes()[..]));
+    assert!(tx.input[0].script_sig.is_empty());
+    assert_eq!(tx.output[0].value.to_sat(), 90_000);
+
+    // Finalized inputs keep only the final witness
+    let mut finalized = combined.clone();
+    signer.finalize_inputs(&mut finalized)?;
+    assert!(finalized.inputs()[0].partial_sigs.is_empty());
+    assert_eq!(finalized.inputs()[0].final_script_witness.as_ref().map(Vec::len), Some(2));
+    // Pairs without a builder field survive combining and finalizing
+    assert_eq!(finalized.to_psbt()?.proprietary[&proprietary], vec![1]);
+
+    // PSBTs of different transactions cannot be combined
+    let mut other = unsigned.clone();
+    other.set_lock_time(100);
+    assert!(signer.combine_psbts(vec![unsigned, other]).is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_psbt_finalize_p2wsh_multisig() -> Result<()> {
+    use bitcoin::blockdata::opcodes::all::OP_CHECKMULTISIG;
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use bitcoin::sighash::SighashCache;
+    use cerberus::bitcoin::psbt::{PsbtInput, PsbtOutput};
+    use cerberus::bitcoin::PsbtSigner;
+    use std::collections::HashMap;
+
+    let secp = Secp256k1::new();
+    let secrets: Vec<SecretKey> = (1..=3u8)
+        .map(|i| SecretKey::from_slice(&[i; 32]))
+        .collect::<Result<_, _>>()?;
+    let pubkeys: Vec<bitcoin::PublicKey> = secrets
+        .iter()
+        .map(|secret| bitcoin::PublicKey::new(secret.public_key(&secp)))
+        .collect();
+
+    // 2-of-3 multisig
+    let witness_script = pubkeys
+        .iter()
+        .fold(bitcoin::script::Builder::new().push_int(2), |builder, pubkey| builder.push_key(pubkey))
+        .push_int(3)
+        .push_opcode(OP_CHECKMULTISIG)
+        .into_script();
+    let script_pubkey = bitcoin::ScriptBuf::new_p2wsh(&witness_script.wscript_hash());
+
+    let mut unsigned = PsbtBuilder::new();
+    unsigned.add_input(PsbtInput {
+        prev_txid: "abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890".to_string(),
+        prev_vout: 1,
+        prev_amount: Amount::from_sat(500_000),
+        script_pubkey: hex::encode(script_pubkey.as_bytes()),
+        non_witness_utxo: None,
+        sequence: 0xfffffffd,
+        redeem_script: None,
+        witness_script: Some(hex::encode(witness_script.as_bytes())),
+        bip32_derivation: HashMap::new(),
+        partial_sigs: HashMap::new(),
+        sighash_type: Some(1),
+        final_script_sig: None,
+        final_script_witness: None,
+        unknown: HashMap::new(),
+    });
+    unsigned.add_output(PsbtOutput {
+        amount: Amount::from_sat(490_000),
+        script_pubkey: "0014751e76e8199196d454941c45d1b3a323f1433bd6".to_string(),
+        bip32_derivation: HashMap::new(),
+        redeem_script: None,
+        witness_script: None,
+        unknown: HashMap::new(),
+    });
+
+    // Cosigners 3 and 1 sign separately
+    let mut signed = Vec::new();
+    for index in [2, 0] {
+        let mut psbt = unsigned.to_psbt()?;
+        let (message, hash_ty) = psbt.sighash_ecdsa(0, &mut SighashCache::new(&psbt.unsigned_tx))?;
+        let sig = secp.sign_ecdsa(&message, &secrets[index]);
+        psbt.inputs[0]
+            .partial_sigs
+            .insert(pubkeys[index], bitcoin::ecdsa::Signature { sig, hash_ty });
+        signed.push(PsbtBuilder::from_psbt(&psbt)?);
+    }
+
+    let signer = PsbtSigner::new();
+    let combined = signer.combine_psbts(signed.clone())?;
+    assert_eq!(combined.inputs()[0].partial_sigs.len(), 2);
+
+    // One signature is not enough for 2-of-3
+    assert!(signer.finalize_psbt(&signed[0]).is_err());
+
+    let tx_hex = signer.finalize_psbt(&combined)?;
+    let tx: bitcoin::Transaction = bitcoin::consensus::encode::deserialize(&hex::decode(&tx_hex)?)?;
+    let witness = &tx.input[0].witness;
+    assert_eq!(witness.len(), 4);
+    assert!(witness.nth(0).unwrap().is_empty());
+    // Signatures follow public key order in the script
+    assert_eq!(
+        hex::encode(witness.nth(1).unwrap()),
+        combined.inputs()[0].partial_sigs[&pubkeys[0].to_string()]
+    );
+    assert_eq!(witness.nth(3), Some(witness_script.as_bytes()));
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_transaction_builder_creation() -> Result<()> {
     let builder = TransactionBuilder::new();
@@ -420,6 +942,429 @@ async fn test_transaction_size_estimation() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_transaction_size_by_input_type() -> Result<()> {
+    let legacy = coin_selection_utxo(1, 100_000, "76a914751e76e8199196d454941c45d1b3a323f1433bd688ac");
+    let segwit = coin_selection_utxo(2, 100_000, "0014751e76e8199196d454941c45d1b3a323f1433bd6");
+
+    let legacy_size = TransactionBuilder::new().add_input_from_utxo(&legacy).estimate_transaction_size(1, true);
+    let segwit_size = TransactionBuilder::new().add_input_from_utxo(&segwit).estimate_transaction_size(1, true);
+
+    // 1-in 2-out P2WPKH is 141 vB, with a P2PKH input 220 vB
+    assert_eq!(segwit_size, 141);
+    assert_eq!(legacy_size, 220);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_script_type_weights() -> Result<()> {
+    use cerberus::bitcoin::coin_selection::estimate_weight;
+
+    let detect = |script: &str| ScriptType::from_script_pubkey(&bitcoin::ScriptBuf::from_hex(script).unwrap());
+    assert_eq!(detect("76a914751e76e8199196d454941c45d1b3a323f1433bd688ac"), ScriptType::P2PKH);
+    assert_eq!(detect("a914751e76e8199196d454941c45d1b3a323f1433bd687"), ScriptType::P2SH);
+    assert_eq!(detect("0014751e76e8199196d454941c45d1b3a323f1433bd6"), ScriptType::P2WPKH);
+    assert_eq!(
+        detect("00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262"),
+        ScriptType::P2WSH
+    );
+    assert_eq!(
+        detect("5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"),
+        ScriptType::P2TR
+    );
+    assert_eq!(detect("6a0100"), ScriptType::Custom);
+
+    assert_eq!(ScriptType::P2PKH.input_weight(), Some(592));
+    assert_eq!(ScriptType::P2WPKH.input_weight(), Some(272));
+    assert_eq!(ScriptType::P2TR.input_weight(), Some(230));
+    assert_eq!(ScriptType::Custom.input_weight(), None);
+    assert_eq!(ScriptType::P2WPKH.output_weight(), 124);
+    assert_eq!(ScriptType::P2TR.output_weight(), 172);
+
+    // Taproot key path spends are the cheapest
+    let weight = |input: ScriptType| estimate_weight(&[input], &[ScriptType::P2WPKH]).unwrap();
+    assert!(weight(ScriptType::P2TR) < weight(ScriptType::P2WPKH));
+    assert!(weight(ScriptType::P2WPKH) < weight(ScriptType::P2SH));
+    assert!(weight(ScriptType::P2SH) < weight(ScriptType::P2PKH));
+    assert!(estimate_weight(&[ScriptType::HashLock], &[ScriptType::P2WPKH]).is_none());
+
+    Ok(())
+}
+
+/// UTXO for coin selection tests
+fn coin_selection_utxo(index: u8, sat: u64, script_pubkey: &str) -> cerberus::bitcoin::Utxo {
+    cerberus::bitcoin::Utxo {
+        txid: hex::encode([index; 32]),
+        vout: index as u32,
+        amount: Amount::from_sat(sat),
+        script_pubkey: script_pubkey.to_string(),
+        address: None,
+        confirmations: 6,
+        spendable: true,
+        safe: true,
+        label: None,
+    }
+}
+
+/// P2WPKH UTXO with its own script
+fn p2wpkh_utxo(index: u8, sat: u64) -> cerberus::bitcoin::Utxo {
+    coin_selection_utxo(index, sat, &format!("0014{}", hex::encode([index; 20])))
+}
+
+#[tokio::test]
+async fn test_coin_selection_branch_and_bound() -> Result<()> {
+    use cerberus::bitcoin::{CoinSelectionConfig, CoinSelector, SelectionAlgorithm, SelectionStrategy};
+
+    // At 1 sat/vB a P2WPKH input costs 68 sat and the rest of a
+    // 1-output transaction 42 sat
+    let utxos = vec![
+        p2wpkh_utxo(1, 300_000),
+        p2wpkh_utxo(2, 100_068),
+        p2wpkh_utxo(3, 7_000),
+        p2wpkh_utxo(4, 50_068),
+    ];
+
+    let selector = CoinSelector::new(CoinSelectionConfig {
+        strategy: SelectionStrategy::BranchAndBound,
+        ..CoinSelectionConfig::default()
+    });
+    let selection = selector.select(&utxos, Amount::from_sat(149_958), &[ScriptType::P2WPKH], 1.0)?;
+
+    assert_eq!(selection.algorithm, SelectionAlgorithm::BranchAndBound);
+    assert!(selection.change.is_none());
+    let mut selected: Vec<u32> = selection.utxos.iter().map(|utxo| utxo.vout).collect();
+    selected.sort();
+    assert_eq!(selected, vec![2, 4]);
+    assert_eq!(selection.input_amount.to_sat(), 150_136);
+    assert_eq!(selection.fee.to_sat(), 150_136 - 149_958);
+    assert_eq!(selection.vsize(), 178);
+    // Inputs are cheaper now than at the long-term fee rate of 10 sat/vB
+    assert_eq!(selection.waste, 2 * (68 - 680));
+
+    // The lowest waste strategy finds the same changeless spend
+    let selection = CoinSelector::default().select(&utxos, Amount::from_sat(149_958), &[ScriptType::P2WPKH], 1.0)?;
+    assert_eq!(selection.algorithm, SelectionAlgorithm::BranchAndBound);
+    assert!(selection.change.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_coin_selection_with_change() -> Result<()> {
+    use cerberus::bitcoin::{CoinSelectionConfig, CoinSelector, SelectionAlgorithm, SelectionStrategy};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let utxos: Vec<_> = (1..=10).map(|i| p2wpkh_utxo(i, i as u64 * 40_000)).collect();
+    let payment = Amount::from_sat(250_000);
+    let outputs = [ScriptType::P2WPKH, ScriptType::P2TR];
+
+    for (strategy, algorithm) in [
+        (SelectionStrategy::Knapsack, SelectionAlgorithm::Knapsack),
+        (SelectionStrategy::SingleRandomDraw, SelectionAlgorithm::SingleRandomDraw),
+        // No changeless solution, falls back to knapsack
+        (SelectionStrategy::BranchAndBound, SelectionAlgorithm::Knapsack),
+    ] {
+        let selector = CoinSelector::new(CoinSelectionConfig {
+            strategy,
+            ..CoinSelectionConfig::default()
+        });
+        let mut rng = StdRng::seed_from_u64(7);
+        let selection = selector.select_with_rng(&utxos, payment, &outputs, 5.0, &mut rng)?;
+
+        assert_eq!(selection.algorithm, algorithm);
+        let change = selection.change.expect("selection with change").to_sat();
+        assert!(change >= 546);
+        assert_eq!(
+            selection.input_amount.to_sat(),
+            payment.to_sat() + selection.fee.to_sat() + change
+        );
+        // Fee covers the estimated size at the requested fee rate
+        assert!(selection.fee.to_sat() as f64 >= 5.0 * selection.weight as f64 / 4.0);
+        assert!(selection.fee_rate() < 5.1);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_coin_selection_filters_and_insufficient_funds() -> Result<()> {
+    use cerberus::bitcoin::{BitcoinError, CoinSelector};
+
+    let mut unspendable = p2wpkh_utxo(1, 1_000_000);
+    unspendable.spendable = false;
+    let mut unsafe_utxo = p2wpkh_utxo(2, 1_000_000);
+    unsafe_utxo.safe = false;
+    let custom = coin_selection_utxo(3, 1_000_000, "6a0100");
+    let utxos = vec![unspendable, unsafe_utxo, custom, p2wpkh_utxo(4, 20_000)];
+
+    let selector = CoinSelector::default();
+    let selection = selector.select(&utxos, Amount::from_sat(15_000), &[ScriptType::P2WPKH], 2.0)?;
+    assert_eq!(selection.utxos.len(), 1);
+    assert_eq!(selection.utxos[0].vout, 4);
+
+    match selector.select(&utxos, Amount::from_sat(50_000), &[ScriptType::P2WPKH], 2.0) {
+        Err(BitcoinError::InsufficientFunds { available, .. }) => assert_eq!(available.to_sat(), 20_000),
+        other => panic!("expected insufficient funds, got {:?}", other),
+    }
+
+    // Dust UTXOs are not worth spending at high fee rates
+    let dust = vec![p2wpkh_utxo(5, 1_000), p2wpkh_utxo(6, 1_000)];
+    assert!(selector.select(&dust, Amount::from_sat(500), &[ScriptType::P2WPKH], 20.0).is_err());
+
+    assert!(selector.select(&utxos, Amount::from_sat(15_000), &[ScriptType::P2WPKH], f64::NAN).is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_coin_selection_privacy_mode() -> Result<()> {
+    use cerberus::bitcoin::{CoinSelectionConfig, CoinSelector};
+
+    let labeled = |index: u8, sat: u64, label: &str| {
+        let mut utxo = p2wpkh_utxo(index, sat);
+        utxo.label = Some(label.to_string());
+        utxo
+    };
+    let mut utxos = vec![
+        labeled(1, 60_000, "exchange"),
+        labeled(2, 60_000, "exchange"),
+        labeled(3, 100_000, "salary"),
+        labeled(4, 30_000, "salary"),
+    ];
+    // Second payment received on the same address as UTXO 3
+    let mut reused = labeled(5, 5_000, "salary");
+    reused.script_pubkey = utxos[2].script_pubkey.clone();
+    utxos.push(reused);
+
+    let selector = CoinSelector::new(CoinSelectionConfig::privacy());
+    for _ in 0..20 {
+        let selection = selector.select(&utxos, Amount::from_sat(110_000), &[ScriptType::P2WPKH], 1.0)?;
+
+        // Labels are never mixed
+        let labels: std::collections::HashSet<_> = selection.utxos.iter().map(|utxo| utxo.label.clone()).collect();
+        assert_eq!(labels.len(), 1);
+
+        // Both UTXOs of the reused address are spent together
+        let vouts: Vec<u32> = selection.utxos.iter().map(|utxo| utxo.vout).collect();
+        assert_eq!(vouts.contains(&3), vouts.contains(&5));
+    }
+
+    // No single label can pay
+    assert!(selector.select(&utxos, Amount::from_sat(160_000), &[ScriptType::P2WPKH], 1.0).is_err());
+    assert!(CoinSelector::default().select(&utxos, Amount::from_sat(160_000), &[ScriptType::P2WPKH], 1.0).is_ok());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transaction_builder_coin_selection() -> Result<()> {
+    use cerberus::bitcoin::FeeEstimate;
+    use std::collections::HashMap;
+
+    let utxos = vec![p2wpkh_utxo(1, 100_000), p2wpkh_utxo(2, 250_000)];
+    let fee_estimate = FeeEstimate {
+        fee_rate: 2.0,
+        estimated_fee: Amount::from_sat(0),
+        target_blocks: 6,
+    };
+    let mut outputs = HashMap::new();
+    outputs.insert("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string(), Amount::from_sat(120_000));
+
+    let builder = TransactionBuilder::new();
+    let (_, fee) = builder
+        .build_transaction(
+            utxos.clone(),
+            outputs.clone(),
+            fee_estimate.clone(),
+            Some("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string()),
+        )
+        .await?;
+    assert!(fee.to_sat() >= 2 * 110);
+
+    // Change needs a change address
+    assert!(builder
+        .build_transaction(utxos.clone(), outputs, fee_estimate.clone(), None)
+        .await
+        .is_err());
+
+    // Invalid recipient address
+    let mut outputs = HashMap::new();
+    outputs.insert("not_an_address".to_string(), Amount::from_sat(120_000));
+    assert!(builder.build_transaction(utxos, outputs, fee_estimate, None).await.is_err());
+
+    Ok(())
+}
+
+/// Unconfirmed 1-in 2-out P2WPKH transaction paying 60_000 sat with
+/// 1_000 sat fee, and the output it spends
+fn stuck_transaction(change: u64, sequence: u32) -> (bitcoin::Transaction, Vec<bitcoin::TxOut>) {
+    use bitcoin::{absolute::LockTime, transaction::Version, OutPoint, ScriptBuf, Sequence, TxIn, TxOut, Witness};
+
+    let p2wpkh = |byte: u8| ScriptBuf::from_hex(&format!("0014{}", hex::encode([byte; 20]))).unwrap();
+    let spent = TxOut {
+        value: bitcoin::Amount::from_sat(61_000 + change),
+        script_pubkey: p2wpkh(1),
+    };
+    let transaction = bitcoin::Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(hex::encode([7u8; 32]).parse().unwrap(), 0),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence(sequence),
+            // Signature and public key sized witness
+            witness: Witness::from_slice(&[vec![0u8; 72], vec![2u8; 33]]),
+        }],
+        output: vec![
+            TxOut {
+                value: bitcoin::Amount::from_sat(60_000),
+                script_pubkey: p2wpkh(2),
+            },
+            TxOut {
+                value: bitcoin::Amount::from_sat(change),
+                script_pubkey: p2wpkh(3),
+            },
+        ],
+    };
+    (transaction, vec![spent])
+}
+
+#[tokio::test]
+async fn test_rbf_fee_bump() -> Result<()> {
+    use cerberus::bitcoin::{FeeBumpMethod, FeeBumper};
+
+    let (original, spent) = stuck_transaction(39_000, 0xfffffffd);
+    let bumper = FeeBumper::new(Network::Regtest);
+    assert_eq!(original.vsize(), 141);
+
+    let bump = bumper.bump_rbf(&original, &spent, 1, 10.0, &[])?;
+    assert_eq!(bump.method, FeeBumpMethod::ReplaceByFee);
+    assert_eq!(bump.vsize, 141);
+    assert_eq!(bump.fee.to_sat(), 1_410);
+    assert!(bump.validation.is_valid);
+
+    let replacement = &bump.transaction;
+    assert_eq!(replacement.input.len(), 1);
+    assert_eq!(replacement.input[0].previous_output, original.input[0].previous_output);
+    assert!(replacement.input[0].witness.is_empty());
+    assert!(replacement.is_explicitly_rbf());
+    // Payment is unchanged, the change pays the higher fee
+    assert_eq!(replacement.output[0], original.output[0]);
+    assert_eq!(replacement.output[1].value.to_sat(), 39_000 - 410);
+
+    // BIP 125 rule 4: the extra fee must pay for the replacement's relay
+    let bump = bumper.bump_rbf(&original, &spent, 1, 7.2, &[])?;
+    assert_eq!(bump.fee.to_sat(), 1_000 + 141);
+
+    // The replacement must pay a higher fee rate
+    assert!(bumper.bump_rbf(&original, &spent, 1, 7.0, &[]).is_err());
+
+    // PSBT for signing carries the spent outputs
+    let psbt = bump.to_psbt()?;
+    assert_eq!(psbt.inputs()[0].prev_amount.to_sat(), 100_000);
+    assert_eq!(psbt.outputs()[1].amount.to_sat(), 39_000 - 141);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rbf_fee_bump_rules() -> Result<()> {
+    use cerberus::bitcoin::security_validator::SecurityConfig;
+    use cerberus::bitcoin::{BitcoinError, FeeBumpConfig, FeeBumper};
+
+    let bumper = FeeBumper::new(Network::Regtest);
+
+    // BIP 125 rule 1: the original must signal replaceability
+    let (final_tx, spent) = stuck_transaction(39_000, 0xfffffffe);
+    assert!(bumper.bump_rbf(&final_tx, &spent, 1, 10.0, &[]).is_err());
+
+    // Small change is topped up with a confirmed UTXO only (rule 2)
+    let (original, spent) = stuck_transaction(1_000, 0xfffffffd);
+    let mut unconfirmed = p2wpkh_utxo(8, 500_000);
+    unconfirmed.confirmations = 0;
+    let confirmed = p2wpkh_utxo(9, 50_000);
+    let bump = bumper.bump_rbf(&original, &spent, 1, 20.0, &[unconfirmed.clone(), confirmed.clone()])?;
+
+    let added: Vec<String> = bump.transaction.input[1..]
+        .iter()
+        .map(|input| input.previous_output.txid.to_string())
+        .collect();
+    assert_eq!(added, vec![confirmed.txid.clone()]);
+    assert_eq!(bump.spent_outputs.len(), 2);
+    assert_eq!(
+        bump.transaction.output[1].value.to_sat(),
+        1_000 + 1_000 + 50_000 - bump.fee.to_sat()
+    );
+    assert!(bump.fee_rate >= 20.0);
+
+    // Without a confirmed UTXO the change cannot pay for the bump
+    match bumper.bump_rbf(&original, &spent, 1, 20.0, &[unconfirmed]) {
+        Err(BitcoinError::InsufficientFunds { .. }) => {}
+        other => panic!("expected insufficient funds, got {:?}", other),
+    }
+
+    // Dust change is dropped when that covers the fee
+    let bump = bumper.bump_rbf(&original, &spent, 1, 12.0, &[])?;
+    assert_eq!(bump.transaction.output.len(), 1);
+    assert_eq!(bump.fee.to_sat(), 2_000);
+
+    // Replacements are checked by the security validator
+    let strict = FeeBumper::with_config(
+        FeeBumpConfig::default(),
+        SecurityValidator::with_config(
+            Network::Regtest,
+            SecurityConfig {
+                max_absolute_fee: 1_200,
+                ..SecurityConfig::default()
+            },
+        ),
+    );
+    let (original, spent) = stuck_transaction(39_000, 0xfffffffd);
+    match strict.bump_rbf(&original, &spent, 1, 10.0, &[]) {
+        Err(BitcoinError::SecurityValidation(message)) => assert!(message.contains("Fee too high")),
+        other => panic!("expected security validation error, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cpfp_fee_bump() -> Result<()> {
+    use cerberus::bitcoin::{FeeBumpMethod, FeeBumper};
+
+    let (parent, _) = stuck_transaction(39_000, 0xffffffff);
+    let destination = bitcoin::ScriptBuf::from_hex(&format!("0014{}", hex::encode([4u8; 20])))?;
+    let bumper = FeeBumper::new(Network::Regtest);
+
+    let bump = bumper.bump_cpfp(&parent, Amount::from_sat(1_000), 1, &destination, 10.0)?;
+    assert_eq!(bump.method, FeeBumpMethod::ChildPaysForParent);
+
+    let child = &bump.transaction;
+    assert_eq!(child.input.len(), 1);
+    assert_eq!(child.input[0].previous_output.txid, parent.txid());
+    assert_eq!(child.input[0].previous_output.vout, 1);
+    assert_eq!(child.output.len(), 1);
+    assert_eq!(child.output[0].script_pubkey, destination);
+
+    // 1-in 1-out P2WPKH child of 110 vB lifts the package to 10 sat/vB
+    assert_eq!(bump.vsize, 110);
+    assert_eq!(bump.fee.to_sat(), 10 * (141 + 110) - 1_000);
+    assert_eq!(child.output[0].value.to_sat(), 39_000 - bump.fee.to_sat());
+    assert!((bump.fee_rate - 10.0).abs() < 0.01);
+    assert!(bump.validation.is_valid);
+
+    // Parent already pays more than requested
+    assert!(bumper.bump_cpfp(&parent, Amount::from_sat(1_000), 1, &destination, 5.0).is_err());
+    // Missing output
+    assert!(bumper.bump_cpfp(&parent, Amount::from_sat(1_000), 2, &destination, 10.0).is_err());
+    // Output too small to pay for the child
+    let (parent, _) = stuck_transaction(1_000, 0xffffffff);
+    assert!(bumper.bump_cpfp(&parent, Amount::from_sat(1_000), 1, &destination, 10.0).is_err());
+
+    Ok(())
+}
+
 // Note: The following tests would require a running Bitcoin Core node
 // They are marked as ignored by default
 
@@ -567,29 +1512,323 @@ async fn test_transaction_signer() -> Result<()> {
         }],
     };
 
-    // Create signing info (mock data)
+    // Create signing info for a P2PKH output owned by the key
+    let secp = bitcoin::secp256k1::Secp256k1::new();
+    let secret_key = bitcoin::secp256k1::SecretKey::from_slice(&[0x01; 32])?;
+    let public_key = bitcoin::PublicKey::new(secret_key.public_key(&secp));
+    let prev_script = bitcoin::ScriptBuf::new_p2pkh(&public_key.pubkey_hash());
     let signing_info = vec![InputSigningInfo {
         input_index: 0,
-        private_key: "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef".to_string(),
+        private_key: secret_key.display_secret().to_string(),
         prev_amount: 200000,
-        prev_script: "76a914".to_string() + &"0".repeat(40) + "88ac", // P2PKH script
+        prev_script: prev_script.to_hex_string(),
         script_type: TxScriptType::P2PKH,
         sighash_type: None,
         derivation_path: Some("m/44'/0'/0'/0/0".to_string()),
+        redeem_script: None,
+        tap_leaf_script: None,
+        control_block: None,
+        tap_merkle_root: None,
     }];
 
     // Test signing
-    let result = signer.sign_transaction(transaction, signing_info)?;
+    let result = signer.sign_transaction(transaction.clone(), signing_info)?;
 
     // Verify results
     assert_eq!(result.total_inputs, 1);
     assert_eq!(result.input_results.len(), 1);
     assert!(!result.txid.is_empty());
+    assert!(result.fully_signed);
+
+    // The reported signature verifies against the legacy sighash
+    let input = &result.input_results[0];
+    assert_eq!(input.public_key.as_deref(), Some(public_key.to_string().as_str()));
+    let signature = bitcoin::ecdsa::Signature::from_slice(&hex::decode(input.signature.as_ref().unwrap())?)?;
+    let sighash = bitcoin::sighash::SighashCache::new(&transaction)
+        .legacy_signature_hash(0, &prev_script, 1)?;
+    let message = bitcoin::secp256k1::Message::from_digest(sighash.to_byte_array());
+    secp.verify_ecdsa(&message, &signature.sig, &public_key.inner)?;
+
+    // The scriptSig carries the signature and key
+    let signed: bitcoin::Transaction =
+        bitcoin::consensus::deserialize(&hex::decode(result.signed_transaction.as_ref().unwrap())?)?;
+    let pushes: Vec<_> = signed.input[0]
+        .script_sig
+        .instructions()
+        .filter_map(|i| i.ok().and_then(|i| i.push_bytes().map(|b| b.as_bytes().to_vec())))
+        .collect();
+    assert_eq!(pushes, vec![signature.to_vec(), public_key.to_bytes()]);
 
     println!("✅ Transaction signer test passed");
     Ok(())
 }
 
+#[tokio::test]
+async fn test_transaction_signer_script_hash_inputs() -> Result<()> {
+    use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+
+    let secp = bitcoin::secp256k1::Secp256k1::new();
+    let signer = TransactionSigner::new(Network::Regtest);
+    let secret_key = bitcoin::secp256k1::SecretKey::from_slice(&[0x07; 32])?;
+    let public_key = bitcoin::PublicKey::new(secret_key.public_key(&secp));
+
+    // Nested P2SH-P2WPKH and a P2WSH <pubkey> CHECKSIG
+    let nested = bitcoin::ScriptBuf::new_p2wpkh(&public_key.wpubkey_hash().unwrap());
+    let witness_script = bitcoin::script::Builder::new()
+        .push_key(&public_key)
+        .push_opcode(bitcoin::opcodes::all::OP_CHECKSIG)
+        .into_script();
+    let scripts = [
+        (TxScriptType::P2SH, bitcoin::ScriptBuf::new_p2sh(&nested.script_hash()), nested.clone()),
+        (TxScriptType::P2WSH, bitcoin::ScriptBuf::new_p2wsh(&witness_script.wscript_hash()), witness_script.clone()),
+    ];
+    let signing_info: Vec<_> = scripts
+        .iter()
+        .enumerate()
+        .map(|(index, (script_type, prev_script, redeem_script))| InputSigningInfo {
+            input_index: index,
+            private_key: secret_key.display_secret().to_string(),
+            prev_amount: 40_000,
+            prev_script: prev_script.to_hex_string(),
+            script_type: *script_type,
+            sighash_type: None,
+            derivation_path: None,
+            redeem_script: Some(redeem_script.to_hex_string()),
+            tap_leaf_script: None,
+            control_block: None,
+            tap_merkle_root: None,
+        })
+        .collect();
+
+    let transaction = spending_transaction(2);
+    let result = signer.sign_transaction(transaction.clone(), signing_info.clone())?;
+    assert!(result.fully_signed);
+
+    let mut cache = SighashCache::new(&transaction);
+    let amount = bitcoin::Amount::from_sat(40_000);
+    let sighashes = [
+        cache.p2wpkh_signature_hash(0, &nested, amount, EcdsaSighashType::All)?,
+        cache.p2wsh_signature_hash(1, &witness_script, amount, EcdsaSighashType::All)?,
+    ];
+    for (input, sighash) in result.input_results.iter().zip(sighashes) {
+        let signature = bitcoin::ecdsa::Signature::from_slice(&hex::decode(input.signature.as_ref().unwrap())?)?;
+        let message = bitcoin::secp256k1::Message::from_digest(sighash.to_byte_array());
+        secp.verify_ecdsa(&message, &signature.sig, &public_key.inner)?;
+    }
+
+    // The nested input pushes the P2WPKH program in its scriptSig
+    let signed: bitcoin::Transaction =
+        bitcoin::consensus::deserialize(&hex::decode(result.signed_transaction.as_ref().unwrap())?)?;
+    assert_eq!(signed.input[0].script_sig.len(), nested.len() + 1);
+    assert_eq!(signed.input[1].witness.last(), Some(witness_script.as_bytes()));
+
+    // Script-hash inputs need their redeem script
+    let mut missing = signing_info;
+    missing[1].redeem_script = None;
+    let result = signer.sign_transaction(transaction, missing)?;
+    assert!(!result.input_results[1].success);
+
+    Ok(())
+}
+
+/// Unsigned transaction spending one outpoint per previous output
+fn spending_transaction(inputs: usize) -> bitcoin::Transaction {
+    bitcoin::Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: bitcoin::absolute::LockTime::ZERO,
+        input: (0..inputs)
+            .map(|vout| bitcoin::TxIn {
+                previous_output: bitcoin::OutPoint::new(bitcoin::Txid::all_zeros(), vout as u32),
+                script_sig: bitcoin::ScriptBuf::new(),
+                sequence: bitcoin::Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: bitcoin::Witness::new(),
+            })
+            .collect(),
+        output: vec![bitcoin::TxOut {
+            value: bitcoin::Amount::from_sat(90_000),
+            script_pubkey: bitcoin::ScriptBuf::new_op_return(&[]),
+        }],
+    }
+}
+
+fn taproot_signing_info(input_index: usize, secret_key: &bitcoin::secp256k1::SecretKey, prev_script: &bitcoin::ScriptBuf) -> InputSigningInfo {
+    InputSigningInfo {
+        input_index,
+        private_key: secret_key.display_secret().to_string(),
+        prev_amount: 50_000,
+        prev_script: prev_script.to_hex_string(),
+        script_type: TxScriptType::P2TR,
+        sighash_type: None,
+        derivation_path: Some("m/86'/1'/0'/0/0".to_string()),
+        redeem_script: None,
+        tap_leaf_script: None,
+        control_block: None,
+        tap_merkle_root: None,
+    }
+}
+
+#[tokio::test]
+async fn test_taproot_key_path_signing() -> Result<()> {
+    use bitcoin::key::TapTweak;
+    use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+
+    let secp = bitcoin::secp256k1::Secp256k1::new();
+    let signer = TransactionSigner::new(Network::Regtest);
+
+    // One P2WPKH and one P2TR input, so the Taproot sighash commits to both prevouts
+    let wpkh_key = bitcoin::secp256k1::SecretKey::from_slice(&[0x02; 32])?;
+    let wpkh_pubkey = bitcoin::PublicKey::new(wpkh_key.public_key(&secp));
+    let wpkh_script = bitcoin::ScriptBuf::new_p2wpkh(&wpkh_pubkey.wpubkey_hash().unwrap());
+    let tr_key = bitcoin::secp256k1::SecretKey::from_slice(&[0x03; 32])?;
+    let keypair = bitcoin::key::Keypair::from_secret_key(&secp, &tr_key);
+    let (internal_key, _) = keypair.x_only_public_key();
+    let tr_script = bitcoin::ScriptBuf::new_p2tr(&secp, internal_key, None);
+
+    let transaction = spending_transaction(2);
+    let signing_info = vec![
+        InputSigningInfo {
+            input_index: 0,
+            private_key: wpkh_key.display_secret().to_string(),
+            prev_amount: 60_000,
+            prev_script: wpkh_script.to_hex_string(),
+            script_type: TxScriptType::P2WPKH,
+            sighash_type: None,
+            derivation_path: None,
+            redeem_script: None,
+            tap_leaf_script: None,
+            control_block: None,
+            tap_merkle_root: None,
+        },
+        taproot_signing_info(1, &tr_key, &tr_script),
+    ];
+
+    let result = signer.sign_transaction(transaction.clone(), signing_info)?;
+    assert!(result.fully_signed);
+    assert!(result.input_results.iter().all(|r| r.success));
+
+    // Key-path signatures are 64 bytes with SIGHASH_DEFAULT and verify against the tweaked key
+    let input = &result.input_results[1];
+    let signature = hex::decode(input.signature.as_ref().unwrap())?;
+    assert_eq!(signature.len(), 64);
+    let output_key = internal_key.tap_tweak(&secp, None).0.to_inner();
+    assert_eq!(input.public_key.as_deref(), Some(hex::encode(output_key.serialize()).as_str()));
+
+    let prevouts = vec![
+        bitcoin::TxOut { value: bitcoin::Amount::from_sat(60_000), script_pubkey: wpkh_script.clone() },
+        bitcoin::TxOut { value: bitcoin::Amount::from_sat(50_000), script_pubkey: tr_script.clone() },
+    ];
+    let sighash = SighashCache::new(&transaction)
+        .taproot_key_spend_signature_hash(1, &Prevouts::All(&prevouts), TapSighashType::Default)?;
+    let message = bitcoin::secp256k1::Message::from_digest(sighash.to_byte_array());
+    let schnorr = bitcoin::secp256k1::schnorr::Signature::from_slice(&signature)?;
+    secp.verify_schnorr(&schnorr, &message, &output_key)?;
+
+    // The witness holds just the signature
+    let signed: bitcoin::Transaction =
+        bitcoin::consensus::deserialize(&hex::decode(result.signed_transaction.as_ref().unwrap())?)?;
+    assert_eq!(signed.input[1].witness.to_vec(), vec![signature]);
+    assert_eq!(signed.input[0].witness.len(), 2);
+
+    // A non-default sighash type is appended to the signature
+    let mut info = taproot_signing_info(0, &tr_key, &tr_script);
+    info.sighash_type = Some(0x01);
+    let result = signer.sign_transaction(spending_transaction(1), vec![info])?;
+    let signature = hex::decode(result.input_results[0].signature.as_ref().unwrap())?;
+    assert_eq!(signature.len(), 65);
+    assert_eq!(signature[64], 0x01);
+
+    // A key that does not own the output is rejected
+    let other_key = bitcoin::secp256k1::SecretKey::from_slice(&[0x04; 32])?;
+    let result = signer.sign_transaction(spending_transaction(1), vec![taproot_signing_info(0, &other_key, &tr_script)])?;
+    assert!(!result.fully_signed);
+    assert!(result.input_results[0].error.is_some());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_taproot_script_path_signing() -> Result<()> {
+    use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+    use bitcoin::taproot::{LeafVersion, TapLeafHash, TaprootBuilder};
+
+    let secp = bitcoin::secp256k1::Secp256k1::new();
+    let signer = TransactionSigner::new(Network::Regtest);
+
+    // Internal key nobody signs with, plus two leaves: <leaf_key> CHECKSIG and an OP_TRUE filler
+    let internal_key = bitcoin::key::Keypair::from_secret_key(
+        &secp,
+        &bitcoin::secp256k1::SecretKey::from_slice(&[0x05; 32])?,
+    )
+    .x_only_public_key()
+    .0;
+    let leaf_key = bitcoin::secp256k1::SecretKey::from_slice(&[0x06; 32])?;
+    let leaf_xonly = bitcoin::key::Keypair::from_secret_key(&secp, &leaf_key).x_only_public_key().0;
+    let leaf_script = bitcoin::script::Builder::new()
+        .push_x_only_key(&leaf_xonly)
+        .push_opcode(bitcoin::opcodes::all::OP_CHECKSIG)
+        .into_script();
+    let filler = bitcoin::script::Builder::new()
+        .push_opcode(bitcoin::opcodes::OP_TRUE)
+        .into_script();
+    let spend_info = TaprootBuilder::new()
+        .add_leaf(1, leaf_script.clone())?
+        .add_leaf(1, filler.clone())?
+        .finalize(&secp, internal_key)
+        .map_err(|_| anyhow::anyhow!("incomplete taproot tree"))?;
+    let prev_script = bitcoin::ScriptBuf::new_p2tr_tweaked(spend_info.output_key());
+    let control_block = spend_info
+        .control_block(&(leaf_script.clone(), LeafVersion::TapScript))
+        .unwrap();
+
+    let transaction = spending_transaction(1);
+    let mut info = taproot_signing_info(0, &leaf_key, &prev_script);
+    info.tap_leaf_script = Some(leaf_script.to_hex_string());
+    info.control_block = Some(hex::encode(control_block.serialize()));
+
+    let result = signer.sign_transaction(transaction.clone(), vec![info.clone()])?;
+    assert!(result.fully_signed);
+
+    // Script-path signatures verify against the untweaked leaf key over the leaf sighash
+    let input = &result.input_results[0];
+    assert_eq!(input.public_key.as_deref(), Some(hex::encode(leaf_xonly.serialize()).as_str()));
+    let signature = hex::decode(input.signature.as_ref().unwrap())?;
+    let prevouts = vec![bitcoin::TxOut { value: bitcoin::Amount::from_sat(50_000), script_pubkey: prev_script.clone() }];
+    let sighash = SighashCache::new(&transaction).taproot_script_spend_signature_hash(
+        0,
+        &Prevouts::All(&prevouts),
+        TapLeafHash::from_script(&leaf_script, LeafVersion::TapScript),
+        TapSighashType::Default,
+    )?;
+    let message = bitcoin::secp256k1::Message::from_digest(sighash.to_byte_array());
+    secp.verify_schnorr(
+        &bitcoin::secp256k1::schnorr::Signature::from_slice(&signature)?,
+        &message,
+        &leaf_xonly,
+    )?;
+
+    // Witness is <sig> <leaf script> <control block>
+    let signed: bitcoin::Transaction =
+        bitcoin::consensus::deserialize(&hex::decode(result.signed_transaction.as_ref().unwrap())?)?;
+    assert_eq!(
+        signed.input[0].witness.to_vec(),
+        vec![signature, leaf_script.to_bytes(), control_block.serialize()]
+    );
+
+    // A control block that does not commit to the leaf is rejected
+    let mut wrong_leaf = info.clone();
+    wrong_leaf.tap_leaf_script = Some(filler.to_hex_string());
+    let result = signer.sign_transaction(transaction.clone(), vec![wrong_leaf])?;
+    assert!(!result.fully_signed);
+
+    // Script-path spends need both the leaf and the control block
+    let mut missing_control = info;
+    missing_control.control_block = None;
+    let result = signer.sign_transaction(transaction, vec![missing_control])?;
+    assert!(!result.fully_signed);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_key_manager() -> Result<()> {
     println!("🔑 Testing Key Manager...");
@@ -633,6 +1872,190 @@ async fn test_key_manager() -> Result<()> {
     Ok(())
 }
 
+/// BIP-84 account xpub for the "abandon ... about" test mnemonic
+const BIP84_ACCOUNT_XPUB: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+/// BIP-86 account xpub for the same mnemonic
+const BIP86_ACCOUNT_XPUB: &str = "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+/// BIP-32 test vector 1 master xpub
+const BIP32_MASTER_XPUB: &str = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+const ABANDON_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+#[tokio::test]
+async fn test_descriptor_checksum() -> Result<()> {
+    use cerberus::bitcoin::{descriptor_checksum, Descriptor};
+
+    // BIP-380 example
+    assert_eq!(descriptor_checksum("raw(deadbeef)")?, "89f8spxm");
+    assert!(descriptor_checksum("wpkh(\u{e9})").is_err());
+
+    // Export always carries a checksum that parses back to the same descriptor
+    let body = format!("wpkh([73c5da0a/84'/0'/0']{}/0/*)", BIP84_ACCOUNT_XPUB);
+    let descriptor = Descriptor::parse(&body)?;
+    let exported = descriptor.to_string();
+    assert_eq!(exported, format!("{}#{}", body, descriptor_checksum(&body)?));
+    assert_eq!(Descriptor::parse(&exported)?, descriptor);
+
+    // `h` hardened markers are accepted and normalized
+    let h_body = body.replace('\'', "h");
+    let h_descriptor = Descriptor::parse(&format!("{}#{}", h_body, descriptor_checksum(&h_body)?))?;
+    assert_eq!(h_descriptor.to_string(), exported);
+
+    // A wrong checksum is rejected
+    let mut corrupted = exported.clone();
+    let last = corrupted.pop().unwrap();
+    corrupted.push(if last == 'q' { 'p' } else { 'q' });
+    assert!(Descriptor::parse(&corrupted).is_err());
+    assert!(Descriptor::parse(&format!("{}#", body)).is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_descriptor_address_derivation() -> Result<()> {
+    use cerberus::bitcoin::{AddressType, Descriptor};
+
+    let secp = bitcoin::secp256k1::Secp256k1::verification_only();
+
+    // BIP-84 receive and change vectors
+    let receive = Descriptor::parse(&format!("wpkh([73c5da0a/84'/0'/0']{}/0/*)", BIP84_ACCOUNT_XPUB))?;
+    assert!(receive.is_ranged());
+    assert_eq!(receive.address_type(), AddressType::Bech32);
+    assert_eq!(receive.address(&secp, 0, Network::Mainnet)?.to_string(), "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+    assert_eq!(receive.address(&secp, 1, Network::Mainnet)?.to_string(), "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g");
+    let change = Descriptor::parse(&format!("wpkh([73c5da0a/84'/0'/0']{}/1/*)", BIP84_ACCOUNT_XPUB))?;
+    assert_eq!(change.address(&secp, 0, Network::Mainnet)?.to_string(), "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el");
+    assert_eq!(
+        receive.keys()[0].full_path(5).map(|p| p.to_string()).as_deref(),
+        Some("m/84'/0'/0'/0/5")
+    );
+
+    // BIP-86 key-path Taproot vector
+    let taproot = Descriptor::parse(&format!("tr([73c5da0a/86'/0'/0']{}/0/*)", BIP86_ACCOUNT_XPUB))?;
+    assert_eq!(taproot.address_type(), AddressType::Taproot);
+    assert_eq!(
+        taproot.address(&secp, 0, Network::Mainnet)?.to_string(),
+        "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+    );
+
+    // sh(wpkh()) wraps the same P2WPKH program in P2SH
+    let nested = Descriptor::parse(&format!("sh(wpkh({}/0/*))", BIP84_ACCOUNT_XPUB))?;
+    let key = bitcoin::PublicKey::new(receive.derive_keys(&secp, 0)?[0]);
+    assert_eq!(nested.address(&secp, 0, Network::Mainnet)?, bitcoin::Address::p2shwpkh(&key, bitcoin::Network::Bitcoin)?);
+
+    // Single-key descriptors are not ranged
+    let single = Descriptor::parse(&format!("wpkh({})", key))?;
+    assert!(!single.is_ranged());
+    assert_eq!(single.address(&secp, 7, Network::Mainnet)?, receive.address(&secp, 0, Network::Mainnet)?);
+
+    // Mainnet keys do not derive testnet addresses
+    assert!(receive.address(&secp, 0, Network::Testnet).is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_descriptor_multisig() -> Result<()> {
+    use cerberus::bitcoin::Descriptor;
+
+    let secp = bitcoin::secp256k1::Secp256k1::verification_only();
+    let keys = [
+        format!("{}/0/*", BIP84_ACCOUNT_XPUB),
+        format!("{}/0/*", BIP86_ACCOUNT_XPUB),
+        format!("{}/0/*", BIP32_MASTER_XPUB),
+    ];
+    let sorted = Descriptor::parse(&format!("wsh(sortedmulti(2,{},{},{}))", keys[0], keys[1], keys[2]))?;
+    let reordered = Descriptor::parse(&format!("wsh(sortedmulti(2,{},{},{}))", keys[2], keys[0], keys[1]))?;
+    let unsorted = Descriptor::parse(&format!("wsh(multi(2,{},{},{}))", keys[0], keys[1], keys[2]))?;
+
+    // sortedmulti is independent of key order
+    for index in 0..3 {
+        assert_eq!(sorted.script_pubkey(&secp, index)?, reordered.script_pubkey(&secp, index)?);
+    }
+
+    // multi keeps the given order in a standard CHECKMULTISIG witness script
+    let derived = unsorted.derive_keys(&secp, 4)?;
+    let mut builder = bitcoin::script::Builder::new().push_int(2);
+    for key in &derived {
+        builder = builder.push_key(&bitcoin::PublicKey::new(*key));
+    }
+    let expected = builder
+        .push_int(3)
+        .push_opcode(bitcoin::opcodes::all::OP_CHECKMULTISIG)
+        .into_script();
+    assert_eq!(unsorted.witness_script(&secp, 4)?, expected);
+    assert_eq!(unsorted.script_pubkey(&secp, 4)?, bitcoin::ScriptBuf::new_p2wsh(&expected.wscript_hash()));
+    assert!(unsorted.address(&secp, 4, Network::Mainnet)?.to_string().starts_with("bc1q"));
+
+    // Round trip through the exported form
+    assert_eq!(Descriptor::parse(&sorted.to_string())?, sorted);
+
+    // Invalid descriptors
+    let invalid = [
+        format!("wsh(multi(3,{},{}))", keys[0], keys[1]),
+        format!("wsh(multi(0,{}))", keys[0]),
+        format!("wpkh({}/0'/*)", BIP84_ACCOUNT_XPUB),
+        format!("wpkh({}/0/*')", BIP84_ACCOUNT_XPUB),
+        format!("tr({},pk({}))", BIP86_ACCOUNT_XPUB, BIP84_ACCOUNT_XPUB),
+        format!("pkh({})", BIP84_ACCOUNT_XPUB),
+        "wpkh(cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115)".to_string(),
+    ];
+    for descriptor in invalid {
+        assert!(Descriptor::parse(&descriptor).is_err(), "{} should be rejected", descriptor);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_key_manager_descriptors() -> Result<()> {
+    use std::str::FromStr;
+
+    let mut key_manager = KeyManager::new(Network::Mainnet, cerberus::bitcoin::key_manager::SecurityLevel::Memory);
+    let wallet = key_manager.create_hd_wallet("Descriptor Wallet".to_string(), ABANDON_MNEMONIC, None)?;
+
+    // Account descriptors carry the master fingerprint and account xpub
+    let segwit = &wallet.descriptors["segwit"];
+    assert!(segwit.starts_with(&format!("wpkh([73c5da0a/84'/0'/0']{}/0/*)#", BIP84_ACCOUNT_XPUB)));
+    assert!(wallet.descriptors["segwit_change"].contains("/1/*)#"));
+    assert!(wallet.descriptors["taproot"].starts_with(&format!("tr([73c5da0a/86'/0'/0']{}/0/*)#", BIP86_ACCOUNT_XPUB)));
+    assert!(wallet.descriptors["nested_segwit"].starts_with("sh(wpkh([73c5da0a/49'/0'/0']"));
+
+    let derived = key_manager.derive_descriptor_address(segwit, 0)?;
+    assert_eq!(derived.address, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+    assert_eq!(derived.path, "m/84'/0'/0'/0/0");
+    assert_eq!(derived.address_type, AddressType::Bech32);
+
+    // Descriptor derivation matches the private key at the same path
+    let private_key = key_manager.get_private_key(&wallet.id, "m/84'/0'/0'/0", 0)?;
+    let secp = bitcoin::secp256k1::Secp256k1::new();
+    let secret_key = bitcoin::secp256k1::SecretKey::from_str(&private_key)?;
+    assert_eq!(derived.public_key, secret_key.public_key(&secp).to_string());
+
+    let taproot = key_manager.derive_descriptor_addresses(&wallet.descriptors["taproot"], 0, 3)?;
+    assert_eq!(taproot.len(), 3);
+    assert_eq!(taproot[0].address, "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr");
+    assert_eq!(taproot[2].index, 2);
+
+    let nested = key_manager.derive_descriptor_address(&wallet.descriptors["nested_segwit"], 0)?;
+    assert_eq!(nested.address, "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf");
+    assert_eq!(nested.address_type, AddressType::P2shSegwit);
+
+    // Watch-only multisig derivation needs no wallet
+    let multisig = format!(
+        "wsh(sortedmulti(2,{}/0/*,{}/0/*))",
+        BIP84_ACCOUNT_XPUB, BIP32_MASTER_XPUB
+    );
+    let derived = key_manager.derive_descriptor_address(&multisig, 0)?;
+    assert_eq!(derived.public_key.split(',').count(), 2);
+    assert!(derived.address.starts_with("bc1q"));
+
+    // Testnet managers reject mainnet descriptors
+    let testnet = KeyManager::new(Network::Testnet, cerberus::bitcoin::key_manager::SecurityLevel::Memory);
+    assert!(testnet.derive_descriptor_address(segwit, 0).is_err());
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_security_validator() -> Result<()> {
     println!("🛡️ Testing Security Validator...");
@@ -181,6 +181,16 @@ fn test_risk_config_validation() {
     risk_config.circuit_breaker_threshold = Decimal::new(15, 0);
     risk_config.max_consecutive_failures = 0;
     assert!(risk_config.validate().is_err());
+
+    // Invalid trade limits
+    risk_config.max_consecutive_failures = 5;
+    assert!(risk_config.validate().is_ok());
+    risk_config.trade_limits.max_daily_trades_per_token =
+        risk_config.trade_limits.max_daily_trades + 1;
+    assert!(risk_config.validate().is_err());
+
+    risk_config.trade_limits.enabled = false;
+    assert!(risk_config.validate().is_ok());
 }
 
 #[test]
@@ -247,6 +257,35 @@ fn test_take_profit_config_validation() {
     assert!(take_profit_config.validate().is_ok());
 }
 
+#[test]
+fn test_strategy_config_validation() {
+    let mut config = Config::default();
+    config.strategy.enabled = true;
+    assert!(config.strategy.validate().is_ok());
+    assert!(config.validate().is_ok());
+
+    // Fazy muszą być posortowane według min_equity
+    config.strategy.phases.swap(0, 1);
+    assert!(config.strategy.validate().is_err());
+    config.strategy.phases.swap(0, 1);
+
+    config.strategy.phases[0].min_confidence = "certain".to_string();
+    assert!(config.strategy.validate().is_err());
+    config.strategy.phases[0].min_confidence = "high".to_string();
+
+    // Maksymalna dźwignia fazy poniżej min_leverage tradingu
+    config.strategy.phases[0].max_leverage = 1;
+    assert!(config.strategy.validate().is_ok());
+    assert!(config.validate().is_err());
+    config.strategy.phases[0].max_leverage = 2;
+
+    // Stop-loss fazy (acceleration: 10%) węższy niż trailing stop
+    config.risk.stop_loss.default_percent = Decimal::new(25, 2);
+    config.risk.stop_loss.use_trailing = true;
+    config.risk.stop_loss.trailing_distance = Decimal::new(12, 2);
+    assert!(config.validate().is_err());
+}
+
 #[test]
 fn test_monitoring_config_validation() {
     let mut monitoring_config = MonitoringConfig::default();
@@ -81,6 +81,7 @@ async fn test_database_backup_creation() -> Result<()> {
     let temp_dir = TempDir::new()?;
     let mut config = DatabaseConfig::default();
     config.path = temp_dir.path().join("test.db");
+    config.backup_directory = temp_dir.path().to_path_buf();
     config.enable_backup = true;
 
     let db_manager = DatabaseManager::new_without_migrations(&config).await?;
@@ -246,6 +247,61 @@ async fn test_database_migration() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_legacy_migration_version_is_renumbered() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let mut config = DatabaseConfig::default();
+    config.path = temp_dir.path().join("legacy.db");
+
+    // Baza utworzona, gdy pliki nazywały się 20250808_00000N_* (wersja sqlx 20250808)
+    {
+        let db_manager = DatabaseManager::new_without_migrations(&config).await?;
+        let pool = db_manager.pool();
+        let wallets = sqlx::migrate!("./migrations")
+            .iter()
+            .next()
+            .cloned()
+            .expect("wallets migration");
+
+        sqlx::query(
+            r#"
+            CREATE TABLE _sqlx_migrations (
+                version BIGINT PRIMARY KEY,
+                description TEXT NOT NULL,
+                installed_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                success BOOLEAN NOT NULL,
+                checksum BLOB NOT NULL,
+                execution_time BIGINT NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        sqlx::query(&wallets.sql).execute(pool).await?;
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+             VALUES (20250808, '000001 create wallets', TRUE, ?, 0)",
+        )
+        .bind(wallets.checksum.as_ref())
+        .execute(pool)
+        .await?;
+    }
+
+    let db_manager = DatabaseManager::new(&config).await?;
+    let versions: Vec<i64> =
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations ORDER BY version")
+            .fetch_all(db_manager.pool())
+            .await?;
+    assert_eq!(versions.first(), Some(&20250808000001));
+    assert!(!versions.contains(&20250808));
+    assert_eq!(
+        versions.len(),
+        sqlx::migrate!("./migrations").iter().count()
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_database_error_handling() -> Result<()> {
     let temp_dir = TempDir::new()?;
@@ -403,3 +459,360 @@ async fn test_database_stress_test() -> Result<()> {
 
     Ok(())
 }
+
+async fn ledger_for(config: &DatabaseConfig) -> Result<TradeLedger> {
+    let db_manager = DatabaseManager::new(config).await?;
+    Ok(TradeLedger::new(std::sync::Arc::new(
+        db_manager.pool().clone(),
+    )))
+}
+
+#[tokio::test]
+async fn test_ledger_records_order_lifecycle() -> Result<()> {
+    use cerberus::risk::TradeSide;
+    use cerberus::trading::{ExecutionResult, OrderStatus, TradeOrder};
+
+    let temp_dir = TempDir::new()?;
+    let mut config = DatabaseConfig::default();
+    config.path = temp_dir.path().join("ledger.db");
+    let ledger = ledger_for(&config).await?;
+
+    let order = TradeOrder::market_order("BTC".to_string(), TradeSide::Long, 1000.0, 5);
+    ledger.record_order(&order, &OrderStatus::Pending).await?;
+
+    let result = ExecutionResult::success("tx-1".to_string(), 50_000.0, 1000.0, 0.6);
+    ledger.record_execution(&order, &result).await?;
+
+    let record = ledger.get_order(&order.id).await?.expect("order stored");
+    assert_eq!(record.status, OrderStatus::Filled);
+    assert_eq!(record.order.token, "BTC");
+
+    let history = ledger.order_history(&order.id).await?;
+    let statuses: Vec<_> = history.iter().map(|e| e.status.clone()).collect();
+    // Znane zlecenie nie wraca do Pending przed wykonaniem
+    assert_eq!(statuses, vec![OrderStatus::Pending, OrderStatus::Filled]);
+
+    let trades = ledger.list_trades(&LedgerQuery::default()).await?;
+    assert_eq!(trades.total, 1);
+    assert_eq!(trades.items[0].result.executed_price, Some(50_000.0));
+    assert_eq!(trades.items[0].side, TradeSide::Long);
+
+    // Odrzucone zlecenie trafia do historii ze statusem Rejected
+    let rejected = TradeOrder::market_order("ETH".to_string(), TradeSide::Short, 500.0, 3);
+    ledger
+        .record_execution(
+            &rejected,
+            &ExecutionResult::error("No liquidity".to_string()),
+        )
+        .await?;
+    let record = ledger.get_order(&rejected.id).await?.expect("order stored");
+    assert_eq!(record.status, OrderStatus::Rejected);
+
+    // Zlecenie z księgi: akceptacja, a potem wypełnienie bez ponownego Pending
+    let resting = TradeOrder::limit_order("SOL".to_string(), TradeSide::Long, 200.0, 2, 100.0);
+    let accepted = ExecutionResult {
+        success: true,
+        transaction_id: Some(resting.id.clone()),
+        executed_price: None,
+        executed_size: None,
+        fees: None,
+        executed_at: None,
+        error_message: None,
+        metadata: serde_json::json!({}),
+    };
+    ledger.record_execution(&resting, &accepted).await?;
+    let fill = ExecutionResult::success("tx-3".to_string(), 100.0, 200.0, 0.1);
+    ledger.record_execution(&resting, &fill).await?;
+
+    let statuses: Vec<_> = ledger
+        .order_history(&resting.id)
+        .await?
+        .into_iter()
+        .map(|e| e.status)
+        .collect();
+    assert_eq!(statuses, vec![OrderStatus::Pending, OrderStatus::Filled]);
+    let record = ledger.get_order(&resting.id).await?.expect("order stored");
+    assert_eq!(record.status, OrderStatus::Filled);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ledger_positions_survive_restart() -> Result<()> {
+    use cerberus::risk::{Position, PositionStatus, TradeSide};
+
+    let temp_dir = TempDir::new()?;
+    let mut config = DatabaseConfig::default();
+    config.path = temp_dir.path().join("ledger.db");
+
+    let open = Position::new("BTC".to_string(), TradeSide::Long, 1000.0, 5, 50_000.0);
+    let mut closed = Position::new("ETH".to_string(), TradeSide::Short, 500.0, 3, 3_000.0);
+
+    {
+        let ledger = ledger_for(&config).await?;
+        ledger.save_position(&open).await?;
+        ledger.save_position(&closed).await?;
+
+        closed.update_price(2_900.0);
+        closed.status = PositionStatus::Closed;
+        closed.closed_at = Some(chrono::Utc::now().timestamp());
+        ledger.record_position_close(&closed, 2_900.0, 50.0).await?;
+    }
+
+    let ledger = ledger_for(&config).await?;
+    let restored = ledger.load_open_positions().await?;
+    assert_eq!(restored.len(), 1);
+    assert_eq!(restored[0].id, open.id);
+    assert_eq!(restored[0].entry_price, 50_000.0);
+
+    let record = ledger
+        .get_position(&closed.id)
+        .await?
+        .expect("position stored");
+    assert_eq!(record.position.status, PositionStatus::Closed);
+    assert_eq!(record.exit_price, Some(2_900.0));
+    assert_eq!(record.realized_pnl, Some(50.0));
+    assert_eq!(ledger.closed_position_pnls().await?, vec![50.0]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ledger_pagination_and_time_range() -> Result<()> {
+    use cerberus::risk::{Position, TradeSide};
+
+    let temp_dir = TempDir::new()?;
+    let mut config = DatabaseConfig::default();
+    config.path = temp_dir.path().join("ledger.db");
+    let ledger = ledger_for(&config).await?;
+
+    for i in 0..5 {
+        let mut position = Position::new(format!("T{}", i), TradeSide::Long, 100.0, 2, 10.0);
+        position.opened_at = 1_000 + i;
+        ledger.save_position(&position).await?;
+    }
+
+    let page = ledger
+        .list_positions(
+            None,
+            &LedgerQuery {
+                page: Some(2),
+                limit: Some(2),
+                ..Default::default()
+            },
+        )
+        .await?;
+    assert_eq!(page.total, 5);
+    assert_eq!(page.items.len(), 2);
+    assert_eq!(page.items[0].position.token, "T2");
+
+    let ranged = ledger
+        .list_positions(
+            None,
+            &LedgerQuery {
+                from: Some(1_001),
+                to: Some(1_002),
+                ..Default::default()
+            },
+        )
+        .await?;
+    assert_eq!(ranged.total, 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_state_survives_restart() -> Result<()> {
+    use cerberus::config::RiskConfig;
+    use cerberus::risk::CircuitBreaker;
+
+    let temp_dir = TempDir::new()?;
+    let mut config = DatabaseConfig::default();
+    config.path = temp_dir.path().join("risk.db");
+
+    {
+        let db_manager = DatabaseManager::new(&config).await?;
+        let store = StateStore::new(std::sync::Arc::new(db_manager.pool().clone()));
+        let breaker = CircuitBreaker::with_store(&RiskConfig::default(), store).await?;
+        assert!(breaker.trip("test".to_string()).await?.is_some());
+    }
+
+    let db_manager = DatabaseManager::new(&config).await?;
+    let store = StateStore::new(std::sync::Arc::new(db_manager.pool().clone()));
+    let breaker = CircuitBreaker::with_store(&RiskConfig::default(), store).await?;
+    assert!(breaker.status().await.active);
+    assert!(!breaker.is_trading_allowed().await);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_trade_limits_survive_restart() -> Result<()> {
+    use cerberus::config::TradeLimitsConfig;
+    use cerberus::risk::{TokenBlockReason, TradeLimiter};
+
+    let temp_dir = TempDir::new()?;
+    let mut config = DatabaseConfig::default();
+    config.path = temp_dir.path().join("limits.db");
+    let limits = TradeLimitsConfig::default();
+    let now = chrono::Utc::now().timestamp();
+
+    {
+        let db_manager = DatabaseManager::new(&config).await?;
+        let store = StateStore::new(std::sync::Arc::new(db_manager.pool().clone()));
+        let limiter = TradeLimiter::with_store(&limits, store).await?;
+        limiter.record_open("BONK", now).await?;
+        limiter
+            .record_block("WIF", TokenBlockReason::Liquidation, now)
+            .await?;
+    }
+
+    let db_manager = DatabaseManager::new(&config).await?;
+    let store = StateStore::new(std::sync::Arc::new(db_manager.pool().clone()));
+    let limiter = TradeLimiter::with_store(&limits, store).await?;
+    assert_eq!(limiter.daily_trades(now).await, 1);
+    assert!(limiter.blocker(Some("WIF"), now + 60).await.is_some());
+    assert!(limiter.blocker(Some("BONK"), now + 60).await.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_emergency_stop_flattens_and_blocks_signals() -> Result<()> {
+    use cerberus::config::{PaperTradingConfig, RiskConfig};
+    use cerberus::risk::{CircuitBreaker, EmergencyStop, EmergencyTrigger, TradeSide};
+    use cerberus::trading::{OrderStatus, PaperExchange, TradeExecutorTrait, TradeOrder};
+    use std::sync::Arc;
+
+    let temp_dir = TempDir::new()?;
+    let mut config = DatabaseConfig::default();
+    config.path = temp_dir.path().join("emergency.db");
+    let db_manager = DatabaseManager::new(&config).await?;
+    let pool = Arc::new(db_manager.pool().clone());
+    let ledger = Arc::new(TradeLedger::new(pool.clone()));
+
+    let exchange = Arc::new(PaperExchange::new(&PaperTradingConfig::default(), 10_000.0));
+    exchange.update_price("BTC", 50_000.0).await;
+
+    // Otwarta pozycja i zlecenie limit oczekujące na wykonanie
+    let entry = TradeOrder::market_order("BTC".to_string(), TradeSide::Long, 1000.0, 5);
+    let result = exchange.execute_trade(&entry).await?;
+    ledger.record_execution(&entry, &result).await?;
+    let position = exchange.open_positions().await.remove(0);
+    ledger.save_position(&position).await?;
+
+    let resting = TradeOrder::limit_order("BTC".to_string(), TradeSide::Long, 500.0, 2, 40_000.0);
+    exchange.execute_trade(&resting).await?;
+    ledger.record_order(&resting, &OrderStatus::Pending).await?;
+
+    let breaker = Arc::new(
+        CircuitBreaker::with_store(&RiskConfig::default(), StateStore::new(pool.clone())).await?,
+    );
+    let emergency = EmergencyStop::new(
+        exchange.clone(),
+        ledger.clone(),
+        breaker.clone(),
+        pool.clone(),
+        false,
+    )
+    .await?;
+
+    let report = emergency.activate(EmergencyTrigger::Api, "test").await?;
+    assert!(report.is_complete());
+    assert_eq!(report.cancelled_count(), 1);
+    assert_eq!(report.closed_count(), 1);
+
+    assert!(ledger.load_open_positions().await?.is_empty());
+    let closed = ledger
+        .get_position(&position.id)
+        .await?
+        .expect("position stored");
+    assert!(closed.exit_price.is_some());
+    let order = ledger.get_order(&resting.id).await?.expect("order stored");
+    assert_eq!(order.status, OrderStatus::Cancelled);
+    assert!(exchange.open_positions().await.is_empty());
+
+    // Blokada sygnałów i circuit breakera do czasu potwierdzonego resetu
+    assert!(emergency.ensure_accepting_signals().await.is_err());
+    assert!(!breaker.is_trading_allowed().await);
+    assert!(emergency.reset(false, "operator").await.is_err());
+    assert!(emergency.is_active().await);
+
+    // Stan przetrwa restart
+    let restored = EmergencyStop::new(
+        exchange.clone(),
+        ledger.clone(),
+        breaker.clone(),
+        pool.clone(),
+        false,
+    )
+    .await?;
+    assert!(restored.is_active().await);
+
+    assert!(restored.reset(true, "operator").await?);
+    assert!(restored.ensure_accepting_signals().await.is_ok());
+    assert!(breaker.is_trading_allowed().await);
+
+    let events: Vec<_> = restored
+        .audit_trail(10)
+        .await?
+        .into_iter()
+        .map(|e| e.event)
+        .collect();
+    assert!(events.contains(&"activated".to_string()));
+    assert!(events.contains(&"flattened".to_string()));
+    assert!(events.contains(&"reset".to_string()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_signal_store_lists_newest_first() -> Result<()> {
+    use cerberus::signals::{Confidence, ScoredSignal, Signal};
+
+    let temp_dir = TempDir::new()?;
+    let mut config = DatabaseConfig::default();
+    config.path = temp_dir.path().join("signals.db");
+    let db_manager = DatabaseManager::new(&config).await?;
+    let store = SignalStore::new(std::sync::Arc::new(db_manager.pool().clone()));
+
+    for (i, token) in ["BONK", "WIF", "PEPE"].iter().enumerate() {
+        let signal = Signal::new(
+            token.to_string(),
+            "pump_fun".to_string(),
+            Confidence::High,
+            0.001,
+            1000.0,
+            serde_json::json!({"rank": i}),
+        );
+        store
+            .save(&ScoredSignal {
+                signal,
+                score: 0.45,
+                received_at: 1_000 + i as i64,
+            })
+            .await?;
+    }
+
+    let page = store
+        .list(&LedgerQuery {
+            page: Some(1),
+            limit: Some(2),
+            from: Some(1_001),
+            to: None,
+        })
+        .await?;
+    assert_eq!(page.total, 2);
+    assert_eq!(page.items.len(), 2);
+    assert_eq!(page.items[0].signal.token, "PEPE");
+    assert_eq!(page.items[1].signal.metadata["rank"], 1);
+
+    assert!(
+        store
+            .seen_since(page.items[0].signal.hash.as_deref().unwrap(), 1_002)
+            .await?
+    );
+    assert!(store.get("missing").await?.is_none());
+
+    Ok(())
+}